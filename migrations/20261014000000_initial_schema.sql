@@ -0,0 +1,314 @@
+-- Schema the API was written against. Tables are created only when missing so that
+-- databases set up by hand before migrations existed can adopt this history as is
+
+CREATE TABLE IF NOT EXISTS users (
+    user_id INT AUTO_INCREMENT PRIMARY KEY,
+    first_name VARCHAR(100) NOT NULL,
+    last_name VARCHAR(100) NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    phone VARCHAR(32) NULL,
+    password VARCHAR(255) NOT NULL,
+    passport_number VARCHAR(32) NULL,
+    nationality VARCHAR(64) NULL,
+    date_of_birth DATE NULL,
+    role ENUM('admin', 'worker', 'user') NOT NULL DEFAULT 'user',
+    -- Only set for workers
+    staff_position ENUM('gate_agent', 'check_in_agent', 'pilot', 'dispatcher') NULL,
+    -- Tokens issued before this are rejected
+    password_changed_at DATETIME NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uq_users_email (email),
+    UNIQUE KEY uq_users_phone (phone)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS routes (
+    route_id INT AUTO_INCREMENT PRIMARY KEY,
+    origin VARCHAR(100) NOT NULL,
+    destination VARCHAR(100) NOT NULL,
+    distance FLOAT NOT NULL,
+    estimated_duration TIME NOT NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS aircraft (
+    aircraft_id INT AUTO_INCREMENT PRIMARY KEY,
+    model VARCHAR(100) NOT NULL,
+    capacity INT NOT NULL,
+    KEY idx_aircraft_model (model)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS flights (
+    flight_id INT AUTO_INCREMENT PRIMARY KEY,
+    flight_number VARCHAR(10) NOT NULL,
+    route_id INT NOT NULL,
+    aircraft_id INT NOT NULL,
+    departure_time DATETIME NOT NULL,
+    arrival_time DATETIME NOT NULL,
+    status ENUM('scheduled', 'boarding', 'departed', 'arrived', 'delayed', 'cancelled') NOT NULL DEFAULT 'scheduled',
+    gate VARCHAR(10) NULL,
+    KEY idx_flights_departure (departure_time),
+    KEY idx_flights_status (status),
+    CONSTRAINT fk_flights_route FOREIGN KEY (route_id) REFERENCES routes (route_id),
+    CONSTRAINT fk_flights_aircraft FOREIGN KEY (aircraft_id) REFERENCES aircraft (aircraft_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS flight_status_history (
+    history_id INT AUTO_INCREMENT PRIMARY KEY,
+    flight_id INT NOT NULL,
+    old_status ENUM('scheduled', 'boarding', 'departed', 'arrived', 'delayed', 'cancelled') NOT NULL,
+    new_status ENUM('scheduled', 'boarding', 'departed', 'arrived', 'delayed', 'cancelled') NOT NULL,
+    changed_at DATETIME NOT NULL,
+    KEY idx_flight_status_history_flight (flight_id, changed_at),
+    CONSTRAINT fk_flight_status_history_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS flight_events (
+    event_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    flight_id INT NOT NULL,
+    event_type ENUM('created', 'crew_assigned', 'gate_set', 'rescheduled', 'delayed', 'boarding_started', 'departed', 'arrived', 'cancelled') NOT NULL,
+    details JSON NULL,
+    actor_id INT NULL,
+    occurred_at DATETIME NOT NULL,
+    KEY idx_flight_events_flight (flight_id, occurred_at),
+    CONSTRAINT fk_flight_events_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE,
+    CONSTRAINT fk_flight_events_actor FOREIGN KEY (actor_id) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS tickets (
+    ticket_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_number VARCHAR(20) NOT NULL,
+    user_id INT NOT NULL,
+    flight_id INT NOT NULL,
+    seat_number VARCHAR(5) NOT NULL,
+    fare_class ENUM('economy', 'business', 'first') NOT NULL DEFAULT 'economy',
+    checked_in BOOLEAN NOT NULL DEFAULT FALSE,
+    special_requests TEXT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE KEY uq_tickets_number (ticket_number),
+    UNIQUE KEY uq_tickets_seat (flight_id, seat_number),
+    KEY idx_tickets_user (user_id),
+    CONSTRAINT fk_tickets_user FOREIGN KEY (user_id) REFERENCES users (user_id),
+    CONSTRAINT fk_tickets_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS flight_fare_classes (
+    flight_id INT NOT NULL,
+    fare_class ENUM('economy', 'business', 'first') NOT NULL,
+    seat_count INT NOT NULL,
+    price DOUBLE NOT NULL,
+    PRIMARY KEY (flight_id, fare_class),
+    CONSTRAINT fk_flight_fare_classes_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS seat_blocks (
+    block_id INT AUTO_INCREMENT PRIMARY KEY,
+    flight_id INT NOT NULL,
+    seat_number VARCHAR(5) NOT NULL,
+    reason ENUM('crew_rest', 'equipment', 'weight_and_balance') NOT NULL,
+    note VARCHAR(255) NULL,
+    blocked_by INT NOT NULL,
+    created_at DATETIME NOT NULL,
+    UNIQUE KEY uq_seat_blocks_seat (flight_id, seat_number),
+    CONSTRAINT fk_seat_blocks_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE,
+    CONSTRAINT fk_seat_blocks_user FOREIGN KEY (blocked_by) REFERENCES users (user_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS crew_members (
+    crew_member_id INT AUTO_INCREMENT PRIMARY KEY,
+    first_name VARCHAR(100) NOT NULL,
+    last_name VARCHAR(100) NOT NULL,
+    role ENUM('pilot', 'cabin_crew') NOT NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS flight_crew_assignments (
+    flight_id INT NOT NULL,
+    crew_member_id INT NOT NULL,
+    PRIMARY KEY (flight_id, crew_member_id),
+    KEY idx_flight_crew_assignments_member (crew_member_id),
+    CONSTRAINT fk_flight_crew_assignments_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE,
+    CONSTRAINT fk_flight_crew_assignments_member FOREIGN KEY (crew_member_id) REFERENCES crew_members (crew_member_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS aircraft_crew_requirements (
+    aircraft_model VARCHAR(100) NOT NULL,
+    role ENUM('pilot', 'cabin_crew') NOT NULL,
+    min_count INT NOT NULL,
+    PRIMARY KEY (aircraft_model, role)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS sessions (
+    session_id VARCHAR(36) PRIMARY KEY,
+    user_id INT NOT NULL,
+    user_agent VARCHAR(255) NULL,
+    ip_address VARCHAR(45) NULL,
+    created_at DATETIME NOT NULL,
+    last_used_at DATETIME NOT NULL,
+    revoked_at DATETIME NULL,
+    KEY idx_sessions_user (user_id),
+    CONSTRAINT fk_sessions_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    token_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id INT NOT NULL,
+    session_id VARCHAR(36) NOT NULL,
+    two_factor BOOLEAN NOT NULL DEFAULT FALSE,
+    -- SHA-256 of the token; the token itself is never stored
+    token_hash CHAR(64) NOT NULL,
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL,
+    used_at DATETIME NULL,
+    revoked_at DATETIME NULL,
+    UNIQUE KEY uq_refresh_tokens_hash (token_hash),
+    KEY idx_refresh_tokens_session (session_id),
+    KEY idx_refresh_tokens_user (user_id),
+    CONSTRAINT fk_refresh_tokens_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS revoked_tokens (
+    jti VARCHAR(64) PRIMARY KEY,
+    user_id INT NOT NULL,
+    expires_at DATETIME NOT NULL,
+    revoked_at DATETIME NOT NULL,
+    KEY idx_revoked_tokens_expires (expires_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS password_reset_tokens (
+    token_hash CHAR(64) PRIMARY KEY,
+    user_id INT NOT NULL,
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL,
+    used_at DATETIME NULL,
+    KEY idx_password_reset_tokens_user (user_id),
+    CONSTRAINT fk_password_reset_tokens_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS otp_codes (
+    otp_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    phone VARCHAR(32) NOT NULL,
+    code_hash CHAR(64) NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL,
+    used_at DATETIME NULL,
+    KEY idx_otp_codes_phone (phone, created_at)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id INT PRIMARY KEY,
+    secret VARCHAR(64) NOT NULL,
+    -- NULL until the first code confirmed the enrollment
+    enabled_at DATETIME NULL,
+    -- Last accepted 30 second step, so a code cannot be replayed
+    last_used_step BIGINT NULL,
+    created_at DATETIME NOT NULL,
+    CONSTRAINT fk_user_totp_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS totp_backup_codes (
+    backup_code_id INT AUTO_INCREMENT PRIMARY KEY,
+    user_id INT NOT NULL,
+    code_hash CHAR(64) NOT NULL,
+    used_at DATETIME NULL,
+    KEY idx_totp_backup_codes_user (user_id, code_hash),
+    CONSTRAINT fk_totp_backup_codes_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    key_id INT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    key_prefix VARCHAR(16) NOT NULL,
+    key_hash CHAR(64) NOT NULL,
+    scopes JSON NOT NULL,
+    created_by INT NOT NULL,
+    created_at DATETIME NOT NULL,
+    last_used_at DATETIME NULL,
+    revoked_at DATETIME NULL,
+    UNIQUE KEY uq_api_keys_hash (key_hash),
+    CONSTRAINT fk_api_keys_user FOREIGN KEY (created_by) REFERENCES users (user_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS promo_codes (
+    promo_code_id INT AUTO_INCREMENT PRIMARY KEY,
+    code VARCHAR(32) NOT NULL,
+    discount_type ENUM('percentage', 'fixed') NOT NULL,
+    discount_value DOUBLE NOT NULL,
+    valid_from DATETIME NOT NULL,
+    valid_until DATETIME NOT NULL,
+    usage_limit INT NULL,
+    times_used INT NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL,
+    UNIQUE KEY uq_promo_codes_code (code)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS miles_ledger (
+    entry_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    user_id INT NOT NULL,
+    ticket_id INT NULL,
+    entry_type ENUM('accrual', 'redemption') NOT NULL,
+    -- Negative for redemptions
+    miles INT NOT NULL,
+    created_at DATETIME NOT NULL,
+    KEY idx_miles_ledger_user (user_id, created_at),
+    KEY idx_miles_ledger_ticket (ticket_id, entry_type),
+    CONSTRAINT fk_miles_ledger_user FOREIGN KEY (user_id) REFERENCES users (user_id),
+    CONSTRAINT fk_miles_ledger_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS flight_status_tokens (
+    token VARCHAR(32) PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    created_at DATETIME NOT NULL,
+    expires_at DATETIME NOT NULL,
+    CONSTRAINT fk_flight_status_tokens_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS content_translations (
+    content_key VARCHAR(128) NOT NULL,
+    locale VARCHAR(16) NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (content_key, locale)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    webhook_id INT AUTO_INCREMENT PRIMARY KEY,
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(64) NOT NULL,
+    events JSON NOT NULL,
+    created_by INT NOT NULL,
+    created_at DATETIME NOT NULL,
+    disabled_at DATETIME NULL,
+    CONSTRAINT fk_webhooks_user FOREIGN KEY (created_by) REFERENCES users (user_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS webhook_deliveries (
+    delivery_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    webhook_id INT NOT NULL,
+    event_id CHAR(36) NOT NULL,
+    event VARCHAR(64) NOT NULL,
+    payload MEDIUMTEXT NOT NULL,
+    status ENUM('pending', 'succeeded', 'failed') NOT NULL DEFAULT 'pending',
+    attempts INT NOT NULL DEFAULT 0,
+    response_status INT NULL,
+    last_error VARCHAR(255) NULL,
+    next_attempt_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL,
+    delivered_at DATETIME NULL,
+    KEY idx_webhook_deliveries_due (status, next_attempt_at),
+    KEY idx_webhook_deliveries_webhook (webhook_id, delivery_id),
+    CONSTRAINT fk_webhook_deliveries_webhook FOREIGN KEY (webhook_id) REFERENCES webhooks (webhook_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS audit_logs (
+    audit_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    -- NULL for actions without a signed-in actor, e.g. password resets
+    actor_id INT NULL,
+    action VARCHAR(64) NOT NULL,
+    entity_type VARCHAR(64) NOT NULL,
+    entity_id VARCHAR(64) NOT NULL,
+    details JSON NULL,
+    request_id VARCHAR(128) NULL,
+    created_at DATETIME NOT NULL,
+    KEY idx_audit_logs_created (created_at),
+    KEY idx_audit_logs_actor (actor_id, created_at),
+    KEY idx_audit_logs_entity (entity_type, entity_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
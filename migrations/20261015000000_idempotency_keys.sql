@@ -0,0 +1,14 @@
+-- Responses of requests sent with an Idempotency-Key header, replayed on retries
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    user_id INT NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    -- SHA-256 of method, path and body; a retry has to match it
+    request_hash CHAR(64) NOT NULL,
+    -- NULL while the first request is still running
+    status_code SMALLINT NULL,
+    response_body MEDIUMBLOB NULL,
+    created_at DATETIME NOT NULL,
+    PRIMARY KEY (user_id, idempotency_key),
+    KEY idx_idempotency_keys_created (created_at),
+    CONSTRAINT fk_idempotency_keys_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
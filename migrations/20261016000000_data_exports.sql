@@ -0,0 +1,15 @@
+-- Archives of everything stored about a user, built in the background on request
+CREATE TABLE IF NOT EXISTS data_exports (
+    -- Retrieval token
+    export_id CHAR(32) PRIMARY KEY,
+    user_id INT NOT NULL,
+    status ENUM('pending', 'ready', 'failed') NOT NULL DEFAULT 'pending',
+    archive LONGTEXT NULL,
+    created_at DATETIME NOT NULL,
+    completed_at DATETIME NULL,
+    -- Set once ready; the archive is deleted afterwards
+    expires_at DATETIME NULL,
+    KEY idx_data_exports_user (user_id, created_at),
+    KEY idx_data_exports_status (status, created_at),
+    CONSTRAINT fk_data_exports_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
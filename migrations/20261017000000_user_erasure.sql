@@ -0,0 +1,2 @@
+-- Erased accounts stay as anonymized rows so tickets, miles and seat blocks keep their user
+ALTER TABLE users ADD COLUMN erased_at DATETIME NULL;
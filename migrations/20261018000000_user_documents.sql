@@ -0,0 +1,17 @@
+-- Identity documents uploaded for a user; the file itself is in object storage under storage_key
+CREATE TABLE IF NOT EXISTS user_documents (
+    document_id INT AUTO_INCREMENT PRIMARY KEY,
+    user_id INT NOT NULL,
+    document_type ENUM('passport', 'national_id', 'visa', 'other') NOT NULL,
+    file_name VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes INT NOT NULL,
+    -- SHA-256 of the file, hex
+    sha256 CHAR(64) NOT NULL,
+    storage_key VARCHAR(255) NOT NULL,
+    uploaded_by INT NULL,
+    created_at DATETIME NOT NULL,
+    KEY idx_user_documents_user (user_id, created_at),
+    CONSTRAINT fk_user_documents_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE,
+    CONSTRAINT fk_user_documents_uploader FOREIGN KEY (uploaded_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,2 @@
+-- File name of the profile photo under avatars/ in object storage
+ALTER TABLE users ADD COLUMN avatar_key VARCHAR(64) NULL;
@@ -0,0 +1,19 @@
+-- Durable background work queued by request handlers and run by the job runner
+CREATE TABLE IF NOT EXISTS jobs (
+    job_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    -- `Job::KIND` of the handler that runs it
+    kind VARCHAR(64) NOT NULL,
+    payload JSON NOT NULL,
+    -- Succeeded jobs are deleted; failed ones stay for inspection
+    status ENUM('queued', 'running', 'failed') NOT NULL DEFAULT 'queued',
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL,
+    run_at DATETIME NOT NULL,
+    -- Claim of the runner working on it; another runner may take over once the lease ends
+    locked_by CHAR(32) NULL,
+    locked_until DATETIME NULL,
+    last_error VARCHAR(255) NULL,
+    created_at DATETIME NOT NULL,
+    KEY idx_jobs_due (status, run_at),
+    KEY idx_jobs_lock (locked_by)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,2 @@
+-- Set by the scheduler on tickets that were not checked in when their flight departed
+ALTER TABLE tickets ADD COLUMN no_show BOOLEAN NOT NULL DEFAULT FALSE AFTER checked_in;
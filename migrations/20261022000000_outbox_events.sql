@@ -0,0 +1,14 @@
+-- Domain events written in the same transaction as the change they describe, and handed on by
+-- the outbox relay; an event is never lost once the change is committed
+CREATE TABLE IF NOT EXISTS outbox_events (
+    event_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    -- e.g. `flight.cancelled`
+    event_type VARCHAR(64) NOT NULL,
+    aggregate_type VARCHAR(32) NOT NULL,
+    aggregate_id INT NOT NULL,
+    payload JSON NOT NULL,
+    created_at DATETIME NOT NULL,
+    published_at DATETIME NULL,
+    KEY idx_outbox_events_unpublished (published_at, event_id),
+    KEY idx_outbox_events_aggregate (aggregate_type, aggregate_id)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,3 @@
+-- Language of the notifications sent to the user, chosen on their profile
+ALTER TABLE users
+    ADD COLUMN language ENUM('en', 'uk') NOT NULL DEFAULT 'en';
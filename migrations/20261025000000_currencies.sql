@@ -0,0 +1,10 @@
+-- Fare class prices are in a currency of their own; clients can ask for them in another, converted
+-- with the euro exchange rates below (units of the currency per euro, as the ECB quotes them)
+ALTER TABLE flight_fare_classes
+    ADD COLUMN currency CHAR(3) NOT NULL DEFAULT 'EUR';
+
+CREATE TABLE IF NOT EXISTS exchange_rates (
+    currency CHAR(3) NOT NULL PRIMARY KEY,
+    per_euro DOUBLE NOT NULL,
+    updated_at DATETIME NOT NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,5 @@
+-- IANA timezone of the airports at each end of a route, e.g. Europe/Kyiv. Flight times stay in
+-- UTC and are also shown as the local time of these airports
+ALTER TABLE routes
+    ADD COLUMN origin_timezone VARCHAR(64) NOT NULL DEFAULT 'UTC' AFTER destination,
+    ADD COLUMN destination_timezone VARCHAR(64) NOT NULL DEFAULT 'UTC' AFTER origin_timezone;
@@ -0,0 +1,18 @@
+-- Engineers keep the maintenance records of the fleet
+ALTER TABLE users
+    MODIFY COLUMN staff_position ENUM('gate_agent', 'check_in_agent', 'pilot', 'dispatcher', 'engineer') NULL;
+
+-- Maintenance of an aircraft, which cannot fly from grounded_from until grounded_to
+CREATE TABLE IF NOT EXISTS aircraft_maintenance (
+    maintenance_id INT AUTO_INCREMENT PRIMARY KEY,
+    aircraft_id INT NOT NULL,
+    maintenance_type ENUM('inspection', 'repair', 'overhaul', 'modification') NOT NULL,
+    grounded_from DATETIME NOT NULL,
+    grounded_to DATETIME NOT NULL,
+    notes TEXT NULL,
+    recorded_by INT NULL,
+    created_at DATETIME NOT NULL,
+    KEY idx_aircraft_maintenance_window (aircraft_id, grounded_from, grounded_to),
+    CONSTRAINT fk_aircraft_maintenance_aircraft FOREIGN KEY (aircraft_id) REFERENCES aircraft (aircraft_id) ON DELETE CASCADE,
+    CONSTRAINT fk_aircraft_maintenance_recorder FOREIGN KEY (recorded_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,27 @@
+-- Reusable seat layouts. `sections` lists the cabin classes front to back as
+-- {fare_class, first_row, last_row, seat_letters}; `exit_rows` the row numbers at emergency exits
+CREATE TABLE IF NOT EXISTS cabin_layouts (
+    layout_id INT AUTO_INCREMENT PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    sections JSON NOT NULL,
+    exit_rows JSON NOT NULL,
+    created_by INT NULL,
+    created_at DATETIME NOT NULL,
+    CONSTRAINT fk_cabin_layouts_creator FOREIGN KEY (created_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+ALTER TABLE aircraft
+    ADD COLUMN cabin_layout_id INT NULL,
+    ADD CONSTRAINT fk_aircraft_cabin_layout FOREIGN KEY (cabin_layout_id) REFERENCES cabin_layouts (layout_id);
+
+-- Seats of a flight, generated from its aircraft's layout when the flight is scheduled
+CREATE TABLE IF NOT EXISTS flight_seats (
+    flight_id INT NOT NULL,
+    seat_number VARCHAR(5) NOT NULL,
+    seat_row INT NOT NULL,
+    fare_class ENUM('economy', 'business', 'first') NOT NULL,
+    exit_row BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (flight_id, seat_number),
+    KEY idx_flight_seats_row (flight_id, seat_row),
+    CONSTRAINT fk_flight_seats_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,17 @@
+-- Departure gates of each airport, by IATA code (the code in brackets of a route's origin)
+CREATE TABLE IF NOT EXISTS gates (
+    gate_id INT AUTO_INCREMENT PRIMARY KEY,
+    airport_code CHAR(3) NOT NULL,
+    gate_code VARCHAR(10) NOT NULL,
+    terminal VARCHAR(10) NULL,
+    created_at DATETIME NOT NULL,
+    UNIQUE KEY uq_gates_code (airport_code, gate_code)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- `gate` stays the name shown to passengers; flights given a gate entity carry its code and
+-- terminal there too, and older flights keep their free-text gate
+ALTER TABLE flights
+    ADD COLUMN gate_id INT NULL,
+    ADD COLUMN terminal VARCHAR(10) NULL,
+    ADD KEY idx_flights_gate (gate_id, departure_time),
+    ADD CONSTRAINT fk_flights_gate FOREIGN KEY (gate_id) REFERENCES gates (gate_id);
@@ -0,0 +1,16 @@
+-- Marketing flight numbers a flight is also sold under (code-share); `flights.flight_number` is
+-- the operating carrier's
+CREATE TABLE IF NOT EXISTS flight_codeshares (
+    flight_id INT NOT NULL,
+    flight_number VARCHAR(10) NOT NULL,
+    PRIMARY KEY (flight_id, flight_number),
+    KEY idx_flight_codeshares_number (flight_number),
+    CONSTRAINT fk_flight_codeshares_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- The flight number the ticket was sold under, operating or marketing
+ALTER TABLE tickets ADD COLUMN sold_as VARCHAR(10) NULL;
+
+UPDATE tickets t
+JOIN flights f ON f.flight_id = t.flight_id
+SET t.sold_as = f.flight_number;
@@ -0,0 +1,26 @@
+-- Checked bags of a ticket, identified by the 10-digit number on their tag
+CREATE TABLE IF NOT EXISTS baggage (
+    bag_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    tag_number CHAR(10) NOT NULL,
+    weight_kg DOUBLE NOT NULL,
+    status ENUM('checked', 'loaded', 'unloaded', 'delivered') NOT NULL DEFAULT 'checked',
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    UNIQUE KEY uq_baggage_tag (tag_number),
+    KEY idx_baggage_ticket (ticket_id),
+    CONSTRAINT fk_baggage_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- Every scan of a bag, the first being its check-in
+CREATE TABLE IF NOT EXISTS baggage_scans (
+    scan_id INT AUTO_INCREMENT PRIMARY KEY,
+    bag_id INT NOT NULL,
+    status ENUM('checked', 'loaded', 'unloaded', 'delivered') NOT NULL,
+    location VARCHAR(50) NULL,
+    scanned_by INT NULL,
+    scanned_at DATETIME NOT NULL,
+    KEY idx_baggage_scans_bag (bag_id, scanned_at),
+    CONSTRAINT fk_baggage_scans_bag FOREIGN KEY (bag_id) REFERENCES baggage (bag_id) ON DELETE CASCADE,
+    CONSTRAINT fk_baggage_scans_user FOREIGN KEY (scanned_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,43 @@
+-- Checked baggage included in a fare class and what more of it costs. Rows without a route
+-- apply to every route; a row for the route overrides them
+CREATE TABLE IF NOT EXISTS baggage_allowances (
+    allowance_id INT AUTO_INCREMENT PRIMARY KEY,
+    fare_class ENUM('economy', 'business', 'first') NOT NULL,
+    route_id INT NULL,
+    pieces INT NOT NULL,
+    max_weight_kg DOUBLE NOT NULL,
+    -- Fees are in `currency`: a piece over the allowance at the desk, a piece bought before
+    -- departure, and each kilogram a bag weighs over `max_weight_kg`
+    extra_piece_fee DOUBLE NOT NULL,
+    prepaid_piece_fee DOUBLE NOT NULL,
+    overweight_fee_per_kg DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL DEFAULT 'EUR',
+    KEY idx_baggage_allowances_class (fare_class, route_id),
+    CONSTRAINT fk_baggage_allowances_route FOREIGN KEY (route_id) REFERENCES routes (route_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+INSERT INTO baggage_allowances
+    (fare_class, route_id, pieces, max_weight_kg, extra_piece_fee, prepaid_piece_fee, overweight_fee_per_kg)
+VALUES
+    ('economy', NULL, 1, 23, 60, 45, 10),
+    ('business', NULL, 2, 32, 60, 45, 10),
+    ('first', NULL, 3, 32, 60, 45, 10);
+
+-- Pieces bought on top of a ticket's allowance before departure
+CREATE TABLE IF NOT EXISTS baggage_purchases (
+    purchase_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    pieces INT NOT NULL,
+    price DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL,
+    purchased_by INT NULL,
+    purchased_at DATETIME NOT NULL,
+    KEY idx_baggage_purchases_ticket (ticket_id),
+    CONSTRAINT fk_baggage_purchases_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    CONSTRAINT fk_baggage_purchases_user FOREIGN KEY (purchased_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- What the passenger paid at check-in for a bag over the allowance
+ALTER TABLE baggage
+    ADD COLUMN excess_fee DOUBLE NOT NULL DEFAULT 0,
+    ADD COLUMN excess_fee_currency CHAR(3) NOT NULL DEFAULT 'EUR';
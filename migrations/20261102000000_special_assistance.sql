@@ -0,0 +1,14 @@
+-- Help a passenger needs on their journey, which staff acknowledge before the ticket can be
+-- checked in
+CREATE TABLE IF NOT EXISTS assistance_requests (
+    assistance_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    assistance_type ENUM('wheelchair', 'unaccompanied_minor', 'medical') NOT NULL,
+    details VARCHAR(500) NULL,
+    created_at DATETIME NOT NULL,
+    acknowledged_by INT NULL,
+    acknowledged_at DATETIME NULL,
+    UNIQUE KEY uq_assistance_requests_ticket_type (ticket_id, assistance_type),
+    CONSTRAINT fk_assistance_requests_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    CONSTRAINT fk_assistance_requests_user FOREIGN KEY (acknowledged_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
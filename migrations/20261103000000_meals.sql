@@ -0,0 +1,10 @@
+-- Meals catered on a flight; passengers choose one of them for their ticket
+CREATE TABLE IF NOT EXISTS flight_meals (
+    flight_id INT NOT NULL,
+    meal ENUM('standard', 'vegetarian', 'vegan', 'halal', 'kosher', 'gluten_free', 'child') NOT NULL,
+    PRIMARY KEY (flight_id, meal),
+    CONSTRAINT fk_flight_meals_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+ALTER TABLE tickets
+    ADD COLUMN meal ENUM('standard', 'vegetarian', 'vegan', 'halal', 'kosher', 'gluten_free', 'child') NULL;
@@ -0,0 +1,22 @@
+-- How many pets an aircraft carries in the cabin and in the hold (0 for none), and how heavy a
+-- pet in the cabin may be with its carrier
+ALTER TABLE aircraft
+    ADD COLUMN pets_in_cabin INT NOT NULL DEFAULT 2,
+    ADD COLUMN pets_in_hold INT NOT NULL DEFAULT 2,
+    ADD COLUMN pet_cabin_max_weight_kg DOUBLE NOT NULL DEFAULT 8;
+
+-- A pet travelling on a ticket, at most one per ticket
+CREATE TABLE IF NOT EXISTS ticket_pets (
+    pet_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    placement ENUM('cabin', 'hold') NOT NULL,
+    species ENUM('dog', 'cat', 'other') NOT NULL,
+    weight_kg DOUBLE NOT NULL,
+    fee DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL,
+    created_by INT NULL,
+    created_at DATETIME NOT NULL,
+    UNIQUE KEY uq_ticket_pets_ticket (ticket_id),
+    CONSTRAINT fk_ticket_pets_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    CONSTRAINT fk_ticket_pets_user FOREIGN KEY (created_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
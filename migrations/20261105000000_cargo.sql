@@ -0,0 +1,41 @@
+-- Freight an aircraft carries besides passengers' baggage
+ALTER TABLE aircraft
+    ADD COLUMN cargo_payload_kg DOUBLE NOT NULL DEFAULT 2000,
+    ADD COLUMN cargo_volume_m3 DOUBLE NOT NULL DEFAULT 10;
+
+-- A consignment under its 11-digit air waybill number, carried on one flight between two
+-- airports
+CREATE TABLE IF NOT EXISTS cargo_shipments (
+    shipment_id INT AUTO_INCREMENT PRIMARY KEY,
+    awb_number CHAR(11) NOT NULL,
+    shipper VARCHAR(100) NOT NULL,
+    consignee VARCHAR(100) NOT NULL,
+    origin_code CHAR(3) NOT NULL,
+    destination_code CHAR(3) NOT NULL,
+    description VARCHAR(255) NULL,
+    weight_kg DOUBLE NOT NULL,
+    volume_m3 DOUBLE NOT NULL,
+    status ENUM('received', 'booked', 'loaded', 'in_transit', 'arrived', 'delivered') NOT NULL DEFAULT 'received',
+    flight_id INT NULL,
+    created_by INT NULL,
+    created_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL,
+    UNIQUE KEY uq_cargo_shipments_awb (awb_number),
+    KEY idx_cargo_shipments_flight (flight_id),
+    CONSTRAINT fk_cargo_shipments_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE SET NULL,
+    CONSTRAINT fk_cargo_shipments_user FOREIGN KEY (created_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- Every status a shipment went through, the first being its acceptance
+CREATE TABLE IF NOT EXISTS cargo_events (
+    event_id INT AUTO_INCREMENT PRIMARY KEY,
+    shipment_id INT NOT NULL,
+    status ENUM('received', 'booked', 'loaded', 'in_transit', 'arrived', 'delivered') NOT NULL,
+    flight_id INT NULL,
+    location VARCHAR(50) NULL,
+    recorded_by INT NULL,
+    recorded_at DATETIME NOT NULL,
+    KEY idx_cargo_events_shipment (shipment_id, recorded_at),
+    CONSTRAINT fk_cargo_events_shipment FOREIGN KEY (shipment_id) REFERENCES cargo_shipments (shipment_id) ON DELETE CASCADE,
+    CONSTRAINT fk_cargo_events_user FOREIGN KEY (recorded_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
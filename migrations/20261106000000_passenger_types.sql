@@ -0,0 +1,20 @@
+-- Who travels on a ticket, by age on the departure date: adults from 12, children from 2, infants
+-- under 2 in a seat of their own
+ALTER TABLE tickets
+    ADD COLUMN passenger_type ENUM('adult', 'child', 'infant') NOT NULL DEFAULT 'adult' AFTER fare_class;
+
+-- An infant under 2 travelling on the lap of an adult ticket holder, without a seat
+CREATE TABLE IF NOT EXISTS ticket_infants (
+    infant_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    first_name VARCHAR(50) NOT NULL,
+    last_name VARCHAR(50) NOT NULL,
+    date_of_birth DATE NOT NULL,
+    fare DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL,
+    created_by INT NULL,
+    created_at DATETIME NOT NULL,
+    UNIQUE KEY uq_ticket_infants_ticket (ticket_id),
+    CONSTRAINT fk_ticket_infants_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    CONSTRAINT fk_ticket_infants_user FOREIGN KEY (created_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
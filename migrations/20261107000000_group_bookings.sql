@@ -0,0 +1,27 @@
+-- Tickets booked together on one flight and fare class under a shared six-character reference,
+-- with what the group paid after any group discount
+CREATE TABLE IF NOT EXISTS booking_groups (
+    group_id INT AUTO_INCREMENT PRIMARY KEY,
+    reference CHAR(6) NOT NULL,
+    flight_id INT NOT NULL,
+    fare_class ENUM('economy', 'business', 'first') NOT NULL,
+    passenger_count INT NOT NULL,
+    subtotal DOUBLE NOT NULL,
+    discount DOUBLE NOT NULL,
+    total DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL,
+    booked_by INT NULL,
+    created_at DATETIME NOT NULL,
+    UNIQUE KEY uq_booking_groups_reference (reference),
+    KEY idx_booking_groups_flight (flight_id),
+    CONSTRAINT fk_booking_groups_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id),
+    CONSTRAINT fk_booking_groups_user FOREIGN KEY (booked_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- The group a ticket was booked in and the fare paid for it; NULL for tickets issued before
+-- fares were recorded
+ALTER TABLE tickets
+    ADD COLUMN group_id INT NULL,
+    ADD COLUMN fare DOUBLE NULL,
+    ADD COLUMN currency CHAR(3) NULL,
+    ADD CONSTRAINT fk_tickets_group FOREIGN KEY (group_id) REFERENCES booking_groups (group_id) ON DELETE SET NULL;
@@ -0,0 +1,14 @@
+-- Every move of a ticket to another seat after booking, with what the passenger paid for it
+CREATE TABLE IF NOT EXISTS seat_changes (
+    change_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    from_seat VARCHAR(5) NOT NULL,
+    to_seat VARCHAR(5) NOT NULL,
+    fee DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL,
+    changed_by INT NULL,
+    changed_at DATETIME NOT NULL,
+    KEY idx_seat_changes_ticket (ticket_id),
+    CONSTRAINT fk_seat_changes_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    CONSTRAINT fk_seat_changes_user FOREIGN KEY (changed_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,18 @@
+-- Every paid move of a ticket to a higher class, with the ticket number it was reissued under
+CREATE TABLE IF NOT EXISTS ticket_upgrades (
+    upgrade_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    from_class ENUM('economy', 'business', 'first') NOT NULL,
+    to_class ENUM('economy', 'business', 'first') NOT NULL,
+    from_seat VARCHAR(5) NOT NULL,
+    to_seat VARCHAR(5) NOT NULL,
+    previous_ticket_number VARCHAR(20) NOT NULL,
+    ticket_number VARCHAR(20) NOT NULL,
+    price DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL,
+    upgraded_by INT NULL,
+    upgraded_at DATETIME NOT NULL,
+    KEY idx_ticket_upgrades_ticket (ticket_id),
+    CONSTRAINT fk_ticket_upgrades_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    CONSTRAINT fk_ticket_upgrades_user FOREIGN KEY (upgraded_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
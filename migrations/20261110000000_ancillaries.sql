@@ -0,0 +1,37 @@
+-- Extras sold on top of a ticket, such as priority boarding, extra legroom or lounge access
+CREATE TABLE IF NOT EXISTS ancillary_products (
+    product_id INT AUTO_INCREMENT PRIMARY KEY,
+    code VARCHAR(32) NOT NULL,
+    name VARCHAR(100) NOT NULL,
+    description TEXT NULL,
+    price DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at DATETIME NOT NULL,
+    UNIQUE KEY uq_ancillary_products_code (code)
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- Products sold on a flight, with how many can be sold; NULL capacity is unlimited
+CREATE TABLE IF NOT EXISTS flight_ancillaries (
+    flight_id INT NOT NULL,
+    product_id INT NOT NULL,
+    capacity INT NULL,
+    PRIMARY KEY (flight_id, product_id),
+    CONSTRAINT fk_flight_ancillaries_flight FOREIGN KEY (flight_id) REFERENCES flights (flight_id) ON DELETE CASCADE,
+    CONSTRAINT fk_flight_ancillaries_product FOREIGN KEY (product_id) REFERENCES ancillary_products (product_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
+
+-- Products bought for a ticket, priced in the currency of the ticket's fare class
+CREATE TABLE IF NOT EXISTS ticket_ancillaries (
+    ticket_ancillary_id INT AUTO_INCREMENT PRIMARY KEY,
+    ticket_id INT NOT NULL,
+    product_id INT NOT NULL,
+    price DOUBLE NOT NULL,
+    currency CHAR(3) NOT NULL,
+    purchased_by INT NULL,
+    purchased_at DATETIME NOT NULL,
+    UNIQUE KEY uq_ticket_ancillaries_product (ticket_id, product_id),
+    CONSTRAINT fk_ticket_ancillaries_ticket FOREIGN KEY (ticket_id) REFERENCES tickets (ticket_id) ON DELETE CASCADE,
+    CONSTRAINT fk_ticket_ancillaries_product FOREIGN KEY (product_id) REFERENCES ancillary_products (product_id),
+    CONSTRAINT fk_ticket_ancillaries_user FOREIGN KEY (purchased_by) REFERENCES users (user_id) ON DELETE SET NULL
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,16 @@
+-- When the flight actually left and landed, stamped by the departed and arrived status changes;
+-- compared with departure_time and arrival_time, which stay as scheduled, for on-time performance
+ALTER TABLE flights
+    ADD COLUMN actual_departure_time DATETIME NULL AFTER arrival_time,
+    ADD COLUMN actual_arrival_time DATETIME NULL AFTER actual_departure_time;
+
+UPDATE flights f
+JOIN (
+    SELECT flight_id,
+           MIN(CASE WHEN new_status = 'departed' THEN changed_at END) AS departed_at,
+           MIN(CASE WHEN new_status = 'arrived' THEN changed_at END) AS arrived_at
+    FROM flight_status_history
+    GROUP BY flight_id
+) h ON h.flight_id = f.flight_id
+SET f.actual_departure_time = h.departed_at,
+    f.actual_arrival_time = h.arrived_at;
@@ -0,0 +1,3 @@
+-- Optional, for passenger demographics; NULL when the user did not say
+ALTER TABLE users
+    ADD COLUMN gender ENUM('female', 'male', 'other') NULL AFTER date_of_birth;
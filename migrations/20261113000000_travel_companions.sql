@@ -0,0 +1,11 @@
+-- Consent given by `user_id` for `companion_id` to book tickets in their name, e.g. in a group
+-- booking the companion travels in
+CREATE TABLE IF NOT EXISTS travel_companions (
+    user_id INT NOT NULL,
+    companion_id INT NOT NULL,
+    created_at DATETIME NOT NULL,
+    PRIMARY KEY (user_id, companion_id),
+    KEY idx_travel_companions_companion (companion_id),
+    CONSTRAINT fk_travel_companions_user FOREIGN KEY (user_id) REFERENCES users (user_id) ON DELETE CASCADE,
+    CONSTRAINT fk_travel_companions_companion FOREIGN KEY (companion_id) REFERENCES users (user_id) ON DELETE CASCADE
+) ENGINE = InnoDB DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,4 @@
+-- Wrong login codes in a row, and until when login codes are refused after too many of them
+ALTER TABLE user_totp
+    ADD COLUMN failed_attempts INT NOT NULL DEFAULT 0 AFTER last_used_step,
+    ADD COLUMN locked_until DATETIME NULL AFTER failed_attempts;
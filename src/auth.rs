@@ -0,0 +1,351 @@
+use std::{collections::HashMap, fs};
+
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::{Config, ConfigError};
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::models::{RevokedToken, StaffPosition, User, UserRole};
+
+// JWT payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: i32,
+    pub role: UserRole,
+    #[serde(default)]
+    pub staff_position: Option<StaffPosition>,
+    // Session the token was issued for, see `models::Session`
+    pub sid: String,
+    pub jti: String,
+    pub iat: usize,
+    pub exp: usize,
+    // Whether the session passed two-factor authentication
+    #[serde(default)]
+    pub two_factor: bool,
+}
+
+impl Claims {
+    pub fn issued_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.iat as i64, 0).unwrap_or_default()
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.exp as i64, 0).unwrap_or_default()
+    }
+}
+
+// Signing key plus every key accepted for verification, loaded once at startup.
+// During a rotation the previous public keys stay listed until the tokens they signed expire
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    key_id: String,
+    encoding: EncodingKey,
+    decoding: HashMap<String, DecodingKey>,
+}
+
+fn read_key_file(path: &str) -> Result<Vec<u8>, ConfigError> {
+    fs::read(path).map_err(|e| ConfigError::KeyFile {
+        path: path.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+fn invalid_key(path: &str, e: jsonwebtoken::errors::Error) -> ConfigError {
+    ConfigError::KeyFile {
+        path: path.to_string(),
+        reason: e.to_string(),
+    }
+}
+
+impl JwtKeys {
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        let mut decoding = HashMap::new();
+
+        let (algorithm, encoding) = match config.jwt_algorithm.as_str() {
+            "HS256" => {
+                let secret = config
+                    .jwt_secret
+                    .as_deref()
+                    .ok_or(ConfigError::Missing("JWT_SECRET"))?;
+                decoding.insert(
+                    config.jwt_key_id.clone(),
+                    DecodingKey::from_secret(secret.as_bytes()),
+                );
+                (
+                    Algorithm::HS256,
+                    EncodingKey::from_secret(secret.as_bytes()),
+                )
+            }
+            "RS256" => {
+                let private_path = config
+                    .jwt_private_key_path
+                    .as_deref()
+                    .ok_or(ConfigError::Missing("JWT_PRIVATE_KEY_PATH"))?;
+                let public_path = config
+                    .jwt_public_key_path
+                    .as_deref()
+                    .ok_or(ConfigError::Missing("JWT_PUBLIC_KEY_PATH"))?;
+
+                let encoding = EncodingKey::from_rsa_pem(&read_key_file(private_path)?)
+                    .map_err(|e| invalid_key(private_path, e))?;
+                decoding.insert(
+                    config.jwt_key_id.clone(),
+                    DecodingKey::from_rsa_pem(&read_key_file(public_path)?)
+                        .map_err(|e| invalid_key(public_path, e))?,
+                );
+                for (key_id, path) in &config.jwt_previous_public_keys {
+                    decoding.insert(
+                        key_id.clone(),
+                        DecodingKey::from_rsa_pem(&read_key_file(path)?)
+                            .map_err(|e| invalid_key(path, e))?,
+                    );
+                }
+                (Algorithm::RS256, encoding)
+            }
+            other => {
+                return Err(ConfigError::Invalid {
+                    name: "JWT_ALGORITHM",
+                    value: other.to_string(),
+                })
+            }
+        };
+
+        Ok(Self {
+            algorithm,
+            key_id: config.jwt_key_id.clone(),
+            encoding,
+            decoding,
+        })
+    }
+
+    // Key ids accepted for verification, for the startup log
+    pub fn key_ids(&self) -> Vec<&str> {
+        self.decoding.keys().map(String::as_str).collect()
+    }
+}
+
+pub fn create_token(
+    keys: &JwtKeys,
+    config: &Config,
+    user: &User,
+    session_id: &str,
+    two_factor: bool,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    let now = Utc::now().timestamp() as u64;
+    let claims = Claims {
+        sub: user.user_id,
+        role: user.role,
+        staff_position: user.staff_position,
+        sid: session_id.to_string(),
+        jti: Uuid::new_v4().simple().to_string(),
+        iat: now as usize,
+        exp: (now + config.jwt_expiration) as usize,
+        two_factor,
+    };
+
+    let mut header = Header::new(keys.algorithm);
+    header.kid = Some(keys.key_id.clone());
+
+    encode(&header, &claims, &keys.encoding)
+}
+
+// Check the token's signature, with the key its `kid` names, and expiry
+fn decode_token(keys: &JwtKeys, token: &str) -> Result<Claims, AppError> {
+    let invalid_token = || {
+        AppError::AuthError(
+            ErrorCode::InvalidToken,
+            "Invalid or expired token".to_string(),
+        )
+    };
+
+    // Tokens without a `kid` predate key ids and were signed with the current key
+    let header = decode_header(token).map_err(|_| invalid_token())?;
+    let key_id = header.kid.as_deref().unwrap_or(&keys.key_id);
+    let key = keys.decoding.get(key_id).ok_or_else(invalid_token)?;
+
+    decode::<Claims>(token, key, &Validation::new(keys.algorithm))
+        .map(|data| data.claims)
+        .map_err(|_| invalid_token())
+}
+
+// Decode the token and reject it if it or its session was revoked, or it predates a password change
+pub async fn verify_token(keys: &JwtKeys, pool: &DbPool, token: &str) -> Result<Claims, AppError> {
+    let claims = decode_token(keys, token)?;
+
+    if RevokedToken::is_revoked(pool, &claims).await? {
+        return Err(AppError::AuthError(
+            ErrorCode::TokenRevoked,
+            "Token has been revoked".to_string(),
+        ));
+    }
+
+    Ok(claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use super::*;
+
+    fn key_path(name: &str) -> String {
+        format!("{}/tests/keys/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    // Defaults for everything but the JWT settings each test picks
+    fn config(algorithm: &str) -> Config {
+        static BASE: OnceLock<Config> = OnceLock::new();
+        let mut config = BASE
+            .get_or_init(|| {
+                std::env::set_var("DATABASE_URL", "mysql://localhost/unused");
+                std::env::set_var("JWT_SECRET", "unit-test-secret");
+                Config::from_env().expect("test configuration")
+            })
+            .clone();
+        config.jwt_algorithm = algorithm.to_string();
+        config.jwt_key_id = "current".to_string();
+        config.jwt_private_key_path = Some(key_path("jwt_current.pem"));
+        config.jwt_public_key_path = Some(key_path("jwt_current.pub.pem"));
+        config.jwt_previous_public_keys =
+            vec![("previous".to_string(), key_path("jwt_previous.pub.pem"))];
+        config
+    }
+
+    fn user() -> User {
+        User {
+            user_id: 7,
+            first_name: "Ada".to_string(),
+            last_name: "Lovelace".to_string(),
+            email: "ada@example.com".to_string(),
+            phone: None,
+            password: String::new(),
+            passport_number: None,
+            nationality: None,
+            date_of_birth: None,
+            gender: None,
+            role: UserRole::User,
+            staff_position: None,
+            avatar_key: None,
+            language: Default::default(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    // A token signed by hand, e.g. with a key that is no longer current
+    fn signed(algorithm: Algorithm, key_id: Option<&str>, key: &EncodingKey) -> String {
+        let now = Utc::now().timestamp() as usize;
+        let claims = Claims {
+            sub: 7,
+            role: UserRole::User,
+            staff_position: None,
+            sid: "session".to_string(),
+            jti: "jti".to_string(),
+            iat: now,
+            exp: now + 900,
+            two_factor: false,
+        };
+        let mut header = Header::new(algorithm);
+        header.kid = key_id.map(str::to_string);
+        encode(&header, &claims, key).expect("token")
+    }
+
+    fn private_key(name: &str) -> EncodingKey {
+        EncodingKey::from_rsa_pem(&fs::read(key_path(name)).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn tokens_name_the_current_key() {
+        let config = config("RS256");
+        let keys = JwtKeys::from_config(&config).unwrap();
+        let token = create_token(&keys, &config, &user(), "session", true).unwrap();
+
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.alg, Algorithm::RS256);
+        assert_eq!(header.kid.as_deref(), Some("current"));
+        let claims = decode_token(&keys, &token).unwrap();
+        assert_eq!(claims.sub, 7);
+        assert!(claims.two_factor);
+    }
+
+    #[test]
+    fn tokens_of_previous_keys_verify_until_the_key_is_dropped() {
+        let config = config("RS256");
+        let keys = JwtKeys::from_config(&config).unwrap();
+        let mut key_ids = keys.key_ids();
+        key_ids.sort_unstable();
+        assert_eq!(key_ids, ["current", "previous"]);
+
+        let old = signed(
+            Algorithm::RS256,
+            Some("previous"),
+            &private_key("jwt_previous.pem"),
+        );
+        assert_eq!(decode_token(&keys, &old).unwrap().sub, 7);
+
+        let mut rotated = config.clone();
+        rotated.jwt_previous_public_keys.clear();
+        let keys = JwtKeys::from_config(&rotated).unwrap();
+        assert!(decode_token(&keys, &old).is_err());
+    }
+
+    #[test]
+    fn tokens_without_a_key_id_are_checked_with_the_current_key() {
+        let keys = JwtKeys::from_config(&config("RS256")).unwrap();
+
+        let current = signed(Algorithm::RS256, None, &private_key("jwt_current.pem"));
+        assert!(decode_token(&keys, &current).is_ok());
+        let previous = signed(Algorithm::RS256, None, &private_key("jwt_previous.pem"));
+        assert!(decode_token(&keys, &previous).is_err());
+    }
+
+    #[test]
+    fn unknown_or_mismatched_key_ids_are_rejected() {
+        let keys = JwtKeys::from_config(&config("RS256")).unwrap();
+
+        let unknown = signed(
+            Algorithm::RS256,
+            Some("retired"),
+            &private_key("jwt_current.pem"),
+        );
+        assert!(decode_token(&keys, &unknown).is_err());
+        // Signed with the previous key but claiming to be the current one
+        let mislabelled = signed(
+            Algorithm::RS256,
+            Some("current"),
+            &private_key("jwt_previous.pem"),
+        );
+        assert!(decode_token(&keys, &mislabelled).is_err());
+    }
+
+    #[test]
+    fn tokens_of_the_other_algorithm_are_rejected() {
+        let hs256_config = config("HS256");
+        let hs256 = JwtKeys::from_config(&hs256_config).unwrap();
+        let token = create_token(&hs256, &hs256_config, &user(), "session", false).unwrap();
+        assert_eq!(decode_header(&token).unwrap().alg, Algorithm::HS256);
+        assert!(decode_token(&hs256, &token).is_ok());
+        // HS256 has no previous keys
+        assert_eq!(hs256.key_ids(), ["current"]);
+
+        let rs256 = JwtKeys::from_config(&config("RS256")).unwrap();
+        assert!(decode_token(&rs256, &token).is_err());
+        let rs256_token = signed(Algorithm::RS256, None, &private_key("jwt_current.pem"));
+        assert!(decode_token(&hs256, &rs256_token).is_err());
+    }
+
+    #[test]
+    fn unknown_algorithms_are_a_config_error() {
+        assert!(matches!(
+            JwtKeys::from_config(&config("none")),
+            Err(ConfigError::Invalid {
+                name: "JWT_ALGORITHM",
+                ..
+            })
+        ));
+    }
+}
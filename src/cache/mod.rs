@@ -0,0 +1,70 @@
+// Short-lived copies of hot reference data (routes, flights and flight searches), kept in Redis
+// when REDIS_URL is set so the search path does not reach the database on every request. The
+// cache is optional: without it the repositories are used as they are, and a cache that fails is
+// logged and skipped rather than failing the request
+mod redis;
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+use tracing::warn;
+
+use crate::config::Config;
+
+pub use redis::RedisCache;
+
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("cache timed out")]
+    Timeout,
+    // An error reply from the server
+    #[error("cache replied `{0}`")]
+    Server(String),
+    #[error("malformed cache reply")]
+    MalformedReply,
+    #[error("{0}")]
+    Config(String),
+}
+
+#[async_trait]
+pub trait Cache: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError>;
+
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<(), CacheError>;
+
+    async fn delete(&self, key: &str) -> Result<(), CacheError>;
+
+    // Add one to a counter, starting from 0, and return the new value
+    async fn increment(&self, key: &str) -> Result<i64, CacheError>;
+}
+
+// A cached value, or None when it is missing, unreadable (e.g. written by an older version) or
+// the cache is failing
+pub async fn get_json<T: DeserializeOwned>(cache: &dyn Cache, key: &str) -> Option<T> {
+    match cache.get(key).await {
+        Ok(bytes) => bytes.and_then(|bytes| serde_json::from_slice(&bytes).ok()),
+        Err(e) => {
+            warn!("Cache read of {} failed: {}", key, e);
+            None
+        }
+    }
+}
+
+pub async fn set_json<T: Serialize>(cache: &dyn Cache, key: &str, value: &T, ttl: Duration) {
+    let bytes = serde_json::to_vec(value).expect("cached values serialize to JSON");
+    if let Err(e) = cache.set(key, &bytes, ttl).await {
+        warn!("Cache write of {} failed: {}", key, e);
+    }
+}
+
+// The cache selected by the configuration, or None to go to the database every time
+pub fn from_config(config: &Config) -> Result<Option<Arc<dyn Cache>>, CacheError> {
+    match &config.redis_url {
+        Some(url) => Ok(Some(Arc::new(RedisCache::new(url)?))),
+        None => Ok(None),
+    }
+}
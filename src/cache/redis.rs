@@ -0,0 +1,235 @@
+// A minimal Redis client (RESP2) for the few commands the cache needs. Connections are kept in a
+// small pool and dropped on any error, so a reply is never read from a connection that is out
+// of step
+use std::{sync::Mutex, time::Duration};
+
+use async_trait::async_trait;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufStream},
+    net::TcpStream,
+};
+use url::Url;
+
+use super::{Cache, CacheError};
+
+// A cache slower than this is treated as down; the database is asked instead
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+// Idle connections kept for reuse
+const POOL_SIZE: usize = 8;
+
+const DEFAULT_PORT: u16 = 6379;
+
+#[derive(Debug, PartialEq, Eq)]
+enum Reply {
+    // `+OK` and the like
+    Status,
+    Integer(i64),
+    Bulk(Option<Vec<u8>>),
+}
+
+// `redis://[[user]:password@]host[:port][/db]`
+pub struct RedisCache {
+    host: String,
+    port: u16,
+    username: Option<String>,
+    password: Option<String>,
+    database: Option<u32>,
+    idle: Mutex<Vec<BufStream<TcpStream>>>,
+}
+
+impl RedisCache {
+    pub fn new(url: &str) -> Result<Self, CacheError> {
+        let invalid =
+            |reason: &str| CacheError::Config(format!("REDIS_URL is invalid: {}", reason));
+        let url = Url::parse(url).map_err(|e| invalid(&e.to_string()))?;
+        if url.scheme() != "redis" {
+            return Err(invalid("only redis:// urls are supported"));
+        }
+        let host = url
+            .host_str()
+            .ok_or_else(|| invalid("no host"))?
+            .to_string();
+        let decode = |part: &str| {
+            urlencoding::decode(part)
+                .map(|part| part.into_owned())
+                .map_err(|_| invalid("credentials are not UTF-8"))
+        };
+        let username = match url.username() {
+            "" => None,
+            username => Some(decode(username)?),
+        };
+        let password = url.password().map(decode).transpose()?;
+        let database = match url.path().trim_start_matches('/') {
+            "" => None,
+            database => Some(
+                database
+                    .parse()
+                    .map_err(|_| invalid("bad database number"))?,
+            ),
+        };
+        Ok(Self {
+            host,
+            port: url.port().unwrap_or(DEFAULT_PORT),
+            username,
+            password,
+            database,
+            idle: Mutex::new(Vec::new()),
+        })
+    }
+
+    async fn connect(&self) -> Result<BufStream<TcpStream>, CacheError> {
+        let tcp = TcpStream::connect((self.host.as_str(), self.port)).await?;
+        tcp.set_nodelay(true)?;
+        let mut connection = BufStream::new(tcp);
+        if let Some(password) = &self.password {
+            let mut auth = vec!["AUTH".as_bytes()];
+            if let Some(username) = &self.username {
+                auth.push(username.as_bytes());
+            }
+            auth.push(password.as_bytes());
+            round_trip(&mut connection, &auth).await?;
+        }
+        if let Some(database) = self.database {
+            round_trip(
+                &mut connection,
+                &[b"SELECT", database.to_string().as_bytes()],
+            )
+            .await?;
+        }
+        Ok(connection)
+    }
+
+    // Run one command on a pooled connection, which goes back to the pool only if it succeeded
+    async fn command(&self, args: &[&[u8]]) -> Result<Reply, CacheError> {
+        let pooled = self.idle.lock().expect("cache pool lock").pop();
+        let attempt = async {
+            let mut connection = match pooled {
+                Some(connection) => connection,
+                None => self.connect().await?,
+            };
+            let reply = round_trip(&mut connection, args).await?;
+            Ok::<_, CacheError>((connection, reply))
+        };
+        let (connection, reply) = tokio::time::timeout(TIMEOUT, attempt)
+            .await
+            .map_err(|_| CacheError::Timeout)??;
+        let mut idle = self.idle.lock().expect("cache pool lock");
+        if idle.len() < POOL_SIZE {
+            idle.push(connection);
+        }
+        Ok(reply)
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        match self.command(&[b"GET", key.as_bytes()]).await? {
+            Reply::Bulk(value) => Ok(value),
+            _ => Err(CacheError::MalformedReply),
+        }
+    }
+
+    async fn set(&self, key: &str, value: &[u8], ttl: Duration) -> Result<(), CacheError> {
+        let millis = ttl.as_millis().max(1).to_string();
+        self.command(&[b"SET", key.as_bytes(), value, b"PX", millis.as_bytes()])
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), CacheError> {
+        self.command(&[b"DEL", key.as_bytes()]).await?;
+        Ok(())
+    }
+
+    async fn increment(&self, key: &str) -> Result<i64, CacheError> {
+        match self.command(&[b"INCR", key.as_bytes()]).await? {
+            Reply::Integer(value) => Ok(value),
+            _ => Err(CacheError::MalformedReply),
+        }
+    }
+}
+
+// A command as an array of bulk strings
+fn encode(args: &[&[u8]]) -> Vec<u8> {
+    let mut command = format!("*{}\r\n", args.len()).into_bytes();
+    for arg in args {
+        command.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+        command.extend_from_slice(arg);
+        command.extend_from_slice(b"\r\n");
+    }
+    command
+}
+
+async fn round_trip(
+    connection: &mut BufStream<TcpStream>,
+    args: &[&[u8]],
+) -> Result<Reply, CacheError> {
+    connection.write_all(&encode(args)).await?;
+    connection.flush().await?;
+    read_reply(connection).await
+}
+
+// One reply; arrays are never asked for
+async fn read_reply<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> Result<Reply, CacheError> {
+    let mut line = Vec::new();
+    reader.read_until(b'\n', &mut line).await?;
+    let line = line
+        .strip_suffix(b"\r\n")
+        .ok_or(CacheError::MalformedReply)?;
+    let (kind, rest) = line.split_first().ok_or(CacheError::MalformedReply)?;
+    let rest = String::from_utf8_lossy(rest).into_owned();
+    let number = || rest.parse::<i64>().map_err(|_| CacheError::MalformedReply);
+    match kind {
+        b'+' => Ok(Reply::Status),
+        b'-' => Err(CacheError::Server(rest)),
+        b':' => Ok(Reply::Integer(number()?)),
+        b'$' => {
+            let Ok(length) = usize::try_from(number()?) else {
+                return Ok(Reply::Bulk(None));
+            };
+            let mut value = vec![0; length + 2];
+            reader.read_exact(&mut value).await?;
+            if !value.ends_with(b"\r\n") {
+                return Err(CacheError::MalformedReply);
+            }
+            value.truncate(length);
+            Ok(Reply::Bulk(Some(value)))
+        }
+        _ => Err(CacheError::MalformedReply),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn speaks_resp() {
+        assert_eq!(
+            encode(&[b"SET", b"k", b"v a"]),
+            b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$3\r\nv a\r\n"
+        );
+
+        let mut replies: &[u8] = b"+OK\r\n:7\r\n$5\r\nab\r\nc\r\n$-1\r\n-ERR wrong type\r\n";
+        assert_eq!(read_reply(&mut replies).await.unwrap(), Reply::Status);
+        assert_eq!(read_reply(&mut replies).await.unwrap(), Reply::Integer(7));
+        assert_eq!(
+            read_reply(&mut replies).await.unwrap(),
+            Reply::Bulk(Some(b"ab\r\nc".to_vec()))
+        );
+        assert_eq!(read_reply(&mut replies).await.unwrap(), Reply::Bulk(None));
+        assert!(matches!(
+            read_reply(&mut replies).await,
+            Err(CacheError::Server(reason)) if reason == "ERR wrong type"
+        ));
+
+        let cache = RedisCache::new("redis://:secret@cache.internal/2").unwrap();
+        assert_eq!(
+            (cache.port, cache.password.as_deref(), cache.database),
+            (DEFAULT_PORT, Some("secret"), Some(2))
+        );
+        assert!(RedisCache::new("rediss://cache.internal").is_err());
+    }
+}
@@ -1,34 +1,525 @@
 use dotenv::dotenv;
-use std::env;
+use std::{env, fmt, net::IpAddr, str::FromStr};
+use thiserror::Error;
+use url::Url;
 
-#[derive(Debug, Clone)]
+use crate::cron::Schedule;
+use crate::http_client;
+use crate::pricing::StrategyKind;
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("{0} must be set")]
+    Missing(&'static str),
+    #[error("{name} has invalid value {value:?}")]
+    Invalid { name: &'static str, value: String },
+    #[error("cannot load key {path}: {reason}")]
+    KeyFile { path: String, reason: String },
+}
+
+#[derive(Clone)]
 pub struct Config {
     pub database_url: String,
+    // Leave schema changes to a separate deploy step instead of applying them at startup
+    pub skip_migrations: bool,
+    pub bind_host: IpAddr,
     pub server_port: u16,
-    pub jwt_secret: String,
+    // PEM certificate chain and private key; both set means the server speaks HTTPS itself
+    pub tls_cert_path: Option<String>,
+    pub tls_key_path: Option<String>,
+    // Plain HTTP port redirecting to HTTPS, only used with TLS
+    pub http_redirect_port: Option<u16>,
+    // Strict-Transport-Security max-age sent over HTTPS
+    pub hsts_max_age: u64,
+    // Browser origins allowed to call the API; empty disables CORS, `*` allows any
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_allowed_methods: Vec<String>,
+    pub cors_allowed_headers: Vec<String>,
+    // Seconds browsers may cache a preflight answer
+    pub cors_max_age: u64,
+    // HS256 (shared secret) or RS256 (PEM key pair)
+    pub jwt_algorithm: String,
+    pub jwt_secret: Option<String>,
+    pub jwt_private_key_path: Option<String>,
+    pub jwt_public_key_path: Option<String>,
+    // `kid` header of newly signed tokens
+    pub jwt_key_id: String,
+    // Public keys of earlier signing keys, still accepted until their tokens expire
+    pub jwt_previous_public_keys: Vec<(String, String)>,
     pub jwt_expiration: u64,
+    // Seconds token revocation checks and user permissions are remembered in memory; 0 reads the
+    // database every time
+    pub auth_cache_ttl: u64,
+    pub refresh_token_expiration: u64,
+    pub password_reset_expiration: u64,
+    pub password_min_length: usize,
+    pub password_min_character_classes: usize,
+    pub otp_expiration: u64,
+    pub totp_issuer: String,
+    pub require_admin_2fa: bool,
+    // `fixed` or `demand`
+    pub pricing_strategy: StrategyKind,
+    // Minutes a crew member needs between arriving on one flight and departing on the next
+    pub crew_turnaround_minutes: u32,
+    // Duty-time limits checked when crew is assigned; dispatchers can override them
+    pub crew_max_duty_hours_per_day: u32,
+    pub crew_max_duty_hours_per_week: u32,
+    pub crew_min_rest_hours: u32,
+    // Minutes before departure a flight holds its gate
+    pub gate_occupancy_minutes: u32,
+    // Hours before departure after which passengers can no longer change their meal
+    pub meal_selection_cutoff_hours: u32,
+    // Pet fees in the base currency: flat in the cabin, flat plus per kilogram in the hold
+    pub pet_cabin_fee: f64,
+    pub pet_hold_fee: f64,
+    pub pet_hold_fee_per_kg: f64,
+    // Share of the adult fare, in percent, paid by children and by infants on a lap
+    pub child_fare_percent: f64,
+    pub lap_infant_fare_percent: f64,
+    // Percentage off every fare of a group booking of at least this many passengers
+    pub group_discount_min_passengers: usize,
+    pub group_discount_percent: f64,
+    // Charged in the base currency for moving into an exit-row seat with extra legroom
+    pub exit_row_seat_fee: f64,
+    pub miles_accrual_interval: u64,
+    pub webhook_delivery_interval: u64,
+    // Seconds between polls for due background jobs
+    pub job_poll_interval: u64,
+    // Seconds between passes of the outbox relay over recorded domain events
+    pub outbox_relay_interval: u64,
+    // Cron expressions (UTC) of the scheduled tasks; `off` disables one
+    pub schedule_mark_departed: Option<Schedule>,
+    pub schedule_flag_no_shows: Option<Schedule>,
+    pub schedule_purge_reset_tokens: Option<Schedule>,
+    pub data_export_interval: u64,
+    // Seconds a finished data export can be downloaded
+    pub data_export_ttl: u64,
+    // Seconds an Idempotency-Key and its stored response are kept
+    pub idempotency_key_ttl: u64,
+    // Euro reference rates in the ECB's XML format, e.g.
+    // https://www.ecb.europa.eu/stats/eurofxref/eurofxref-daily.xml; the stored rates are left as
+    // they are when unset
+    pub exchange_rates_url: Option<Url>,
+    // Seconds between refreshes of the exchange rates
+    pub exchange_rates_interval: u64,
+    // `local` or `s3`
+    pub storage_backend: String,
+    // Directory files are stored under with the local backend
+    pub storage_path: String,
+    // S3 or S3-compatible (MinIO) service; AWS's regional endpoint when unset
+    pub s3_endpoint: Option<String>,
+    pub s3_bucket: Option<String>,
+    pub s3_region: String,
+    pub s3_access_key_id: Option<String>,
+    pub s3_secret_access_key: Option<String>,
+    // Bucket in the path rather than the host name, as MinIO expects
+    pub s3_path_style: bool,
+    // Largest request body accepted by endpoints that take JSON, in bytes
+    pub json_body_max_bytes: usize,
+    // Largest identity document accepted, in bytes
+    pub document_max_bytes: usize,
+    // Largest profile photo accepted, in bytes
+    pub avatar_max_bytes: usize,
+    // Key of links to files fetched without a token (profile photos); random per process when
+    // unset, so links then stop working on restart and differ between instances
+    pub url_signing_secret: Option<String>,
+    // Seconds such a link stays valid, at least; it is renewed once per window
+    pub signed_url_ttl: u64,
+    // host:port of a clamd to scan uploads with; uploads are not scanned when unset
+    pub clamav_address: Option<String>,
+    // `log`, `smtp` or `ses`; the log backend only writes emails to the log
+    pub mail_backend: String,
+    // Sender of every email, `address` or `Name <address>`
+    pub mail_from: String,
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    // `starttls`, `tls` (implicit, port 465) or `none`
+    pub smtp_security: String,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    // SES's regional endpoint when unset
+    pub ses_endpoint: Option<String>,
+    pub ses_region: String,
+    pub ses_access_key_id: Option<String>,
+    pub ses_secret_access_key: Option<String>,
+    // `redis://[[user]:password@]host[:port][/db]`; flights and routes are read from the database
+    // every time when unset
+    pub redis_url: Option<String>,
+    // Seconds a cached flight or route is served; flights are also dropped when they change
+    pub cache_ttl: u64,
+    // Seconds a cached flight search or count is served
+    pub cache_search_ttl: u64,
+    // `none`, `nats` or `kafka` (through a REST Proxy); outbox events are only streamed when set
+    pub event_bus: String,
+    // `nats://[user:password@]host:port` or the REST Proxy's http(s) url
+    pub event_bus_url: Option<String>,
+    // Events go to `<prefix>.<event type>`, e.g. `airlines.flight.cancelled`
+    pub event_bus_topic_prefix: String,
+    // Seconds in-flight requests and background work get to finish after SIGTERM
+    pub shutdown_grace_period: u64,
+    // gzip or brotli response bodies for clients that accept them, from this size in bytes
+    pub compress_responses: bool,
+    pub compression_min_bytes: u16,
+    // Accept gzip or brotli request bodies (Content-Encoding)
+    pub decompress_requests: bool,
+    pub legacy_responses: bool,
+    // Sentry-compatible DSN; server errors are only logged when unset
+    pub error_reporting_dsn: Option<String>,
+    // Share of server errors sent to the error tracker, 0.0 to 1.0
+    pub error_reporting_sample_rate: f64,
+    pub error_reporting_environment: String,
 }
 
-impl Config {
-    pub fn from_env() -> Result<Self, env::VarError> {
-        dotenv().ok();
+fn required(name: &'static str) -> Result<String, ConfigError> {
+    match env::var(name) {
+        Ok(value) if !value.is_empty() => Ok(value),
+        _ => Err(ConfigError::Missing(name)),
+    }
+}
 
-        let database_url = env::var("DATABASE_URL")?;
-        let server_port = env::var("SERVER_PORT")
-            .unwrap_or_else(|_| "3000".to_string())
-            .parse()
-            .expect("SERVER_PORT must be a number");
-        let jwt_secret = env::var("JWT_SECRET")?;
-        let jwt_expiration = env::var("JWT_EXPIRATION")
-            .unwrap_or_else(|_| "86400".to_string()) // 24 hours in seconds
+fn optional(name: &'static str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+// Comma-separated values, e.g. CORS_ALLOWED_ORIGINS=https://a.example,https://b.example
+fn list(name: &'static str, default: &str) -> Vec<String> {
+    optional(name)
+        .as_deref()
+        .unwrap_or(default)
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// `kid=path,kid=path` list of previous public keys
+fn key_list(name: &'static str) -> Result<Vec<(String, String)>, ConfigError> {
+    let Some(value) = optional(name) else {
+        return Ok(Vec::new());
+    };
+
+    value
+        .split(',')
+        .map(|entry| match entry.trim().split_once('=') {
+            Some((key_id, path)) if !key_id.is_empty() && !path.is_empty() => {
+                Ok((key_id.to_string(), path.to_string()))
+            }
+            _ => Err(ConfigError::Invalid {
+                name,
+                value: value.clone(),
+            }),
+        })
+        .collect()
+}
+
+fn parsed_or<T: FromStr>(name: &'static str, default: T) -> Result<T, ConfigError> {
+    match env::var(name) {
+        Ok(value) => value
             .parse()
-            .expect("JWT_EXPIRATION must be a number");
+            .map_err(|_| ConfigError::Invalid { name, value }),
+        Err(_) => Ok(default),
+    }
+}
+
+fn optional_parsed<T: FromStr>(name: &'static str) -> Result<Option<T>, ConfigError> {
+    optional(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|_| ConfigError::Invalid { name, value })
+        })
+        .transpose()
+}
+
+// An http(s) URL the server fetches from
+fn optional_url(name: &'static str) -> Result<Option<Url>, ConfigError> {
+    optional(name)
+        .map(|value| {
+            http_client::validate_url(&value).map_err(|_| ConfigError::Invalid { name, value })
+        })
+        .transpose()
+}
+
+// A cron expression, or `off` for no schedule at all
+fn schedule(name: &'static str, default: &str) -> Result<Option<Schedule>, ConfigError> {
+    let value = optional(name).unwrap_or_else(|| default.to_string());
+    if value == "off" {
+        return Ok(None);
+    }
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| ConfigError::Invalid { name, value })
+}
+
+// A boolean setting: `true`, `1`, `yes` or `on`, and `false`, `0`, `no`, `off` or empty
+fn flag(name: &'static str, default: bool) -> Result<bool, ConfigError> {
+    match env::var(name) {
+        Ok(value) => match value.as_str() {
+            "true" | "1" | "yes" | "on" => Ok(true),
+            "false" | "0" | "no" | "off" | "" => Ok(false),
+            _ => Err(ConfigError::Invalid { name, value }),
+        },
+        Err(_) => Ok(default),
+    }
+}
+
+impl Config {
+    // Read and validate all settings once at startup; any missing or malformed value is an error
+    pub fn from_env() -> Result<Self, ConfigError> {
+        dotenv().ok();
 
         Ok(Self {
-            database_url,
-            server_port,
-            jwt_secret,
-            jwt_expiration,
+            database_url: required("DATABASE_URL")?,
+            skip_migrations: flag("SKIP_MIGRATIONS", false)?,
+            bind_host: parsed_or("BIND_HOST", IpAddr::from([0, 0, 0, 0]))?,
+            server_port: parsed_or("SERVER_PORT", 3000)?,
+            tls_cert_path: optional("TLS_CERT_PATH"),
+            tls_key_path: optional("TLS_KEY_PATH"),
+            http_redirect_port: optional_parsed("HTTP_REDIRECT_PORT")?,
+            hsts_max_age: parsed_or("HSTS_MAX_AGE", 31_536_000)?, // 1 year
+            cors_allowed_origins: list("CORS_ALLOWED_ORIGINS", ""),
+            cors_allowed_methods: list("CORS_ALLOWED_METHODS", "GET,POST,PUT,PATCH,DELETE"),
+            cors_allowed_headers: list(
+                "CORS_ALLOWED_HEADERS",
+                "authorization,content-type,x-api-key,x-request-id,accept-language",
+            ),
+            cors_max_age: parsed_or("CORS_MAX_AGE", 600)?,
+            jwt_algorithm: parsed_or("JWT_ALGORITHM", "HS256".to_string())?,
+            jwt_secret: optional("JWT_SECRET"),
+            jwt_private_key_path: optional("JWT_PRIVATE_KEY_PATH"),
+            jwt_public_key_path: optional("JWT_PUBLIC_KEY_PATH"),
+            jwt_key_id: parsed_or("JWT_KEY_ID", "primary".to_string())?,
+            jwt_previous_public_keys: key_list("JWT_PREVIOUS_PUBLIC_KEYS")?,
+            jwt_expiration: parsed_or("JWT_EXPIRATION", 900)?, // 15 minutes in seconds
+            auth_cache_ttl: parsed_or("AUTH_CACHE_TTL", 5)?,
+            refresh_token_expiration: parsed_or("REFRESH_TOKEN_EXPIRATION", 2_592_000)?, // 30 days
+            password_reset_expiration: parsed_or("PASSWORD_RESET_EXPIRATION", 3600)?,    // 1 hour
+            password_min_length: parsed_or("PASSWORD_MIN_LENGTH", 10)?,
+            password_min_character_classes: parsed_or("PASSWORD_MIN_CHARACTER_CLASSES", 3)?,
+            otp_expiration: parsed_or("OTP_EXPIRATION", 300)?, // 5 minutes in seconds
+            totp_issuer: parsed_or("TOTP_ISSUER", "Airlines API".to_string())?,
+            // Admin powers are withheld from sessions that did not pass two-factor authentication
+            require_admin_2fa: flag("REQUIRE_ADMIN_2FA", false)?,
+            pricing_strategy: parsed_or("PRICING_STRATEGY", StrategyKind::Demand)?,
+            crew_turnaround_minutes: parsed_or("CREW_TURNAROUND_MINUTES", 45)?,
+            crew_max_duty_hours_per_day: parsed_or("CREW_MAX_DUTY_HOURS_PER_DAY", 13)?,
+            crew_max_duty_hours_per_week: parsed_or("CREW_MAX_DUTY_HOURS_PER_WEEK", 60)?,
+            crew_min_rest_hours: parsed_or("CREW_MIN_REST_HOURS", 10)?,
+            gate_occupancy_minutes: parsed_or("GATE_OCCUPANCY_MINUTES", 60)?,
+            meal_selection_cutoff_hours: parsed_or("MEAL_SELECTION_CUTOFF_HOURS", 24)?,
+            pet_cabin_fee: parsed_or("PET_CABIN_FEE", 50.0)?,
+            pet_hold_fee: parsed_or("PET_HOLD_FEE", 100.0)?,
+            pet_hold_fee_per_kg: parsed_or("PET_HOLD_FEE_PER_KG", 2.0)?,
+            child_fare_percent: parsed_or("CHILD_FARE_PERCENT", 75.0)?,
+            lap_infant_fare_percent: parsed_or("LAP_INFANT_FARE_PERCENT", 10.0)?,
+            group_discount_min_passengers: parsed_or("GROUP_DISCOUNT_MIN_PASSENGERS", 10)?,
+            group_discount_percent: parsed_or("GROUP_DISCOUNT_PERCENT", 5.0)?,
+            exit_row_seat_fee: parsed_or("EXIT_ROW_SEAT_FEE", 25.0)?,
+            miles_accrual_interval: parsed_or("MILES_ACCRUAL_INTERVAL", 300)?, // 5 minutes
+            webhook_delivery_interval: parsed_or("WEBHOOK_DELIVERY_INTERVAL", 10)?,
+            job_poll_interval: parsed_or("JOB_POLL_INTERVAL", 5)?,
+            outbox_relay_interval: parsed_or("OUTBOX_RELAY_INTERVAL", 2)?,
+            schedule_mark_departed: schedule("SCHEDULE_MARK_DEPARTED", "*/5 * * * *")?,
+            schedule_flag_no_shows: schedule("SCHEDULE_FLAG_NO_SHOWS", "*/15 * * * *")?,
+            schedule_purge_reset_tokens: schedule("SCHEDULE_PURGE_RESET_TOKENS", "@hourly")?,
+            data_export_interval: parsed_or("DATA_EXPORT_INTERVAL", 10)?,
+            data_export_ttl: parsed_or("DATA_EXPORT_TTL", 604_800)?, // 7 days
+            idempotency_key_ttl: parsed_or("IDEMPOTENCY_KEY_TTL", 86_400)?, // 24 hours
+            exchange_rates_url: optional_url("EXCHANGE_RATES_URL")?,
+            exchange_rates_interval: parsed_or("EXCHANGE_RATES_INTERVAL", 21_600)?, // 6 hours
+            storage_backend: parsed_or("STORAGE_BACKEND", "local".to_string())?,
+            storage_path: parsed_or("STORAGE_PATH", "./storage".to_string())?,
+            s3_endpoint: optional("S3_ENDPOINT"),
+            s3_bucket: optional("S3_BUCKET"),
+            s3_region: parsed_or("S3_REGION", "us-east-1".to_string())?,
+            s3_access_key_id: optional("S3_ACCESS_KEY_ID"),
+            s3_secret_access_key: optional("S3_SECRET_ACCESS_KEY"),
+            s3_path_style: flag("S3_PATH_STYLE", false)?,
+            json_body_max_bytes: parsed_or("JSON_BODY_MAX_BYTES", 1024 * 1024)?,
+            document_max_bytes: parsed_or("DOCUMENT_MAX_BYTES", 10 * 1024 * 1024)?,
+            avatar_max_bytes: parsed_or("AVATAR_MAX_BYTES", 2 * 1024 * 1024)?,
+            url_signing_secret: optional("URL_SIGNING_SECRET"),
+            signed_url_ttl: parsed_or("SIGNED_URL_TTL", 86_400)?, // 1 day
+            clamav_address: optional("CLAMAV_ADDRESS"),
+            mail_backend: parsed_or("MAIL_BACKEND", "log".to_string())?,
+            mail_from: parsed_or("MAIL_FROM", "Airlines API <no-reply@localhost>".to_string())?,
+            smtp_host: optional("SMTP_HOST"),
+            smtp_port: parsed_or("SMTP_PORT", 587)?,
+            smtp_security: parsed_or("SMTP_SECURITY", "starttls".to_string())?,
+            smtp_username: optional("SMTP_USERNAME"),
+            smtp_password: optional("SMTP_PASSWORD"),
+            ses_endpoint: optional("SES_ENDPOINT"),
+            ses_region: parsed_or("SES_REGION", "us-east-1".to_string())?,
+            ses_access_key_id: optional("SES_ACCESS_KEY_ID"),
+            ses_secret_access_key: optional("SES_SECRET_ACCESS_KEY"),
+            redis_url: optional("REDIS_URL"),
+            cache_ttl: parsed_or("CACHE_TTL", 300)?,
+            cache_search_ttl: parsed_or("CACHE_SEARCH_TTL", 30)?,
+            event_bus: parsed_or("EVENT_BUS", "none".to_string())?,
+            event_bus_url: optional("EVENT_BUS_URL"),
+            event_bus_topic_prefix: parsed_or("EVENT_BUS_TOPIC_PREFIX", "airlines".to_string())?,
+            shutdown_grace_period: parsed_or("SHUTDOWN_GRACE_PERIOD", 30)?,
+            compress_responses: flag("COMPRESS_RESPONSES", true)?,
+            compression_min_bytes: parsed_or("COMPRESSION_MIN_BYTES", 1024)?,
+            decompress_requests: flag("DECOMPRESS_REQUESTS", true)?,
+            // Serve the Node.js API's camelCase format unless a request asks otherwise
+            legacy_responses: flag("LEGACY_RESPONSES", false)?,
+            error_reporting_dsn: optional("ERROR_REPORTING_DSN"),
+            error_reporting_sample_rate: parsed_or("ERROR_REPORTING_SAMPLE_RATE", 1.0)?,
+            error_reporting_environment: parsed_or(
+                "ERROR_REPORTING_ENVIRONMENT",
+                "production".to_string(),
+            )?,
         })
     }
 }
+
+// Secrets are left out so the configuration can be logged at startup
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("database_url", &"<redacted>")
+            .field("skip_migrations", &self.skip_migrations)
+            .field("bind_host", &self.bind_host)
+            .field("server_port", &self.server_port)
+            .field("tls_cert_path", &self.tls_cert_path)
+            .field("tls_key_path", &self.tls_key_path)
+            .field("http_redirect_port", &self.http_redirect_port)
+            .field("hsts_max_age", &self.hsts_max_age)
+            .field("cors_allowed_origins", &self.cors_allowed_origins)
+            .field("cors_allowed_methods", &self.cors_allowed_methods)
+            .field("cors_allowed_headers", &self.cors_allowed_headers)
+            .field("cors_max_age", &self.cors_max_age)
+            .field("jwt_algorithm", &self.jwt_algorithm)
+            .field(
+                "jwt_secret",
+                &self.jwt_secret.as_ref().map(|_| "<redacted>"),
+            )
+            .field("jwt_private_key_path", &self.jwt_private_key_path)
+            .field("jwt_public_key_path", &self.jwt_public_key_path)
+            .field("jwt_key_id", &self.jwt_key_id)
+            .field("jwt_previous_public_keys", &self.jwt_previous_public_keys)
+            .field("jwt_expiration", &self.jwt_expiration)
+            .field("auth_cache_ttl", &self.auth_cache_ttl)
+            .field("refresh_token_expiration", &self.refresh_token_expiration)
+            .field("password_reset_expiration", &self.password_reset_expiration)
+            .field("password_min_length", &self.password_min_length)
+            .field(
+                "password_min_character_classes",
+                &self.password_min_character_classes,
+            )
+            .field("otp_expiration", &self.otp_expiration)
+            .field("totp_issuer", &self.totp_issuer)
+            .field("require_admin_2fa", &self.require_admin_2fa)
+            .field("pricing_strategy", &self.pricing_strategy)
+            .field("crew_turnaround_minutes", &self.crew_turnaround_minutes)
+            .field(
+                "crew_max_duty_hours_per_day",
+                &self.crew_max_duty_hours_per_day,
+            )
+            .field(
+                "crew_max_duty_hours_per_week",
+                &self.crew_max_duty_hours_per_week,
+            )
+            .field("crew_min_rest_hours", &self.crew_min_rest_hours)
+            .field("gate_occupancy_minutes", &self.gate_occupancy_minutes)
+            .field(
+                "meal_selection_cutoff_hours",
+                &self.meal_selection_cutoff_hours,
+            )
+            .field("pet_cabin_fee", &self.pet_cabin_fee)
+            .field("pet_hold_fee", &self.pet_hold_fee)
+            .field("pet_hold_fee_per_kg", &self.pet_hold_fee_per_kg)
+            .field("child_fare_percent", &self.child_fare_percent)
+            .field("lap_infant_fare_percent", &self.lap_infant_fare_percent)
+            .field(
+                "group_discount_min_passengers",
+                &self.group_discount_min_passengers,
+            )
+            .field("group_discount_percent", &self.group_discount_percent)
+            .field("exit_row_seat_fee", &self.exit_row_seat_fee)
+            .field("miles_accrual_interval", &self.miles_accrual_interval)
+            .field("webhook_delivery_interval", &self.webhook_delivery_interval)
+            .field("job_poll_interval", &self.job_poll_interval)
+            .field("outbox_relay_interval", &self.outbox_relay_interval)
+            .field("schedule_mark_departed", &self.schedule_mark_departed)
+            .field("schedule_flag_no_shows", &self.schedule_flag_no_shows)
+            .field(
+                "schedule_purge_reset_tokens",
+                &self.schedule_purge_reset_tokens,
+            )
+            .field("data_export_interval", &self.data_export_interval)
+            .field("data_export_ttl", &self.data_export_ttl)
+            .field("idempotency_key_ttl", &self.idempotency_key_ttl)
+            .field("exchange_rates_url", &self.exchange_rates_url)
+            .field("exchange_rates_interval", &self.exchange_rates_interval)
+            .field("storage_backend", &self.storage_backend)
+            .field("storage_path", &self.storage_path)
+            .field("s3_endpoint", &self.s3_endpoint)
+            .field("s3_bucket", &self.s3_bucket)
+            .field("s3_region", &self.s3_region)
+            .field("s3_access_key_id", &self.s3_access_key_id)
+            .field(
+                "s3_secret_access_key",
+                &self.s3_secret_access_key.as_ref().map(|_| "<redacted>"),
+            )
+            .field("s3_path_style", &self.s3_path_style)
+            .field("json_body_max_bytes", &self.json_body_max_bytes)
+            .field("document_max_bytes", &self.document_max_bytes)
+            .field("avatar_max_bytes", &self.avatar_max_bytes)
+            .field(
+                "url_signing_secret",
+                &self.url_signing_secret.as_ref().map(|_| "<redacted>"),
+            )
+            .field("signed_url_ttl", &self.signed_url_ttl)
+            .field("clamav_address", &self.clamav_address)
+            .field("mail_backend", &self.mail_backend)
+            .field("mail_from", &self.mail_from)
+            .field("smtp_host", &self.smtp_host)
+            .field("smtp_port", &self.smtp_port)
+            .field("smtp_security", &self.smtp_security)
+            .field("smtp_username", &self.smtp_username)
+            .field(
+                "smtp_password",
+                &self.smtp_password.as_ref().map(|_| "<redacted>"),
+            )
+            .field("ses_endpoint", &self.ses_endpoint)
+            .field("ses_region", &self.ses_region)
+            .field("ses_access_key_id", &self.ses_access_key_id)
+            .field(
+                "ses_secret_access_key",
+                &self.ses_secret_access_key.as_ref().map(|_| "<redacted>"),
+            )
+            // May carry a password
+            .field("redis_url", &self.redis_url.as_ref().map(|_| "<redacted>"))
+            .field("cache_ttl", &self.cache_ttl)
+            .field("cache_search_ttl", &self.cache_search_ttl)
+            .field("event_bus", &self.event_bus)
+            // May carry credentials
+            .field(
+                "event_bus_url",
+                &self.event_bus_url.as_ref().map(|_| "<redacted>"),
+            )
+            .field("event_bus_topic_prefix", &self.event_bus_topic_prefix)
+            .field("shutdown_grace_period", &self.shutdown_grace_period)
+            .field("compress_responses", &self.compress_responses)
+            .field("compression_min_bytes", &self.compression_min_bytes)
+            .field("decompress_requests", &self.decompress_requests)
+            .field("legacy_responses", &self.legacy_responses)
+            .field(
+                "error_reporting_dsn",
+                &self.error_reporting_dsn.as_ref().map(|_| "<redacted>"),
+            )
+            .field(
+                "error_reporting_sample_rate",
+                &self.error_reporting_sample_rate,
+            )
+            .field(
+                "error_reporting_environment",
+                &self.error_reporting_environment,
+            )
+            .finish()
+    }
+}
@@ -0,0 +1,208 @@
+// Cron expressions for the scheduled tasks: `minute hour day-of-month month day-of-week` in UTC,
+// each field `*`, a number, a range `a-b`, a step `*/n` or `a-b/n`, or a comma list of those.
+// Sunday is 0 or 7. As in cron, a day matches when either day field matches if both are set.
+// `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are accepted too
+use std::{fmt, str::FromStr};
+
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+#[error("invalid cron expression `{expression}`: {reason}")]
+pub struct CronError {
+    expression: String,
+    reason: String,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Schedule {
+    source: String,
+    // Bit n set when value n matches
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    // Whether the day fields were `*`, which decides how they combine
+    any_day: bool,
+    any_weekday: bool,
+}
+
+// The bits of one field, with whether it was `*`
+fn field(text: &str, min: u32, max: u32) -> Result<(u64, bool), String> {
+    let mut bits = 0u64;
+    for part in text.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step.parse().map_err(|_| format!("bad step `{}`", part))?;
+                if step == 0 {
+                    return Err(format!("bad step `{}`", part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            let start = start.parse().map_err(|_| format!("bad range `{}`", part))?;
+            let end = end.parse().map_err(|_| format!("bad range `{}`", part))?;
+            (start, end)
+        } else {
+            let value = range.parse().map_err(|_| format!("bad value `{}`", part))?;
+            // `5/15` means from 5 to the end in steps of 15
+            (value, if step > 1 { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("`{}` is outside {}-{}", part, min, max));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok((bits, text == "*"))
+}
+
+impl FromStr for Schedule {
+    type Err = CronError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let error = |reason: String| CronError {
+            expression: expression.to_string(),
+            reason,
+        };
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(error(format!("expected 5 fields, got {}", fields.len())));
+        };
+
+        let (minutes, _) = field(minutes, 0, 59).map_err(error)?;
+        let (hours, _) = field(hours, 0, 23).map_err(error)?;
+        let (days, any_day) = field(days, 1, 31).map_err(error)?;
+        let (months, _) = field(months, 1, 12).map_err(error)?;
+        let (weekdays, any_weekday) = field(weekdays, 0, 7).map_err(error)?;
+        // Sunday as 7 is Sunday as 0
+        let weekdays = (weekdays | (weekdays >> 7)) & 0x7f;
+
+        Ok(Self {
+            source: expression.trim().to_string(),
+            minutes,
+            hours: hours as u32,
+            days: days as u32,
+            months: months as u16,
+            weekdays: weekdays as u8,
+            any_day,
+            any_weekday,
+        })
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl fmt::Debug for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Schedule({:?})", self.source)
+    }
+}
+
+impl Schedule {
+    fn day_matches(&self, time: DateTime<Utc>) -> bool {
+        let day = self.days & (1 << time.day()) != 0;
+        let weekday = self.weekdays & (1 << time.weekday().num_days_from_sunday()) != 0;
+        let month = self.months & (1 << time.month()) != 0;
+        month
+            && match (self.any_day, self.any_weekday) {
+                (true, true) => true,
+                (false, true) => day,
+                (true, false) => weekday,
+                (false, false) => day || weekday,
+            }
+    }
+
+    // The first matching minute after `after`, or None if there is none within five years
+    // (e.g. `0 0 31 2 *`)
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.duration_trunc(Duration::minutes(1)).ok()? + Duration::minutes(1);
+        let limit = after + Duration::days(5 * 366);
+        while time < limit {
+            if !self.day_matches(time) {
+                let midnight = time.duration_trunc(Duration::days(1)).ok()?;
+                time = midnight + Duration::days(1);
+            } else if self.hours & (1 << time.hour()) == 0 {
+                let hour = time.duration_trunc(Duration::hours(1)).ok()?;
+                time = hour + Duration::hours(1);
+            } else if self.minutes & (1 << time.minute()) == 0 {
+                time += Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, mo: u32, d: u32, h: u32, mi: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap()
+    }
+
+    #[test]
+    fn finds_the_next_run() {
+        let every_five: Schedule = "*/5 * * * *".parse().unwrap();
+        assert_eq!(
+            every_five.next_after(at(2026, 3, 1, 10, 2)),
+            Some(at(2026, 3, 1, 10, 5))
+        );
+        assert_eq!(
+            every_five.next_after(at(2026, 3, 1, 10, 5)),
+            Some(at(2026, 3, 1, 10, 10))
+        );
+
+        // 2026-03-01 is a Sunday; weekdays 1-5 at 03:30
+        let weekdays: Schedule = "30 3 * * 1-5".parse().unwrap();
+        assert_eq!(
+            weekdays.next_after(at(2026, 2, 28, 12, 0)),
+            Some(at(2026, 3, 2, 3, 30))
+        );
+
+        // Either day field matches when both are set: the 15th or a Sunday (as 7)
+        let either: Schedule = "0 0 15 * 7".parse().unwrap();
+        assert_eq!(
+            either.next_after(at(2026, 3, 2, 0, 0)),
+            Some(at(2026, 3, 8, 0, 0))
+        );
+
+        let daily: Schedule = "@daily".parse().unwrap();
+        assert_eq!(
+            daily.next_after(at(2026, 12, 31, 23, 59)),
+            Some(at(2027, 1, 1, 0, 0))
+        );
+        assert_eq!(
+            "0 0 31 2 *"
+                .parse::<Schedule>()
+                .unwrap()
+                .next_after(at(2026, 1, 1, 0, 0)),
+            None
+        );
+
+        assert!("* * *".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+    }
+}
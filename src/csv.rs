@@ -0,0 +1,145 @@
+// Minimal RFC 4180 reader and writer for spreadsheets: comma separated, fields optionally in
+// double quotes with `""` for a literal quote, CRLF or LF line endings. Blank lines are skipped
+// and a leading UTF-8 byte order mark (added by Excel) is ignored
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum CsvError {
+    #[error("Unterminated quoted field starting in record {0}")]
+    UnterminatedQuote(usize),
+    #[error("Unexpected character after closing quote in record {0}")]
+    TextAfterQuote(usize),
+}
+
+// Records in file order, the header included
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>, CsvError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut chars = text.chars().peekable();
+    // Whether the current record has any content, to skip blank lines
+    let mut started = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if field.is_empty() => {
+                started = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => field.push(c),
+                        None => return Err(CsvError::UnterminatedQuote(records.len() + 1)),
+                    }
+                }
+                if !matches!(chars.peek(), None | Some(',' | '\r' | '\n')) {
+                    return Err(CsvError::TextAfterQuote(records.len() + 1));
+                }
+            }
+            ',' => {
+                started = true;
+                record.push(std::mem::take(&mut field));
+            }
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                if started {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                }
+                started = false;
+            }
+            c => {
+                started = true;
+                field.push(c);
+            }
+        }
+    }
+    if started {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+// Spreadsheets run cells starting with these as formulas
+fn is_formula(field: &str) -> bool {
+    let Some(first) = field.chars().next() else {
+        return false;
+    };
+    match first {
+        '=' | '@' | '\t' | '\r' => true,
+        // Signed numbers and phone numbers (+380 50 123 4567) are data
+        '+' | '-' => !field[1..]
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == ' ' || c == '.'),
+        _ => false,
+    }
+}
+
+// One CSV line, CRLF terminated. Fields are quoted when needed, and text that a spreadsheet
+// would evaluate as a formula is prefixed with an apostrophe
+pub fn record<S: AsRef<str>>(fields: &[S]) -> String {
+    let mut line = String::new();
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            line.push(',');
+        }
+        let field = field.as_ref();
+        let field = if is_formula(field) {
+            format!("'{}", field)
+        } else {
+            field.to_string()
+        };
+        if field.contains([',', '"', '\r', '\n']) {
+            line.push('"');
+            line.push_str(&field.replace('"', "\"\""));
+            line.push('"');
+        } else {
+            line.push_str(&field);
+        }
+    }
+    line.push_str("\r\n");
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_quoted_fields_and_line_endings() {
+        let text = "\u{feff}name,note\r\n\"Doe, Jane\",\"said \"\"hi\"\"\nthen left\"\r\n\nx,\n";
+        assert_eq!(
+            parse(text).unwrap(),
+            [
+                vec!["name", "note"],
+                vec!["Doe, Jane", "said \"hi\"\nthen left"],
+                vec!["x", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_records_that_read_back() {
+        let line = record(&["Doe, Jane", "say \"hi\"", "+380 50 123", "=1+1", ""]);
+        assert_eq!(
+            line,
+            "\"Doe, Jane\",\"say \"\"hi\"\"\",+380 50 123,'=1+1,\r\n"
+        );
+        assert_eq!(
+            parse(&line).unwrap(),
+            [vec!["Doe, Jane", "say \"hi\"", "+380 50 123", "'=1+1", ""]]
+        );
+    }
+
+    #[test]
+    fn rejects_broken_quotes() {
+        assert_eq!(
+            parse("a\n\"open").unwrap_err(),
+            CsvError::UnterminatedQuote(2)
+        );
+        assert_eq!(parse("\"a\"b,c").unwrap_err(), CsvError::TextAfterQuote(1));
+    }
+}
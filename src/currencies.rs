@@ -0,0 +1,126 @@
+// Prices are stored in the currency of their fare class and can be shown in another one on
+// request (`?currency=USD`), converted with the stored exchange rates. Rates are quoted as units
+// per euro, the way the European Central Bank publishes them, and refreshed by
+// `jobs::exchange_rates`
+use std::collections::HashMap;
+
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::models::ExchangeRate;
+
+// The currency rates are quoted against, and of prices configured without one
+pub const BASE_CURRENCY: &str = "EUR";
+
+// ISO 4217 shape: three capital letters
+pub fn is_currency_code(code: &str) -> bool {
+    code.len() == 3 && code.chars().all(|c| c.is_ascii_uppercase())
+}
+
+// The rates known at one moment, loaded once per request
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeRates {
+    per_euro: HashMap<String, f64>,
+}
+
+impl ExchangeRates {
+    pub fn new(rates: impl IntoIterator<Item = (String, f64)>) -> Self {
+        Self {
+            per_euro: rates
+                .into_iter()
+                .filter(|(_, rate)| rate.is_finite() && *rate > 0.0)
+                .collect(),
+        }
+    }
+
+    pub async fn load(pool: &DbPool) -> Result<Self, sqlx::Error> {
+        let rates = ExchangeRate::find_all(pool).await?;
+        Ok(Self::new(
+            rates.into_iter().map(|rate| (rate.currency, rate.per_euro)),
+        ))
+    }
+
+    fn per_euro(&self, currency: &str) -> Option<f64> {
+        if currency == BASE_CURRENCY {
+            return Some(1.0);
+        }
+        self.per_euro.get(currency).copied()
+    }
+
+    // `amount` of `from` in `to`, rounded to cents; None if either rate is unknown
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        if from == to {
+            return Some(amount);
+        }
+        let converted = amount / self.per_euro(from)? * self.per_euro(to)?;
+        Some((converted * 100.0).round() / 100.0)
+    }
+
+    // The currency a client asked for, normalized, if prices can be converted into it
+    pub fn requested(&self, currency: &str) -> Result<String, AppError> {
+        let currency = currency.trim().to_uppercase();
+        if !is_currency_code(&currency) || self.per_euro(&currency).is_none() {
+            return Err(AppError::ValidationError(
+                ErrorCode::UnsupportedCurrency,
+                format!("No exchange rate is known for currency {}", currency),
+            ));
+        }
+        Ok(currency)
+    }
+}
+
+// Rates from the ECB's daily reference feed (eurofxref-daily.xml), whose entries look like
+// `<Cube currency='USD' rate='1.0813'/>`; entries that do not parse are skipped
+pub fn parse_ecb_rates(xml: &str) -> Vec<(String, f64)> {
+    let attribute = |element: &str, name: &str| -> Option<String> {
+        let start = element.find(&format!("{}=", name))? + name.len() + 1;
+        let quote = element[start..].chars().next()?;
+        let value = &element[start + 1..];
+        Some(value[..value.find(quote)?].to_string())
+    };
+
+    xml.split('<')
+        .filter(|element| element.starts_with("Cube "))
+        .filter_map(|element| {
+            let currency = attribute(element, "currency")?;
+            let rate = attribute(element, "rate")?.parse().ok()?;
+            is_currency_code(&currency).then_some((currency, rate))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_through_the_euro() {
+        let rates = ExchangeRates::new([("USD".to_string(), 1.10), ("UAH".to_string(), 44.0)]);
+        assert_eq!(rates.convert(100.0, "EUR", "USD"), Some(110.0));
+        assert_eq!(rates.convert(110.0, "USD", "EUR"), Some(100.0));
+        assert_eq!(rates.convert(110.0, "USD", "UAH"), Some(4400.0));
+        assert_eq!(rates.convert(99.99, "UAH", "UAH"), Some(99.99));
+        assert_eq!(rates.convert(1.0, "EUR", "GBP"), None);
+
+        assert_eq!(rates.requested(" usd ").unwrap(), "USD");
+        assert!(rates.requested("GBP").is_err());
+        assert!(rates.requested("dollars").is_err());
+    }
+
+    #[test]
+    fn parses_the_ecb_feed() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<gesmes:Envelope xmlns:gesmes="http://www.gesmes.org/xml/2002-08-01">
+  <Cube>
+    <Cube time='2026-10-14'>
+      <Cube currency='USD' rate='1.0813'/>
+      <Cube currency="JPY" rate="162.41"/>
+      <Cube currency='XXX' rate='n/a'/>
+    </Cube>
+  </Cube>
+</gesmes:Envelope>"#;
+        assert_eq!(
+            parse_ecb_rates(xml),
+            [("USD".to_string(), 1.0813), ("JPY".to_string(), 162.41)]
+        );
+    }
+}
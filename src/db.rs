@@ -1,10 +1,43 @@
-use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
+use sqlx::migrate::Migrator;
+use sqlx::pool::PoolOptions;
+use sqlx::Database;
 use std::time::Duration;
 
-pub async fn create_pool(database_url: &str) -> Result<MySqlPool, sqlx::Error> {
-    MySqlPoolOptions::new()
+// The server runs on MySQL. Unit tests run the same model code against an in-memory SQLite
+// database instead (see `test_db`), so the rest of the crate names the backend through these
+#[cfg(not(test))]
+pub type Db = sqlx::MySql;
+#[cfg(test)]
+pub type Db = sqlx::Sqlite;
+
+pub type DbPool = sqlx::Pool<Db>;
+pub type DbConnection = <Db as Database>::Connection;
+
+// SQL files under migrations/, compiled into the binary
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+// Id generated by the last INSERT; MySQL and SQLite name it differently
+#[cfg(not(test))]
+pub fn last_insert_id(result: &<Db as Database>::QueryResult) -> i64 {
+    result.last_insert_id() as i64
+}
+#[cfg(test)]
+pub fn last_insert_id(result: &<Db as Database>::QueryResult) -> i64 {
+    result.last_insert_rowid()
+}
+
+// Whether the error is a duplicate of the unique key `key`. MySQL only names the key in the
+// message ("Duplicate entry '...' for key 'tickets.uq_tickets_seat'")
+pub fn is_duplicate_key(error: &sqlx::Error, key: &str) -> bool {
+    error
+        .as_database_error()
+        .is_some_and(|db| db.is_unique_violation() && db.message().contains(key))
+}
+
+pub async fn create_pool(database_url: &str) -> Result<DbPool, sqlx::Error> {
+    PoolOptions::<Db>::new()
         .max_connections(10)
         .acquire_timeout(Duration::from_secs(10))
         .connect(database_url)
         .await
-}
\ No newline at end of file
+}
@@ -0,0 +1,250 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use thiserror::Error;
+use tracing::error;
+
+use crate::i18n::Locale;
+
+// Declares the error code enum together with the catalog served at /api/meta/error-codes
+macro_rules! error_codes {
+    ($($variant:ident => $description:literal,)*) => {
+        // Machine-readable error identifiers, serialized as SCREAMING_SNAKE_CASE
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+        #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+        pub enum ErrorCode {
+            $($variant,)*
+        }
+
+        impl ErrorCode {
+            pub const ALL: &'static [ErrorCode] = &[$(ErrorCode::$variant,)*];
+
+            pub fn description(&self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $description,)*
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    ValidationFailed => "The request body or parameters are invalid",
+    InvalidCursor => "The pagination cursor is malformed or belongs to another listing",
+    UnsupportedCurrency => "No exchange rate is known for the requested currency",
+    WeakPassword => "The password does not meet the password policy",
+    Unauthorized => "Authentication is required",
+    InvalidToken => "The bearer token is invalid or expired",
+    TokenRevoked => "The bearer token was revoked by logout or a password change",
+    InvalidApiKey => "The API key is unknown or revoked",
+    InvalidCredentials => "Email or password is wrong",
+    TwoFactorRequired => "The account has two-factor authentication enabled, a code is required",
+    InvalidTwoFactorCode => "The two-factor or backup code is wrong or was already used",
+    TwoFactorLocked => "Two-factor codes are refused for a while after too many wrong ones",
+    TwoFactorEnrollmentRequired => "Administrators must enable two-factor authentication and log in with it",
+    InvalidRefreshToken => "The refresh token is unknown, expired or revoked",
+    InvalidOtp => "The one-time code is wrong, expired or used up",
+    InvalidResetToken => "The password reset token is unknown, expired or already used",
+    RefreshTokenReused => "An already rotated refresh token was presented; its session is revoked",
+    Forbidden => "The caller's role does not allow this action",
+    MissingScope => "The API key lacks the scope needed for this endpoint",
+    WrongStaffPosition => "The caller's staff position does not allow this action",
+    InvalidSignature => "The signed link is invalid or has expired",
+    NotOwner => "The resource belongs to another user",
+    NotACompanion => "A passenger has not allowed the caller to book for them",
+    RouteNotFound => "No route with this id",
+    FlightNotFound => "No flight with this id",
+    TicketNotFound => "No ticket with this id for the caller",
+    UserNotFound => "No user with this id",
+    CompanionNotFound => "The user has not allowed this account holder to book for them",
+    SeatBlockNotFound => "No such seat block on the flight",
+    ContentNotFound => "No display content under this key or locale",
+    StatusLinkNotFound => "The status link does not exist or has expired",
+    TwoFactorNotEnrolled => "No matching two-factor enrollment for the account",
+    ApiKeyNotFound => "No active API key with this id",
+    WebhookNotFound => "No active webhook with this id",
+    SessionNotFound => "No active session with this id for the caller",
+    AvatarNotFound => "No profile photo under this link",
+    DocumentNotFound => "No document with this id for the user",
+    DataExportNotFound => "No data export with this token for the caller, or it has expired",
+    PromoCodeNotFound => "No promo code with this code or id",
+    AircraftNotFound => "No aircraft with this id",
+    MaintenanceRecordNotFound => "No maintenance record with this id on the aircraft",
+    CabinLayoutNotFound => "No cabin layout with this id",
+    GateNotFound => "No gate with this id",
+    BagNotFound => "No checked bag with this tag number",
+    ShipmentNotFound => "No cargo shipment with this air waybill number",
+    AssistanceRequestNotFound => "No assistance request with this id on the ticket",
+    BookingGroupNotFound => "No booking group with this reference",
+    AncillaryNotFound => "No ancillary product with this id",
+    BaggageAllowanceNotFound => "No baggage allowance is set for the ticket's fare class and route",
+    InvalidStatusTransition => "The flight cannot move from its current status to the requested one",
+    InvalidBaggageScan => "The bag cannot move from its current status to the scanned one",
+    InvalidCargoStatus => "The shipment cannot move from its current status to the requested one",
+    CargoRouteMismatch => "The flight does not fly between the shipment's airports",
+    ConcurrentModification => "The resource was changed by another request, retry",
+    PreconditionFailed => "The resource changed since the version named in If-Match",
+    IdempotencyKeyInProgress => "A request with this Idempotency-Key is still being processed",
+    IdempotencyKeyReused => "The Idempotency-Key was already used for a different request",
+    CrewIncomplete => "The crew assigned to the flight is below the aircraft's minimum",
+    UnknownCrewMember => "A referenced crew member does not exist",
+    CrewScheduleConflict => "A crew member is already assigned to an overlapping flight",
+    CrewDutyLimitExceeded => "The flight would take a crew member past the duty-time or rest limits",
+    FlightClosed => "The flight has departed, arrived or was cancelled",
+    AircraftGrounded => "The aircraft is grounded for maintenance during the flight",
+    SeatAlreadyTaken => "The seat is already booked or blocked",
+    UnknownSeat => "The seat is not on the flight's seat map",
+    SeatNotInClass => "The seat belongs to another fare class than the one booked",
+    ClassSoldOut => "The fare class has fewer seats left than passengers to seat",
+    PassengerAlreadyBooked => "The passenger already holds a ticket on the flight",
+    GateOccupied => "Another flight holds the gate during this flight's boarding",
+    GateExists => "The airport already has a gate with this code",
+    AssistanceAlreadyRequested => "The ticket already has a request for this kind of assistance",
+    MealNotOffered => "The meal is not catered on the ticket's flight",
+    MealSelectionClosed => "Meals can no longer be chosen for the flight",
+    CargoPayloadExceeded => "The shipment would take the flight past its aircraft's cargo limits",
+    PetNotAccepted => "The aircraft does not carry this pet where it was booked",
+    PetCapacityReached => "The flight carries no more pets in this placement",
+    PetAlreadyBooked => "The ticket already has a pet",
+    LapInfantNotAllowed => "Only an adult ticket holder can carry an infant on their lap",
+    NotAnInfant => "The infant would be 2 or older on the departure date",
+    InfantAlreadyBooked => "The ticket already has an infant on the lap",
+    PassengerAgeMismatch => "A passenger's age on the departure date does not match their ticket",
+    UpgradeNotOffered => "The flight sells no such class above the ticket's",
+    AncillaryNotOffered => "The ancillary product is not sold on the ticket's flight",
+    AncillarySoldOut => "Every unit of the ancillary product the flight sells was bought",
+    AncillaryAlreadyPurchased => "The ticket already has this ancillary product",
+    AncillaryCodeExists => "An ancillary product with this code already exists",
+    AssistanceNotAcknowledged => "Staff have not acknowledged every assistance request of the ticket",
+    CapacityExceeded => "The requested seats exceed the aircraft capacity",
+    ClassOversold => "A fare class cannot shrink below the seats already sold",
+    TwoFactorAlreadyEnabled => "Two-factor authentication is already enabled for the account",
+    PromoCodeExists => "A promo code with this code already exists",
+    PromoCodeNotYetValid => "The promo code validity window has not started",
+    PromoCodeExpired => "The promo code validity window has ended",
+    PromoCodeExhausted => "The promo code usage limit is reached",
+    InsufficientMiles => "The miles balance is too low",
+    RedemptionClosed => "Miles can no longer be redeemed for this ticket",
+    UnsupportedFileType => "The file is not one of the accepted types",
+    FileTooLarge => "The file exceeds the upload size limit",
+    FileRejected => "The file failed the malware scan",
+    PayloadTooLarge => "The request body exceeds the endpoint's size limit",
+    UnsupportedMediaType => "The request body's Content-Type is not accepted by the endpoint",
+    TooManyRequests => "Too many requests, retry later",
+    DatabaseError => "The database failed to process the request",
+    InternalError => "Unexpected server error",
+}
+
+// A broken rule of one request body field, listed under `fields` in the error envelope
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldError {
+    // Path of the field, e.g. `email` or `classes[1].price`
+    pub field: String,
+    // The rule, e.g. `email`, `length` or `range`
+    pub code: String,
+    pub message: String,
+}
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("{1}")]
+    ValidationError(ErrorCode, String),
+    #[error("Invalid fields: {}", .0.iter().map(|e| e.field.as_str()).collect::<Vec<_>>().join(", "))]
+    InvalidFields(Vec<FieldError>),
+    #[error("{1}")]
+    AuthError(ErrorCode, String),
+    #[error("{1}")]
+    Forbidden(ErrorCode, String),
+    #[error("{1}")]
+    NotFound(ErrorCode, String),
+    #[error("{1}")]
+    ConflictError(ErrorCode, String),
+    #[error("{1}")]
+    PreconditionFailed(ErrorCode, String),
+    #[error("{1}")]
+    PayloadTooLarge(ErrorCode, String),
+    #[error("{1}")]
+    UnsupportedMediaType(ErrorCode, String),
+    #[error("{1}")]
+    RateLimited(ErrorCode, String),
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+    #[error("{0}")]
+    InternalError(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::ValidationError(code, _)
+            | AppError::AuthError(code, _)
+            | AppError::Forbidden(code, _)
+            | AppError::NotFound(code, _)
+            | AppError::ConflictError(code, _)
+            | AppError::PreconditionFailed(code, _)
+            | AppError::PayloadTooLarge(code, _)
+            | AppError::UnsupportedMediaType(code, _)
+            | AppError::RateLimited(code, _) => *code,
+            AppError::InvalidFields(_) => ErrorCode::ValidationFailed,
+            AppError::DatabaseError(_) => ErrorCode::DatabaseError,
+            AppError::InternalError(_) => ErrorCode::InternalError,
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::ValidationError(..) | AppError::InvalidFields(_) => StatusCode::BAD_REQUEST,
+            AppError::AuthError(..) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(..) => StatusCode::FORBIDDEN,
+            AppError::NotFound(..) => StatusCode::NOT_FOUND,
+            AppError::ConflictError(..) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(..) => StatusCode::PRECONDITION_FAILED,
+            AppError::PayloadTooLarge(..) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::UnsupportedMediaType(..) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::RateLimited(..) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::DatabaseError(_) | AppError::InternalError(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        if status.is_server_error() {
+            error!("{}", self);
+            crate::error_reporting::capture(&self);
+        }
+
+        // Handlers word their messages in English; in other languages the code's meaning from
+        // the catalog is sent instead
+        let locale = crate::middleware::locale::current();
+        let message = match locale {
+            Locale::En => self.to_string(),
+            _ => locale.describe(self.code()).to_string(),
+        };
+        let mut body = serde_json::json!({
+            "success": false,
+            "error": message,
+            "error_code": self.code()
+        });
+        if let AppError::InvalidFields(mut fields) = self {
+            for field in &mut fields {
+                if let Some(message) = locale.rule_message(&field.code) {
+                    field.message = message.to_string();
+                }
+            }
+            body["fields"] = serde_json::json!(fields);
+        }
+        // Quoted by users in bug reports to find the request's log lines
+        if let Some(request_id) = crate::middleware::request_id::current() {
+            body["request_id"] = request_id.into();
+        }
+
+        (status, Json(body)).into_response()
+    }
+}
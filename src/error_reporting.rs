@@ -0,0 +1,121 @@
+// Sends server errors to a Sentry-compatible error tracker through its store endpoint,
+// so they are grouped and alerted on instead of only appearing in the logs
+use std::{sync::OnceLock, time::Duration};
+
+use chrono::Utc;
+use serde_json::json;
+use tracing::warn;
+use url::Url;
+use uuid::Uuid;
+
+use crate::config::{Config, ConfigError};
+use crate::error::AppError;
+use crate::http_client;
+use crate::middleware::request_id;
+
+const REPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Reporter {
+    store_url: Url,
+    auth_header: String,
+    sample_rate: f64,
+    environment: String,
+}
+
+static REPORTER: OnceLock<Reporter> = OnceLock::new();
+
+// `https://<public key>@<host>[/<path>]/<project id>` becomes the store url and auth header
+fn parse_dsn(dsn: &str) -> Option<(Url, String)> {
+    let parsed = http_client::validate_url(dsn).ok()?;
+    let key = parsed.username();
+    let (prefix, project) = parsed.path().trim_end_matches('/').rsplit_once('/')?;
+    if key.is_empty() || project.is_empty() {
+        return None;
+    }
+
+    let mut store_url = parsed.clone();
+    store_url.set_username("").ok()?;
+    store_url.set_password(None).ok()?;
+    store_url.set_path(&format!("{}/api/{}/store/", prefix, project));
+
+    let auth_header = format!(
+        "Sentry sentry_version=7, sentry_client=airlines-api/{}, sentry_key={}",
+        env!("CARGO_PKG_VERSION"),
+        key
+    );
+    Some((store_url, auth_header))
+}
+
+// Enable reporting when ERROR_REPORTING_DSN is set; returns whether it was enabled
+pub fn init(config: &Config) -> Result<bool, ConfigError> {
+    let Some(dsn) = &config.error_reporting_dsn else {
+        return Ok(false);
+    };
+    let (store_url, auth_header) = parse_dsn(dsn).ok_or(ConfigError::Invalid {
+        name: "ERROR_REPORTING_DSN",
+        value: "<redacted>".to_string(),
+    })?;
+    if !(0.0..=1.0).contains(&config.error_reporting_sample_rate) {
+        return Err(ConfigError::Invalid {
+            name: "ERROR_REPORTING_SAMPLE_RATE",
+            value: config.error_reporting_sample_rate.to_string(),
+        });
+    }
+
+    let _ = REPORTER.set(Reporter {
+        store_url,
+        auth_header,
+        sample_rate: config.error_reporting_sample_rate,
+        environment: config.error_reporting_environment.clone(),
+    });
+    Ok(true)
+}
+
+// Report a database or internal error together with the request it happened in.
+// Sending happens in the background; a failure to report is only logged
+pub fn capture(error: &AppError) {
+    let Some(reporter) = REPORTER.get() else {
+        return;
+    };
+    let kind = match error {
+        AppError::DatabaseError(_) => "DatabaseError",
+        AppError::InternalError(_) => "InternalError",
+        _ => return,
+    };
+    if rand::random::<f64>() >= reporter.sample_rate {
+        return;
+    }
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+
+    let message = error.to_string();
+    let mut event = json!({
+        "event_id": Uuid::new_v4().simple().to_string(),
+        "timestamp": Utc::now().to_rfc3339(),
+        "level": "error",
+        "platform": "other",
+        "logger": "airlines-api",
+        "release": env!("CARGO_PKG_VERSION"),
+        "environment": reporter.environment,
+        "message": { "formatted": message },
+        "exception": { "values": [{ "type": kind, "value": message }] },
+        "tags": { "error_code": error.code() },
+    });
+    if let Some(request) = request_id::context() {
+        event["tags"]["request_id"] = request.id.into();
+        event["request"] = json!({ "method": request.method, "url": request.path });
+    }
+
+    let headers = vec![("X-Sentry-Auth", reporter.auth_header.clone())];
+    let url = reporter.store_url.clone();
+    runtime.spawn(async move {
+        match http_client::post_json(url, headers, event.to_string().into_bytes(), REPORT_TIMEOUT)
+            .await
+        {
+            Ok(status) if (200..300).contains(&status) => {}
+            Ok(status) => warn!("Error tracker rejected the report with status {}", status),
+            Err(e) => warn!("Failed to report error: {}", e),
+        }
+    });
+}
@@ -0,0 +1,113 @@
+// Kafka through a Confluent REST Proxy (API v2): each message is POSTed to `/topics/<topic>` and
+// the proxy answers once the broker has acknowledged it. `user:password@` in the url becomes
+// basic auth
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use serde_json::json;
+use url::Url;
+
+use super::{EventBusError, Transport};
+use crate::http_client;
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+pub struct KafkaRestTransport {
+    base: Url,
+    authorization: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ProduceResponse {
+    #[serde(default)]
+    offsets: Vec<Offset>,
+}
+
+#[derive(Deserialize)]
+struct Offset {
+    error: Option<String>,
+}
+
+impl KafkaRestTransport {
+    pub fn new(url: Url) -> Result<Self, EventBusError> {
+        let mut base = http_client::validate_url(url.as_str())?;
+        let authorization = match (base.username(), base.password()) {
+            ("", _) => None,
+            (user, password) => {
+                let decode = |part: &str| {
+                    urlencoding::decode(part)
+                        .map(|part| part.into_owned())
+                        .map_err(|_| {
+                            EventBusError::Config("the Kafka url credentials are not UTF-8".into())
+                        })
+                };
+                let credentials = format!(
+                    "{}:{}",
+                    decode(user)?,
+                    decode(password.unwrap_or_default())?
+                );
+                Some(format!("Basic {}", STANDARD.encode(credentials)))
+            }
+        };
+        // Only the header carries the credentials
+        let _ = base.set_username("");
+        let _ = base.set_password(None);
+        Ok(Self {
+            base,
+            authorization,
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for KafkaRestTransport {
+    async fn publish(&self, topic: &str, key: &str, body: &[u8]) -> Result<(), EventBusError> {
+        let value: serde_json::Value = serde_json::from_slice(body)
+            .map_err(|e| EventBusError::Config(format!("event is not JSON: {}", e)))?;
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .map_err(|_| EventBusError::Config("EVENT_BUS_URL cannot be a base".to_string()))?
+            .pop_if_empty()
+            .extend(["topics", topic]);
+        let mut headers = vec![
+            (
+                "Content-Type".to_string(),
+                "application/vnd.kafka.json.v2+json".to_string(),
+            ),
+            (
+                "Accept".to_string(),
+                "application/vnd.kafka.v2+json".to_string(),
+            ),
+        ];
+        if let Some(authorization) = &self.authorization {
+            headers.push(("Authorization".to_string(), authorization.clone()));
+        }
+        let request = json!({ "records": [{ "key": key, "value": value }] });
+
+        let response = http_client::send(
+            "POST",
+            url,
+            headers,
+            request.to_string().into_bytes(),
+            TIMEOUT,
+        )
+        .await?;
+        let text = String::from_utf8_lossy(&response.body);
+        if !(200..300).contains(&response.status) {
+            return Err(EventBusError::Rejected(format!(
+                "{} {}",
+                response.status,
+                text.trim()
+            )));
+        }
+        // A 200 can still carry a per-record failure
+        let produced: ProduceResponse = serde_json::from_str(&text)
+            .map_err(|_| EventBusError::Rejected(format!("unexpected response {}", text.trim())))?;
+        match produced.offsets.into_iter().find_map(|offset| offset.error) {
+            Some(error) => Err(EventBusError::Rejected(error)),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// Streams the outbox's domain events to a message broker for analytics and operations, chosen by
+// EVENT_BUS: NATS (core publish, acknowledged by a PING round trip) or Kafka through a Confluent
+// REST Proxy. Each event goes to `<EVENT_BUS_TOPIC_PREFIX>.<event type>`, keyed by the record it
+// is about so a partitioned consumer sees one flight's events in order
+mod kafka;
+mod nats;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use thiserror::Error;
+use url::Url;
+
+use crate::config::Config;
+use crate::http_client::HttpError;
+use crate::models::DomainEvent;
+
+pub use kafka::KafkaRestTransport;
+pub use nats::NatsTransport;
+
+#[derive(Debug, Error)]
+pub enum EventBusError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Http(#[from] HttpError),
+    // The broker refused the message, with its reply
+    #[error("broker replied `{0}`")]
+    Rejected(String),
+    #[error("{0}")]
+    Config(String),
+}
+
+// Delivers one message to a topic (a subject on NATS)
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn publish(&self, topic: &str, key: &str, body: &[u8]) -> Result<(), EventBusError>;
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    transport: Arc<dyn Transport>,
+    prefix: String,
+}
+
+impl EventBus {
+    pub fn new(transport: Arc<dyn Transport>, prefix: String) -> Self {
+        Self { transport, prefix }
+    }
+
+    // Publish an outbox event as `{id, type, occurred_at, data}`; consumers use `id` to drop the
+    // duplicates at-least-once delivery can produce
+    pub async fn publish(
+        &self,
+        event_id: i64,
+        occurred_at: DateTime<Utc>,
+        event: &DomainEvent,
+    ) -> Result<(), EventBusError> {
+        let (aggregate_type, aggregate_id) = event.aggregate();
+        let mut body = serde_json::to_value(event).expect("domain events serialize to JSON");
+        body["id"] = json!(event_id);
+        body["occurred_at"] = json!(occurred_at);
+        let topic = format!("{}.{}", self.prefix, event.name());
+        let key = format!("{}:{}", aggregate_type, aggregate_id);
+        self.transport
+            .publish(&topic, &key, body.to_string().as_bytes())
+            .await
+    }
+}
+
+// The bus selected by the configuration, or None when events stay in the database
+pub fn from_config(config: &Config) -> Result<Option<EventBus>, EventBusError> {
+    let url = || -> Result<Url, EventBusError> {
+        let url = config
+            .event_bus_url
+            .as_deref()
+            .ok_or_else(|| EventBusError::Config("EVENT_BUS_URL must be set".to_string()))?;
+        Url::parse(url)
+            .map_err(|e| EventBusError::Config(format!("EVENT_BUS_URL is invalid: {}", e)))
+    };
+    let transport: Arc<dyn Transport> = match config.event_bus.as_str() {
+        "none" => return Ok(None),
+        "nats" => Arc::new(NatsTransport::new(&url()?)?),
+        "kafka" => Arc::new(KafkaRestTransport::new(url()?)?),
+        other => {
+            return Err(EventBusError::Config(format!(
+                "EVENT_BUS must be one of: none, nats, kafka (got {})",
+                other
+            )))
+        }
+    };
+    Ok(Some(EventBus::new(
+        transport,
+        config.event_bus_topic_prefix.clone(),
+    )))
+}
@@ -0,0 +1,379 @@
+// CSV and Excel downloads of list endpoints. The query runs on a task of its own and hands rows
+// over a small channel to the response body, which encodes each row as it arrives; a large
+// export never sits in memory and a client that hangs up stops the query
+use std::future::Future;
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tracing::error;
+
+use crate::models::{Flight, FlightLoad, FlightPunctuality, RevenueRow, Ticket, User};
+use crate::xlsx::{self, XlsxWriter};
+
+pub use crate::xlsx::Cell;
+
+// Rows buffered between the query and the response body
+const CHANNEL_SIZE: usize = 256;
+
+pub type RowSender<T> = mpsc::Sender<Result<T, sqlx::Error>>;
+
+// `?format=` of list endpoints
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    // `?format=` wins; otherwise a file is sent when the Accept header prefers one
+    pub fn negotiate(format: Option<Self>, headers: &HeaderMap) -> Self {
+        if let Some(format) = format {
+            return format;
+        }
+        let accept = headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        accept
+            .split(',')
+            .map(|media| media.split(';').next().unwrap_or_default().trim())
+            .find_map(|media| match media {
+                "application/json" => Some(ExportFormat::Json),
+                "text/csv" => Some(ExportFormat::Csv),
+                xlsx::CONTENT_TYPE => Some(ExportFormat::Xlsx),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+}
+
+// Query parameters of list endpoints that take no other ones
+#[derive(Debug, Deserialize)]
+pub struct ExportParams {
+    pub format: Option<ExportFormat>,
+}
+
+// A record as a spreadsheet row; `COLUMNS` is the header row
+pub trait ExportRow: Send + 'static {
+    const COLUMNS: &'static [&'static str];
+
+    fn cells(&self) -> Vec<Cell>;
+}
+
+fn text(value: impl ToString) -> Cell {
+    Cell::Text(value.to_string())
+}
+
+fn optional(value: Option<impl ToString>) -> Cell {
+    value.map_or(Cell::Empty, text)
+}
+
+fn timestamp(value: DateTime<Utc>) -> Cell {
+    Cell::Text(value.to_rfc3339())
+}
+
+fn date(value: Option<NaiveDate>) -> Cell {
+    optional(value.map(|date| date.format("%Y-%m-%d")))
+}
+
+impl ExportRow for User {
+    const COLUMNS: &'static [&'static str] = &[
+        "user_id",
+        "first_name",
+        "last_name",
+        "email",
+        "phone",
+        "passport_number",
+        "nationality",
+        "date_of_birth",
+        "role",
+        "staff_position",
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Number(self.user_id.into()),
+            text(&self.first_name),
+            text(&self.last_name),
+            text(&self.email),
+            optional(self.phone.as_ref()),
+            optional(self.passport_number.as_ref()),
+            optional(self.nationality.as_ref()),
+            date(self.date_of_birth),
+            text(self.role.as_str()),
+            optional(self.staff_position.map(|position| position.as_str())),
+        ]
+    }
+}
+
+impl ExportRow for Flight {
+    const COLUMNS: &'static [&'static str] = &[
+        "flight_id",
+        "flight_number",
+        "route_id",
+        "aircraft_id",
+        "departure_time",
+        "arrival_time",
+        "status",
+        "gate",
+        "marketing_flight_numbers",
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Number(self.flight_id.into()),
+            text(&self.flight_number),
+            Cell::Number(self.route_id.into()),
+            Cell::Number(self.aircraft_id.into()),
+            timestamp(self.departure_time),
+            timestamp(self.arrival_time),
+            text(self.status.as_str()),
+            optional(self.gate.as_ref()),
+            text(self.marketing_flight_numbers.0.join(" ")),
+        ]
+    }
+}
+
+impl ExportRow for Ticket {
+    const COLUMNS: &'static [&'static str] = &[
+        "ticket_id",
+        "ticket_number",
+        "user_id",
+        "flight_id",
+        "seat_number",
+        "fare_class",
+        "passenger_type",
+        "sold_as",
+        "checked_in",
+        "no_show",
+        "special_requests",
+        "meal",
+        "group_id",
+        "fare",
+        "currency",
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Number(self.ticket_id.into()),
+            text(&self.ticket_number),
+            Cell::Number(self.user_id.into()),
+            Cell::Number(self.flight_id.into()),
+            text(&self.seat_number),
+            text(self.fare_class.as_str()),
+            text(self.passenger_type.as_str()),
+            optional(self.sold_as.as_ref()),
+            text(self.checked_in),
+            text(self.no_show),
+            optional(self.special_requests.as_ref()),
+            optional(self.meal.map(|meal| meal.as_str())),
+            self.group_id
+                .map_or(Cell::Empty, |group_id| Cell::Number(group_id.into())),
+            self.fare.map_or(Cell::Empty, Cell::Number),
+            optional(self.currency.as_ref()),
+        ]
+    }
+}
+
+impl ExportRow for FlightLoad {
+    const COLUMNS: &'static [&'static str] = &[
+        "flight_id",
+        "flight_number",
+        "route_id",
+        "origin",
+        "destination",
+        "departure_time",
+        "status",
+        "capacity",
+        "seats_sold",
+        "load_factor",
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Number(self.flight_id.into()),
+            text(&self.flight_number),
+            Cell::Number(self.route_id.into()),
+            text(&self.origin),
+            text(&self.destination),
+            timestamp(self.departure_time),
+            text(self.status.as_str()),
+            Cell::Number(self.capacity.into()),
+            Cell::Number(self.seats_sold as f64),
+            Cell::Number(self.load_factor),
+        ]
+    }
+}
+
+impl ExportRow for FlightPunctuality {
+    const COLUMNS: &'static [&'static str] = &[
+        "flight_id",
+        "flight_number",
+        "route_id",
+        "origin",
+        "destination",
+        "aircraft_id",
+        "aircraft_model",
+        "departure_time",
+        "actual_departure_time",
+        "departure_delay_minutes",
+        "arrival_time",
+        "actual_arrival_time",
+        "arrival_delay_minutes",
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Number(self.flight_id.into()),
+            text(&self.flight_number),
+            Cell::Number(self.route_id.into()),
+            text(&self.origin),
+            text(&self.destination),
+            Cell::Number(self.aircraft_id.into()),
+            text(&self.aircraft_model),
+            timestamp(self.departure_time),
+            timestamp(self.actual_departure_time),
+            Cell::Number(self.departure_delay_minutes as f64),
+            timestamp(self.arrival_time),
+            self.actual_arrival_time.map_or(Cell::Empty, timestamp),
+            self.arrival_delay_minutes
+                .map_or(Cell::Empty, |minutes| Cell::Number(minutes as f64)),
+        ]
+    }
+}
+
+impl ExportRow for RevenueRow {
+    const COLUMNS: &'static [&'static str] = &[
+        "group",
+        "route_id",
+        "tickets_sold",
+        "ticket_revenue",
+        "ancillary_revenue",
+        "total",
+        "currency",
+    ];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            text(&self.group),
+            self.route_id
+                .map_or(Cell::Empty, |id| Cell::Number(id.into())),
+            Cell::Number(self.tickets_sold as f64),
+            Cell::Number(self.ticket_revenue),
+            Cell::Number(self.ancillary_revenue),
+            Cell::Number(self.total),
+            text(&self.currency),
+        ]
+    }
+}
+
+// Pass a query's rows on until they run out or the receiving response is gone
+pub async fn forward<T>(mut rows: BoxStream<'_, Result<T, sqlx::Error>>, sender: &RowSender<T>) {
+    while let Some(row) = rows.next().await {
+        let failed = row.is_err();
+        if sender.send(row).await.is_err() || failed {
+            break;
+        }
+    }
+}
+
+enum Encoder {
+    Csv,
+    Xlsx(Box<XlsxWriter>),
+}
+
+impl Encoder {
+    fn start(format: ExportFormat, sheet_name: &str, columns: &[&str]) -> (Self, Vec<u8>) {
+        match format {
+            ExportFormat::Xlsx => {
+                let (writer, bytes) = XlsxWriter::start(sheet_name, columns);
+                (Encoder::Xlsx(Box::new(writer)), bytes)
+            }
+            _ => (Encoder::Csv, crate::csv::record(columns).into_bytes()),
+        }
+    }
+
+    fn row(&mut self, cells: &[Cell]) -> Vec<u8> {
+        match self {
+            Encoder::Csv => {
+                let fields: Vec<String> = cells
+                    .iter()
+                    .map(|cell| match cell {
+                        Cell::Text(value) => value.clone(),
+                        Cell::Number(value) => value.to_string(),
+                        Cell::Empty => String::new(),
+                    })
+                    .collect();
+                crate::csv::record(&fields).into_bytes()
+            }
+            Encoder::Xlsx(writer) => writer.row(cells),
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        match self {
+            Encoder::Csv => Vec::new(),
+            Encoder::Xlsx(writer) => writer.finish(),
+        }
+    }
+}
+
+// Download of the rows `query` sends, as `<name>.csv` or `<name>.xlsx`. `query` runs on its own
+// task; a database error ends the body early, so the client sees a failed download rather than
+// a silently truncated file
+pub fn download<T, F, Fut>(format: ExportFormat, name: &str, query: F) -> Response
+where
+    T: ExportRow,
+    F: FnOnce(RowSender<T>) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel(CHANNEL_SIZE);
+    tokio::spawn(query(sender));
+
+    let (encoder, head) = Encoder::start(format, name, T::COLUMNS);
+    let rows = stream::unfold(
+        (receiver, Some(encoder)),
+        |(mut receiver, encoder)| async move {
+            let mut encoder = encoder?;
+            match receiver.recv().await {
+                Some(Ok(row)) => {
+                    let bytes = encoder.row(&row.cells());
+                    Some((Ok(bytes), (receiver, Some(encoder))))
+                }
+                Some(Err(e)) => {
+                    error!("Export query failed: {}", e);
+                    Some((Err(std::io::Error::other(e)), (receiver, None)))
+                }
+                None => Some((Ok(encoder.finish()), (receiver, None))),
+            }
+        },
+    );
+    let body = stream::once(async { Ok(head) }).chain(rows);
+
+    let (content_type, extension) = match format {
+        ExportFormat::Xlsx => (xlsx::CONTENT_TYPE, "xlsx"),
+        _ => ("text/csv; charset=utf-8", "csv"),
+    };
+    let disposition = format!("attachment; filename=\"{}.{}\"", name, extension);
+    (
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_str(&disposition).expect("export names are ASCII"),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response()
+}
@@ -0,0 +1,43 @@
+// Types of uploaded files, recognised by their first bytes rather than by what the client declares
+pub const PDF: &str = "application/pdf";
+pub const JPEG: &str = "image/jpeg";
+pub const PNG: &str = "image/png";
+
+pub fn sniff(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"%PDF-") {
+        Some(PDF)
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(JPEG)
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(PNG)
+    } else {
+        None
+    }
+}
+
+pub fn extension(content_type: &str) -> &'static str {
+    match content_type {
+        PDF => "pdf",
+        JPEG => "jpg",
+        PNG => "png",
+        _ => "bin",
+    }
+}
+
+pub fn from_extension(file_name: &str) -> Option<&'static str> {
+    match file_name.rsplit_once('.')?.1 {
+        "pdf" => Some(PDF),
+        "jpg" => Some(JPEG),
+        "png" => Some(PNG),
+        _ => None,
+    }
+}
+
+// Whether the Content-Type a multipart part declared contradicts the sniffed type; generic
+// binary or no declaration at all is fine
+pub fn contradicts(declared: Option<&str>, sniffed: &str) -> bool {
+    declared
+        .and_then(|value| value.split(';').next())
+        .map(str::trim)
+        .is_some_and(|declared| declared != sniffed && declared != "application/octet-stream")
+}
@@ -0,0 +1,375 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::response::ApiResponse;
+use super::ticket_handler::ticket_not_found;
+use crate::currencies::ExchangeRates;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{
+    AncillaryProduct, AncillaryPurchase, AuditLog, Flight, FlightAncillary, FlightAncillaryOffer,
+    FlightFareClass, FlightStatus, NewAncillaryProduct, StaffPosition, Ticket, TicketAncillary,
+};
+use crate::validation::ValidJson;
+
+// Products sold on a flight request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetFlightAncillariesRequest {
+    #[validate(nested)]
+    pub products: Vec<FlightAncillaryOffer>,
+}
+
+// Buy ancillary request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct PurchaseAncillaryRequest {
+    pub product_id: i32,
+}
+
+// What was bought for a ticket on top of its fare, in the currency of its fare class
+#[derive(Debug, Serialize)]
+pub struct TicketExtras {
+    pub ancillaries: Vec<TicketAncillary>,
+    pub ancillaries_total: f64,
+    // Fare and ancillaries together; None for tickets issued before fares were recorded
+    pub total: Option<f64>,
+    pub currency: Option<String>,
+}
+
+fn ancillary_not_found(id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::AncillaryNotFound,
+        format!("Ancillary product with id {} not found", id),
+    )
+}
+
+fn code_taken(e: sqlx::Error, code: &str) -> AppError {
+    if e.as_database_error()
+        .is_some_and(|db| db.is_unique_violation())
+    {
+        AppError::ConflictError(
+            ErrorCode::AncillaryCodeExists,
+            format!("Ancillary product {} already exists", code),
+        )
+    } else {
+        e.into()
+    }
+}
+
+// Sum of prices rounded to cents
+pub(crate) fn ancillaries_total(ancillaries: &[TicketAncillary]) -> f64 {
+    let total: f64 = ancillaries.iter().map(|ancillary| ancillary.price).sum();
+    (total * 100.0).round() / 100.0
+}
+
+// The ancillary catalog, inactive products included (admin only)
+pub async fn get_ancillaries(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+) -> Result<Json<ApiResponse<Vec<AncillaryProduct>>>, AppError> {
+    let products = AncillaryProduct::find_all(&pool).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: products,
+    }))
+}
+
+// Add a product to the ancillary catalog (admin only)
+pub async fn create_ancillary(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    ValidJson(mut payload): ValidJson<NewAncillaryProduct>,
+) -> Result<(StatusCode, Json<ApiResponse<AncillaryProduct>>), AppError> {
+    payload.code = payload.code.trim().to_uppercase();
+    let product = AncillaryProduct::create(&pool, &payload)
+        .await
+        .map_err(|e| code_taken(e, &payload.code))?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ancillary.created",
+        "ancillary_product",
+        product.product_id,
+        serde_json::json!({
+            "code": &product.code,
+            "price": product.price,
+            "currency": &product.currency,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: product,
+        }),
+    ))
+}
+
+// Replace a product of the ancillary catalog, or stop selling it with `active: false` (admin
+// only). Tickets keep what they paid
+pub async fn update_ancillary(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(id): Path<i32>,
+    ValidJson(mut payload): ValidJson<NewAncillaryProduct>,
+) -> Result<Json<ApiResponse<AncillaryProduct>>, AppError> {
+    payload.code = payload.code.trim().to_uppercase();
+    let product = AncillaryProduct::update(&pool, id, &payload)
+        .await
+        .map_err(|e| code_taken(e, &payload.code))?
+        .ok_or_else(|| ancillary_not_found(id))?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ancillary.updated",
+        "ancillary_product",
+        id,
+        serde_json::json!({
+            "code": &product.code,
+            "price": product.price,
+            "currency": &product.currency,
+            "active": product.active,
+        }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: product,
+    }))
+}
+
+// Active products sold on a flight and how many were bought
+pub async fn get_flight_ancillaries(
+    State(pool): State<DbPool>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<FlightAncillary>>>, AppError> {
+    if Flight::find_by_id(&pool, id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let products = FlightAncillary::find_by_flight(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: products,
+    }))
+}
+
+// Replace the products sold on a flight (admin or dispatcher)
+pub async fn set_flight_ancillaries(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<SetFlightAncillariesRequest>,
+) -> Result<Json<ApiResponse<Vec<FlightAncillary>>>, AppError> {
+    auth.require_position(&[StaffPosition::Dispatcher])?;
+
+    if Flight::find_by_id(&pool, id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let mut seen = HashSet::new();
+    for offer in &payload.products {
+        if !seen.insert(offer.product_id) {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                format!("Product {} listed more than once", offer.product_id),
+            ));
+        }
+        if AncillaryProduct::find_by_id(&pool, offer.product_id)
+            .await?
+            .is_none()
+        {
+            return Err(ancillary_not_found(offer.product_id));
+        }
+    }
+
+    FlightAncillary::replace_for_flight(&pool, id, &payload.products).await?;
+    let products = FlightAncillary::find_by_flight(&pool, id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.ancillaries_updated",
+        "flight",
+        id,
+        serde_json::json!({ "products": &payload.products }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: products,
+    }))
+}
+
+// Ancillaries bought for a ticket and its total with them (the holder or staff)
+pub async fn get_ticket_ancillaries(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<TicketExtras>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let ancillaries = TicketAncillary::find_by_ticket(&pool, id).await?;
+    let ancillaries_total = ancillaries_total(&ancillaries);
+    let currency = ticket.currency.clone().or_else(|| {
+        ancillaries
+            .first()
+            .map(|ancillary| ancillary.currency.clone())
+    });
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: TicketExtras {
+            total: ticket
+                .fare
+                .map(|fare| ((fare + ancillaries_total) * 100.0).round() / 100.0),
+            ancillaries,
+            ancillaries_total,
+            currency,
+        },
+    }))
+}
+
+// Buy one of the flight's ancillary products for a ticket before departure (the holder or
+// staff). It is priced in the currency of the ticket's fare class and captured at purchase
+pub async fn purchase_ticket_ancillary(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<PurchaseAncillaryRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<TicketAncillary>>), AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) || flight.departure_time <= Utc::now()
+    {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Ancillaries can only be bought before departure, the flight is {}",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let product = AncillaryProduct::find_by_id(&pool, payload.product_id)
+        .await?
+        .ok_or_else(|| ancillary_not_found(payload.product_id))?;
+    let not_offered = || {
+        AppError::ValidationError(
+            ErrorCode::AncillaryNotOffered,
+            format!(
+                "{} is not sold on flight {}",
+                product.code, flight.flight_number
+            ),
+        )
+    };
+    if !product.active {
+        return Err(not_offered());
+    }
+
+    let currency = FlightFareClass::find_by_flight(&pool, flight.flight_id)
+        .await?
+        .into_iter()
+        .find(|class| class.fare_class == ticket.fare_class)
+        .map_or_else(|| product.currency.clone(), |class| class.currency);
+    let price = if currency == product.currency {
+        product.price
+    } else {
+        ExchangeRates::load(&pool)
+            .await?
+            .convert(product.price, &product.currency, &currency)
+            .ok_or_else(|| {
+                AppError::InternalError(format!(
+                    "No exchange rate between {} and {} for product {}",
+                    product.currency, currency, product.product_id
+                ))
+            })?
+    };
+
+    let purchase = TicketAncillary::purchase(
+        &pool,
+        id,
+        flight.flight_id,
+        product.product_id,
+        (price, &currency),
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|db| db.is_unique_violation())
+        {
+            AppError::ConflictError(
+                ErrorCode::AncillaryAlreadyPurchased,
+                format!("Ticket {} already has {}", id, product.code),
+            )
+        } else {
+            e.into()
+        }
+    })?;
+    let ancillary = match purchase {
+        AncillaryPurchase::Purchased(ancillary) => ancillary,
+        AncillaryPurchase::NotOffered => return Err(not_offered()),
+        AncillaryPurchase::SoldOut => {
+            return Err(AppError::ConflictError(
+                ErrorCode::AncillarySoldOut,
+                format!(
+                    "{} is sold out on flight {}",
+                    product.code, flight.flight_number
+                ),
+            ));
+        }
+    };
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ticket.ancillary_purchased",
+        "ticket",
+        id,
+        serde_json::json!({
+            "code": &ancillary.code,
+            "price": ancillary.price,
+            "currency": &ancillary.currency,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: ancillary,
+        }),
+    ))
+}
@@ -0,0 +1,108 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{ApiKey, ApiScope, AuditLog};
+use crate::validation::{not_blank, ValidJson};
+
+// Create API key request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(custom(function = "not_blank"), length(max = 100))]
+    pub name: String,
+    #[validate(length(min = 1))]
+    pub scopes: Vec<ApiScope>,
+}
+
+// A freshly issued key; the cleartext `api_key` is only ever returned here
+#[derive(Debug, Serialize)]
+pub struct IssuedApiKey {
+    #[serde(flatten)]
+    pub key: ApiKey,
+    pub api_key: String,
+}
+
+// List partner API keys, including revoked ones (admin only)
+pub async fn get_api_keys(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+) -> Result<Json<ApiResponse<Vec<ApiKey>>>, AppError> {
+    let keys = ApiKey::find_all(&pool).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: keys,
+    }))
+}
+
+// Issue a partner API key with the given scopes (admin only)
+pub async fn create_api_key(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    ValidJson(payload): ValidJson<CreateApiKeyRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<IssuedApiKey>>), AppError> {
+    let name = payload.name.trim();
+    let mut scopes = Vec::new();
+    for scope in payload.scopes {
+        if !scopes.contains(&scope) {
+            scopes.push(scope);
+        }
+    }
+
+    let (key, api_key) = ApiKey::create(&pool, name, &scopes, auth.user_id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "api_key.created",
+        "api_key",
+        key.key_id,
+        serde_json::json!({ "name": &key.name, "scopes": &key.scopes }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: IssuedApiKey { key, api_key },
+        }),
+    ))
+}
+
+// Revoke a partner API key; requests with it fail from now on (admin only)
+pub async fn revoke_api_key(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    if !ApiKey::revoke(&pool, id).await? {
+        return Err(AppError::NotFound(
+            ErrorCode::ApiKeyNotFound,
+            format!("Active API key with id {} not found", id),
+        ));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "api_key.revoked",
+        "api_key",
+        id,
+        serde_json::Value::Null,
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: serde_json::json!({}),
+    }))
+}
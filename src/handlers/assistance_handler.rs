@@ -0,0 +1,131 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use super::response::ApiResponse;
+use super::ticket_handler::ticket_not_found;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{AssistanceRequest, AuditLog, NewAssistanceRequest, StaffPosition, Ticket};
+use crate::validation::ValidJson;
+
+// Assistance requested on a ticket (its holder or staff)
+pub async fn get_ticket_assistance(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<AssistanceRequest>>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let requests = AssistanceRequest::find_by_ticket(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: requests,
+    }))
+}
+
+// Ask for wheelchair, unaccompanied minor or medical assistance on a ticket (its holder or
+// staff). The ticket cannot be checked in until staff acknowledge the request
+pub async fn request_assistance(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<NewAssistanceRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<AssistanceRequest>>), AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let request = AssistanceRequest::create(&pool, id, &payload)
+        .await
+        .map_err(|e| {
+            if e.as_database_error()
+                .is_some_and(|db| db.is_unique_violation())
+            {
+                AppError::ConflictError(
+                    ErrorCode::AssistanceAlreadyRequested,
+                    format!(
+                        "Ticket {} already has a {} assistance request",
+                        id,
+                        payload.assistance_type.as_str()
+                    ),
+                )
+            } else {
+                e.into()
+            }
+        })?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ticket.assistance_requested",
+        "ticket",
+        id,
+        serde_json::json!({ "assistance_type": request.assistance_type.as_str() }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: request,
+        }),
+    ))
+}
+
+// Confirm the assistance will be provided (admin, check-in agent or gate agent)
+pub async fn acknowledge_assistance(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path((id, assistance_id)): Path<(i32, i32)>,
+) -> Result<Json<ApiResponse<AssistanceRequest>>, AppError> {
+    auth.require_position(&[StaffPosition::CheckInAgent, StaffPosition::GateAgent])?;
+
+    let not_found = || {
+        AppError::NotFound(
+            ErrorCode::AssistanceRequestNotFound,
+            format!(
+                "Assistance request {} not found on ticket {}",
+                assistance_id, id
+            ),
+        )
+    };
+    let request = AssistanceRequest::find_by_id(&pool, assistance_id)
+        .await?
+        .filter(|request| request.ticket_id == id)
+        .ok_or_else(not_found)?;
+
+    // Acknowledging again leaves the first acknowledgment in place
+    if AssistanceRequest::acknowledge(&pool, assistance_id, auth.user_id).await? {
+        AuditLog::record(
+            &pool,
+            Some(auth.user_id),
+            "ticket.assistance_acknowledged",
+            "ticket",
+            id,
+            serde_json::json!({
+                "assistance_id": assistance_id,
+                "assistance_type": request.assistance_type.as_str(),
+            }),
+        )
+        .await;
+    }
+
+    let request = AssistanceRequest::find_by_id(&pool, assistance_id)
+        .await?
+        .ok_or_else(not_found)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: request,
+    }))
+}
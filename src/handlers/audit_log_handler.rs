@@ -0,0 +1,37 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+
+use super::response::{PaginatedResponse, Pagination};
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{AuditLog, AuditLogFilter};
+
+// Search the audit log by actor, action, entity and time range, newest first (admin only)
+pub async fn get_audit_logs(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    Query(filter): Query<AuditLogFilter>,
+) -> Result<Json<PaginatedResponse<AuditLog>>, AppError> {
+    let page = filter.page.unwrap_or(1);
+    let limit = filter.limit.unwrap_or(50);
+
+    let entries = AuditLog::find(&pool, &filter, page, limit).await?;
+    let total = AuditLog::count(&pool, &filter).await?;
+
+    let total_pages = (total as f64 / limit as f64).ceil() as i32;
+
+    Ok(Json(PaginatedResponse {
+        success: true,
+        count: entries.len(),
+        pagination: Pagination {
+            page,
+            limit,
+            total_pages,
+            total_items: total,
+        },
+        data: entries,
+    }))
+}
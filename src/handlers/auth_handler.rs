@@ -0,0 +1,431 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{error, warn};
+use validator::Validate;
+
+use super::response::ApiResponse;
+use super::two_factor_handler::verify_second_factor;
+use crate::auth::{create_token, JwtKeys};
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::middleware::client_info::ClientInfo;
+use crate::models::otp_code::{MAX_CODES_PER_WINDOW, RATE_LIMIT_WINDOW_MINUTES};
+use crate::models::{
+    AuditLog, OtpCode, PasswordReset, RefreshToken, RevokedToken, Rotation, Session, User,
+};
+use crate::notifications::{Notifier, SmsProvider};
+use crate::password_policy::PasswordPolicy;
+use crate::repositories::UserRepository;
+use crate::validation::{phone_number, ValidJson};
+
+// Login request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct LoginRequest {
+    #[validate(email)]
+    pub email: String,
+    #[validate(length(min = 1))]
+    pub password: String,
+    // Required when the account has two-factor authentication enabled
+    pub totp_code: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LoginResponse {
+    pub token: String,
+    pub refresh_token: String,
+    pub user: User,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct RefreshRequest {
+    #[validate(length(min = 1))]
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefreshResponse {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionInfo {
+    #[serde(flatten)]
+    pub session: Session,
+    pub current: bool,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangePasswordRequest {
+    #[validate(length(min = 1))]
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email)]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1))]
+    pub token: String,
+    pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OtpRequest {
+    #[validate(custom(function = "phone_number"))]
+    pub phone: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct OtpVerifyRequest {
+    #[validate(custom(function = "phone_number"))]
+    pub phone: String,
+    #[validate(length(min = 1))]
+    pub code: String,
+    pub totp_code: Option<String>,
+}
+
+fn access_token(
+    keys: &JwtKeys,
+    config: &Config,
+    user: &User,
+    session_id: &str,
+    two_factor: bool,
+) -> Result<String, AppError> {
+    create_token(keys, config, user, session_id, two_factor)
+        .map_err(|e| AppError::InternalError(format!("Failed to create token: {}", e)))
+}
+
+// Exchange email and password for a short-lived JWT and a refresh token
+pub async fn login(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    State(config): State<Config>,
+    State(keys): State<Arc<JwtKeys>>,
+    client: ClientInfo,
+    ValidJson(payload): ValidJson<LoginRequest>,
+) -> Result<Json<ApiResponse<LoginResponse>>, AppError> {
+    let invalid_credentials = || {
+        AppError::AuthError(
+            ErrorCode::InvalidCredentials,
+            "Invalid email or password".to_string(),
+        )
+    };
+
+    let user = users
+        .find_by_email(&payload.email)
+        .await?
+        .ok_or_else(invalid_credentials)?;
+
+    let password_matches = bcrypt::verify(&payload.password, &user.password)
+        .map_err(|e| AppError::InternalError(format!("Password check failed: {}", e)))?;
+    if !password_matches {
+        return Err(invalid_credentials());
+    }
+
+    let two_factor =
+        verify_second_factor(&pool, user.user_id, payload.totp_code.as_deref()).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: start_session(&pool, &keys, &config, &client, user, two_factor).await?,
+    }))
+}
+
+// Open a session for a fresh login and issue its access and refresh token pair
+async fn start_session(
+    pool: &DbPool,
+    keys: &JwtKeys,
+    config: &Config,
+    client: &ClientInfo,
+    user: User,
+    two_factor: bool,
+) -> Result<LoginResponse, AppError> {
+    let session_id = Session::create(
+        pool,
+        user.user_id,
+        client.user_agent.as_deref(),
+        client.ip_address.as_deref(),
+    )
+    .await?;
+
+    let token = access_token(keys, config, &user, &session_id, two_factor)?;
+    let refresh_token = RefreshToken::issue(
+        pool,
+        user.user_id,
+        &session_id,
+        two_factor,
+        config.refresh_token_expiration,
+    )
+    .await?;
+
+    Ok(LoginResponse {
+        token,
+        refresh_token,
+        user,
+    })
+}
+
+// Text a 6-digit login code to a registered phone. Like forgot-password, the response
+// does not reveal whether the phone is registered
+pub async fn request_otp(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    State(config): State<Config>,
+    Extension(sms): Extension<Arc<dyn SmsProvider>>,
+    ValidJson(payload): ValidJson<OtpRequest>,
+) -> Result<StatusCode, AppError> {
+    let phone = payload.phone.trim();
+
+    if OtpCode::recent_count(&pool, phone).await? >= MAX_CODES_PER_WINDOW {
+        return Err(AppError::RateLimited(
+            ErrorCode::TooManyRequests,
+            format!(
+                "At most {} codes can be requested per {} minutes",
+                MAX_CODES_PER_WINDOW, RATE_LIMIT_WINDOW_MINUTES
+            ),
+        ));
+    }
+
+    if let Some(user) = users.find_by_phone(phone).await? {
+        let code = OtpCode::issue(&pool, phone, config.otp_expiration).await?;
+        let message = user
+            .language
+            .login_code_sms(&code, config.otp_expiration / 60);
+        if let Err(e) = sms.send_sms(phone, &message).await {
+            error!("Failed to send login code: {}", e);
+        }
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+// Exchange a texted login code for the same token pair a password login returns
+pub async fn verify_otp(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    State(config): State<Config>,
+    State(keys): State<Arc<JwtKeys>>,
+    client: ClientInfo,
+    ValidJson(payload): ValidJson<OtpVerifyRequest>,
+) -> Result<Json<ApiResponse<LoginResponse>>, AppError> {
+    let invalid_code =
+        || AppError::AuthError(ErrorCode::InvalidOtp, "Invalid or expired code".to_string());
+
+    let phone = payload.phone.trim();
+    if !OtpCode::verify(&pool, phone, payload.code.trim()).await? {
+        return Err(invalid_code());
+    }
+
+    let user = users.find_by_phone(phone).await?.ok_or_else(invalid_code)?;
+    let two_factor =
+        verify_second_factor(&pool, user.user_id, payload.totp_code.as_deref()).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: start_session(&pool, &keys, &config, &client, user, two_factor).await?,
+    }))
+}
+
+// Rotate a refresh token: the presented token is spent and a new pair is issued
+pub async fn refresh(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    State(config): State<Config>,
+    State(keys): State<Arc<JwtKeys>>,
+    ValidJson(payload): ValidJson<RefreshRequest>,
+) -> Result<Json<ApiResponse<RefreshResponse>>, AppError> {
+    let invalid_token = || {
+        AppError::AuthError(
+            ErrorCode::InvalidRefreshToken,
+            "Invalid or expired refresh token".to_string(),
+        )
+    };
+
+    let (user_id, session_id, two_factor, refresh_token) = match RefreshToken::rotate(
+        &pool,
+        &payload.refresh_token,
+        config.refresh_token_expiration,
+    )
+    .await?
+    {
+        Rotation::Rotated {
+            user_id,
+            session_id,
+            two_factor,
+            token,
+        } => (user_id, session_id, two_factor, token),
+        Rotation::Reused => {
+            warn!("Refresh token reuse detected, session revoked");
+            return Err(AppError::AuthError(
+                ErrorCode::RefreshTokenReused,
+                "Refresh token was already used; please log in again".to_string(),
+            ));
+        }
+        Rotation::Invalid => return Err(invalid_token()),
+    };
+
+    let user = users.find_by_id(user_id).await?.ok_or_else(invalid_token)?;
+    let token = access_token(&keys, &config, &user, &session_id, two_factor)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: RefreshResponse {
+            token,
+            refresh_token,
+        },
+    }))
+}
+
+// Revoke the caller's access token and end its session, which revokes its refresh tokens
+pub async fn logout(State(pool): State<DbPool>, auth: AuthUser) -> Result<StatusCode, AppError> {
+    RevokedToken::revoke(&pool, &auth.claims).await?;
+    Session::revoke(&pool, auth.user_id, &auth.claims.sid).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// List the caller's active sessions, marking the one making the request
+pub async fn get_sessions(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Vec<SessionInfo>>>, AppError> {
+    let sessions = Session::find_active_by_user(&pool, auth.user_id)
+        .await?
+        .into_iter()
+        .map(|session| SessionInfo {
+            current: session.session_id == auth.claims.sid,
+            session,
+        })
+        .collect();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: sessions,
+    }))
+}
+
+// Sign one of the caller's devices out
+pub async fn revoke_session(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    if !Session::revoke(&pool, auth.user_id, &id).await? {
+        return Err(AppError::NotFound(
+            ErrorCode::SessionNotFound,
+            format!("Active session {} not found", id),
+        ));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Change the caller's password; every token issued before the change is revoked
+pub async fn change_password(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    State(config): State<Config>,
+    auth: AuthUser,
+    ValidJson(payload): ValidJson<ChangePasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    PasswordPolicy::from_config(&config).validate(&payload.new_password)?;
+
+    let user = users.find_by_id(auth.user_id).await?.ok_or_else(|| {
+        AppError::AuthError(ErrorCode::InvalidToken, "User no longer exists".to_string())
+    })?;
+
+    let password_matches = bcrypt::verify(&payload.current_password, &user.password)
+        .map_err(|e| AppError::InternalError(format!("Password check failed: {}", e)))?;
+    if !password_matches {
+        return Err(AppError::AuthError(
+            ErrorCode::InvalidCredentials,
+            "Current password is wrong".to_string(),
+        ));
+    }
+
+    let password_hash = hash_password(&payload.new_password)?;
+    users.update_password(user.user_id, &password_hash).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "user.password_changed",
+        "user",
+        auth.user_id,
+        serde_json::Value::Null,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Email a reset token if the address belongs to a user. The response is the same either way
+// so the endpoint cannot be used to discover registered emails
+pub async fn forgot_password(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    State(config): State<Config>,
+    Extension(notifier): Extension<Notifier>,
+    ValidJson(payload): ValidJson<ForgotPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    if let Some(user) = users.find_by_email(&payload.email).await? {
+        let token =
+            PasswordReset::issue(&pool, user.user_id, config.password_reset_expiration).await?;
+        notifier.password_reset(&user, &token, config.password_reset_expiration);
+    }
+
+    Ok(StatusCode::ACCEPTED)
+}
+
+// Set a new password with a reset token; all existing sessions of the user are revoked
+pub async fn reset_password(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    State(config): State<Config>,
+    ValidJson(payload): ValidJson<ResetPasswordRequest>,
+) -> Result<StatusCode, AppError> {
+    PasswordPolicy::from_config(&config).validate(&payload.new_password)?;
+
+    let user_id = PasswordReset::consume(&pool, &payload.token)
+        .await?
+        .ok_or_else(|| {
+            AppError::AuthError(
+                ErrorCode::InvalidResetToken,
+                "Invalid or expired reset token".to_string(),
+            )
+        })?;
+
+    let password_hash = hash_password(&payload.new_password)?;
+    users.update_password(user_id, &password_hash).await?;
+
+    AuditLog::record(
+        &pool,
+        None,
+        "user.password_reset",
+        "user",
+        user_id,
+        serde_json::Value::Null,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn hash_password(password: &str) -> Result<String, AppError> {
+    bcrypt::hash(password, bcrypt::DEFAULT_COST)
+        .map_err(|e| AppError::InternalError(format!("Password hashing failed: {}", e)))
+}
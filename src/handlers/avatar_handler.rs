@@ -0,0 +1,232 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::response::ApiResponse;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::file_type;
+use crate::middleware::auth::AuthUser;
+use crate::models::{AuditLog, User};
+use crate::multipart::{self, Form};
+use crate::routes::CURRENT_PREFIX;
+use crate::signed_url;
+use crate::storage::ObjectStorage;
+use crate::virus_scan::{ScanVerdict, VirusScanner};
+
+fn invalid(code: ErrorCode, message: impl Into<String>) -> AppError {
+    AppError::ValidationError(code, message.into())
+}
+
+fn storage_failed(e: impl std::fmt::Display) -> AppError {
+    AppError::InternalError(format!("Avatar storage failed: {}", e))
+}
+
+fn storage_key(file: &str) -> String {
+    format!("avatars/{}", file)
+}
+
+fn user_not_found(id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::UserNotFound,
+        format!("User with id {} not found", id),
+    )
+}
+
+// Remove a replaced or deleted photo; a failure only leaves an unreferenced file behind
+pub async fn delete_file(storage: &dyn ObjectStorage, file: &str) {
+    if let Err(e) = storage.delete(&storage_key(file)).await {
+        error!("Failed to delete avatar {}: {}", file, e);
+    }
+}
+
+// Set a user's profile photo (the user themselves or staff) from the `file` field of a
+// multipart form, a JPEG or PNG image. The response's `avatar_url` is a signed link to it
+pub async fn upload_avatar(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    Extension(storage): Extension<Arc<dyn ObjectStorage>>,
+    Extension(scanner): Extension<Arc<dyn VirusScanner>>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    Form(parts): Form,
+) -> Result<Json<ApiResponse<User>>, AppError> {
+    auth.require_owner(id)?;
+    if User::find_by_id(&pool, id).await?.is_none() {
+        return Err(user_not_found(id));
+    }
+
+    let file = multipart::take(parts, "file")
+        .ok_or_else(|| invalid(ErrorCode::ValidationFailed, "The form has no `file` field"))?;
+    if file.data.len() > config.avatar_max_bytes {
+        return Err(invalid(
+            ErrorCode::FileTooLarge,
+            format!(
+                "Profile photos can be at most {} bytes",
+                config.avatar_max_bytes
+            ),
+        ));
+    }
+    let content_type = file_type::sniff(&file.data)
+        .filter(|content_type| [file_type::JPEG, file_type::PNG].contains(content_type))
+        .ok_or_else(|| {
+            invalid(
+                ErrorCode::UnsupportedFileType,
+                "Profile photos must be JPEG or PNG images",
+            )
+        })?;
+    if file_type::contradicts(file.content_type.as_deref(), content_type) {
+        return Err(invalid(
+            ErrorCode::UnsupportedFileType,
+            format!("The file is {}, not the declared type", content_type),
+        ));
+    }
+
+    match scanner.scan(&file.data).await {
+        Ok(ScanVerdict::Clean) => {}
+        Ok(ScanVerdict::Infected(signature)) => {
+            warn!("Rejected avatar upload for user {}: {}", id, signature);
+            return Err(invalid(
+                ErrorCode::FileRejected,
+                "The file was rejected by the malware scan",
+            ));
+        }
+        Err(e) => {
+            error!("Virus scan failed: {}", e);
+            return Err(AppError::InternalError(
+                "The file could not be scanned".to_string(),
+            ));
+        }
+    }
+
+    // A new name per upload, so links to the previous photo stop resolving and caches never
+    // serve a stale one
+    let file_name = format!(
+        "{}.{}",
+        Uuid::new_v4().simple(),
+        file_type::extension(content_type)
+    );
+    storage
+        .put(&storage_key(&file_name), file.data, content_type)
+        .await
+        .map_err(storage_failed)?;
+
+    let previous = match User::set_avatar(&pool, id, Some(&file_name)).await {
+        Ok(previous) => previous,
+        Err(e) => {
+            delete_file(storage.as_ref(), &file_name).await;
+            return Err(e.into());
+        }
+    };
+    if let Some(previous) = previous {
+        delete_file(storage.as_ref(), &previous).await;
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "user.avatar_updated",
+        "user",
+        id,
+        serde_json::Value::Null,
+    )
+    .await;
+
+    let user = User::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| user_not_found(id))?;
+    Ok(Json(ApiResponse {
+        success: true,
+        data: user,
+    }))
+}
+
+// Remove a user's profile photo (the user themselves or staff)
+pub async fn delete_avatar(
+    State(pool): State<DbPool>,
+    Extension(storage): Extension<Arc<dyn ObjectStorage>>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<StatusCode, AppError> {
+    auth.require_owner(id)?;
+    if User::find_by_id(&pool, id).await?.is_none() {
+        return Err(user_not_found(id));
+    }
+
+    if let Some(previous) = User::set_avatar(&pool, id, None).await? {
+        delete_file(storage.as_ref(), &previous).await;
+        AuditLog::record(
+            &pool,
+            Some(auth.user_id),
+            "user.avatar_deleted",
+            "user",
+            id,
+            serde_json::Value::Null,
+        )
+        .await;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SignedLink {
+    pub expires: i64,
+    pub signature: String,
+}
+
+// A profile photo by the signed link in a user's `avatar_url`; no token needed, so the link
+// works in an <img> tag
+pub async fn get_avatar(
+    Extension(storage): Extension<Arc<dyn ObjectStorage>>,
+    Path(file): Path<String>,
+    Query(link): Query<SignedLink>,
+) -> Result<Response, AppError> {
+    let path = format!("{}/avatars/{}", CURRENT_PREFIX, file);
+    if !signed_url::verify(&path, link.expires, &link.signature) {
+        return Err(AppError::Forbidden(
+            ErrorCode::InvalidSignature,
+            "The link is invalid or has expired".to_string(),
+        ));
+    }
+    let not_found = || {
+        AppError::NotFound(
+            ErrorCode::AvatarNotFound,
+            "Profile photo not found".to_string(),
+        )
+    };
+    let content_type = file_type::from_extension(&file).ok_or_else(not_found)?;
+    let data = storage
+        .get(&storage_key(&file))
+        .await
+        .map_err(storage_failed)?
+        .ok_or_else(not_found)?;
+
+    // File names are never reused, so the photo can be cached for as long as the link is valid
+    let max_age = (link.expires - Utc::now().timestamp()).max(0);
+    let cache_control = format!("private, max-age={}, immutable", max_age);
+    Ok((
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static(content_type)),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_str(&cache_control).expect("ASCII header"),
+            ),
+            (
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
@@ -0,0 +1,377 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::response::ApiResponse;
+use super::ticket_handler::ticket_not_found;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{
+    AuditLog, Bag, BagScan, BaggageAllowance, BaggagePurchase, BaggageStatus, Flight, FlightStatus,
+    NewBag, NewBaggageAllowance, Route, StaffPosition, Ticket,
+};
+use crate::validation::ValidJson;
+
+// Scan bag request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct ScanBagRequest {
+    pub status: BaggageStatus,
+    // Where the bag was scanned, e.g. `KBP belt 3`
+    #[validate(length(max = 50))]
+    pub location: Option<String>,
+}
+
+// A bag and every scan of it, oldest first
+#[derive(Debug, Serialize)]
+pub struct TrackedBag {
+    #[serde(flatten)]
+    pub bag: Bag,
+    pub scans: Vec<BagScan>,
+}
+
+// Buy additional allowance request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct BuyAllowanceRequest {
+    #[validate(range(min = 1, max = 5))]
+    pub pieces: i32,
+}
+
+// The allowance rule of a ticket with the pieces it has bought and checked
+#[derive(Debug, Serialize)]
+pub struct TicketAllowance {
+    pub ticket_id: i32,
+    #[serde(flatten)]
+    pub rule: BaggageAllowance,
+    pub pieces_purchased: i64,
+    pub pieces_checked: i64,
+    pub purchases: Vec<BaggagePurchase>,
+}
+
+impl TicketAllowance {
+    fn excess_fee(&self, weight_kg: f64) -> f64 {
+        let pieces_allowed = i64::from(self.rule.pieces) + self.pieces_purchased;
+        self.rule
+            .excess_fee(pieces_allowed, self.pieces_checked, weight_kg)
+    }
+}
+
+fn allowance_not_found(ticket_id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::BaggageAllowanceNotFound,
+        format!("No baggage allowance applies to ticket {}", ticket_id),
+    )
+}
+
+async fn ticket_allowance(pool: &DbPool, ticket: &Ticket) -> Result<TicketAllowance, AppError> {
+    let rule = BaggageAllowance::for_ticket(pool, ticket.ticket_id)
+        .await?
+        .ok_or_else(|| allowance_not_found(ticket.ticket_id))?;
+    let purchases = BaggagePurchase::find_by_ticket(pool, ticket.ticket_id).await?;
+    let pieces_checked = Bag::find_by_ticket(pool, ticket.ticket_id).await?.len() as i64;
+
+    Ok(TicketAllowance {
+        ticket_id: ticket.ticket_id,
+        rule,
+        pieces_purchased: purchases.iter().map(|p| i64::from(p.pieces)).sum(),
+        pieces_checked,
+        purchases,
+    })
+}
+
+// Baggage allowance rules of every fare class, those of every route first
+pub async fn list_baggage_allowances(
+    State(pool): State<DbPool>,
+) -> Result<Json<ApiResponse<Vec<BaggageAllowance>>>, AppError> {
+    let rules = BaggageAllowance::find_all(&pool).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: rules,
+    }))
+}
+
+// Set the allowance of a fare class on one route or, without a route, on every route (admin
+// only)
+pub async fn set_baggage_allowance(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    ValidJson(payload): ValidJson<NewBaggageAllowance>,
+) -> Result<Json<ApiResponse<BaggageAllowance>>, AppError> {
+    if let Some(route_id) = payload.route_id {
+        if Route::find_by_id(&pool, route_id).await?.is_none() {
+            return Err(AppError::NotFound(
+                ErrorCode::RouteNotFound,
+                format!("Route with id {} not found", route_id),
+            ));
+        }
+    }
+
+    let rule = BaggageAllowance::set(&pool, &payload).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "baggage_allowance.set",
+        "baggage_allowance",
+        rule.allowance_id,
+        serde_json::json!({
+            "fare_class": rule.fare_class.as_str(),
+            "route_id": rule.route_id,
+            "pieces": rule.pieces,
+            "max_weight_kg": rule.max_weight_kg,
+        }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: rule,
+    }))
+}
+
+// What checked baggage a ticket includes, has bought and has used (its holder or staff)
+pub async fn get_ticket_allowance(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<TicketAllowance>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let allowance = ticket_allowance(&pool, &ticket).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: allowance,
+    }))
+}
+
+// Buy pieces on top of the allowance at the prepaid fee, which is lower than the one charged at
+// the desk (its holder or staff). Only until boarding starts
+pub async fn buy_ticket_allowance(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<BuyAllowanceRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<BaggagePurchase>>), AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) || flight.departure_time <= Utc::now()
+    {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Baggage allowance can only be bought before departure, the flight is {}",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let rule = BaggageAllowance::for_ticket(&pool, id)
+        .await?
+        .ok_or_else(|| allowance_not_found(id))?;
+    let price = ((rule.prepaid_piece_fee * f64::from(payload.pieces)) * 100.0).round() / 100.0;
+    let purchase = BaggagePurchase::create(
+        &pool,
+        id,
+        payload.pieces,
+        price,
+        &rule.currency,
+        auth.user_id,
+    )
+    .await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ticket.baggage_allowance_bought",
+        "ticket",
+        id,
+        serde_json::json!({
+            "pieces": purchase.pieces,
+            "price": purchase.price,
+            "currency": &purchase.currency,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: purchase,
+        }),
+    ))
+}
+
+// Where the bags of a ticket are (its holder or staff)
+pub async fn get_ticket_baggage(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<TrackedBag>>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let scans = BagScan::find_by_ticket(&pool, id).await?;
+    let bags = Bag::find_by_ticket(&pool, id)
+        .await?
+        .into_iter()
+        .map(|bag| TrackedBag {
+            scans: scans
+                .iter()
+                .filter(|scan| scan.bag_id == bag.bag_id)
+                .cloned()
+                .collect(),
+            bag,
+        })
+        .collect();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: bags,
+    }))
+}
+
+// Check a bag in on a ticket and issue its tag (admin or check-in agent). A bag over the
+// ticket's allowance, in pieces or in weight, is charged the excess fee
+pub async fn check_in_bag(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<NewBag>,
+) -> Result<(StatusCode, Json<ApiResponse<Bag>>), AppError> {
+    auth.require_position(&[StaffPosition::CheckInAgent])?;
+
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    if matches!(
+        flight.status,
+        FlightStatus::Departed | FlightStatus::Arrived | FlightStatus::Cancelled
+    ) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Bags cannot be checked in for a {} flight",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let allowance = ticket_allowance(&pool, &ticket).await?;
+    let excess_fee = allowance.excess_fee(payload.weight_kg);
+    let bag = Bag::check_in(
+        &pool,
+        id,
+        &payload,
+        (excess_fee, &allowance.rule.currency),
+        auth.user_id,
+    )
+    .await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ticket.bag_checked",
+        "ticket",
+        id,
+        serde_json::json!({
+            "tag_number": &bag.tag_number,
+            "weight_kg": bag.weight_kg,
+            "excess_fee": bag.excess_fee,
+            "excess_fee_currency": &bag.excess_fee_currency,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: bag,
+        }),
+    ))
+}
+
+// Record a scan of a bag's tag as it is loaded, unloaded or handed over (admin, check-in agent
+// or gate agent)
+pub async fn scan_bag(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(tag_number): Path<String>,
+    ValidJson(payload): ValidJson<ScanBagRequest>,
+) -> Result<Json<ApiResponse<Bag>>, AppError> {
+    auth.require_position(&[StaffPosition::CheckInAgent, StaffPosition::GateAgent])?;
+
+    let not_found = || {
+        AppError::NotFound(
+            ErrorCode::BagNotFound,
+            format!("Bag with tag number {} not found", tag_number),
+        )
+    };
+    let bag = Bag::find_by_tag(&pool, &tag_number)
+        .await?
+        .ok_or_else(not_found)?;
+
+    if !bag.status.can_scan_to(payload.status) {
+        return Err(AppError::ValidationError(
+            ErrorCode::InvalidBaggageScan,
+            format!(
+                "Bag {} cannot go from {} to {}",
+                tag_number,
+                bag.status.as_str(),
+                payload.status.as_str()
+            ),
+        ));
+    }
+
+    let scanned = Bag::scan(
+        &pool,
+        bag.bag_id,
+        bag.status,
+        payload.status,
+        payload.location.as_deref(),
+        auth.user_id,
+    )
+    .await?;
+    if !scanned {
+        return Err(AppError::ConflictError(
+            ErrorCode::ConcurrentModification,
+            "Bag was scanned by another request, please retry".to_string(),
+        ));
+    }
+
+    let bag = Bag::find_by_tag(&pool, &tag_number)
+        .await?
+        .ok_or_else(not_found)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: bag,
+    }))
+}
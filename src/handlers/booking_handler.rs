@@ -0,0 +1,338 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::ancillary_handler::ancillaries_total;
+use super::fare_class_handler::current_price;
+use super::flight_handler::flight_not_found;
+use super::passenger_handler::passenger_fares;
+use super::response::ApiResponse;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{
+    AuditLog, BookingGroup, FareClass, Flight, FlightFareClass, FlightSeat, FlightStatus,
+    GroupDiscount, GroupPassenger, GroupSeating, PassengerType, Ticket, TicketAncillary,
+    TravelCompanion, User,
+};
+use crate::pricing::PricingEngine;
+use crate::validation::ValidJson;
+
+// One passenger of a group booking
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct GroupPassengerRequest {
+    pub user_id: i32,
+    // Omit to be given the first free seat of the class
+    #[validate(length(min = 2, max = 5))]
+    pub seat_number: Option<String>,
+}
+
+// Group booking request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct GroupBookingRequest {
+    pub flight_id: i32,
+    pub fare_class: FareClass,
+    #[validate(length(min = 2, max = 50), nested)]
+    pub passengers: Vec<GroupPassengerRequest>,
+}
+
+// A booking group, the tickets issued in it and the ancillaries bought for them
+#[derive(Debug, Serialize)]
+pub struct GroupBooking {
+    #[serde(flatten)]
+    pub group: BookingGroup,
+    pub tickets: Vec<Ticket>,
+    pub ancillaries: Vec<TicketAncillary>,
+    // The group's total and its ancillaries together
+    pub total_with_ancillaries: f64,
+}
+
+async fn group_booking(pool: &DbPool, group: BookingGroup) -> Result<GroupBooking, AppError> {
+    let tickets = Ticket::find_by_group(pool, group.group_id).await?;
+    let ancillaries = TicketAncillary::find_by_group(pool, group.group_id).await?;
+    let total_with_ancillaries =
+        ((group.total + ancillaries_total(&ancillaries)) * 100.0).round() / 100.0;
+    Ok(GroupBooking {
+        group,
+        tickets,
+        ancillaries,
+        total_with_ancillaries,
+    })
+}
+
+fn group_not_found(reference: &str) -> AppError {
+    AppError::NotFound(
+        ErrorCode::BookingGroupNotFound,
+        format!("Booking group {} not found", reference),
+    )
+}
+
+// Book several passengers on one flight and fare class in a single transaction: every one of
+// them gets a seat or none does. Staff book for anyone, passengers for groups they travel in
+// with companions who allowed them to (`/users/{id}/companions`).
+// Each passenger is typed and priced by their age on the departure date, and large groups get
+// the group discount
+pub async fn book_group(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    Extension(pricing): Extension<PricingEngine>,
+    auth: AuthUser,
+    ValidJson(payload): ValidJson<GroupBookingRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<GroupBooking>>), AppError> {
+    // Passengers book for themselves and the companions who allowed them to; ids of users who
+    // did not, existing or not, are refused alike
+    let travelling = payload
+        .passengers
+        .iter()
+        .any(|passenger| passenger.user_id == auth.user_id);
+    if auth.role.is_staff() || !travelling {
+        auth.require_staff()?;
+    } else {
+        let others: Vec<i32> = payload
+            .passengers
+            .iter()
+            .map(|passenger| passenger.user_id)
+            .filter(|user_id| *user_id != auth.user_id)
+            .collect();
+        let allowing = TravelCompanion::allowing(&pool, auth.user_id, &others).await?;
+        if let Some(user_id) = others.iter().find(|user_id| !allowing.contains(user_id)) {
+            return Err(AppError::Forbidden(
+                ErrorCode::NotACompanion,
+                format!("User {} has not allowed you to book for them", user_id),
+            ));
+        }
+    }
+
+    let mut users = HashSet::new();
+    let mut seats = HashSet::new();
+    let requested: Vec<(i32, Option<String>)> = payload
+        .passengers
+        .iter()
+        .map(|passenger| {
+            (
+                passenger.user_id,
+                passenger
+                    .seat_number
+                    .as_deref()
+                    .map(|seat| seat.trim().to_uppercase()),
+            )
+        })
+        .collect();
+    for (user_id, seat_number) in &requested {
+        if !users.insert(*user_id) {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                format!("User {} is listed more than once", user_id),
+            ));
+        }
+        if let Some(seat_number) = seat_number {
+            if !seats.insert(seat_number.clone()) {
+                return Err(AppError::ValidationError(
+                    ErrorCode::ValidationFailed,
+                    format!("Seat {} is requested more than once", seat_number),
+                ));
+            }
+        }
+    }
+
+    let flight = Flight::find_by_id(&pool, payload.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(payload.flight_id))?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) || flight.departure_time <= Utc::now()
+    {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Flight {} is {} and no longer sold",
+                flight.flight_number,
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let class = FlightFareClass::find_by_flight(&pool, flight.flight_id)
+        .await?
+        .into_iter()
+        .find(|class| class.fare_class == payload.fare_class)
+        .ok_or_else(|| {
+            AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                format!(
+                    "Flight {} does not sell {}",
+                    flight.flight_number,
+                    payload.fare_class.as_str()
+                ),
+            )
+        })?;
+
+    let seat_map = FlightSeat::find_by_flight(&pool, flight.flight_id).await?;
+    for (_, seat_number) in &requested {
+        match (seat_number, seat_map.is_empty()) {
+            (None, true) => {
+                return Err(AppError::ValidationError(
+                    ErrorCode::ValidationFailed,
+                    format!(
+                        "Flight {} has no seat map, every passenger needs a seat_number",
+                        flight.flight_number
+                    ),
+                ));
+            }
+            (Some(seat_number), false) => {
+                let seat = seat_map
+                    .iter()
+                    .find(|seat| &seat.seat_number == seat_number)
+                    .ok_or_else(|| {
+                        AppError::ValidationError(
+                            ErrorCode::UnknownSeat,
+                            format!("Seat {} is not on the flight's seat map", seat_number),
+                        )
+                    })?;
+                if seat.fare_class != payload.fare_class {
+                    return Err(AppError::ValidationError(
+                        ErrorCode::SeatNotInClass,
+                        format!(
+                            "Seat {} is in {}, not {}",
+                            seat_number,
+                            seat.fare_class.as_str(),
+                            payload.fare_class.as_str()
+                        ),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let departure_date = flight.departure_time.date_naive();
+    let adult_fare = current_price(
+        &pricing,
+        &class,
+        (flight.departure_time - Utc::now()).num_days(),
+    );
+    let fares = passenger_fares(&config);
+    let mut passengers = Vec::with_capacity(requested.len());
+    for (user_id, seat_number) in requested {
+        let user = User::find_by_id(&pool, user_id).await?.ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::UserNotFound,
+                format!("User with id {} not found", user_id),
+            )
+        })?;
+        let passenger_type = user.date_of_birth.map_or(PassengerType::Adult, |born| {
+            PassengerType::on(born, departure_date)
+        });
+        passengers.push(GroupPassenger {
+            user_id,
+            seat_number,
+            passenger_type,
+            fare: fares.fare(adult_fare, passenger_type),
+        });
+    }
+
+    let discount = GroupDiscount {
+        min_passengers: config.group_discount_min_passengers,
+        percent: config.group_discount_percent,
+    };
+    let seating = BookingGroup::book(
+        &pool,
+        flight.flight_id,
+        payload.fare_class,
+        &passengers,
+        discount,
+        &class.currency,
+        auth.user_id,
+    )
+    .await?;
+    let group = match seating {
+        GroupSeating::Booked(group) => group,
+        GroupSeating::ClassFull { available } => {
+            return Err(AppError::ConflictError(
+                ErrorCode::ClassSoldOut,
+                format!(
+                    "{} on flight {} has {} seat(s) left for {} passengers",
+                    payload.fare_class.as_str(),
+                    flight.flight_number,
+                    available,
+                    passengers.len()
+                ),
+            ));
+        }
+        GroupSeating::SeatTaken(seat_number) => {
+            return Err(AppError::ConflictError(
+                ErrorCode::SeatAlreadyTaken,
+                format!("Seat {} is already booked or blocked", seat_number),
+            ));
+        }
+        GroupSeating::AlreadyBooked(user_id) => {
+            return Err(AppError::ConflictError(
+                ErrorCode::PassengerAlreadyBooked,
+                format!(
+                    "User {} already holds a ticket on flight {}",
+                    user_id, flight.flight_number
+                ),
+            ));
+        }
+    };
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "booking_group.created",
+        "booking_group",
+        group.group_id,
+        serde_json::json!({
+            "reference": &group.reference,
+            "flight_id": group.flight_id,
+            "passengers": group.passenger_count,
+            "total": group.total,
+            "currency": &group.currency,
+        }),
+    )
+    .await;
+
+    let booking = group_booking(&pool, group).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: booking,
+        }),
+    ))
+}
+
+// A booking group and its tickets (whoever booked it, its passengers or staff)
+pub async fn get_group(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(reference): Path<String>,
+) -> Result<Json<ApiResponse<GroupBooking>>, AppError> {
+    let group = BookingGroup::find_by_reference(&pool, &reference)
+        .await?
+        .ok_or_else(|| group_not_found(&reference))?;
+    let booking = group_booking(&pool, group).await?;
+
+    if !booking
+        .tickets
+        .iter()
+        .any(|ticket| ticket.user_id == auth.user_id)
+    {
+        auth.require_owner(booking.group.booked_by.unwrap_or_default())?;
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: booking,
+    }))
+}
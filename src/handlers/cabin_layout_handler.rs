@@ -0,0 +1,123 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::maintenance_handler::aircraft_not_found;
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{Aircraft, AuditLog, CabinLayout, NewCabinLayout};
+use crate::validation::ValidJson;
+
+// Attach cabin layout request body; null detaches the aircraft's layout
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetCabinLayoutRequest {
+    pub layout_id: Option<i32>,
+}
+
+// List cabin layouts (admin only)
+pub async fn get_cabin_layouts(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+) -> Result<Json<ApiResponse<Vec<CabinLayout>>>, AppError> {
+    let layouts = CabinLayout::find_all(&pool).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: layouts,
+    }))
+}
+
+// Create a cabin layout (admin only)
+pub async fn create_cabin_layout(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    ValidJson(payload): ValidJson<NewCabinLayout>,
+) -> Result<(StatusCode, Json<ApiResponse<CabinLayout>>), AppError> {
+    if let Some(problem) = payload.layout_error() {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            problem,
+        ));
+    }
+
+    let layout = CabinLayout::create(&pool, &payload, auth.user_id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "cabin_layout.created",
+        "cabin_layout",
+        layout.layout_id,
+        serde_json::json!({ "name": &layout.name, "seats": layout.seat_count() }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: layout,
+        }),
+    ))
+}
+
+// Give an aircraft the layout its future flights are seated by (admin only)
+pub async fn set_aircraft_cabin_layout(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<SetCabinLayoutRequest>,
+) -> Result<Json<ApiResponse<Aircraft>>, AppError> {
+    let aircraft = Aircraft::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| aircraft_not_found(id))?;
+
+    if let Some(layout_id) = payload.layout_id {
+        let layout = CabinLayout::find_by_id(&pool, layout_id)
+            .await?
+            .ok_or_else(|| {
+                AppError::ValidationError(
+                    ErrorCode::CabinLayoutNotFound,
+                    format!("Cabin layout with id {} not found", layout_id),
+                )
+            })?;
+        if layout.seat_count() > aircraft.capacity as usize {
+            return Err(AppError::ValidationError(
+                ErrorCode::CapacityExceeded,
+                format!(
+                    "Layout {} has {} seats, the aircraft only {}",
+                    layout.name,
+                    layout.seat_count(),
+                    aircraft.capacity
+                ),
+            ));
+        }
+    }
+
+    CabinLayout::attach(&pool, id, payload.layout_id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "aircraft.cabin_layout_set",
+        "aircraft",
+        id,
+        serde_json::json!({ "layout_id": payload.layout_id }),
+    )
+    .await;
+
+    let aircraft = Aircraft::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| aircraft_not_found(id))?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: aircraft,
+    }))
+}
@@ -0,0 +1,351 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::maintenance_handler::aircraft_not_found;
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::{RequireAdmin, RequireStaff};
+use crate::models::{
+    Aircraft, AuditLog, CargoEvent, CargoLimits, CargoLoad, CargoStatus, Flight, FlightStatus,
+    NewShipment, Route, Shipment, StaffPosition,
+};
+use crate::validation::ValidJson;
+
+// Accept shipment request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateShipmentRequest {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub shipment: NewShipment,
+    // Where the shipment was accepted, e.g. `KBP cargo terminal`
+    #[validate(length(max = 50))]
+    pub location: Option<String>,
+}
+
+// Book shipment request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct AssignShipmentRequest {
+    pub flight_id: i32,
+}
+
+// Record cargo status request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct TrackShipmentRequest {
+    pub status: CargoStatus,
+    #[validate(length(max = 50))]
+    pub location: Option<String>,
+}
+
+// A shipment and every status it went through, oldest first
+#[derive(Debug, Serialize)]
+pub struct TrackedShipment {
+    #[serde(flatten)]
+    pub shipment: Shipment,
+    pub events: Vec<CargoEvent>,
+}
+
+// Cargo booked on a flight against its aircraft's limits
+#[derive(Debug, Serialize)]
+pub struct FlightCargo {
+    pub flight_id: i32,
+    pub cargo_payload_kg: f64,
+    pub cargo_volume_m3: f64,
+    pub booked: CargoLoad,
+    pub shipments: Vec<Shipment>,
+}
+
+fn shipment_not_found(awb_number: &str) -> AppError {
+    AppError::NotFound(
+        ErrorCode::ShipmentNotFound,
+        format!("Shipment with air waybill {} not found", awb_number),
+    )
+}
+
+async fn tracked(pool: &DbPool, shipment: Shipment) -> Result<TrackedShipment, AppError> {
+    let events = CargoEvent::find_by_shipment(pool, shipment.shipment_id).await?;
+    Ok(TrackedShipment { shipment, events })
+}
+
+// Accept a shipment and issue its air waybill number (staff only)
+pub async fn create_shipment(
+    State(pool): State<DbPool>,
+    auth: RequireStaff,
+    ValidJson(payload): ValidJson<CreateShipmentRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<TrackedShipment>>), AppError> {
+    let shipment = Shipment::create(
+        &pool,
+        &payload.shipment,
+        payload.location.as_deref(),
+        auth.user_id,
+    )
+    .await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "cargo.received",
+        "cargo_shipment",
+        shipment.shipment_id,
+        serde_json::json!({
+            "awb_number": &shipment.awb_number,
+            "weight_kg": shipment.weight_kg,
+            "volume_m3": shipment.volume_m3,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: tracked(&pool, shipment).await?,
+        }),
+    ))
+}
+
+// Where a shipment is, by its air waybill number
+pub async fn track_shipment(
+    State(pool): State<DbPool>,
+    Path(awb_number): Path<String>,
+) -> Result<Json<ApiResponse<TrackedShipment>>, AppError> {
+    let shipment = Shipment::find_by_awb(&pool, &awb_number)
+        .await?
+        .ok_or_else(|| shipment_not_found(&awb_number))?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: tracked(&pool, shipment).await?,
+    }))
+}
+
+// Book a shipment on a flight between its airports, or move it to another one before it is
+// loaded (admin or dispatcher). The flight's cargo must stay within its aircraft's limits
+pub async fn assign_shipment(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(awb_number): Path<String>,
+    ValidJson(payload): ValidJson<AssignShipmentRequest>,
+) -> Result<Json<ApiResponse<TrackedShipment>>, AppError> {
+    auth.require_position(&[StaffPosition::Dispatcher])?;
+
+    let shipment = Shipment::find_by_awb(&pool, &awb_number)
+        .await?
+        .ok_or_else(|| shipment_not_found(&awb_number))?;
+    if !shipment.status.assignable() {
+        return Err(AppError::ValidationError(
+            ErrorCode::InvalidCargoStatus,
+            format!(
+                "Shipment {} is already {} and cannot be booked on another flight",
+                awb_number,
+                shipment.status.as_str()
+            ),
+        ));
+    }
+
+    let flight = Flight::find_by_id(&pool, payload.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(payload.flight_id))?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Cargo cannot be booked on a {} flight",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let route = Route::find_by_id(&pool, flight.route_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    if route.origin_code() != shipment.origin_code
+        || route.destination_code() != shipment.destination_code
+    {
+        return Err(AppError::ValidationError(
+            ErrorCode::CargoRouteMismatch,
+            format!(
+                "Flight {} flies {}-{}, the shipment goes {}-{}",
+                flight.flight_number,
+                route.origin_code(),
+                route.destination_code(),
+                shipment.origin_code,
+                shipment.destination_code
+            ),
+        ));
+    }
+
+    let aircraft = Aircraft::find_by_id(&pool, flight.aircraft_id)
+        .await?
+        .ok_or_else(|| aircraft_not_found(flight.aircraft_id))?;
+    let booked = Shipment::load_on_flight(&pool, flight.flight_id, shipment.shipment_id).await?;
+    let weight = booked.weight_kg + shipment.weight_kg;
+    let volume = booked.volume_m3 + shipment.volume_m3;
+    if weight > aircraft.cargo_payload_kg || volume > aircraft.cargo_volume_m3 {
+        return Err(AppError::ConflictError(
+            ErrorCode::CargoPayloadExceeded,
+            format!(
+                "Flight {} would carry {} kg / {} m³ of cargo, over its {} kg / {} m³",
+                flight.flight_number,
+                weight,
+                volume,
+                aircraft.cargo_payload_kg,
+                aircraft.cargo_volume_m3
+            ),
+        ));
+    }
+
+    if !Shipment::assign(&pool, shipment.shipment_id, flight.flight_id, auth.user_id).await? {
+        return Err(AppError::ConflictError(
+            ErrorCode::ConcurrentModification,
+            "Shipment was changed by another request, please retry".to_string(),
+        ));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "cargo.booked",
+        "cargo_shipment",
+        shipment.shipment_id,
+        serde_json::json!({ "flight_id": flight.flight_id, "from_flight_id": shipment.flight_id }),
+    )
+    .await;
+
+    let shipment = Shipment::find_by_awb(&pool, &awb_number)
+        .await?
+        .ok_or_else(|| shipment_not_found(&awb_number))?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: tracked(&pool, shipment).await?,
+    }))
+}
+
+// Record a shipment as loaded, offloaded, in transit, arrived or delivered (admin, check-in
+// agent or gate agent)
+pub async fn record_shipment_status(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(awb_number): Path<String>,
+    ValidJson(payload): ValidJson<TrackShipmentRequest>,
+) -> Result<Json<ApiResponse<TrackedShipment>>, AppError> {
+    auth.require_position(&[StaffPosition::CheckInAgent, StaffPosition::GateAgent])?;
+
+    let shipment = Shipment::find_by_awb(&pool, &awb_number)
+        .await?
+        .ok_or_else(|| shipment_not_found(&awb_number))?;
+    if !shipment.status.can_track_to(payload.status) {
+        return Err(AppError::ValidationError(
+            ErrorCode::InvalidCargoStatus,
+            format!(
+                "Shipment {} cannot go from {} to {}",
+                awb_number,
+                shipment.status.as_str(),
+                payload.status.as_str()
+            ),
+        ));
+    }
+
+    let recorded = Shipment::track(
+        &pool,
+        &shipment,
+        payload.status,
+        payload.location.as_deref(),
+        auth.user_id,
+    )
+    .await?;
+    if !recorded {
+        return Err(AppError::ConflictError(
+            ErrorCode::ConcurrentModification,
+            "Shipment was changed by another request, please retry".to_string(),
+        ));
+    }
+
+    let shipment = Shipment::find_by_awb(&pool, &awb_number)
+        .await?
+        .ok_or_else(|| shipment_not_found(&awb_number))?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: tracked(&pool, shipment).await?,
+    }))
+}
+
+// Cargo booked on a flight and how much its aircraft takes (staff only)
+pub async fn get_flight_cargo(
+    State(pool): State<DbPool>,
+    _: RequireStaff,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<FlightCargo>>, AppError> {
+    let flight = Flight::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+    let aircraft = Aircraft::find_by_id(&pool, flight.aircraft_id)
+        .await?
+        .ok_or_else(|| aircraft_not_found(flight.aircraft_id))?;
+
+    let shipments = Shipment::find_by_flight(&pool, id).await?;
+    let booked = CargoLoad {
+        weight_kg: shipments.iter().map(|s| s.weight_kg).sum(),
+        volume_m3: shipments.iter().map(|s| s.volume_m3).sum(),
+    };
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: FlightCargo {
+            flight_id: id,
+            cargo_payload_kg: aircraft.cargo_payload_kg,
+            cargo_volume_m3: aircraft.cargo_volume_m3,
+            booked,
+            shipments,
+        },
+    }))
+}
+
+// Set how much freight an aircraft carries (admin only). Shipments already booked keep their
+// flights
+pub async fn set_aircraft_cargo_limits(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<CargoLimits>,
+) -> Result<Json<ApiResponse<Aircraft>>, AppError> {
+    if Aircraft::find_by_id(&pool, id).await?.is_none() {
+        return Err(aircraft_not_found(id));
+    }
+
+    Aircraft::set_cargo_limits(&pool, id, &payload).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "aircraft.cargo_limits_set",
+        "aircraft",
+        id,
+        serde_json::json!({
+            "cargo_payload_kg": payload.cargo_payload_kg,
+            "cargo_volume_m3": payload.cargo_volume_m3,
+        }),
+    )
+    .await;
+
+    let aircraft = Aircraft::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| aircraft_not_found(id))?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: aircraft,
+    }))
+}
@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{AuditLog, TravelCompanion, User};
+use crate::validation::ValidJson;
+
+// Grant request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct GrantCompanionRequest {
+    pub companion_id: i32,
+}
+
+fn user_not_found(id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::UserNotFound,
+        format!("User with id {} not found", id),
+    )
+}
+
+// Account holders the user lets book tickets in their name (owner or staff)
+pub async fn get_companions(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<TravelCompanion>>>, AppError> {
+    auth.require_owner(id)?;
+    let companions = TravelCompanion::find_by_user(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: companions,
+    }))
+}
+
+// Let another account holder book tickets in the user's name, e.g. in a group booking they
+// travel in (owner or staff)
+pub async fn grant_companion(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<GrantCompanionRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<TravelCompanion>>), AppError> {
+    auth.require_owner(id)?;
+    if payload.companion_id == id {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "A user always books for themselves".to_string(),
+        ));
+    }
+    for user_id in [id, payload.companion_id] {
+        if User::find_by_id(&pool, user_id).await?.is_none() {
+            return Err(user_not_found(user_id));
+        }
+    }
+
+    let companion = TravelCompanion::grant(&pool, id, payload.companion_id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "user.companion_granted",
+        "user",
+        id,
+        serde_json::json!({ "companion_id": payload.companion_id }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: companion,
+        }),
+    ))
+}
+
+// Withdraw the consent (owner or staff)
+pub async fn revoke_companion(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path((id, companion_id)): Path<(i32, i32)>,
+) -> Result<StatusCode, AppError> {
+    auth.require_owner(id)?;
+    if !TravelCompanion::revoke(&pool, id, companion_id).await? {
+        return Err(AppError::NotFound(
+            ErrorCode::CompanionNotFound,
+            format!(
+                "User {} has not allowed user {} to book for them",
+                id, companion_id
+            ),
+        ));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "user.companion_revoked",
+        "user",
+        id,
+        serde_json::json!({ "companion_id": companion_id }),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
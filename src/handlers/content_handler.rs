@@ -0,0 +1,149 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::guard::RequireAdmin;
+use crate::middleware::locale::PreferredLocales;
+use crate::models::{AuditLog, LocalizedText, Translation};
+use crate::validation::{not_blank, ValidJson};
+
+#[derive(Debug, Deserialize)]
+pub struct ContentQuery {
+    pub prefix: String,
+}
+
+// Set translation request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpsertTranslationRequest {
+    #[validate(custom(function = "not_blank"))]
+    pub value: String,
+}
+
+fn valid_locale(locale: &str) -> bool {
+    (2..=3).contains(&locale.len()) && locale.chars().all(|c| c.is_ascii_lowercase())
+}
+
+// Get a display text in the caller's language (Accept-Language, falling back to English)
+pub async fn get_content(
+    State(pool): State<DbPool>,
+    PreferredLocales(locales): PreferredLocales,
+    Path(key): Path<String>,
+) -> Result<Json<ApiResponse<LocalizedText>>, AppError> {
+    match Translation::resolve(&pool, &key, &locales).await {
+        Ok(Some(text)) => Ok(Json(ApiResponse {
+            success: true,
+            data: text,
+        })),
+        Ok(None) => Err(AppError::NotFound(
+            ErrorCode::ContentNotFound,
+            format!("Content {} not found", key),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Get all display texts under a key prefix, e.g. ?prefix=airport.
+pub async fn list_content(
+    State(pool): State<DbPool>,
+    PreferredLocales(locales): PreferredLocales,
+    Query(query): Query<ContentQuery>,
+) -> Result<Json<ApiResponse<Vec<LocalizedText>>>, AppError> {
+    let texts = Translation::resolve_prefix(&pool, &query.prefix, &locales).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: texts,
+    }))
+}
+
+// Get every translation of a key (admin only)
+pub async fn get_translations(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    Path(key): Path<String>,
+) -> Result<Json<ApiResponse<Vec<Translation>>>, AppError> {
+    let translations = Translation::find_by_key(&pool, &key).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: translations,
+    }))
+}
+
+// Create or update the translation of a key for one locale (admin only)
+pub async fn upsert_translation(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path((key, locale)): Path<(String, String)>,
+    ValidJson(payload): ValidJson<UpsertTranslationRequest>,
+) -> Result<Json<ApiResponse<Translation>>, AppError> {
+    let locale = locale.to_lowercase();
+    if !valid_locale(&locale) {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            format!("Invalid locale {}", locale),
+        ));
+    }
+    if key.trim().is_empty() {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "Content key is required".to_string(),
+        ));
+    }
+
+    Translation::upsert(&pool, &key, &locale, &payload.value).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "content.translation_updated",
+        "content",
+        format!("{}/{}", key, locale),
+        serde_json::Value::Null,
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Translation {
+            content_key: key,
+            locale,
+            value: payload.value,
+        },
+    }))
+}
+
+// Remove the translation of a key for one locale (admin only)
+pub async fn delete_translation(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path((key, locale)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    if !Translation::delete(&pool, &key, &locale.to_lowercase()).await? {
+        return Err(AppError::NotFound(
+            ErrorCode::ContentNotFound,
+            format!("No {} translation for {}", locale, key),
+        ));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "content.translation_deleted",
+        "content",
+        format!("{}/{}", key, locale.to_lowercase()),
+        serde_json::Value::Null,
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: serde_json::json!({}),
+    }))
+}
@@ -0,0 +1,332 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::response::ApiResponse;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::api_key::ReadCaller;
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireAdmin;
+use crate::models::crew::describe_conflicts;
+use crate::models::{
+    ApiScope, AuditLog, CrewMember, CrewRequirement, CrewRole, CrewShortfall, DutyRules, Flight,
+    FlightStatus, StaffPosition,
+};
+use crate::validation::{not_blank, ValidJson};
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CrewRequirementInput {
+    pub role: CrewRole,
+    #[validate(range(min = 0))]
+    pub min_count: i32,
+}
+
+// Set crew requirements request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateCrewRequirementsRequest {
+    #[validate(nested)]
+    pub requirements: Vec<CrewRequirementInput>,
+}
+
+// Assign crew request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct AssignCrewRequest {
+    pub crew_member_ids: Vec<i32>,
+}
+
+// Assign crew past the duty-time limits request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct OverrideCrewRequest {
+    pub crew_member_ids: Vec<i32>,
+    #[validate(custom(function = "not_blank"), length(max = 500))]
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlightCrew {
+    pub crew: Vec<CrewMember>,
+    pub complete: bool,
+    pub shortfalls: Vec<CrewShortfall>,
+}
+
+async fn load_flight_crew(pool: &DbPool, flight_id: i32) -> Result<FlightCrew, AppError> {
+    let crew = CrewMember::find_by_flight(pool, flight_id).await?;
+    let shortfalls = CrewRequirement::shortfalls_for_flight(pool, flight_id).await?;
+
+    Ok(FlightCrew {
+        crew,
+        complete: shortfalls.is_empty(),
+        shortfalls,
+    })
+}
+
+// Get the minimum crew of an aircraft model (staff or API key with crew:read)
+pub async fn get_crew_requirements(
+    State(pool): State<DbPool>,
+    caller: ReadCaller,
+    Path(model): Path<String>,
+) -> Result<Json<ApiResponse<Vec<CrewRequirement>>>, AppError> {
+    caller.require(ApiScope::Crew)?;
+
+    let requirements = CrewRequirement::find_by_model(&pool, &model).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: requirements,
+    }))
+}
+
+// Replace the minimum crew of an aircraft model (admin only)
+pub async fn update_crew_requirements(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(model): Path<String>,
+    ValidJson(payload): ValidJson<UpdateCrewRequirementsRequest>,
+) -> Result<Json<ApiResponse<Vec<CrewRequirement>>>, AppError> {
+    let mut roles = HashSet::new();
+    for requirement in &payload.requirements {
+        if !roles.insert(requirement.role) {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                format!("Role {} listed more than once", requirement.role.as_str()),
+            ));
+        }
+    }
+
+    let requirements: Vec<(CrewRole, i32)> = payload
+        .requirements
+        .iter()
+        .map(|r| (r.role, r.min_count))
+        .collect();
+    CrewRequirement::replace_for_model(&pool, &model, &requirements).await?;
+
+    let requirements = CrewRequirement::find_by_model(&pool, &model).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "aircraft_type.crew_requirements_updated",
+        "aircraft_type",
+        &model,
+        serde_json::json!({ "requirements": &requirements }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: requirements,
+    }))
+}
+
+// Get the crew assigned to a flight and whether it is complete (staff or API key with crew:read)
+pub async fn get_flight_crew(
+    State(pool): State<DbPool>,
+    caller: ReadCaller,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<FlightCrew>>, AppError> {
+    caller.require(ApiScope::Crew)?;
+
+    if Flight::find_by_id(&pool, id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let crew = load_flight_crew(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: crew,
+    }))
+}
+
+fn duty_rules(config: &Config) -> DutyRules {
+    DutyRules {
+        max_duty_per_day: Duration::hours(config.crew_max_duty_hours_per_day.into()),
+        max_duty_per_week: Duration::hours(config.crew_max_duty_hours_per_week.into()),
+        min_rest: Duration::hours(config.crew_min_rest_hours.into()),
+    }
+}
+
+// Replace the crew of a flight after checking it. Duty-time limits are only checked without an
+// override; what they would have refused is returned so the override can be audited
+async fn assign_crew(
+    pool: &DbPool,
+    config: &Config,
+    flight_id: i32,
+    crew_member_ids: &[i32],
+    actor_id: i32,
+    overridden: bool,
+) -> Result<Vec<String>, AppError> {
+    let flight = Flight::find_by_id(pool, flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(flight_id))?;
+
+    if matches!(
+        flight.status,
+        FlightStatus::Departed | FlightStatus::Arrived | FlightStatus::Cancelled
+    ) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Crew cannot be changed on a {} flight",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let unique: HashSet<i32> = crew_member_ids.iter().copied().collect();
+    if unique.len() != crew_member_ids.len() {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "Crew member listed more than once".to_string(),
+        ));
+    }
+
+    let members = CrewMember::find_by_ids(pool, crew_member_ids).await?;
+    if members.len() != unique.len() {
+        let found: HashSet<i32> = members.iter().map(|m| m.crew_member_id).collect();
+        let missing: Vec<String> = unique.difference(&found).map(|id| id.to_string()).collect();
+        return Err(AppError::ValidationError(
+            ErrorCode::UnknownCrewMember,
+            format!("Unknown crew members: {}", missing.join(", ")),
+        ));
+    }
+
+    let conflicts = CrewMember::conflicts(
+        pool,
+        flight_id,
+        crew_member_ids,
+        config.crew_turnaround_minutes,
+    )
+    .await?;
+    if !conflicts.is_empty() {
+        return Err(AppError::ConflictError(
+            ErrorCode::CrewScheduleConflict,
+            format!(
+                "Crew already assigned to overlapping flights: {}",
+                describe_conflicts(&conflicts)
+            ),
+        ));
+    }
+
+    let rules = duty_rules(config);
+    let duty = CrewMember::duty_around(pool, flight_id, crew_member_ids, rules.reach()).await?;
+    let violations: Vec<String> = members
+        .iter()
+        .flat_map(|member| {
+            let others: Vec<_> = duty
+                .iter()
+                .filter(|d| d.crew_member_id == member.crew_member_id)
+                .map(|d| (d.departure_time, d.arrival_time))
+                .collect();
+            rules
+                .violations((flight.departure_time, flight.arrival_time), &others)
+                .into_iter()
+                .map(|violation| {
+                    format!("{} {}: {}", member.first_name, member.last_name, violation)
+                })
+        })
+        .collect();
+    if !violations.is_empty() && !overridden {
+        return Err(AppError::ConflictError(
+            ErrorCode::CrewDutyLimitExceeded,
+            format!(
+                "Crew duty-time limits would be broken: {}",
+                violations.join("; ")
+            ),
+        ));
+    }
+
+    CrewMember::assign_to_flight(pool, flight_id, crew_member_ids, actor_id).await?;
+
+    Ok(violations)
+}
+
+// Replace the crew assigned to a flight (admin or dispatcher)
+pub async fn assign_flight_crew(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<AssignCrewRequest>,
+) -> Result<Json<ApiResponse<FlightCrew>>, AppError> {
+    auth.require_position(&[StaffPosition::Dispatcher])?;
+
+    assign_crew(
+        &pool,
+        &config,
+        id,
+        &payload.crew_member_ids,
+        auth.user_id,
+        false,
+    )
+    .await?;
+
+    let crew = load_flight_crew(&pool, id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.crew_assigned",
+        "flight",
+        id,
+        serde_json::json!({ "crew_member_ids": &payload.crew_member_ids }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: crew,
+    }))
+}
+
+// Assign crew despite the duty-time limits, e.g. to recover from a disruption (admin or dispatcher). The
+// limits it breaks are audited along with the reason given
+pub async fn override_flight_crew(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<OverrideCrewRequest>,
+) -> Result<Json<ApiResponse<FlightCrew>>, AppError> {
+    auth.require_position(&[StaffPosition::Dispatcher])?;
+
+    let violations = assign_crew(
+        &pool,
+        &config,
+        id,
+        &payload.crew_member_ids,
+        auth.user_id,
+        true,
+    )
+    .await?;
+
+    let crew = load_flight_crew(&pool, id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.crew_duty_overridden",
+        "flight",
+        id,
+        serde_json::json!({
+            "crew_member_ids": &payload.crew_member_ids,
+            "reason": payload.reason.trim(),
+            "violations": violations,
+        }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: crew,
+    }))
+}
@@ -0,0 +1,29 @@
+use axum::{extract::State, Json};
+use serde::Serialize;
+
+use super::response::ApiResponse;
+use crate::currencies::BASE_CURRENCY;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::models::ExchangeRate;
+
+#[derive(Debug, Serialize)]
+pub struct Currencies {
+    // The currency rates are quoted against
+    pub base: &'static str,
+    pub rates: Vec<ExchangeRate>,
+}
+
+// List the currencies prices can be converted into with `?currency=`, besides the base one
+pub async fn get_currencies(
+    State(pool): State<DbPool>,
+) -> Result<Json<ApiResponse<Currencies>>, AppError> {
+    let rates = ExchangeRate::find_all(&pool).await?;
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Currencies {
+            base: BASE_CURRENCY,
+            rates,
+        },
+    }))
+}
@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{AuditLog, DataExport, DataExportStatus};
+use crate::routes::CURRENT_PREFIX;
+
+#[derive(Debug, Serialize)]
+pub struct DataExportView {
+    #[serde(flatten)]
+    pub export: DataExport,
+    // Where the archive is fetched once `status` is ready
+    pub download_url: String,
+}
+
+fn view(export: DataExport) -> DataExportView {
+    DataExportView {
+        download_url: format!("{}/users/me/export/{}", CURRENT_PREFIX, export.export_id),
+        export,
+    }
+}
+
+// Ask for a copy of everything stored about the caller. The archive is built in the background;
+// 202 while it is, 200 once the returned token can be downloaded. Asking again returns the same
+// export until it expires
+pub async fn request_data_export(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+) -> Result<(StatusCode, Json<ApiResponse<DataExportView>>), AppError> {
+    let (export, created) = DataExport::request(&pool, auth.user_id).await?;
+
+    if created {
+        AuditLog::record(
+            &pool,
+            Some(auth.user_id),
+            "user.data_export_requested",
+            "user",
+            auth.user_id,
+            serde_json::json!({ "export_id": export.export_id }),
+        )
+        .await;
+    }
+
+    let status = match export.status {
+        DataExportStatus::Pending => StatusCode::ACCEPTED,
+        DataExportStatus::Ready | DataExportStatus::Failed => StatusCode::OK,
+    };
+    Ok((
+        status,
+        Json(ApiResponse {
+            success: true,
+            data: view(export),
+        }),
+    ))
+}
+
+// Download a finished export as a JSON file; while it is being built, its status with 202
+pub async fn download_data_export(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(token): Path<String>,
+) -> Result<Response, AppError> {
+    let not_found = || {
+        AppError::NotFound(
+            ErrorCode::DataExportNotFound,
+            "Data export not found".to_string(),
+        )
+    };
+    let export = DataExport::find(&pool, auth.user_id, &token)
+        .await?
+        .ok_or_else(not_found)?;
+
+    if export.status != DataExportStatus::Ready {
+        let status = match export.status {
+            DataExportStatus::Pending => StatusCode::ACCEPTED,
+            _ => StatusCode::OK,
+        };
+        let body = ApiResponse {
+            success: true,
+            data: view(export),
+        };
+        return Ok((status, Json(body)).into_response());
+    }
+
+    let archive = DataExport::archive(&pool, &token)
+        .await?
+        .ok_or_else(not_found)?;
+    let disposition = format!(
+        "attachment; filename=\"user-{}-data-export.json\"",
+        auth.user_id
+    );
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_str(&disposition).expect("ASCII file name"),
+            ),
+        ],
+        archive,
+    )
+        .into_response())
+}
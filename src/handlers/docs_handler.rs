@@ -0,0 +1,30 @@
+use axum::{response::Html, Json};
+use serde_json::Value;
+
+use crate::openapi;
+
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Airlines API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>"##;
+
+// Machine-readable OpenAPI contract of every route
+pub async fn get_openapi() -> Json<Value> {
+    Json(openapi::document())
+}
+
+// Swagger UI rendering /api/openapi.json
+pub async fn swagger_ui() -> Html<&'static str> {
+    Html(SWAGGER_UI)
+}
@@ -0,0 +1,255 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use sha2::{Digest, Sha256};
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use super::response::ApiResponse;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::file_type;
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireStaff;
+use crate::models::{AuditLog, DocumentType, NewUserDocument, User, UserDocument};
+use crate::multipart::{self, Form};
+use crate::storage::ObjectStorage;
+use crate::virus_scan::{ScanVerdict, VirusScanner};
+
+// Room for the multipart framing and the other form fields on top of the file itself
+pub const FORM_OVERHEAD_BYTES: usize = 64 * 1024;
+
+fn invalid(code: ErrorCode, message: impl Into<String>) -> AppError {
+    AppError::ValidationError(code, message.into())
+}
+
+fn storage_failed(e: impl std::fmt::Display) -> AppError {
+    AppError::InternalError(format!("Document storage failed: {}", e))
+}
+
+// The uploaded name without any directories, for display and the download's file name
+fn file_name(name: Option<&str>) -> String {
+    let name = name
+        .and_then(|name| name.rsplit(['/', '\\']).next())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .unwrap_or("document");
+    name.chars().take(255).collect()
+}
+
+// Upload an identity document for a user (the user themselves or staff), as the `file` field of
+// a multipart form with its `document_type` (passport, national_id, visa or other). PDF, JPEG and
+// PNG files are accepted; each is scanned for malware before it is stored
+pub async fn upload_document(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    Extension(storage): Extension<Arc<dyn ObjectStorage>>,
+    Extension(scanner): Extension<Arc<dyn VirusScanner>>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    Form(parts): Form,
+) -> Result<(StatusCode, Json<ApiResponse<UserDocument>>), AppError> {
+    auth.require_owner(id)?;
+    if User::find_by_id(&pool, id).await?.is_none() {
+        return Err(AppError::NotFound(
+            ErrorCode::UserNotFound,
+            format!("User with id {} not found", id),
+        ));
+    }
+
+    let document_type = parts
+        .iter()
+        .find(|part| part.name == "document_type")
+        .and_then(|part| std::str::from_utf8(&part.data).ok())
+        .and_then(|name| DocumentType::from_name(name.trim()))
+        .ok_or_else(|| {
+            invalid(
+                ErrorCode::ValidationFailed,
+                "document_type must be one of: passport, national_id, visa, other",
+            )
+        })?;
+    let file = multipart::take(parts, "file")
+        .ok_or_else(|| invalid(ErrorCode::ValidationFailed, "The form has no `file` field"))?;
+
+    if file.data.is_empty() {
+        return Err(invalid(ErrorCode::ValidationFailed, "The file is empty"));
+    }
+    if file.data.len() > config.document_max_bytes {
+        return Err(invalid(
+            ErrorCode::FileTooLarge,
+            format!(
+                "Documents can be at most {} bytes",
+                config.document_max_bytes
+            ),
+        ));
+    }
+    let content_type = file_type::sniff(&file.data).ok_or_else(|| {
+        invalid(
+            ErrorCode::UnsupportedFileType,
+            "Documents must be PDF, JPEG or PNG files",
+        )
+    })?;
+    if file_type::contradicts(file.content_type.as_deref(), content_type) {
+        return Err(invalid(
+            ErrorCode::UnsupportedFileType,
+            format!("The file is {}, not the declared type", content_type),
+        ));
+    }
+
+    match scanner.scan(&file.data).await {
+        Ok(ScanVerdict::Clean) => {}
+        Ok(ScanVerdict::Infected(signature)) => {
+            warn!("Rejected document upload for user {}: {}", id, signature);
+            AuditLog::record(
+                &pool,
+                Some(auth.user_id),
+                "user.document_rejected",
+                "user",
+                id,
+                serde_json::json!({ "signature": signature }),
+            )
+            .await;
+            return Err(invalid(
+                ErrorCode::FileRejected,
+                "The file was rejected by the malware scan",
+            ));
+        }
+        Err(e) => {
+            error!("Virus scan failed: {}", e);
+            return Err(AppError::InternalError(
+                "The file could not be scanned".to_string(),
+            ));
+        }
+    }
+
+    let storage_key = format!("documents/{}/{}", id, Uuid::new_v4().simple());
+    let new_document = NewUserDocument {
+        user_id: id,
+        document_type,
+        file_name: file_name(file.filename.as_deref()),
+        content_type: content_type.to_string(),
+        size_bytes: file.data.len() as i32,
+        sha256: hex::encode(Sha256::digest(&file.data)),
+        storage_key: storage_key.clone(),
+        uploaded_by: auth.user_id,
+    };
+    storage
+        .put(&storage_key, file.data, content_type)
+        .await
+        .map_err(storage_failed)?;
+
+    let document = match UserDocument::insert(&pool, &new_document).await {
+        Ok(document) => document,
+        Err(e) => {
+            if let Err(e) = storage.delete(&storage_key).await {
+                error!("Failed to remove orphaned document {}: {}", storage_key, e);
+            }
+            return Err(e.into());
+        }
+    };
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "user.document_uploaded",
+        "user",
+        id,
+        serde_json::json!({
+            "document_id": document.document_id,
+            "document_type": document.document_type,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: document,
+        }),
+    ))
+}
+
+// A user's identity documents, newest first (staff only)
+pub async fn get_documents(
+    State(pool): State<DbPool>,
+    _: RequireStaff,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<UserDocument>>>, AppError> {
+    let documents = UserDocument::find_by_user(&pool, id).await?;
+    Ok(Json(ApiResponse {
+        success: true,
+        data: documents,
+    }))
+}
+
+// Download a document (staff only); every view is audited
+pub async fn download_document(
+    State(pool): State<DbPool>,
+    Extension(storage): Extension<Arc<dyn ObjectStorage>>,
+    auth: RequireStaff,
+    Path((id, document_id)): Path<(i32, i32)>,
+) -> Result<Response, AppError> {
+    let not_found = || {
+        AppError::NotFound(
+            ErrorCode::DocumentNotFound,
+            format!("Document with id {} not found", document_id),
+        )
+    };
+    let document = UserDocument::find(&pool, id, document_id)
+        .await?
+        .ok_or_else(not_found)?;
+    let data = storage
+        .get(&document.storage_key)
+        .await
+        .map_err(storage_failed)?
+        .ok_or_else(|| storage_failed(format!("{} is missing", document.storage_key)))?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "user.document_viewed",
+        "user",
+        id,
+        serde_json::json!({ "document_id": document_id }),
+    )
+    .await;
+
+    let ascii_name: String = document
+        .file_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' && c != '\\' || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let disposition = format!("attachment; filename=\"{}\"", ascii_name);
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_str(&document.content_type).map_err(storage_failed)?,
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_str(&disposition).map_err(storage_failed)?,
+            ),
+            (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+            (
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ),
+        ],
+        data,
+    )
+        .into_response())
+}
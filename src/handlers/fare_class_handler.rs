@@ -0,0 +1,404 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    Extension, Json,
+};
+use chrono::{DateTime, Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::passenger_handler::passenger_fares;
+use super::response::ApiResponse;
+use crate::config::Config;
+use crate::currencies::{ExchangeRates, BASE_CURRENCY};
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::guard::RequireStaff;
+use crate::models::{
+    AuditLog, FareClassInventory, FareOption, Flight, FlightFareClass, FlightFilter, PassengerType,
+};
+use crate::pricing::{PricingContext, PricingEngine};
+use crate::validation::ValidJson;
+
+// Configure fare classes request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateFareClassesRequest {
+    #[validate(nested)]
+    pub classes: Vec<FareClassInventory>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CurrencyQuery {
+    // Show prices in this currency instead of the one they are set in
+    pub currency: Option<String>,
+}
+
+// Fare class with the price currently charged for it, for an adult, a child or an infant in a
+// seat of their own, and an infant on a lap
+#[derive(Debug, Serialize)]
+pub struct PricedFareClass {
+    #[serde(flatten)]
+    pub class: FlightFareClass,
+    pub current_price: f64,
+    pub child_price: f64,
+    pub lap_infant_price: f64,
+}
+
+// Days either side of the requested one a flexible-dates search covers at most
+const MAX_FLEX_DAYS: i64 = 7;
+
+// One day of a flexible-dates search; `cheapest` is None when nothing with a seat left departs
+#[derive(Debug, Serialize)]
+pub struct FareDay {
+    pub date: NaiveDate,
+    pub cheapest: Option<FareOption>,
+}
+
+// Adult fare the pricing strategy charges for a class right now
+pub(crate) fn current_price(
+    pricing: &PricingEngine,
+    class: &FlightFareClass,
+    days_until_departure: i64,
+) -> f64 {
+    let load_factor = if class.seat_count > 0 {
+        class.seats_sold as f64 / class.seat_count as f64
+    } else {
+        1.0
+    };
+    pricing.quote(&PricingContext {
+        base_fare: class.price,
+        days_until_departure,
+        load_factor,
+    })
+}
+
+// Get fare classes of a flight with remaining seats and current price per class, in the
+// currency each class is priced in or converted to `?currency=`
+pub async fn get_fare_classes(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    Extension(pricing): Extension<PricingEngine>,
+    Path(id): Path<i32>,
+    Query(query): Query<CurrencyQuery>,
+) -> Result<Json<ApiResponse<Vec<PricedFareClass>>>, AppError> {
+    let flight = Flight::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+
+    let mut classes = FlightFareClass::find_by_flight(&pool, id).await?;
+    if let Some(currency) = query.currency {
+        let rates = ExchangeRates::load(&pool).await?;
+        let currency = rates.requested(&currency)?;
+        for class in &mut classes {
+            class.price = rates
+                .convert(class.price, &class.currency, &currency)
+                .ok_or_else(|| {
+                    AppError::InternalError(format!(
+                        "No exchange rate for {}, the currency of {} on flight {}",
+                        class.currency,
+                        class.fare_class.as_str(),
+                        id
+                    ))
+                })?;
+            class.currency = currency.clone();
+        }
+    }
+
+    let days_until_departure = (flight.departure_time - Utc::now()).num_days();
+    let fares = passenger_fares(&config);
+    let priced = classes
+        .into_iter()
+        .map(|class| {
+            let current_price = current_price(&pricing, &class, days_until_departure);
+            PricedFareClass {
+                class,
+                current_price,
+                child_price: fares.fare(current_price, PassengerType::Child),
+                lap_infant_price: fares.lap_infant_fare(current_price),
+            }
+        })
+        .collect();
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: priced,
+    }))
+}
+
+// Replace the fare class configuration of a flight (staff only)
+pub async fn update_fare_classes(
+    State(pool): State<DbPool>,
+    auth: RequireStaff,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<UpdateFareClassesRequest>,
+) -> Result<Json<ApiResponse<Vec<FlightFareClass>>>, AppError> {
+    if Flight::find_by_id(&pool, id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let mut seen = HashSet::new();
+    for class in &payload.classes {
+        if !seen.insert(class.fare_class) {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                format!(
+                    "Fare class {} listed more than once",
+                    class.fare_class.as_str()
+                ),
+            ));
+        }
+    }
+
+    let capacity = FlightFareClass::aircraft_capacity(&pool, id).await?;
+    let total_seats: i32 = payload.classes.iter().map(|class| class.seat_count).sum();
+    if total_seats > capacity {
+        return Err(AppError::ValidationError(
+            ErrorCode::CapacityExceeded,
+            format!(
+                "Fare classes have {} seats but the aircraft only has {}",
+                total_seats, capacity
+            ),
+        ));
+    }
+
+    // Never shrink a class below what has already been sold
+    let current = FlightFareClass::find_by_flight(&pool, id).await?;
+    for existing in current.iter().filter(|class| class.seats_sold > 0) {
+        let new_count = payload
+            .classes
+            .iter()
+            .find(|class| class.fare_class == existing.fare_class)
+            .map_or(0, |class| class.seat_count);
+        if (new_count as i64) < existing.seats_sold {
+            return Err(AppError::ConflictError(
+                ErrorCode::ClassOversold,
+                format!(
+                    "{} already has {} seats sold",
+                    existing.fare_class.as_str(),
+                    existing.seats_sold
+                ),
+            ));
+        }
+    }
+
+    FlightFareClass::replace_for_flight(&pool, id, &payload.classes).await?;
+
+    let classes = FlightFareClass::find_by_flight(&pool, id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.fare_classes_updated",
+        "flight",
+        id,
+        serde_json::json!({ "classes": &classes }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: classes,
+    }))
+}
+
+// Flexible-dates search of `GET /flights?flex_days=`: every day `flex_days` either side of
+// `date` with the cheapest fare on the route that day, at the adult price currently charged for
+// it, in the class's currency or `currency`
+pub(crate) async fn cheapest_by_day(
+    pool: &DbPool,
+    pricing: &PricingEngine,
+    filter: &FlightFilter,
+    flex_days: i64,
+) -> Result<Vec<FareDay>, AppError> {
+    let invalid =
+        |message: &str| AppError::ValidationError(ErrorCode::ValidationFailed, message.to_string());
+    if !(0..=MAX_FLEX_DAYS).contains(&flex_days) {
+        return Err(invalid(&format!(
+            "flex_days must be between 0 and {}",
+            MAX_FLEX_DAYS
+        )));
+    }
+    let (Some(origin), Some(destination), Some(date)) =
+        (&filter.origin, &filter.destination, filter.date)
+    else {
+        return Err(invalid("flex_days needs origin, destination and date"));
+    };
+    let flex = Days::new(flex_days as u64);
+    let first = date.checked_sub_days(flex).unwrap_or(date);
+    let last = date.checked_add_days(flex).unwrap_or(date);
+
+    let rates = ExchangeRates::load(pool).await?;
+    let currency = match &filter.currency {
+        Some(currency) => Some(rates.requested(currency)?),
+        None => None,
+    };
+
+    let options = FareOption::find_by_days(pool, origin, destination, first, last).await?;
+    cheapest_per_day(
+        options,
+        pricing,
+        &rates,
+        currency.as_deref(),
+        Utc::now(),
+        first,
+        last,
+    )
+}
+
+// Prices every option as charged at `now` and keeps the cheapest of each day, `first` through
+// `last`. Options are compared in `currency`, or euros when none was requested; those in a
+// currency without a rate come last. The pick cannot be a GROUP BY in SQL: the price charged
+// depends on the pricing strategy (demand pricing weighs the load factor and the days left to
+// departure) and on the exchange rates, and neither is known to the database, so the lowest
+// base fare of a day is often not the cheapest fare
+fn cheapest_per_day(
+    options: Vec<FareOption>,
+    pricing: &PricingEngine,
+    rates: &ExchangeRates,
+    currency: Option<&str>,
+    now: DateTime<Utc>,
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Result<Vec<FareDay>, AppError> {
+    let ranking_currency = currency.unwrap_or(BASE_CURRENCY);
+    let mut cheapest: HashMap<NaiveDate, (Option<f64>, FareOption)> = HashMap::new();
+    for mut option in options {
+        let class = FlightFareClass {
+            flight_id: option.flight_id,
+            fare_class: option.fare_class,
+            seat_count: option.seat_count,
+            price: option.price,
+            currency: option.currency.clone(),
+            seats_sold: option.seats_sold,
+            seats_available: option.seats_available,
+        };
+        let days_until_departure = (option.departure_time - now).num_days();
+        option.price = current_price(pricing, &class, days_until_departure);
+        let rank = rates.convert(option.price, &option.currency, ranking_currency);
+
+        let cheaper = match cheapest.get(&option.departure_date) {
+            None => true,
+            Some((best, _)) => match (rank, best) {
+                (Some(rank), Some(best)) => rank < *best,
+                (Some(_), None) => true,
+                (None, _) => false,
+            },
+        };
+        if cheaper {
+            cheapest.insert(option.departure_date, (rank, option));
+        }
+    }
+
+    first
+        .iter_days()
+        .take_while(|day| *day <= last)
+        .map(|day| {
+            let cheapest = match cheapest.remove(&day) {
+                Some((rank, mut fare)) => {
+                    if let Some(currency) = currency {
+                        fare.price = rank.ok_or_else(|| {
+                            AppError::InternalError(format!(
+                                "No exchange rate for {}, the currency of {} on flight {}",
+                                fare.currency,
+                                fare.fare_class.as_str(),
+                                fare.flight_id
+                            ))
+                        })?;
+                        fare.currency = currency.to_string();
+                    }
+                    Some(fare)
+                }
+                None => None,
+            };
+            Ok(FareDay {
+                date: day,
+                cheapest,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FareClass;
+    use crate::pricing::{DemandPricing, FixedPricing};
+    use chrono::{Duration, TimeZone};
+    use std::sync::Arc;
+
+    fn option(flight_id: i32, departure_time: DateTime<Utc>, price: f64, sold: i64) -> FareOption {
+        FareOption {
+            departure_date: departure_time.date_naive(),
+            flight_id,
+            flight_number: format!("PS{}", flight_id),
+            departure_time,
+            arrival_time: departure_time + Duration::hours(2),
+            fare_class: FareClass::Economy,
+            seat_count: 8,
+            seats_sold: sold,
+            seats_available: 8 - sold,
+            price,
+            currency: "EUR".into(),
+        }
+    }
+
+    #[test]
+    fn demand_pricing_decides_the_cheapest_fare_of_a_day() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        let morning = now + Duration::days(20) - Duration::hours(4);
+        let evening = morning + Duration::hours(8);
+        let day = morning.date_naive();
+        let pricing = PricingEngine::new(Arc::new(DemandPricing));
+        let rates = ExchangeRates::new([("UAH".to_string(), 45.0)]);
+
+        // The morning flight has the lower base fare but is nearly full
+        let options = vec![option(1, morning, 100.0, 7), option(2, evening, 110.0, 0)];
+        let days = cheapest_per_day(
+            options.clone(),
+            &pricing,
+            &rates,
+            None,
+            now,
+            day.pred_opt().unwrap(),
+            day,
+        )
+        .unwrap();
+
+        assert_eq!(days.len(), 2);
+        assert!(days[0].cheapest.is_none());
+        let cheapest = days[1].cheapest.as_ref().unwrap();
+        assert_eq!(cheapest.flight_id, 2);
+        assert_eq!(cheapest.price, 126.5);
+        assert_eq!(cheapest.currency, "EUR");
+
+        let days = cheapest_per_day(options, &pricing, &rates, Some("UAH"), now, day, day).unwrap();
+        let cheapest = days[0].cheapest.as_ref().unwrap();
+        assert_eq!(cheapest.flight_id, 2);
+        assert_eq!(cheapest.price, 5692.5);
+        assert_eq!(cheapest.currency, "UAH");
+    }
+
+    #[test]
+    fn fares_in_other_currencies_are_compared_converted() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        let departure = now + Duration::days(40);
+        let day = departure.date_naive();
+        let pricing = PricingEngine::new(Arc::new(FixedPricing));
+        let rates = ExchangeRates::new([("UAH".to_string(), 45.0)]);
+
+        let mut hryvnia = option(1, departure, 4000.0, 0);
+        hryvnia.currency = "UAH".into();
+        let mut unknown = option(2, departure, 1.0, 0);
+        unknown.currency = "XTS".into();
+        let options = vec![unknown, option(3, departure, 100.0, 0), hryvnia];
+
+        let days = cheapest_per_day(options, &pricing, &rates, None, now, day, day).unwrap();
+        let cheapest = days[0].cheapest.as_ref().unwrap();
+        // 4000 UAH is about 88.89 EUR; the fare without a rate cannot be ranked
+        assert_eq!(cheapest.flight_id, 1);
+        assert_eq!(cheapest.price, 4000.0);
+        assert_eq!(cheapest.currency, "UAH");
+    }
+}
@@ -0,0 +1,438 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tracing::{error, info};
+use validator::Validate;
+
+use super::fare_class_handler::cheapest_by_day;
+use super::response::{
+    cursor_limit, decode_cursor, ApiResponse, CursorResponse, ETag, ListResponse,
+    PaginatedResponse, Pagination,
+};
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::export::{self, ExportFormat};
+use crate::jobs::miles_accrual::AccrueFlightMiles;
+use crate::jobs::queue;
+use crate::live::FlightUpdates;
+use crate::middleware::api_key::ReadCaller;
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireStaff;
+use crate::models::crew::describe_shortfalls;
+use crate::models::{
+    Aircraft, ApiScope, AuditLog, CrewRequirement, FlightEvent, FlightFilter, FlightSort,
+    FlightStatus, FlightStatusChange, MaintenanceRecord, ManifestEntry, NewFlight, Route,
+    StaffPosition,
+};
+use crate::pricing::PricingEngine;
+use crate::repositories::FlightRepository;
+use crate::validation::{not_blank, ValidJson};
+
+// Schedule flight request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct ScheduleFlightRequest {
+    #[validate(custom(function = "not_blank"), length(max = 10))]
+    pub flight_number: String,
+    pub route_id: i32,
+    pub aircraft_id: i32,
+    pub departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+    #[validate(length(max = 10))]
+    pub gate: Option<String>,
+}
+
+// Set code-shares request body: every marketing flight number of the flight
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetCodesharesRequest {
+    #[validate(length(max = 20))]
+    pub marketing_flight_numbers: Vec<String>,
+}
+
+// Update flight status request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateFlightStatusRequest {
+    pub status: FlightStatus,
+}
+
+pub(crate) fn flight_not_found(id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::FlightNotFound,
+        format!("Flight with id {} not found", id),
+    )
+}
+
+// Get flights filtered by route, flight number (code-shares included), status, departure window
+// and day, sorted by `sort` and `order`; by page or with a `cursor` (keyset pagination, departure
+// order only), or all of them as a CSV or Excel download (`format=` or the Accept header). With
+// `flex_days`, the cheapest fare of each day around `date` instead
+pub async fn get_flights(
+    State(pool): State<DbPool>,
+    State(repository): State<Arc<dyn FlightRepository>>,
+    Extension(pricing): Extension<PricingEngine>,
+    headers: HeaderMap,
+    Query(filter): Query<FlightFilter>,
+) -> Result<Response, AppError> {
+    if let Some(flex_days) = filter.flex_days {
+        let days = cheapest_by_day(&pool, &pricing, &filter, flex_days).await?;
+        return Ok(Json(ApiResponse {
+            success: true,
+            data: days,
+        })
+        .into_response());
+    }
+
+    let format = ExportFormat::negotiate(filter.format, &headers);
+    if format != ExportFormat::Json {
+        return Ok(export::download(
+            format,
+            "flights",
+            move |rows| async move { repository.export(&filter, rows).await },
+        ));
+    }
+
+    if let Some(cursor) = &filter.cursor {
+        if filter.sort.unwrap_or_default() != FlightSort::DepartureTime {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                "Cursor pagination only supports sort=departure_time".to_string(),
+            ));
+        }
+        let limit = cursor_limit(filter.limit);
+        let flights = repository
+            .find_after(&filter, decode_cursor(cursor)?, limit + 1)
+            .await?;
+        return Ok(Json(ListResponse::Cursor(CursorResponse::from_rows(
+            flights,
+            limit,
+            |flight| (flight.departure_time, flight.flight_id),
+        )))
+        .into_response());
+    }
+
+    let page = filter.page.unwrap_or(1);
+    let limit = filter.limit.unwrap_or(10);
+
+    let flights = repository.find_all(&filter, page, limit).await?;
+    let total = repository.count(&filter).await?;
+
+    let total_pages = (total as f64 / limit as f64).ceil() as i32;
+
+    Ok(Json(ListResponse::Paginated(PaginatedResponse {
+        success: true,
+        count: flights.len(),
+        pagination: Pagination {
+            page,
+            limit,
+            total_pages,
+            total_items: total,
+        },
+        data: flights,
+    }))
+    .into_response())
+}
+
+// Get flight by id; 304 while If-None-Match is current
+pub async fn get_flight_by_id(
+    State(repository): State<Arc<dyn FlightRepository>>,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    match repository.find_by_id(id).await {
+        Ok(Some(flight)) => Ok(ETag::from_updated_at(flight.updated_at).respond(&headers, flight)),
+        Ok(None) => Err(flight_not_found(id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Schedule a flight (admin or dispatcher). The aircraft must not be grounded for maintenance at
+// any point between departure and arrival
+pub async fn schedule_flight(
+    State(pool): State<DbPool>,
+    State(repository): State<Arc<dyn FlightRepository>>,
+    auth: AuthUser,
+    ValidJson(payload): ValidJson<ScheduleFlightRequest>,
+) -> Result<Response, AppError> {
+    auth.require_position(&[StaffPosition::Dispatcher])?;
+
+    if payload.arrival_time <= payload.departure_time {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "arrival_time must be after departure_time".to_string(),
+        ));
+    }
+    if Route::find_by_id(&pool, payload.route_id).await?.is_none() {
+        return Err(AppError::ValidationError(
+            ErrorCode::RouteNotFound,
+            format!("Route with id {} not found", payload.route_id),
+        ));
+    }
+    if Aircraft::find_by_id(&pool, payload.aircraft_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::ValidationError(
+            ErrorCode::AircraftNotFound,
+            format!("Aircraft with id {} not found", payload.aircraft_id),
+        ));
+    }
+
+    if let Some(maintenance) = MaintenanceRecord::grounding(
+        &pool,
+        payload.aircraft_id,
+        payload.departure_time,
+        payload.arrival_time,
+    )
+    .await?
+    {
+        return Err(AppError::ConflictError(
+            ErrorCode::AircraftGrounded,
+            format!(
+                "Aircraft {} is grounded for maintenance from {} until {}",
+                payload.aircraft_id,
+                maintenance.grounded_from.to_rfc3339(),
+                maintenance.grounded_to.to_rfc3339()
+            ),
+        ));
+    }
+
+    let flight_id = repository
+        .insert(
+            &NewFlight {
+                flight_number: payload.flight_number.trim().to_uppercase(),
+                route_id: payload.route_id,
+                aircraft_id: payload.aircraft_id,
+                departure_time: payload.departure_time,
+                arrival_time: payload.arrival_time,
+                gate: payload.gate,
+            },
+            Some(auth.user_id),
+        )
+        .await?;
+    let flight = repository
+        .find_by_id(flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(flight_id))?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.scheduled",
+        "flight",
+        flight_id,
+        serde_json::json!({
+            "flight_number": &flight.flight_number,
+            "aircraft_id": flight.aircraft_id,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        ETag::from_updated_at(flight.updated_at).tag(flight),
+    )
+        .into_response())
+}
+
+// Change flight status, enforcing the allowed transitions; 412 if If-Match names an older
+// version of the flight
+pub async fn update_flight_status(
+    State(pool): State<DbPool>,
+    State(repository): State<Arc<dyn FlightRepository>>,
+    Extension(live_updates): Extension<FlightUpdates>,
+    auth: RequireStaff,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    ValidJson(payload): ValidJson<UpdateFlightStatusRequest>,
+) -> Result<Response, AppError> {
+    let flight = repository
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+    ETag::from_updated_at(flight.updated_at).check_if_match(&headers)?;
+
+    if !flight.status.can_transition_to(payload.status) {
+        return Err(AppError::ValidationError(
+            ErrorCode::InvalidStatusTransition,
+            format!(
+                "Invalid status transition from {} to {}",
+                flight.status.as_str(),
+                payload.status.as_str()
+            ),
+        ));
+    }
+
+    // Boarding may only start once the aircraft's minimum crew is assigned
+    if payload.status == FlightStatus::Boarding {
+        let shortfalls = CrewRequirement::shortfalls_for_flight(&pool, id).await?;
+        if !shortfalls.is_empty() {
+            return Err(AppError::ConflictError(
+                ErrorCode::CrewIncomplete,
+                format!(
+                    "Cannot start boarding, crew is incomplete: {}",
+                    describe_shortfalls(&shortfalls)
+                ),
+            ));
+        }
+    }
+
+    let updated = repository
+        .update_status(id, flight.status, payload.status, Some(auth.user_id))
+        .await?;
+
+    if !updated {
+        return Err(AppError::ConflictError(
+            ErrorCode::ConcurrentModification,
+            "Flight status was changed by another request, please retry".to_string(),
+        ));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.status_changed",
+        "flight",
+        id,
+        serde_json::json!({ "from": flight.status, "to": payload.status }),
+    )
+    .await;
+
+    // Read back for the new updated_at, and so the new ETag
+    let flight = repository
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+    live_updates.publish(&flight);
+
+    // Cancellations and delays were written to the outbox with the status change; miles are left
+    // to a background job
+    if payload.status == FlightStatus::Arrived {
+        if let Err(e) = queue::enqueue(&pool, &AccrueFlightMiles { flight_id: id }).await {
+            error!("Failed to queue miles accrual for flight {}: {}", id, e);
+        }
+    }
+
+    Ok(ETag::from_updated_at(flight.updated_at).tag(flight))
+}
+
+// Replace the marketing flight numbers other carriers sell the flight under (admin or
+// dispatcher)
+pub async fn set_flight_codeshares(
+    State(pool): State<DbPool>,
+    State(repository): State<Arc<dyn FlightRepository>>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<SetCodesharesRequest>,
+) -> Result<Response, AppError> {
+    auth.require_position(&[StaffPosition::Dispatcher])?;
+
+    let flight = repository
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+
+    let mut numbers: Vec<String> = Vec::new();
+    for number in &payload.marketing_flight_numbers {
+        let number = number.trim().to_uppercase();
+        if number.is_empty() || number.len() > 10 {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                "Flight numbers must have 1 to 10 characters".to_string(),
+            ));
+        }
+        if number == flight.flight_number || numbers.contains(&number) {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                format!("Flight number {} listed more than once", number),
+            ));
+        }
+        numbers.push(number);
+    }
+
+    repository.set_marketing_numbers(id, &numbers).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.codeshares_updated",
+        "flight",
+        id,
+        serde_json::json!({
+            "from": &flight.marketing_flight_numbers,
+            "to": &numbers,
+        }),
+    )
+    .await;
+
+    let flight = repository
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+
+    Ok(ETag::from_updated_at(flight.updated_at).tag(flight))
+}
+
+// Get the status history of a flight
+pub async fn get_flight_status_history(
+    State(repository): State<Arc<dyn FlightRepository>>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<FlightStatusChange>>>, AppError> {
+    if repository.find_by_id(id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let history = repository.status_history(id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: history,
+    }))
+}
+
+// Get the passenger manifest of a flight (staff or API key with manifests:read)
+pub async fn get_flight_manifest(
+    State(repository): State<Arc<dyn FlightRepository>>,
+    caller: ReadCaller,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<ManifestEntry>>>, AppError> {
+    caller.require(ApiScope::Manifests)?;
+    info!("{} requested manifest of flight {}", caller, id);
+
+    if repository.find_by_id(id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let manifest = repository.manifest(id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: manifest,
+    }))
+}
+
+// Get the chronological event feed of a flight (staff or API key with flights:read)
+pub async fn get_flight_timeline(
+    State(pool): State<DbPool>,
+    State(repository): State<Arc<dyn FlightRepository>>,
+    caller: ReadCaller,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<FlightEvent>>>, AppError> {
+    caller.require(ApiScope::Flights)?;
+
+    if repository.find_by_id(id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let events = FlightEvent::timeline(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: events,
+    }))
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::response::ApiResponse;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::live::FlightUpdates;
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::{RequireAdmin, RequireStaff};
+use crate::models::gate::describe_clashes;
+use crate::models::{AuditLog, Flight, FlightStatus, Gate, NewGate, Route, StaffPosition};
+use crate::repositories::FlightRepository;
+use crate::validation::ValidJson;
+
+// Assign gate request body; null takes the flight's gate away
+#[derive(Debug, Deserialize, Validate)]
+pub struct AssignGateRequest {
+    pub gate_id: Option<i32>,
+}
+
+// Airport codes are IATA codes, matched in capitals
+fn airport_code(code: &str) -> Result<String, AppError> {
+    let code = code.trim().to_uppercase();
+    if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            format!("{} is not an IATA airport code", code),
+        ));
+    }
+    Ok(code)
+}
+
+// List the gates of an airport (staff)
+pub async fn get_airport_gates(
+    State(pool): State<DbPool>,
+    _: RequireStaff,
+    Path(code): Path<String>,
+) -> Result<Json<ApiResponse<Vec<Gate>>>, AppError> {
+    let gates = Gate::find_by_airport(&pool, &airport_code(&code)?).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: gates,
+    }))
+}
+
+// Add a gate to an airport (admin only)
+pub async fn create_gate(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(code): Path<String>,
+    ValidJson(payload): ValidJson<NewGate>,
+) -> Result<(StatusCode, Json<ApiResponse<Gate>>), AppError> {
+    let code = airport_code(&code)?;
+    let gate = Gate::create(&pool, &code, &payload).await.map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|db| db.is_unique_violation())
+        {
+            AppError::ConflictError(
+                ErrorCode::GateExists,
+                format!("{} already has a gate {}", code, payload.gate_code.trim()),
+            )
+        } else {
+            e.into()
+        }
+    })?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "gate.created",
+        "gate",
+        gate.gate_id,
+        serde_json::json!({
+            "airport_code": &gate.airport_code,
+            "gate_code": &gate.gate_code,
+            "terminal": &gate.terminal,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: gate,
+        }),
+    ))
+}
+
+// Give a flight a gate of its origin airport (admin, dispatcher or gate agent). The gate must
+// not be held by another flight while this one boards
+pub async fn assign_flight_gate(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    State(repository): State<Arc<dyn FlightRepository>>,
+    Extension(live_updates): Extension<FlightUpdates>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<AssignGateRequest>,
+) -> Result<Json<ApiResponse<Flight>>, AppError> {
+    auth.require_position(&[StaffPosition::Dispatcher, StaffPosition::GateAgent])?;
+
+    let flight = repository
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+    if matches!(
+        flight.status,
+        FlightStatus::Departed | FlightStatus::Arrived | FlightStatus::Cancelled
+    ) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "The gate cannot be changed on a {} flight",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let gate = match payload.gate_id {
+        Some(gate_id) => {
+            let gate = Gate::find_by_id(&pool, gate_id).await?.ok_or_else(|| {
+                AppError::ValidationError(
+                    ErrorCode::GateNotFound,
+                    format!("Gate with id {} not found", gate_id),
+                )
+            })?;
+            let route = Route::find_by_id(&pool, flight.route_id)
+                .await?
+                .ok_or(sqlx::Error::RowNotFound)?;
+            if gate.airport_code != route.origin_code() {
+                return Err(AppError::ValidationError(
+                    ErrorCode::ValidationFailed,
+                    format!(
+                        "Gate {} is at {}, the flight departs from {}",
+                        gate.gate_code, gate.airport_code, route.origin
+                    ),
+                ));
+            }
+
+            let clashes = Gate::clashes(&pool, gate_id, id, config.gate_occupancy_minutes).await?;
+            if !clashes.is_empty() {
+                return Err(AppError::ConflictError(
+                    ErrorCode::GateOccupied,
+                    format!(
+                        "Gate {} is taken by {}",
+                        gate.gate_code,
+                        describe_clashes(&clashes)
+                    ),
+                ));
+            }
+            Some(gate)
+        }
+        None => None,
+    };
+
+    repository
+        .set_gate(id, gate.as_ref(), Some(auth.user_id))
+        .await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.gate_assigned",
+        "flight",
+        id,
+        serde_json::json!({ "from": &flight.gate, "gate_id": payload.gate_id }),
+    )
+    .await;
+
+    let flight = repository
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+    live_updates.publish(&flight);
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: flight,
+    }))
+}
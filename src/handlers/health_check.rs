@@ -1,9 +1,12 @@
 use axum::{http::StatusCode, Json};
 use serde_json::json;
-use sqlx::MySqlPool;
+
+use crate::db::DbPool;
+use crate::db::MIGRATOR;
+use crate::shutdown;
 
 pub async fn health_check(
-    pool: axum::extract::State<MySqlPool>,
+    pool: axum::extract::State<DbPool>,
 ) -> Result<Json<serde_json::Value>, StatusCode> {
     // Test database connection
     match sqlx::query("SELECT 1").execute(&*pool).await {
@@ -17,4 +20,71 @@ pub async fn health_check(
             Err(StatusCode::SERVICE_UNAVAILABLE)
         }
     }
-}
\ No newline at end of file
+}
+
+// Liveness: the process is up and serving requests. Dependencies are not checked, so a
+// database outage does not get the container restarted
+pub async fn liveness() -> Json<serde_json::Value> {
+    Json(json!({ "status": "ok" }))
+}
+
+// Migration state recorded by sqlx; a failed migration leaves the schema half applied and
+// pending ones mean the schema is older than this build expects
+async fn migrations(pool: &DbPool) -> (bool, serde_json::Value) {
+    let result: Result<(i64, Option<i64>), sqlx::Error> =
+        sqlx::query_as("SELECT COUNT(*), CAST(SUM(success = 0) AS SIGNED) FROM _sqlx_migrations")
+            .fetch_one(pool)
+            .await;
+
+    match result {
+        Ok((applied, failed)) => {
+            let failed = failed.unwrap_or(0);
+            let pending = (MIGRATOR.iter().count() as i64 - (applied - failed)).max(0);
+            (
+                failed == 0 && pending == 0,
+                json!({ "applied": applied - failed, "pending": pending, "failed": failed }),
+            )
+        }
+        // Schema managed outside of sqlx migrations (SKIP_MIGRATIONS)
+        Err(sqlx::Error::Database(e)) if e.code().as_deref() == Some("42S02") => {
+            (true, json!({ "status": "untracked" }))
+        }
+        Err(_) => (false, json!({ "status": "unknown" })),
+    }
+}
+
+// Readiness: whether the instance should receive traffic. Fails while the database is
+// unreachable, migrations failed or are pending, or the server is draining for shutdown
+pub async fn readiness(
+    pool: axum::extract::State<DbPool>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let database = sqlx::query("SELECT 1").execute(&*pool).await.is_ok();
+    let (migrations_ok, migrations) = if database {
+        migrations(&pool).await
+    } else {
+        (false, json!({ "status": "unknown" }))
+    };
+    let draining = shutdown::is_draining();
+    let ready = database && migrations_ok && !draining;
+
+    let body = json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "checks": {
+            "database": if database { "connected" } else { "unreachable" },
+            "migrations": migrations,
+            "draining": draining,
+        },
+        "pool": {
+            "size": pool.size(),
+            "idle": pool.num_idle(),
+            "max": pool.options().get_max_connections(),
+        },
+    });
+
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(body))
+}
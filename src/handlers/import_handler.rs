@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{header, HeaderMap},
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Serialize;
+use tracing::error;
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{AuditLog, NewPassenger, User};
+use crate::{csv, multipart};
+
+// Largest passenger file accepted, applied as the route's body limit
+pub const MAX_IMPORT_BYTES: usize = 10 * 1024 * 1024;
+
+// Rows inserted per transaction; a database failure loses at most one batch
+const BATCH_SIZE: usize = 100;
+
+// Columns a passenger file may have, in any order; the header row names them
+const COLUMNS: [&str; 7] = [
+    "first_name",
+    "last_name",
+    "email",
+    "phone",
+    "passport_number",
+    "nationality",
+    "date_of_birth",
+];
+const REQUIRED_COLUMNS: [&str; 3] = ["first_name", "last_name", "email"];
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RowStatus {
+    Imported,
+    Failed,
+}
+
+// Outcome of one data row; `row` is its record number in the file, the header being row 1
+#[derive(Debug, Serialize)]
+pub struct RowResult {
+    pub row: usize,
+    pub status: RowStatus,
+    pub email: Option<String>,
+    pub user_id: Option<i32>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub total_rows: usize,
+    pub imported: usize,
+    pub failed: usize,
+    pub rows: Vec<RowResult>,
+}
+
+fn invalid(message: impl Into<String>) -> AppError {
+    AppError::ValidationError(ErrorCode::ValidationFailed, message.into())
+}
+
+fn failed(row: usize, email: Option<&str>, error: impl Into<String>) -> RowResult {
+    RowResult {
+        row,
+        status: RowStatus::Failed,
+        email: email.map(str::to_string),
+        user_id: None,
+        error: Some(error.into()),
+    }
+}
+
+// The CSV text, sent as the `file` field of a multipart form or as a text/csv body
+fn csv_text(headers: &HeaderMap, body: Bytes) -> Result<String, AppError> {
+    let data = if multipart::is_multipart(headers) {
+        let parts = multipart::parse(headers, &body).map_err(|e| invalid(e.to_string()))?;
+        multipart::take(parts, "file")
+            .ok_or_else(|| invalid("The form has no `file` field"))?
+            .data
+    } else if headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/csv"))
+    {
+        body.to_vec()
+    } else {
+        return Err(invalid(
+            "Send the CSV as multipart/form-data (field `file`) or as text/csv",
+        ));
+    };
+
+    String::from_utf8(data).map_err(|_| invalid("The CSV file must be UTF-8 encoded"))
+}
+
+// Position of every known column in the header row
+fn header_columns(header: &[String]) -> Result<HashMap<&'static str, usize>, AppError> {
+    let mut columns = HashMap::new();
+    for (index, name) in header.iter().enumerate() {
+        let name = name.trim().to_ascii_lowercase();
+        let column = COLUMNS
+            .iter()
+            .find(|column| **column == name)
+            .ok_or_else(|| {
+                invalid(format!(
+                    "Unknown column `{}`, expected: {}",
+                    name,
+                    COLUMNS.join(", ")
+                ))
+            })?;
+        if columns.insert(*column, index).is_some() {
+            return Err(invalid(format!("Column `{}` appears twice", column)));
+        }
+    }
+
+    if let Some(missing) = REQUIRED_COLUMNS
+        .iter()
+        .find(|column| !columns.contains_key(*column))
+    {
+        return Err(invalid(format!("Required column `{}` is missing", missing)));
+    }
+    Ok(columns)
+}
+
+fn parse_row(
+    columns: &HashMap<&'static str, usize>,
+    record: &[String],
+    width: usize,
+) -> Result<NewPassenger, String> {
+    if record.len() != width {
+        return Err(format!("Expected {} fields, found {}", width, record.len()));
+    }
+    let field = |name: &str| {
+        columns
+            .get(name)
+            .map(|&index| record[index].trim())
+            .filter(|value| !value.is_empty())
+    };
+    let text = |name: &str, max: usize, required: bool| -> Result<Option<String>, String> {
+        match field(name) {
+            None if required => Err(format!("{} is required", name)),
+            Some(value) if value.chars().count() > max => {
+                Err(format!("{} is longer than {} characters", name, max))
+            }
+            value => Ok(value.map(str::to_string)),
+        }
+    };
+
+    let email = text("email", 255, true)?.unwrap_or_default();
+    let valid_email = email
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && domain.contains('.'))
+        && !email.contains(char::is_whitespace);
+    if !valid_email {
+        return Err(format!("`{}` is not a valid email address", email));
+    }
+
+    let date_of_birth = field("date_of_birth")
+        .map(|value| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map_err(|_| format!("date_of_birth `{}` is not a YYYY-MM-DD date", value))
+        })
+        .transpose()?;
+    if date_of_birth.is_some_and(|date| date > Utc::now().date_naive()) {
+        return Err("date_of_birth is in the future".to_string());
+    }
+
+    Ok(NewPassenger {
+        first_name: text("first_name", 100, true)?.unwrap_or_default(),
+        last_name: text("last_name", 100, true)?.unwrap_or_default(),
+        email,
+        phone: text("phone", 32, false)?,
+        passport_number: text("passport_number", 32, false)?,
+        nationality: text("nationality", 64, false)?,
+        date_of_birth,
+    })
+}
+
+// Which of the passenger's unique fields an existing user already has
+fn conflict(existing: &User, passenger: &NewPassenger) -> String {
+    if existing.email.eq_ignore_ascii_case(&passenger.email) {
+        "Email is already registered".to_string()
+    } else if existing.phone.is_some() && existing.phone == passenger.phone {
+        "Phone is already registered".to_string()
+    } else {
+        "Passport number is already registered".to_string()
+    }
+}
+
+// Insert one batch in a transaction; rows that collide with existing users are reported and
+// skipped without failing the batch
+async fn import_batch(
+    pool: &DbPool,
+    batch: &[(usize, NewPassenger)],
+    password_hash: &str,
+) -> Result<Vec<RowResult>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(batch.len());
+
+    for (row, passenger) in batch {
+        if let Some(existing) = User::find_conflicting(&mut tx, passenger).await? {
+            results.push(failed(
+                *row,
+                Some(&passenger.email),
+                conflict(&existing, passenger),
+            ));
+            continue;
+        }
+        match User::insert_passenger(&mut tx, passenger, password_hash).await {
+            Ok(user_id) => results.push(RowResult {
+                row: *row,
+                status: RowStatus::Imported,
+                email: Some(passenger.email.clone()),
+                user_id: Some(user_id),
+                error: None,
+            }),
+            // Registered by someone else since the check
+            Err(sqlx::Error::Database(e)) if e.is_unique_violation() => results.push(failed(
+                *row,
+                Some(&passenger.email),
+                "Email or phone is already registered",
+            )),
+            Err(e) => return Err(e),
+        }
+    }
+
+    tx.commit().await?;
+    Ok(results)
+}
+
+// Create passenger accounts from a CSV file (admin only). Every row is validated on its own and
+// the report lists each row's outcome; invalid rows and rows whose email, phone or passport
+// number is taken (in the database or earlier in the file) are skipped, the rest are inserted
+pub async fn import_passengers(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ApiResponse<ImportReport>>, AppError> {
+    let text = csv_text(&headers, body)?;
+    let records = csv::parse(&text).map_err(|e| invalid(e.to_string()))?;
+    let Some((header, records)) = records.split_first() else {
+        return Err(invalid("The CSV file is empty"));
+    };
+    let columns = header_columns(header)?;
+
+    let mut results = Vec::new();
+    let mut pending = Vec::new();
+    // Unique fields seen so far, with the row that has them
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for (index, record) in records.iter().enumerate() {
+        let row = index + 2;
+        let passenger = match parse_row(&columns, record, header.len()) {
+            Ok(passenger) => passenger,
+            Err(e) => {
+                let email = columns.get("email").and_then(|&i| record.get(i));
+                results.push(failed(row, email.map(|e| e.trim()), e));
+                continue;
+            }
+        };
+
+        let keys = [
+            Some(format!("email:{}", passenger.email.to_lowercase())),
+            passenger
+                .phone
+                .as_ref()
+                .map(|phone| format!("phone:{}", phone)),
+            passenger
+                .passport_number
+                .as_ref()
+                .map(|passport| format!("passport:{}", passport.to_uppercase())),
+        ];
+        if let Some((key, first)) = keys
+            .iter()
+            .flatten()
+            .find_map(|key| seen.get(key).map(|first| (key, *first)))
+        {
+            let field = key.split(':').next().unwrap_or_default();
+            results.push(failed(
+                row,
+                Some(&passenger.email),
+                format!("Same {} as row {}", field, first),
+            ));
+            continue;
+        }
+        for key in keys.into_iter().flatten() {
+            seen.insert(key, row);
+        }
+        pending.push((row, passenger));
+    }
+
+    // Nobody knows this password; imported passengers set their own with a password reset
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    let password_hash = bcrypt::hash(secret, bcrypt::DEFAULT_COST)
+        .map_err(|e| AppError::InternalError(format!("Password hashing failed: {}", e)))?;
+
+    for batch in pending.chunks(BATCH_SIZE) {
+        match import_batch(&pool, batch, &password_hash).await {
+            Ok(batch_results) => results.extend(batch_results),
+            Err(e) => {
+                error!("Passenger import batch failed: {}", e);
+                results.extend(batch.iter().map(|(row, passenger)| {
+                    failed(
+                        *row,
+                        Some(&passenger.email),
+                        "Database error, the row was not imported",
+                    )
+                }));
+            }
+        }
+    }
+    results.sort_by_key(|result| result.row);
+
+    let imported = results
+        .iter()
+        .filter(|result| matches!(result.status, RowStatus::Imported))
+        .count();
+    let report = ImportReport {
+        total_rows: results.len(),
+        imported,
+        failed: results.len() - imported,
+        rows: results,
+    };
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "users.imported",
+        "user",
+        "bulk",
+        serde_json::json!({ "imported": report.imported, "failed": report.failed }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: report,
+    }))
+}
@@ -0,0 +1,155 @@
+use std::{collections::HashMap, convert::Infallible};
+
+use axum::{
+    extract::{Path, State},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Response,
+    },
+    Extension,
+};
+use futures_util::stream::{self, Stream};
+use serde::Serialize;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, error};
+
+use super::flight_handler::flight_not_found;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::live::websocket::{Message, WebSocket, WebSocketUpgrade};
+use crate::live::{FlightUpdate, FlightUpdates};
+use crate::middleware::auth::AuthUser;
+use crate::models::{Flight, Ticket};
+
+// Payload of a `flight_update` event on the ticket stream
+#[derive(Debug, Serialize)]
+pub struct TicketFlightUpdate {
+    pub ticket_ids: Vec<i32>,
+    pub flight: FlightUpdate,
+}
+
+// Live status, gate and schedule changes of a flight over a WebSocket.
+// The first message is the current state, later ones follow every change
+pub async fn flight_updates_ws(
+    State(pool): State<DbPool>,
+    Extension(updates): Extension<FlightUpdates>,
+    Path(id): Path<i32>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, AppError> {
+    let flight = Flight::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+
+    // Subscribe before answering so no change between the snapshot and the feed is lost
+    let receiver = updates.subscribe();
+    Ok(upgrade.on_upgrade(move |socket| {
+        stream_flight_updates(socket, FlightUpdate::from(&flight), receiver)
+    }))
+}
+
+async fn stream_flight_updates(
+    socket: WebSocket,
+    snapshot: FlightUpdate,
+    mut updates: broadcast::Receiver<FlightUpdate>,
+) {
+    let flight_id = snapshot.flight_id;
+    let (mut sender, mut receiver) = socket.split();
+
+    // Client frames are read on their own task; reads are not safe to cancel mid-frame
+    let (incoming_tx, mut incoming) = mpsc::channel(8);
+    let reader = tokio::spawn(async move {
+        while let Ok(message) = receiver.recv().await {
+            let close = matches!(message, Message::Close);
+            if incoming_tx.send(message).await.is_err() || close {
+                break;
+            }
+        }
+    });
+
+    let mut next = Some(snapshot);
+    loop {
+        if let Some(update) = next.take() {
+            let text = match serde_json::to_string(&update) {
+                Ok(text) => text,
+                Err(e) => {
+                    error!("Failed to serialize update of flight {}: {}", flight_id, e);
+                    break;
+                }
+            };
+            if sender.send(Message::Text(text)).await.is_err() {
+                break;
+            }
+        }
+
+        tokio::select! {
+            update = updates.recv() => match update {
+                Ok(update) if update.flight_id == flight_id => next = Some(update),
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("Live feed of flight {} skipped {} updates", flight_id, skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            message = incoming.recv() => match message {
+                Some(Message::Ping(payload)) => {
+                    if sender.send(Message::Pong(payload)).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Message::Text(_) | Message::Pong(_)) => {}
+                Some(Message::Close) | None => break,
+            },
+        }
+    }
+
+    let _ = sender.send(Message::Close).await;
+    reader.abort();
+}
+
+// Server-sent events for changes to the flights of the caller's tickets.
+// Tickets are read when the stream opens; clients reconnect to pick up new bookings
+pub async fn ticket_events(
+    State(pool): State<DbPool>,
+    Extension(updates): Extension<FlightUpdates>,
+    auth: AuthUser,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    let receiver = updates.subscribe();
+
+    let mut tickets_by_flight: HashMap<i32, Vec<i32>> = HashMap::new();
+    for ticket in Ticket::find_by_user(&pool, auth.user_id).await? {
+        tickets_by_flight
+            .entry(ticket.flight_id)
+            .or_default()
+            .push(ticket.ticket_id);
+    }
+
+    let events = stream::unfold(
+        (receiver, tickets_by_flight),
+        |(mut receiver, tickets_by_flight)| async move {
+            loop {
+                let update = match receiver.recv().await {
+                    Ok(update) => update,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        debug!("Ticket stream skipped {} flight updates", skipped);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                };
+                let Some(ticket_ids) = tickets_by_flight.get(&update.flight_id) else {
+                    continue;
+                };
+
+                let payload = TicketFlightUpdate {
+                    ticket_ids: ticket_ids.clone(),
+                    flight: update,
+                };
+                match Event::default().event("flight_update").json_data(&payload) {
+                    Ok(event) => return Some((Ok(event), (receiver, tickets_by_flight))),
+                    Err(e) => error!("Failed to serialize ticket event: {}", e),
+                }
+            }
+        },
+    );
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
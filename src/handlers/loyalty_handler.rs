@@ -0,0 +1,101 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::miles::MILE_VALUE;
+use crate::models::{Flight, FlightStatus, MilesEntry, Ticket};
+use crate::validation::ValidJson;
+
+// Redeem miles request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct RedeemMilesRequest {
+    pub ticket_id: i32,
+    #[validate(range(min = 1))]
+    pub miles: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MilesAccount {
+    pub balance: i64,
+    pub recent_entries: Vec<MilesEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MilesRedemption {
+    pub entry: MilesEntry,
+    pub discount: f64,
+    pub balance: i64,
+}
+
+// Get the caller's miles balance and latest ledger entries
+pub async fn get_miles(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<MilesAccount>>, AppError> {
+    let balance = MilesEntry::balance(&pool, auth.user_id).await?;
+    let recent_entries = MilesEntry::recent(&pool, auth.user_id, 20).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: MilesAccount {
+            balance,
+            recent_entries,
+        },
+    }))
+}
+
+// Spend miles as a discount on one of the caller's upcoming tickets
+pub async fn redeem_miles(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    ValidJson(payload): ValidJson<RedeemMilesRequest>,
+) -> Result<Json<ApiResponse<MilesRedemption>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, payload.ticket_id)
+        .await?
+        .filter(|ticket| ticket.user_id == auth.user_id)
+        .ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::TicketNotFound,
+                format!("Ticket with id {} not found", payload.ticket_id),
+            )
+        })?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::FlightNotFound,
+                "Flight of the ticket not found".to_string(),
+            )
+        })?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) {
+        return Err(AppError::ValidationError(
+            ErrorCode::RedemptionClosed,
+            "Miles can only be redeemed before boarding".to_string(),
+        ));
+    }
+
+    let entry = MilesEntry::redeem(&pool, auth.user_id, ticket.ticket_id, payload.miles)
+        .await?
+        .ok_or_else(|| {
+            AppError::ValidationError(ErrorCode::InsufficientMiles, "Not enough miles".to_string())
+        })?;
+
+    let balance = MilesEntry::balance(&pool, auth.user_id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: MilesRedemption {
+            discount: (payload.miles as f64 * MILE_VALUE * 100.0).round() / 100.0,
+            entry,
+            balance,
+        },
+    }))
+}
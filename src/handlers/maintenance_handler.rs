@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireStaff;
+use crate::models::{Aircraft, AuditLog, MaintenanceRecord, NewMaintenanceRecord, StaffPosition};
+use crate::validation::ValidJson;
+
+pub(crate) fn aircraft_not_found(id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::AircraftNotFound,
+        format!("Aircraft with id {} not found", id),
+    )
+}
+
+// List the maintenance records of an aircraft (staff)
+pub async fn get_maintenance_records(
+    State(pool): State<DbPool>,
+    _: RequireStaff,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<MaintenanceRecord>>>, AppError> {
+    if Aircraft::find_by_id(&pool, id).await?.is_none() {
+        return Err(aircraft_not_found(id));
+    }
+
+    let records = MaintenanceRecord::find_by_aircraft(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: records,
+    }))
+}
+
+// Record maintenance that grounds an aircraft (admin or engineer). Flights already scheduled in
+// the window are left to dispatchers; new ones are refused
+pub async fn create_maintenance_record(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<NewMaintenanceRecord>,
+) -> Result<(StatusCode, Json<ApiResponse<MaintenanceRecord>>), AppError> {
+    auth.require_position(&[StaffPosition::Engineer])?;
+
+    if payload.grounded_to <= payload.grounded_from {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "grounded_to must be after grounded_from".to_string(),
+        ));
+    }
+    if Aircraft::find_by_id(&pool, id).await?.is_none() {
+        return Err(aircraft_not_found(id));
+    }
+
+    let record = MaintenanceRecord::insert(&pool, id, &payload, auth.user_id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "aircraft.maintenance_recorded",
+        "aircraft",
+        id,
+        serde_json::json!({
+            "maintenance_id": record.maintenance_id,
+            "maintenance_type": record.maintenance_type,
+            "grounded_from": record.grounded_from,
+            "grounded_to": record.grounded_to,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: record,
+        }),
+    ))
+}
+
+// Delete a maintenance record, e.g. work that was called off (admin or engineer)
+pub async fn delete_maintenance_record(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path((id, maintenance_id)): Path<(i32, i32)>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    auth.require_position(&[StaffPosition::Engineer])?;
+
+    if !MaintenanceRecord::delete(&pool, id, maintenance_id).await? {
+        return Err(AppError::NotFound(
+            ErrorCode::MaintenanceRecordNotFound,
+            format!(
+                "Maintenance record with id {} not found on aircraft {}",
+                maintenance_id, id
+            ),
+        ));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "aircraft.maintenance_deleted",
+        "aircraft",
+        id,
+        serde_json::json!({ "maintenance_id": maintenance_id }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: serde_json::json!({}),
+    }))
+}
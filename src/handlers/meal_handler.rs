@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::response::ApiResponse;
+use super::ticket_handler::ticket_not_found;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireStaff;
+use crate::models::{AuditLog, CateringLine, Flight, FlightStatus, Meal, StaffPosition, Ticket};
+use crate::validation::ValidJson;
+
+// Set flight meals request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetMealsRequest {
+    pub meals: Vec<Meal>,
+}
+
+// Choose meal request body; None goes back to no choice
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChooseMealRequest {
+    pub meal: Option<Meal>,
+}
+
+// Meals to load on a flight
+#[derive(Debug, Serialize)]
+pub struct CateringSummary {
+    pub flight_id: i32,
+    pub passengers: i64,
+    pub meals: Vec<CateringLine>,
+}
+
+// Meals catered on a flight
+pub async fn get_flight_meals(
+    State(pool): State<DbPool>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<Meal>>>, AppError> {
+    if Flight::find_by_id(&pool, id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let meals = Meal::find_by_flight(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: meals,
+    }))
+}
+
+// Replace the meals catered on a flight (admin or dispatcher)
+pub async fn set_flight_meals(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<SetMealsRequest>,
+) -> Result<Json<ApiResponse<Vec<Meal>>>, AppError> {
+    auth.require_position(&[StaffPosition::Dispatcher])?;
+
+    if Flight::find_by_id(&pool, id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let mut seen = HashSet::new();
+    if let Some(meal) = payload.meals.iter().find(|meal| !seen.insert(**meal)) {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            format!("Meal {} listed more than once", meal.as_str()),
+        ));
+    }
+
+    Meal::replace_for_flight(&pool, id, &payload.meals).await?;
+    let meals = Meal::find_by_flight(&pool, id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "flight.meals_updated",
+        "flight",
+        id,
+        serde_json::json!({ "meals": &meals }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: meals,
+    }))
+}
+
+// Choose one of the flight's meals for a ticket (its holder or staff), up to the cutoff before
+// departure
+pub async fn choose_ticket_meal(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<ChooseMealRequest>,
+) -> Result<Json<ApiResponse<Ticket>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    let cutoff =
+        flight.departure_time - Duration::hours(i64::from(config.meal_selection_cutoff_hours));
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) || Utc::now() >= cutoff
+    {
+        return Err(AppError::ConflictError(
+            ErrorCode::MealSelectionClosed,
+            format!(
+                "Meals can only be chosen until {} hours before departure",
+                config.meal_selection_cutoff_hours
+            ),
+        ));
+    }
+
+    if let Some(meal) = payload.meal {
+        if !Meal::find_by_flight(&pool, flight.flight_id)
+            .await?
+            .contains(&meal)
+        {
+            return Err(AppError::ValidationError(
+                ErrorCode::MealNotOffered,
+                format!(
+                    "Flight {} does not cater {} meals",
+                    flight.flight_number,
+                    meal.as_str()
+                ),
+            ));
+        }
+    }
+
+    Ticket::set_meal(&pool, id, payload.meal).await?;
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ticket,
+    }))
+}
+
+// How many meals of each kind to load on a flight (staff only)
+pub async fn get_catering_summary(
+    State(pool): State<DbPool>,
+    _: RequireStaff,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<CateringSummary>>, AppError> {
+    if Flight::find_by_id(&pool, id).await?.is_none() {
+        return Err(flight_not_found(id));
+    }
+
+    let meals = CateringLine::find_by_flight(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: CateringSummary {
+            flight_id: id,
+            passengers: meals.iter().map(|line| line.count).sum(),
+            meals,
+        },
+    }))
+}
@@ -0,0 +1,29 @@
+use axum::Json;
+use serde::Serialize;
+
+use super::response::ApiResponse;
+use crate::error::ErrorCode;
+use crate::i18n::Locale;
+use crate::middleware::locale::PreferredLocales;
+
+#[derive(Debug, Serialize)]
+pub struct ErrorCodeInfo {
+    pub code: ErrorCode,
+    pub description: &'static str,
+}
+
+// List every error code the API can return, for client-side handling and translations;
+// descriptions are in the caller's language (Accept-Language) when the API speaks it
+pub async fn get_error_codes(preferred: PreferredLocales) -> Json<ApiResponse<Vec<ErrorCodeInfo>>> {
+    let locale = Locale::negotiate(&preferred);
+    Json(ApiResponse {
+        success: true,
+        data: ErrorCode::ALL
+            .iter()
+            .map(|code| ErrorCodeInfo {
+                code: *code,
+                description: locale.describe(*code),
+            })
+            .collect(),
+    })
+}
@@ -1,2 +1,40 @@
+pub mod ancillary_handler;
+pub mod api_key_handler;
+pub mod assistance_handler;
+pub mod audit_log_handler;
+pub mod auth_handler;
+pub mod avatar_handler;
+pub mod baggage_handler;
+pub mod booking_handler;
+pub mod cabin_layout_handler;
+pub mod cargo_handler;
+pub mod companion_handler;
+pub mod content_handler;
+pub mod crew_handler;
+pub mod currency_handler;
+pub mod data_export_handler;
+pub mod docs_handler;
+pub mod document_handler;
+pub mod fare_class_handler;
+pub mod flight_handler;
+pub mod gate_handler;
 pub mod health_check;
+pub mod import_handler;
+pub mod live_handler;
+pub mod loyalty_handler;
+pub mod maintenance_handler;
+pub mod meal_handler;
+pub mod meta_handler;
+pub mod passenger_handler;
+pub mod pet_handler;
+pub mod promo_code_handler;
+pub mod report_handler;
+pub mod response;
 pub mod route_handler;
+pub mod seat_block_handler;
+pub mod status_token_handler;
+pub mod ticket_handler;
+pub mod two_factor_handler;
+pub mod upgrade_handler;
+pub mod user_handler;
+pub mod webhook_handler;
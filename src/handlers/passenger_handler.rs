@@ -0,0 +1,148 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use super::flight_handler::flight_not_found;
+use super::response::ApiResponse;
+use super::ticket_handler::ticket_not_found;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{
+    AuditLog, Flight, FlightFareClass, FlightStatus, NewTicketInfant, PassengerFares,
+    PassengerType, Ticket, TicketInfant,
+};
+use crate::validation::ValidJson;
+
+pub(crate) fn passenger_fares(config: &Config) -> PassengerFares {
+    PassengerFares {
+        child_percent: config.child_fare_percent,
+        lap_infant_percent: config.lap_infant_fare_percent,
+    }
+}
+
+// The infant travelling on the lap of a ticket holder, if any (the holder or staff)
+pub async fn get_ticket_infant(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Option<TicketInfant>>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let infant = TicketInfant::find_by_ticket(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: infant,
+    }))
+}
+
+// Book an infant on the lap of an adult ticket holder (the holder or staff). The infant must be
+// under 2 today and still on the departure date, and pays a share of the ticket's class fare
+pub async fn add_ticket_infant(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<NewTicketInfant>,
+) -> Result<(StatusCode, Json<ApiResponse<TicketInfant>>), AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    if ticket.passenger_type != PassengerType::Adult {
+        return Err(AppError::ValidationError(
+            ErrorCode::LapInfantNotAllowed,
+            format!(
+                "Ticket {} is for a {} passenger, who cannot carry an infant",
+                id,
+                ticket.passenger_type.as_str()
+            ),
+        ));
+    }
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Infants cannot be booked on a {} flight",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let departure_date = flight.departure_time.date_naive();
+    if PassengerType::on(payload.date_of_birth, departure_date) != PassengerType::Infant {
+        return Err(AppError::ValidationError(
+            ErrorCode::NotAnInfant,
+            format!(
+                "An infant born on {} is 2 or older on {} and needs a seat",
+                payload.date_of_birth, departure_date
+            ),
+        ));
+    }
+
+    let class = FlightFareClass::find_by_flight(&pool, flight.flight_id)
+        .await?
+        .into_iter()
+        .find(|class| class.fare_class == ticket.fare_class)
+        .ok_or_else(|| {
+            AppError::InternalError(format!(
+                "Flight {} has no {} fare class for ticket {}",
+                flight.flight_id,
+                ticket.fare_class.as_str(),
+                id
+            ))
+        })?;
+    let fare = passenger_fares(&config).lap_infant_fare(class.price);
+
+    let infant = TicketInfant::create(&pool, id, &payload, (fare, &class.currency), auth.user_id)
+        .await
+        .map_err(|e| {
+            if e.as_database_error()
+                .is_some_and(|db| db.is_unique_violation())
+            {
+                AppError::ConflictError(
+                    ErrorCode::InfantAlreadyBooked,
+                    format!("Ticket {} already has an infant on the lap", id),
+                )
+            } else {
+                e.into()
+            }
+        })?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ticket.infant_added",
+        "ticket",
+        id,
+        serde_json::json!({
+            "date_of_birth": infant.date_of_birth,
+            "fare": infant.fare,
+            "currency": &infant.currency,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: infant,
+        }),
+    ))
+}
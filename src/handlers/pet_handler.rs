@@ -0,0 +1,254 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Serialize;
+
+use super::flight_handler::flight_not_found;
+use super::maintenance_handler::aircraft_not_found;
+use super::response::ApiResponse;
+use super::ticket_handler::ticket_not_found;
+use crate::config::Config;
+use crate::currencies::BASE_CURRENCY;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{
+    Aircraft, AuditLog, Flight, FlightStatus, NewTicketPet, PetFees, PetLimits, PetPlacement,
+    Ticket, TicketPet,
+};
+use crate::validation::ValidJson;
+
+// Pet places of one placement on a flight
+#[derive(Debug, Serialize)]
+pub struct PetPlaces {
+    pub capacity: i32,
+    pub booked: i64,
+    pub available: i64,
+}
+
+// Pet places left on a flight and what a pet costs
+#[derive(Debug, Serialize)]
+pub struct PetAvailability {
+    pub flight_id: i32,
+    pub cabin: PetPlaces,
+    pub hold: PetPlaces,
+    pub cabin_max_weight_kg: f64,
+    pub fees: PetFees,
+    pub currency: &'static str,
+}
+
+fn pet_fees(config: &Config) -> PetFees {
+    PetFees {
+        cabin: config.pet_cabin_fee,
+        hold: config.pet_hold_fee,
+        hold_per_kg: config.pet_hold_fee_per_kg,
+    }
+}
+
+async fn pet_places(
+    pool: &DbPool,
+    flight_id: i32,
+    aircraft: &Aircraft,
+    placement: PetPlacement,
+) -> Result<PetPlaces, sqlx::Error> {
+    let capacity = placement.limit(aircraft);
+    let booked = TicketPet::count_on_flight(pool, flight_id, placement).await?;
+    Ok(PetPlaces {
+        capacity,
+        booked,
+        available: (i64::from(capacity) - booked).max(0),
+    })
+}
+
+async fn flight_aircraft(pool: &DbPool, flight: &Flight) -> Result<Aircraft, AppError> {
+    Aircraft::find_by_id(pool, flight.aircraft_id)
+        .await?
+        .ok_or_else(|| aircraft_not_found(flight.aircraft_id))
+}
+
+// Pet places left in the cabin and the hold of a flight
+pub async fn get_flight_pets(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<PetAvailability>>, AppError> {
+    let flight = Flight::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| flight_not_found(id))?;
+    let aircraft = flight_aircraft(&pool, &flight).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: PetAvailability {
+            flight_id: id,
+            cabin: pet_places(&pool, id, &aircraft, PetPlacement::Cabin).await?,
+            hold: pet_places(&pool, id, &aircraft, PetPlacement::Hold).await?,
+            cabin_max_weight_kg: aircraft.pet_cabin_max_weight_kg,
+            fees: pet_fees(&config),
+            currency: BASE_CURRENCY,
+        },
+    }))
+}
+
+// The pet travelling on a ticket, if any (its holder or staff)
+pub async fn get_ticket_pet(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Option<TicketPet>>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let pet = TicketPet::find_by_ticket(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: pet,
+    }))
+}
+
+// Book a pet in the cabin or the hold of a ticket's flight (its holder or staff). The aircraft
+// must carry pets there, take one this heavy and have a place left
+pub async fn add_ticket_pet(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<NewTicketPet>,
+) -> Result<(StatusCode, Json<ApiResponse<TicketPet>>), AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Pets cannot be booked on a {} flight",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let aircraft = flight_aircraft(&pool, &flight).await?;
+    let placement = payload.placement;
+    if placement.limit(&aircraft) == 0 {
+        return Err(AppError::ValidationError(
+            ErrorCode::PetNotAccepted,
+            format!(
+                "The {} does not carry pets in the {}",
+                aircraft.model,
+                placement.as_str()
+            ),
+        ));
+    }
+    if placement == PetPlacement::Cabin && payload.weight_kg > aircraft.pet_cabin_max_weight_kg {
+        return Err(AppError::ValidationError(
+            ErrorCode::PetNotAccepted,
+            format!(
+                "Pets in the cabin of the {} may weigh at most {} kg with their carrier",
+                aircraft.model, aircraft.pet_cabin_max_weight_kg
+            ),
+        ));
+    }
+
+    let places = pet_places(&pool, flight.flight_id, &aircraft, placement).await?;
+    if places.available == 0 {
+        return Err(AppError::ConflictError(
+            ErrorCode::PetCapacityReached,
+            format!(
+                "Flight {} carries no more pets in the {}",
+                flight.flight_number,
+                placement.as_str()
+            ),
+        ));
+    }
+
+    let fee = pet_fees(&config).fee(placement, payload.weight_kg);
+    let pet = TicketPet::create(&pool, id, &payload, (fee, BASE_CURRENCY), auth.user_id)
+        .await
+        .map_err(|e| {
+            if e.as_database_error()
+                .is_some_and(|db| db.is_unique_violation())
+            {
+                AppError::ConflictError(
+                    ErrorCode::PetAlreadyBooked,
+                    format!("Ticket {} already has a pet", id),
+                )
+            } else {
+                e.into()
+            }
+        })?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ticket.pet_added",
+        "ticket",
+        id,
+        serde_json::json!({
+            "placement": placement.as_str(),
+            "weight_kg": pet.weight_kg,
+            "fee": pet.fee,
+        }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: pet,
+        }),
+    ))
+}
+
+// Set how many pets an aircraft carries in the cabin and the hold, and how heavy one in the
+// cabin may be (admin only). Pets already booked keep their places
+pub async fn set_aircraft_pet_limits(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<PetLimits>,
+) -> Result<Json<ApiResponse<Aircraft>>, AppError> {
+    if Aircraft::find_by_id(&pool, id).await?.is_none() {
+        return Err(aircraft_not_found(id));
+    }
+
+    Aircraft::set_pet_limits(&pool, id, &payload).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "aircraft.pet_limits_set",
+        "aircraft",
+        id,
+        serde_json::json!({
+            "pets_in_cabin": payload.pets_in_cabin,
+            "pets_in_hold": payload.pets_in_hold,
+            "pet_cabin_max_weight_kg": payload.pet_cabin_max_weight_kg,
+        }),
+    )
+    .await;
+
+    let aircraft = Aircraft::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| aircraft_not_found(id))?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: aircraft,
+    }))
+}
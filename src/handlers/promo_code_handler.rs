@@ -0,0 +1,174 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::response::{ApiResponse, PaginatedResponse, Pagination, PaginationParams};
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{AuditLog, DiscountType, NewPromoCode, PromoCode};
+use crate::validation::{not_blank, ValidJson};
+
+// Validate promo code request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct ValidatePromoCodeRequest {
+    #[validate(custom(function = "not_blank"))]
+    pub code: String,
+    #[validate(range(min = 0.0))]
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromoCodeQuote {
+    pub code: String,
+    pub amount: f64,
+    pub discount: f64,
+    pub final_amount: f64,
+}
+
+// Create a promo code (admin only)
+pub async fn create_promo_code(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    ValidJson(mut payload): ValidJson<NewPromoCode>,
+) -> Result<(StatusCode, Json<ApiResponse<PromoCode>>), AppError> {
+    payload.code = payload.code.trim().to_uppercase();
+    if payload.discount_type == DiscountType::Percentage && payload.discount_value > 100.0 {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "Percentage discounts can be at most 100".to_string(),
+        ));
+    }
+    if payload.valid_until <= payload.valid_from {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "valid_until must be after valid_from".to_string(),
+        ));
+    }
+    let promo_code = PromoCode::create(&pool, &payload).await.map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|db| db.is_unique_violation())
+        {
+            AppError::ConflictError(
+                ErrorCode::PromoCodeExists,
+                format!("Promo code {} already exists", payload.code),
+            )
+        } else {
+            e.into()
+        }
+    })?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "promo_code.created",
+        "promo_code",
+        promo_code.promo_code_id,
+        serde_json::json!({ "code": &promo_code.code }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: promo_code,
+        }),
+    ))
+}
+
+// List promo codes with usage (admin only)
+pub async fn get_promo_codes(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<PaginatedResponse<PromoCode>>, AppError> {
+    let page = params.page.unwrap_or(1);
+    let limit = params.limit.unwrap_or(10);
+
+    let codes = PromoCode::find_all(&pool, page, limit).await?;
+    let total = PromoCode::count(&pool).await?;
+
+    let total_pages = (total as f64 / limit as f64).ceil() as i32;
+
+    Ok(Json(PaginatedResponse {
+        success: true,
+        count: codes.len(),
+        pagination: Pagination {
+            page,
+            limit,
+            total_pages,
+            total_items: total,
+        },
+        data: codes,
+    }))
+}
+
+// Delete a promo code (admin only)
+pub async fn delete_promo_code(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    if !PromoCode::delete(&pool, id).await? {
+        return Err(AppError::NotFound(
+            ErrorCode::PromoCodeNotFound,
+            format!("Promo code with id {} not found", id),
+        ));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "promo_code.deleted",
+        "promo_code",
+        id,
+        serde_json::Value::Null,
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: serde_json::json!({}),
+    }))
+}
+
+// Check a promo code and quote the discount for an amount without using it up
+pub async fn validate_promo_code(
+    State(pool): State<DbPool>,
+    ValidJson(payload): ValidJson<ValidatePromoCodeRequest>,
+) -> Result<Json<ApiResponse<PromoCodeQuote>>, AppError> {
+    let code = payload.code.trim().to_uppercase();
+    let promo_code = PromoCode::find_by_code(&pool, &code)
+        .await?
+        .ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::PromoCodeNotFound,
+                "Promo code not found".to_string(),
+            )
+        })?;
+
+    if let Some(code) = promo_code.unusable_reason(Utc::now()) {
+        return Err(AppError::ValidationError(
+            code,
+            code.description().to_string(),
+        ));
+    }
+
+    let discount = promo_code.discount_for(payload.amount);
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: PromoCodeQuote {
+            code: promo_code.code,
+            amount: payload.amount,
+            discount,
+            final_amount: payload.amount - discount,
+        },
+    }))
+}
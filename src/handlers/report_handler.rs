@@ -0,0 +1,175 @@
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::NaiveDate;
+use futures_util::{stream, StreamExt};
+
+use super::response::ApiResponse;
+use crate::currencies::{ExchangeRates, BASE_CURRENCY};
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::export::{self, ExportFormat};
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{
+    DemandAnalytics, DemandFilter, DemographicGroup, DemographicsFilter, FlightLoad,
+    FlightPunctuality, LoadFactorFilter, OnTimeFilter, OnTimePerformance, RevenueEntry,
+    RevenueFilter, RevenueRow, RouteLoad,
+};
+
+// Reports cover at most this many days
+const MAX_PERIOD_DAYS: i64 = 366;
+
+// Most booked routes listed by the demand analytics at most
+const MAX_TOP_ROUTES: i32 = 50;
+
+fn check_period(from: NaiveDate, to: NaiveDate) -> Result<(), AppError> {
+    if to < from {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "to must not be before from".to_string(),
+        ));
+    }
+    if (to - from).num_days() >= MAX_PERIOD_DAYS {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            format!("Reports cover at most {} days", MAX_PERIOD_DAYS),
+        ));
+    }
+    Ok(())
+}
+
+// Seats sold against capacity for the flights departing in `from`..=`to` that were not
+// cancelled, grouped by route with the least full routes and flights first; or one row per
+// flight as a CSV or Excel download (admin only)
+pub async fn get_load_factor_report(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    headers: HeaderMap,
+    Query(filter): Query<LoadFactorFilter>,
+) -> Result<Response, AppError> {
+    check_period(filter.from, filter.to)?;
+
+    let format = ExportFormat::negotiate(filter.format, &headers);
+    if format != ExportFormat::Json {
+        return Ok(export::download(
+            format,
+            "load-factor",
+            move |rows| async move { FlightLoad::export(&pool, &filter, rows).await },
+        ));
+    }
+
+    let flights = FlightLoad::find(&pool, &filter).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: RouteLoad::group(flights),
+    })
+    .into_response())
+}
+
+// Ticket and ancillary sales made in `from`..=`to`, by route or by month, converted to
+// `?currency=` (EUR by default); as JSON or a CSV or Excel download (admin only). Nothing is
+// subtracted for refunds, there are none yet
+pub async fn get_revenue_report(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    headers: HeaderMap,
+    Query(filter): Query<RevenueFilter>,
+) -> Result<Response, AppError> {
+    check_period(filter.from, filter.to)?;
+
+    let rates = ExchangeRates::load(&pool).await?;
+    let currency = match &filter.currency {
+        Some(currency) => rates.requested(currency)?,
+        None => BASE_CURRENCY.to_string(),
+    };
+    let entries = RevenueEntry::find(&pool, &filter).await?;
+    let rows = RevenueRow::summarize(entries, filter.group_by, &rates, &currency)
+        .map_err(|from| AppError::InternalError(format!("No exchange rate for {} sales", from)))?;
+
+    let format = ExportFormat::negotiate(filter.format, &headers);
+    if format != ExportFormat::Json {
+        return Ok(export::download(
+            format,
+            "revenue",
+            move |sender| async move {
+                export::forward(stream::iter(rows.into_iter().map(Ok)).boxed(), &sender).await
+            },
+        ));
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: rows,
+    })
+    .into_response())
+}
+
+// On-time performance of the flights scheduled to depart in `from`..=`to` that departed: the
+// share that left and landed at most 15 minutes late and their average delay, by route,
+// aircraft or month; or one row per flight as a CSV or Excel download (admin only)
+pub async fn get_on_time_report(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    headers: HeaderMap,
+    Query(filter): Query<OnTimeFilter>,
+) -> Result<Response, AppError> {
+    check_period(filter.from, filter.to)?;
+
+    let format = ExportFormat::negotiate(filter.format, &headers);
+    if format != ExportFormat::Json {
+        return Ok(export::download(
+            format,
+            "on-time",
+            move |rows| async move { FlightPunctuality::export(&pool, &filter, rows).await },
+        ));
+    }
+
+    let flights = FlightPunctuality::find(&pool, &filter).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: OnTimePerformance::group(flights, filter.group_by),
+    })
+    .into_response())
+}
+
+// Demand for tickets issued in `from`..=`to`: the most booked routes (`?limit=`, 10 by default),
+// tickets by day of the week their flight departs and how far ahead of departure they were
+// booked, for the marketing dashboard (admin only)
+pub async fn get_demand_report(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    Query(filter): Query<DemandFilter>,
+) -> Result<Json<ApiResponse<DemandAnalytics>>, AppError> {
+    check_period(filter.from, filter.to)?;
+    let limit = filter.limit.unwrap_or(10).clamp(1, MAX_TOP_ROUTES);
+
+    let analytics = DemandAnalytics::find(&pool, &filter, limit).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: analytics,
+    }))
+}
+
+// Passengers on flights departing in `from`..=`to` that were not cancelled, ticket holders and
+// lap infants alike, by nationality, age bracket and gender, per route or month; profile fields
+// left out, and a lap infant's nationality and gender, are counted as unknown (admin only)
+pub async fn get_demographics_report(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    Query(filter): Query<DemographicsFilter>,
+) -> Result<Json<ApiResponse<Vec<DemographicGroup>>>, AppError> {
+    check_period(filter.from, filter.to)?;
+
+    let groups = DemographicGroup::find(&pool, &filter).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: groups,
+    }))
+}
@@ -0,0 +1,234 @@
+use axum::{
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::error::{AppError, ErrorCode};
+
+// Largest page served by keyset pagination
+const MAX_CURSOR_LIMIT: i32 = 100;
+
+// Query parameters for pagination
+#[derive(Debug, Deserialize)]
+pub struct PaginationParams {
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+    // Keyset pagination instead of pages; empty for the first page, then `next_cursor`
+    pub cursor: Option<String>,
+}
+
+// Response wrapper
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: T,
+}
+
+// Pagination response wrapper
+#[derive(Debug, Serialize)]
+pub struct PaginatedResponse<T> {
+    pub success: bool,
+    pub count: usize,
+    pub pagination: Pagination,
+    pub data: Vec<T>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pagination {
+    pub page: i32,
+    pub limit: i32,
+    pub total_pages: i32,
+    pub total_items: i64,
+}
+
+// Keyset page; `next_cursor` is null on the last page
+#[derive(Debug, Serialize)]
+pub struct CursorResponse<T> {
+    pub success: bool,
+    pub count: usize,
+    pub next_cursor: Option<String>,
+    pub data: Vec<T>,
+}
+
+impl<T> CursorResponse<T> {
+    // `rows` comes from a query for `limit + 1` rows; the extra one only tells that another
+    // page follows and is not returned. `key` gives the sort key the next page starts after
+    pub fn from_rows<K: Serialize>(mut rows: Vec<T>, limit: i32, key: impl Fn(&T) -> K) -> Self {
+        let next_cursor = if rows.len() > limit as usize {
+            rows.truncate(limit as usize);
+            rows.last().map(|row| encode_cursor(&key(row)))
+        } else {
+            None
+        };
+
+        Self {
+            success: true,
+            count: rows.len(),
+            next_cursor,
+            data: rows,
+        }
+    }
+}
+
+// A list endpoint answers with pages or a keyset page, depending on whether `cursor` was sent
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum ListResponse<T> {
+    Paginated(PaginatedResponse<T>),
+    Cursor(CursorResponse<T>),
+}
+
+pub fn cursor_limit(limit: Option<i32>) -> i32 {
+    limit.unwrap_or(10).clamp(1, MAX_CURSOR_LIMIT)
+}
+
+// Cursors are the sort key of the last returned row as URL-safe base64 JSON; clients treat
+// them as opaque
+fn encode_cursor<K: Serialize>(key: &K) -> String {
+    URL_SAFE_NO_PAD.encode(serde_json::to_vec(key).unwrap_or_default())
+}
+
+// None for the empty cursor that starts a listing
+pub fn decode_cursor<K: DeserializeOwned>(cursor: &str) -> Result<Option<K>, AppError> {
+    if cursor.is_empty() {
+        return Ok(None);
+    }
+
+    URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .map(Some)
+        .ok_or_else(|| {
+            AppError::ValidationError(ErrorCode::InvalidCursor, "Invalid cursor".to_string())
+        })
+}
+
+// Version of a single resource, taken from its `updated_at`. Clients polling it send the tag
+// back in If-None-Match to get an empty 304 while nothing changed, and in If-Match to make an
+// update fail instead of overwriting a change they have not seen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ETag(String);
+
+impl ETag {
+    pub fn from_updated_at(updated_at: DateTime<Utc>) -> Self {
+        Self(format!("\"{:x}\"", updated_at.timestamp_micros()))
+    }
+
+    // None without the header; `*` matches any version. A weak `W/` tag only counts where
+    // `weak` comparison is allowed (If-None-Match)
+    fn listed_in(&self, headers: &HeaderMap, name: HeaderName, weak: bool) -> Option<bool> {
+        let values: Vec<&str> = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+
+        Some(values.into_iter().any(|tag| {
+            tag == "*" || tag == self.0 || (weak && tag.strip_prefix("W/") == Some(self.0.as_str()))
+        }))
+    }
+
+    // Refuse a write when If-Match names another version; without the header it goes ahead
+    pub fn check_if_match(&self, headers: &HeaderMap) -> Result<(), AppError> {
+        match self.listed_in(headers, header::IF_MATCH, false) {
+            Some(false) => Err(AppError::PreconditionFailed(
+                ErrorCode::PreconditionFailed,
+                "The resource was changed since it was fetched, reload it and retry".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // `data` in the usual envelope with this tag, or an empty 304 when If-None-Match shows the
+    // client already has this version
+    pub fn respond<T: Serialize>(self, headers: &HeaderMap, data: T) -> Response {
+        if self
+            .listed_in(headers, header::IF_NONE_MATCH, true)
+            .unwrap_or(false)
+        {
+            return (StatusCode::NOT_MODIFIED, [self.header()]).into_response();
+        }
+        self.tag(data)
+    }
+
+    // `data` in the usual envelope with this tag, e.g. the result of an update
+    pub fn tag<T: Serialize>(self, data: T) -> Response {
+        (
+            [self.header()],
+            Json(ApiResponse {
+                success: true,
+                data,
+            }),
+        )
+            .into_response()
+    }
+
+    fn header(&self) -> (HeaderName, HeaderValue) {
+        (
+            header::ETAG,
+            HeaderValue::from_str(&self.0).expect("ETags are ASCII"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn headers(name: HeaderName, value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn if_none_match_accepts_weak_and_listed_tags() {
+        let etag = ETag::from_updated_at(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+        let tag = etag.0.clone();
+
+        for value in [
+            tag.clone(),
+            format!("W/{}", tag),
+            format!("\"x\", {}", tag),
+            "*".into(),
+        ] {
+            let response = etag
+                .clone()
+                .respond(&headers(header::IF_NONE_MATCH, &value), ());
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{}", value);
+        }
+
+        let response = etag.respond(&headers(header::IF_NONE_MATCH, "\"x\""), ());
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key(header::ETAG));
+    }
+
+    #[test]
+    fn if_match_compares_strongly() {
+        let etag = ETag::from_updated_at(Utc.timestamp_opt(1_700_000_000, 0).unwrap());
+        let tag = etag.0.clone();
+
+        assert!(etag.check_if_match(&HeaderMap::new()).is_ok());
+        assert!(etag
+            .check_if_match(&headers(header::IF_MATCH, &tag))
+            .is_ok());
+        assert!(etag.check_if_match(&headers(header::IF_MATCH, "*")).is_ok());
+        assert!(etag
+            .check_if_match(&headers(header::IF_MATCH, &format!("W/{}", tag)))
+            .is_err());
+        assert!(etag
+            .check_if_match(&headers(header::IF_MATCH, "\"x\""))
+            .is_err());
+    }
+}
@@ -1,21 +1,23 @@
+use std::sync::Arc;
+
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::HeaderMap,
+    response::Response,
     Json,
 };
-use serde::{Deserialize, Serialize};
-use sqlx::MySqlPool;
+use serde::Deserialize;
 
+use super::response::{
+    cursor_limit, decode_cursor, CursorResponse, ETag, ListResponse, PaginatedResponse, Pagination,
+    PaginationParams,
+};
+use crate::error::{AppError, ErrorCode};
 use crate::models::Route;
-
-// Query parameters for pagination
-#[derive(Debug, Deserialize)]
-pub struct PaginationParams {
-    pub page: Option<i32>,
-    pub limit: Option<i32>,
-}
+use crate::repositories::RouteRepository;
 
 // Create route request body
+#[allow(dead_code)] // not routed yet
 #[derive(Debug, Deserialize)]
 pub struct CreateRouteRequest {
     pub origin: String,
@@ -25,6 +27,7 @@ pub struct CreateRouteRequest {
 }
 
 // Update route request body
+#[allow(dead_code)] // not routed yet
 #[derive(Debug, Deserialize)]
 pub struct UpdateRouteRequest {
     pub origin: Option<String>,
@@ -33,70 +36,35 @@ pub struct UpdateRouteRequest {
     pub estimated_duration: Option<String>,
 }
 
-// Response wrapper
-#[derive(Debug, Serialize)]
-pub struct ApiResponse<T> {
-    pub success: bool,
-    pub data: T,
-}
-
-// Pagination response wrapper
-#[derive(Debug, Serialize)]
-pub struct PaginatedResponse<T> {
-    pub success: bool,
-    pub count: usize,
-    pub pagination: Pagination,
-    pub data: Vec<T>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct Pagination {
-    pub page: i32,
-    pub limit: i32,
-    pub total_pages: i32,
-    pub total_items: i64,
-}
-
-// Get all routes with pagination
+// Get all routes, by page or with a `cursor` (keyset pagination)
 pub async fn get_routes(
-    State(pool): State<MySqlPool>,
+    State(repository): State<Arc<dyn RouteRepository>>,
     Query(params): Query<PaginationParams>,
-) -> Result<Json<PaginatedResponse<Route>>, (StatusCode, Json<serde_json::Value>)> {
+) -> Result<Json<ListResponse<Route>>, AppError> {
+    if let Some(cursor) = &params.cursor {
+        let limit = cursor_limit(params.limit);
+        let routes = repository
+            .find_after(decode_cursor(cursor)?, limit + 1)
+            .await?;
+        return Ok(Json(ListResponse::Cursor(CursorResponse::from_rows(
+            routes,
+            limit,
+            |route| route.route_id,
+        ))));
+    }
+
     let page = params.page.unwrap_or(1);
     let limit = params.limit.unwrap_or(10);
 
     // Get routes and count
-    let routes = match Route::find_all(&pool, page, limit).await {
-        Ok(routes) => routes,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "success": false,
-                    "error": format!("Database error: {}", e)
-                })),
-            ));
-        }
-    };
-
-    let total = match Route::count(&pool).await {
-        Ok(count) => count,
-        Err(e) => {
-            return Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(serde_json::json!({
-                    "success": false,
-                    "error": format!("Database error: {}", e)
-                })),
-            ));
-        }
-    };
+    let routes = repository.find_all(page, limit).await?;
+    let total = repository.count().await?;
 
     // Calculate total pages
     let total_pages = (total as f64 / limit as f64).ceil() as i32;
 
     // Create and return response
-    Ok(Json(PaginatedResponse {
+    Ok(Json(ListResponse::Paginated(PaginatedResponse {
         success: true,
         count: routes.len(),
         pagination: Pagination {
@@ -106,32 +74,21 @@ pub async fn get_routes(
             total_items: total,
         },
         data: routes,
-    }))
+    })))
 }
 
-// Add get_route_by_id handler
+// Get route by id; 304 while If-None-Match is current
 pub async fn get_route_by_id(
-    State(pool): State<MySqlPool>,
+    State(repository): State<Arc<dyn RouteRepository>>,
     Path(id): Path<i32>,
-) -> Result<Json<ApiResponse<Route>>, (StatusCode, Json<serde_json::Value>)> {
-    match Route::find_by_id(&pool, id).await {
-        Ok(Some(route)) => Ok(Json(ApiResponse {
-            success: true,
-            data: route,
-        })),
-        Ok(None) => Err((
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({
-                "success": false,
-                "error": format!("Route with id {} not found", id)
-            })),
-        )),
-        Err(e) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "success": false,
-                "error": format!("Database error: {}", e)
-            })),
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    match repository.find_by_id(id).await {
+        Ok(Some(route)) => Ok(ETag::from_updated_at(route.updated_at).respond(&headers, route)),
+        Ok(None) => Err(AppError::NotFound(
+            ErrorCode::RouteNotFound,
+            format!("Route with id {} not found", id),
         )),
+        Err(e) => Err(e.into()),
     }
 }
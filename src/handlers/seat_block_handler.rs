@@ -0,0 +1,166 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::api_key::ReadCaller;
+use crate::middleware::guard::RequireStaff;
+use crate::models::{
+    ApiScope, AuditLog, Flight, FlightSeat, Occupancy, SeatBlock, SeatBlockReason,
+};
+use crate::validation::{not_blank, ValidJson};
+
+// Place seat block request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateSeatBlockRequest {
+    #[validate(custom(function = "not_blank"), length(max = 5))]
+    pub seat_number: String,
+    pub reason: SeatBlockReason,
+    #[validate(length(max = 255))]
+    pub note: Option<String>,
+}
+
+async fn ensure_flight_exists(pool: &DbPool, id: i32) -> Result<(), AppError> {
+    match Flight::find_by_id(pool, id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(flight_not_found(id)),
+        Err(e) => Err(e.into()),
+    }
+}
+
+// List seat blocks of a flight (staff or API key with flights:read)
+pub async fn get_seat_blocks(
+    State(pool): State<DbPool>,
+    caller: ReadCaller,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<SeatBlock>>>, AppError> {
+    caller.require(ApiScope::Flights)?;
+    ensure_flight_exists(&pool, id).await?;
+
+    let blocks = SeatBlock::find_by_flight(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: blocks,
+    }))
+}
+
+// Block a seat on a flight (staff only)
+pub async fn create_seat_block(
+    State(pool): State<DbPool>,
+    auth: RequireStaff,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<CreateSeatBlockRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<SeatBlock>>), AppError> {
+    ensure_flight_exists(&pool, id).await?;
+
+    let seat_number = payload.seat_number.trim().to_uppercase();
+
+    if !FlightSeat::exists(&pool, id, &seat_number).await? {
+        return Err(AppError::ValidationError(
+            ErrorCode::UnknownSeat,
+            format!("Seat {} is not on the flight's seat map", seat_number),
+        ));
+    }
+    if SeatBlock::seat_taken(&pool, id, &seat_number).await? {
+        return Err(AppError::ConflictError(
+            ErrorCode::SeatAlreadyTaken,
+            format!("Seat {} is already booked or blocked", seat_number),
+        ));
+    }
+
+    let block = SeatBlock::create(
+        &pool,
+        id,
+        &seat_number,
+        payload.reason,
+        payload.note.as_deref(),
+        auth.user_id,
+    )
+    .await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "seat_block.created",
+        "seat_block",
+        block.block_id,
+        serde_json::json!({ "flight_id": id, "seat_number": &block.seat_number, "reason": block.reason }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: block,
+        }),
+    ))
+}
+
+// Release a seat block (staff only)
+pub async fn delete_seat_block(
+    State(pool): State<DbPool>,
+    auth: RequireStaff,
+    Path((id, block_id)): Path<(i32, i32)>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    if !SeatBlock::delete(&pool, id, block_id).await? {
+        return Err(AppError::NotFound(
+            ErrorCode::SeatBlockNotFound,
+            format!("Seat block with id {} not found on flight {}", block_id, id),
+        ));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "seat_block.deleted",
+        "seat_block",
+        block_id,
+        serde_json::json!({ "flight_id": id }),
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: serde_json::json!({}),
+    }))
+}
+
+// Seat occupancy of a flight; blocked seats are excluded from availability
+pub async fn get_flight_occupancy(
+    State(pool): State<DbPool>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Occupancy>>, AppError> {
+    ensure_flight_exists(&pool, id).await?;
+
+    let occupancy = SeatBlock::occupancy(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: occupancy,
+    }))
+}
+
+// Seat map of a flight with the state of every seat; empty for flights scheduled on an aircraft
+// without a cabin layout
+pub async fn get_flight_seats(
+    State(pool): State<DbPool>,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<FlightSeat>>>, AppError> {
+    ensure_flight_exists(&pool, id).await?;
+
+    let seats = FlightSeat::find_by_flight(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: seats,
+    }))
+}
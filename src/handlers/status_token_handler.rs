@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{PublicFlightStatus, StatusToken, Ticket};
+
+// Create a shareable status link for a ticket's flight (ticket owner or staff)
+pub async fn create_status_token(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(ticket_id): Path<i32>,
+) -> Result<(StatusCode, Json<ApiResponse<StatusToken>>), AppError> {
+    let ticket = Ticket::find_by_id(&pool, ticket_id).await?.ok_or_else(|| {
+        AppError::NotFound(
+            ErrorCode::TicketNotFound,
+            format!("Ticket with id {} not found", ticket_id),
+        )
+    })?;
+
+    auth.require_owner(ticket.user_id)?;
+
+    let token = StatusToken::create(&pool, ticket.ticket_id).await?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: token,
+        }),
+    ))
+}
+
+// Public flight status behind a share token; no authentication, no personal data
+pub async fn get_public_flight_status(
+    State(pool): State<DbPool>,
+    Path(token): Path<String>,
+) -> Result<Json<ApiResponse<PublicFlightStatus>>, AppError> {
+    match StatusToken::flight_status(&pool, &token).await {
+        Ok(Some(status)) => Ok(Json(ApiResponse {
+            success: true,
+            data: status,
+        })),
+        Ok(None) => Err(AppError::NotFound(
+            ErrorCode::StatusLinkNotFound,
+            "Status link not found or expired".to_string(),
+        )),
+        Err(e) => Err(e.into()),
+    }
+}
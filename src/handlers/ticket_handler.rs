@@ -0,0 +1,308 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::flight_handler::flight_not_found;
+use super::response::ApiResponse;
+use crate::config::Config;
+use crate::currencies::BASE_CURRENCY;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{
+    AssistanceRequest, AuditLog, Flight, FlightSeat, FlightStatus, PassengerType, SeatBlock,
+    SeatChange, Ticket, TicketInfant, User,
+};
+use crate::validation::ValidJson;
+
+// Change seat request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct ChangeSeatRequest {
+    #[validate(length(min = 2, max = 5))]
+    pub seat_number: String,
+}
+
+// A ticket in its new seat and what the move cost
+#[derive(Debug, Serialize)]
+pub struct ChangedSeat {
+    pub ticket: Ticket,
+    pub change: SeatChange,
+}
+
+pub(crate) fn ticket_not_found(id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::TicketNotFound,
+        format!("Ticket with id {} not found", id),
+    )
+}
+
+// Why the ticket holder or the infant on their lap is not of the age the ticket was booked for
+// on the departure date, if they are not
+async fn age_mismatch(
+    pool: &DbPool,
+    ticket: &Ticket,
+    departure_date: NaiveDate,
+) -> Result<Option<String>, AppError> {
+    let holder = User::find_by_id(pool, ticket.user_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+    match holder
+        .date_of_birth
+        .map(|born| PassengerType::on(born, departure_date))
+    {
+        Some(actual) if actual != ticket.passenger_type => {
+            return Ok(Some(format!(
+                "Ticket {} is for a {} passenger but its holder is a {} on {}",
+                ticket.ticket_id,
+                ticket.passenger_type.as_str(),
+                actual.as_str(),
+                departure_date
+            )));
+        }
+        None if ticket.passenger_type != PassengerType::Adult => {
+            return Ok(Some(format!(
+                "Ticket {} is for a {} passenger but its holder's profile has no date of birth",
+                ticket.ticket_id,
+                ticket.passenger_type.as_str()
+            )));
+        }
+        _ => {}
+    }
+
+    if let Some(infant) = TicketInfant::find_by_ticket(pool, ticket.ticket_id).await? {
+        if PassengerType::on(infant.date_of_birth, departure_date) != PassengerType::Infant {
+            return Ok(Some(format!(
+                "{} {} is 2 or older on {} and needs a seat of their own",
+                infant.first_name, infant.last_name, departure_date
+            )));
+        }
+    }
+    Ok(None)
+}
+
+// Get a ticket (its holder or staff)
+pub async fn get_ticket(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Ticket>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+
+    auth.require_owner(ticket.user_id)?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ticket,
+    }))
+}
+
+// Check a ticket in (its holder or staff) while the flight has not departed. Refused while an
+// assistance request on the ticket is waiting for staff to acknowledge it, or when a passenger
+// had a birthday since booking that changes what they may travel as
+pub async fn check_in_ticket(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Ticket>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed | FlightStatus::Boarding
+    ) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Tickets cannot be checked in for a {} flight",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    if let Some(mismatch) = age_mismatch(&pool, &ticket, flight.departure_time.date_naive()).await?
+    {
+        return Err(AppError::ConflictError(
+            ErrorCode::PassengerAgeMismatch,
+            mismatch,
+        ));
+    }
+
+    Ticket::check_in(&pool, id).await?;
+    let checked = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+
+    if !checked.checked_in {
+        let pending: Vec<&str> = AssistanceRequest::find_by_ticket(&pool, id)
+            .await?
+            .iter()
+            .filter(|request| !request.acknowledged())
+            .map(|request| request.assistance_type.as_str())
+            .collect();
+        return Err(AppError::ConflictError(
+            ErrorCode::AssistanceNotAcknowledged,
+            format!(
+                "Staff must acknowledge the {} assistance request(s) before check-in",
+                pending.join(", ")
+            ),
+        ));
+    }
+
+    if !ticket.checked_in {
+        AuditLog::record(
+            &pool,
+            Some(auth.user_id),
+            "ticket.checked_in",
+            "ticket",
+            id,
+            serde_json::json!({ "flight_id": ticket.flight_id }),
+        )
+        .await;
+    }
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: checked,
+    }))
+}
+
+// Move a ticket to a free seat of the same class (its holder or staff) before the flight closes.
+// Moving into an exit row from a seat without extra legroom costs the exit-row fee
+pub async fn change_seat(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<ChangeSeatRequest>,
+) -> Result<Json<ApiResponse<ChangedSeat>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    if !matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Seats cannot be changed on a {} flight",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let seat_number = payload.seat_number.trim().to_uppercase();
+    if seat_number == ticket.seat_number {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            format!("Ticket {} is already in seat {}", id, seat_number),
+        ));
+    }
+
+    // Flights without a seat map have no classes of seats and no premium ones
+    let seat_map = FlightSeat::find_by_flight(&pool, flight.flight_id).await?;
+    let mut fee = 0.0;
+    if !seat_map.is_empty() {
+        let seat = seat_map
+            .iter()
+            .find(|seat| seat.seat_number == seat_number)
+            .ok_or_else(|| {
+                AppError::ValidationError(
+                    ErrorCode::UnknownSeat,
+                    format!("Seat {} is not on the flight's seat map", seat_number),
+                )
+            })?;
+        if seat.fare_class != ticket.fare_class {
+            return Err(AppError::ValidationError(
+                ErrorCode::SeatNotInClass,
+                format!(
+                    "Seat {} is in {}, the ticket is {}",
+                    seat_number,
+                    seat.fare_class.as_str(),
+                    ticket.fare_class.as_str()
+                ),
+            ));
+        }
+        let from_exit_row = seat_map
+            .iter()
+            .any(|current| current.seat_number == ticket.seat_number && current.exit_row);
+        if seat.exit_row && !from_exit_row {
+            fee = config.exit_row_seat_fee;
+        }
+    }
+
+    if SeatBlock::seat_taken(&pool, flight.flight_id, &seat_number).await? {
+        return Err(AppError::ConflictError(
+            ErrorCode::SeatAlreadyTaken,
+            format!("Seat {} is already booked or blocked", seat_number),
+        ));
+    }
+
+    let change = SeatChange::apply(
+        &pool,
+        id,
+        &ticket.seat_number,
+        &seat_number,
+        (fee, BASE_CURRENCY),
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|db| db.is_unique_violation())
+        {
+            AppError::ConflictError(
+                ErrorCode::SeatAlreadyTaken,
+                format!("Seat {} was booked meanwhile", seat_number),
+            )
+        } else {
+            e.into()
+        }
+    })?
+    .ok_or_else(|| {
+        AppError::ConflictError(
+            ErrorCode::ConcurrentModification,
+            "The ticket or the seat changed meanwhile, please retry".to_string(),
+        )
+    })?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ticket.seat_changed",
+        "ticket",
+        id,
+        serde_json::json!({
+            "from_seat": &change.from_seat,
+            "to_seat": &change.to_seat,
+            "fee": change.fee,
+        }),
+    )
+    .await;
+
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: ChangedSeat { ticket, change },
+    }))
+}
@@ -0,0 +1,189 @@
+use std::sync::Arc;
+
+use axum::{extract::State, Json};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::response::ApiResponse;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::two_factor::{BACKUP_CODE_COUNT, LOCKOUT_MINUTES};
+use crate::models::UserTotp;
+use crate::repositories::UserRepository;
+use crate::totp;
+use crate::validation::ValidJson;
+
+#[derive(Debug, Serialize)]
+pub struct Enrollment {
+    pub secret: String,
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct TwoFactorCodeRequest {
+    #[validate(length(min = 1))]
+    pub code: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackupCodes {
+    pub backup_codes: Vec<String>,
+}
+
+fn invalid_code() -> AppError {
+    AppError::AuthError(
+        ErrorCode::InvalidTwoFactorCode,
+        "Invalid two-factor code".to_string(),
+    )
+}
+
+fn new_backup_codes() -> Vec<String> {
+    (0..BACKUP_CODE_COUNT)
+        .map(|_| totp::generate_backup_code())
+        .collect()
+}
+
+// Whether `code` is a fresh TOTP code for the secret; an accepted code cannot be replayed
+async fn accept_totp(pool: &DbPool, enrollment: &UserTotp, code: &str) -> Result<bool, AppError> {
+    let now = Utc::now().timestamp() as u64;
+    match totp::matching_step(&enrollment.secret, code.trim(), now) {
+        Some(step) => Ok(UserTotp::consume_step(pool, enrollment.user_id, step).await?),
+        None => Ok(false),
+    }
+}
+
+// Second login factor. Returns whether 2FA was performed: false for users without 2FA,
+// true once a TOTP or backup code was accepted; an error when the code is missing or wrong, or
+// while login codes are locked after too many wrong ones
+pub(crate) async fn verify_second_factor(
+    pool: &DbPool,
+    user_id: i32,
+    code: Option<&str>,
+) -> Result<bool, AppError> {
+    let Some(enrollment) = UserTotp::find(pool, user_id).await? else {
+        return Ok(false);
+    };
+    if !enrollment.is_enabled() {
+        return Ok(false);
+    }
+
+    let code = code.ok_or_else(|| {
+        AppError::AuthError(
+            ErrorCode::TwoFactorRequired,
+            "Two-factor code required".to_string(),
+        )
+    })?;
+
+    if enrollment.is_locked(Utc::now()) {
+        return Err(AppError::RateLimited(
+            ErrorCode::TwoFactorLocked,
+            format!(
+                "Too many wrong two-factor codes, retry in up to {} minutes",
+                LOCKOUT_MINUTES
+            ),
+        ));
+    }
+
+    if accept_totp(pool, &enrollment, code).await?
+        || UserTotp::use_backup_code(pool, user_id, code.trim()).await?
+    {
+        UserTotp::reset_failures(pool, user_id).await?;
+        Ok(true)
+    } else {
+        UserTotp::record_failure(pool, user_id).await?;
+        Err(invalid_code())
+    }
+}
+
+// Generate a TOTP secret for the caller; 2FA is not enforced until the first code is confirmed
+pub async fn enroll(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    State(config): State<Config>,
+    auth: AuthUser,
+) -> Result<Json<ApiResponse<Enrollment>>, AppError> {
+    if let Some(enrollment) = UserTotp::find(&pool, auth.user_id).await? {
+        if enrollment.is_enabled() {
+            return Err(AppError::ConflictError(
+                ErrorCode::TwoFactorAlreadyEnabled,
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+    }
+
+    let user = users.find_by_id(auth.user_id).await?.ok_or_else(|| {
+        AppError::AuthError(ErrorCode::InvalidToken, "User no longer exists".to_string())
+    })?;
+
+    let secret = totp::generate_secret();
+    UserTotp::start_enrollment(&pool, auth.user_id, &secret).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: Enrollment {
+            provisioning_uri: totp::provisioning_uri(&config.totp_issuer, &user.email, &secret),
+            secret,
+        },
+    }))
+}
+
+// Confirm the enrollment with a code from the authenticator app and receive backup codes
+pub async fn activate(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    ValidJson(payload): ValidJson<TwoFactorCodeRequest>,
+) -> Result<Json<ApiResponse<BackupCodes>>, AppError> {
+    let enrollment = UserTotp::find(&pool, auth.user_id)
+        .await?
+        .filter(|enrollment| !enrollment.is_enabled())
+        .ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::TwoFactorNotEnrolled,
+                "No pending two-factor enrollment".to_string(),
+            )
+        })?;
+
+    if !accept_totp(&pool, &enrollment, &payload.code).await? {
+        return Err(invalid_code());
+    }
+
+    let backup_codes = new_backup_codes();
+    UserTotp::activate(&pool, auth.user_id, &backup_codes).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: BackupCodes { backup_codes },
+    }))
+}
+
+// Replace the caller's backup codes; requires a current TOTP code
+pub async fn regenerate_backup_codes(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    ValidJson(payload): ValidJson<TwoFactorCodeRequest>,
+) -> Result<Json<ApiResponse<BackupCodes>>, AppError> {
+    let enrollment = UserTotp::find(&pool, auth.user_id)
+        .await?
+        .filter(UserTotp::is_enabled)
+        .ok_or_else(|| {
+            AppError::NotFound(
+                ErrorCode::TwoFactorNotEnrolled,
+                "Two-factor authentication is not enabled".to_string(),
+            )
+        })?;
+
+    if !accept_totp(&pool, &enrollment, &payload.code).await? {
+        return Err(invalid_code());
+    }
+
+    let backup_codes = new_backup_codes();
+    UserTotp::replace_backup_codes(&pool, auth.user_id, &backup_codes).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: BackupCodes { backup_codes },
+    }))
+}
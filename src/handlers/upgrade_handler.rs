@@ -0,0 +1,346 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::fare_class_handler::current_price;
+use super::flight_handler::flight_not_found;
+use super::passenger_handler::passenger_fares;
+use super::response::ApiResponse;
+use super::ticket_handler::ticket_not_found;
+use crate::config::Config;
+use crate::currencies::ExchangeRates;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{
+    AuditLog, FareClass, Flight, FlightFareClass, FlightSeat, FlightStatus, Ticket, TicketUpgrade,
+    UpgradeOutcome,
+};
+use crate::pricing::PricingEngine;
+use crate::validation::ValidJson;
+
+// Upgrade request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpgradeRequest {
+    pub fare_class: FareClass,
+    // Omit to be given the first free seat of the class
+    #[validate(length(min = 2, max = 5))]
+    pub seat_number: Option<String>,
+}
+
+// A higher class the ticket can move to and what the move costs
+#[derive(Debug, Serialize)]
+pub struct UpgradeOffer {
+    pub fare_class: FareClass,
+    pub seats_available: i64,
+    // Free seats of the class, empty on flights without a seat map
+    pub seats: Vec<String>,
+    // Difference between the current fares of the two classes for the ticket's passenger type
+    pub price: f64,
+    pub currency: String,
+}
+
+// A reissued ticket and the upgrade that reissued it
+#[derive(Debug, Serialize)]
+pub struct UpgradedTicket {
+    pub ticket: Ticket,
+    pub upgrade: TicketUpgrade,
+}
+
+fn open_for_upgrades(flight: &Flight) -> bool {
+    matches!(
+        flight.status,
+        FlightStatus::Scheduled | FlightStatus::Delayed
+    ) && flight.departure_time > Utc::now()
+}
+
+// Every class above the ticket's that the flight sells, priced in the currency the ticket's fare
+// was paid in (that of its class when the fare was not recorded), including the sold out ones
+async fn upgrade_offers(
+    pool: &DbPool,
+    config: &Config,
+    pricing: &PricingEngine,
+    ticket: &Ticket,
+    flight: &Flight,
+) -> Result<Vec<UpgradeOffer>, AppError> {
+    let classes = FlightFareClass::find_by_flight(pool, flight.flight_id).await?;
+    let booked = classes
+        .iter()
+        .find(|class| class.fare_class == ticket.fare_class)
+        .ok_or_else(|| {
+            AppError::InternalError(format!(
+                "Flight {} has no {} fare class for ticket {}",
+                flight.flight_id,
+                ticket.fare_class.as_str(),
+                ticket.ticket_id
+            ))
+        })?;
+    let currency = ticket
+        .currency
+        .clone()
+        .unwrap_or_else(|| booked.currency.clone());
+    let rates = if classes.iter().any(|class| class.currency != currency) {
+        Some(ExchangeRates::load(pool).await?)
+    } else {
+        None
+    };
+
+    let days_until_departure = (flight.departure_time - Utc::now()).num_days();
+    let fares = passenger_fares(config);
+    let fare = |class: &FlightFareClass| {
+        fares.fare(
+            current_price(pricing, class, days_until_departure),
+            ticket.passenger_type,
+        )
+    };
+    let in_currency = |class: &FlightFareClass| match &rates {
+        Some(rates) => rates
+            .convert(fare(class), &class.currency, &currency)
+            .ok_or_else(|| {
+                AppError::InternalError(format!(
+                    "No exchange rate between {} and {} on flight {}",
+                    class.currency, currency, flight.flight_id
+                ))
+            }),
+        None => Ok(fare(class)),
+    };
+    let booked_fare = in_currency(booked)?;
+    let seat_map = FlightSeat::find_by_flight(pool, flight.flight_id).await?;
+
+    let mut offers = Vec::new();
+    for class in classes
+        .iter()
+        .filter(|class| ticket.fare_class.higher().contains(&class.fare_class))
+    {
+        let class_fare = in_currency(class)?;
+        let seats: Vec<String> = seat_map
+            .iter()
+            .filter(|seat| seat.fare_class == class.fare_class && seat.status == "available")
+            .map(|seat| seat.seat_number.clone())
+            .collect();
+        let seats_available = if seat_map.is_empty() {
+            class.seats_available.max(0)
+        } else {
+            class.seats_available.clamp(0, seats.len() as i64)
+        };
+        offers.push(UpgradeOffer {
+            fare_class: class.fare_class,
+            seats_available,
+            seats,
+            price: ((class_fare - booked_fare).max(0.0) * 100.0).round() / 100.0,
+            currency: currency.clone(),
+        });
+    }
+    Ok(offers)
+}
+
+// Upgrades the ticket can be bought now, with their price (the holder or staff). Empty once the
+// flight departed or was cancelled
+pub async fn get_upgrade_offers(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    Extension(pricing): Extension<PricingEngine>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<UpgradeOffer>>>, AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    let offers = if open_for_upgrades(&flight) {
+        upgrade_offers(&pool, &config, &pricing, &ticket, &flight)
+            .await?
+            .into_iter()
+            .filter(|offer| offer.seats_available > 0)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: offers,
+    }))
+}
+
+// Buy an upgrade to a higher class before departure (the holder or staff). The fare difference
+// is added to the ticket's fare, in the ticket's currency, and the ticket is reissued under a new
+// number in the requested seat or the first free one of the class. No payment is taken here;
+// like the fare itself, the difference is only recorded
+pub async fn purchase_upgrade(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    Extension(pricing): Extension<PricingEngine>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    ValidJson(payload): ValidJson<UpgradeRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<UpgradedTicket>>), AppError> {
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+    auth.require_owner(ticket.user_id)?;
+
+    let flight = Flight::find_by_id(&pool, ticket.flight_id)
+        .await?
+        .ok_or_else(|| flight_not_found(ticket.flight_id))?;
+    if !open_for_upgrades(&flight) {
+        return Err(AppError::ConflictError(
+            ErrorCode::FlightClosed,
+            format!(
+                "Upgrades can only be bought before departure, the flight is {}",
+                flight.status.as_str()
+            ),
+        ));
+    }
+
+    let offer = upgrade_offers(&pool, &config, &pricing, &ticket, &flight)
+        .await?
+        .into_iter()
+        .find(|offer| offer.fare_class == payload.fare_class)
+        .ok_or_else(|| {
+            AppError::ValidationError(
+                ErrorCode::UpgradeNotOffered,
+                format!(
+                    "Flight {} sells no {} seats above the ticket's {}",
+                    flight.flight_number,
+                    payload.fare_class.as_str(),
+                    ticket.fare_class.as_str()
+                ),
+            )
+        })?;
+    if offer.seats_available < 1 {
+        return Err(AppError::ConflictError(
+            ErrorCode::ClassSoldOut,
+            format!(
+                "{} on flight {} has no seats left",
+                offer.fare_class.as_str(),
+                flight.flight_number
+            ),
+        ));
+    }
+
+    let seat_number = payload
+        .seat_number
+        .as_deref()
+        .map(|seat| seat.trim().to_uppercase());
+    let seat_map = FlightSeat::find_by_flight(&pool, flight.flight_id).await?;
+    match (&seat_number, seat_map.is_empty()) {
+        (None, true) => {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                format!(
+                    "Flight {} has no seat map, the upgrade needs a seat_number",
+                    flight.flight_number
+                ),
+            ));
+        }
+        (Some(seat_number), false) => {
+            let seat = seat_map
+                .iter()
+                .find(|seat| &seat.seat_number == seat_number)
+                .ok_or_else(|| {
+                    AppError::ValidationError(
+                        ErrorCode::UnknownSeat,
+                        format!("Seat {} is not on the flight's seat map", seat_number),
+                    )
+                })?;
+            if seat.fare_class != offer.fare_class {
+                return Err(AppError::ValidationError(
+                    ErrorCode::SeatNotInClass,
+                    format!(
+                        "Seat {} is in {}, not {}",
+                        seat_number,
+                        seat.fare_class.as_str(),
+                        offer.fare_class.as_str()
+                    ),
+                ));
+            }
+        }
+        _ => {}
+    }
+
+    let outcome = TicketUpgrade::purchase(
+        &pool,
+        &ticket,
+        offer.fare_class,
+        seat_number.as_deref(),
+        (offer.price, &offer.currency),
+        auth.user_id,
+    )
+    .await
+    .map_err(|e| {
+        if e.as_database_error()
+            .is_some_and(|db| db.is_unique_violation())
+        {
+            AppError::ConflictError(
+                ErrorCode::SeatAlreadyTaken,
+                "The seat was booked meanwhile, please retry".to_string(),
+            )
+        } else {
+            e.into()
+        }
+    })?;
+    let upgrade = match outcome {
+        UpgradeOutcome::Upgraded(upgrade) => upgrade,
+        UpgradeOutcome::ClassFull => {
+            return Err(AppError::ConflictError(
+                ErrorCode::ClassSoldOut,
+                format!(
+                    "{} on flight {} has no seats left",
+                    offer.fare_class.as_str(),
+                    flight.flight_number
+                ),
+            ));
+        }
+        UpgradeOutcome::SeatTaken(seat_number) => {
+            return Err(AppError::ConflictError(
+                ErrorCode::SeatAlreadyTaken,
+                format!("Seat {} is already booked or blocked", seat_number),
+            ));
+        }
+        UpgradeOutcome::TicketChanged => {
+            return Err(AppError::ConflictError(
+                ErrorCode::ConcurrentModification,
+                "The ticket changed meanwhile, please retry".to_string(),
+            ));
+        }
+    };
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "ticket.upgraded",
+        "ticket",
+        id,
+        serde_json::json!({
+            "from_class": upgrade.from_class,
+            "to_class": upgrade.to_class,
+            "to_seat": &upgrade.to_seat,
+            "ticket_number": &upgrade.ticket_number,
+            "price": upgrade.price,
+            "currency": &upgrade.currency,
+        }),
+    )
+    .await;
+
+    let ticket = Ticket::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| ticket_not_found(id))?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: UpgradedTicket { ticket, upgrade },
+        }),
+    ))
+}
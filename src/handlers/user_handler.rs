@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Deserialize;
+use tracing::error;
+use validator::Validate;
+
+use super::response::{
+    cursor_limit, decode_cursor, ApiResponse, CursorResponse, ETag, ListResponse,
+    PaginatedResponse, Pagination,
+};
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::export::{self, ExportFormat, ExportParams};
+use crate::middleware::auth::AuthUser;
+use crate::middleware::guard::RequireStaff;
+use crate::models::{AuditLog, Ticket, UpdateProfile, UserDocument, UserFilter, UserSort};
+use crate::repositories::UserRepository;
+use crate::storage::ObjectStorage;
+use crate::validation::ValidJson;
+
+fn user_not_found(id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::UserNotFound,
+        format!("User with id {} not found", id),
+    )
+}
+
+// List users filtered by role, staff position and nationality, sorted by `sort` and `order`;
+// by page or with a `cursor` (keyset pagination, name order only), or all of them as a CSV or
+// Excel download (`format=` or the Accept header). Staff only
+pub async fn get_users(
+    State(users): State<Arc<dyn UserRepository>>,
+    _: RequireStaff,
+    headers: HeaderMap,
+    Query(filter): Query<UserFilter>,
+) -> Result<Response, AppError> {
+    let format = ExportFormat::negotiate(filter.format, &headers);
+    if format != ExportFormat::Json {
+        return Ok(export::download(format, "users", move |rows| async move {
+            users.export(&filter, rows).await
+        }));
+    }
+
+    if let Some(cursor) = &filter.cursor {
+        if filter.sort.unwrap_or_default() != UserSort::LastName {
+            return Err(AppError::ValidationError(
+                ErrorCode::ValidationFailed,
+                "Cursor pagination only supports sort=last_name".to_string(),
+            ));
+        }
+        let limit = cursor_limit(filter.limit);
+        let found = users
+            .find_after(&filter, decode_cursor(cursor)?, limit + 1)
+            .await?;
+        return Ok(Json(ListResponse::Cursor(CursorResponse::from_rows(
+            found,
+            limit,
+            |user| (user.last_name.clone(), user.user_id),
+        )))
+        .into_response());
+    }
+
+    let page = filter.page.unwrap_or(1);
+    let limit = filter.limit.unwrap_or(10);
+
+    let found = users.find_all(&filter, page, limit).await?;
+    let total = users.count(&filter).await?;
+
+    let total_pages = (total as f64 / limit as f64).ceil() as i32;
+
+    Ok(Json(ListResponse::Paginated(PaginatedResponse {
+        success: true,
+        count: found.len(),
+        pagination: Pagination {
+            page,
+            limit,
+            total_pages,
+            total_items: total,
+        },
+        data: found,
+    }))
+    .into_response())
+}
+
+// Get a user's profile (the user themselves or staff); 304 while If-None-Match is current
+pub async fn get_user(
+    State(users): State<Arc<dyn UserRepository>>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    auth.require_owner(id)?;
+
+    let user = users
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| user_not_found(id))?;
+
+    Ok(ETag::from_updated_at(user.updated_at).respond(&headers, user))
+}
+
+// Update a user's profile (the user themselves or staff); 412 if If-Match names an older
+// version of it
+pub async fn update_user(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    ValidJson(payload): ValidJson<UpdateProfile>,
+) -> Result<Response, AppError> {
+    auth.require_owner(id)?;
+
+    let current = users
+        .find_by_id(id)
+        .await?
+        .ok_or_else(|| user_not_found(id))?;
+    ETag::from_updated_at(current.updated_at).check_if_match(&headers)?;
+
+    let user = users
+        .update_profile(id, &payload)
+        .await?
+        .ok_or_else(|| user_not_found(id))?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "user.updated",
+        "user",
+        id,
+        serde_json::json!({ "fields": payload.changed_fields() }),
+    )
+    .await;
+
+    Ok(ETag::from_updated_at(user.updated_at).tag(user))
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct EraseAccountRequest {
+    // The current password, confirming the request
+    #[validate(length(min = 1))]
+    pub password: String,
+}
+
+// Erase the caller's account. Their name, contact and passport details are removed from the
+// account and from the tickets, miles and seat blocks that outlive it, and the account can no
+// longer sign in. Irreversible
+pub async fn erase_account(
+    State(pool): State<DbPool>,
+    State(users): State<Arc<dyn UserRepository>>,
+    Extension(storage): Extension<Arc<dyn ObjectStorage>>,
+    auth: AuthUser,
+    ValidJson(payload): ValidJson<EraseAccountRequest>,
+) -> Result<StatusCode, AppError> {
+    let user = users
+        .find_by_id(auth.user_id)
+        .await?
+        .ok_or_else(|| user_not_found(auth.user_id))?;
+
+    let password_matches = bcrypt::verify(&payload.password, &user.password)
+        .map_err(|e| AppError::InternalError(format!("Password check failed: {}", e)))?;
+    if !password_matches {
+        return Err(AppError::AuthError(
+            ErrorCode::InvalidCredentials,
+            "Password is wrong".to_string(),
+        ));
+    }
+
+    // Nobody knows this password, so the anonymized account cannot be signed into
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect();
+    let password_hash = bcrypt::hash(secret, bcrypt::DEFAULT_COST)
+        .map_err(|e| AppError::InternalError(format!("Password hashing failed: {}", e)))?;
+    let documents = UserDocument::find_by_user(&pool, auth.user_id).await?;
+    users.erase(auth.user_id, &password_hash).await?;
+    if let Some(avatar) = &user.avatar_key {
+        super::avatar_handler::delete_file(storage.as_ref(), avatar).await;
+    }
+    for document in documents {
+        if let Err(e) = storage.delete(&document.storage_key).await {
+            error!(
+                "Failed to delete document {} of erased user {}: {}",
+                document.storage_key, auth.user_id, e
+            );
+        }
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "user.erased",
+        "user",
+        auth.user_id,
+        serde_json::Value::Null,
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// List a user's tickets (the user themselves or staff), or download them as CSV or Excel
+pub async fn get_user_tickets(
+    State(pool): State<DbPool>,
+    auth: AuthUser,
+    Path(id): Path<i32>,
+    headers: HeaderMap,
+    Query(params): Query<ExportParams>,
+) -> Result<Response, AppError> {
+    auth.require_owner(id)?;
+
+    let format = ExportFormat::negotiate(params.format, &headers);
+    if format != ExportFormat::Json {
+        return Ok(export::download(
+            format,
+            "tickets",
+            move |rows| async move { Ticket::export_by_user(&pool, id, rows).await },
+        ));
+    }
+
+    let tickets = Ticket::find_by_user(&pool, id).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: tickets,
+    })
+    .into_response())
+}
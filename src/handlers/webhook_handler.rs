@@ -0,0 +1,142 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::response::ApiResponse;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::http_client::validate_url;
+use crate::middleware::guard::RequireAdmin;
+use crate::models::{AuditLog, Webhook, WebhookDelivery, WebhookEvent};
+use crate::validation::ValidJson;
+
+// Deliveries shown in a webhook's log
+const DELIVERY_LOG_LIMIT: i32 = 100;
+
+// Register webhook request body
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    #[validate(length(min = 1))]
+    pub events: Vec<WebhookEvent>,
+}
+
+// A freshly registered webhook; the signing `secret` is only ever returned here
+#[derive(Debug, Serialize)]
+pub struct IssuedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+fn webhook_not_found(id: i32) -> AppError {
+    AppError::NotFound(
+        ErrorCode::WebhookNotFound,
+        format!("Active webhook with id {} not found", id),
+    )
+}
+
+// List webhooks, including disabled ones (admin only)
+pub async fn get_webhooks(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+) -> Result<Json<ApiResponse<Vec<Webhook>>>, AppError> {
+    let webhooks = Webhook::find_all(&pool).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: webhooks,
+    }))
+}
+
+// Register a webhook URL for the given events (admin only)
+pub async fn create_webhook(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    ValidJson(payload): ValidJson<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<ApiResponse<IssuedWebhook>>), AppError> {
+    let url = payload.url.trim();
+    if let Err(e) = validate_url(url) {
+        return Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            e.to_string(),
+        ));
+    }
+    let mut events = Vec::new();
+    for event in payload.events {
+        if !events.contains(&event) {
+            events.push(event);
+        }
+    }
+
+    let (webhook, secret) = Webhook::create(&pool, url, &events, auth.user_id).await?;
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "webhook.created",
+        "webhook",
+        webhook.webhook_id,
+        serde_json::json!({ "url": &webhook.url, "events": &webhook.events }),
+    )
+    .await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse {
+            success: true,
+            data: IssuedWebhook { webhook, secret },
+        }),
+    ))
+}
+
+// Disable a webhook and drop its pending deliveries (admin only)
+pub async fn disable_webhook(
+    State(pool): State<DbPool>,
+    auth: RequireAdmin,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<serde_json::Value>>, AppError> {
+    if !Webhook::disable(&pool, id).await? {
+        return Err(webhook_not_found(id));
+    }
+
+    AuditLog::record(
+        &pool,
+        Some(auth.user_id),
+        "webhook.disabled",
+        "webhook",
+        id,
+        serde_json::Value::Null,
+    )
+    .await;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: serde_json::json!({}),
+    }))
+}
+
+// Latest deliveries of a webhook with their attempt count and last result (admin only)
+pub async fn get_webhook_deliveries(
+    State(pool): State<DbPool>,
+    _: RequireAdmin,
+    Path(id): Path<i32>,
+) -> Result<Json<ApiResponse<Vec<WebhookDelivery>>>, AppError> {
+    if !Webhook::exists(&pool, id).await? {
+        return Err(AppError::NotFound(
+            ErrorCode::WebhookNotFound,
+            format!("Webhook with id {} not found", id),
+        ));
+    }
+
+    let deliveries = WebhookDelivery::find_by_webhook(&pool, id, DELIVERY_LOG_LIMIT).await?;
+
+    Ok(Json(ApiResponse {
+        success: true,
+        data: deliveries,
+    }))
+}
@@ -0,0 +1,244 @@
+// Just enough HTTP/1.1 to POST a JSON payload (webhooks, error reports) and read the response
+// status, or to send any request and read the whole response (object storage). Runs on a
+// blocking thread so the same code serves plain TCP and rustls streams
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use thiserror::Error;
+use url::Url;
+
+#[derive(Debug, Error)]
+pub enum HttpError {
+    #[error("invalid url: {0}")]
+    InvalidUrl(String),
+    #[error("tls: {0}")]
+    Tls(#[from] rustls::Error),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+    #[error("malformed response")]
+    MalformedResponse,
+}
+
+// Trusts the webpki roots; shared with the SMTP client
+pub(crate) fn tls_config() -> Arc<rustls::ClientConfig> {
+    static CONFIG: OnceLock<Arc<rustls::ClientConfig>> = OnceLock::new();
+    CONFIG
+        .get_or_init(|| {
+            let mut roots = rustls::RootCertStore::empty();
+            roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+                rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
+                    anchor.subject,
+                    anchor.spki,
+                    anchor.name_constraints,
+                )
+            }));
+            Arc::new(
+                rustls::ClientConfig::builder()
+                    .with_safe_defaults()
+                    .with_root_certificates(roots)
+                    .with_no_client_auth(),
+            )
+        })
+        .clone()
+}
+
+// Whether the URL can be delivered to: http(s) with a host
+pub fn validate_url(url: &str) -> Result<Url, HttpError> {
+    let parsed = Url::parse(url).map_err(|e| HttpError::InvalidUrl(e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(HttpError::InvalidUrl(
+            "only http and https urls are supported".to_string(),
+        ));
+    }
+    Ok(parsed)
+}
+
+// Status and, when asked for, body of a response
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+// Body of a `Transfer-Encoding: chunked` response
+fn read_chunked<R: BufRead>(reader: &mut R) -> Result<Vec<u8>, HttpError> {
+    let mut body = Vec::new();
+    loop {
+        let mut size_line = String::new();
+        reader.read_line(&mut size_line)?;
+        let size = size_line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16).map_err(|_| HttpError::MalformedResponse)?;
+        if size == 0 {
+            return Ok(body);
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        let mut crlf = [0; 2];
+        reader.read_exact(&mut crlf)?;
+    }
+}
+
+fn exchange<S: Read + Write>(
+    stream: &mut S,
+    request: &[u8],
+    read_body: bool,
+) -> Result<HttpResponse, HttpError> {
+    stream.write_all(request)?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    // e.g. "HTTP/1.1 204 No Content"
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse().ok())
+        .ok_or(HttpError::MalformedResponse)?;
+    if !read_body {
+        return Ok(HttpResponse {
+            status,
+            body: Vec::new(),
+        });
+    }
+
+    let mut content_length = None;
+    let mut chunked = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(HttpError::MalformedResponse);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        if name.eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse::<usize>().ok();
+        } else if name.eq_ignore_ascii_case("transfer-encoding") {
+            chunked = value.trim().eq_ignore_ascii_case("chunked");
+        }
+    }
+
+    let body = if chunked {
+        read_chunked(&mut reader)?
+    } else if let Some(length) = content_length {
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body)?;
+        body
+    } else {
+        // Delimited by the server closing the connection; TLS peers often skip close_notify
+        let mut body = Vec::new();
+        match reader.read_to_end(&mut body) {
+            Err(e) if e.kind() != io::ErrorKind::UnexpectedEof => return Err(e.into()),
+            _ => body,
+        }
+    };
+    Ok(HttpResponse { status, body })
+}
+
+fn send_blocking(
+    method: &str,
+    url: &Url,
+    headers: &[(String, String)],
+    body: &[u8],
+    timeout: Duration,
+    read_body: bool,
+) -> Result<HttpResponse, HttpError> {
+    let host = url
+        .host_str()
+        .ok_or_else(|| HttpError::InvalidUrl("missing host".to_string()))?;
+    let port = url
+        .port_or_known_default()
+        .ok_or_else(|| HttpError::InvalidUrl("missing port".to_string()))?;
+    let address = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| HttpError::InvalidUrl(format!("cannot resolve {}", host)))?;
+
+    let tcp = TcpStream::connect_timeout(&address, timeout)?;
+    tcp.set_read_timeout(Some(timeout))?;
+    tcp.set_write_timeout(Some(timeout))?;
+
+    let target = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\nUser-Agent: airlines-api\r\n",
+        method,
+        target,
+        host_header(url),
+        body.len()
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    let mut request = request.into_bytes();
+    request.extend_from_slice(body);
+
+    if url.scheme() == "https" {
+        let server_name = rustls::ServerName::try_from(host)
+            .map_err(|_| HttpError::InvalidUrl(format!("invalid host {}", host)))?;
+        let connection = rustls::ClientConnection::new(tls_config(), server_name)?;
+        exchange(
+            &mut rustls::StreamOwned::new(connection, tcp),
+            &request,
+            read_body,
+        )
+    } else {
+        let mut tcp = tcp;
+        exchange(&mut tcp, &request, read_body)
+    }
+}
+
+// Value of the Host header for the URL: the port is included when it is not the scheme's default
+pub fn host_header(url: &Url) -> String {
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    }
+}
+
+// POST a JSON body and return the response status code
+pub async fn post_json(
+    url: Url,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+    timeout: Duration,
+) -> Result<u16, HttpError> {
+    let mut headers: Vec<(String, String)> = headers
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    headers.push(("Content-Type".to_string(), "application/json".to_string()));
+    tokio::task::spawn_blocking(move || {
+        send_blocking("POST", &url, &headers, &body, timeout, false)
+    })
+    .await
+    .map_err(|e| HttpError::Io(io::Error::other(e)))?
+    .map(|response| response.status)
+}
+
+// Send a request with any method and read the whole response
+pub async fn send(
+    method: &'static str,
+    url: Url,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    timeout: Duration,
+) -> Result<HttpResponse, HttpError> {
+    tokio::task::spawn_blocking(move || send_blocking(method, &url, &headers, &body, timeout, true))
+        .await
+        .map_err(|e| HttpError::Io(io::Error::other(e)))?
+}
@@ -0,0 +1,276 @@
+// The languages the API itself speaks: error messages, field rule messages and the
+// notifications sent to passengers. The error language follows the request's Accept-Language;
+// notifications follow the language saved on the user's profile. Display content in the
+// `translations` table is separate and can be in any locale
+use serde::{Deserialize, Serialize};
+
+use crate::error::ErrorCode;
+use crate::middleware::locale::PreferredLocales;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    Uk,
+}
+
+impl Locale {
+    pub const ALL: &'static [Locale] = &[Locale::En, Locale::Uk];
+
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "en" => Some(Locale::En),
+            "uk" => Some(Locale::Uk),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Uk => "uk",
+        }
+    }
+
+    // The caller's most preferred language that has a catalog
+    pub fn negotiate(preferred: &PreferredLocales) -> Self {
+        preferred
+            .0
+            .iter()
+            .find_map(|tag| Self::from_tag(tag))
+            .unwrap_or_default()
+    }
+
+    // What an error code means. English is `ErrorCode::description`; handlers write their
+    // messages in English, so other languages get the code's general meaning instead
+    pub fn describe(self, code: ErrorCode) -> &'static str {
+        match self {
+            Locale::En => code.description(),
+            Locale::Uk => describe_uk(code),
+        }
+    }
+
+    // Message of a broken field rule (see `validation`), for rules whose English message does
+    // not need translating
+    pub fn rule_message(self, rule: &str) -> Option<&'static str> {
+        match self {
+            Locale::En => None,
+            Locale::Uk => Some(match rule {
+                "blank" => "не може бути порожнім",
+                "email" => "має бути дійсною адресою електронної пошти",
+                "url" => "має бути дійсною URL-адресою",
+                "length" => "має недопустиму довжину",
+                "range" => "поза допустимим діапазоном",
+                "phone_number" => "має бути номером у форматі E.164, наприклад +380501234567",
+                "passport_number" => "має складатися з 6-9 великих латинських літер і цифр",
+                "not_in_past" => "має бути датою в минулому",
+                "currency_code" => "має бути кодом ISO 4217, наприклад EUR",
+                "seat_letters" => {
+                    "має складатися з 1-10 різних великих латинських літер, наприклад ABCDEF"
+                }
+                _ => "має недопустиме значення",
+            }),
+        }
+    }
+
+    // Text of the SMS carrying a login code
+    pub fn login_code_sms(self, code: &str, expires_in_minutes: u64) -> String {
+        match self {
+            Locale::En => format!(
+                "Your login code is {}. It expires in {} minutes.",
+                code, expires_in_minutes
+            ),
+            Locale::Uk => format!(
+                "Ваш код для входу: {}. Він дійсний {} хв.",
+                code, expires_in_minutes
+            ),
+        }
+    }
+}
+
+fn describe_uk(code: ErrorCode) -> &'static str {
+    match code {
+        ErrorCode::ValidationFailed => "Тіло або параметри запиту недійсні",
+        ErrorCode::InvalidCursor => {
+            "Курсор пагінації пошкоджений або належить іншому списку"
+        }
+        ErrorCode::UnsupportedCurrency => "Курс обміну для запитаної валюти невідомий",
+        ErrorCode::WeakPassword => "Пароль не відповідає вимогам політики паролів",
+        ErrorCode::Unauthorized => "Потрібна автентифікація",
+        ErrorCode::InvalidToken => "Токен доступу недійсний або прострочений",
+        ErrorCode::TokenRevoked => "Токен доступу відкликано виходом або зміною пароля",
+        ErrorCode::InvalidApiKey => "API-ключ невідомий або відкликаний",
+        ErrorCode::InvalidCredentials => "Неправильна електронна пошта або пароль",
+        ErrorCode::TwoFactorRequired => {
+            "Для облікового запису ввімкнено двофакторну автентифікацію, потрібен код"
+        }
+        ErrorCode::InvalidTwoFactorCode => {
+            "Двофакторний або резервний код неправильний чи вже використаний"
+        }
+        ErrorCode::TwoFactorLocked => {
+            "Після забагатьох неправильних двофакторних кодів вхід з кодом тимчасово заблоковано"
+        }
+        ErrorCode::TwoFactorEnrollmentRequired => {
+            "Адміністратори мають увімкнути двофакторну автентифікацію та входити з нею"
+        }
+        ErrorCode::InvalidRefreshToken => "Токен оновлення невідомий, прострочений або відкликаний",
+        ErrorCode::InvalidOtp => "Одноразовий код неправильний, прострочений або вичерпаний",
+        ErrorCode::InvalidResetToken => {
+            "Токен скидання пароля невідомий, прострочений або вже використаний"
+        }
+        ErrorCode::RefreshTokenReused => {
+            "Надано вже замінений токен оновлення; його сеанс відкликано"
+        }
+        ErrorCode::Forbidden => "Ваша роль не дозволяє цю дію",
+        ErrorCode::MissingScope => "API-ключ не має дозволу, потрібного для цього запиту",
+        ErrorCode::WrongStaffPosition => "Ваша посада не дозволяє цю дію",
+        ErrorCode::InvalidSignature => "Підписане посилання недійсне або прострочене",
+        ErrorCode::NotOwner => "Ресурс належить іншому користувачеві",
+        ErrorCode::NotACompanion => "Пасажир не дозволив вам бронювати квитки від його імені",
+        ErrorCode::RouteNotFound => "Маршрут не знайдено",
+        ErrorCode::FlightNotFound => "Рейс не знайдено",
+        ErrorCode::TicketNotFound => "Квиток не знайдено",
+        ErrorCode::UserNotFound => "Користувача не знайдено",
+        ErrorCode::CompanionNotFound => {
+            "Користувач не дозволяв цьому власнику облікового запису бронювати від його імені"
+        }
+        ErrorCode::SeatBlockNotFound => "Блокування місця на рейсі не знайдено",
+        ErrorCode::ContentNotFound => "Вміст із цим ключем або мовою не знайдено",
+        ErrorCode::StatusLinkNotFound => "Посилання на статус не існує або прострочене",
+        ErrorCode::TwoFactorNotEnrolled => {
+            "Для облікового запису не знайдено відповідного налаштування двофакторної автентифікації"
+        }
+        ErrorCode::ApiKeyNotFound => "Активний API-ключ не знайдено",
+        ErrorCode::WebhookNotFound => "Активний вебхук не знайдено",
+        ErrorCode::SessionNotFound => "Активний сеанс не знайдено",
+        ErrorCode::AvatarNotFound => "Фото профілю за цим посиланням не знайдено",
+        ErrorCode::DocumentNotFound => "Документ користувача не знайдено",
+        ErrorCode::DataExportNotFound => "Експорт даних не знайдено або його термін минув",
+        ErrorCode::PromoCodeNotFound => "Промокод не знайдено",
+        ErrorCode::AircraftNotFound => "Літак не знайдено",
+        ErrorCode::MaintenanceRecordNotFound => "Запис про техобслуговування літака не знайдено",
+        ErrorCode::CabinLayoutNotFound => "Компонування салону не знайдено",
+        ErrorCode::GateNotFound => "Вихід на посадку не знайдено",
+        ErrorCode::BagNotFound => "Зареєстрований багаж із таким номером бирки не знайдено",
+        ErrorCode::AssistanceRequestNotFound => "Запит на допомогу для цього квитка не знайдено",
+        ErrorCode::ShipmentNotFound => "Вантажне відправлення з таким номером накладної не знайдено",
+        ErrorCode::BookingGroupNotFound => "Групового бронювання з таким кодом не знайдено",
+        ErrorCode::AncillaryNotFound => "Додаткової послуги з таким id не знайдено",
+        ErrorCode::BaggageAllowanceNotFound => {
+            "Норму багажу для класу обслуговування та маршруту квитка не встановлено"
+        }
+        ErrorCode::InvalidStatusTransition => {
+            "Рейс не може перейти з поточного статусу до запитаного"
+        }
+        ErrorCode::InvalidCargoStatus => {
+            "Вантаж не може перейти з поточного статусу до запитаного"
+        }
+        ErrorCode::CargoRouteMismatch => "Рейс не летить між аеропортами відправлення вантажу",
+        ErrorCode::InvalidBaggageScan => {
+            "Багаж не може перейти з поточного статусу до відсканованого"
+        }
+        ErrorCode::ConcurrentModification => "Ресурс змінено іншим запитом, повторіть спробу",
+        ErrorCode::PreconditionFailed => "Ресурс змінився після версії, вказаної в If-Match",
+        ErrorCode::IdempotencyKeyInProgress => "Запит із цим Idempotency-Key ще обробляється",
+        ErrorCode::IdempotencyKeyReused => {
+            "Цей Idempotency-Key уже використано для іншого запиту"
+        }
+        ErrorCode::CrewIncomplete => "Призначений на рейс екіпаж менший за мінімальний",
+        ErrorCode::UnknownCrewMember => "Вказаного члена екіпажу не існує",
+        ErrorCode::CrewScheduleConflict => {
+            "Член екіпажу вже призначений на рейс, що перетинається в часі"
+        }
+        ErrorCode::CrewDutyLimitExceeded => {
+            "Рейс порушив би для члена екіпажу ліміти робочого часу або відпочинку"
+        }
+        ErrorCode::FlightClosed => "Рейс уже відправився, прибув або скасований",
+        ErrorCode::AircraftGrounded => {
+            "Літак на час рейсу знятий з польотів для техобслуговування"
+        }
+        ErrorCode::SeatAlreadyTaken => "Місце вже заброньоване або заблоковане",
+        ErrorCode::UnknownSeat => "Такого місця немає на схемі салону рейсу",
+        ErrorCode::SeatNotInClass => "Місце належить до іншого класу обслуговування, ніж заброньований",
+        ErrorCode::ClassSoldOut => {
+            "У класі обслуговування залишилося менше місць, ніж пасажирів"
+        }
+        ErrorCode::PassengerAlreadyBooked => "Пасажир уже має квиток на цей рейс",
+        ErrorCode::GateOccupied => "Вихід на посадку зайнятий іншим рейсом у цей час",
+        ErrorCode::GateExists => "В аеропорту вже є вихід на посадку з таким кодом",
+        ErrorCode::AssistanceAlreadyRequested => {
+            "Для квитка вже є запит на такий вид допомоги"
+        }
+        ErrorCode::MealNotOffered => "Це харчування не передбачене на рейсі квитка",
+        ErrorCode::MealSelectionClosed => "Обрати харчування на цей рейс уже неможливо",
+        ErrorCode::CargoPayloadExceeded => {
+            "Вантаж перевищить вантажні обмеження літака на цьому рейсі"
+        }
+        ErrorCode::PetNotAccepted => "Літак не перевозить цю тварину в обраному місці",
+        ErrorCode::PetCapacityReached => "Місць для тварин у цьому відсіку на рейсі більше немає",
+        ErrorCode::PetAlreadyBooked => "Для квитка вже оформлено тварину",
+        ErrorCode::LapInfantNotAllowed => {
+            "Немовля на руках може перевозити лише дорослий власник квитка"
+        }
+        ErrorCode::NotAnInfant => "На дату вильоту дитині вже виповниться 2 роки",
+        ErrorCode::InfantAlreadyBooked => "Для квитка вже оформлено немовля на руках",
+        ErrorCode::PassengerAgeMismatch => {
+            "Вік пасажира на дату вильоту не відповідає його квитку"
+        }
+        ErrorCode::UpgradeNotOffered => "Рейс не продає такого класу, вищого за клас квитка",
+        ErrorCode::AncillaryNotOffered => "Ця додаткова послуга не продається на рейс квитка",
+        ErrorCode::AncillarySoldOut => "Усі одиниці цієї додаткової послуги на рейс уже продано",
+        ErrorCode::AncillaryAlreadyPurchased => "Для квитка вже придбано цю додаткову послугу",
+        ErrorCode::AncillaryCodeExists => "Додаткова послуга з таким кодом уже існує",
+        ErrorCode::AssistanceNotAcknowledged => {
+            "Персонал ще не підтвердив усі запити на допомогу для квитка"
+        }
+        ErrorCode::CapacityExceeded => "Запитана кількість місць перевищує місткість літака",
+        ErrorCode::ClassOversold => {
+            "Клас обслуговування не можна зменшити нижче вже проданих місць"
+        }
+        ErrorCode::TwoFactorAlreadyEnabled => {
+            "Двофакторну автентифікацію для облікового запису вже ввімкнено"
+        }
+        ErrorCode::PromoCodeExists => "Промокод із таким кодом уже існує",
+        ErrorCode::PromoCodeNotYetValid => "Промокод ще не діє",
+        ErrorCode::PromoCodeExpired => "Термін дії промокоду минув",
+        ErrorCode::PromoCodeExhausted => "Ліміт використань промокоду вичерпано",
+        ErrorCode::InsufficientMiles => "Недостатньо миль на рахунку",
+        ErrorCode::RedemptionClosed => "Милі більше не можна списати за цей квиток",
+        ErrorCode::UnsupportedFileType => "Тип файлу не підтримується",
+        ErrorCode::FileTooLarge => "Файл перевищує допустимий розмір",
+        ErrorCode::FileRejected => "Файл не пройшов перевірку на шкідливе програмне забезпечення",
+        ErrorCode::PayloadTooLarge => "Тіло запиту перевищує допустимий розмір",
+        ErrorCode::UnsupportedMediaType => "Content-Type тіла запиту не підтримується",
+        ErrorCode::TooManyRequests => "Забагато запитів, повторіть пізніше",
+        ErrorCode::DatabaseError => "Базі даних не вдалося обробити запит",
+        ErrorCode::InternalError => "Неочікувана помилка сервера",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negotiates_the_first_supported_language() {
+        assert_eq!(
+            Locale::negotiate(&PreferredLocales::parse("de-DE, uk;q=0.8, en;q=0.5")),
+            Locale::Uk
+        );
+        assert_eq!(
+            Locale::negotiate(&PreferredLocales::parse("fr, de")),
+            Locale::En
+        );
+        assert_eq!(Locale::negotiate(&PreferredLocales::parse("")), Locale::En);
+    }
+
+    #[test]
+    fn english_descriptions_are_the_error_catalog() {
+        for code in ErrorCode::ALL {
+            assert_eq!(Locale::En.describe(*code), code.description());
+            assert_ne!(Locale::Uk.describe(*code), code.description());
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::queue::{Job, JobContext, JobError};
+use crate::models::{Flight, Route, Ticket, User};
+
+// Send the passenger the confirmation of a booked ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmBooking {
+    pub ticket_id: i32,
+}
+
+#[async_trait]
+impl Job for ConfirmBooking {
+    const KIND: &'static str = "confirm_booking";
+
+    async fn run(&self, context: &JobContext) -> Result<(), JobError> {
+        let pool = &context.pool;
+        let missing = |what: &str| JobError::Permanent(format!("{} is missing", what));
+        let ticket = Ticket::find_by_id(pool, self.ticket_id)
+            .await?
+            .ok_or_else(|| missing("ticket"))?;
+        let user = User::find_by_id(pool, ticket.user_id)
+            .await?
+            .ok_or_else(|| missing("passenger"))?;
+        let flight = Flight::find_by_id(pool, ticket.flight_id)
+            .await?
+            .ok_or_else(|| missing("flight"))?;
+        let route = Route::find_by_id(pool, flight.route_id)
+            .await?
+            .ok_or_else(|| missing("route"))?;
+        context
+            .notifier
+            .booking_confirmed(&user, &ticket, &flight, &route);
+        Ok(())
+    }
+}
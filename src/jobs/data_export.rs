@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::db::DbPool;
+use crate::models::{AuditLog, DataExport, MilesEntry, Session, Ticket, User};
+use crate::shutdown::ShutdownReceiver;
+
+// Exports built per tick
+const BATCH_SIZE: i32 = 10;
+
+// Everything stored about the user, as one JSON document. Credentials (password hash, TOTP
+// secret, token hashes) are left out
+async fn assemble(pool: &DbPool, user_id: i32) -> Result<Value, sqlx::Error> {
+    let profile = User::find_by_id(pool, user_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)?;
+
+    Ok(json!({
+        "generated_at": Utc::now(),
+        "profile": profile,
+        "tickets": Ticket::find_by_user(pool, user_id).await?,
+        "miles_ledger": MilesEntry::find_by_user(pool, user_id).await?,
+        "sessions": Session::find_by_user(pool, user_id).await?,
+        "audit_log": AuditLog::find_about_user(pool, user_id).await?,
+    }))
+}
+
+// Periodically build requested data exports and drop archives past their expiry
+pub fn spawn(
+    pool: DbPool,
+    interval: Duration,
+    ttl_secs: u64,
+    mut shutdown: ShutdownReceiver,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            if let Err(e) = DataExport::purge_expired(&pool).await {
+                error!("Failed to purge expired data exports: {}", e);
+            }
+            let pending = match DataExport::pending(&pool, BATCH_SIZE).await {
+                Ok(pending) => pending,
+                Err(e) => {
+                    error!("Failed to load pending data exports: {}", e);
+                    continue;
+                }
+            };
+
+            for export in pending {
+                let result = match assemble(&pool, export.user_id).await {
+                    Ok(archive) => {
+                        DataExport::complete(
+                            &pool,
+                            &export.export_id,
+                            &archive.to_string(),
+                            ttl_secs,
+                        )
+                        .await
+                    }
+                    Err(e) => {
+                        error!("Data export {} failed: {}", export.export_id, e);
+                        DataExport::fail(&pool, &export.export_id).await
+                    }
+                };
+                match result {
+                    Ok(()) => info!("Finished data export {}", export.export_id),
+                    Err(e) => error!("Failed to store data export {}: {}", export.export_id, e),
+                }
+            }
+        }
+    })
+}
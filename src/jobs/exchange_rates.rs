@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+use url::Url;
+
+use crate::currencies;
+use crate::db::DbPool;
+use crate::http_client;
+use crate::models::ExchangeRate;
+use crate::shutdown::ShutdownReceiver;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// Fetch the feed and store its rates; the number of rates stored
+async fn update(pool: &DbPool, url: &Url) -> Result<usize, String> {
+    let response = http_client::send("GET", url.clone(), Vec::new(), Vec::new(), TIMEOUT)
+        .await
+        .map_err(|e| e.to_string())?;
+    if response.status != 200 {
+        return Err(format!("the feed returned {}", response.status));
+    }
+    let rates = currencies::parse_ecb_rates(&String::from_utf8_lossy(&response.body));
+    if rates.is_empty() {
+        return Err("the feed has no rates".to_string());
+    }
+    ExchangeRate::upsert_all(pool, &rates)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(rates.len())
+}
+
+// Refresh the euro exchange rates from the ECB-format feed at `url`, at startup and then every
+// `interval`. A failed refresh keeps the rates already stored
+pub fn spawn(
+    pool: DbPool,
+    url: Url,
+    interval: Duration,
+    mut shutdown: ShutdownReceiver,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            match update(&pool, &url).await {
+                Ok(count) => info!("Updated {} exchange rates", count),
+                Err(e) => error!("Exchange rate update from {} failed: {}", url, e),
+            }
+        }
+    })
+}
@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::queue::{Job, JobContext, JobError};
+use crate::models::{Flight, FlightStatus, Route};
+
+// Tell every ticket holder that their flight was delayed or cancelled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotifyFlightDisruption {
+    pub flight_id: i32,
+    pub status: FlightStatus,
+}
+
+#[async_trait]
+impl Job for NotifyFlightDisruption {
+    const KIND: &'static str = "notify_flight_disruption";
+
+    async fn run(&self, context: &JobContext) -> Result<(), JobError> {
+        let pool = &context.pool;
+        let missing = |what: &str| JobError::Permanent(format!("{} is missing", what));
+        let flight = Flight::find_by_id(pool, self.flight_id)
+            .await?
+            .ok_or_else(|| missing("flight"))?;
+        let route = Route::find_by_id(pool, flight.route_id)
+            .await?
+            .ok_or_else(|| missing("route"))?;
+        let holders = Flight::ticket_holders(pool, self.flight_id).await?;
+        context
+            .notifier
+            .flight_disrupted(&flight, &route, self.status, holders);
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::db::DbPool;
+use crate::models::IdempotencyKey;
+use crate::shutdown::ShutdownReceiver;
+
+// Periodically forget Idempotency-Keys and their responses once they are older than `ttl_secs`
+pub fn spawn(
+    pool: DbPool,
+    interval: Duration,
+    ttl_secs: u64,
+    mut shutdown: ShutdownReceiver,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            match IdempotencyKey::delete_expired(&pool, ttl_secs).await {
+                Ok(0) => {}
+                Ok(deleted) => info!("Deleted {} expired idempotency keys", deleted),
+                Err(e) => error!("Idempotency key cleanup failed: {}", e),
+            }
+        }
+    })
+}
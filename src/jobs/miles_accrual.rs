@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use super::queue::{Job, JobContext, JobError};
+use crate::db::DbPool;
+use crate::models::MilesEntry;
+use crate::shutdown::ShutdownReceiver;
+
+// Credit the miles of one flight right after it arrives
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccrueFlightMiles {
+    pub flight_id: i32,
+}
+
+#[async_trait]
+impl Job for AccrueFlightMiles {
+    const KIND: &'static str = "accrue_flight_miles";
+
+    async fn run(&self, context: &JobContext) -> Result<(), JobError> {
+        let credited = MilesEntry::accrue_flight(&context.pool, self.flight_id).await?;
+        if credited > 0 {
+            info!(
+                "Credited miles for {} tickets of flight {}",
+                credited, self.flight_id
+            );
+        }
+        Ok(())
+    }
+}
+
+// Periodically credit loyalty miles for flights that reached `arrived` without going through
+// the status endpoint (imports, manual fixes) or whose job failed
+pub fn spawn(pool: DbPool, interval: Duration, mut shutdown: ShutdownReceiver) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            match MilesEntry::accrue_arrived_flights(&pool).await {
+                Ok(0) => {}
+                Ok(credited) => info!("Credited miles for {} tickets", credited),
+                Err(e) => error!("Miles accrual failed: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,12 @@
+pub mod booking_confirmation;
+pub mod data_export;
+pub mod exchange_rates;
+pub mod flight_disruption;
+pub mod idempotency_cleanup;
+pub mod miles_accrual;
+pub mod outbox_relay;
+pub mod publish_event;
+pub mod queue;
+pub mod scheduler;
+pub mod send_email;
+pub mod webhook_delivery;
@@ -0,0 +1,114 @@
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::task::JoinHandle;
+use tracing::{error, warn};
+
+use super::booking_confirmation::ConfirmBooking;
+use super::flight_disruption::NotifyFlightDisruption;
+use super::publish_event::PublishEvent;
+use super::queue;
+use super::webhook_delivery::QueueWebhookEvent;
+use crate::db::{DbConnection, DbPool};
+use crate::models::{DomainEvent, FlightStatus, OutboxEvent, WebhookEvent};
+use crate::shutdown::ShutdownReceiver;
+
+// Events relayed per transaction
+const BATCH_SIZE: i32 = 50;
+
+// Queue the jobs that act on an event, inside the relay's transaction
+async fn dispatch(conn: &mut DbConnection, event: &DomainEvent) -> Result<(), sqlx::Error> {
+    match event {
+        DomainEvent::FlightCancelled { flight } => {
+            let webhooks = QueueWebhookEvent {
+                event: WebhookEvent::FlightCancelled,
+                data: json!({ "flight": flight }),
+            };
+            queue::enqueue(&mut *conn, &webhooks).await?;
+            let notifications = NotifyFlightDisruption {
+                flight_id: flight.flight_id,
+                status: FlightStatus::Cancelled,
+            };
+            queue::enqueue(&mut *conn, &notifications).await?;
+        }
+        DomainEvent::FlightDelayed { flight } => {
+            let notifications = NotifyFlightDisruption {
+                flight_id: flight.flight_id,
+                status: FlightStatus::Delayed,
+            };
+            queue::enqueue(&mut *conn, &notifications).await?;
+        }
+        DomainEvent::TicketBooked { ticket } => {
+            let webhooks = QueueWebhookEvent {
+                event: WebhookEvent::TicketCreated,
+                data: json!({ "ticket": ticket }),
+            };
+            queue::enqueue(&mut *conn, &webhooks).await?;
+            let confirmation = ConfirmBooking {
+                ticket_id: ticket.ticket_id,
+            };
+            queue::enqueue(&mut *conn, &confirmation).await?;
+        }
+    }
+    Ok(())
+}
+
+// Relay one batch; the jobs and the published marks commit together, so every event is acted
+// on exactly once. With `stream` each event is also queued for the event bus. Returns how many
+// events were relayed
+async fn relay_batch(pool: &DbPool, stream: bool) -> Result<usize, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let events = OutboxEvent::lock_unpublished(&mut tx, BATCH_SIZE).await?;
+    let mut relayed = 0;
+    for outbox in &events {
+        // Left in place for a newer version of the relay that knows the type
+        let Some(event) = outbox.event() else {
+            warn!(
+                "Outbox event {} has unknown type {}",
+                outbox.event_id, outbox.event_type
+            );
+            continue;
+        };
+        dispatch(&mut tx, &event).await?;
+        if stream {
+            let publish = PublishEvent {
+                event_id: outbox.event_id,
+                occurred_at: outbox.created_at,
+                event,
+            };
+            queue::enqueue(&mut *tx, &publish).await?;
+        }
+        OutboxEvent::mark_published(&mut tx, outbox.event_id).await?;
+        relayed += 1;
+    }
+    tx.commit().await?;
+    Ok(relayed)
+}
+
+// Periodically relay recorded domain events, a batch at a time until none are left
+pub fn spawn(
+    pool: DbPool,
+    interval: Duration,
+    stream: bool,
+    mut shutdown: ShutdownReceiver,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            loop {
+                match relay_batch(&pool, stream).await {
+                    Ok(relayed) if relayed == BATCH_SIZE as usize => continue,
+                    Ok(_) => break,
+                    Err(e) => {
+                        error!("Outbox relay failed: {}", e);
+                        break;
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::queue::{Job, JobContext, JobError};
+use crate::event_bus::EventBusError;
+use crate::models::DomainEvent;
+
+// One outbox event to the event bus, retried while the broker is unreachable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishEvent {
+    pub event_id: i64,
+    pub occurred_at: DateTime<Utc>,
+    pub event: DomainEvent,
+}
+
+#[async_trait]
+impl Job for PublishEvent {
+    const KIND: &'static str = "publish_event";
+
+    const MAX_ATTEMPTS: i32 = 10;
+
+    async fn run(&self, context: &JobContext) -> Result<(), JobError> {
+        // The bus was turned off since the event was queued
+        let Some(event_bus) = &context.event_bus else {
+            return Ok(());
+        };
+        event_bus
+            .publish(self.event_id, self.occurred_at, &self.event)
+            .await
+            .map_err(|e| match e {
+                EventBusError::Config(_) => JobError::Permanent(e.to_string()),
+                e => e.into(),
+            })
+    }
+}
@@ -0,0 +1,170 @@
+// Durable background work. Handlers queue a `Job` (stored in the `jobs` table) and return; the
+// runner claims due jobs with a lease, runs them and retries failures with exponential backoff
+// until `MAX_ATTEMPTS`. A job can run more than once (a runner may die after finishing one but
+// before recording it), so jobs should be safe to repeat
+use std::{collections::HashMap, future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{error, warn};
+
+use crate::db::{Db, DbPool};
+use crate::event_bus::{EventBus, EventBusError};
+use crate::mailer::{MailError, Mailer};
+use crate::models::QueuedJob;
+use crate::notifications::Notifier;
+use crate::shutdown::ShutdownReceiver;
+
+// Jobs claimed per tick, run concurrently
+const BATCH_SIZE: i32 = 20;
+
+// How long a claimed job is reserved for its runner; jobs must finish well within it
+const LEASE_SECS: i64 = 300;
+
+#[derive(Debug, Error)]
+pub enum JobError {
+    #[error("{0}")]
+    Database(#[from] sqlx::Error),
+    #[error("{0}")]
+    Mail(#[from] MailError),
+    #[error("{0}")]
+    EventBus(#[from] EventBusError),
+    // Retrying cannot help, e.g. the payload no longer parses
+    #[error("{0}")]
+    Permanent(String),
+}
+
+// What jobs may use besides their payload
+#[derive(Clone)]
+pub struct JobContext {
+    pub pool: DbPool,
+    pub mailer: Arc<dyn Mailer>,
+    pub notifier: Notifier,
+    // None unless EVENT_BUS is set
+    pub event_bus: Option<EventBus>,
+}
+
+#[async_trait]
+pub trait Job: Serialize + DeserializeOwned + Send + Sync + 'static {
+    // Stored with each queued job to find its handler; never rename one that may still be queued
+    const KIND: &'static str;
+
+    // Attempts before the job is recorded as failed
+    const MAX_ATTEMPTS: i32 = 5;
+
+    async fn run(&self, context: &JobContext) -> Result<(), JobError>;
+}
+
+// Queue a job to run as soon as a runner picks it up; pass a transaction to queue it only if the
+// rest of the transaction commits
+pub async fn enqueue<'e, E, J>(executor: E, job: &J) -> Result<i64, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = Db>,
+    J: Job,
+{
+    enqueue_in(executor, job, Duration::ZERO).await
+}
+
+// Queue a job to run once `delay` has passed
+pub async fn enqueue_in<'e, E, J>(executor: E, job: &J, delay: Duration) -> Result<i64, sqlx::Error>
+where
+    E: sqlx::Executor<'e, Database = Db>,
+    J: Job,
+{
+    let payload = serde_json::to_value(job).expect("jobs serialize to JSON");
+    QueuedJob::insert(
+        executor,
+        J::KIND,
+        &payload,
+        J::MAX_ATTEMPTS,
+        delay.as_secs() as i64,
+    )
+    .await
+}
+
+type JobFuture = Pin<Box<dyn Future<Output = Result<(), JobError>> + Send>>;
+type Handler = Arc<dyn Fn(serde_json::Value, Arc<JobContext>) -> JobFuture + Send + Sync>;
+
+// Runs the registered kinds of job; a claimed job of any other kind fails for good
+pub struct JobRunner {
+    context: Arc<JobContext>,
+    handlers: HashMap<&'static str, Handler>,
+}
+
+impl JobRunner {
+    pub fn new(context: JobContext) -> Self {
+        Self {
+            context: Arc::new(context),
+            handlers: HashMap::new(),
+        }
+    }
+
+    pub fn register<J: Job>(mut self) -> Self {
+        let handler: Handler = Arc::new(|payload, context| {
+            Box::pin(async move {
+                let job: J = serde_json::from_value(payload)
+                    .map_err(|e| JobError::Permanent(format!("invalid payload: {}", e)))?;
+                job.run(&context).await
+            })
+        });
+        self.handlers.insert(J::KIND, handler);
+        self
+    }
+
+    // One job of the batch, recording how it went
+    async fn run(pool: DbPool, context: Arc<JobContext>, handler: Option<Handler>, job: QueuedJob) {
+        let result = match (handler, serde_json::from_str(&job.payload)) {
+            (Some(handler), Ok(payload)) => handler(payload, context).await,
+            (None, _) => Err(JobError::Permanent(format!(
+                "no handler for job kind {}",
+                job.kind
+            ))),
+            (_, Err(e)) => Err(JobError::Permanent(format!("invalid payload: {}", e))),
+        };
+        let recorded = match result {
+            Ok(()) => QueuedJob::mark_succeeded(&pool, job.job_id).await,
+            Err(e) => {
+                warn!(
+                    "Job {} ({}) failed on attempt {} of {}: {}",
+                    job.job_id, job.kind, job.attempts, job.max_attempts, e
+                );
+                let retry = !matches!(e, JobError::Permanent(_));
+                job.mark_failed(&pool, &e.to_string(), retry).await
+            }
+        };
+        if let Err(e) = recorded {
+            error!("Failed to record the outcome of job {}: {}", job.job_id, e);
+        }
+    }
+
+    // Poll for due jobs every `interval`. On shutdown the current batch is finished; the rest
+    // stays queued for the next start
+    pub fn spawn(self, interval: Duration, mut shutdown: ShutdownReceiver) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let pool = self.context.pool.clone();
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                tokio::select! {
+                    _ = ticker.tick() => {}
+                    _ = shutdown.changed() => break,
+                }
+                let due = match QueuedJob::claim(&pool, BATCH_SIZE, LEASE_SECS).await {
+                    Ok(due) => due,
+                    Err(e) => {
+                        error!("Failed to claim due jobs: {}", e);
+                        continue;
+                    }
+                };
+
+                let mut batch = JoinSet::new();
+                for job in due {
+                    let handler = self.handlers.get(job.kind.as_str()).cloned();
+                    batch.spawn(Self::run(pool.clone(), self.context.clone(), handler, job));
+                }
+                while batch.join_next().await.is_some() {}
+            }
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+use crate::cron::Schedule;
+use crate::db::DbPool;
+use crate::live::FlightUpdates;
+use crate::models::{Flight, FlightStatus, PasswordReset, Ticket};
+use crate::repositories::FlightRepository;
+use crate::shutdown::ShutdownReceiver;
+
+// Recurring maintenance run on cron schedules. Every instance runs them, so each task is a
+// single conditional statement per row that is harmless to repeat
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Task {
+    // Boarding flights past their departure time become departed
+    MarkDeparted,
+    // Tickets not checked in on departed flights become no-shows
+    FlagNoShows,
+    // Used and expired password reset tokens are deleted
+    PurgeResetTokens,
+}
+
+impl Task {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Task::MarkDeparted => "mark_departed",
+            Task::FlagNoShows => "flag_no_shows",
+            Task::PurgeResetTokens => "purge_reset_tokens",
+        }
+    }
+
+    // Rows changed. Flights change through the repository so cached copies are dropped
+    async fn run(
+        &self,
+        pool: &DbPool,
+        flights: &dyn FlightRepository,
+        flight_updates: &FlightUpdates,
+    ) -> Result<u64, sqlx::Error> {
+        match self {
+            Task::MarkDeparted => {
+                let mut departed = 0;
+                for id in Flight::overdue_boarding(pool).await? {
+                    let moved = flights
+                        .update_status(id, FlightStatus::Boarding, FlightStatus::Departed, None)
+                        .await?;
+                    if moved {
+                        departed += 1;
+                        if let Some(flight) = flights.find_by_id(id).await? {
+                            flight_updates.publish(&flight);
+                        }
+                    }
+                }
+                Ok(departed)
+            }
+            Task::FlagNoShows => Ticket::flag_no_shows(pool).await,
+            Task::PurgeResetTokens => PasswordReset::delete_stale(pool).await,
+        }
+    }
+}
+
+// Run each task whenever its schedule comes due, until shutdown
+pub fn spawn(
+    pool: DbPool,
+    flights: Arc<dyn FlightRepository>,
+    flight_updates: FlightUpdates,
+    tasks: Vec<(Task, Schedule)>,
+    mut shutdown: ShutdownReceiver,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let now = Utc::now();
+        let mut upcoming: Vec<(Task, Schedule, Option<DateTime<Utc>>)> = tasks
+            .into_iter()
+            .map(|(task, schedule)| {
+                let next = schedule.next_after(now);
+                (task, schedule, next)
+            })
+            .collect();
+
+        loop {
+            let Some(due_at) = upcoming.iter().filter_map(|(_, _, next)| *next).min() else {
+                // Nothing left to run
+                let _ = shutdown.changed().await;
+                break;
+            };
+            let wait = (due_at - Utc::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = shutdown.changed() => break,
+            }
+
+            let now = Utc::now();
+            for (task, schedule, next) in &mut upcoming {
+                if next.is_some_and(|next| next <= now) {
+                    match task.run(&pool, &*flights, &flight_updates).await {
+                        Ok(0) => {}
+                        Ok(changed) => {
+                            info!("Scheduled task {} changed {} rows", task.name(), changed)
+                        }
+                        Err(e) => error!("Scheduled task {} failed: {}", task.name(), e),
+                    }
+                    *next = schedule.next_after(now);
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,27 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::queue::{Job, JobContext, JobError};
+use crate::mailer::{Email, MailError};
+
+// One email through the configured mailer, retried while the mail server is unreachable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendEmail {
+    pub email: Email,
+}
+
+#[async_trait]
+impl Job for SendEmail {
+    const KIND: &'static str = "send_email";
+
+    const MAX_ATTEMPTS: i32 = 8;
+
+    async fn run(&self, context: &JobContext) -> Result<(), JobError> {
+        context.mailer.send(&self.email).await.map_err(|e| match e {
+            MailError::InvalidAddress(_) | MailError::Config(_) => {
+                JobError::Permanent(e.to_string())
+            }
+            e => e.into(),
+        })
+    }
+}
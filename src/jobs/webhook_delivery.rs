@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use super::queue::{Job, JobContext, JobError};
+use crate::db::DbPool;
+use crate::models::{WebhookDelivery, WebhookEvent};
+use crate::shutdown::ShutdownReceiver;
+use crate::webhooks::{self, Attempt};
+
+// Fan an event out to a delivery per subscribed webhook, which the loop below then sends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueWebhookEvent {
+    pub event: WebhookEvent,
+    pub data: serde_json::Value,
+}
+
+#[async_trait]
+impl Job for QueueWebhookEvent {
+    const KIND: &'static str = "queue_webhook_event";
+
+    async fn run(&self, context: &JobContext) -> Result<(), JobError> {
+        let queued = WebhookDelivery::enqueue(&context.pool, self.event, self.data.clone()).await?;
+        if queued > 0 {
+            info!(
+                "Queued {} {} webhook deliveries",
+                queued,
+                self.event.as_str()
+            );
+        }
+        Ok(())
+    }
+}
+
+// Deliveries attempted per tick
+const BATCH_SIZE: i32 = 50;
+
+// Periodically send due webhook deliveries and schedule retries for failed ones.
+// On shutdown the current batch is finished; the rest stays queued for the next start
+pub fn spawn(pool: DbPool, interval: Duration, mut shutdown: ShutdownReceiver) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = shutdown.changed() => break,
+            }
+            let due = match WebhookDelivery::due(&pool, BATCH_SIZE).await {
+                Ok(due) => due,
+                Err(e) => {
+                    error!("Failed to load due webhook deliveries: {}", e);
+                    continue;
+                }
+            };
+
+            for delivery in due {
+                let result = match webhooks::attempt(&delivery).await {
+                    Attempt::Delivered(status) => {
+                        WebhookDelivery::mark_succeeded(&pool, delivery.delivery_id, status).await
+                    }
+                    Attempt::Rejected(status) => {
+                        warn!(
+                            "Webhook delivery {} rejected with status {}",
+                            delivery.delivery_id, status
+                        );
+                        WebhookDelivery::mark_attempt_failed(
+                            &pool,
+                            &delivery,
+                            Some(status),
+                            &format!("receiver answered {}", status),
+                        )
+                        .await
+                    }
+                    Attempt::Failed(reason) => {
+                        warn!(
+                            "Webhook delivery {} failed: {}",
+                            delivery.delivery_id, reason
+                        );
+                        let reason: String = reason.chars().take(255).collect();
+                        WebhookDelivery::mark_attempt_failed(&pool, &delivery, None, &reason).await
+                    }
+                };
+                if let Err(e) = result {
+                    error!(
+                        "Failed to record webhook delivery {}: {}",
+                        delivery.delivery_id, e
+                    );
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,109 @@
+// The API as a library: `main.rs` wires it to configuration, the network and the background
+// jobs, the integration tests under tests/ drive the same router in-process
+pub mod auth;
+pub mod cache;
+pub mod config;
+pub mod cron;
+pub mod csv;
+pub mod currencies;
+pub mod db;
+pub mod error;
+pub mod error_reporting;
+pub mod event_bus;
+pub mod export;
+pub mod file_type;
+pub mod handlers;
+pub mod http_client;
+pub mod i18n;
+pub mod jobs;
+pub mod live;
+pub mod logging;
+pub mod mailer;
+pub mod middleware;
+pub mod models;
+pub mod multipart;
+pub mod notifications;
+pub mod openapi;
+pub mod password_policy;
+pub mod pricing;
+pub mod repositories;
+pub mod routes;
+pub mod seed;
+pub mod shutdown;
+pub mod signed_url;
+pub mod sigv4;
+pub mod state;
+pub mod storage;
+#[cfg(test)]
+mod test_db;
+pub mod tls;
+pub mod totp;
+pub mod ttl_cache;
+pub mod validation;
+pub mod virus_scan;
+pub mod webhooks;
+pub mod xlsx;
+
+use std::sync::Arc;
+
+use axum::{extract::DefaultBodyLimit, Extension, Router};
+
+use crate::config::Config;
+use crate::live::FlightUpdates;
+use crate::notifications::{Notifier, SmsProvider};
+use crate::pricing::PricingEngine;
+use crate::state::AppState;
+use crate::storage::ObjectStorage;
+use crate::virus_scan::VirusScanner;
+
+// Request-scoped services the handlers take as extensions
+pub struct Services {
+    pub notifier: Notifier,
+    pub flight_updates: FlightUpdates,
+    pub sms: Arc<dyn SmsProvider>,
+    pub pricing: PricingEngine,
+    pub storage: Arc<dyn ObjectStorage>,
+    pub virus_scanner: Arc<dyn VirusScanner>,
+}
+
+// Every route with the middleware stack in front of it; HSTS is added by the caller when
+// serving HTTPS
+pub fn app(state: AppState, services: Services) -> Router {
+    let config: &Config = &state.config;
+
+    // Upload routes raise the body limit for themselves
+    let mut router = routes::app_router(&state)
+        .layer(axum::middleware::from_fn(middleware::body::enforce_body))
+        .layer(DefaultBodyLimit::max(config.json_body_max_bytes))
+        .layer(axum::middleware::from_fn(middleware::fields::sparse_fields))
+        .layer(axum::middleware::from_fn_with_state(
+            config.legacy_responses,
+            middleware::compat::response_compat,
+        ))
+        .layer(Extension(services.notifier))
+        .layer(Extension(services.flight_updates))
+        .layer(Extension(services.sms))
+        .layer(Extension(services.pricing))
+        .layer(Extension(services.storage))
+        .layer(Extension(services.virus_scanner));
+    // Outside the middleware above, which rewrites bodies as plain JSON
+    if config.decompress_requests {
+        router = router.layer(middleware::compression::decompression());
+    }
+    if config.compress_responses {
+        router = router.layer(middleware::compression::compression(
+            config.compression_min_bytes,
+        ));
+    }
+
+    router
+        .layer(axum::middleware::from_fn_with_state(
+            Arc::new(middleware::cors::CorsPolicy::from_config(config)),
+            middleware::cors::cors,
+        ))
+        .layer(axum::middleware::from_fn(middleware::locale::locale))
+        .layer(axum::middleware::from_fn(
+            middleware::request_id::request_id,
+        ))
+        .with_state(state)
+}
@@ -0,0 +1,62 @@
+pub mod websocket;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+use crate::models::{Flight, FlightStatus};
+
+// Updates a slow subscriber may fall behind by before it starts missing them
+const CHANNEL_CAPACITY: usize = 256;
+
+// Snapshot of a flight's live fields, pushed to subscribers whenever one of them changes
+#[derive(Debug, Clone, Serialize)]
+pub struct FlightUpdate {
+    pub flight_id: i32,
+    pub flight_number: String,
+    pub status: FlightStatus,
+    pub gate: Option<String>,
+    pub terminal: Option<String>,
+    pub departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&Flight> for FlightUpdate {
+    fn from(flight: &Flight) -> Self {
+        Self {
+            flight_id: flight.flight_id,
+            flight_number: flight.flight_number.clone(),
+            status: flight.status,
+            gate: flight.gate.clone(),
+            terminal: flight.terminal.clone(),
+            departure_time: flight.departure_time,
+            arrival_time: flight.arrival_time,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+// Fan-out of flight changes to live connections. Cloning is cheap, every clone shares the channel
+#[derive(Clone)]
+pub struct FlightUpdates {
+    sender: broadcast::Sender<FlightUpdate>,
+}
+
+impl Default for FlightUpdates {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+}
+
+impl FlightUpdates {
+    // Publish the flight's current state; a no-op while nobody is listening
+    pub fn publish(&self, flight: &Flight) {
+        let _ = self.sender.send(FlightUpdate::from(flight));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<FlightUpdate> {
+        self.sender.subscribe()
+    }
+}
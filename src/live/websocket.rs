@@ -0,0 +1,242 @@
+// Minimal server side of the WebSocket protocol (RFC 6455): the upgrade handshake plus
+// unfragmented text and control frames, which is all the live feeds need
+use std::future::Future;
+
+use axum::{
+    body::Body,
+    extract::FromRequestParts,
+    http::{header, request::Parts, Method, StatusCode},
+    response::Response,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hyper::upgrade::{OnUpgrade, Upgraded};
+use hyper_util::rt::TokioIo;
+use sha1::{Digest, Sha1};
+use tokio::io::{self, AsyncReadExt, AsyncWriteExt, ReadHalf, WriteHalf};
+use tracing::error;
+
+use crate::error::{AppError, ErrorCode};
+
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// Clients only send control frames and short messages; anything larger is dropped
+const MAX_CLIENT_PAYLOAD: u64 = 64 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+type Stream = TokioIo<Upgraded>;
+
+#[derive(Debug)]
+pub enum Message {
+    Text(String),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close,
+}
+
+// Extractor for a `GET` request asking to switch to the WebSocket protocol
+pub struct WebSocketUpgrade {
+    accept: String,
+    on_upgrade: OnUpgrade,
+}
+
+fn header_has_token(parts: &Parts, name: header::HeaderName, token: &str) -> bool {
+    parts
+        .headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .split(',')
+                .any(|item| item.trim().eq_ignore_ascii_case(token))
+        })
+}
+
+fn not_websocket(reason: &str) -> AppError {
+    AppError::ValidationError(
+        ErrorCode::ValidationFailed,
+        format!("Expected a WebSocket upgrade request: {}", reason),
+    )
+}
+
+impl<S> FromRequestParts<S> for WebSocketUpgrade
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if parts.method != Method::GET {
+            return Err(not_websocket("method must be GET"));
+        }
+        if !header_has_token(parts, header::CONNECTION, "upgrade")
+            || !header_has_token(parts, header::UPGRADE, "websocket")
+        {
+            return Err(not_websocket("missing Upgrade: websocket"));
+        }
+        if !header_has_token(parts, header::SEC_WEBSOCKET_VERSION, "13") {
+            return Err(not_websocket("unsupported Sec-WebSocket-Version"));
+        }
+
+        let key = parts
+            .headers
+            .get(header::SEC_WEBSOCKET_KEY)
+            .ok_or_else(|| not_websocket("missing Sec-WebSocket-Key"))?;
+        let mut hasher = Sha1::new();
+        hasher.update(key.as_bytes());
+        hasher.update(ACCEPT_GUID.as_bytes());
+        let accept = STANDARD.encode(hasher.finalize());
+
+        let on_upgrade = parts
+            .extensions
+            .remove::<OnUpgrade>()
+            .ok_or_else(|| not_websocket("connection cannot be upgraded"))?;
+
+        Ok(Self { accept, on_upgrade })
+    }
+}
+
+impl WebSocketUpgrade {
+    // Answer with 101 Switching Protocols and run the callback on the upgraded connection
+    pub fn on_upgrade<F, Fut>(self, callback: F) -> Response
+    where
+        F: FnOnce(WebSocket) -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let on_upgrade = self.on_upgrade;
+        tokio::spawn(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => callback(WebSocket::new(TokioIo::new(upgraded))).await,
+                Err(e) => error!("WebSocket upgrade failed: {}", e),
+            }
+        });
+
+        Response::builder()
+            .status(StatusCode::SWITCHING_PROTOCOLS)
+            .header(header::CONNECTION, "upgrade")
+            .header(header::UPGRADE, "websocket")
+            .header(header::SEC_WEBSOCKET_ACCEPT, self.accept)
+            .body(Body::empty())
+            .unwrap()
+    }
+}
+
+pub struct WebSocket {
+    reader: ReadHalf<Stream>,
+    writer: WriteHalf<Stream>,
+}
+
+impl WebSocket {
+    fn new(stream: Stream) -> Self {
+        let (reader, writer) = io::split(stream);
+        Self { reader, writer }
+    }
+
+    // Separate halves so a connection can wait for client frames while pushing updates
+    pub fn split(self) -> (WebSocketSender, WebSocketReceiver) {
+        (
+            WebSocketSender {
+                writer: self.writer,
+            },
+            WebSocketReceiver {
+                reader: self.reader,
+            },
+        )
+    }
+}
+
+pub struct WebSocketSender {
+    writer: WriteHalf<Stream>,
+}
+
+impl WebSocketSender {
+    pub async fn send(&mut self, message: Message) -> io::Result<()> {
+        let (opcode, payload) = match &message {
+            Message::Text(text) => (OPCODE_TEXT, text.as_bytes()),
+            Message::Ping(payload) => (OPCODE_PING, payload.as_slice()),
+            Message::Pong(payload) => (OPCODE_PONG, payload.as_slice()),
+            Message::Close => (OPCODE_CLOSE, &[][..]),
+        };
+
+        // Server frames are never masked
+        let mut frame = Vec::with_capacity(payload.len() + 10);
+        frame.push(0x80 | opcode);
+        match payload.len() {
+            len if len < 126 => frame.push(len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(payload);
+
+        self.writer.write_all(&frame).await?;
+        self.writer.flush().await
+    }
+}
+
+pub struct WebSocketReceiver {
+    reader: ReadHalf<Stream>,
+}
+
+impl WebSocketReceiver {
+    // Next message from the client; binary and fragmented frames are skipped
+    pub async fn recv(&mut self) -> io::Result<Message> {
+        loop {
+            let mut head = [0u8; 2];
+            self.reader.read_exact(&mut head).await?;
+            let fin = head[0] & 0x80 != 0;
+            let opcode = head[0] & 0x0F;
+            let masked = head[1] & 0x80 != 0;
+
+            let len = match head[1] & 0x7F {
+                126 => {
+                    let mut ext = [0u8; 2];
+                    self.reader.read_exact(&mut ext).await?;
+                    u16::from_be_bytes(ext) as u64
+                }
+                127 => {
+                    let mut ext = [0u8; 8];
+                    self.reader.read_exact(&mut ext).await?;
+                    u64::from_be_bytes(ext)
+                }
+                len => len as u64,
+            };
+
+            // Clients must mask every frame
+            if !masked || len > MAX_CLIENT_PAYLOAD {
+                return Ok(Message::Close);
+            }
+
+            let mut mask = [0u8; 4];
+            self.reader.read_exact(&mut mask).await?;
+            let mut payload = vec![0u8; len as usize];
+            self.reader.read_exact(&mut payload).await?;
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[i % 4];
+            }
+
+            match opcode {
+                OPCODE_TEXT if fin => {
+                    return Ok(String::from_utf8(payload)
+                        .map(Message::Text)
+                        .unwrap_or(Message::Close))
+                }
+                OPCODE_PING => return Ok(Message::Ping(payload)),
+                OPCODE_PONG => return Ok(Message::Pong(payload)),
+                OPCODE_CLOSE => return Ok(Message::Close),
+                OPCODE_TEXT | OPCODE_BINARY | OPCODE_CONTINUATION => continue,
+                _ => return Ok(Message::Close),
+            }
+        }
+    }
+}
@@ -0,0 +1,143 @@
+// Outgoing email through SMTP or Amazon SES, chosen by MAIL_BACKEND; without one, emails are
+// written to the log. The message bodies come from the built-in templates
+mod ses;
+mod smtp;
+pub mod templates;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::info;
+use url::Url;
+
+use crate::config::Config;
+use crate::http_client::HttpError;
+
+pub use ses::SesMailer;
+pub use smtp::{SmtpMailer, SmtpSecurity};
+pub use templates::{EmailTemplate, RenderedEmail};
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("tls: {0}")]
+    Tls(#[from] rustls::Error),
+    #[error("{0}")]
+    Http(#[from] HttpError),
+    // The server refused a step of the conversation, with its reply
+    #[error("mail server replied `{0}`")]
+    Rejected(String),
+    #[error("invalid address `{0}`")]
+    InvalidAddress(String),
+    #[error("{0}")]
+    Config(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Email {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    pub html: Option<String>,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: &Email) -> Result<(), MailError>;
+}
+
+// Default until a mail backend is configured: writes emails to the log
+pub struct LogMailer;
+
+#[async_trait]
+impl Mailer for LogMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        info!(
+            "Email to <{}>: {} - {}",
+            email.to, email.subject, email.text
+        );
+        Ok(())
+    }
+}
+
+// The bare address of `Name <address>` or `address`; CR and LF are refused so an address can
+// never add a header or an SMTP command
+pub fn address(mailbox: &str) -> Result<&str, MailError> {
+    let address = match (mailbox.find('<'), mailbox.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &mailbox[start + 1..end],
+        _ => mailbox,
+    }
+    .trim();
+    let valid = address
+        .split_once('@')
+        .is_some_and(|(local, domain)| !local.is_empty() && !domain.is_empty())
+        && !address
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || matches!(c, '<' | '>'));
+    if valid {
+        Ok(address)
+    } else {
+        Err(MailError::InvalidAddress(mailbox.to_string()))
+    }
+}
+
+// The backend selected by the configuration
+pub fn from_config(config: &Config) -> Result<Arc<dyn Mailer>, MailError> {
+    let setting = |value: &Option<String>, name: &str| {
+        value
+            .clone()
+            .ok_or_else(|| MailError::Config(format!("{} must be set", name)))
+    };
+    address(&config.mail_from)
+        .map_err(|_| MailError::Config(format!("MAIL_FROM is invalid: {}", config.mail_from)))?;
+
+    match config.mail_backend.as_str() {
+        "log" => Ok(Arc::new(LogMailer)),
+        "smtp" => {
+            let security = SmtpSecurity::from_name(&config.smtp_security).ok_or_else(|| {
+                MailError::Config(format!(
+                    "SMTP_SECURITY must be one of: starttls, tls, none (got {})",
+                    config.smtp_security
+                ))
+            })?;
+            let credentials = match (&config.smtp_username, &config.smtp_password) {
+                (Some(username), Some(password)) => Some((username.clone(), password.clone())),
+                (None, None) => None,
+                _ => {
+                    return Err(MailError::Config(
+                        "SMTP_USERNAME and SMTP_PASSWORD must be set together".to_string(),
+                    ))
+                }
+            };
+            Ok(Arc::new(SmtpMailer::new(
+                setting(&config.smtp_host, "SMTP_HOST")?,
+                config.smtp_port,
+                security,
+                credentials,
+                config.mail_from.clone(),
+            )))
+        }
+        "ses" => {
+            let endpoint = match &config.ses_endpoint {
+                Some(endpoint) => endpoint.clone(),
+                None => format!("https://email.{}.amazonaws.com", config.ses_region),
+            };
+            let endpoint = Url::parse(&endpoint)
+                .map_err(|e| MailError::Config(format!("SES_ENDPOINT is invalid: {}", e)))?;
+            Ok(Arc::new(SesMailer::new(
+                endpoint,
+                config.ses_region.clone(),
+                setting(&config.ses_access_key_id, "SES_ACCESS_KEY_ID")?,
+                setting(&config.ses_secret_access_key, "SES_SECRET_ACCESS_KEY")?,
+                config.mail_from.clone(),
+            )))
+        }
+        other => Err(MailError::Config(format!(
+            "MAIL_BACKEND must be one of: log, smtp, ses (got {})",
+            other
+        ))),
+    }
+}
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::json;
+use url::Url;
+
+use super::{address, Email, MailError, Mailer};
+use crate::http_client;
+use crate::sigv4::{self, SigningRequest};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+const SEND_EMAIL_PATH: &str = "/v2/email/outbound-emails";
+
+// Amazon SES through its v2 SendEmail API, signed with AWS Signature Version 4
+pub struct SesMailer {
+    endpoint: Url,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    from: String,
+}
+
+impl SesMailer {
+    pub fn new(
+        endpoint: Url,
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+        from: String,
+    ) -> Self {
+        Self {
+            endpoint,
+            region,
+            access_key_id,
+            secret_access_key,
+            from,
+        }
+    }
+}
+
+fn content(data: &str) -> serde_json::Value {
+    json!({ "Data": data, "Charset": "UTF-8" })
+}
+
+#[async_trait]
+impl Mailer for SesMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        let to = address(&email.to)?;
+        let mut body = json!({ "Text": content(&email.text) });
+        if let Some(html) = &email.html {
+            body["Html"] = content(html);
+        }
+        let payload = json!({
+            "FromEmailAddress": self.from,
+            "Destination": { "ToAddresses": [to] },
+            "Content": {
+                "Simple": {
+                    "Subject": content(&email.subject),
+                    "Body": body,
+                },
+            },
+        });
+        let payload = serde_json::to_vec(&payload).expect("JSON values serialize");
+
+        let mut url = self.endpoint.clone();
+        url.set_path(SEND_EMAIL_PATH);
+        let mut headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("host".to_string(), http_client::host_header(&url)),
+        ];
+        let authorization = sigv4::sign(
+            SigningRequest {
+                service: "ses",
+                method: "POST",
+                path: url.path(),
+                headers: &mut headers,
+                payload: &payload,
+                region: &self.region,
+                access_key_id: &self.access_key_id,
+                secret_access_key: &self.secret_access_key,
+            },
+            Utc::now(),
+        );
+        headers.push(("authorization".to_string(), authorization));
+        // The Host line is written by the client itself
+        headers.retain(|(name, _)| name != "host");
+
+        let response = http_client::send("POST", url, headers, payload, TIMEOUT).await?;
+        match response.status {
+            200..=299 => Ok(()),
+            status => Err(MailError::Rejected(format!(
+                "{} {}",
+                status,
+                String::from_utf8_lossy(&response.body)
+                    .chars()
+                    .take(200)
+                    .collect::<String>()
+            ))),
+        }
+    }
+}
@@ -0,0 +1,311 @@
+// SMTP submission (RFC 5321) with STARTTLS or implicit TLS and AUTH PLAIN. Like the HTTP client
+// it runs on a blocking thread, so one conversation serves plain TCP and rustls streams
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::Utc;
+use uuid::Uuid;
+
+use super::{address, Email, MailError, Mailer};
+use crate::http_client;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    // Plain connection upgraded with STARTTLS, usually port 587
+    StartTls,
+    // TLS from the first byte, usually port 465
+    Tls,
+    // No encryption, for a relay on the local network
+    None,
+}
+
+impl SmtpSecurity {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "starttls" => Some(SmtpSecurity::StartTls),
+            "tls" => Some(SmtpSecurity::Tls),
+            "none" => Some(SmtpSecurity::None),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SmtpMailer {
+    host: String,
+    port: u16,
+    security: SmtpSecurity,
+    // Username and password for AUTH PLAIN
+    credentials: Option<(String, String)>,
+    from: String,
+}
+
+impl SmtpMailer {
+    pub fn new(
+        host: String,
+        port: u16,
+        security: SmtpSecurity,
+        credentials: Option<(String, String)>,
+        from: String,
+    ) -> Self {
+        Self {
+            host,
+            port,
+            security,
+            credentials,
+            from,
+        }
+    }
+
+    fn deliver(&self, email: &Email) -> Result<(), MailError> {
+        let address = (self.host.as_str(), self.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| MailError::Config(format!("cannot resolve {}", self.host)))?;
+        let mut tcp = TcpStream::connect_timeout(&address, TIMEOUT)?;
+        tcp.set_read_timeout(Some(TIMEOUT))?;
+        tcp.set_write_timeout(Some(TIMEOUT))?;
+
+        let tls = || -> Result<rustls::ClientConnection, MailError> {
+            let server_name = rustls::ServerName::try_from(self.host.as_str())
+                .map_err(|_| MailError::Config(format!("invalid host {}", self.host)))?;
+            Ok(rustls::ClientConnection::new(
+                http_client::tls_config(),
+                server_name,
+            )?)
+        };
+        match self.security {
+            SmtpSecurity::Tls => {
+                let mut stream = rustls::StreamOwned::new(tls()?, tcp);
+                expect(&mut stream, &[220])?;
+                self.session(&mut stream, email)
+            }
+            SmtpSecurity::StartTls => {
+                expect(&mut tcp, &[220])?;
+                command(
+                    &mut tcp,
+                    &format!("EHLO {}", sender_domain(&self.from)),
+                    &[250],
+                )?;
+                command(&mut tcp, "STARTTLS", &[220])?;
+                let mut stream = rustls::StreamOwned::new(tls()?, tcp);
+                self.session(&mut stream, email)
+            }
+            SmtpSecurity::None => {
+                expect(&mut tcp, &[220])?;
+                self.session(&mut tcp, email)
+            }
+        }
+    }
+
+    // Everything after the greeting (and STARTTLS) up to QUIT
+    fn session<S: Read + Write>(&self, stream: &mut S, email: &Email) -> Result<(), MailError> {
+        command(
+            stream,
+            &format!("EHLO {}", sender_domain(&self.from)),
+            &[250],
+        )?;
+        if let Some((username, password)) = &self.credentials {
+            let token = STANDARD.encode(format!("\0{}\0{}", username, password));
+            command(stream, &format!("AUTH PLAIN {}", token), &[235])?;
+        }
+        command(
+            stream,
+            &format!("MAIL FROM:<{}>", address(&self.from)?),
+            &[250],
+        )?;
+        command(
+            stream,
+            &format!("RCPT TO:<{}>", address(&email.to)?),
+            &[250, 251],
+        )?;
+        command(stream, "DATA", &[354])?;
+        stream.write_all(&dot_stuffed(&message(&self.from, email)))?;
+        command(stream, ".", &[250])?;
+        // The message is accepted at this point, a failed goodbye changes nothing
+        let _ = command(stream, "QUIT", &[221]);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: &Email) -> Result<(), MailError> {
+        let mailer = self.clone();
+        let email = email.clone();
+        tokio::task::spawn_blocking(move || mailer.deliver(&email))
+            .await
+            .map_err(|e| MailError::Io(io::Error::other(e)))?
+    }
+}
+
+// The domain of the sender, named in EHLO and the Message-ID
+fn sender_domain(from: &str) -> &str {
+    address(from)
+        .ok()
+        .and_then(|address| address.rsplit_once('@'))
+        .map_or("localhost", |(_, domain)| domain)
+}
+
+// One line, byte by byte so nothing past the reply is buffered when the stream turns to TLS
+fn read_line<S: Read>(stream: &mut S) -> Result<String, MailError> {
+    let mut line = Vec::new();
+    let mut byte = [0; 1];
+    while line.last() != Some(&b'\n') {
+        if stream.read(&mut byte)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        line.push(byte[0]);
+    }
+    Ok(String::from_utf8_lossy(&line).trim_end().to_string())
+}
+
+// Read a possibly multi-line reply (`250-...` lines ended by `250 ...`) and check its code
+fn expect<S: Read>(stream: &mut S, codes: &[u16]) -> Result<String, MailError> {
+    let mut reply = Vec::new();
+    loop {
+        let line = read_line(stream)?;
+        let last = line.as_bytes().get(3) != Some(&b'-');
+        let code = line.get(..3).and_then(|code| code.parse::<u16>().ok());
+        reply.push(line);
+        if code.is_none() {
+            return Err(MailError::Rejected(reply.join(" ")));
+        }
+        if last {
+            return if code.is_some_and(|code| codes.contains(&code)) {
+                Ok(reply.join("\n"))
+            } else {
+                Err(MailError::Rejected(reply.join(" ")))
+            };
+        }
+    }
+}
+
+fn command<S: Read + Write>(
+    stream: &mut S,
+    line: &str,
+    codes: &[u16],
+) -> Result<String, MailError> {
+    stream.write_all(format!("{}\r\n", line).as_bytes())?;
+    stream.flush()?;
+    expect(stream, codes)
+}
+
+// A header value as an RFC 2047 encoded word when it is not plain ASCII
+fn encode_header(value: &str) -> String {
+    let value = value.replace(['\r', '\n'], " ");
+    if value.is_ascii() {
+        value
+    } else {
+        format!("=?UTF-8?B?{}?=", STANDARD.encode(value))
+    }
+}
+
+// Base64 wrapped at 76 characters per line, as MIME requires
+fn base64_lines(data: &str) -> String {
+    let encoded = STANDARD.encode(data);
+    let mut out = String::with_capacity(encoded.len() + encoded.len() / 38);
+    for chunk in encoded.as_bytes().chunks(76) {
+        out.push_str(std::str::from_utf8(chunk).expect("base64 is ASCII"));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+// The full message: headers, then the text body alone or with its HTML alternative
+fn message(from: &str, email: &Email) -> String {
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}@{}>\r\nMIME-Version: 1.0\r\n",
+        encode_header(from),
+        encode_header(&email.to),
+        encode_header(&email.subject),
+        Utc::now().to_rfc2822(),
+        Uuid::new_v4().simple(),
+        sender_domain(from)
+    );
+    let part = |content_type: &str, body: &str| {
+        format!(
+            "Content-Type: {}; charset=UTF-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{}",
+            content_type,
+            base64_lines(body)
+        )
+    };
+    match &email.html {
+        None => message.push_str(&part("text/plain", &email.text)),
+        Some(html) => {
+            let boundary = format!("=_{}", Uuid::new_v4().simple());
+            message.push_str(&format!(
+                "Content-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n",
+                boundary
+            ));
+            message.push_str(&format!(
+                "--{}\r\n{}",
+                boundary,
+                part("text/plain", &email.text)
+            ));
+            message.push_str(&format!("--{}\r\n{}", boundary, part("text/html", html)));
+            message.push_str(&format!("--{}--\r\n", boundary));
+        }
+    }
+    message
+}
+
+// DATA content: a leading dot is doubled so no line ends the message early
+fn dot_stuffed(message: &str) -> Vec<u8> {
+    let mut out = String::with_capacity(message.len() + 16);
+    for line in message.split_inclusive("\r\n") {
+        if line.starts_with('.') {
+            out.push('.');
+        }
+        out.push_str(line);
+    }
+    if !out.ends_with("\r\n") {
+        out.push_str("\r\n");
+    }
+    out.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_replies_and_builds_messages() {
+        let mut replies =
+            io::Cursor::new(b"250-mail.example\r\n250-AUTH PLAIN\r\n250 OK\r\n550 no\r\n");
+        assert_eq!(
+            expect(&mut replies, &[250]).unwrap(),
+            "250-mail.example\n250-AUTH PLAIN\n250 OK"
+        );
+        assert!(matches!(
+            expect(&mut replies, &[250]),
+            Err(MailError::Rejected(reply)) if reply == "550 no"
+        ));
+
+        assert_eq!(
+            encode_header("Ваш рейс"),
+            "=?UTF-8?B?0JLQsNGIINGA0LXQudGB?="
+        );
+        assert_eq!(encode_header("Hi\r\nBcc: x@y"), "Hi  Bcc: x@y");
+        assert_eq!(dot_stuffed(".a\r\nb\r\n..c"), b"..a\r\nb\r\n...c\r\n");
+
+        let email = Email {
+            to: "ann@example.com".to_string(),
+            subject: "Hello".to_string(),
+            text: "Plain".to_string(),
+            html: Some("<p>Rich</p>".to_string()),
+        };
+        let message = message("Airlines <no-reply@airlines.example>", &email);
+        assert!(message.contains("From: Airlines <no-reply@airlines.example>\r\n"));
+        assert!(message.contains("@airlines.example>\r\n"));
+        assert!(message.contains("multipart/alternative"));
+        assert!(message.contains(&STANDARD.encode("<p>Rich</p>")));
+    }
+}
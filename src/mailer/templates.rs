@@ -0,0 +1,266 @@
+// The built-in emails, each a subject plus a plain-text and an HTML body under templates/email.
+// Placeholders are `{{ name }}` or `{{ flight.number }}`, looked up in a JSON context and
+// HTML-escaped in the HTML body; `{{#if name}} .. {{else}} .. {{/if}}` keeps a part only when the
+// value is present and not false or empty
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::i18n::Locale;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("template value `{0}` is missing")]
+    Missing(String),
+    #[error("malformed template: {0}")]
+    Malformed(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmailTemplate {
+    Verification,
+    PasswordReset,
+    BookingConfirmation,
+    FlightDisruption,
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+// The subject, text and HTML of a template in each language: English under templates/email,
+// other languages in a subdirectory named after them
+macro_rules! sources {
+    ($locale:expr, $name:literal) => {
+        match $locale {
+            Locale::En => (
+                include_str!(concat!("../../templates/email/", $name, ".subject")),
+                include_str!(concat!("../../templates/email/", $name, ".txt")),
+                include_str!(concat!("../../templates/email/", $name, ".html")),
+            ),
+            Locale::Uk => (
+                include_str!(concat!("../../templates/email/uk/", $name, ".subject")),
+                include_str!(concat!("../../templates/email/uk/", $name, ".txt")),
+                include_str!(concat!("../../templates/email/uk/", $name, ".html")),
+            ),
+        }
+    };
+}
+
+impl EmailTemplate {
+    fn sources(self, locale: Locale) -> (&'static str, &'static str, &'static str) {
+        match self {
+            EmailTemplate::Verification => sources!(locale, "verification"),
+            EmailTemplate::PasswordReset => sources!(locale, "password_reset"),
+            EmailTemplate::BookingConfirmation => sources!(locale, "booking_confirmation"),
+            EmailTemplate::FlightDisruption => sources!(locale, "flight_disruption"),
+        }
+    }
+
+    pub fn render(self, locale: Locale, context: &Value) -> Result<RenderedEmail, TemplateError> {
+        let (subject, text, html) = self.sources(locale);
+        Ok(RenderedEmail {
+            // One line however the subject file ends
+            subject: render(subject, context, false)?
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            text: render(text, context, false)?,
+            html: render(html, context, true)?,
+        })
+    }
+}
+
+enum Node<'a> {
+    Text(&'a str),
+    Value(&'a str),
+    If {
+        name: &'a str,
+        then: Vec<Node<'a>>,
+        otherwise: Vec<Node<'a>>,
+    },
+}
+
+// Nodes up to the `{{/if}}` or `{{else}}` closing the current block (or the end of the source),
+// with the tag that ended them
+fn parse<'a>(source: &mut &'a str) -> Result<(Vec<Node<'a>>, Option<&'a str>), TemplateError> {
+    let mut nodes = Vec::new();
+    loop {
+        let Some(start) = source.find("{{") else {
+            if !source.is_empty() {
+                nodes.push(Node::Text(source));
+            }
+            *source = "";
+            return Ok((nodes, None));
+        };
+        if start > 0 {
+            nodes.push(Node::Text(&source[..start]));
+        }
+        let rest = &source[start + 2..];
+        let end = rest
+            .find("}}")
+            .ok_or_else(|| TemplateError::Malformed("unclosed `{{`".to_string()))?;
+        let tag = rest[..end].trim();
+        *source = &rest[end + 2..];
+
+        if tag == "else" || tag == "/if" {
+            return Ok((nodes, Some(tag)));
+        } else if let Some(name) = tag.strip_prefix("#if ") {
+            let (then, closing) = parse(source)?;
+            let otherwise = match closing {
+                Some("else") => match parse(source)? {
+                    (otherwise, Some("/if")) => otherwise,
+                    _ => return Err(TemplateError::Malformed(format!("unclosed #if {}", name))),
+                },
+                Some("/if") => Vec::new(),
+                _ => return Err(TemplateError::Malformed(format!("unclosed #if {}", name))),
+            };
+            nodes.push(Node::If {
+                name: name.trim(),
+                then,
+                otherwise,
+            });
+        } else {
+            nodes.push(Node::Value(tag));
+        }
+    }
+}
+
+fn lookup<'v>(context: &'v Value, name: &str) -> Option<&'v Value> {
+    name.split('.')
+        .try_fold(context, |value, key| value.get(key))
+        .filter(|value| !value.is_null())
+}
+
+fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(_) => true,
+    }
+}
+
+fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+fn write_nodes(
+    nodes: &[Node<'_>],
+    context: &Value,
+    html: bool,
+    out: &mut String,
+) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value(name) => {
+                let value = lookup(context, name)
+                    .ok_or_else(|| TemplateError::Missing(name.to_string()))?;
+                let text = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                if html {
+                    escape_html(&text, out);
+                } else {
+                    out.push_str(&text);
+                }
+            }
+            Node::If {
+                name,
+                then,
+                otherwise,
+            } => {
+                let branch = if is_truthy(lookup(context, name)) {
+                    then
+                } else {
+                    otherwise
+                };
+                write_nodes(branch, context, html, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Fill in a template; `html` escapes the values for an HTML document
+pub fn render(source: &str, context: &Value, html: bool) -> Result<String, TemplateError> {
+    let mut rest = source;
+    let nodes = match parse(&mut rest)? {
+        (nodes, None) => nodes,
+        (_, Some(tag)) => return Err(TemplateError::Malformed(format!("stray {{{{{}}}}}", tag))),
+    };
+    let mut out = String::with_capacity(source.len());
+    write_nodes(&nodes, context, html, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn fills_values_and_sections() {
+        let context = json!({ "user": { "name": "<Ann>" }, "gate": null, "late": true });
+        let source = "Hi {{ user.name }}{{#if gate}} at {{gate}}{{else}}, gate TBA{{/if}}\
+                      {{#if late}}, delayed{{/if}}";
+        assert_eq!(
+            render(source, &context, false).unwrap(),
+            "Hi <Ann>, gate TBA, delayed"
+        );
+        assert_eq!(
+            render(source, &context, true).unwrap(),
+            "Hi &lt;Ann&gt;, gate TBA, delayed"
+        );
+        assert_eq!(
+            render("{{ user.email }}", &context, false),
+            Err(TemplateError::Missing("user.email".to_string()))
+        );
+        assert!(render("{{#if late}}open", &context, false).is_err());
+    }
+
+    #[test]
+    fn renders_every_built_in_email() {
+        let context = json!({
+            "passenger": { "first_name": "Ann", "last_name": "Lee" },
+            "token": "abc123",
+            "expires_in_minutes": 60,
+            "ticket": { "number": "TK-1", "seat": "12A", "fare_class": "economy" },
+            "flight": {
+                "number": "PS101",
+                "origin": "KBP",
+                "destination": "LHR",
+                "departure_time": "Monday 2 March 2026, 08:15 UTC",
+                "arrival_time": "Monday 2 March 2026, 11:05 UTC",
+                "gate": "B4",
+                "cancelled": false,
+            },
+        });
+        for template in [
+            EmailTemplate::Verification,
+            EmailTemplate::PasswordReset,
+            EmailTemplate::BookingConfirmation,
+            EmailTemplate::FlightDisruption,
+        ] {
+            for locale in Locale::ALL {
+                let email = template.render(*locale, &context).unwrap();
+                assert!(!email.subject.is_empty() && !email.subject.contains('\n'));
+                assert!(email.text.contains("Ann"));
+                assert!(email.html.contains("Ann"));
+            }
+        }
+    }
+}
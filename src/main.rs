@@ -1,23 +1,68 @@
-mod config;
-mod db;
-mod handlers;
-mod logging;
-mod models;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tracing::{error, info, warn};
 
-use axum::{routing::get, Router};
-use std::net::SocketAddr;
-use tracing::info;
+use airlines_api::{
+    auth, cache, config, db, error_reporting, event_bus, jobs, live, logging, mailer, middleware,
+    models, notifications, pricing, seed, shutdown, signed_url, state, storage, tls, virus_scan,
+    Services,
+};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     logging::setup_logging();
 
-    // Load configuration
-    let config = config::Config::from_env().expect("Failed to load configuration");
+    // Load configuration; refuse to start on missing or malformed settings
+    let config = match config::Config::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Invalid configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
 
     info!("Starting application with configuration: {:?}", config);
 
+    match error_reporting::init(&config) {
+        Ok(true) => info!(
+            "Reporting server errors to the error tracker (sample rate {})",
+            config.error_reporting_sample_rate
+        ),
+        Ok(false) => {}
+        Err(e) => {
+            error!("Invalid error reporting configuration: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if !signed_url::init(&config) {
+        warn!("URL_SIGNING_SECRET is not set, file links stop working on restart");
+    }
+
+    let jwt_keys = match auth::JwtKeys::from_config(&config) {
+        Ok(keys) => Arc::new(keys),
+        Err(e) => {
+            error!("Invalid JWT key configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    info!(
+        "Signing {} tokens, accepting key ids {:?}",
+        config.jwt_algorithm,
+        jwt_keys.key_ids()
+    );
+    // Remember token revocation checks and users' permissions for AUTH_CACHE_TTL seconds
+    models::RevokedToken::cache_checks(Duration::from_secs(config.auth_cache_ttl));
+    models::UserPermissions::cache(Duration::from_secs(config.auth_cache_ttl));
+
+    let tls = match tls::server_config(&config) {
+        Ok(tls) => tls,
+        Err(e) => {
+            error!("Invalid TLS configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+
     // Create database connection pool
     let pool = db::create_pool(&config.database_url)
         .await
@@ -31,24 +76,269 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     info!("Successfully connected to database");
 
-    // Build our application with routes
-    let app = Router::new()
-        .route("/health", get(handlers::health_check::health_check))
-        .route("/routes", get(handlers::route_handler::get_routes))
-        .route(
-            "/routes/{id}",
-            get(handlers::route_handler::get_route_by_id),
+    // Bring the schema up to date before serving requests
+    if config.skip_migrations {
+        info!("Skipping database migrations");
+    } else if let Err(e) = db::MIGRATOR.run(&pool).await {
+        error!("Failed to apply database migrations: {}", e);
+        std::process::exit(1);
+    } else {
+        info!("Database schema is up to date");
+    }
+
+    // `--seed` fills the database with demo data and exits instead of serving
+    if std::env::args().any(|arg| arg == "--seed") {
+        seed::run(&pool).await?;
+        pool.close().await;
+        return Ok(());
+    }
+
+    // Start the notification queue worker; emails are handed to the job runner
+    let (notifier, notification_worker) = notifications::Notifier::start(vec![Arc::new(
+        notifications::EmailSender::new(pool.clone()),
+    )]);
+
+    // Email through MAIL_BACKEND, sent by the job runner
+    let mailer = match mailer::from_config(&config) {
+        Ok(mailer) => mailer,
+        Err(e) => {
+            error!("Invalid mail configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // Outbox events streamed to EVENT_BUS, if any
+    let event_bus = match event_bus::from_config(&config) {
+        Ok(event_bus) => event_bus,
+        Err(e) => {
+            error!("Invalid event bus configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    // Flights and routes cached in Redis when REDIS_URL is set
+    let cache = match cache::from_config(&config) {
+        Ok(cache) => cache,
+        Err(e) => {
+            error!("Invalid cache configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let sms: Arc<dyn notifications::SmsProvider> = Arc::new(notifications::LogSmsProvider);
+
+    // Uploaded files, scanned for malware when a clamd is configured
+    let storage = match storage::from_config(&config) {
+        Ok(storage) => storage,
+        Err(e) => {
+            error!("Invalid storage configuration: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let virus_scanner: Arc<dyn virus_scan::VirusScanner> = match &config.clamav_address {
+        Some(address) => Arc::new(virus_scan::ClamdScanner::new(address)),
+        None => {
+            warn!("CLAMAV_ADDRESS is not set, uploads are not scanned for malware");
+            Arc::new(virus_scan::NoScanner)
+        }
+    };
+
+    // Live flight updates for WebSocket subscribers
+    let flight_updates = live::FlightUpdates::default();
+
+    // Background jobs stop when this flips to true
+    let (stop_jobs, jobs_shutdown) = tokio::sync::watch::channel(false);
+
+    // Run queued jobs (emails, disruption notifications, miles, webhook fan-out) in the background
+    let job_runner = jobs::queue::JobRunner::new(jobs::queue::JobContext {
+        pool: pool.clone(),
+        mailer,
+        notifier: notifier.clone(),
+        event_bus: event_bus.clone(),
+    })
+    .register::<jobs::send_email::SendEmail>()
+    .register::<jobs::flight_disruption::NotifyFlightDisruption>()
+    .register::<jobs::booking_confirmation::ConfirmBooking>()
+    .register::<jobs::miles_accrual::AccrueFlightMiles>()
+    .register::<jobs::webhook_delivery::QueueWebhookEvent>()
+    .register::<jobs::publish_event::PublishEvent>()
+    .spawn(
+        Duration::from_secs(config.job_poll_interval),
+        jobs_shutdown.clone(),
+    );
+
+    // Turn domain events recorded with their change into jobs
+    let outbox_relay = jobs::outbox_relay::spawn(
+        pool.clone(),
+        Duration::from_secs(config.outbox_relay_interval),
+        event_bus.is_some(),
+        jobs_shutdown.clone(),
+    );
+
+    let mut state = state::AppState::new(pool.clone(), config.clone(), jwt_keys);
+    if let Some(cache) = cache {
+        state = state.with_cache(
+            cache,
+            Duration::from_secs(config.cache_ttl),
+            Duration::from_secs(config.cache_search_ttl),
+        );
+    }
+
+    // Recurring maintenance on the SCHEDULE_* cron expressions
+    let scheduled_tasks = [
+        (
+            jobs::scheduler::Task::MarkDeparted,
+            &config.schedule_mark_departed,
+        ),
+        (
+            jobs::scheduler::Task::FlagNoShows,
+            &config.schedule_flag_no_shows,
+        ),
+        (
+            jobs::scheduler::Task::PurgeResetTokens,
+            &config.schedule_purge_reset_tokens,
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(task, schedule)| schedule.clone().map(|schedule| (task, schedule)))
+    .collect();
+    let scheduler = jobs::scheduler::spawn(
+        pool.clone(),
+        state.flights.clone(),
+        flight_updates.clone(),
+        scheduled_tasks,
+        jobs_shutdown.clone(),
+    );
+
+    // Credit loyalty miles for arrived flights in the background
+    let miles_accrual = jobs::miles_accrual::spawn(
+        pool.clone(),
+        Duration::from_secs(config.miles_accrual_interval),
+        jobs_shutdown.clone(),
+    );
+
+    // Send queued webhook deliveries and their retries in the background
+    let webhook_delivery = jobs::webhook_delivery::spawn(
+        pool.clone(),
+        Duration::from_secs(config.webhook_delivery_interval),
+        jobs_shutdown.clone(),
+    );
+
+    // Build requested GDPR data exports in the background
+    let data_export = jobs::data_export::spawn(
+        pool.clone(),
+        Duration::from_secs(config.data_export_interval),
+        config.data_export_ttl,
+        jobs_shutdown.clone(),
+    );
+
+    // Forget expired Idempotency-Keys hourly
+    let idempotency_cleanup = jobs::idempotency_cleanup::spawn(
+        pool.clone(),
+        Duration::from_secs(3600),
+        config.idempotency_key_ttl,
+        jobs_shutdown.clone(),
+    );
+
+    // Keep the exchange rates behind `?currency=` current, when a source is configured
+    let exchange_rates = config.exchange_rates_url.clone().map(|url| {
+        jobs::exchange_rates::spawn(
+            pool.clone(),
+            url,
+            Duration::from_secs(config.exchange_rates_interval),
+            jobs_shutdown,
         )
-        .with_state(pool);
+    });
+
+    // Select the ticket pricing strategy
+    let pricing = pricing::PricingEngine::from_kind(config.pricing_strategy);
+    info!("Using {} pricing strategy", pricing.strategy_name());
+
+    // Build our application with routes
+    let app = airlines_api::app(
+        state,
+        Services {
+            notifier,
+            flight_updates,
+            sms,
+            pricing,
+            storage,
+            virus_scanner,
+        },
+    );
+    let app = if tls.is_some() {
+        app.layer(axum::middleware::from_fn_with_state(
+            config.hsts_max_age,
+            middleware::hsts::hsts,
+        ))
+    } else {
+        app
+    };
+
+    // Stop accepting connections on SIGTERM/Ctrl+C and let in-flight requests finish
+    let grace = Duration::from_secs(config.shutdown_grace_period);
+    let server = axum_server::Handle::new();
+    shutdown::on_signal(server.clone(), grace);
 
     // Run it with hyper
-    let addr = SocketAddr::from(([0, 0, 0, 0], config.server_port));
-    info!("Listening on {}", addr);
+    let addr = SocketAddr::new(config.bind_host, config.server_port);
+    let service = app.into_make_service_with_connect_info::<SocketAddr>();
+    match tls {
+        Some(tls) => {
+            if let Some(port) = config.http_redirect_port {
+                let redirect_addr = SocketAddr::new(config.bind_host, port);
+                info!("Redirecting HTTP on {} to HTTPS", redirect_addr);
+                let redirect = axum_server::bind(redirect_addr)
+                    .handle(server.clone())
+                    .serve(tls::redirect_router(config.server_port).into_make_service());
+                tokio::spawn(async move {
+                    if let Err(e) = redirect.await {
+                        error!("HTTP redirect listener failed: {}", e);
+                    }
+                });
+            }
 
-    axum_server::bind(addr)
-        .serve(app.into_make_service())
-        .await
-        .unwrap();
+            info!("Listening on {} (HTTPS)", addr);
+            axum_server::bind(addr)
+                .acceptor(tls::TlsAcceptor::new(tls))
+                .handle(server)
+                .serve(service)
+                .await
+                .unwrap();
+        }
+        None => {
+            info!("Listening on {}", addr);
+            axum_server::bind(addr)
+                .handle(server)
+                .serve(service)
+                .await
+                .unwrap();
+        }
+    }
+
+    // The router and its notifier handle are gone now, so the queue worker exits once drained
+    info!("Server stopped, waiting for background jobs");
+    let _ = stop_jobs.send(true);
+    let background = async {
+        let _ = tokio::join!(
+            job_runner,
+            outbox_relay,
+            scheduler,
+            miles_accrual,
+            webhook_delivery,
+            data_export,
+            idempotency_cleanup,
+            async {
+                if let Some(exchange_rates) = exchange_rates {
+                    let _ = exchange_rates.await;
+                }
+            },
+            notification_worker
+        );
+    };
+    if tokio::time::timeout(grace, background).await.is_err() {
+        error!("Background jobs did not finish within {:?}", grace);
+    }
+
+    pool.close().await;
+    info!("Shutdown complete");
 
     Ok(())
 }
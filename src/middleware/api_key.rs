@@ -0,0 +1,75 @@
+use std::{fmt, sync::Arc};
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+
+use super::auth::AuthUser;
+use crate::auth::JwtKeys;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::models::{ApiKey, ApiScope};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+// Caller of a read endpoint: a user by Bearer JWT, or a partner by `X-Api-Key`
+#[derive(Debug, Clone)]
+pub enum ReadCaller {
+    User(AuthUser),
+    Partner(ApiKey),
+}
+
+impl ReadCaller {
+    // Staff users may read everything; partner keys need the matching scope
+    pub fn require(&self, scope: ApiScope) -> Result<(), AppError> {
+        match self {
+            ReadCaller::User(auth) => auth.require_staff(),
+            ReadCaller::Partner(key) if key.has_scope(scope) => Ok(()),
+            ReadCaller::Partner(_) => Err(AppError::Forbidden(
+                ErrorCode::MissingScope,
+                "The API key is not allowed to read this resource".to_string(),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ReadCaller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadCaller::User(auth) => write!(f, "User {}", auth.user_id),
+            ReadCaller::Partner(key) => write!(f, "API key {} ({})", key.key_id, key.name),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for ReadCaller
+where
+    S: Send + Sync,
+    DbPool: FromRef<S>,
+    Config: FromRef<S>,
+    Arc<JwtKeys>: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Some(key) = parts
+            .headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+        else {
+            return AuthUser::from_request_parts(parts, state)
+                .await
+                .map(ReadCaller::User);
+        };
+
+        let pool = DbPool::from_ref(state);
+        ApiKey::authenticate(&pool, key)
+            .await?
+            .map(ReadCaller::Partner)
+            .ok_or_else(|| {
+                AppError::AuthError(ErrorCode::InvalidApiKey, "Invalid API key".to_string())
+            })
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts},
+};
+
+use crate::auth::{verify_token, Claims, JwtKeys};
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::models::{StaffPosition, UserPermissions, UserRole};
+
+// Authenticated caller, extracted from the `Authorization: Bearer <token>` header
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: i32,
+    pub role: UserRole,
+    pub staff_position: Option<StaffPosition>,
+    pub claims: Claims,
+    // Admin whose session skipped the two-factor authentication required by config
+    pub needs_two_factor: bool,
+}
+
+impl AuthUser {
+    fn require_two_factor(&self) -> Result<(), AppError> {
+        if self.needs_two_factor {
+            Err(AppError::Forbidden(
+                ErrorCode::TwoFactorEnrollmentRequired,
+                "Administrators must log in with two-factor authentication".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Reject callers that are not Admin
+    pub fn require_admin(&self) -> Result<(), AppError> {
+        if self.role == UserRole::Admin {
+            self.require_two_factor()
+        } else {
+            Err(AppError::Forbidden(
+                ErrorCode::Forbidden,
+                "Access restricted to administrators".to_string(),
+            ))
+        }
+    }
+
+    // Reject callers that are not Admin or Worker
+    pub fn require_staff(&self) -> Result<(), AppError> {
+        if self.role.is_staff() {
+            self.require_two_factor()
+        } else {
+            Err(AppError::Forbidden(
+                ErrorCode::Forbidden,
+                "Access restricted to staff".to_string(),
+            ))
+        }
+    }
+
+    // Reject callers other than the resource's owner; Admin and Worker may act on any user's resources
+    pub fn require_owner(&self, owner_id: i32) -> Result<(), AppError> {
+        if self.user_id == owner_id {
+            Ok(())
+        } else if self.role.is_staff() {
+            self.require_two_factor()
+        } else {
+            Err(AppError::Forbidden(
+                ErrorCode::NotOwner,
+                "You can only access your own resources".to_string(),
+            ))
+        }
+    }
+
+    // Reject callers that are neither Admin nor a Worker in one of the positions
+    pub fn require_position(&self, positions: &[StaffPosition]) -> Result<(), AppError> {
+        if self.role == UserRole::Admin {
+            return self.require_two_factor();
+        }
+
+        match self.staff_position {
+            Some(position) if self.role == UserRole::Worker && positions.contains(&position) => {
+                Ok(())
+            }
+            _ => Err(AppError::Forbidden(
+                ErrorCode::WrongStaffPosition,
+                format!(
+                    "Access restricted to {}",
+                    positions
+                        .iter()
+                        .map(StaffPosition::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            )),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for AuthUser
+where
+    S: Send + Sync,
+    DbPool: FromRef<S>,
+    Config: FromRef<S>,
+    Arc<JwtKeys>: FromRef<S>,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Already verified by a route guard
+        if let Some(auth) = parts.extensions.get::<Self>() {
+            return Ok(auth.clone());
+        }
+
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| {
+                AppError::AuthError(ErrorCode::Unauthorized, "Missing bearer token".to_string())
+            })?;
+
+        let config = Config::from_ref(state);
+        let keys = Arc::<JwtKeys>::from_ref(state);
+        let pool = DbPool::from_ref(state);
+        let claims = verify_token(&keys, &pool, token).await?;
+        // The role and position stored now, not the ones the token was issued with
+        let permissions = UserPermissions::find(&pool, claims.sub)
+            .await?
+            .ok_or_else(|| {
+                AppError::AuthError(
+                    ErrorCode::InvalidToken,
+                    "Invalid or expired token".to_string(),
+                )
+            })?;
+
+        Ok(Self {
+            user_id: claims.sub,
+            role: permissions.role,
+            staff_position: permissions.staff_position,
+            needs_two_factor: config.require_admin_2fa
+                && permissions.role == UserRole::Admin
+                && !claims.two_factor,
+            claims,
+        })
+    }
+}
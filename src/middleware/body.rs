@@ -0,0 +1,58 @@
+use axum::{
+    body::HttpBody,
+    extract::Request,
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::error::{AppError, ErrorCode};
+
+// Body types some endpoint takes: JSON, multipart/form-data for uploads and text/csv for the
+// passenger import
+const ACCEPTED_MEDIA_TYPES: [&str; 3] = ["application/json", "multipart/form-data", "text/csv"];
+
+fn media_type(headers: &HeaderMap) -> Option<String> {
+    let content_type = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+    Some(content_type.split(';').next()?.trim().to_ascii_lowercase())
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    media_type(headers).as_deref() == Some("application/json")
+}
+
+// A request body of a type no endpoint takes is refused up front, and the refusals of the body
+// extractors (over the route's `DefaultBodyLimit`, a type the handler does not take) get the
+// error envelope instead of axum's plain text
+pub async fn enforce_body(request: Request, next: Next) -> Response {
+    let has_body = request.body().size_hint().exact() != Some(0);
+    if has_body
+        && !media_type(request.headers())
+            .is_some_and(|media_type| ACCEPTED_MEDIA_TYPES.contains(&media_type.as_str()))
+    {
+        return AppError::UnsupportedMediaType(
+            ErrorCode::UnsupportedMediaType,
+            "Request bodies must be application/json, multipart/form-data or text/csv".to_string(),
+        )
+        .into_response();
+    }
+
+    let response = next.run(request).await;
+    if is_json(response.headers()) {
+        return response;
+    }
+
+    match response.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge(
+            ErrorCode::PayloadTooLarge,
+            "The request body is too large for this endpoint".to_string(),
+        )
+        .into_response(),
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => AppError::UnsupportedMediaType(
+            ErrorCode::UnsupportedMediaType,
+            "This endpoint does not accept the request body's Content-Type".to_string(),
+        )
+        .into_response(),
+        _ => response,
+    }
+}
@@ -0,0 +1,112 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::{Map, Value};
+use tracing::error;
+
+// Per-request override of the configured response format: `legacy` or `modern`
+pub const RESPONSE_FORMAT_HEADER: &str = "x-response-format";
+
+// Whether the client asked for (or defaults to) the Node.js API's response format
+fn wants_legacy(request: &Request, legacy_by_default: bool) -> bool {
+    match request
+        .headers()
+        .get(RESPONSE_FORMAT_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(value) if value.eq_ignore_ascii_case("legacy") => true,
+        Some(value) if value.eq_ignore_ascii_case("modern") => false,
+        _ => legacy_by_default,
+    }
+}
+
+fn to_camel_case(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut upper_next = false;
+    for c in key.chars() {
+        if c == '_' {
+            upper_next = !result.is_empty();
+        } else if upper_next {
+            result.extend(c.to_uppercase());
+            upper_next = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn camel_case_keys(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (to_camel_case(&key), camel_case_keys(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(camel_case_keys).collect()),
+        other => other,
+    }
+}
+
+// The Node.js API reported failures as `{ success: false, message, code }`
+fn legacy_envelope(value: Value) -> Value {
+    match value {
+        Value::Object(mut map) if map.get("success") == Some(&Value::Bool(false)) => {
+            let mut legacy = Map::new();
+            legacy.insert("success".to_string(), Value::Bool(false));
+            if let Some(message) = map.remove("error") {
+                legacy.insert("message".to_string(), message);
+            }
+            if let Some(code) = map.remove("error_code") {
+                legacy.insert("code".to_string(), code);
+            }
+            legacy.extend(map);
+            Value::Object(legacy)
+        }
+        other => other,
+    }
+}
+
+// Rewrite JSON responses into the legacy camelCase format for clients that still expect it
+pub async fn response_compat(
+    State(legacy_by_default): State<bool>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let legacy = wants_legacy(&request, legacy_by_default);
+    let response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !legacy || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Failed to buffer response for legacy format: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(value) => serde_json::to_vec(&camel_case_keys(legacy_envelope(value)))
+            .unwrap_or_else(|_| bytes.to_vec()),
+        Err(_) => bytes.to_vec(),
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts
+        .headers
+        .insert(RESPONSE_FORMAT_HEADER, HeaderValue::from_static("legacy"));
+    Response::from_parts(parts, Body::from(body))
+}
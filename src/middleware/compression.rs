@@ -0,0 +1,31 @@
+use tower_http::compression::{
+    predicate::{NotForContentType, Predicate, SizeAbove},
+    CompressionLayer,
+};
+use tower_http::decompression::RequestDecompressionLayer;
+
+// Excel downloads are zip archives already
+const XLSX: NotForContentType =
+    NotForContentType::const_new("application/vnd.openxmlformats-officedocument");
+
+// gzip or brotli, whichever the client prefers in Accept-Encoding, for bodies of at least
+// `min_bytes`. Images, already compressed spreadsheets and event streams are sent as they are,
+// the latter so that every event is flushed as soon as it is written
+pub fn compression(min_bytes: u16) -> CompressionLayer<impl Predicate> {
+    CompressionLayer::new()
+        .no_deflate()
+        .no_zstd()
+        .compress_when(
+            SizeAbove::new(min_bytes)
+                .and(NotForContentType::GRPC)
+                .and(NotForContentType::IMAGES)
+                .and(NotForContentType::SSE)
+                .and(XLSX),
+        )
+}
+
+// Request bodies sent with `Content-Encoding: gzip` or `br` are inflated before the handlers
+// read them; body size limits apply to the inflated bytes. Other encodings get a 415
+pub fn decompression() -> RequestDecompressionLayer {
+    RequestDecompressionLayer::new().no_deflate().no_zstd()
+}
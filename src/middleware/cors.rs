@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::config::Config;
+
+// Response headers browser code may read besides the CORS-safelisted ones
+const EXPOSED_HEADERS: &str = "x-request-id, deprecation, link";
+
+// Which browser origins may call the API, from CORS_ALLOWED_ORIGINS. Entries are exact
+// origins, `*` for any origin (development) or `https://*.example.com` for subdomains
+pub struct CorsPolicy {
+    origins: Vec<String>,
+    methods: String,
+    headers: String,
+    max_age: u64,
+}
+
+impl CorsPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            origins: config.cors_allowed_origins.clone(),
+            methods: config.cors_allowed_methods.join(", "),
+            headers: config.cors_allowed_headers.join(", "),
+            max_age: config.cors_max_age,
+        }
+    }
+
+    fn allows_any(&self) -> bool {
+        self.origins.iter().any(|allowed| allowed == "*")
+    }
+
+    fn allows(&self, origin: &str) -> bool {
+        self.origins.iter().any(|allowed| {
+            if allowed == "*" || allowed.eq_ignore_ascii_case(origin) {
+                return true;
+            }
+            // "https://*.example.com" matches "https://api.example.com" but not "https://example.com"
+            match allowed.split_once("*.") {
+                Some((scheme, domain)) => origin
+                    .strip_prefix(scheme)
+                    .and_then(|host| host.strip_suffix(domain))
+                    .is_some_and(|sub| sub.ends_with('.') && sub.len() > 1),
+                None => false,
+            }
+        })
+    }
+
+    fn apply(&self, headers: &mut HeaderMap, origin: &HeaderValue) {
+        if self.allows_any() {
+            headers.insert(
+                header::ACCESS_CONTROL_ALLOW_ORIGIN,
+                HeaderValue::from_static("*"),
+            );
+        } else {
+            headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, origin.clone());
+            headers.append(header::VARY, HeaderValue::from_static("origin"));
+        }
+        headers.insert(
+            header::ACCESS_CONTROL_EXPOSE_HEADERS,
+            HeaderValue::from_static(EXPOSED_HEADERS),
+        );
+    }
+}
+
+// Answer preflight requests and add CORS headers for allowed origins; requests from other
+// origins get no CORS headers, so browsers keep their responses from page scripts
+pub async fn cors(State(policy): State<Arc<CorsPolicy>>, request: Request, next: Next) -> Response {
+    let Some(origin) = request
+        .headers()
+        .get(header::ORIGIN)
+        .filter(|origin| origin.to_str().is_ok_and(|origin| policy.allows(origin)))
+        .cloned()
+    else {
+        return next.run(request).await;
+    };
+
+    let preflight = request.method() == Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+    if preflight {
+        let mut response = Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body(Body::empty())
+            .unwrap();
+        let headers = response.headers_mut();
+        policy.apply(headers, &origin);
+        for (name, value) in [
+            (header::ACCESS_CONTROL_ALLOW_METHODS, &policy.methods),
+            (header::ACCESS_CONTROL_ALLOW_HEADERS, &policy.headers),
+            (header::ACCESS_CONTROL_MAX_AGE, &policy.max_age.to_string()),
+        ] {
+            if let Ok(value) = HeaderValue::from_str(value) {
+                headers.insert(name, value);
+            }
+        }
+        return response;
+    }
+
+    let mut response = next.run(request).await;
+    policy.apply(response.headers_mut(), &origin);
+    response
+}
@@ -0,0 +1,27 @@
+use axum::{
+    extract::{OriginalUri, Request},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::routes::CURRENT_PREFIX;
+
+// Marks responses of unversioned aliases deprecated and links the versioned path
+pub async fn deprecated(OriginalUri(uri): OriginalUri, request: Request, next: Next) -> Response {
+    let path = uri.path();
+    let successor = format!(
+        "{}{}",
+        CURRENT_PREFIX,
+        path.strip_prefix("/api").unwrap_or(path)
+    );
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&format!("<{}>; rel=\"successor-version\"", successor))
+    {
+        headers.insert("link", link);
+    }
+    response
+}
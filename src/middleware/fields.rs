@@ -0,0 +1,85 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::Request,
+    http::header,
+    middleware::Next,
+    response::Response,
+};
+use serde_json::Value;
+use tracing::error;
+
+// The comma-separated field names of `?fields=`, if the client sent any
+fn requested_fields(request: &Request) -> Option<Vec<String>> {
+    let query = request.uri().query()?;
+    let fields: Vec<String> = url::form_urlencoded::parse(query.as_bytes())
+        .filter(|(key, _)| key == "fields")
+        .flat_map(|(_, value)| {
+            value
+                .split(',')
+                .map(|field| field.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|field| !field.is_empty())
+        .collect();
+    (!fields.is_empty()).then_some(fields)
+}
+
+fn keep_fields(value: Value, fields: &[String]) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(key, _)| fields.contains(key))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(|item| keep_fields(item, fields))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+// `?fields=user_id,email` trims the resource(s) under `data` of a successful JSON response to
+// the listed top-level fields, whatever the handler. The envelope (success, count, pagination,
+// next_cursor) is left alone, as are error responses; unknown names are ignored. Field names are
+// the API's snake_case ones, this runs before the legacy camelCase rewrite
+pub async fn sparse_fields(request: Request, next: Next) -> Response {
+    let fields = requested_fields(&request);
+    let response = next.run(request).await;
+
+    let Some(fields) = fields else {
+        return response;
+    };
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !response.status().is_success() || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Failed to buffer response for sparse fields: {}", e);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut envelope)) => {
+            if let Some(data) = envelope.get_mut("data") {
+                *data = keep_fields(data.take(), &fields);
+            }
+            serde_json::to_vec(&envelope).unwrap_or_else(|_| bytes.to_vec())
+        }
+        _ => bytes.to_vec(),
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(body))
+}
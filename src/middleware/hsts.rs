@@ -0,0 +1,17 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+// Tell browsers to only use HTTPS for `max_age` seconds; added when the server terminates TLS
+pub async fn hsts(State(max_age): State<u64>, request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    if let Ok(value) = HeaderValue::from_str(&format!("max-age={}; includeSubDomains", max_age)) {
+        response
+            .headers_mut()
+            .insert(header::STRICT_TRANSPORT_SECURITY, value);
+    }
+    response
+}
@@ -0,0 +1,135 @@
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+use tracing::error;
+
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, ErrorCode};
+use crate::middleware::auth::AuthUser;
+use crate::models::{Claim, IdempotencyKey};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+// Set on responses replayed from a previous request with the same key
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+// Largest request body accepted on idempotent routes
+const MAX_BODY: usize = 1024 * 1024;
+
+fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, AppError> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    match value.to_str().map(str::trim) {
+        Ok(key) if !key.is_empty() && key.len() <= 255 => Ok(Some(key.to_string())),
+        _ => Err(AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "Idempotency-Key must be 1 to 255 visible ASCII characters".to_string(),
+        )),
+    }
+}
+
+fn request_hash(request: &Request, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(request.method().as_str());
+    hasher.update(b" ");
+    hasher.update(
+        request
+            .uri()
+            .path_and_query()
+            .map_or("", |path| path.as_str()),
+    );
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn replayed(status_code: u16, body: Vec<u8>) -> Response {
+    let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
+    (
+        status,
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            ),
+            (
+                header::HeaderName::from_static(IDEMPOTENT_REPLAYED_HEADER),
+                HeaderValue::from_static("true"),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+// Route layer for endpoints that must not run twice for one client action (booking, payment,
+// spending miles). A request with an `Idempotency-Key` header runs once per caller and key; a
+// retry with the same key and the same request gets the stored response, one with a different
+// request is rejected, and one sent while the first is still running gets a conflict. Server
+// errors are not stored, so the retry runs again. Requests without the header are not affected
+pub async fn idempotent(
+    State(pool): State<DbPool>,
+    State(config): State<Config>,
+    auth: AuthUser,
+    request: Request,
+    next: Next,
+) -> Result<Response, AppError> {
+    let Some(key) = idempotency_key(request.headers())? else {
+        return Ok(next.run(request).await);
+    };
+
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_BODY).await.map_err(|_| {
+        AppError::ValidationError(
+            ErrorCode::ValidationFailed,
+            "Request body is too large".to_string(),
+        )
+    })?;
+    let request = Request::from_parts(parts, Body::from(bytes.clone()));
+    let hash = request_hash(&request, &bytes);
+
+    match IdempotencyKey::claim(&pool, auth.user_id, &key, &hash, config.idempotency_key_ttl)
+        .await?
+    {
+        Claim::Acquired => {}
+        Claim::Replay { status_code, body } => return Ok(replayed(status_code, body)),
+        Claim::InProgress => {
+            return Err(AppError::ConflictError(
+                ErrorCode::IdempotencyKeyInProgress,
+                "A request with this Idempotency-Key is still in progress".to_string(),
+            ))
+        }
+        Claim::Mismatch => {
+            return Err(AppError::ValidationError(
+                ErrorCode::IdempotencyKeyReused,
+                "This Idempotency-Key was used for a different request".to_string(),
+            ))
+        }
+    }
+
+    let response = next.run(request).await;
+    if response.status().is_server_error() {
+        IdempotencyKey::release(&pool, auth.user_id, &key).await?;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("Failed to buffer response for idempotency key: {}", e);
+            IdempotencyKey::release(&pool, auth.user_id, &key).await?;
+            return Ok(Response::from_parts(parts, Body::empty()));
+        }
+    };
+    IdempotencyKey::complete(&pool, auth.user_id, &key, parts.status.as_u16(), &bytes).await?;
+
+    Ok(Response::from_parts(parts, Body::from(bytes)))
+}
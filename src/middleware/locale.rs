@@ -0,0 +1,96 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{header, request::Parts, HeaderMap, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::i18n::Locale;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+// Languages from the `Accept-Language` header, most preferred first, always ending
+// with the default locale so lookups have something to fall back to
+#[derive(Debug, Clone)]
+pub struct PreferredLocales(pub Vec<String>);
+
+impl PreferredLocales {
+    pub fn parse(header_value: &str) -> Self {
+        let mut weighted: Vec<(String, f32)> = header_value
+            .split(',')
+            .filter_map(|part| {
+                let mut pieces = part.trim().split(';');
+                let tag = pieces.next()?.trim();
+                if tag.is_empty() || tag == "*" {
+                    return None;
+                }
+                let quality = pieces
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse().ok())
+                    .unwrap_or(1.0);
+                // Only the primary language matters for our catalogs: "uk-UA" -> "uk"
+                let language = tag.split('-').next()?.to_lowercase();
+                Some((language, quality))
+            })
+            .collect();
+        weighted.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        let mut locales: Vec<String> = Vec::new();
+        for (language, _) in weighted {
+            if !locales.contains(&language) {
+                locales.push(language);
+            }
+        }
+        if !locales.iter().any(|l| l == DEFAULT_LOCALE) {
+            locales.push(DEFAULT_LOCALE.to_string());
+        }
+        Self(locales)
+    }
+
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let value = headers
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("");
+        Self::parse(value)
+    }
+}
+
+impl<S> FromRequestParts<S> for PreferredLocales
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_headers(&parts.headers))
+    }
+}
+
+tokio::task_local! {
+    static LOCALE: Locale;
+}
+
+// Language of the request being handled, for error bodies built without access to it
+pub fn current() -> Locale {
+    LOCALE.try_with(|locale| *locale).unwrap_or_default()
+}
+
+// Settle the language of the API's own messages from Accept-Language for the whole request,
+// and name it in Content-Language on error responses, which are the ones it changes
+pub async fn locale(request: Request, next: Next) -> Response {
+    let locale = Locale::negotiate(&PreferredLocales::from_headers(request.headers()));
+    let mut response = LOCALE.scope(locale, next.run(request)).await;
+    if response.status().is_client_error() || response.status().is_server_error() {
+        response.headers_mut().insert(
+            header::CONTENT_LANGUAGE,
+            HeaderValue::from_static(locale.as_str()),
+        );
+        response
+            .headers_mut()
+            .append(header::VARY, HeaderValue::from_static("accept-language"));
+    }
+    response
+}
@@ -0,0 +1,14 @@
+pub mod api_key;
+pub mod auth;
+pub mod body;
+pub mod client_info;
+pub mod compat;
+pub mod compression;
+pub mod cors;
+pub mod deprecation;
+pub mod fields;
+pub mod guard;
+pub mod hsts;
+pub mod idempotency;
+pub mod locale;
+pub mod request_id;
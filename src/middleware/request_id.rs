@@ -0,0 +1,78 @@
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use tracing::{info_span, Instrument};
+use uuid::Uuid;
+
+static REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+static CORRELATION_ID_HEADER: HeaderName = HeaderName::from_static("x-correlation-id");
+
+// Longest caller-supplied id that is accepted instead of generating one
+const MAX_ID_LEN: usize = 128;
+
+// The request being handled, as seen by code that has no access to it
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub id: String,
+    pub method: String,
+    pub path: String,
+}
+
+tokio::task_local! {
+    static REQUEST: RequestContext;
+}
+
+// Id of the request being handled, for error bodies and log lines outside its span
+pub fn current() -> Option<String> {
+    REQUEST.try_with(|context| context.id.clone()).ok()
+}
+
+pub fn context() -> Option<RequestContext> {
+    REQUEST.try_with(RequestContext::clone).ok()
+}
+
+fn incoming_id(request: &Request) -> Option<String> {
+    [&REQUEST_ID_HEADER, &CORRELATION_ID_HEADER]
+        .into_iter()
+        .filter_map(|name| request.headers().get(name))
+        .filter_map(|value| value.to_str().ok())
+        .map(str::trim)
+        .find(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_ID_LEN
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_.:".contains(c))
+        })
+        .map(str::to_string)
+}
+
+// Tag every request with an id, taken from the caller's X-Request-Id or X-Correlation-Id
+// when present, run it inside a span carrying the id and echo the id in X-Request-Id
+pub async fn request_id(request: Request, next: Next) -> Response {
+    let context = RequestContext {
+        id: incoming_id(&request).unwrap_or_else(|| Uuid::new_v4().to_string()),
+        method: request.method().to_string(),
+        path: request.uri().path().to_string(),
+    };
+    let id = context.id.clone();
+    let span = info_span!(
+        "request",
+        request_id = %context.id,
+        method = %context.method,
+        path = %context.path,
+    );
+
+    let mut response = REQUEST
+        .scope(context, next.run(request).instrument(span))
+        .await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER.clone(), value);
+    }
+    response
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::db::{self, DbPool};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Aircraft {
+    pub aircraft_id: i32,
+    pub model: String,
+    pub capacity: i32,
+    // Seat layout new flights of the aircraft are given
+    pub cabin_layout_id: Option<i32>,
+    // Pets carried per flight in the cabin and in the hold; 0 where the aircraft takes none
+    pub pets_in_cabin: i32,
+    pub pets_in_hold: i32,
+    // Of a pet in the cabin with its carrier
+    pub pet_cabin_max_weight_kg: f64,
+    // Freight the aircraft carries on a flight
+    pub cargo_payload_kg: f64,
+    pub cargo_volume_m3: f64,
+}
+
+// Limits an aircraft sets on pets
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct PetLimits {
+    #[validate(range(min = 0, max = 10))]
+    pub pets_in_cabin: i32,
+    #[validate(range(min = 0, max = 10))]
+    pub pets_in_hold: i32,
+    #[validate(range(min = 0.0, max = 20.0))]
+    pub pet_cabin_max_weight_kg: f64,
+}
+
+// Freight limits of an aircraft
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct CargoLimits {
+    #[validate(range(min = 0.0, max = 150000.0))]
+    pub cargo_payload_kg: f64,
+    #[validate(range(min = 0.0, max = 1000.0))]
+    pub cargo_volume_m3: f64,
+}
+
+impl Aircraft {
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM aircraft WHERE aircraft_id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn insert(pool: &DbPool, model: &str, capacity: i32) -> Result<i32, sqlx::Error> {
+        let result = sqlx::query("INSERT INTO aircraft (model, capacity) VALUES (?, ?)")
+            .bind(model)
+            .bind(capacity)
+            .execute(pool)
+            .await?;
+
+        Ok(db::last_insert_id(&result) as i32)
+    }
+
+    pub async fn set_pet_limits(
+        pool: &DbPool,
+        aircraft_id: i32,
+        limits: &PetLimits,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE aircraft
+            SET pets_in_cabin = ?, pets_in_hold = ?, pet_cabin_max_weight_kg = ?
+            WHERE aircraft_id = ?
+            "#,
+        )
+        .bind(limits.pets_in_cabin)
+        .bind(limits.pets_in_hold)
+        .bind(limits.pet_cabin_max_weight_kg)
+        .bind(aircraft_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_cargo_limits(
+        pool: &DbPool,
+        aircraft_id: i32,
+        limits: &CargoLimits,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE aircraft SET cargo_payload_kg = ?, cargo_volume_m3 = ? WHERE aircraft_id = ?",
+        )
+        .bind(limits.cargo_payload_kg)
+        .bind(limits.cargo_volume_m3)
+        .bind(aircraft_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,317 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::currencies::BASE_CURRENCY;
+use crate::db::{self, DbPool};
+use crate::validation::{currency_code, not_blank};
+
+// Extra sold on top of a ticket, such as priority boarding, extra legroom or lounge access
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AncillaryProduct {
+    pub product_id: i32,
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub price: f64,
+    pub currency: String,
+    // Inactive products stay on the tickets that bought them but are no longer sold
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// Product to add to the catalog, or what to replace one with
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewAncillaryProduct {
+    #[validate(custom(function = "not_blank"), length(max = 32))]
+    pub code: String,
+    #[validate(custom(function = "not_blank"), length(max = 100))]
+    pub name: String,
+    #[validate(length(max = 1000))]
+    pub description: Option<String>,
+    #[validate(range(min = 0.0))]
+    pub price: f64,
+    #[serde(default = "base_currency")]
+    #[validate(custom(function = "currency_code"))]
+    pub currency: String,
+    #[serde(default = "active")]
+    pub active: bool,
+}
+
+fn base_currency() -> String {
+    BASE_CURRENCY.to_string()
+}
+
+fn active() -> bool {
+    true
+}
+
+// A product sold on a flight and how many of it were bought
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FlightAncillary {
+    pub product_id: i32,
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub price: f64,
+    pub currency: String,
+    // None when unlimited
+    pub capacity: Option<i32>,
+    pub sold: i64,
+}
+
+// A product to sell on a flight
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct FlightAncillaryOffer {
+    pub product_id: i32,
+    // Omit for unlimited
+    #[validate(range(min = 0))]
+    pub capacity: Option<i32>,
+}
+
+// A product bought for a ticket, at the price it was bought for
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TicketAncillary {
+    pub ticket_ancillary_id: i32,
+    pub ticket_id: i32,
+    pub product_id: i32,
+    pub code: String,
+    pub name: String,
+    pub price: f64,
+    pub currency: String,
+    pub purchased_by: Option<i32>,
+    pub purchased_at: DateTime<Utc>,
+}
+
+// Outcome of buying a product for a ticket
+pub enum AncillaryPurchase {
+    Purchased(TicketAncillary),
+    // The flight does not sell the product
+    NotOffered,
+    // Every unit the flight sells was bought
+    SoldOut,
+}
+
+// A bought product with its catalog code and name
+const TICKET_ANCILLARY_ROWS: &str = r#"
+    SELECT ta.ticket_ancillary_id, ta.ticket_id, ta.product_id, p.code, p.name, ta.price,
+           ta.currency, ta.purchased_by, ta.purchased_at
+    FROM ticket_ancillaries ta
+    JOIN ancillary_products p ON p.product_id = ta.product_id
+"#;
+
+impl AncillaryProduct {
+    pub async fn find_all(pool: &DbPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM ancillary_products ORDER BY code")
+            .fetch_all(pool)
+            .await
+    }
+
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM ancillary_products WHERE product_id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn create(pool: &DbPool, product: &NewAncillaryProduct) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO ancillary_products
+                (code, name, description, price, currency, active, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(&product.code)
+        .bind(product.name.trim())
+        .bind(&product.description)
+        .bind(product.price)
+        .bind(&product.currency)
+        .bind(product.active)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM ancillary_products WHERE product_id = ?")
+            .bind(db::last_insert_id(&result) as i32)
+            .fetch_one(pool)
+            .await
+    }
+
+    // Replace a product; None if there is none with this id. Tickets keep what they paid
+    pub async fn update(
+        pool: &DbPool,
+        id: i32,
+        product: &NewAncillaryProduct,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE ancillary_products
+            SET code = ?, name = ?, description = ?, price = ?, currency = ?, active = ?
+            WHERE product_id = ?
+            "#,
+        )
+        .bind(&product.code)
+        .bind(product.name.trim())
+        .bind(&product.description)
+        .bind(product.price)
+        .bind(&product.currency)
+        .bind(product.active)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+        Self::find_by_id(pool, id).await
+    }
+}
+
+impl FlightAncillary {
+    // Active products sold on the flight
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT p.product_id, p.code, p.name, p.description, p.price, p.currency, fa.capacity,
+                   (SELECT COUNT(*) FROM ticket_ancillaries ta
+                    JOIN tickets t ON t.ticket_id = ta.ticket_id
+                    WHERE t.flight_id = fa.flight_id AND ta.product_id = fa.product_id) AS sold
+            FROM flight_ancillaries fa
+            JOIN ancillary_products p ON p.product_id = fa.product_id
+            WHERE fa.flight_id = ? AND p.active = TRUE
+            ORDER BY p.code
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Replace the products sold on a flight in one transaction. Tickets keep what they bought
+    pub async fn replace_for_flight(
+        pool: &DbPool,
+        flight_id: i32,
+        offers: &[FlightAncillaryOffer],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM flight_ancillaries WHERE flight_id = ?")
+            .bind(flight_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for offer in offers {
+            sqlx::query(
+                "INSERT INTO flight_ancillaries (flight_id, product_id, capacity) VALUES (?, ?, ?)",
+            )
+            .bind(flight_id)
+            .bind(offer.product_id)
+            .bind(offer.capacity)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+}
+
+impl TicketAncillary {
+    pub async fn find_by_ticket(pool: &DbPool, ticket_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(&format!(
+            "{} WHERE ta.ticket_id = ? ORDER BY ta.ticket_ancillary_id",
+            TICKET_ANCILLARY_ROWS
+        ))
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Products bought for the tickets of a booking group, for its total
+    pub async fn find_by_group(pool: &DbPool, group_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(&format!(
+            r#"
+            {}
+            JOIN tickets t ON t.ticket_id = ta.ticket_id
+            WHERE t.group_id = ?
+            ORDER BY ta.ticket_id, ta.ticket_ancillary_id
+            "#,
+            TICKET_ANCILLARY_ROWS
+        ))
+        .bind(group_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Buy one of the flight's products for a ticket. The flight's offer is locked while units
+    // are counted, so concurrent purchases cannot sell past its capacity; buying the same product
+    // twice fails on the unique ticket and product key
+    pub async fn purchase(
+        pool: &DbPool,
+        ticket_id: i32,
+        flight_id: i32,
+        product_id: i32,
+        price: (f64, &str),
+        purchased_by: i32,
+    ) -> Result<AncillaryPurchase, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let capacity: Option<(Option<i32>,)> = sqlx::query_as(
+            r#"
+            SELECT capacity FROM flight_ancillaries
+            WHERE flight_id = ? AND product_id = ?
+            FOR UPDATE
+            "#,
+        )
+        .bind(flight_id)
+        .bind(product_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((capacity,)) = capacity else {
+            tx.rollback().await?;
+            return Ok(AncillaryPurchase::NotOffered);
+        };
+
+        if let Some(capacity) = capacity {
+            let (sold,): (i64,) = sqlx::query_as(
+                r#"
+                SELECT COUNT(*)
+                FROM ticket_ancillaries ta
+                JOIN tickets t ON t.ticket_id = ta.ticket_id
+                WHERE t.flight_id = ? AND ta.product_id = ?
+                "#,
+            )
+            .bind(flight_id)
+            .bind(product_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            if sold >= i64::from(capacity) {
+                tx.rollback().await?;
+                return Ok(AncillaryPurchase::SoldOut);
+            }
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO ticket_ancillaries
+                (ticket_id, product_id, price, currency, purchased_by, purchased_at)
+            VALUES (?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(product_id)
+        .bind(price.0)
+        .bind(price.1)
+        .bind(purchased_by)
+        .execute(&mut *tx)
+        .await?;
+
+        let purchased = sqlx::query_as::<_, Self>(&format!(
+            "{} WHERE ta.ticket_ancillary_id = ?",
+            TICKET_ANCILLARY_ROWS
+        ))
+        .bind(db::last_insert_id(&result) as i32)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(AncillaryPurchase::Purchased(purchased))
+    }
+}
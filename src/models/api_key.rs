@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow};
+use uuid::Uuid;
+
+use crate::db::{self, DbPool};
+
+// What a partner key may read; every scope grants read-only access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ApiScope {
+    #[serde(rename = "flights:read")]
+    Flights,
+    #[serde(rename = "manifests:read")]
+    Manifests,
+    #[serde(rename = "crew:read")]
+    Crew,
+}
+
+// Machine credential of a partner such as a travel agency; only the key's SHA-256 hash is stored
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ApiKey {
+    pub key_id: i32,
+    pub name: String,
+    // First characters of the key, enough to tell keys apart in listings
+    pub key_prefix: String,
+    pub scopes: Json<Vec<ApiScope>>,
+    pub created_by: i32,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+const KEY_PREFIX_LEN: usize = 11;
+
+impl ApiKey {
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        self.scopes.contains(&scope)
+    }
+
+    pub async fn find_all(pool: &DbPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT key_id, name, key_prefix, scopes, created_by, created_at, last_used_at, revoked_at
+            FROM api_keys
+            ORDER BY created_at DESC
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    // Store a new key and return it together with the cleartext key, which is not kept
+    pub async fn create(
+        pool: &DbPool,
+        name: &str,
+        scopes: &[ApiScope],
+        created_by: i32,
+    ) -> Result<(Self, String), sqlx::Error> {
+        let key = format!("ak_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO api_keys (name, key_prefix, key_hash, scopes, created_by, created_at)
+            VALUES (?, ?, SHA2(?, 256), ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(name)
+        .bind(&key[..KEY_PREFIX_LEN])
+        .bind(&key)
+        .bind(Json(scopes))
+        .bind(created_by)
+        .execute(pool)
+        .await?;
+
+        let api_key = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT key_id, name, key_prefix, scopes, created_by, created_at, last_used_at, revoked_at
+            FROM api_keys
+            WHERE key_id = ?
+            "#,
+        )
+        .bind(db::last_insert_id(&result) as i32)
+        .fetch_one(pool)
+        .await?;
+
+        Ok((api_key, key))
+    }
+
+    pub async fn revoke(pool: &DbPool, key_id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE api_keys SET revoked_at = UTC_TIMESTAMP() WHERE key_id = ? AND revoked_at IS NULL",
+        )
+        .bind(key_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Look up an active key by its cleartext value and record the use
+    pub async fn authenticate(pool: &DbPool, key: &str) -> Result<Option<Self>, sqlx::Error> {
+        let api_key = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT key_id, name, key_prefix, scopes, created_by, created_at, last_used_at, revoked_at
+            FROM api_keys
+            WHERE key_hash = SHA2(?, 256) AND revoked_at IS NULL
+            "#,
+        )
+        .bind(key)
+        .fetch_optional(pool)
+        .await?;
+
+        if let Some(api_key) = &api_key {
+            sqlx::query("UPDATE api_keys SET last_used_at = UTC_TIMESTAMP() WHERE key_id = ?")
+                .bind(api_key.key_id)
+                .execute(pool)
+                .await?;
+        }
+
+        Ok(api_key)
+    }
+}
@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::db::{self, DbPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AssistanceType {
+    Wheelchair,
+    UnaccompaniedMinor,
+    Medical,
+}
+
+impl AssistanceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AssistanceType::Wheelchair => "wheelchair",
+            AssistanceType::UnaccompaniedMinor => "unaccompanied_minor",
+            AssistanceType::Medical => "medical",
+        }
+    }
+}
+
+// Help a passenger needs on the flight of a ticket; unacknowledged until staff confirm they
+// can provide it
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AssistanceRequest {
+    pub assistance_id: i32,
+    pub ticket_id: i32,
+    pub assistance_type: AssistanceType,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub acknowledged_by: Option<i32>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+}
+
+// Assistance to request
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewAssistanceRequest {
+    pub assistance_type: AssistanceType,
+    // What staff should know, e.g. `cannot climb stairs` or the minor's guardian at arrival
+    #[validate(length(max = 500))]
+    pub details: Option<String>,
+}
+
+impl AssistanceRequest {
+    pub fn acknowledged(&self) -> bool {
+        self.acknowledged_at.is_some()
+    }
+
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM assistance_requests WHERE assistance_id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_by_ticket(pool: &DbPool, ticket_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM assistance_requests WHERE ticket_id = ? ORDER BY assistance_id",
+        )
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Requests of every ticket of the flight, for its manifest
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT a.*
+            FROM assistance_requests a
+            JOIN tickets t ON t.ticket_id = a.ticket_id
+            WHERE t.flight_id = ?
+            ORDER BY a.assistance_id
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &DbPool,
+        ticket_id: i32,
+        request: &NewAssistanceRequest,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO assistance_requests (ticket_id, assistance_type, details, created_at)
+            VALUES (?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(request.assistance_type)
+        .bind(request.details.as_deref().map(str::trim))
+        .execute(pool)
+        .await?;
+
+        Self::find_by_id(pool, db::last_insert_id(&result) as i32)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    // Record that staff can provide the assistance; false if it was already acknowledged
+    pub async fn acknowledge(pool: &DbPool, id: i32, actor_id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE assistance_requests
+            SET acknowledged_by = ?, acknowledged_at = UTC_TIMESTAMP()
+            WHERE assistance_id = ? AND acknowledged_at IS NULL
+            "#,
+        )
+        .bind(actor_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
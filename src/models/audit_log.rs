@@ -0,0 +1,142 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{types::Json, FromRow};
+use tracing::error;
+
+use crate::db::DbPool;
+use crate::middleware::request_id;
+
+// Who did what to which record; written by handlers after sensitive changes succeed
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditLog {
+    pub audit_id: i64,
+    // None for changes made without a signed-in user, e.g. a password reset link
+    pub actor_id: Option<i32>,
+    // `<entity>.<verb>`, e.g. `flight.status_changed`
+    pub action: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub details: Option<Json<Value>>,
+    pub request_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Query parameters of the audit log listing; every filter is optional
+#[derive(Debug, Deserialize)]
+pub struct AuditLogFilter {
+    pub actor_id: Option<i32>,
+    pub action: Option<String>,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+}
+
+const FILTER: &str = r#"
+    WHERE (? IS NULL OR actor_id = ?)
+      AND (? IS NULL OR action = ?)
+      AND (? IS NULL OR entity_type = ?)
+      AND (? IS NULL OR entity_id = ?)
+      AND (? IS NULL OR created_at >= ?)
+      AND (? IS NULL OR created_at < ?)
+"#;
+
+macro_rules! bind_filter {
+    ($query:expr, $filter:expr) => {
+        $query
+            .bind($filter.actor_id)
+            .bind($filter.actor_id)
+            .bind(&$filter.action)
+            .bind(&$filter.action)
+            .bind(&$filter.entity_type)
+            .bind(&$filter.entity_type)
+            .bind(&$filter.entity_id)
+            .bind(&$filter.entity_id)
+            .bind($filter.from)
+            .bind($filter.from)
+            .bind($filter.to)
+            .bind($filter.to)
+    };
+}
+
+impl AuditLog {
+    // Append an entry. A failure is logged rather than returned: the audited change already happened
+    pub async fn record(
+        pool: &DbPool,
+        actor_id: Option<i32>,
+        action: &str,
+        entity_type: &str,
+        entity_id: impl ToString,
+        details: Value,
+    ) {
+        let entity_id = entity_id.to_string();
+        let details = (!details.is_null()).then_some(Json(details));
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO audit_logs (actor_id, action, entity_type, entity_id, details, request_id, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(actor_id)
+        .bind(action)
+        .bind(entity_type)
+        .bind(&entity_id)
+        .bind(details)
+        .bind(request_id::current())
+        .execute(pool)
+        .await;
+
+        if let Err(e) = result {
+            error!(
+                "Failed to write audit log {} on {} {}: {}",
+                action, entity_type, entity_id, e
+            );
+        }
+    }
+
+    pub async fn find(
+        pool: &DbPool,
+        filter: &AuditLogFilter,
+        page: i32,
+        limit: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let offset = (page - 1) * limit;
+        let sql = format!(
+            "SELECT * FROM audit_logs {} ORDER BY audit_id DESC LIMIT ? OFFSET ?",
+            FILTER
+        );
+
+        bind_filter!(sqlx::query_as::<_, Self>(&sql), filter)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+    }
+
+    // Entries the user made or that are about their account, oldest first
+    pub async fn find_about_user(pool: &DbPool, user_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM audit_logs
+            WHERE actor_id = ? OR (entity_type = 'user' AND entity_id = ?)
+            ORDER BY audit_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(user_id.to_string())
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn count(pool: &DbPool, filter: &AuditLogFilter) -> Result<i64, sqlx::Error> {
+        let sql = format!("SELECT COUNT(*) FROM audit_logs {}", FILTER);
+
+        bind_filter!(sqlx::query_scalar(&sql), filter)
+            .fetch_one(pool)
+            .await
+    }
+}
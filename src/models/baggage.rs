@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::db::{self, DbPool};
+
+// Where a checked bag is on its way from the check-in desk to the passenger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BaggageStatus {
+    Checked,
+    Loaded,
+    Unloaded,
+    Delivered,
+}
+
+impl BaggageStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BaggageStatus::Checked => "checked",
+            BaggageStatus::Loaded => "loaded",
+            BaggageStatus::Unloaded => "unloaded",
+            BaggageStatus::Delivered => "delivered",
+        }
+    }
+
+    // Bags move one step at a time; a loaded bag taken off again before departure goes back to
+    // checked
+    pub fn can_scan_to(&self, next: BaggageStatus) -> bool {
+        use BaggageStatus::*;
+
+        matches!(
+            (self, next),
+            (Checked, Loaded) | (Loaded, Checked | Unloaded) | (Unloaded, Delivered)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Bag {
+    pub bag_id: i32,
+    pub ticket_id: i32,
+    pub tag_number: String,
+    pub weight_kg: f64,
+    pub status: BaggageStatus,
+    // Charged at check-in for a bag over the ticket's allowance
+    pub excess_fee: f64,
+    pub excess_fee_currency: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// One scan of a bag's tag
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BagScan {
+    pub scan_id: i32,
+    pub bag_id: i32,
+    pub status: BaggageStatus,
+    pub location: Option<String>,
+    pub scanned_by: Option<i32>,
+    pub scanned_at: DateTime<Utc>,
+}
+
+// Bag to check in
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewBag {
+    #[validate(range(exclusive_min = 0.0, max = 50.0))]
+    pub weight_kg: f64,
+    #[validate(length(max = 50))]
+    pub location: Option<String>,
+}
+
+impl Bag {
+    pub async fn find_by_tag(pool: &DbPool, tag_number: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM baggage WHERE tag_number = ?")
+            .bind(tag_number)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_by_ticket(pool: &DbPool, ticket_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM baggage WHERE ticket_id = ? ORDER BY bag_id")
+            .bind(ticket_id)
+            .fetch_all(pool)
+            .await
+    }
+
+    // Check a bag in under a new tag number, recording the check-in as its first scan
+    pub async fn check_in(
+        pool: &DbPool,
+        ticket_id: i32,
+        bag: &NewBag,
+        excess_fee: (f64, &str),
+        actor_id: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let tag_number = format!("{:010}", rand::thread_rng().gen_range(0..10_000_000_000u64));
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO baggage
+                (ticket_id, tag_number, weight_kg, status, excess_fee, excess_fee_currency,
+                 created_at, updated_at)
+            VALUES (?, ?, ?, 'checked', ?, ?, UTC_TIMESTAMP(), UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(&tag_number)
+        .bind(bag.weight_kg)
+        .bind(excess_fee.0)
+        .bind(excess_fee.1)
+        .execute(&mut *tx)
+        .await?;
+        let bag_id = db::last_insert_id(&result) as i32;
+
+        sqlx::query(
+            r#"
+            INSERT INTO baggage_scans (bag_id, status, location, scanned_by, scanned_at)
+            VALUES (?, 'checked', ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(bag_id)
+        .bind(&bag.location)
+        .bind(actor_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Self::find_by_tag(pool, &tag_number)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    // Compare-and-set from `from` to `to` with a scan; false if the bag was scanned concurrently
+    pub async fn scan(
+        pool: &DbPool,
+        bag_id: i32,
+        from: BaggageStatus,
+        to: BaggageStatus,
+        location: Option<&str>,
+        actor_id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE baggage SET status = ?, updated_at = UTC_TIMESTAMP() WHERE bag_id = ? AND status = ?",
+        )
+        .bind(to)
+        .bind(bag_id)
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO baggage_scans (bag_id, status, location, scanned_by, scanned_at)
+            VALUES (?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(bag_id)
+        .bind(to)
+        .bind(location)
+        .bind(actor_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+}
+
+impl BagScan {
+    // Scans of every bag of the ticket, oldest first
+    pub async fn find_by_ticket(pool: &DbPool, ticket_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT s.*
+            FROM baggage_scans s
+            JOIN baggage b ON b.bag_id = s.bag_id
+            WHERE b.ticket_id = ?
+            ORDER BY s.scanned_at, s.scan_id
+            "#,
+        )
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bags_are_scanned_one_step_at_a_time() {
+        use BaggageStatus::*;
+
+        assert!(Checked.can_scan_to(Loaded));
+        assert!(Loaded.can_scan_to(Checked));
+        assert!(Unloaded.can_scan_to(Delivered));
+        assert!(!Checked.can_scan_to(Delivered));
+        assert!(!Delivered.can_scan_to(Checked));
+        assert!(!Loaded.can_scan_to(Loaded));
+    }
+}
@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use super::FareClass;
+use crate::currencies::BASE_CURRENCY;
+use crate::db::{self, DbPool};
+use crate::validation::currency_code;
+
+// Checked baggage a fare class includes and what more of it costs; `route_id` is None for the
+// rule of every route
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BaggageAllowance {
+    pub allowance_id: i32,
+    pub fare_class: FareClass,
+    pub route_id: Option<i32>,
+    pub pieces: i32,
+    pub max_weight_kg: f64,
+    pub extra_piece_fee: f64,
+    pub prepaid_piece_fee: f64,
+    pub overweight_fee_per_kg: f64,
+    // ISO 4217 code of the fees
+    pub currency: String,
+}
+
+// Rule to set, replacing the one of the same fare class and route
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewBaggageAllowance {
+    pub fare_class: FareClass,
+    pub route_id: Option<i32>,
+    #[validate(range(min = 0, max = 10))]
+    pub pieces: i32,
+    #[validate(range(exclusive_min = 0.0, max = 50.0))]
+    pub max_weight_kg: f64,
+    #[validate(range(min = 0.0))]
+    pub extra_piece_fee: f64,
+    #[validate(range(min = 0.0))]
+    pub prepaid_piece_fee: f64,
+    #[validate(range(min = 0.0))]
+    pub overweight_fee_per_kg: f64,
+    #[serde(default = "base_currency")]
+    #[validate(custom(function = "currency_code"))]
+    pub currency: String,
+}
+
+fn base_currency() -> String {
+    BASE_CURRENCY.to_string()
+}
+
+// Pieces bought on top of a ticket's allowance
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BaggagePurchase {
+    pub purchase_id: i32,
+    pub ticket_id: i32,
+    pub pieces: i32,
+    pub price: f64,
+    pub currency: String,
+    pub purchased_by: Option<i32>,
+    pub purchased_at: DateTime<Utc>,
+}
+
+impl BaggageAllowance {
+    // What checking in a bag of `weight_kg` costs when the ticket already has `pieces_checked`
+    // of its `pieces_allowed` bags checked: the extra piece fee once they are used up, plus the
+    // overweight fee for each kilogram over the limit. Rounded to cents
+    pub fn excess_fee(&self, pieces_allowed: i64, pieces_checked: i64, weight_kg: f64) -> f64 {
+        let piece = if pieces_checked >= pieces_allowed {
+            self.extra_piece_fee
+        } else {
+            0.0
+        };
+        let overweight = (weight_kg - self.max_weight_kg).max(0.0) * self.overweight_fee_per_kg;
+        ((piece + overweight) * 100.0).round() / 100.0
+    }
+
+    pub async fn find_all(pool: &DbPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM baggage_allowances ORDER BY route_id IS NOT NULL, route_id, fare_class",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    // The rule for the ticket's fare class on its flight's route, else the one of every route
+    pub async fn for_ticket(pool: &DbPool, ticket_id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT a.*
+            FROM tickets t
+            JOIN flights f ON f.flight_id = t.flight_id
+            JOIN baggage_allowances a
+              ON a.fare_class = t.fare_class AND (a.route_id = f.route_id OR a.route_id IS NULL)
+            WHERE t.ticket_id = ?
+            ORDER BY a.route_id IS NULL, a.allowance_id DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(ticket_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn set(pool: &DbPool, rule: &NewBaggageAllowance) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM baggage_allowances
+            WHERE fare_class = ? AND (route_id = ? OR (route_id IS NULL AND ? IS NULL))
+            "#,
+        )
+        .bind(rule.fare_class)
+        .bind(rule.route_id)
+        .bind(rule.route_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO baggage_allowances
+                (fare_class, route_id, pieces, max_weight_kg, extra_piece_fee, prepaid_piece_fee,
+                 overweight_fee_per_kg, currency)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(rule.fare_class)
+        .bind(rule.route_id)
+        .bind(rule.pieces)
+        .bind(rule.max_weight_kg)
+        .bind(rule.extra_piece_fee)
+        .bind(rule.prepaid_piece_fee)
+        .bind(rule.overweight_fee_per_kg)
+        .bind(&rule.currency)
+        .execute(&mut *tx)
+        .await?;
+        let allowance_id = db::last_insert_id(&result) as i32;
+
+        tx.commit().await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM baggage_allowances WHERE allowance_id = ?")
+            .bind(allowance_id)
+            .fetch_one(pool)
+            .await
+    }
+}
+
+impl BaggagePurchase {
+    pub async fn find_by_ticket(pool: &DbPool, ticket_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM baggage_purchases WHERE ticket_id = ? ORDER BY purchase_id",
+        )
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &DbPool,
+        ticket_id: i32,
+        pieces: i32,
+        price: f64,
+        currency: &str,
+        purchased_by: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO baggage_purchases (ticket_id, pieces, price, currency, purchased_by, purchased_at)
+            VALUES (?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(pieces)
+        .bind(price)
+        .bind(currency)
+        .bind(purchased_by)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM baggage_purchases WHERE purchase_id = ?")
+            .bind(db::last_insert_id(&result) as i32)
+            .fetch_one(pool)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn economy() -> BaggageAllowance {
+        BaggageAllowance {
+            allowance_id: 1,
+            fare_class: FareClass::Economy,
+            route_id: None,
+            pieces: 1,
+            max_weight_kg: 23.0,
+            extra_piece_fee: 60.0,
+            prepaid_piece_fee: 45.0,
+            overweight_fee_per_kg: 10.0,
+            currency: "EUR".to_string(),
+        }
+    }
+
+    #[test]
+    fn charges_pieces_over_the_allowance_and_kilograms_over_the_limit() {
+        let rule = economy();
+
+        assert_eq!(rule.excess_fee(1, 0, 20.0), 0.0);
+        assert_eq!(rule.excess_fee(1, 0, 23.0), 0.0);
+        assert_eq!(rule.excess_fee(1, 0, 25.5), 25.0);
+        assert_eq!(rule.excess_fee(1, 1, 20.0), 60.0);
+        assert_eq!(rule.excess_fee(2, 1, 20.0), 0.0);
+        assert_eq!(rule.excess_fee(1, 2, 24.25), 72.5);
+    }
+}
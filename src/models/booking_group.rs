@@ -0,0 +1,323 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::Serialize;
+use sqlx::FromRow;
+
+use super::ticket::new_ticket_number;
+use super::{DomainEvent, FareClass, OutboxEvent, PassengerType, Ticket};
+use crate::db::{self, DbPool};
+
+// Letters and digits of a group reference, without the easily confused 0, O, 1 and I
+const REFERENCE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+// Fresh references tried when the drawn one is already in use
+const REFERENCE_ATTEMPTS: usize = 5;
+
+// Tickets booked together on one flight and fare class under a shared reference
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct BookingGroup {
+    pub group_id: i32,
+    pub reference: String,
+    pub flight_id: i32,
+    pub fare_class: FareClass,
+    pub passenger_count: i32,
+    // Fares before the group discount
+    pub subtotal: f64,
+    pub discount: f64,
+    pub total: f64,
+    pub currency: String,
+    pub booked_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+// A passenger to book, typed by age and priced before the group discount
+#[derive(Debug, Clone)]
+pub struct GroupPassenger {
+    pub user_id: i32,
+    // None to be given the first free seat of the class
+    pub seat_number: Option<String>,
+    pub passenger_type: PassengerType,
+    pub fare: f64,
+}
+
+// Share taken off every fare of a group of at least `min_passengers`. The hook for group deals:
+// anything smarter than a flat percentage goes here
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct GroupDiscount {
+    pub min_passengers: usize,
+    pub percent: f64,
+}
+
+impl GroupDiscount {
+    // Fare a group of `passengers` pays for one of them, rounded to cents
+    pub fn discounted(&self, passengers: usize, fare: f64) -> f64 {
+        let fare = if passengers >= self.min_passengers {
+            fare * (100.0 - self.percent) / 100.0
+        } else {
+            fare
+        };
+        (fare * 100.0).round() / 100.0
+    }
+}
+
+// Outcome of booking a group
+pub enum GroupSeating {
+    Booked(BookingGroup),
+    // The class has fewer sellable seats left than passengers
+    ClassFull { available: i64 },
+    // A requested seat was booked or blocked meanwhile
+    SeatTaken(String),
+    // The user already holds a ticket on the flight
+    AlreadyBooked(i32),
+}
+
+fn new_reference() -> String {
+    let mut rng = rand::thread_rng();
+    (0..6)
+        .map(|_| REFERENCE_CHARS[rng.gen_range(0..REFERENCE_CHARS.len())] as char)
+        .collect()
+}
+
+impl BookingGroup {
+    pub async fn find_by_reference(
+        pool: &DbPool,
+        reference: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM booking_groups WHERE reference = ?")
+            .bind(reference)
+            .fetch_optional(pool)
+            .await
+    }
+
+    // Book every passenger in one transaction or none of them, recording a TicketBooked event
+    // for each ticket. The flight row is locked so
+    // concurrent bookings see each other's tickets when counting seats; seat changes do not take
+    // that lock, so a seat can still turn out taken when its ticket is inserted
+    pub async fn book(
+        pool: &DbPool,
+        flight_id: i32,
+        fare_class: FareClass,
+        passengers: &[GroupPassenger],
+        discount: GroupDiscount,
+        currency: &str,
+        booked_by: i32,
+    ) -> Result<GroupSeating, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let (flight_number,): (String,) =
+            sqlx::query_as("SELECT flight_number FROM flights WHERE flight_id = ? FOR UPDATE")
+                .bind(flight_id)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        let (available,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT CAST(COALESCE(MAX(fc.seat_count), 0) AS SIGNED) - COUNT(t.ticket_id)
+            FROM flight_fare_classes fc
+            LEFT JOIN tickets t ON t.flight_id = fc.flight_id AND t.fare_class = fc.fare_class
+            WHERE fc.flight_id = ? AND fc.fare_class = ?
+            "#,
+        )
+        .bind(flight_id)
+        .bind(fare_class)
+        .fetch_one(&mut *tx)
+        .await?;
+        if available < passengers.len() as i64 {
+            tx.rollback().await?;
+            return Ok(GroupSeating::ClassFull {
+                available: available.max(0),
+            });
+        }
+
+        for passenger in passengers {
+            let booked: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM tickets WHERE flight_id = ? AND user_id = ?)",
+            )
+            .bind(flight_id)
+            .bind(passenger.user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            if booked {
+                tx.rollback().await?;
+                return Ok(GroupSeating::AlreadyBooked(passenger.user_id));
+            }
+        }
+
+        let requested: Vec<&str> = passengers
+            .iter()
+            .filter_map(|passenger| passenger.seat_number.as_deref())
+            .collect();
+        for seat_number in &requested {
+            let (taken,): (i64,) = sqlx::query_as(
+                r#"
+                SELECT (SELECT COUNT(*) FROM tickets WHERE flight_id = ? AND seat_number = ?)
+                     + (SELECT COUNT(*) FROM seat_blocks WHERE flight_id = ? AND seat_number = ?)
+                "#,
+            )
+            .bind(flight_id)
+            .bind(seat_number)
+            .bind(flight_id)
+            .bind(seat_number)
+            .fetch_one(&mut *tx)
+            .await?;
+            if taken > 0 {
+                tx.rollback().await?;
+                return Ok(GroupSeating::SeatTaken(seat_number.to_string()));
+            }
+        }
+
+        // Passengers without a seat get the free seats of the class front to back
+        let free: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT s.seat_number
+            FROM flight_seats s
+            WHERE s.flight_id = ? AND s.fare_class = ?
+              AND NOT EXISTS (
+                  SELECT 1 FROM tickets t
+                  WHERE t.flight_id = s.flight_id AND t.seat_number = s.seat_number
+              )
+              AND NOT EXISTS (
+                  SELECT 1 FROM seat_blocks b
+                  WHERE b.flight_id = s.flight_id AND b.seat_number = s.seat_number
+              )
+            ORDER BY s.seat_row, s.seat_number
+            "#,
+        )
+        .bind(flight_id)
+        .bind(fare_class)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut free = free
+            .into_iter()
+            .filter(|seat| !requested.contains(&seat.as_str()));
+        let mut seats = Vec::with_capacity(passengers.len());
+        for passenger in passengers {
+            match passenger.seat_number.clone().or_else(|| free.next()) {
+                Some(seat) => seats.push(seat),
+                None => {
+                    tx.rollback().await?;
+                    return Ok(GroupSeating::ClassFull {
+                        available: requested.len() as i64 + free.count() as i64,
+                    });
+                }
+            }
+        }
+
+        let fares: Vec<f64> = passengers
+            .iter()
+            .map(|passenger| discount.discounted(passengers.len(), passenger.fare))
+            .collect();
+        let subtotal: f64 = passengers.iter().map(|passenger| passenger.fare).sum();
+        let total: f64 = fares.iter().sum();
+
+        // A failed statement leaves the MySQL transaction open, so a taken reference is simply
+        // drawn again
+        let mut attempts = 1;
+        let group_id = loop {
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO booking_groups
+                    (reference, flight_id, fare_class, passenger_count, subtotal, discount, total,
+                     currency, booked_by, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+                "#,
+            )
+            .bind(new_reference())
+            .bind(flight_id)
+            .bind(fare_class)
+            .bind(passengers.len() as i32)
+            .bind((subtotal * 100.0).round() / 100.0)
+            .bind(((subtotal - total) * 100.0).round() / 100.0)
+            .bind((total * 100.0).round() / 100.0)
+            .bind(currency)
+            .bind(booked_by)
+            .execute(&mut *tx)
+            .await;
+            match inserted {
+                Ok(result) => break db::last_insert_id(&result) as i32,
+                Err(e)
+                    if attempts < REFERENCE_ATTEMPTS
+                        && db::is_duplicate_key(&e, "uq_booking_groups_reference") =>
+                {
+                    attempts += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        for ((passenger, seat_number), fare) in passengers.iter().zip(&seats).zip(&fares) {
+            let inserted = sqlx::query(
+                r#"
+                INSERT INTO tickets
+                    (ticket_number, user_id, flight_id, seat_number, fare_class, passenger_type,
+                     sold_as, group_id, fare, currency)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(new_ticket_number())
+            .bind(passenger.user_id)
+            .bind(flight_id)
+            .bind(seat_number)
+            .bind(fare_class)
+            .bind(passenger.passenger_type)
+            .bind(&flight_number)
+            .bind(group_id)
+            .bind(fare)
+            .bind(currency)
+            .execute(&mut *tx)
+            .await;
+            if let Err(e) = inserted {
+                if db::is_duplicate_key(&e, "uq_tickets_seat") {
+                    tx.rollback().await?;
+                    return Ok(GroupSeating::SeatTaken(seat_number.clone()));
+                }
+                return Err(e);
+            }
+        }
+
+        // Webhooks and confirmation emails go out through the outbox, so they are sent for
+        // exactly the tickets that were committed
+        let tickets = sqlx::query_as::<_, Ticket>(
+            "SELECT * FROM tickets WHERE group_id = ? ORDER BY ticket_id",
+        )
+        .bind(group_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for ticket in tickets {
+            OutboxEvent::record(&mut *tx, &DomainEvent::TicketBooked { ticket }).await?;
+        }
+
+        let group = sqlx::query_as::<_, Self>("SELECT * FROM booking_groups WHERE group_id = ?")
+            .bind(group_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(GroupSeating::Booked(group))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_groups_get_the_discount() {
+        let discount = GroupDiscount {
+            min_passengers: 10,
+            percent: 5.0,
+        };
+
+        assert_eq!(discount.discounted(9, 120.0), 120.0);
+        assert_eq!(discount.discounted(10, 120.0), 114.0);
+        assert_eq!(discount.discounted(12, 99.99), 94.99);
+    }
+
+    #[test]
+    fn references_are_six_unambiguous_characters() {
+        let reference = new_reference();
+
+        assert_eq!(reference.len(), 6);
+        assert!(reference.bytes().all(|c| REFERENCE_CHARS.contains(&c)));
+    }
+}
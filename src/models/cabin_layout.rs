@@ -0,0 +1,308 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{types::Json, FromRow};
+use validator::Validate;
+
+use super::FareClass;
+use crate::db::{self, DbConnection, DbPool};
+use crate::validation::{not_blank, seat_letters};
+
+// Consecutive rows of one cabin class with the same seat letters, e.g. rows 1-3 of business
+// with seats A, C, D and F
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct CabinSection {
+    pub fare_class: FareClass,
+    #[validate(range(min = 1, max = 99))]
+    pub first_row: i32,
+    #[validate(range(min = 1, max = 99))]
+    pub last_row: i32,
+    #[validate(custom(function = "seat_letters"))]
+    pub seat_letters: String,
+}
+
+// Seat layout that aircraft share; flights get their seats from it when they are scheduled
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CabinLayout {
+    pub layout_id: i32,
+    pub name: String,
+    pub sections: Json<Vec<CabinSection>>,
+    pub exit_rows: Json<Vec<i32>>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Layout to store
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewCabinLayout {
+    #[validate(custom(function = "not_blank"), length(max = 100))]
+    pub name: String,
+    #[validate(length(min = 1), nested)]
+    pub sections: Vec<CabinSection>,
+    #[serde(default)]
+    pub exit_rows: Vec<i32>,
+}
+
+impl NewCabinLayout {
+    // Why the sections do not make a cabin, if they don't: sections run front to back without
+    // sharing rows, and exit rows are rows of the cabin
+    pub fn layout_error(&self) -> Option<String> {
+        for (index, section) in self.sections.iter().enumerate() {
+            if section.last_row < section.first_row {
+                return Some(format!(
+                    "sections[{}]: last_row must not be before first_row",
+                    index
+                ));
+            }
+            if index > 0 && section.first_row <= self.sections[index - 1].last_row {
+                return Some(format!(
+                    "sections[{}]: rows must follow those of the previous section",
+                    index
+                ));
+            }
+        }
+        self.exit_rows
+            .iter()
+            .find(|row| {
+                !self
+                    .sections
+                    .iter()
+                    .any(|s| (s.first_row..=s.last_row).contains(row))
+            })
+            .map(|row| format!("Exit row {} is not a row of the cabin", row))
+    }
+}
+
+// One seat of a layout
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedSeat {
+    pub seat_number: String,
+    pub seat_row: i32,
+    pub fare_class: FareClass,
+    pub exit_row: bool,
+}
+
+// Every seat of the sections, front to back and left to right
+pub fn seat_plan(sections: &[CabinSection], exit_rows: &[i32]) -> Vec<PlannedSeat> {
+    sections
+        .iter()
+        .flat_map(|section| {
+            (section.first_row..=section.last_row).flat_map(move |row| {
+                section.seat_letters.chars().map(move |letter| PlannedSeat {
+                    seat_number: format!("{}{}", row, letter),
+                    seat_row: row,
+                    fare_class: section.fare_class,
+                    exit_row: exit_rows.contains(&row),
+                })
+            })
+        })
+        .collect()
+}
+
+impl CabinLayout {
+    pub fn seat_count(&self) -> usize {
+        seat_plan(&self.sections, &self.exit_rows).len()
+    }
+
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM cabin_layouts WHERE layout_id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_all(pool: &DbPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM cabin_layouts ORDER BY name, layout_id")
+            .fetch_all(pool)
+            .await
+    }
+
+    pub async fn create(
+        pool: &DbPool,
+        layout: &NewCabinLayout,
+        created_by: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO cabin_layouts (name, sections, exit_rows, created_by, created_at)
+            VALUES (?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(layout.name.trim())
+        .bind(Json(&layout.sections))
+        .bind(Json(&layout.exit_rows))
+        .bind(created_by)
+        .execute(pool)
+        .await?;
+
+        Self::find_by_id(pool, db::last_insert_id(&result) as i32)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    // Give the aircraft a layout, or take it away with None. Flights already scheduled keep
+    // their seats
+    pub async fn attach(
+        pool: &DbPool,
+        aircraft_id: i32,
+        layout_id: Option<i32>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE aircraft SET cabin_layout_id = ? WHERE aircraft_id = ?")
+            .bind(layout_id)
+            .bind(aircraft_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
+
+// A seat of a flight and whether it can still be sold
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FlightSeat {
+    pub seat_number: String,
+    pub seat_row: i32,
+    pub fare_class: FareClass,
+    pub exit_row: bool,
+    // available, booked or blocked
+    pub status: String,
+}
+
+impl FlightSeat {
+    // Give a new flight the seats of its aircraft's layout; nothing when the aircraft has none
+    pub async fn generate(
+        conn: &mut DbConnection,
+        flight_id: i32,
+        aircraft_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let layout = sqlx::query_as::<_, CabinLayout>(
+            r#"
+            SELECT l.*
+            FROM aircraft a
+            JOIN cabin_layouts l ON l.layout_id = a.cabin_layout_id
+            WHERE a.aircraft_id = ?
+            "#,
+        )
+        .bind(aircraft_id)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some(layout) = layout else {
+            return Ok(());
+        };
+
+        for seat in seat_plan(&layout.sections, &layout.exit_rows) {
+            sqlx::query(
+                r#"
+                INSERT INTO flight_seats (flight_id, seat_number, seat_row, fare_class, exit_row)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(flight_id)
+            .bind(&seat.seat_number)
+            .bind(seat.seat_row)
+            .bind(seat.fare_class)
+            .bind(seat.exit_row)
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+
+    // Seat map of a flight, empty for flights scheduled without a layout
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT s.seat_number, s.seat_row, s.fare_class, s.exit_row,
+                   CASE
+                       WHEN t.ticket_id IS NOT NULL THEN 'booked'
+                       WHEN b.block_id IS NOT NULL THEN 'blocked'
+                       ELSE 'available'
+                   END AS status
+            FROM flight_seats s
+            LEFT JOIN tickets t ON t.flight_id = s.flight_id AND t.seat_number = s.seat_number
+            LEFT JOIN seat_blocks b ON b.flight_id = s.flight_id AND b.seat_number = s.seat_number
+            WHERE s.flight_id = ?
+            ORDER BY s.seat_row, s.seat_number
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Whether the seat is on the flight's seat map; flights without one accept any seat
+    pub async fn exists(
+        pool: &DbPool,
+        flight_id: i32,
+        seat_number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let (seats, matching): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(seat_number = ?), 0)
+            FROM flight_seats
+            WHERE flight_id = ?
+            "#,
+        )
+        .bind(seat_number)
+        .bind(flight_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(seats == 0 || matching > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(
+        fare_class: FareClass,
+        first_row: i32,
+        last_row: i32,
+        letters: &str,
+    ) -> CabinSection {
+        CabinSection {
+            fare_class,
+            first_row,
+            last_row,
+            seat_letters: letters.to_string(),
+        }
+    }
+
+    #[test]
+    fn plans_seats_front_to_back() {
+        let sections = [
+            section(FareClass::Business, 1, 2, "AC"),
+            section(FareClass::Economy, 3, 4, "ABC"),
+        ];
+        let seats = seat_plan(&sections, &[4]);
+
+        let numbers: Vec<&str> = seats.iter().map(|s| s.seat_number.as_str()).collect();
+        assert_eq!(
+            numbers,
+            ["1A", "1C", "2A", "2C", "3A", "3B", "3C", "4A", "4B", "4C"]
+        );
+        assert_eq!(seats[0].fare_class, FareClass::Business);
+        assert_eq!(seats[4].fare_class, FareClass::Economy);
+        assert!(!seats[6].exit_row);
+        assert!(seats[7].exit_row);
+    }
+
+    #[test]
+    fn sections_must_not_share_rows() {
+        let mut layout = NewCabinLayout {
+            name: "A320 two-class".to_string(),
+            sections: vec![
+                section(FareClass::Business, 1, 3, "ACDF"),
+                section(FareClass::Economy, 4, 30, "ABCDEF"),
+            ],
+            exit_rows: vec![12, 13],
+        };
+        assert_eq!(layout.layout_error(), None);
+
+        layout.sections[1].first_row = 3;
+        assert!(layout.layout_error().is_some());
+
+        layout.sections[1].first_row = 4;
+        layout.exit_rows = vec![31];
+        assert!(layout.layout_error().is_some());
+    }
+}
@@ -0,0 +1,326 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::db::{self, DbPool};
+use crate::validation::{iata_code, not_blank};
+
+// Where a shipment is between acceptance at the origin and hand-over at the destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CargoStatus {
+    Received,
+    Booked,
+    Loaded,
+    InTransit,
+    Arrived,
+    Delivered,
+}
+
+impl CargoStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CargoStatus::Received => "received",
+            CargoStatus::Booked => "booked",
+            CargoStatus::Loaded => "loaded",
+            CargoStatus::InTransit => "in_transit",
+            CargoStatus::Arrived => "arrived",
+            CargoStatus::Delivered => "delivered",
+        }
+    }
+
+    // Statuses ground staff record once a shipment is booked on a flight, one step at a time;
+    // a loaded shipment taken off again goes back to booked. Booking itself is an assignment
+    pub fn can_track_to(&self, next: CargoStatus) -> bool {
+        use CargoStatus::*;
+
+        matches!(
+            (self, next),
+            (Booked, Loaded)
+                | (Loaded, Booked | InTransit)
+                | (InTransit, Arrived)
+                | (Arrived, Delivered)
+        )
+    }
+
+    // Shipments not yet loaded can be booked on a flight, or moved to another one
+    pub fn assignable(&self) -> bool {
+        matches!(self, CargoStatus::Received | CargoStatus::Booked)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Shipment {
+    pub shipment_id: i32,
+    pub awb_number: String,
+    pub shipper: String,
+    pub consignee: String,
+    pub origin_code: String,
+    pub destination_code: String,
+    pub description: Option<String>,
+    pub weight_kg: f64,
+    pub volume_m3: f64,
+    pub status: CargoStatus,
+    pub flight_id: Option<i32>,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Shipment to accept
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewShipment {
+    #[validate(custom(function = "not_blank"), length(max = 100))]
+    pub shipper: String,
+    #[validate(custom(function = "not_blank"), length(max = 100))]
+    pub consignee: String,
+    #[validate(custom(function = "iata_code"))]
+    pub origin_code: String,
+    #[validate(custom(function = "iata_code"))]
+    pub destination_code: String,
+    #[validate(length(max = 255))]
+    pub description: Option<String>,
+    #[validate(range(exclusive_min = 0.0, max = 10000.0))]
+    pub weight_kg: f64,
+    #[validate(range(exclusive_min = 0.0, max = 100.0))]
+    pub volume_m3: f64,
+}
+
+// One status a shipment went through
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CargoEvent {
+    pub event_id: i32,
+    pub shipment_id: i32,
+    pub status: CargoStatus,
+    pub flight_id: Option<i32>,
+    pub location: Option<String>,
+    pub recorded_by: Option<i32>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+// Weight and volume of the cargo booked on a flight
+#[derive(Debug, Clone, Copy, Default, Serialize, FromRow)]
+pub struct CargoLoad {
+    pub weight_kg: f64,
+    pub volume_m3: f64,
+}
+
+impl Shipment {
+    pub async fn find_by_awb(pool: &DbPool, awb_number: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM cargo_shipments WHERE awb_number = ?")
+            .bind(awb_number)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM cargo_shipments WHERE flight_id = ? ORDER BY shipment_id",
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Accept a shipment under a new air waybill number, recording its receipt as the first
+    // event
+    pub async fn create(
+        pool: &DbPool,
+        shipment: &NewShipment,
+        location: Option<&str>,
+        actor_id: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let awb_number = format!(
+            "{:011}",
+            rand::thread_rng().gen_range(0..100_000_000_000u64)
+        );
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO cargo_shipments
+                (awb_number, shipper, consignee, origin_code, destination_code, description,
+                 weight_kg, volume_m3, status, created_by, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'received', ?, UTC_TIMESTAMP(), UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(&awb_number)
+        .bind(shipment.shipper.trim())
+        .bind(shipment.consignee.trim())
+        .bind(&shipment.origin_code)
+        .bind(&shipment.destination_code)
+        .bind(&shipment.description)
+        .bind(shipment.weight_kg)
+        .bind(shipment.volume_m3)
+        .bind(actor_id)
+        .execute(&mut *tx)
+        .await?;
+        let shipment_id = db::last_insert_id(&result) as i32;
+
+        sqlx::query(
+            r#"
+            INSERT INTO cargo_events (shipment_id, status, location, recorded_by, recorded_at)
+            VALUES (?, 'received', ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(shipment_id)
+        .bind(location)
+        .bind(actor_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Self::find_by_awb(pool, &awb_number)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    // Weight and volume booked on the flight, leaving out one shipment that is being moved
+    pub async fn load_on_flight(
+        pool: &DbPool,
+        flight_id: i32,
+        except_shipment_id: i32,
+    ) -> Result<CargoLoad, sqlx::Error> {
+        sqlx::query_as::<_, CargoLoad>(
+            r#"
+            SELECT COALESCE(SUM(weight_kg), 0) AS weight_kg,
+                   COALESCE(SUM(volume_m3), 0) AS volume_m3
+            FROM cargo_shipments
+            WHERE flight_id = ? AND shipment_id <> ?
+            "#,
+        )
+        .bind(flight_id)
+        .bind(except_shipment_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    // Book the shipment on a flight unless it was loaded concurrently; false if it was
+    pub async fn assign(
+        pool: &DbPool,
+        shipment_id: i32,
+        flight_id: i32,
+        actor_id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE cargo_shipments
+            SET status = 'booked', flight_id = ?, updated_at = UTC_TIMESTAMP()
+            WHERE shipment_id = ? AND status IN ('received', 'booked')
+            "#,
+        )
+        .bind(flight_id)
+        .bind(shipment_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO cargo_events (shipment_id, status, flight_id, recorded_by, recorded_at)
+            VALUES (?, 'booked', ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(shipment_id)
+        .bind(flight_id)
+        .bind(actor_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    // Compare-and-set from `from` to `to` with an event; false if the status changed
+    // concurrently
+    pub async fn track(
+        pool: &DbPool,
+        shipment: &Shipment,
+        to: CargoStatus,
+        location: Option<&str>,
+        actor_id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE cargo_shipments SET status = ?, updated_at = UTC_TIMESTAMP()
+            WHERE shipment_id = ? AND status = ?
+            "#,
+        )
+        .bind(to)
+        .bind(shipment.shipment_id)
+        .bind(shipment.status)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO cargo_events
+                (shipment_id, status, flight_id, location, recorded_by, recorded_at)
+            VALUES (?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(shipment.shipment_id)
+        .bind(to)
+        .bind(shipment.flight_id)
+        .bind(location)
+        .bind(actor_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+}
+
+impl CargoEvent {
+    // Events of the shipment, oldest first
+    pub async fn find_by_shipment(
+        pool: &DbPool,
+        shipment_id: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM cargo_events WHERE shipment_id = ? ORDER BY recorded_at, event_id",
+        )
+        .bind(shipment_id)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipments_are_tracked_one_step_at_a_time() {
+        use CargoStatus::*;
+
+        assert!(Booked.can_track_to(Loaded));
+        assert!(Loaded.can_track_to(Booked));
+        assert!(InTransit.can_track_to(Arrived));
+        assert!(!Received.can_track_to(Loaded));
+        assert!(!Booked.can_track_to(InTransit));
+        assert!(!Delivered.can_track_to(Arrived));
+
+        assert!(Received.assignable());
+        assert!(Booked.assignable());
+        assert!(!Loaded.assignable());
+    }
+}
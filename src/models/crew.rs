@@ -0,0 +1,472 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::{FlightEvent, FlightEventType};
+use crate::db::{self, DbPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum CrewRole {
+    Pilot,
+    CabinCrew,
+}
+
+impl CrewRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CrewRole::Pilot => "pilot",
+            CrewRole::CabinCrew => "cabin_crew",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CrewMember {
+    pub crew_member_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub role: CrewRole,
+}
+
+// Minimum number of crew of one role required to operate an aircraft model
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CrewRequirement {
+    pub aircraft_model: String,
+    pub role: CrewRole,
+    pub min_count: i32,
+}
+
+// A role for which the assigned crew of a flight falls short
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CrewShortfall {
+    pub role: CrewRole,
+    pub required: i32,
+    pub assigned: i64,
+}
+
+// Another flight a crew member is assigned to that clashes with the one being crewed
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CrewConflict {
+    pub crew_member_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub flight_id: i32,
+    pub flight_number: String,
+    pub departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+}
+
+// A flight a crew member is assigned to, for checking duty time
+#[derive(Debug, Clone, FromRow)]
+pub struct CrewDuty {
+    pub crew_member_id: i32,
+    pub departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+}
+
+// Duty-time limits a crew member's flights must stay within. Duty counts from departure to
+// arrival; flights with less than the minimum rest between them make one duty period
+#[derive(Debug, Clone, Copy)]
+pub struct DutyRules {
+    pub max_duty_per_day: Duration,
+    pub max_duty_per_week: Duration,
+    pub min_rest: Duration,
+}
+
+impl CrewMember {
+    pub async fn insert(
+        pool: &DbPool,
+        first_name: &str,
+        last_name: &str,
+        role: CrewRole,
+    ) -> Result<i32, sqlx::Error> {
+        let result =
+            sqlx::query("INSERT INTO crew_members (first_name, last_name, role) VALUES (?, ?, ?)")
+                .bind(first_name)
+                .bind(last_name)
+                .bind(role)
+                .execute(pool)
+                .await?;
+
+        Ok(db::last_insert_id(&result) as i32)
+    }
+
+    pub async fn find_by_ids(pool: &DbPool, ids: &[i32]) -> Result<Vec<Self>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!(
+            "SELECT * FROM crew_members WHERE crew_member_id IN ({})",
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, Self>(&sql);
+        for id in ids {
+            query = query.bind(id);
+        }
+        query.fetch_all(pool).await
+    }
+
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT c.*
+            FROM flight_crew_assignments fca
+            JOIN crew_members c ON c.crew_member_id = fca.crew_member_id
+            WHERE fca.flight_id = ?
+            ORDER BY c.role, c.last_name, c.first_name
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Assignments of the crew members to other flights that are not cancelled and overlap the
+    // flight, widened on both sides by the turnaround the crew needs between two flights
+    pub async fn conflicts(
+        pool: &DbPool,
+        flight_id: i32,
+        crew_member_ids: &[i32],
+        turnaround_minutes: u32,
+    ) -> Result<Vec<CrewConflict>, sqlx::Error> {
+        if crew_member_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; crew_member_ids.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT c.crew_member_id, c.first_name, c.last_name,
+                   o.flight_id, o.flight_number, o.departure_time, o.arrival_time
+            FROM flights f
+            JOIN flights o
+                ON o.flight_id <> f.flight_id
+                AND o.status <> 'cancelled'
+                AND o.departure_time < DATE_ADD(f.arrival_time, INTERVAL ? MINUTE)
+                AND o.arrival_time > DATE_SUB(f.departure_time, INTERVAL ? MINUTE)
+            JOIN flight_crew_assignments fca ON fca.flight_id = o.flight_id
+            JOIN crew_members c ON c.crew_member_id = fca.crew_member_id
+            WHERE f.flight_id = ? AND c.crew_member_id IN ({})
+            ORDER BY c.last_name, c.first_name, o.departure_time
+            "#,
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, CrewConflict>(&sql)
+            .bind(turnaround_minutes)
+            .bind(turnaround_minutes)
+            .bind(flight_id);
+        for id in crew_member_ids {
+            query = query.bind(id);
+        }
+        query.fetch_all(pool).await
+    }
+
+    // Other flights, not cancelled, of the crew members that are close enough to the flight to
+    // count towards its duty-time limits
+    pub async fn duty_around(
+        pool: &DbPool,
+        flight_id: i32,
+        crew_member_ids: &[i32],
+        reach: Duration,
+    ) -> Result<Vec<CrewDuty>, sqlx::Error> {
+        if crew_member_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let placeholders = vec!["?"; crew_member_ids.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT fca.crew_member_id, o.departure_time, o.arrival_time
+            FROM flights f
+            JOIN flights o
+                ON o.flight_id <> f.flight_id
+                AND o.status <> 'cancelled'
+                AND o.departure_time < DATE_ADD(f.arrival_time, INTERVAL ? MINUTE)
+                AND o.arrival_time > DATE_SUB(f.departure_time, INTERVAL ? MINUTE)
+            JOIN flight_crew_assignments fca ON fca.flight_id = o.flight_id
+            WHERE f.flight_id = ? AND fca.crew_member_id IN ({})
+            ORDER BY o.departure_time
+            "#,
+            placeholders
+        );
+        let mut query = sqlx::query_as::<_, CrewDuty>(&sql)
+            .bind(reach.num_minutes())
+            .bind(reach.num_minutes())
+            .bind(flight_id);
+        for id in crew_member_ids {
+            query = query.bind(id);
+        }
+        query.fetch_all(pool).await
+    }
+
+    // Replace the crew assigned to a flight
+    pub async fn assign_to_flight(
+        pool: &DbPool,
+        flight_id: i32,
+        crew_member_ids: &[i32],
+        actor_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM flight_crew_assignments WHERE flight_id = ?")
+            .bind(flight_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for crew_member_id in crew_member_ids {
+            sqlx::query(
+                "INSERT INTO flight_crew_assignments (flight_id, crew_member_id) VALUES (?, ?)",
+            )
+            .bind(flight_id)
+            .bind(crew_member_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        FlightEvent::record(
+            &mut *tx,
+            flight_id,
+            FlightEventType::CrewAssigned,
+            Some(serde_json::json!({ "crew_member_ids": crew_member_ids })),
+            Some(actor_id),
+        )
+        .await?;
+
+        tx.commit().await
+    }
+}
+
+impl CrewRequirement {
+    pub async fn find_by_model(
+        pool: &DbPool,
+        aircraft_model: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM aircraft_crew_requirements WHERE aircraft_model = ? ORDER BY role",
+        )
+        .bind(aircraft_model)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Replace the crew composition required for an aircraft model
+    pub async fn replace_for_model(
+        pool: &DbPool,
+        aircraft_model: &str,
+        requirements: &[(CrewRole, i32)],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM aircraft_crew_requirements WHERE aircraft_model = ?")
+            .bind(aircraft_model)
+            .execute(&mut *tx)
+            .await?;
+
+        for (role, min_count) in requirements {
+            sqlx::query(
+                "INSERT INTO aircraft_crew_requirements (aircraft_model, role, min_count) VALUES (?, ?, ?)",
+            )
+            .bind(aircraft_model)
+            .bind(role)
+            .bind(min_count)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    // Roles for which the crew assigned to the flight is below the aircraft's requirement
+    pub async fn shortfalls_for_flight(
+        pool: &DbPool,
+        flight_id: i32,
+    ) -> Result<Vec<CrewShortfall>, sqlx::Error> {
+        sqlx::query_as::<_, CrewShortfall>(
+            r#"
+            SELECT r.role, r.min_count AS required, COUNT(c.crew_member_id) AS assigned
+            FROM flights f
+            JOIN aircraft a ON a.aircraft_id = f.aircraft_id
+            JOIN aircraft_crew_requirements r ON r.aircraft_model = a.model
+            LEFT JOIN flight_crew_assignments fca ON fca.flight_id = f.flight_id
+            LEFT JOIN crew_members c
+                ON c.crew_member_id = fca.crew_member_id AND c.role = r.role
+            WHERE f.flight_id = ?
+            GROUP BY r.role, r.min_count
+            HAVING COUNT(c.crew_member_id) < r.min_count
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+type Span = (DateTime<Utc>, DateTime<Utc>);
+
+impl DutyRules {
+    // How far from a flight other flights can still count towards its limits
+    pub fn reach(&self) -> Duration {
+        Duration::days(7)
+    }
+
+    // The limits a crew member breaks by flying `flight` on top of `others`, worded for the
+    // dispatcher, e.g. "14h 20m on duty within 24 hours (limit 13h)"
+    pub fn violations(&self, flight: Span, others: &[Span]) -> Vec<String> {
+        let mut flights = others.to_vec();
+        flights.push(flight);
+        flights.sort();
+
+        let mut violations = Vec::new();
+        for (window, limit, label) in [
+            (Duration::hours(24), self.max_duty_per_day, "24 hours"),
+            (Duration::days(7), self.max_duty_per_week, "7 days"),
+        ] {
+            let duty = busiest_window(&flights, flight, window);
+            if duty > limit {
+                violations.push(format!(
+                    "{} on duty within {} (limit {})",
+                    hours_and_minutes(duty),
+                    label,
+                    hours_and_minutes(limit)
+                ));
+            }
+        }
+
+        // The duty period is the flight and every flight reached from it with shorter breaks
+        // than the minimum rest; it has to leave room for that rest within 24 hours
+        let index = flights.iter().position(|f| *f == flight).unwrap_or(0);
+        let mut first = index;
+        while first > 0 && flights[first].0 - flights[first - 1].1 < self.min_rest {
+            first -= 1;
+        }
+        let mut last = index;
+        while last + 1 < flights.len() && flights[last + 1].0 - flights[last].1 < self.min_rest {
+            last += 1;
+        }
+        let period = flights[last].1 - flights[first].0;
+        if period + self.min_rest > Duration::hours(24) {
+            violations.push(format!(
+                "duty period of {} leaves less than {} of rest within 24 hours",
+                hours_and_minutes(period),
+                hours_and_minutes(self.min_rest)
+            ));
+        }
+
+        violations
+    }
+}
+
+// Most flying in any window of the given length that overlaps the flight. The busiest window
+// starts at a departure or ends at an arrival, so only those are tried
+fn busiest_window(flights: &[Span], flight: Span, window: Duration) -> Duration {
+    flights
+        .iter()
+        .flat_map(|(departure, arrival)| [*departure, *arrival - window])
+        .filter(|start| *start < flight.1 && *start + window > flight.0)
+        .map(|start| {
+            let end = start + window;
+            flights
+                .iter()
+                .map(|(departure, arrival)| {
+                    ((*arrival).min(end) - (*departure).max(start)).max(Duration::zero())
+                })
+                .sum()
+        })
+        .max()
+        .unwrap_or_else(Duration::zero)
+}
+
+// "13h" or "14h 20m"
+fn hours_and_minutes(duration: Duration) -> String {
+    match duration.num_minutes() % 60 {
+        0 => format!("{}h", duration.num_hours()),
+        minutes => format!("{}h {}m", duration.num_hours(), minutes),
+    }
+}
+
+// Human readable summary such as "1 more pilot, 2 more cabin_crew"
+pub fn describe_shortfalls(shortfalls: &[CrewShortfall]) -> String {
+    shortfalls
+        .iter()
+        .map(|s| {
+            format!(
+                "{} more {}",
+                s.required as i64 - s.assigned,
+                s.role.as_str()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// Human readable list such as "Olena Koval on PS101 (2026-11-02 08:00 UTC)"
+pub fn describe_conflicts(conflicts: &[CrewConflict]) -> String {
+    conflicts
+        .iter()
+        .map(|c| {
+            format!(
+                "{} {} on {} ({} UTC)",
+                c.first_name,
+                c.last_name,
+                c.flight_number,
+                c.departure_time.format("%Y-%m-%d %H:%M")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn rules() -> DutyRules {
+        DutyRules {
+            max_duty_per_day: Duration::hours(13),
+            max_duty_per_week: Duration::hours(60),
+            min_rest: Duration::hours(10),
+        }
+    }
+
+    fn flight(day: u32, hour: u32, hours: i64) -> Span {
+        let departure = Utc.with_ymd_and_hms(2030, 6, day, hour, 0, 0).unwrap();
+        (departure, departure + Duration::hours(hours))
+    }
+
+    #[test]
+    fn rested_crew_may_fly() {
+        let others = [flight(1, 6, 3), flight(1, 11, 3)];
+        assert!(rules().violations(flight(2, 6, 4), &others).is_empty());
+    }
+
+    #[test]
+    fn limits_flying_within_a_day() {
+        // 14 hours of flying in 24 hours, with a long enough break in between
+        let others = [flight(1, 0, 7)];
+        let violations = rules().violations(flight(1, 17, 7), &others);
+        assert_eq!(violations, ["14h on duty within 24 hours (limit 13h)"]);
+    }
+
+    #[test]
+    fn limits_flying_within_a_week() {
+        let others: Vec<Span> = (1..=6).map(|day| flight(day, 8, 10)).collect();
+        let violations = rules().violations(flight(7, 8, 3), &others);
+        assert_eq!(violations, ["63h on duty within 7 days (limit 60h)"]);
+    }
+
+    #[test]
+    fn requires_rest_between_duty_periods() {
+        // Short breaks chain the flights into one 15-hour duty period
+        let others = [flight(1, 6, 4), flight(1, 12, 4)];
+        let violations = rules().violations(flight(1, 18, 3), &others);
+        assert_eq!(
+            violations,
+            ["duty period of 15h leaves less than 10h of rest within 24 hours"]
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::db::DbPool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DataExportStatus {
+    Pending,
+    Ready,
+    Failed,
+}
+
+// A user's request for a copy of their data; `export_id` is the token the archive is retrieved with
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DataExport {
+    pub export_id: String,
+    pub user_id: i32,
+    pub status: DataExportStatus,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+const COLUMNS: &str = "export_id, user_id, status, created_at, completed_at, expires_at";
+
+impl DataExport {
+    // The user's pending or still downloadable export, or a new one queued for the job; true if
+    // it was created now
+    pub async fn request(pool: &DbPool, user_id: i32) -> Result<(Self, bool), sqlx::Error> {
+        let current = sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM data_exports
+            WHERE user_id = ?
+              AND (status = 'pending' OR (status = 'ready' AND expires_at > UTC_TIMESTAMP()))
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            COLUMNS
+        ))
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+        if let Some(current) = current {
+            return Ok((current, false));
+        }
+
+        let export_id = Uuid::new_v4().simple().to_string();
+        sqlx::query(
+            "INSERT INTO data_exports (export_id, user_id, created_at) VALUES (?, ?, UTC_TIMESTAMP())",
+        )
+        .bind(&export_id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        let created = Self::find(pool, user_id, &export_id)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)?;
+        Ok((created, true))
+    }
+
+    // The user's export with this token, unless its archive has expired
+    pub async fn find(
+        pool: &DbPool,
+        user_id: i32,
+        export_id: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {} FROM data_exports
+            WHERE export_id = ? AND user_id = ?
+              AND (expires_at IS NULL OR expires_at > UTC_TIMESTAMP())
+            "#,
+            COLUMNS
+        ))
+        .bind(export_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn archive(pool: &DbPool, export_id: &str) -> Result<Option<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT archive FROM data_exports WHERE export_id = ?")
+            .bind(export_id)
+            .fetch_optional(pool)
+            .await
+            .map(Option::flatten)
+    }
+
+    // Oldest exports waiting for the job
+    pub async fn pending(pool: &DbPool, limit: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM data_exports WHERE status = 'pending' ORDER BY created_at LIMIT ?",
+            COLUMNS
+        ))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Store the archive, downloadable for `ttl_secs`
+    pub async fn complete(
+        pool: &DbPool,
+        export_id: &str,
+        archive: &str,
+        ttl_secs: u64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE data_exports
+            SET status = 'ready', archive = ?, completed_at = UTC_TIMESTAMP(),
+                expires_at = UTC_TIMESTAMP() + INTERVAL ? SECOND
+            WHERE export_id = ?
+            "#,
+        )
+        .bind(archive)
+        .bind(ttl_secs as i64)
+        .bind(export_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn fail(pool: &DbPool, export_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE data_exports SET status = 'failed', completed_at = UTC_TIMESTAMP() WHERE export_id = ?",
+        )
+        .bind(export_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // Drop archives past their expiry; the request itself stays as a record
+    pub async fn purge_expired(pool: &DbPool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE data_exports SET archive = NULL WHERE archive IS NOT NULL AND expires_at <= UTC_TIMESTAMP()",
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::db::DbPool;
+
+// Units of a currency one euro buys
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExchangeRate {
+    pub currency: String,
+    pub per_euro: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ExchangeRate {
+    pub async fn find_all(pool: &DbPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM exchange_rates ORDER BY currency")
+            .fetch_all(pool)
+            .await
+    }
+
+    // Store a fresh set of rates in one transaction; currencies missing from it keep their rate
+    pub async fn upsert_all(pool: &DbPool, rates: &[(String, f64)]) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let now = Utc::now();
+        for (currency, per_euro) in rates {
+            sqlx::query(
+                r#"
+                INSERT INTO exchange_rates (currency, per_euro, updated_at)
+                VALUES (?, ?, ?)
+                ON DUPLICATE KEY UPDATE per_euro = VALUES(per_euro), updated_at = VALUES(updated_at)
+                "#,
+            )
+            .bind(currency)
+            .bind(per_euro)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await
+    }
+}
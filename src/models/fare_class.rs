@@ -0,0 +1,195 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use super::report::period_bounds;
+use crate::currencies::BASE_CURRENCY;
+use crate::db::DbPool;
+use crate::validation::currency_code;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum FareClass {
+    Economy,
+    Business,
+    First,
+}
+
+impl FareClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FareClass::Economy => "economy",
+            FareClass::Business => "business",
+            FareClass::First => "first",
+        }
+    }
+
+    // Classes a ticket of this class can be upgraded to, cheapest first
+    pub fn higher(&self) -> &'static [FareClass] {
+        match self {
+            FareClass::Economy => &[FareClass::Business, FareClass::First],
+            FareClass::Business => &[FareClass::First],
+            FareClass::First => &[],
+        }
+    }
+}
+
+// Seat inventory and price of one cabin class on a flight
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FlightFareClass {
+    pub flight_id: i32,
+    pub fare_class: FareClass,
+    pub seat_count: i32,
+    pub price: f64,
+    // ISO 4217 code of `price`
+    pub currency: String,
+    pub seats_sold: i64,
+    pub seats_available: i64,
+}
+
+// Inventory to configure for a class
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct FareClassInventory {
+    pub fare_class: FareClass,
+    #[validate(range(min = 0))]
+    pub seat_count: i32,
+    #[validate(range(min = 0.0))]
+    pub price: f64,
+    #[serde(default = "base_currency")]
+    #[validate(custom(function = "currency_code"))]
+    pub currency: String,
+}
+
+// A fare class with a seat left on a flight of a flexible-dates search, at its base fare until
+// priced
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FareOption {
+    #[serde(skip)]
+    pub departure_date: NaiveDate,
+    pub flight_id: i32,
+    pub flight_number: String,
+    pub departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+    pub fare_class: FareClass,
+    #[serde(skip)]
+    pub seat_count: i32,
+    #[serde(skip)]
+    pub seats_sold: i64,
+    pub seats_available: i64,
+    pub price: f64,
+    pub currency: String,
+}
+
+fn base_currency() -> String {
+    BASE_CURRENCY.to_string()
+}
+
+impl FlightFareClass {
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT fc.flight_id, fc.fare_class, fc.seat_count, fc.price, fc.currency,
+                   COUNT(t.ticket_id) AS seats_sold,
+                   GREATEST(fc.seat_count - COUNT(t.ticket_id), 0) AS seats_available
+            FROM flight_fare_classes fc
+            LEFT JOIN tickets t ON t.flight_id = fc.flight_id AND t.fare_class = fc.fare_class
+            WHERE fc.flight_id = ?
+            GROUP BY fc.flight_id, fc.fare_class, fc.seat_count, fc.price, fc.currency
+            ORDER BY fc.fare_class
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Replace the class configuration of a flight in one transaction
+    pub async fn replace_for_flight(
+        pool: &DbPool,
+        flight_id: i32,
+        classes: &[FareClassInventory],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM flight_fare_classes WHERE flight_id = ?")
+            .bind(flight_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for class in classes {
+            sqlx::query(
+                r#"
+                INSERT INTO flight_fare_classes (flight_id, fare_class, seat_count, price, currency)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(flight_id)
+            .bind(class.fare_class)
+            .bind(class.seat_count)
+            .bind(class.price)
+            .bind(&class.currency)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn aircraft_capacity(pool: &DbPool, flight_id: i32) -> Result<i32, sqlx::Error> {
+        let (capacity,): (i32,) = sqlx::query_as(
+            r#"
+            SELECT a.capacity
+            FROM flights f
+            JOIN aircraft a ON a.aircraft_id = f.aircraft_id
+            WHERE f.flight_id = ?
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(capacity)
+    }
+}
+
+impl FareOption {
+    // Every class with a seat left on the flights of a route still to depart, `from` through `to`
+    // in UTC, by departure. Not grouped by day: which fare is cheapest depends on the price the
+    // pricing strategy charges, so the caller prices them and picks
+    pub async fn find_by_days(
+        pool: &DbPool,
+        origin: &str,
+        destination: &str,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let (from, to) = period_bounds(from, to);
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT DATE(f.departure_time) AS departure_date, f.flight_id, f.flight_number,
+                   f.departure_time, f.arrival_time, fc.fare_class, fc.seat_count, fc.price,
+                   fc.currency, COUNT(t.ticket_id) AS seats_sold,
+                   fc.seat_count - COUNT(t.ticket_id) AS seats_available
+            FROM flights f
+            JOIN routes r ON r.route_id = f.route_id
+            JOIN flight_fare_classes fc ON fc.flight_id = f.flight_id
+            LEFT JOIN tickets t ON t.flight_id = fc.flight_id AND t.fare_class = fc.fare_class
+            WHERE r.origin = ? AND r.destination = ?
+              AND f.departure_time >= ? AND f.departure_time < ?
+              AND f.departure_time > UTC_TIMESTAMP()
+              AND f.status IN ('scheduled', 'delayed')
+            GROUP BY f.flight_id, f.flight_number, f.departure_time, f.arrival_time,
+                     fc.fare_class, fc.seat_count, fc.price, fc.currency
+            HAVING seats_available > 0
+            ORDER BY f.departure_time, f.flight_id, fc.fare_class
+            "#,
+        )
+        .bind(origin)
+        .bind(destination)
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await
+    }
+}
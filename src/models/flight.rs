@@ -0,0 +1,701 @@
+use chrono::{DateTime, FixedOffset, NaiveDate, Utc};
+use serde::{Deserialize, Serialize, Serializer};
+use sqlx::FromRow;
+
+use super::{
+    AirportTimezone, AssistanceRequest, DomainEvent, FareClass, FlightEvent, FlightEventType,
+    FlightSeat, Gate, OutboxEvent, PassengerType, SortOrder, TicketInfant, TicketPet,
+};
+use crate::db::{self, DbPool};
+use crate::export::{self, ExportFormat, RowSender};
+use crate::i18n::Locale;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum FlightStatus {
+    Scheduled,
+    Boarding,
+    Departed,
+    Arrived,
+    Delayed,
+    Cancelled,
+}
+
+impl FlightStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlightStatus::Scheduled => "scheduled",
+            FlightStatus::Boarding => "boarding",
+            FlightStatus::Departed => "departed",
+            FlightStatus::Arrived => "arrived",
+            FlightStatus::Delayed => "delayed",
+            FlightStatus::Cancelled => "cancelled",
+        }
+    }
+
+    // Allowed moves of the flight status state machine
+    pub fn can_transition_to(&self, next: FlightStatus) -> bool {
+        use FlightStatus::*;
+
+        matches!(
+            (self, next),
+            (Scheduled, Boarding | Delayed | Cancelled)
+                | (Delayed, Scheduled | Boarding | Cancelled)
+                | (Boarding, Departed | Delayed | Cancelled)
+                | (Departed, Arrived)
+        )
+    }
+}
+
+// Columns the flight listing can be sorted by (`sort=`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlightSort {
+    #[default]
+    DepartureTime,
+    ArrivalTime,
+    FlightNumber,
+    Status,
+}
+
+impl FlightSort {
+    fn column(&self) -> &'static str {
+        match self {
+            FlightSort::DepartureTime => "f.departure_time",
+            FlightSort::ArrivalTime => "f.arrival_time",
+            FlightSort::FlightNumber => "f.flight_number",
+            FlightSort::Status => "f.status",
+        }
+    }
+}
+
+// Query parameters of the flight listing; every filter is optional. `origin` and
+// `destination` match the route as stored, e.g. `Kyiv (KBP)`; `flight_number` matches the
+// operating number or any marketing one
+#[derive(Debug, Default, Deserialize)]
+pub struct FlightFilter {
+    pub origin: Option<String>,
+    pub destination: Option<String>,
+    pub flight_number: Option<String>,
+    pub status: Option<FlightStatus>,
+    pub departure_from: Option<DateTime<Utc>>,
+    pub departure_to: Option<DateTime<Utc>>,
+    // Departures on this day, in UTC
+    pub date: Option<NaiveDate>,
+    // Instead of flights, the cheapest fare of each day this many days either side of `date`
+    // (origin and destination required), in `currency` if given
+    pub flex_days: Option<i64>,
+    pub currency: Option<String>,
+    pub sort: Option<FlightSort>,
+    pub order: Option<SortOrder>,
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+    // Keyset pagination, only with the default sort
+    pub cursor: Option<String>,
+    // csv or xlsx to download every matching flight
+    pub format: Option<ExportFormat>,
+}
+
+impl FlightFilter {
+    // Sort column and direction, ties broken by flight id so pages never overlap
+    fn order_by(&self) -> String {
+        let order = self.order.unwrap_or_default().as_sql();
+        format!(
+            "{} {}, f.flight_id {}",
+            self.sort.unwrap_or_default().column(),
+            order,
+            order
+        )
+    }
+}
+
+// A flight row with the timezones of its route's airports and its marketing flight numbers
+const COLUMNS: &str = r#"
+    f.*, r.origin_timezone, r.destination_timezone,
+    (SELECT GROUP_CONCAT(c.flight_number ORDER BY c.flight_number SEPARATOR ',')
+     FROM flight_codeshares c WHERE c.flight_id = f.flight_id) AS marketing_flight_numbers
+"#;
+
+const FILTER: &str = r#"
+    FROM flights f
+    JOIN routes r ON r.route_id = f.route_id
+    WHERE (? IS NULL OR r.origin = ?)
+      AND (? IS NULL OR r.destination = ?)
+      AND (? IS NULL OR f.status = ?)
+      AND (? IS NULL OR f.departure_time >= ?)
+      AND (? IS NULL OR f.departure_time < ?)
+      AND (? IS NULL OR (f.departure_time >= ? AND f.departure_time < ? + INTERVAL 1 DAY))
+      AND (? IS NULL OR f.flight_number = ? OR EXISTS (
+          SELECT 1 FROM flight_codeshares c WHERE c.flight_id = f.flight_id AND c.flight_number = ?
+      ))
+"#;
+
+macro_rules! bind_filter {
+    ($query:expr, $filter:expr) => {
+        $query
+            .bind(&$filter.origin)
+            .bind(&$filter.origin)
+            .bind(&$filter.destination)
+            .bind(&$filter.destination)
+            .bind($filter.status)
+            .bind($filter.status)
+            .bind($filter.departure_from)
+            .bind($filter.departure_from)
+            .bind($filter.departure_to)
+            .bind($filter.departure_to)
+            .bind($filter.date)
+            .bind($filter.date)
+            .bind($filter.date)
+            .bind(&$filter.flight_number)
+            .bind(&$filter.flight_number)
+            .bind(&$filter.flight_number)
+    };
+}
+
+// Flight to schedule; it starts out `scheduled`
+#[derive(Debug)]
+pub struct NewFlight {
+    pub flight_number: String,
+    pub route_id: i32,
+    pub aircraft_id: i32,
+    pub departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+    pub gate: Option<String>,
+}
+
+// Flight numbers of other carriers a flight is sold under, read as one comma-separated column
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FlightNumbers(pub Vec<String>);
+
+impl From<Option<String>> for FlightNumbers {
+    fn from(column: Option<String>) -> Self {
+        FlightNumbers(
+            column
+                .iter()
+                .flat_map(|numbers| numbers.split(','))
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, FromRow)]
+pub struct Flight {
+    pub flight_id: i32,
+    // Of the operating carrier
+    pub flight_number: String,
+    #[sqlx(try_from = "Option<String>")]
+    #[serde(default)]
+    pub marketing_flight_numbers: FlightNumbers,
+    pub route_id: i32,
+    pub aircraft_id: i32,
+    pub departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+    // When it actually left and landed; departure_time and arrival_time stay as scheduled
+    #[serde(default)]
+    pub actual_departure_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub actual_arrival_time: Option<DateTime<Utc>>,
+    pub status: FlightStatus,
+    pub gate: Option<String>,
+    // Set when the gate was assigned from the airport's gates rather than typed in
+    #[serde(default)]
+    pub gate_id: Option<i32>,
+    #[serde(default)]
+    pub terminal: Option<String>,
+    // Bumped by every write to the row; the flight's ETag
+    pub updated_at: DateTime<Utc>,
+    // Of the route's airports; default for flights serialized before routes had them
+    #[sqlx(try_from = "String")]
+    #[serde(default)]
+    pub origin_timezone: AirportTimezone,
+    #[sqlx(try_from = "String")]
+    #[serde(default)]
+    pub destination_timezone: AirportTimezone,
+}
+
+// Times are sent in UTC and again as the local time of the airport they happen at, so a
+// Kyiv-Lisbon flight shows the clock at each end
+impl Serialize for Flight {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Localized<'a> {
+            flight_id: i32,
+            flight_number: &'a str,
+            marketing_flight_numbers: &'a FlightNumbers,
+            route_id: i32,
+            aircraft_id: i32,
+            departure_time: DateTime<Utc>,
+            departure_time_local: DateTime<FixedOffset>,
+            arrival_time: DateTime<Utc>,
+            arrival_time_local: DateTime<FixedOffset>,
+            actual_departure_time: Option<DateTime<Utc>>,
+            actual_arrival_time: Option<DateTime<Utc>>,
+            origin_timezone: AirportTimezone,
+            destination_timezone: AirportTimezone,
+            status: FlightStatus,
+            gate: &'a Option<String>,
+            gate_id: Option<i32>,
+            terminal: &'a Option<String>,
+            updated_at: DateTime<Utc>,
+        }
+
+        Localized {
+            flight_id: self.flight_id,
+            flight_number: &self.flight_number,
+            marketing_flight_numbers: &self.marketing_flight_numbers,
+            route_id: self.route_id,
+            aircraft_id: self.aircraft_id,
+            departure_time: self.departure_time,
+            departure_time_local: self.origin_timezone.local(self.departure_time),
+            arrival_time: self.arrival_time,
+            arrival_time_local: self.destination_timezone.local(self.arrival_time),
+            actual_departure_time: self.actual_departure_time,
+            actual_arrival_time: self.actual_arrival_time,
+            origin_timezone: self.origin_timezone,
+            destination_timezone: self.destination_timezone,
+            status: self.status,
+            gate: &self.gate,
+            gate_id: self.gate_id,
+            terminal: &self.terminal,
+            updated_at: self.updated_at,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FlightStatusChange {
+    pub history_id: i32,
+    pub flight_id: i32,
+    pub old_status: FlightStatus,
+    pub new_status: FlightStatus,
+    pub changed_at: DateTime<Utc>,
+}
+
+// Contact details of a passenger holding a ticket on a flight, one per ticket
+#[derive(Debug, Clone, FromRow)]
+pub struct TicketHolder {
+    pub ticket_number: String,
+    pub user_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub language: Locale,
+}
+
+// One booked passenger on the flight manifest
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct ManifestEntry {
+    pub ticket_id: i32,
+    pub ticket_number: String,
+    pub seat_number: String,
+    pub fare_class: FareClass,
+    pub passenger_type: PassengerType,
+    pub user_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub passport_number: Option<String>,
+    pub nationality: Option<String>,
+    pub checked_in: bool,
+    pub special_requests: Option<String>,
+    #[sqlx(skip)]
+    pub assistance: Vec<AssistanceRequest>,
+    // For the crew to know which passenger travels with an animal
+    #[sqlx(skip)]
+    pub pet: Option<TicketPet>,
+    // Travelling on the passenger's lap, without a seat
+    #[sqlx(skip)]
+    pub infant: Option<TicketInfant>,
+}
+
+// Sort key for seats like "9C" or "12A": numeric row first, then seat letter
+fn seat_sort_key(seat: &str) -> (u32, String) {
+    let digits: String = seat.chars().take_while(|c| c.is_ascii_digit()).collect();
+    (
+        digits.parse().unwrap_or(u32::MAX),
+        seat[digits.len()..].to_string(),
+    )
+}
+
+impl Flight {
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(&format!(
+            "SELECT {} FROM flights f JOIN routes r ON r.route_id = f.route_id WHERE f.flight_id = ?",
+            COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn find_all(
+        pool: &DbPool,
+        filter: &FlightFilter,
+        page: i32,
+        limit: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let offset = (page - 1) * limit;
+        let sql = format!(
+            "SELECT {} {} ORDER BY {} LIMIT ? OFFSET ?",
+            COLUMNS,
+            FILTER,
+            filter.order_by()
+        );
+
+        bind_filter!(sqlx::query_as::<_, Self>(&sql), filter)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+    }
+
+    // Keyset page in departure order, starting after the (departure_time, flight_id) of the
+    // previous page's last flight. The comparison is spelled out so MySQL can range-scan
+    // idx_flights_departure
+    pub async fn find_after(
+        pool: &DbPool,
+        filter: &FlightFilter,
+        after: Option<(DateTime<Utc>, i32)>,
+        limit: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let after_sql = filter.order.unwrap_or_default().after_sql();
+        let sql = format!(
+            r#"
+            SELECT {} {}
+              AND (? IS NULL OR f.departure_time {op} ? OR (f.departure_time = ? AND f.flight_id {op} ?))
+            ORDER BY {}
+            LIMIT ?
+            "#,
+            COLUMNS,
+            FILTER,
+            filter.order_by(),
+            op = after_sql
+        );
+        let (departure_time, flight_id) = after.unzip();
+
+        bind_filter!(sqlx::query_as::<_, Self>(&sql), filter)
+            .bind(departure_time)
+            .bind(departure_time)
+            .bind(departure_time)
+            .bind(flight_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+    }
+
+    pub async fn count(pool: &DbPool, filter: &FlightFilter) -> Result<i64, sqlx::Error> {
+        let sql = format!("SELECT COUNT(*) {}", FILTER);
+
+        bind_filter!(sqlx::query_scalar(&sql), filter)
+            .fetch_one(pool)
+            .await
+    }
+
+    // Every flight matching the filter, in listing order, for a download
+    pub async fn export(pool: &DbPool, filter: &FlightFilter, rows: RowSender<Self>) {
+        let sql = format!(
+            "SELECT {} {} ORDER BY {}",
+            COLUMNS,
+            FILTER,
+            filter.order_by()
+        );
+        export::forward(
+            bind_filter!(sqlx::query_as::<_, Self>(&sql), filter).fetch(pool),
+            &rows,
+        )
+        .await;
+    }
+
+    // Schedule a flight with the seats of its aircraft's layout and start its timeline with a
+    // `created` event
+    pub async fn insert(
+        pool: &DbPool,
+        flight: &NewFlight,
+        actor_id: Option<i32>,
+    ) -> Result<i32, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO flights (flight_number, route_id, aircraft_id, departure_time, arrival_time, status, gate)
+            VALUES (?, ?, ?, ?, ?, 'scheduled', ?)
+            "#,
+        )
+        .bind(&flight.flight_number)
+        .bind(flight.route_id)
+        .bind(flight.aircraft_id)
+        .bind(flight.departure_time)
+        .bind(flight.arrival_time)
+        .bind(&flight.gate)
+        .execute(&mut *tx)
+        .await?;
+        let flight_id = db::last_insert_id(&result) as i32;
+
+        FlightSeat::generate(&mut tx, flight_id, flight.aircraft_id).await?;
+
+        FlightEvent::record(
+            &mut *tx,
+            flight_id,
+            FlightEventType::Created,
+            Some(serde_json::json!({ "flight_number": flight.flight_number })),
+            actor_id,
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(flight_id)
+    }
+
+    // Move the flight from `from` to `to` and record the change in the history table.
+    // Returns false if the flight is no longer in `from` (changed concurrently).
+    pub async fn update_status(
+        pool: &DbPool,
+        id: i32,
+        from: FlightStatus,
+        to: FlightStatus,
+        actor_id: Option<i32>,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Departing and arriving stamp the actual time
+        let stamp = match to {
+            FlightStatus::Departed => ", actual_departure_time = UTC_TIMESTAMP()",
+            FlightStatus::Arrived => ", actual_arrival_time = UTC_TIMESTAMP()",
+            _ => "",
+        };
+        let result = sqlx::query(&format!(
+            "UPDATE flights SET status = ?{} WHERE flight_id = ? AND status = ?",
+            stamp
+        ))
+        .bind(to)
+        .bind(id)
+        .bind(from)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+
+        sqlx::query(
+            "INSERT INTO flight_status_history (flight_id, old_status, new_status, changed_at) VALUES (?, ?, ?, UTC_TIMESTAMP())",
+        )
+        .bind(id)
+        .bind(from)
+        .bind(to)
+        .execute(&mut *tx)
+        .await?;
+
+        FlightEvent::record(
+            &mut *tx,
+            id,
+            FlightEventType::for_status(to),
+            Some(serde_json::json!({ "from": from, "to": to })),
+            actor_id,
+        )
+        .await?;
+
+        // Disruptions reach webhooks and passengers through the outbox, so committing the change
+        // guarantees they are told
+        if matches!(to, FlightStatus::Cancelled | FlightStatus::Delayed) {
+            let flight = sqlx::query_as::<_, Flight>(&format!(
+                "SELECT {} FROM flights f JOIN routes r ON r.route_id = f.route_id WHERE f.flight_id = ?",
+                COLUMNS
+            ))
+            .bind(id)
+            .fetch_one(&mut *tx)
+            .await?;
+            let event = if to == FlightStatus::Cancelled {
+                DomainEvent::FlightCancelled { flight }
+            } else {
+                DomainEvent::FlightDelayed { flight }
+            };
+            OutboxEvent::record(&mut *tx, &event).await?;
+        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    // Give the flight a gate of its origin airport, or take its gate away with None, and note
+    // it on the flight's timeline
+    pub async fn set_gate(
+        pool: &DbPool,
+        id: i32,
+        gate: Option<&Gate>,
+        actor_id: Option<i32>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("UPDATE flights SET gate_id = ?, gate = ?, terminal = ? WHERE flight_id = ?")
+            .bind(gate.map(|g| g.gate_id))
+            .bind(gate.map(|g| &g.gate_code))
+            .bind(gate.and_then(|g| g.terminal.as_ref()))
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        FlightEvent::record(
+            &mut *tx,
+            id,
+            FlightEventType::GateSet,
+            Some(serde_json::json!({
+                "gate_id": gate.map(|g| g.gate_id),
+                "gate": gate.map(|g| &g.gate_code),
+                "terminal": gate.and_then(|g| g.terminal.as_ref()),
+            })),
+            actor_id,
+        )
+        .await?;
+
+        tx.commit().await
+    }
+
+    // Replace the marketing flight numbers the flight is sold under
+    pub async fn set_marketing_numbers(
+        pool: &DbPool,
+        id: i32,
+        flight_numbers: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM flight_codeshares WHERE flight_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        for flight_number in flight_numbers {
+            sqlx::query("INSERT INTO flight_codeshares (flight_id, flight_number) VALUES (?, ?)")
+                .bind(id)
+                .bind(flight_number)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        // Bump updated_at, which the flight's ETag is taken from
+        sqlx::query("UPDATE flights SET updated_at = CURRENT_TIMESTAMP(6) WHERE flight_id = ?")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await
+    }
+
+    // Flights still boarding after their departure time
+    pub async fn overdue_boarding(pool: &DbPool) -> Result<Vec<i32>, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT flight_id FROM flights WHERE status = 'boarding' AND departure_time <= UTC_TIMESTAMP()",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn status_history(
+        pool: &DbPool,
+        id: i32,
+    ) -> Result<Vec<FlightStatusChange>, sqlx::Error> {
+        sqlx::query_as::<_, FlightStatusChange>(
+            "SELECT * FROM flight_status_history WHERE flight_id = ? ORDER BY changed_at, history_id",
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn ticket_holders(pool: &DbPool, id: i32) -> Result<Vec<TicketHolder>, sqlx::Error> {
+        sqlx::query_as::<_, TicketHolder>(
+            r#"
+            SELECT t.ticket_number, u.user_id, u.first_name, u.last_name, u.email, u.phone,
+                   u.language
+            FROM tickets t
+            JOIN users u ON u.user_id = t.user_id
+            WHERE t.flight_id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // All booked passengers of the flight with the assistance they requested and their pets,
+    // ordered by seat
+    pub async fn manifest(pool: &DbPool, id: i32) -> Result<Vec<ManifestEntry>, sqlx::Error> {
+        let mut entries = sqlx::query_as::<_, ManifestEntry>(
+            r#"
+            SELECT t.ticket_id, t.ticket_number, t.seat_number, t.fare_class, t.passenger_type,
+                   u.user_id, u.first_name, u.last_name, u.passport_number, u.nationality,
+                   t.checked_in, t.special_requests
+            FROM tickets t
+            JOIN users u ON u.user_id = t.user_id
+            WHERE t.flight_id = ?
+            "#,
+        )
+        .bind(id)
+        .fetch_all(pool)
+        .await?;
+
+        let assistance = AssistanceRequest::find_by_flight(pool, id).await?;
+        let pets = TicketPet::find_by_flight(pool, id).await?;
+        let infants = TicketInfant::find_by_flight(pool, id).await?;
+        for entry in &mut entries {
+            entry.assistance = assistance
+                .iter()
+                .filter(|request| request.ticket_id == entry.ticket_id)
+                .cloned()
+                .collect();
+            entry.pet = pets
+                .iter()
+                .find(|pet| pet.ticket_id == entry.ticket_id)
+                .cloned();
+            entry.infant = infants
+                .iter()
+                .find(|infant| infant.ticket_id == entry.ticket_id)
+                .cloned();
+        }
+
+        entries.sort_by_cached_key(|entry| seat_sort_key(&entry.seat_number));
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_transitions_follow_the_flight_lifecycle() {
+        use FlightStatus::*;
+
+        let all = [Scheduled, Boarding, Departed, Arrived, Delayed, Cancelled];
+        // Every transition allowed; any other pair is forbidden
+        let allowed = [
+            (Scheduled, Boarding),
+            (Scheduled, Delayed),
+            (Scheduled, Cancelled),
+            (Delayed, Scheduled),
+            (Delayed, Boarding),
+            (Delayed, Cancelled),
+            (Boarding, Departed),
+            (Boarding, Delayed),
+            (Boarding, Cancelled),
+            (Departed, Arrived),
+        ];
+
+        for from in all {
+            for to in all {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    allowed.contains(&(from, to)),
+                    "{} -> {}",
+                    from.as_str(),
+                    to.as_str()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::FlightStatus;
+use crate::db::{Db, DbPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum FlightEventType {
+    Created,
+    CrewAssigned,
+    GateSet,
+    Rescheduled,
+    Delayed,
+    BoardingStarted,
+    Departed,
+    Arrived,
+    Cancelled,
+}
+
+impl FlightEventType {
+    // Event recorded when a flight enters the given status
+    pub fn for_status(status: FlightStatus) -> Self {
+        match status {
+            FlightStatus::Scheduled => FlightEventType::Rescheduled,
+            FlightStatus::Delayed => FlightEventType::Delayed,
+            FlightStatus::Boarding => FlightEventType::BoardingStarted,
+            FlightStatus::Departed => FlightEventType::Departed,
+            FlightStatus::Arrived => FlightEventType::Arrived,
+            FlightStatus::Cancelled => FlightEventType::Cancelled,
+        }
+    }
+}
+
+// One entry of a flight's timeline
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FlightEvent {
+    pub event_id: i64,
+    pub flight_id: i32,
+    pub event_type: FlightEventType,
+    pub details: Option<serde_json::Value>,
+    pub actor_id: Option<i32>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl FlightEvent {
+    // Append an event; pass a transaction to record it atomically with the change it describes
+    pub async fn record<'e, E>(
+        executor: E,
+        flight_id: i32,
+        event_type: FlightEventType,
+        details: Option<serde_json::Value>,
+        actor_id: Option<i32>,
+    ) -> Result<(), sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Db>,
+    {
+        sqlx::query(
+            r#"
+            INSERT INTO flight_events (flight_id, event_type, details, actor_id, occurred_at)
+            VALUES (?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(flight_id)
+        .bind(event_type)
+        .bind(details)
+        .bind(actor_id)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn timeline(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM flight_events WHERE flight_id = ? ORDER BY occurred_at, event_id",
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+}
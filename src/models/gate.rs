@@ -0,0 +1,122 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::db::{self, DbPool};
+use crate::validation::not_blank;
+
+// A departure gate of an airport
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Gate {
+    pub gate_id: i32,
+    pub airport_code: String,
+    pub gate_code: String,
+    pub terminal: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Gate to add to an airport
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewGate {
+    #[validate(custom(function = "not_blank"), length(max = 10))]
+    pub gate_code: String,
+    #[validate(custom(function = "not_blank"), length(max = 10))]
+    pub terminal: Option<String>,
+}
+
+// A flight holding a gate at the same time as the one being given it
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct GateClash {
+    pub flight_id: i32,
+    pub flight_number: String,
+    pub departure_time: DateTime<Utc>,
+}
+
+impl Gate {
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM gates WHERE gate_id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_by_airport(
+        pool: &DbPool,
+        airport_code: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM gates WHERE airport_code = ? ORDER BY terminal, gate_code",
+        )
+        .bind(airport_code)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &DbPool,
+        airport_code: &str,
+        gate: &NewGate,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO gates (airport_code, gate_code, terminal, created_at)
+            VALUES (?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(airport_code)
+        .bind(gate.gate_code.trim())
+        .bind(gate.terminal.as_deref().map(str::trim))
+        .execute(pool)
+        .await?;
+
+        Self::find_by_id(pool, db::last_insert_id(&result) as i32)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    // Other flights, not cancelled, that hold the gate while the flight would. A flight holds
+    // its gate from `occupancy_minutes` before departure until it departs
+    pub async fn clashes(
+        pool: &DbPool,
+        gate_id: i32,
+        flight_id: i32,
+        occupancy_minutes: u32,
+    ) -> Result<Vec<GateClash>, sqlx::Error> {
+        sqlx::query_as::<_, GateClash>(
+            r#"
+            SELECT o.flight_id, o.flight_number, o.departure_time
+            FROM flights f
+            JOIN flights o
+                ON o.gate_id = ?
+                AND o.flight_id <> f.flight_id
+                AND o.status <> 'cancelled'
+                AND o.departure_time > DATE_SUB(f.departure_time, INTERVAL ? MINUTE)
+                AND o.departure_time < DATE_ADD(f.departure_time, INTERVAL ? MINUTE)
+            WHERE f.flight_id = ?
+            ORDER BY o.departure_time
+            "#,
+        )
+        .bind(gate_id)
+        .bind(occupancy_minutes)
+        .bind(occupancy_minutes)
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+// Human readable list such as "PS101 (2026-11-02 08:00 UTC)"
+pub fn describe_clashes(clashes: &[GateClash]) -> String {
+    clashes
+        .iter()
+        .map(|c| {
+            format!(
+                "{} ({} UTC)",
+                c.flight_number,
+                c.departure_time.format("%Y-%m-%d %H:%M")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
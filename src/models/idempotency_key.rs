@@ -0,0 +1,139 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+use crate::db::DbPool;
+
+// Seconds after which a request that never stored its response counts as abandoned, e.g.
+// because the server restarted while handling it
+const ABANDONED_AFTER: i64 = 300;
+
+// A caller's Idempotency-Key with the response of the request first sent under it
+#[derive(Debug, Clone, FromRow)]
+pub struct IdempotencyKey {
+    pub user_id: i32,
+    pub idempotency_key: String,
+    pub request_hash: String,
+    pub status_code: Option<i16>,
+    pub response_body: Option<Vec<u8>>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Outcome of presenting a key
+pub enum Claim {
+    // First use: run the request, then `complete` or `release` the key
+    Acquired,
+    // The first request is still running
+    InProgress,
+    // The key was used for a different request
+    Mismatch,
+    Replay { status_code: u16, body: Vec<u8> },
+}
+
+impl IdempotencyKey {
+    // Take the key for this request, or report what it was used for. Keys older than
+    // `ttl_secs` are forgotten and can be used again
+    pub async fn claim(
+        pool: &DbPool,
+        user_id: i32,
+        key: &str,
+        request_hash: &str,
+        ttl_secs: u64,
+    ) -> Result<Claim, sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE user_id = ? AND idempotency_key = ?
+              AND (created_at < UTC_TIMESTAMP() - INTERVAL ? SECOND
+                   OR (status_code IS NULL AND created_at < UTC_TIMESTAMP() - INTERVAL ? SECOND))
+            "#,
+        )
+        .bind(user_id)
+        .bind(key)
+        .bind(ttl_secs as i64)
+        .bind(ABANDONED_AFTER)
+        .execute(pool)
+        .await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT IGNORE INTO idempotency_keys (user_id, idempotency_key, request_hash, created_at)
+            VALUES (?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(user_id)
+        .bind(key)
+        .bind(request_hash)
+        .execute(pool)
+        .await?
+        .rows_affected();
+        if inserted == 1 {
+            return Ok(Claim::Acquired);
+        }
+
+        let Some(existing) = sqlx::query_as::<_, Self>(
+            "SELECT * FROM idempotency_keys WHERE user_id = ? AND idempotency_key = ?",
+        )
+        .bind(user_id)
+        .bind(key)
+        .fetch_optional(pool)
+        .await?
+        else {
+            // Released by the first request between our insert and select
+            return Ok(Claim::InProgress);
+        };
+
+        if existing.request_hash != request_hash {
+            return Ok(Claim::Mismatch);
+        }
+        Ok(match (existing.status_code, existing.response_body) {
+            (Some(status_code), Some(body)) => Claim::Replay {
+                status_code: status_code as u16,
+                body,
+            },
+            _ => Claim::InProgress,
+        })
+    }
+
+    // Store the response to replay for retries
+    pub async fn complete(
+        pool: &DbPool,
+        user_id: i32,
+        key: &str,
+        status_code: u16,
+        body: &[u8],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE idempotency_keys SET status_code = ?, response_body = ?
+            WHERE user_id = ? AND idempotency_key = ?
+            "#,
+        )
+        .bind(status_code as i16)
+        .bind(body)
+        .bind(user_id)
+        .bind(key)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // Free the key after a failure the client may retry, so the retry runs the request again
+    pub async fn release(pool: &DbPool, user_id: i32, key: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE user_id = ? AND idempotency_key = ?")
+            .bind(user_id)
+            .bind(key)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn delete_expired(pool: &DbPool, ttl_secs: u64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM idempotency_keys WHERE created_at < UTC_TIMESTAMP() - INTERVAL ? SECOND",
+        )
+        .bind(ttl_secs as i64)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
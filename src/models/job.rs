@@ -0,0 +1,122 @@
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use crate::db::{self, Db, DbPool};
+
+// Wait before the first retry, doubled after every further failure
+const RETRY_BASE_SECS: i64 = 10;
+
+// A job claimed by a runner, with its attempt already counted
+#[derive(Debug, Clone, FromRow)]
+pub struct QueuedJob {
+    pub job_id: i64,
+    pub kind: String,
+    pub payload: String,
+    pub attempts: i32,
+    pub max_attempts: i32,
+}
+
+impl QueuedJob {
+    // Queue a job to run once `delay_secs` have passed; pass a transaction to queue it only if
+    // the rest of the transaction commits
+    pub async fn insert<'e, E>(
+        executor: E,
+        kind: &str,
+        payload: &serde_json::Value,
+        max_attempts: i32,
+        delay_secs: i64,
+    ) -> Result<i64, sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Db>,
+    {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO jobs (kind, payload, status, attempts, max_attempts, run_at, created_at)
+            VALUES (?, ?, 'queued', 0, ?, UTC_TIMESTAMP() + INTERVAL ? SECOND, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(kind)
+        .bind(payload.to_string())
+        .bind(max_attempts)
+        .bind(delay_secs)
+        .execute(executor)
+        .await?;
+        Ok(db::last_insert_id(&result))
+    }
+
+    // Lease up to `limit` due jobs for `lease_secs`, oldest first. Jobs whose runner died keep
+    // their lease until it ends and are then taken over, so a job may run more than once
+    pub async fn claim(
+        pool: &DbPool,
+        limit: i32,
+        lease_secs: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let claim_id = Uuid::new_v4().simple().to_string();
+        sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = 'running', attempts = attempts + 1, locked_by = ?,
+                locked_until = UTC_TIMESTAMP() + INTERVAL ? SECOND
+            WHERE (status = 'queued' AND run_at <= UTC_TIMESTAMP())
+               OR (status = 'running' AND locked_until < UTC_TIMESTAMP())
+            ORDER BY run_at
+            LIMIT ?
+            "#,
+        )
+        .bind(&claim_id)
+        .bind(lease_secs)
+        .bind(limit)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT job_id, kind, payload, attempts, max_attempts
+            FROM jobs
+            WHERE locked_by = ? AND status = 'running'
+            ORDER BY run_at
+            "#,
+        )
+        .bind(&claim_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // A finished job is not kept
+    pub async fn mark_succeeded(pool: &DbPool, job_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM jobs WHERE job_id = ?")
+            .bind(job_id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    // Schedule the retry with exponential backoff, or give up after the last attempt or when
+    // retrying cannot help
+    pub async fn mark_failed(
+        &self,
+        pool: &DbPool,
+        error: &str,
+        retry: bool,
+    ) -> Result<(), sqlx::Error> {
+        let give_up = !retry || self.attempts >= self.max_attempts;
+        let retry_in = RETRY_BASE_SECS << (self.attempts - 1).clamp(0, 10);
+        let error: String = error.chars().take(255).collect();
+
+        sqlx::query(
+            r#"
+            UPDATE jobs
+            SET status = IF(?, 'failed', 'queued'), last_error = ?, locked_by = NULL,
+                locked_until = NULL, run_at = UTC_TIMESTAMP() + INTERVAL ? SECOND
+            WHERE job_id = ?
+            "#,
+        )
+        .bind(give_up)
+        .bind(error)
+        .bind(retry_in)
+        .bind(self.job_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::db::{self, DbPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MaintenanceType {
+    Inspection,
+    Repair,
+    Overhaul,
+    Modification,
+}
+
+// Maintenance of an aircraft; the aircraft is grounded from `grounded_from` until `grounded_to`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MaintenanceRecord {
+    pub maintenance_id: i32,
+    pub aircraft_id: i32,
+    pub maintenance_type: MaintenanceType,
+    pub grounded_from: DateTime<Utc>,
+    pub grounded_to: DateTime<Utc>,
+    pub notes: Option<String>,
+    pub recorded_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Maintenance to record on an aircraft
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewMaintenanceRecord {
+    pub maintenance_type: MaintenanceType,
+    pub grounded_from: DateTime<Utc>,
+    pub grounded_to: DateTime<Utc>,
+    #[validate(length(max = 2000))]
+    pub notes: Option<String>,
+}
+
+impl MaintenanceRecord {
+    pub async fn find_by_id(
+        pool: &DbPool,
+        aircraft_id: i32,
+        id: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM aircraft_maintenance WHERE maintenance_id = ? AND aircraft_id = ?",
+        )
+        .bind(id)
+        .bind(aircraft_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    // Records of an aircraft, latest grounding first
+    pub async fn find_by_aircraft(
+        pool: &DbPool,
+        aircraft_id: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM aircraft_maintenance WHERE aircraft_id = ? ORDER BY grounded_from DESC, maintenance_id DESC",
+        )
+        .bind(aircraft_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // The first maintenance that grounds the aircraft at some point between `from` and `to`
+    pub async fn grounding(
+        pool: &DbPool,
+        aircraft_id: i32,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM aircraft_maintenance
+            WHERE aircraft_id = ? AND grounded_from < ? AND grounded_to > ?
+            ORDER BY grounded_from
+            LIMIT 1
+            "#,
+        )
+        .bind(aircraft_id)
+        .bind(to)
+        .bind(from)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub async fn insert(
+        pool: &DbPool,
+        aircraft_id: i32,
+        record: &NewMaintenanceRecord,
+        recorded_by: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO aircraft_maintenance
+                (aircraft_id, maintenance_type, grounded_from, grounded_to, notes, recorded_by, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(aircraft_id)
+        .bind(record.maintenance_type)
+        .bind(record.grounded_from)
+        .bind(record.grounded_to)
+        .bind(&record.notes)
+        .bind(recorded_by)
+        .execute(pool)
+        .await?;
+
+        Self::find_by_id(pool, aircraft_id, db::last_insert_id(&result) as i32)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn delete(pool: &DbPool, aircraft_id: i32, id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM aircraft_maintenance WHERE maintenance_id = ? AND aircraft_id = ?",
+        )
+        .bind(id)
+        .bind(aircraft_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::db::DbPool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Meal {
+    Standard,
+    Vegetarian,
+    Vegan,
+    Halal,
+    Kosher,
+    GlutenFree,
+    Child,
+}
+
+impl Meal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Meal::Standard => "standard",
+            Meal::Vegetarian => "vegetarian",
+            Meal::Vegan => "vegan",
+            Meal::Halal => "halal",
+            Meal::Kosher => "kosher",
+            Meal::GlutenFree => "gluten_free",
+            Meal::Child => "child",
+        }
+    }
+
+    // Meals catered on the flight
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_scalar::<_, Self>(
+            "SELECT meal FROM flight_meals WHERE flight_id = ? ORDER BY meal",
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Replace the meals of a flight in one transaction. Tickets that chose a meal no longer
+    // catered lose their choice
+    pub async fn replace_for_flight(
+        pool: &DbPool,
+        flight_id: i32,
+        meals: &[Meal],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM flight_meals WHERE flight_id = ?")
+            .bind(flight_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for meal in meals {
+            sqlx::query("INSERT INTO flight_meals (flight_id, meal) VALUES (?, ?)")
+                .bind(flight_id)
+                .bind(meal)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE tickets SET meal = NULL
+            WHERE flight_id = ?
+              AND meal IS NOT NULL
+              AND meal NOT IN (SELECT meal FROM flight_meals WHERE flight_id = ?)
+            "#,
+        )
+        .bind(flight_id)
+        .bind(flight_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+}
+
+// How many meals of one kind to load; `meal` is None for passengers who did not choose
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct CateringLine {
+    pub meal: Option<Meal>,
+    pub count: i64,
+}
+
+impl CateringLine {
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT meal, COUNT(*) AS count
+            FROM tickets
+            WHERE flight_id = ?
+            GROUP BY meal
+            ORDER BY meal IS NULL, meal
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+}
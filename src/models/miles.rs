@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::db::{self, DbPool};
+
+// Discount granted per redeemed mile
+pub const MILE_VALUE: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum MilesEntryType {
+    Accrual,
+    Redemption,
+}
+
+// One movement on a user's miles account; redemptions are negative
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MilesEntry {
+    pub entry_id: i64,
+    pub user_id: i32,
+    pub ticket_id: Option<i32>,
+    pub entry_type: MilesEntryType,
+    pub miles: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MilesEntry {
+    pub async fn balance(pool: &DbPool, user_id: i32) -> Result<i64, sqlx::Error> {
+        let (balance,): (i64,) = sqlx::query_as(
+            "SELECT CAST(COALESCE(SUM(miles), 0) AS SIGNED) FROM miles_ledger WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+        Ok(balance)
+    }
+
+    pub async fn recent(pool: &DbPool, user_id: i32, limit: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM miles_ledger WHERE user_id = ? ORDER BY created_at DESC, entry_id DESC LIMIT ?",
+        )
+        .bind(user_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
+    // The user's whole ledger, oldest first
+    pub async fn find_by_user(pool: &DbPool, user_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM miles_ledger WHERE user_id = ? ORDER BY created_at, entry_id",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Credit miles for every ticket on an arrived flight that has not been credited yet.
+    // Business earns 1.5x and first 2x the route distance. Safe to run repeatedly.
+    pub async fn accrue_arrived_flights(pool: &DbPool) -> Result<u64, sqlx::Error> {
+        Self::accrue(pool, None).await
+    }
+
+    // The same for one flight, if it has arrived
+    pub async fn accrue_flight(pool: &DbPool, flight_id: i32) -> Result<u64, sqlx::Error> {
+        Self::accrue(pool, Some(flight_id)).await
+    }
+
+    async fn accrue(pool: &DbPool, flight_id: Option<i32>) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO miles_ledger (user_id, ticket_id, entry_type, miles, created_at)
+            SELECT t.user_id, t.ticket_id, 'accrual',
+                   ROUND(r.distance * CASE t.fare_class
+                       WHEN 'first' THEN 2.0
+                       WHEN 'business' THEN 1.5
+                       ELSE 1.0 END),
+                   UTC_TIMESTAMP()
+            FROM tickets t
+            JOIN flights f ON f.flight_id = t.flight_id
+            JOIN routes r ON r.route_id = f.route_id
+            WHERE f.status = 'arrived'
+              AND (? IS NULL OR f.flight_id = ?)
+              AND NOT EXISTS (
+                  SELECT 1 FROM miles_ledger m
+                  WHERE m.ticket_id = t.ticket_id AND m.entry_type = 'accrual'
+              )
+            "#,
+        )
+        .bind(flight_id)
+        .bind(flight_id)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    // Spend miles against a ticket. Returns None if the balance is too low.
+    pub async fn redeem(
+        pool: &DbPool,
+        user_id: i32,
+        ticket_id: i32,
+        miles: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        // Lock the user so concurrent redemptions cannot overspend
+        sqlx::query("SELECT user_id FROM users WHERE user_id = ? FOR UPDATE")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let (balance,): (i64,) = sqlx::query_as(
+            "SELECT CAST(COALESCE(SUM(miles), 0) AS SIGNED) FROM miles_ledger WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if balance < miles as i64 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO miles_ledger (user_id, ticket_id, entry_type, miles, created_at)
+            VALUES (?, ?, 'redemption', ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(user_id)
+        .bind(ticket_id)
+        .bind(-miles)
+        .execute(&mut *tx)
+        .await?;
+
+        let entry = sqlx::query_as::<_, Self>("SELECT * FROM miles_ledger WHERE entry_id = ?")
+            .bind(db::last_insert_id(&result))
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(entry))
+    }
+}
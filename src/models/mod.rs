@@ -1,3 +1,107 @@
+pub mod aircraft;
+pub mod ancillary;
+pub mod api_key;
+pub mod assistance;
+pub mod audit_log;
+pub mod baggage;
+pub mod baggage_allowance;
+pub mod booking_group;
+pub mod cabin_layout;
+pub mod cargo;
+pub mod crew;
+pub mod data_export;
+pub mod exchange_rate;
+pub mod fare_class;
+pub mod flight;
+pub mod flight_event;
+pub mod gate;
+pub mod idempotency_key;
+pub mod job;
+pub mod maintenance;
+pub mod meal;
+pub mod miles;
+pub mod otp_code;
+pub mod outbox;
+pub mod passenger;
+pub mod password_reset;
+pub mod pet;
+pub mod promo_code;
+pub mod refresh_token;
+pub mod report;
+pub mod revoked_token;
 pub mod route;
+pub mod seat_block;
+pub mod seat_change;
+pub mod session;
+pub mod sort;
+pub mod status_token;
+pub mod ticket;
+pub mod ticket_upgrade;
+pub mod translation;
+pub mod travel_companion;
+pub mod two_factor;
+pub mod user;
+pub mod user_document;
+pub mod user_permissions;
+pub mod webhook;
 
-pub use route::Route;
+pub use aircraft::{Aircraft, CargoLimits, PetLimits};
+pub use ancillary::{
+    AncillaryProduct, AncillaryPurchase, FlightAncillary, FlightAncillaryOffer,
+    NewAncillaryProduct, TicketAncillary,
+};
+pub use api_key::{ApiKey, ApiScope};
+pub use assistance::{AssistanceRequest, AssistanceType, NewAssistanceRequest};
+pub use audit_log::{AuditLog, AuditLogFilter};
+pub use baggage::{Bag, BagScan, BaggageStatus, NewBag};
+pub use baggage_allowance::{BaggageAllowance, BaggagePurchase, NewBaggageAllowance};
+pub use booking_group::{BookingGroup, GroupDiscount, GroupPassenger, GroupSeating};
+pub use cabin_layout::{CabinLayout, CabinSection, FlightSeat, NewCabinLayout};
+pub use cargo::{CargoEvent, CargoLoad, CargoStatus, NewShipment, Shipment};
+pub use crew::{CrewMember, CrewRequirement, CrewRole, CrewShortfall, DutyRules};
+pub use data_export::{DataExport, DataExportStatus};
+pub use exchange_rate::ExchangeRate;
+pub use fare_class::{FareClass, FareClassInventory, FareOption, FlightFareClass};
+pub use flight::{
+    Flight, FlightFilter, FlightNumbers, FlightSort, FlightStatus, FlightStatusChange,
+    ManifestEntry, NewFlight, TicketHolder,
+};
+pub use flight_event::{FlightEvent, FlightEventType};
+pub use gate::{Gate, GateClash, NewGate};
+pub use idempotency_key::{Claim, IdempotencyKey};
+pub use job::QueuedJob;
+pub use maintenance::{MaintenanceRecord, MaintenanceType, NewMaintenanceRecord};
+pub use meal::{CateringLine, Meal};
+pub use miles::MilesEntry;
+pub use otp_code::OtpCode;
+pub use outbox::{DomainEvent, OutboxEvent};
+pub use passenger::{NewTicketInfant, PassengerFares, PassengerType, TicketInfant};
+pub use password_reset::PasswordReset;
+pub use pet::{NewTicketPet, PetFees, PetPlacement, PetSpecies, TicketPet};
+pub use promo_code::{DiscountType, NewPromoCode, PromoCode};
+pub use refresh_token::{RefreshToken, Rotation};
+pub use report::{
+    DemandAnalytics, DemandFilter, DemographicCount, DemographicGroup, DemographicsFilter,
+    DemographicsGrouping, FlightLoad, FlightPunctuality, LeadTimeBucket, LoadFactorFilter,
+    OnTimeFilter, OnTimePerformance, PunctualityGrouping, RevenueEntry, RevenueFilter,
+    RevenueGrouping, RevenueRow, RouteDemand, RouteLoad, WeekdayDemand,
+};
+pub use revoked_token::RevokedToken;
+pub use route::{AirportTimezone, Route};
+pub use seat_block::{Occupancy, SeatBlock, SeatBlockReason};
+pub use seat_change::SeatChange;
+pub use session::Session;
+pub use sort::SortOrder;
+pub use status_token::{PublicFlightStatus, StatusToken};
+pub use ticket::Ticket;
+pub use ticket_upgrade::{TicketUpgrade, UpgradeOutcome};
+pub use translation::{LocalizedText, Translation};
+pub use travel_companion::TravelCompanion;
+pub use two_factor::UserTotp;
+pub use user::{
+    Gender, NewPassenger, NewUser, StaffPosition, UpdateProfile, User, UserFilter, UserRole,
+    UserSort,
+};
+pub use user_document::{DocumentType, NewUserDocument, UserDocument};
+pub use user_permissions::UserPermissions;
+pub use webhook::{PendingDelivery, Webhook, WebhookDelivery, WebhookEvent};
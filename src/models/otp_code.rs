@@ -0,0 +1,109 @@
+use uuid::Uuid;
+
+use crate::db::DbPool;
+
+// Wrong guesses allowed per code before it is burned
+pub const MAX_ATTEMPTS: i32 = 5;
+// Codes that may be sent to one phone within `RATE_LIMIT_WINDOW_MINUTES`
+pub const MAX_CODES_PER_WINDOW: i64 = 3;
+pub const RATE_LIMIT_WINDOW_MINUTES: i64 = 15;
+
+// One-time login codes sent by SMS; the code is stored hashed together with the phone
+pub struct OtpCode;
+
+impl OtpCode {
+    // Codes sent to the phone within the rate limit window
+    pub async fn recent_count(pool: &DbPool, phone: &str) -> Result<i64, sqlx::Error> {
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM otp_codes WHERE phone = ? AND created_at > UTC_TIMESTAMP() - INTERVAL ? MINUTE",
+        )
+        .bind(phone)
+        .bind(RATE_LIMIT_WINDOW_MINUTES)
+        .fetch_one(pool)
+        .await?;
+        Ok(count)
+    }
+
+    // Generate a 6-digit code for the phone, superseding any earlier unused code
+    pub async fn issue(
+        pool: &DbPool,
+        phone: &str,
+        lifetime_secs: u64,
+    ) -> Result<String, sqlx::Error> {
+        let code = format!("{:06}", Uuid::new_v4().as_u128() % 1_000_000);
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE otp_codes SET used_at = UTC_TIMESTAMP() WHERE phone = ? AND used_at IS NULL",
+        )
+        .bind(phone)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO otp_codes (phone, code_hash, attempts, expires_at, created_at)
+            VALUES (?, SHA2(CONCAT(?, ':', ?), 256), 0, UTC_TIMESTAMP() + INTERVAL ? SECOND, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(phone)
+        .bind(phone)
+        .bind(&code)
+        .bind(lifetime_secs as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(code)
+    }
+
+    // Check a code against the phone's active code. A match spends it; a miss counts
+    // as an attempt and the code is burned after `MAX_ATTEMPTS` misses
+    pub async fn verify(pool: &DbPool, phone: &str, code: &str) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let active: Option<(i64, bool)> = sqlx::query_as(
+            r#"
+            SELECT otp_id, code_hash = SHA2(CONCAT(?, ':', ?), 256)
+            FROM otp_codes
+            WHERE phone = ? AND used_at IS NULL AND expires_at > UTC_TIMESTAMP()
+            ORDER BY created_at DESC
+            LIMIT 1
+            FOR UPDATE
+            "#,
+        )
+        .bind(phone)
+        .bind(code)
+        .bind(phone)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((otp_id, matches)) = active else {
+            return Ok(false);
+        };
+
+        if matches {
+            sqlx::query("UPDATE otp_codes SET used_at = UTC_TIMESTAMP() WHERE otp_id = ?")
+                .bind(otp_id)
+                .execute(&mut *tx)
+                .await?;
+        } else {
+            // MySQL applies assignments left to right, so `attempts` below is already incremented
+            sqlx::query(
+                r#"
+                UPDATE otp_codes
+                SET attempts = attempts + 1,
+                    used_at = IF(attempts >= ?, UTC_TIMESTAMP(), NULL)
+                WHERE otp_id = ?
+                "#,
+            )
+            .bind(MAX_ATTEMPTS)
+            .bind(otp_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(matches)
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::{Flight, Ticket};
+use crate::db::{Db, DbConnection};
+
+// Something that happened to the airline's data, with the state it left behind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+    #[serde(rename = "flight.cancelled")]
+    FlightCancelled { flight: Flight },
+    #[serde(rename = "flight.delayed")]
+    FlightDelayed { flight: Flight },
+    #[serde(rename = "ticket.booked")]
+    TicketBooked { ticket: Ticket },
+}
+
+impl DomainEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DomainEvent::FlightCancelled { .. } => "flight.cancelled",
+            DomainEvent::FlightDelayed { .. } => "flight.delayed",
+            DomainEvent::TicketBooked { .. } => "ticket.booked",
+        }
+    }
+
+    // Kind and id of the record the event is about
+    pub fn aggregate(&self) -> (&'static str, i32) {
+        match self {
+            DomainEvent::FlightCancelled { flight } | DomainEvent::FlightDelayed { flight } => {
+                ("flight", flight.flight_id)
+            }
+            DomainEvent::TicketBooked { ticket } => ("ticket", ticket.ticket_id),
+        }
+    }
+}
+
+// A recorded event waiting for the relay
+#[derive(Debug, Clone, FromRow)]
+pub struct OutboxEvent {
+    pub event_id: i64,
+    pub event_type: String,
+    pub payload: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OutboxEvent {
+    // Write the event; pass the transaction of the change it describes so both commit together
+    pub async fn record<'e, E>(executor: E, event: &DomainEvent) -> Result<(), sqlx::Error>
+    where
+        E: sqlx::Executor<'e, Database = Db>,
+    {
+        let (aggregate_type, aggregate_id) = event.aggregate();
+        let payload = serde_json::to_string(event).expect("domain events serialize to JSON");
+        sqlx::query(
+            r#"
+            INSERT INTO outbox_events (event_type, aggregate_type, aggregate_id, payload, created_at)
+            VALUES (?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(event.name())
+        .bind(aggregate_type)
+        .bind(aggregate_id)
+        .bind(payload)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    // The oldest unpublished events, locked until the transaction ends; events another relay
+    // holds are skipped rather than waited for
+    pub async fn lock_unpublished(
+        conn: &mut DbConnection,
+        limit: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT event_id, event_type, payload, created_at
+            FROM outbox_events
+            WHERE published_at IS NULL
+            ORDER BY event_id
+            LIMIT ?
+            FOR UPDATE SKIP LOCKED
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(conn)
+        .await
+    }
+
+    pub async fn mark_published(conn: &mut DbConnection, event_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE outbox_events SET published_at = UTC_TIMESTAMP() WHERE event_id = ?")
+            .bind(event_id)
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    // The event itself; None for a type this version does not know
+    pub fn event(&self) -> Option<DomainEvent> {
+        serde_json::from_str(&self.payload).ok()
+    }
+}
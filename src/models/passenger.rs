@@ -0,0 +1,189 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::db::{self, DbPool};
+use crate::validation::{in_the_past, not_blank};
+
+// Who travels on a ticket, by age on the departure date
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PassengerType {
+    #[default]
+    Adult,
+    Child,
+    // Under 2, in a seat of their own; infants on a lap are a `TicketInfant`
+    Infant,
+}
+
+impl PassengerType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PassengerType::Adult => "adult",
+            PassengerType::Child => "child",
+            PassengerType::Infant => "infant",
+        }
+    }
+
+    // Infants are under 2 and children under 12 on `day`
+    pub fn on(date_of_birth: NaiveDate, day: NaiveDate) -> Self {
+        match day.years_since(date_of_birth) {
+            None | Some(0..=1) => PassengerType::Infant,
+            Some(2..=11) => PassengerType::Child,
+            Some(_) => PassengerType::Adult,
+        }
+    }
+}
+
+// Fares of children and infants as a percentage of the adult fare
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PassengerFares {
+    pub child_percent: f64,
+    pub lap_infant_percent: f64,
+}
+
+impl PassengerFares {
+    // Rounded to cents. Infants in a seat of their own pay the child fare
+    pub fn fare(&self, adult_fare: f64, passenger_type: PassengerType) -> f64 {
+        let fare = match passenger_type {
+            PassengerType::Adult => adult_fare,
+            PassengerType::Child | PassengerType::Infant => adult_fare * self.child_percent / 100.0,
+        };
+        (fare * 100.0).round() / 100.0
+    }
+
+    pub fn lap_infant_fare(&self, adult_fare: f64) -> f64 {
+        let fare = adult_fare * self.lap_infant_percent / 100.0;
+        (fare * 100.0).round() / 100.0
+    }
+}
+
+// An infant travelling on the lap of a ticket holder
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TicketInfant {
+    pub infant_id: i32,
+    pub ticket_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub date_of_birth: NaiveDate,
+    pub fare: f64,
+    pub currency: String,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Infant to add to a ticket
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewTicketInfant {
+    #[validate(custom(function = "not_blank"), length(max = 50))]
+    pub first_name: String,
+    #[validate(custom(function = "not_blank"), length(max = 50))]
+    pub last_name: String,
+    #[validate(custom(function = "in_the_past"))]
+    pub date_of_birth: NaiveDate,
+}
+
+impl TicketInfant {
+    pub async fn find_by_ticket(
+        pool: &DbPool,
+        ticket_id: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM ticket_infants WHERE ticket_id = ?")
+            .bind(ticket_id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    // Infants of every ticket of the flight, for its manifest
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT i.*
+            FROM ticket_infants i
+            JOIN tickets t ON t.ticket_id = i.ticket_id
+            WHERE t.flight_id = ?
+            ORDER BY i.infant_id
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &DbPool,
+        ticket_id: i32,
+        infant: &NewTicketInfant,
+        fare: (f64, &str),
+        created_by: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO ticket_infants
+                (ticket_id, first_name, last_name, date_of_birth, fare, currency, created_by,
+                 created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(infant.first_name.trim())
+        .bind(infant.last_name.trim())
+        .bind(infant.date_of_birth)
+        .bind(fare.0)
+        .bind(fare.1)
+        .bind(created_by)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM ticket_infants WHERE infant_id = ?")
+            .bind(db::last_insert_id(&result) as i32)
+            .fetch_one(pool)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn passenger_type_follows_age_on_the_day() {
+        let born = date(2024, 3, 10);
+
+        assert_eq!(
+            PassengerType::on(born, date(2026, 3, 9)),
+            PassengerType::Infant
+        );
+        assert_eq!(
+            PassengerType::on(born, date(2026, 3, 10)),
+            PassengerType::Child
+        );
+        assert_eq!(
+            PassengerType::on(born, date(2036, 3, 9)),
+            PassengerType::Child
+        );
+        assert_eq!(
+            PassengerType::on(born, date(2036, 3, 10)),
+            PassengerType::Adult
+        );
+    }
+
+    #[test]
+    fn children_and_infants_pay_a_share_of_the_adult_fare() {
+        let fares = PassengerFares {
+            child_percent: 75.0,
+            lap_infant_percent: 10.0,
+        };
+
+        assert_eq!(fares.fare(123.45, PassengerType::Adult), 123.45);
+        assert_eq!(fares.fare(123.45, PassengerType::Child), 92.59);
+        assert_eq!(fares.fare(100.0, PassengerType::Infant), 75.0);
+        assert_eq!(fares.lap_infant_fare(123.4), 12.34);
+    }
+}
@@ -0,0 +1,80 @@
+use uuid::Uuid;
+
+use crate::db::DbPool;
+
+// Single-use, expiring password reset tokens; only their SHA-256 hash is stored
+pub struct PasswordReset;
+
+impl PasswordReset {
+    // Issue a reset token, superseding any earlier unused token of the user
+    pub async fn issue(
+        pool: &DbPool,
+        user_id: i32,
+        lifetime_secs: u64,
+    ) -> Result<String, sqlx::Error> {
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE password_reset_tokens SET used_at = UTC_TIMESTAMP() WHERE user_id = ? AND used_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (token_hash, user_id, expires_at, created_at)
+            VALUES (SHA2(?, 256), ?, UTC_TIMESTAMP() + INTERVAL ? SECOND, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(&token)
+        .bind(user_id)
+        .bind(lifetime_secs as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(token)
+    }
+
+    // Spend a reset token, returning its user if it was valid, unused and not expired
+    pub async fn consume(pool: &DbPool, token: &str) -> Result<Option<i32>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let user_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            SELECT user_id FROM password_reset_tokens
+            WHERE token_hash = SHA2(?, 256) AND used_at IS NULL AND expires_at > UTC_TIMESTAMP()
+            FOR UPDATE
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some((user_id,)) = user_id else {
+            return Ok(None);
+        };
+
+        sqlx::query(
+            "UPDATE password_reset_tokens SET used_at = UTC_TIMESTAMP() WHERE token_hash = SHA2(?, 256)",
+        )
+        .bind(token)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(user_id))
+    }
+
+    // Drop tokens that were used, superseded or have expired; returns how many
+    pub async fn delete_stale(pool: &DbPool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM password_reset_tokens WHERE used_at IS NOT NULL OR expires_at <= UTC_TIMESTAMP()",
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,180 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use super::Aircraft;
+use crate::db::{self, DbPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PetPlacement {
+    Cabin,
+    Hold,
+}
+
+impl PetPlacement {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PetPlacement::Cabin => "cabin",
+            PetPlacement::Hold => "hold",
+        }
+    }
+
+    // Pets of this placement the aircraft carries on each flight
+    pub fn limit(&self, aircraft: &Aircraft) -> i32 {
+        match self {
+            PetPlacement::Cabin => aircraft.pets_in_cabin,
+            PetPlacement::Hold => aircraft.pets_in_hold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum PetSpecies {
+    Dog,
+    Cat,
+    Other,
+}
+
+// What a pet costs, in the base currency: a flat fee in the cabin; in the hold a flat fee plus
+// a fee per kilogram
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PetFees {
+    pub cabin: f64,
+    pub hold: f64,
+    pub hold_per_kg: f64,
+}
+
+impl PetFees {
+    // Rounded to cents
+    pub fn fee(&self, placement: PetPlacement, weight_kg: f64) -> f64 {
+        let fee = match placement {
+            PetPlacement::Cabin => self.cabin,
+            PetPlacement::Hold => self.hold + self.hold_per_kg * weight_kg,
+        };
+        (fee * 100.0).round() / 100.0
+    }
+}
+
+// A pet travelling on a ticket
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TicketPet {
+    pub pet_id: i32,
+    pub ticket_id: i32,
+    pub placement: PetPlacement,
+    pub species: PetSpecies,
+    // With its carrier
+    pub weight_kg: f64,
+    pub fee: f64,
+    pub currency: String,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+// Pet to add to a ticket
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewTicketPet {
+    pub placement: PetPlacement,
+    pub species: PetSpecies,
+    #[validate(range(exclusive_min = 0.0, max = 75.0))]
+    pub weight_kg: f64,
+}
+
+impl TicketPet {
+    pub async fn find_by_ticket(
+        pool: &DbPool,
+        ticket_id: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM ticket_pets WHERE ticket_id = ?")
+            .bind(ticket_id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    // Pets of every ticket of the flight, for its manifest
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT p.*
+            FROM ticket_pets p
+            JOIN tickets t ON t.ticket_id = p.ticket_id
+            WHERE t.flight_id = ?
+            ORDER BY p.pet_id
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn count_on_flight(
+        pool: &DbPool,
+        flight_id: i32,
+        placement: PetPlacement,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM ticket_pets p
+            JOIN tickets t ON t.ticket_id = p.ticket_id
+            WHERE t.flight_id = ? AND p.placement = ?
+            "#,
+        )
+        .bind(flight_id)
+        .bind(placement)
+        .fetch_one(pool)
+        .await
+    }
+
+    pub async fn create(
+        pool: &DbPool,
+        ticket_id: i32,
+        pet: &NewTicketPet,
+        fee: (f64, &str),
+        created_by: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO ticket_pets
+                (ticket_id, placement, species, weight_kg, fee, currency, created_by, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(pet.placement)
+        .bind(pet.species)
+        .bind(pet.weight_kg)
+        .bind(fee.0)
+        .bind(fee.1)
+        .bind(created_by)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM ticket_pets WHERE pet_id = ?")
+            .bind(db::last_insert_id(&result) as i32)
+            .fetch_one(pool)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hold_fees_grow_with_weight() {
+        let fees = PetFees {
+            cabin: 50.0,
+            hold: 100.0,
+            hold_per_kg: 2.5,
+        };
+
+        assert_eq!(fees.fee(PetPlacement::Cabin, 7.5), 50.0);
+        assert_eq!(fees.fee(PetPlacement::Hold, 20.0), 150.0);
+        assert_eq!(fees.fee(PetPlacement::Hold, 12.3), 130.75);
+    }
+}
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::db::{self, DbPool};
+use crate::error::ErrorCode;
+use crate::validation::not_blank;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DiscountType {
+    Percentage,
+    Fixed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PromoCode {
+    pub promo_code_id: i32,
+    pub code: String,
+    pub discount_type: DiscountType,
+    pub discount_value: f64,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+    pub usage_limit: Option<i32>,
+    pub times_used: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+// New promo code to store
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct NewPromoCode {
+    #[validate(custom(function = "not_blank"), length(max = 32))]
+    pub code: String,
+    pub discount_type: DiscountType,
+    #[validate(range(exclusive_min = 0.0))]
+    pub discount_value: f64,
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: DateTime<Utc>,
+    #[validate(range(min = 1))]
+    pub usage_limit: Option<i32>,
+}
+
+impl PromoCode {
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM promo_codes WHERE promo_code_id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_by_code(pool: &DbPool, code: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM promo_codes WHERE code = ?")
+            .bind(code)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_all(pool: &DbPool, page: i32, limit: i32) -> Result<Vec<Self>, sqlx::Error> {
+        let offset = (page - 1) * limit;
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM promo_codes ORDER BY created_at DESC, promo_code_id DESC LIMIT ? OFFSET ?",
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn count(pool: &DbPool) -> Result<i64, sqlx::Error> {
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM promo_codes")
+            .fetch_one(pool)
+            .await?;
+        Ok(count)
+    }
+
+    pub async fn create(pool: &DbPool, new: &NewPromoCode) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO promo_codes
+                (code, discount_type, discount_value, valid_from, valid_until, usage_limit, times_used, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, 0, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(&new.code)
+        .bind(new.discount_type)
+        .bind(new.discount_value)
+        .bind(new.valid_from)
+        .bind(new.valid_until)
+        .bind(new.usage_limit)
+        .execute(pool)
+        .await?;
+
+        Self::find_by_id(pool, db::last_insert_id(&result) as i32)
+            .await?
+            .ok_or(sqlx::Error::RowNotFound)
+    }
+
+    pub async fn delete(pool: &DbPool, id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM promo_codes WHERE promo_code_id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Why the code cannot be used right now, if anything
+    pub fn unusable_reason(&self, now: DateTime<Utc>) -> Option<ErrorCode> {
+        if now < self.valid_from {
+            Some(ErrorCode::PromoCodeNotYetValid)
+        } else if now > self.valid_until {
+            Some(ErrorCode::PromoCodeExpired)
+        } else if self
+            .usage_limit
+            .is_some_and(|limit| self.times_used >= limit)
+        {
+            Some(ErrorCode::PromoCodeExhausted)
+        } else {
+            None
+        }
+    }
+
+    // Discount on `amount`, never more than the amount itself
+    pub fn discount_for(&self, amount: f64) -> f64 {
+        let discount = match self.discount_type {
+            DiscountType::Percentage => amount * self.discount_value / 100.0,
+            DiscountType::Fixed => self.discount_value,
+        };
+        (discount.clamp(0.0, amount) * 100.0).round() / 100.0
+    }
+}
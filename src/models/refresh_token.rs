@@ -0,0 +1,131 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::{RevokedToken, Session};
+use crate::db::{DbConnection, DbPool};
+
+// Long-lived credential exchanged for new access tokens; only its SHA-256 hash is stored.
+// Tokens rotated from the same login share its session, so a replayed token ends the whole session
+#[derive(Debug, Clone, FromRow)]
+pub struct RefreshToken {
+    pub token_id: i64,
+    pub user_id: i32,
+    pub session_id: String,
+    pub two_factor: bool,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+// Result of presenting a refresh token
+pub enum Rotation {
+    Rotated {
+        user_id: i32,
+        session_id: String,
+        two_factor: bool,
+        token: String,
+    },
+    Reused,
+    Invalid,
+}
+
+impl RefreshToken {
+    // First refresh token of a freshly created session
+    pub async fn issue(
+        pool: &DbPool,
+        user_id: i32,
+        session_id: &str,
+        two_factor: bool,
+        lifetime_secs: u64,
+    ) -> Result<String, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        Self::insert(&mut conn, user_id, session_id, two_factor, lifetime_secs).await
+    }
+
+    // Spend a refresh token and issue its successor in the same session.
+    // Presenting an already spent or revoked token revokes the session
+    pub async fn rotate(
+        pool: &DbPool,
+        token: &str,
+        lifetime_secs: u64,
+    ) -> Result<Rotation, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let current = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT token_id, user_id, session_id, two_factor, expires_at, used_at, revoked_at
+            FROM refresh_tokens
+            WHERE token_hash = SHA2(?, 256)
+            FOR UPDATE
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(current) = current else {
+            return Ok(Rotation::Invalid);
+        };
+
+        if current.used_at.is_some() || current.revoked_at.is_some() {
+            Session::revoke_in(&mut tx, &current.session_id).await?;
+            tx.commit().await?;
+            RevokedToken::forget_session(&current.session_id);
+            return Ok(Rotation::Reused);
+        }
+
+        if current.expires_at <= Utc::now() {
+            return Ok(Rotation::Invalid);
+        }
+
+        sqlx::query("UPDATE refresh_tokens SET used_at = UTC_TIMESTAMP() WHERE token_id = ?")
+            .bind(current.token_id)
+            .execute(&mut *tx)
+            .await?;
+        Session::touch(&mut tx, &current.session_id).await?;
+
+        let token = Self::insert(
+            &mut tx,
+            current.user_id,
+            &current.session_id,
+            current.two_factor,
+            lifetime_secs,
+        )
+        .await?;
+        tx.commit().await?;
+
+        Ok(Rotation::Rotated {
+            user_id: current.user_id,
+            session_id: current.session_id,
+            two_factor: current.two_factor,
+            token,
+        })
+    }
+
+    async fn insert(
+        conn: &mut DbConnection,
+        user_id: i32,
+        session_id: &str,
+        two_factor: bool,
+        lifetime_secs: u64,
+    ) -> Result<String, sqlx::Error> {
+        let token = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (user_id, session_id, two_factor, token_hash, expires_at, created_at)
+            VALUES (?, ?, ?, SHA2(?, 256), UTC_TIMESTAMP() + INTERVAL ? SECOND, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(user_id)
+        .bind(session_id)
+        .bind(two_factor)
+        .bind(&token)
+        .bind(lifetime_secs as i64)
+        .execute(conn)
+        .await?;
+
+        Ok(token)
+    }
+}
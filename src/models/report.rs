@@ -0,0 +1,1207 @@
+use std::{cmp::Reverse, collections::HashMap, hash::Hash};
+
+use chrono::{DateTime, Days, NaiveDate, NaiveTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::FlightStatus;
+use crate::currencies::ExchangeRates;
+use crate::db::DbPool;
+use crate::export::{self, ExportFormat, RowSender};
+
+// Query of the load factor report: flights departing from `from` through `to`, both inclusive
+#[derive(Debug, Clone, Deserialize)]
+pub struct LoadFactorFilter {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub route_id: Option<i32>,
+    // csv or xlsx to download one row per flight
+    pub format: Option<ExportFormat>,
+}
+
+// Start of the first day and end of the last day of a date range, in UTC
+pub fn period_bounds(from: NaiveDate, to: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let end = to.checked_add_days(Days::new(1)).unwrap_or(to);
+    (
+        from.and_time(NaiveTime::MIN).and_utc(),
+        end.and_time(NaiveTime::MIN).and_utc(),
+    )
+}
+
+// Rows being summed up by key, in the order their keys were first seen
+struct Groups<K, T> {
+    index: HashMap<K, usize>,
+    groups: Vec<T>,
+}
+
+impl<K: Eq + Hash + Clone, T> Groups<K, T> {
+    fn new() -> Self {
+        Self {
+            index: HashMap::new(),
+            groups: Vec::new(),
+        }
+    }
+
+    // The group `key` belongs to, added with `new` when it is the first of it
+    fn get(&mut self, key: K, new: impl FnOnce(K) -> T) -> &mut T {
+        match self.index.get(&key) {
+            Some(&index) => &mut self.groups[index],
+            None => {
+                self.index.insert(key.clone(), self.groups.len());
+                self.groups.push(new(key));
+                self.groups.last_mut().expect("group was just added")
+            }
+        }
+    }
+
+    fn into_vec(self) -> Vec<T> {
+        self.groups
+    }
+}
+
+// Share of `capacity` sold, rounded to a tenth of a percent; 0 for an aircraft without seats
+pub fn load_factor(seats_sold: i64, capacity: i64) -> f64 {
+    if capacity <= 0 {
+        return 0.0;
+    }
+    (seats_sold as f64 / capacity as f64 * 1000.0).round() / 1000.0
+}
+
+// Seats sold against the aircraft's capacity on one flight
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FlightLoad {
+    pub flight_id: i32,
+    pub flight_number: String,
+    pub route_id: i32,
+    pub origin: String,
+    pub destination: String,
+    pub departure_time: DateTime<Utc>,
+    pub status: FlightStatus,
+    pub capacity: i32,
+    pub seats_sold: i64,
+    #[sqlx(skip)]
+    pub load_factor: f64,
+}
+
+// The flights of one route in the period and their combined load
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteLoad {
+    pub route_id: i32,
+    pub origin: String,
+    pub destination: String,
+    pub capacity: i64,
+    pub seats_sold: i64,
+    pub load_factor: f64,
+    // Least full first
+    pub flights: Vec<FlightLoad>,
+}
+
+const FLIGHT_LOADS: &str = r#"
+    SELECT f.flight_id, f.flight_number, f.route_id, r.origin, r.destination, f.departure_time,
+           f.status, a.capacity,
+           (SELECT COUNT(*) FROM tickets t WHERE t.flight_id = f.flight_id) AS seats_sold
+    FROM flights f
+    JOIN routes r ON r.route_id = f.route_id
+    JOIN aircraft a ON a.aircraft_id = f.aircraft_id
+    WHERE f.departure_time >= ? AND f.departure_time < ?
+      AND f.status <> 'cancelled'
+      AND (? IS NULL OR f.route_id = ?)
+    ORDER BY f.route_id, f.departure_time, f.flight_id
+"#;
+
+impl FlightLoad {
+    fn with_load_factor(mut self) -> Self {
+        self.load_factor = load_factor(self.seats_sold, self.capacity.into());
+        self
+    }
+
+    // Flights that were not cancelled, by route and departure
+    pub async fn find(pool: &DbPool, filter: &LoadFactorFilter) -> Result<Vec<Self>, sqlx::Error> {
+        let (from, to) = period_bounds(filter.from, filter.to);
+        let flights = sqlx::query_as::<_, Self>(FLIGHT_LOADS)
+            .bind(from)
+            .bind(to)
+            .bind(filter.route_id)
+            .bind(filter.route_id)
+            .fetch_all(pool)
+            .await?;
+        Ok(flights.into_iter().map(Self::with_load_factor).collect())
+    }
+
+    // The same flights for a download
+    pub async fn export(pool: &DbPool, filter: &LoadFactorFilter, rows: RowSender<Self>) {
+        let (from, to) = period_bounds(filter.from, filter.to);
+        let query = sqlx::query_as::<_, Self>(FLIGHT_LOADS)
+            .bind(from)
+            .bind(to)
+            .bind(filter.route_id)
+            .bind(filter.route_id);
+        let flights = query
+            .fetch(pool)
+            .map(|flight| flight.map(Self::with_load_factor))
+            .boxed();
+        export::forward(flights, &rows).await;
+    }
+}
+
+impl RouteLoad {
+    // Group flights by route, least full routes first
+    pub fn group(flights: Vec<FlightLoad>) -> Vec<Self> {
+        let mut routes = Groups::new();
+        for flight in flights {
+            let route = routes.get(flight.route_id, |route_id| RouteLoad {
+                route_id,
+                origin: flight.origin.clone(),
+                destination: flight.destination.clone(),
+                capacity: 0,
+                seats_sold: 0,
+                load_factor: 0.0,
+                flights: Vec::new(),
+            });
+            route.capacity += i64::from(flight.capacity);
+            route.seats_sold += flight.seats_sold;
+            route.flights.push(flight);
+        }
+
+        let mut routes = routes.into_vec();
+        for route in &mut routes {
+            route.load_factor = load_factor(route.seats_sold, route.capacity);
+            route
+                .flights
+                .sort_by(|a, b| a.load_factor.total_cmp(&b.load_factor));
+        }
+        routes.sort_by(|a, b| a.load_factor.total_cmp(&b.load_factor));
+        routes
+    }
+}
+
+// How the revenue report is broken down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RevenueGrouping {
+    #[default]
+    Route,
+    Month,
+}
+
+// Query of the revenue report: sales made from `from` through `to`, both inclusive
+#[derive(Debug, Clone, Deserialize)]
+pub struct RevenueFilter {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(default)]
+    pub group_by: RevenueGrouping,
+    // Currency to report in, EUR when omitted
+    pub currency: Option<String>,
+    pub format: Option<ExportFormat>,
+}
+
+// Sales on one route in one month in one currency, as stored
+#[derive(Debug, Clone, FromRow)]
+pub struct RevenueEntry {
+    pub route_id: i32,
+    pub origin: String,
+    pub destination: String,
+    // `2026-03`
+    pub month: String,
+    // None for tickets issued before fares were recorded
+    pub currency: Option<String>,
+    pub tickets_sold: i64,
+    pub ticket_revenue: f64,
+    pub ancillary_revenue: f64,
+}
+
+// Revenue of a route or a month, converted to one currency
+#[derive(Debug, Clone, Serialize)]
+pub struct RevenueRow {
+    // `KBP-LWO` by route, `2026-03` by month
+    pub group: String,
+    pub route_id: Option<i32>,
+    pub tickets_sold: i64,
+    // Fares, lap infants and upgrades
+    pub ticket_revenue: f64,
+    // Ancillaries, seat changes, baggage and pets
+    pub ancillary_revenue: f64,
+    pub total: f64,
+    pub currency: String,
+}
+
+// Every charge by the day it was paid: fares when the ticket was issued, lap infants when they
+// were added, upgrades when they were bought, and extras (ancillaries, seat changes, baggage and
+// pets) when they were bought or charged. A ticket's fare already includes its upgrades, so those
+// are taken out of it and counted on their own day
+const REVENUE_ENTRIES: &str = r#"
+    SELECT f.route_id, r.origin, r.destination, DATE_FORMAT(s.sold_at, '%Y-%m') AS month,
+           s.currency, CAST(SUM(s.ticket) AS SIGNED) AS tickets_sold,
+           SUM(s.ticket_revenue) AS ticket_revenue, SUM(s.ancillary_revenue) AS ancillary_revenue
+    FROM (
+        SELECT t.flight_id, t.created_at AS sold_at, t.currency, 1 AS ticket,
+               GREATEST(COALESCE(t.fare, 0e0) - COALESCE((
+                   SELECT SUM(tu.price) FROM ticket_upgrades tu WHERE tu.ticket_id = t.ticket_id
+               ), 0e0), 0e0) AS ticket_revenue,
+               0e0 AS ancillary_revenue
+        FROM tickets t
+        WHERE t.created_at >= ? AND t.created_at < ?
+        UNION ALL
+        SELECT t.flight_id, ti.created_at, ti.currency, 0, ti.fare, 0e0
+        FROM ticket_infants ti
+        JOIN tickets t ON t.ticket_id = ti.ticket_id
+        WHERE ti.created_at >= ? AND ti.created_at < ?
+        UNION ALL
+        SELECT t.flight_id, tu.upgraded_at, tu.currency, 0, tu.price, 0e0
+        FROM ticket_upgrades tu
+        JOIN tickets t ON t.ticket_id = tu.ticket_id
+        WHERE tu.upgraded_at >= ? AND tu.upgraded_at < ?
+        UNION ALL
+        SELECT t.flight_id, ta.purchased_at, ta.currency, 0, 0e0, ta.price
+        FROM ticket_ancillaries ta
+        JOIN tickets t ON t.ticket_id = ta.ticket_id
+        WHERE ta.purchased_at >= ? AND ta.purchased_at < ?
+        UNION ALL
+        SELECT t.flight_id, sc.changed_at, sc.currency, 0, 0e0, sc.fee
+        FROM seat_changes sc
+        JOIN tickets t ON t.ticket_id = sc.ticket_id
+        WHERE sc.fee > 0 AND sc.changed_at >= ? AND sc.changed_at < ?
+        UNION ALL
+        SELECT t.flight_id, bp.purchased_at, bp.currency, 0, 0e0, bp.price
+        FROM baggage_purchases bp
+        JOIN tickets t ON t.ticket_id = bp.ticket_id
+        WHERE bp.purchased_at >= ? AND bp.purchased_at < ?
+        UNION ALL
+        SELECT t.flight_id, b.created_at, b.excess_fee_currency, 0, 0e0, b.excess_fee
+        FROM baggage b
+        JOIN tickets t ON t.ticket_id = b.ticket_id
+        WHERE b.excess_fee > 0 AND b.created_at >= ? AND b.created_at < ?
+        UNION ALL
+        SELECT t.flight_id, tp.created_at, tp.currency, 0, 0e0, tp.fee
+        FROM ticket_pets tp
+        JOIN tickets t ON t.ticket_id = tp.ticket_id
+        WHERE tp.created_at >= ? AND tp.created_at < ?
+    ) s
+    JOIN flights f ON f.flight_id = s.flight_id
+    JOIN routes r ON r.route_id = f.route_id
+    GROUP BY f.route_id, r.origin, r.destination, month, s.currency
+    ORDER BY f.route_id, month
+"#;
+
+// Parts of `REVENUE_ENTRIES`, each bound to the period
+const REVENUE_SOURCES: usize = 8;
+
+impl RevenueEntry {
+    pub async fn find(pool: &DbPool, filter: &RevenueFilter) -> Result<Vec<Self>, sqlx::Error> {
+        let (from, to) = period_bounds(filter.from, filter.to);
+        let mut query = sqlx::query_as::<_, Self>(REVENUE_ENTRIES);
+        for _ in 0..REVENUE_SOURCES {
+            query = query.bind(from).bind(to);
+        }
+        query.fetch_all(pool).await
+    }
+}
+
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+impl RevenueRow {
+    // Sum entries by route, highest revenue first, or by month in order, in `currency`. Err names
+    // a currency there is no exchange rate for
+    pub fn summarize(
+        entries: Vec<RevenueEntry>,
+        group_by: RevenueGrouping,
+        rates: &ExchangeRates,
+        currency: &str,
+    ) -> Result<Vec<Self>, String> {
+        let mut rows = Groups::new();
+        for entry in entries {
+            let convert = |amount: f64| match &entry.currency {
+                Some(from) => rates
+                    .convert(amount, from, currency)
+                    .ok_or_else(|| from.clone()),
+                None => Ok(amount),
+            };
+            let ticket_revenue = convert(entry.ticket_revenue)?;
+            let ancillary_revenue = convert(entry.ancillary_revenue)?;
+
+            let (group, route_id) = match group_by {
+                RevenueGrouping::Route => (
+                    format!("{}-{}", entry.origin, entry.destination),
+                    Some(entry.route_id),
+                ),
+                RevenueGrouping::Month => (entry.month.clone(), None),
+            };
+            let row = rows.get((group, route_id), |(group, route_id)| RevenueRow {
+                group,
+                route_id,
+                tickets_sold: 0,
+                ticket_revenue: 0.0,
+                ancillary_revenue: 0.0,
+                total: 0.0,
+                currency: currency.to_string(),
+            });
+            row.tickets_sold += entry.tickets_sold;
+            row.ticket_revenue += ticket_revenue;
+            row.ancillary_revenue += ancillary_revenue;
+        }
+
+        let mut rows = rows.into_vec();
+        for row in &mut rows {
+            row.ticket_revenue = cents(row.ticket_revenue);
+            row.ancillary_revenue = cents(row.ancillary_revenue);
+            row.total = cents(row.ticket_revenue + row.ancillary_revenue);
+        }
+        match group_by {
+            RevenueGrouping::Route => rows.sort_by(|a, b| b.total.total_cmp(&a.total)),
+            RevenueGrouping::Month => rows.sort_by(|a, b| a.group.cmp(&b.group)),
+        }
+        Ok(rows)
+    }
+}
+
+// A departure or arrival at most this many minutes behind schedule is on time (the industry's
+// D15 and A15)
+pub const ON_TIME_MINUTES: i64 = 15;
+
+// How the on-time performance report is broken down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PunctualityGrouping {
+    #[default]
+    Route,
+    Aircraft,
+    Month,
+}
+
+// Query of the on-time performance report: flights scheduled to depart from `from` through `to`,
+// both inclusive
+#[derive(Debug, Clone, Deserialize)]
+pub struct OnTimeFilter {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(default)]
+    pub group_by: PunctualityGrouping,
+    // csv or xlsx to download one row per flight
+    pub format: Option<ExportFormat>,
+}
+
+// Scheduled against actual times of a flight that departed
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct FlightPunctuality {
+    pub flight_id: i32,
+    pub flight_number: String,
+    pub route_id: i32,
+    pub origin: String,
+    pub destination: String,
+    pub aircraft_id: i32,
+    pub aircraft_model: String,
+    pub departure_time: DateTime<Utc>,
+    pub actual_departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+    // None until it arrives
+    pub actual_arrival_time: Option<DateTime<Utc>>,
+    // Negative when early
+    #[sqlx(skip)]
+    pub departure_delay_minutes: i64,
+    #[sqlx(skip)]
+    pub arrival_delay_minutes: Option<i64>,
+}
+
+// On-time performance of a route, an aircraft or a month. Early flights count as no delay in
+// the averages
+#[derive(Debug, Clone, Serialize)]
+pub struct OnTimePerformance {
+    // `KBP-LWO` by route, the model by aircraft, `2026-03` by month
+    pub group: String,
+    pub route_id: Option<i32>,
+    pub aircraft_id: Option<i32>,
+    pub departures: i64,
+    pub on_time_departures_percent: f64,
+    pub average_departure_delay_minutes: f64,
+    pub arrivals: i64,
+    // None while none of the flights has arrived
+    pub on_time_arrivals_percent: Option<f64>,
+    pub average_arrival_delay_minutes: Option<f64>,
+}
+
+const FLIGHT_PUNCTUALITY: &str = r#"
+    SELECT f.flight_id, f.flight_number, f.route_id, r.origin, r.destination, f.aircraft_id,
+           a.model AS aircraft_model, f.departure_time, f.actual_departure_time, f.arrival_time,
+           f.actual_arrival_time
+    FROM flights f
+    JOIN routes r ON r.route_id = f.route_id
+    JOIN aircraft a ON a.aircraft_id = f.aircraft_id
+    WHERE f.departure_time >= ? AND f.departure_time < ?
+      AND f.actual_departure_time IS NOT NULL
+    ORDER BY f.departure_time, f.flight_id
+"#;
+
+impl FlightPunctuality {
+    fn with_delays(mut self) -> Self {
+        self.departure_delay_minutes =
+            (self.actual_departure_time - self.departure_time).num_minutes();
+        self.arrival_delay_minutes = self
+            .actual_arrival_time
+            .map(|arrived| (arrived - self.arrival_time).num_minutes());
+        self
+    }
+
+    // Flights that departed, by scheduled departure
+    pub async fn find(pool: &DbPool, filter: &OnTimeFilter) -> Result<Vec<Self>, sqlx::Error> {
+        let (from, to) = period_bounds(filter.from, filter.to);
+        let flights = sqlx::query_as::<_, Self>(FLIGHT_PUNCTUALITY)
+            .bind(from)
+            .bind(to)
+            .fetch_all(pool)
+            .await?;
+        Ok(flights.into_iter().map(Self::with_delays).collect())
+    }
+
+    // The same flights for a download
+    pub async fn export(pool: &DbPool, filter: &OnTimeFilter, rows: RowSender<Self>) {
+        let (from, to) = period_bounds(filter.from, filter.to);
+        let query = sqlx::query_as::<_, Self>(FLIGHT_PUNCTUALITY)
+            .bind(from)
+            .bind(to);
+        let flights = query
+            .fetch(pool)
+            .map(|flight| flight.map(Self::with_delays))
+            .boxed();
+        export::forward(flights, &rows).await;
+    }
+}
+
+// Delays in minutes of the flights of one group
+struct Delays {
+    group: String,
+    route_id: Option<i32>,
+    aircraft_id: Option<i32>,
+    departures: Vec<i64>,
+    arrivals: Vec<i64>,
+}
+
+// Share of `delays` within ON_TIME_MINUTES, in percent to one decimal, and their average with
+// early ones as 0, to one decimal; None for no delays
+fn punctuality(delays: &[i64]) -> Option<(f64, f64)> {
+    if delays.is_empty() {
+        return None;
+    }
+    let count = delays.len() as f64;
+    let on_time = delays
+        .iter()
+        .filter(|delay| **delay <= ON_TIME_MINUTES)
+        .count() as f64;
+    let late: i64 = delays.iter().map(|delay| (*delay).max(0)).sum();
+    Some((
+        (on_time / count * 1000.0).round() / 10.0,
+        (late as f64 / count * 10.0).round() / 10.0,
+    ))
+}
+
+impl OnTimePerformance {
+    // Group flights by route or aircraft, least punctual first, or by month in order
+    pub fn group(flights: Vec<FlightPunctuality>, group_by: PunctualityGrouping) -> Vec<Self> {
+        let mut groups = Groups::new();
+        for flight in flights {
+            let (group, route_id, aircraft_id) = match group_by {
+                PunctualityGrouping::Route => (
+                    format!("{}-{}", flight.origin, flight.destination),
+                    Some(flight.route_id),
+                    None,
+                ),
+                PunctualityGrouping::Aircraft => {
+                    (flight.aircraft_model, None, Some(flight.aircraft_id))
+                }
+                PunctualityGrouping::Month => (
+                    flight.departure_time.format("%Y-%m").to_string(),
+                    None,
+                    None,
+                ),
+            };
+            let delays = groups.get(
+                (group, route_id, aircraft_id),
+                |(group, route_id, aircraft_id)| Delays {
+                    group,
+                    route_id,
+                    aircraft_id,
+                    departures: Vec::new(),
+                    arrivals: Vec::new(),
+                },
+            );
+            delays.departures.push(flight.departure_delay_minutes);
+            delays.arrivals.extend(flight.arrival_delay_minutes);
+        }
+
+        let mut rows: Vec<Self> = groups
+            .into_vec()
+            .into_iter()
+            .map(|delays| {
+                let (on_time_departures_percent, average_departure_delay_minutes) =
+                    punctuality(&delays.departures).unwrap_or_default();
+                let arrived = punctuality(&delays.arrivals);
+                OnTimePerformance {
+                    group: delays.group,
+                    route_id: delays.route_id,
+                    aircraft_id: delays.aircraft_id,
+                    departures: delays.departures.len() as i64,
+                    on_time_departures_percent,
+                    average_departure_delay_minutes,
+                    arrivals: delays.arrivals.len() as i64,
+                    on_time_arrivals_percent: arrived.map(|(percent, _)| percent),
+                    average_arrival_delay_minutes: arrived.map(|(_, average)| average),
+                }
+            })
+            .collect();
+        match group_by {
+            PunctualityGrouping::Month => rows.sort_by(|a, b| a.group.cmp(&b.group)),
+            _ => rows.sort_by(|a, b| {
+                a.on_time_departures_percent
+                    .total_cmp(&b.on_time_departures_percent)
+            }),
+        }
+        rows
+    }
+}
+
+// Query of the demand analytics: tickets issued from `from` through `to`, both inclusive
+#[derive(Debug, Clone, Deserialize)]
+pub struct DemandFilter {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    // How many of the most booked routes to list, 10 by default
+    pub limit: Option<i32>,
+}
+
+// Tickets issued for flights of a route
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct RouteDemand {
+    pub route_id: i32,
+    pub origin: String,
+    pub destination: String,
+    pub tickets: i64,
+}
+
+// Tickets for flights departing on a day of the week, in UTC
+#[derive(Debug, Clone, Serialize)]
+pub struct WeekdayDemand {
+    // `monday`
+    pub day: &'static str,
+    pub tickets: i64,
+}
+
+// Tickets issued a number of days ahead of departure, `min_days`..=`max_days`
+#[derive(Debug, Clone, Serialize)]
+pub struct LeadTimeBucket {
+    pub min_days: i64,
+    // None for the last, open-ended bucket
+    pub max_days: Option<i64>,
+    pub tickets: i64,
+    // Of all tickets in the period, to one decimal
+    pub percent: f64,
+}
+
+// What was booked in a period, for the marketing dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct DemandAnalytics {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub tickets: i64,
+    // Most booked first
+    pub top_routes: Vec<RouteDemand>,
+    // Busiest first, all seven days
+    pub busiest_days: Vec<WeekdayDemand>,
+    pub lead_times: Vec<LeadTimeBucket>,
+    // None without tickets
+    pub average_lead_days: Option<f64>,
+}
+
+const WEEKDAYS: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+// Lower bounds of the lead time buckets in days; the last one is open-ended
+const LEAD_TIME_BUCKETS: [i64; 6] = [0, 7, 14, 30, 60, 90];
+
+// Tickets by WEEKDAY() of departure, 0 for Monday
+fn busiest_days(counts: &[(i64, i64)]) -> Vec<WeekdayDemand> {
+    let mut days: Vec<WeekdayDemand> = WEEKDAYS
+        .iter()
+        .enumerate()
+        .map(|(weekday, day)| WeekdayDemand {
+            day,
+            tickets: counts
+                .iter()
+                .filter(|(counted, _)| *counted == weekday as i64)
+                .map(|(_, tickets)| tickets)
+                .sum(),
+        })
+        .collect();
+    // Stable, so days with as many tickets stay in week order
+    days.sort_by_key(|day| Reverse(day.tickets));
+    days
+}
+
+// Tickets by days booked ahead of departure into buckets, and the average lead time. Tickets
+// issued after departure count as booked on the day
+fn lead_times(counts: &[(i64, i64)]) -> (Vec<LeadTimeBucket>, Option<f64>) {
+    let total: i64 = counts.iter().map(|(_, tickets)| tickets).sum();
+    let buckets = LEAD_TIME_BUCKETS
+        .iter()
+        .enumerate()
+        .map(|(index, min_days)| {
+            let max_days = LEAD_TIME_BUCKETS.get(index + 1).map(|next| next - 1);
+            let tickets = counts
+                .iter()
+                .filter(|(days, _)| {
+                    let days = (*days).max(0);
+                    days >= *min_days && max_days.is_none_or(|max_days| days <= max_days)
+                })
+                .map(|(_, tickets)| tickets)
+                .sum();
+            LeadTimeBucket {
+                min_days: *min_days,
+                max_days,
+                tickets,
+                percent: if total > 0 {
+                    (tickets as f64 / total as f64 * 1000.0).round() / 10.0
+                } else {
+                    0.0
+                },
+            }
+        })
+        .collect();
+    let average = (total > 0).then(|| {
+        let days: i64 = counts
+            .iter()
+            .map(|(days, tickets)| (*days).max(0) * tickets)
+            .sum();
+        (days as f64 / total as f64 * 10.0).round() / 10.0
+    });
+    (buckets, average)
+}
+
+// Tickets issued in the period with their flight and route
+const DEMAND: &str = r#"
+    FROM tickets t
+    JOIN flights f ON f.flight_id = t.flight_id
+    JOIN routes r ON r.route_id = f.route_id
+    WHERE t.created_at >= ? AND t.created_at < ?
+"#;
+
+impl DemandAnalytics {
+    pub async fn find(
+        pool: &DbPool,
+        filter: &DemandFilter,
+        limit: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let (from, to) = period_bounds(filter.from, filter.to);
+
+        let top_routes = sqlx::query_as::<_, RouteDemand>(&format!(
+            r#"
+            SELECT r.route_id, r.origin, r.destination, COUNT(*) AS tickets
+            {}
+            GROUP BY r.route_id, r.origin, r.destination
+            ORDER BY tickets DESC, r.route_id
+            LIMIT ?
+            "#,
+            DEMAND
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        let weekdays: Vec<(i64, i64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT CAST(WEEKDAY(f.departure_time) AS SIGNED) AS weekday, COUNT(*)
+            {}
+            GROUP BY weekday
+            "#,
+            DEMAND
+        ))
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+
+        let lead_days: Vec<(i64, i64)> = sqlx::query_as(&format!(
+            r#"
+            SELECT CAST(DATEDIFF(f.departure_time, t.created_at) AS SIGNED) AS lead_days, COUNT(*)
+            {}
+            GROUP BY lead_days
+            "#,
+            DEMAND
+        ))
+        .bind(from)
+        .bind(to)
+        .fetch_all(pool)
+        .await?;
+
+        let (lead_times, average_lead_days) = lead_times(&lead_days);
+        Ok(DemandAnalytics {
+            from: filter.from,
+            to: filter.to,
+            tickets: weekdays.iter().map(|(_, tickets)| tickets).sum(),
+            top_routes,
+            busiest_days: busiest_days(&weekdays),
+            lead_times,
+            average_lead_days,
+        })
+    }
+}
+
+// How the passenger demographics are broken down
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DemographicsGrouping {
+    #[default]
+    Route,
+    Month,
+}
+
+// Query of the passenger demographics: passengers on flights departing from `from` through `to`,
+// both inclusive
+#[derive(Debug, Clone, Deserialize)]
+pub struct DemographicsFilter {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(default)]
+    pub group_by: DemographicsGrouping,
+    pub route_id: Option<i32>,
+}
+
+// Passengers sharing a nationality, an age bracket or a gender
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DemographicCount {
+    // `unknown` (`unspecified` for gender) where the profile leaves it out
+    pub value: String,
+    pub passengers: i64,
+}
+
+// Passengers of a route or a month: ticket holders and the infants on their laps. Someone flying
+// twice counts twice
+#[derive(Debug, Clone, Serialize)]
+pub struct DemographicGroup {
+    // `KBP-LWO` by route, `2026-03` by month
+    pub group: String,
+    pub route_id: Option<i32>,
+    pub passengers: i64,
+    // Most passengers first
+    pub nationalities: Vec<DemographicCount>,
+    // In AGE_BRACKETS order
+    pub age_brackets: Vec<DemographicCount>,
+    // Most passengers first
+    pub genders: Vec<DemographicCount>,
+}
+
+// Age on the day of departure; `unknown` comes last
+const AGE_BRACKETS: [&str; 9] = [
+    "0-11", "12-17", "18-24", "25-34", "35-44", "45-54", "55-64", "65+", "unknown",
+];
+
+const NATIONALITY: &str = "COALESCE(UPPER(NULLIF(TRIM(p.nationality), '')), 'unknown')";
+
+const AGE_BRACKET: &str = r#"
+    CASE
+        WHEN p.date_of_birth IS NULL THEN 'unknown'
+        WHEN TIMESTAMPDIFF(YEAR, p.date_of_birth, f.departure_time) < 12 THEN '0-11'
+        WHEN TIMESTAMPDIFF(YEAR, p.date_of_birth, f.departure_time) < 18 THEN '12-17'
+        WHEN TIMESTAMPDIFF(YEAR, p.date_of_birth, f.departure_time) < 25 THEN '18-24'
+        WHEN TIMESTAMPDIFF(YEAR, p.date_of_birth, f.departure_time) < 35 THEN '25-34'
+        WHEN TIMESTAMPDIFF(YEAR, p.date_of_birth, f.departure_time) < 45 THEN '35-44'
+        WHEN TIMESTAMPDIFF(YEAR, p.date_of_birth, f.departure_time) < 55 THEN '45-54'
+        WHEN TIMESTAMPDIFF(YEAR, p.date_of_birth, f.departure_time) < 65 THEN '55-64'
+        ELSE '65+'
+    END
+"#;
+
+const GENDER: &str = "COALESCE(CAST(p.gender AS CHAR), 'unspecified')";
+
+// Passengers of a route or month counted by one profile field. Ticket holders are described by
+// their profile; lap infants only have a date of birth on record, so their nationality is
+// `unknown` and their gender `unspecified`
+#[derive(Debug, Clone, FromRow)]
+struct DemographicRow {
+    group_key: String,
+    route_id: Option<i32>,
+    value: String,
+    passengers: i64,
+}
+
+impl DemographicRow {
+    async fn find(
+        pool: &DbPool,
+        filter: &DemographicsFilter,
+        value: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let (group_key, route_id) = match filter.group_by {
+            DemographicsGrouping::Route => ("CONCAT(r.origin, '-', r.destination)", "r.route_id"),
+            DemographicsGrouping::Month => ("DATE_FORMAT(f.departure_time, '%Y-%m')", "NULL"),
+        };
+        let (from, to) = period_bounds(filter.from, filter.to);
+        sqlx::query_as::<_, Self>(&format!(
+            r#"
+            SELECT {group_key} AS group_key, {route_id} AS route_id, {value} AS value,
+                   COUNT(*) AS passengers
+            FROM (
+                SELECT t.flight_id, u.nationality, u.date_of_birth, u.gender
+                FROM tickets t
+                JOIN users u ON u.user_id = t.user_id
+                UNION ALL
+                SELECT t.flight_id, NULL, ti.date_of_birth, NULL
+                FROM ticket_infants ti
+                JOIN tickets t ON t.ticket_id = ti.ticket_id
+            ) p
+            JOIN flights f ON f.flight_id = p.flight_id
+            JOIN routes r ON r.route_id = f.route_id
+            WHERE f.departure_time >= ? AND f.departure_time < ?
+              AND f.status <> 'cancelled'
+              AND (? IS NULL OR f.route_id = ?)
+            GROUP BY 1, 2, 3
+            "#,
+        ))
+        .bind(from)
+        .bind(to)
+        .bind(filter.route_id)
+        .bind(filter.route_id)
+        .fetch_all(pool)
+        .await
+    }
+}
+
+impl DemographicGroup {
+    // Passengers by nationality, age bracket and gender, by route with the most passengers first
+    // or by month in order
+    pub async fn find(
+        pool: &DbPool,
+        filter: &DemographicsFilter,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let nationalities = DemographicRow::find(pool, filter, NATIONALITY).await?;
+        let ages = DemographicRow::find(pool, filter, AGE_BRACKET).await?;
+        let genders = DemographicRow::find(pool, filter, GENDER).await?;
+        Ok(Self::assemble(
+            filter.group_by,
+            nationalities,
+            ages,
+            genders,
+        ))
+    }
+
+    fn assemble(
+        group_by: DemographicsGrouping,
+        nationalities: Vec<DemographicRow>,
+        ages: Vec<DemographicRow>,
+        genders: Vec<DemographicRow>,
+    ) -> Vec<Self> {
+        let mut groups = Groups::new();
+        let dimensions = [(0, nationalities), (1, ages), (2, genders)];
+        for (dimension, rows) in dimensions {
+            for row in rows {
+                let group = groups.get((row.group_key, row.route_id), |(group, route_id)| {
+                    DemographicGroup {
+                        group,
+                        route_id,
+                        passengers: 0,
+                        nationalities: Vec::new(),
+                        age_brackets: Vec::new(),
+                        genders: Vec::new(),
+                    }
+                });
+                let count = DemographicCount {
+                    value: row.value,
+                    passengers: row.passengers,
+                };
+                match dimension {
+                    0 => {
+                        // Every ticket has exactly one nationality, known or not
+                        group.passengers += count.passengers;
+                        group.nationalities.push(count);
+                    }
+                    1 => group.age_brackets.push(count),
+                    _ => group.genders.push(count),
+                }
+            }
+        }
+
+        let mut groups = groups.into_vec();
+        let most_first = |a: &DemographicCount, b: &DemographicCount| {
+            b.passengers
+                .cmp(&a.passengers)
+                .then_with(|| a.value.cmp(&b.value))
+        };
+        for group in &mut groups {
+            group.nationalities.sort_by(most_first);
+            group.genders.sort_by(most_first);
+            group.age_brackets.sort_by_key(|count| {
+                AGE_BRACKETS
+                    .iter()
+                    .position(|bracket| *bracket == count.value)
+                    .unwrap_or(AGE_BRACKETS.len())
+            });
+        }
+        match group_by {
+            DemographicsGrouping::Route => groups.sort_by_key(|group| Reverse(group.passengers)),
+            DemographicsGrouping::Month => groups.sort_by(|a, b| a.group.cmp(&b.group)),
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flight(flight_id: i32, route_id: i32, capacity: i32, seats_sold: i64) -> FlightLoad {
+        FlightLoad {
+            flight_id,
+            flight_number: format!("TS{}", flight_id),
+            route_id,
+            origin: format!("A{}", route_id),
+            destination: format!("B{}", route_id),
+            departure_time: Utc::now(),
+            status: FlightStatus::Scheduled,
+            capacity,
+            seats_sold,
+            load_factor: 0.0,
+        }
+        .with_load_factor()
+    }
+
+    #[test]
+    fn load_factor_is_the_share_sold() {
+        assert_eq!(load_factor(45, 180), 0.25);
+        assert_eq!(load_factor(1, 3), 0.333);
+        assert_eq!(load_factor(5, 0), 0.0);
+    }
+
+    #[test]
+    fn routes_and_their_flights_are_ordered_least_full_first() {
+        let routes = RouteLoad::group(vec![
+            flight(1, 1, 100, 90),
+            flight(2, 1, 100, 30),
+            flight(3, 2, 50, 10),
+        ]);
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].route_id, 2);
+        assert_eq!(routes[0].load_factor, 0.2);
+        assert_eq!(routes[1].capacity, 200);
+        assert_eq!(routes[1].seats_sold, 120);
+        assert_eq!(routes[1].load_factor, 0.6);
+        assert_eq!(routes[1].flights[0].flight_id, 2);
+    }
+
+    #[test]
+    fn periods_include_the_last_day() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let (from, to) = period_bounds(day(1), day(31));
+
+        assert_eq!(from.to_rfc3339(), "2026-03-01T00:00:00+00:00");
+        assert_eq!(to.to_rfc3339(), "2026-04-01T00:00:00+00:00");
+    }
+
+    fn entry(
+        route_id: i32,
+        month: &str,
+        currency: &str,
+        tickets: f64,
+        ancillaries: f64,
+    ) -> RevenueEntry {
+        RevenueEntry {
+            route_id,
+            origin: format!("A{}", route_id),
+            destination: format!("B{}", route_id),
+            month: month.to_string(),
+            currency: Some(currency.to_string()),
+            tickets_sold: 1,
+            ticket_revenue: tickets,
+            ancillary_revenue: ancillaries,
+        }
+    }
+
+    #[test]
+    fn revenue_is_summed_by_route_in_one_currency() {
+        let rates = ExchangeRates::new([("UAH".to_string(), 40.0)]);
+        let rows = RevenueRow::summarize(
+            vec![
+                entry(1, "2026-03", "EUR", 100.0, 20.0),
+                entry(1, "2026-04", "UAH", 4000.0, 0.0),
+                entry(2, "2026-03", "EUR", 300.0, 0.0),
+            ],
+            RevenueGrouping::Route,
+            &rates,
+            "EUR",
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].group, "A2-B2");
+        assert_eq!(rows[1].route_id, Some(1));
+        assert_eq!(rows[1].tickets_sold, 2);
+        assert_eq!(rows[1].ticket_revenue, 200.0);
+        assert_eq!(rows[1].total, 220.0);
+    }
+
+    #[test]
+    fn revenue_by_month_is_in_order_and_needs_every_rate() {
+        let rates = ExchangeRates::new([]);
+        let entries = vec![
+            entry(1, "2026-04", "EUR", 50.0, 5.0),
+            entry(2, "2026-03", "EUR", 100.0, 0.0),
+            entry(1, "2026-03", "EUR", 10.0, 0.0),
+        ];
+        let rows =
+            RevenueRow::summarize(entries.clone(), RevenueGrouping::Month, &rates, "EUR").unwrap();
+
+        assert_eq!(rows[0].group, "2026-03");
+        assert_eq!(rows[0].total, 110.0);
+        assert_eq!(rows[0].route_id, None);
+        assert_eq!(rows[1].total, 55.0);
+
+        let missing = RevenueRow::summarize(entries, RevenueGrouping::Month, &rates, "USD");
+        assert_eq!(missing.unwrap_err(), "EUR");
+    }
+
+    fn departed(
+        route_id: i32,
+        aircraft_id: i32,
+        late_by: i64,
+        landed_late_by: Option<i64>,
+    ) -> FlightPunctuality {
+        let scheduled = NaiveDate::from_ymd_opt(2026, 3, 2)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+            .and_utc();
+        let arrival = scheduled + chrono::Duration::hours(2);
+        FlightPunctuality {
+            flight_id: 1,
+            flight_number: "TS1".to_string(),
+            route_id,
+            origin: format!("A{}", route_id),
+            destination: format!("B{}", route_id),
+            aircraft_id,
+            aircraft_model: format!("Model {}", aircraft_id),
+            departure_time: scheduled,
+            actual_departure_time: scheduled + chrono::Duration::minutes(late_by),
+            arrival_time: arrival,
+            actual_arrival_time: landed_late_by
+                .map(|late_by| arrival + chrono::Duration::minutes(late_by)),
+            departure_delay_minutes: 0,
+            arrival_delay_minutes: None,
+        }
+        .with_delays()
+    }
+
+    #[test]
+    fn on_time_means_at_most_fifteen_minutes_late() {
+        assert_eq!(punctuality(&[]), None);
+        // Early flights are on time and add no delay
+        assert_eq!(punctuality(&[-5, 15, 16, 60]), Some((50.0, 22.8)));
+    }
+
+    #[test]
+    fn punctuality_is_grouped_least_punctual_first() {
+        let flights = vec![
+            departed(1, 7, 0, Some(-10)),
+            departed(1, 8, 40, None),
+            departed(2, 7, 5, Some(20)),
+        ];
+
+        let routes = OnTimePerformance::group(flights.clone(), PunctualityGrouping::Route);
+        assert_eq!(routes[0].route_id, Some(1));
+        assert_eq!(routes[0].departures, 2);
+        assert_eq!(routes[0].on_time_departures_percent, 50.0);
+        assert_eq!(routes[0].average_departure_delay_minutes, 20.0);
+        assert_eq!(routes[0].arrivals, 1);
+        assert_eq!(routes[0].on_time_arrivals_percent, Some(100.0));
+        assert_eq!(routes[1].on_time_arrivals_percent, Some(0.0));
+
+        let aircraft = OnTimePerformance::group(flights.clone(), PunctualityGrouping::Aircraft);
+        assert_eq!(aircraft[0].group, "Model 8");
+        assert_eq!(aircraft[0].on_time_arrivals_percent, None);
+        assert_eq!(aircraft[1].aircraft_id, Some(7));
+        assert_eq!(aircraft[1].departures, 2);
+
+        let months = OnTimePerformance::group(flights, PunctualityGrouping::Month);
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].group, "2026-03");
+        assert_eq!(months[0].departures, 3);
+    }
+
+    #[test]
+    fn days_of_the_week_are_ordered_busiest_first() {
+        let days = busiest_days(&[(4, 12), (0, 3), (6, 12)]);
+
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[0].day, "friday");
+        assert_eq!(days[1].day, "sunday");
+        assert_eq!(days[2].day, "monday");
+        assert_eq!(days[2].tickets, 3);
+        assert_eq!(days[6].tickets, 0);
+    }
+
+    #[test]
+    fn lead_times_are_bucketed_with_their_share() {
+        let (buckets, average) = lead_times(&[(-1, 1), (3, 1), (7, 2), (45, 1), (120, 1)]);
+
+        let tickets: Vec<i64> = buckets.iter().map(|bucket| bucket.tickets).collect();
+        assert_eq!(tickets, [2, 2, 0, 1, 0, 1]);
+        assert_eq!(buckets[1].min_days, 7);
+        assert_eq!(buckets[1].max_days, Some(13));
+        assert_eq!(buckets[5].max_days, None);
+        assert_eq!(buckets[0].percent, 33.3);
+        assert_eq!(average, Some(30.3));
+
+        let (empty, average) = lead_times(&[]);
+        assert_eq!(empty[0].percent, 0.0);
+        assert_eq!(average, None);
+    }
+
+    fn counted(group: &str, route_id: Option<i32>, value: &str, passengers: i64) -> DemographicRow {
+        DemographicRow {
+            group_key: group.to_string(),
+            route_id,
+            value: value.to_string(),
+            passengers,
+        }
+    }
+
+    #[test]
+    fn demographics_are_grouped_with_ordered_counts() {
+        let groups = DemographicGroup::assemble(
+            DemographicsGrouping::Route,
+            vec![
+                counted("A1-B1", Some(1), "UA", 1),
+                counted("A2-B2", Some(2), "PL", 2),
+                counted("A2-B2", Some(2), "unknown", 3),
+            ],
+            vec![
+                counted("A1-B1", Some(1), "25-34", 1),
+                counted("A2-B2", Some(2), "unknown", 1),
+                counted("A2-B2", Some(2), "65+", 2),
+                counted("A2-B2", Some(2), "0-11", 2),
+            ],
+            vec![counted("A2-B2", Some(2), "unspecified", 5)],
+        );
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].route_id, Some(2));
+        assert_eq!(groups[0].passengers, 5);
+        assert_eq!(groups[0].nationalities[0].value, "unknown");
+        let ages: Vec<&str> = groups[0]
+            .age_brackets
+            .iter()
+            .map(|count| count.value.as_str())
+            .collect();
+        assert_eq!(ages, ["0-11", "65+", "unknown"]);
+        assert_eq!(groups[1].passengers, 1);
+        assert!(groups[1].genders.is_empty());
+    }
+}
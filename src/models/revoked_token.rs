@@ -0,0 +1,106 @@
+use std::{sync::OnceLock, time::Duration};
+
+use chrono::{DateTime, Utc};
+
+use crate::auth::Claims;
+use crate::db::DbPool;
+use crate::ttl_cache::TtlCache;
+
+// Verified tokens remembered at most, about a megabyte
+const CACHE_CAPACITY: usize = 10_000;
+
+// A remembered result of `is_revoked`, with what it can be invalidated by
+#[derive(Clone)]
+struct Check {
+    user_id: i32,
+    session_id: String,
+    revoked: bool,
+}
+
+// Keyed by jti; unset (every check reads the database) until `cache_checks` is called
+static CHECKS: OnceLock<TtlCache<String, Check>> = OnceLock::new();
+
+// Access tokens revoked before their expiry, keyed by the JWT id
+pub struct RevokedToken;
+
+impl RevokedToken {
+    // Remember revocation checks for `ttl` so the auth extractor does not query the database on
+    // every request. Revocations made by this instance apply at once; ones made by another
+    // instance take up to `ttl` to be seen here
+    pub fn cache_checks(ttl: Duration) {
+        if !ttl.is_zero() {
+            let _ = CHECKS.set(TtlCache::new(ttl, CACHE_CAPACITY));
+        }
+    }
+
+    // Forget remembered checks of one session's or one user's tokens after revoking them
+    pub fn forget_session(session_id: &str) {
+        if let Some(checks) = CHECKS.get() {
+            checks.invalidate(|_, check| check.session_id == session_id);
+        }
+    }
+
+    pub fn forget_user(user_id: i32) {
+        if let Some(checks) = CHECKS.get() {
+            checks.invalidate(|_, check| check.user_id == user_id);
+        }
+    }
+
+    // Revoke a single access token; rows are kept only until the token would have expired anyway
+    pub async fn revoke(pool: &DbPool, claims: &Claims) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < UTC_TIMESTAMP()")
+            .execute(pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT IGNORE INTO revoked_tokens (jti, user_id, expires_at, revoked_at)
+            VALUES (?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(&claims.jti)
+        .bind(claims.sub)
+        .bind(claims.expires_at())
+        .execute(pool)
+        .await?;
+
+        if let Some(checks) = CHECKS.get() {
+            checks.invalidate(|jti, _| *jti == claims.jti);
+        }
+        Ok(())
+    }
+
+    // Whether the token or its session was revoked, or it was issued before the user's
+    // last password change
+    pub async fn is_revoked(pool: &DbPool, claims: &Claims) -> Result<bool, sqlx::Error> {
+        let checks = CHECKS.get();
+        if let Some(check) = checks.and_then(|checks| checks.get(&claims.jti)) {
+            return Ok(check.revoked);
+        }
+
+        let (revoked, password_changed_at): (bool, Option<DateTime<Utc>>) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(SELECT 1 FROM revoked_tokens WHERE jti = ?)
+                   OR EXISTS(SELECT 1 FROM sessions WHERE session_id = ? AND revoked_at IS NOT NULL),
+                   (SELECT password_changed_at FROM users WHERE user_id = ?)
+            "#,
+        )
+        .bind(&claims.jti)
+        .bind(&claims.sid)
+        .bind(claims.sub)
+        .fetch_one(pool)
+        .await?;
+
+        let revoked =
+            revoked || password_changed_at.is_some_and(|changed| changed > claims.issued_at());
+        if let Some(checks) = checks {
+            let check = Check {
+                user_id: claims.sub,
+                session_id: claims.sid.clone(),
+                revoked,
+            };
+            checks.insert(claims.jti.clone(), check);
+        }
+        Ok(revoked)
+    }
+}
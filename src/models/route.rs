@@ -1,19 +1,71 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, MySql, MySqlPool, Pool};
+use sqlx::FromRow;
+
+use crate::db::{self, DbPool};
+
+// IANA timezone of an airport, e.g. `Europe/Kyiv`, stored and sent by name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AirportTimezone(pub Tz);
+
+impl AirportTimezone {
+    // `time` on the airport's clock, with the UTC offset in force there at that moment
+    pub fn local(&self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        time.with_timezone(&self.0).fixed_offset()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.0.name()
+    }
+}
+
+// Routes created before airports had timezones
+impl Default for AirportTimezone {
+    fn default() -> Self {
+        Self(Tz::UTC)
+    }
+}
+
+impl TryFrom<String> for AirportTimezone {
+    type Error = chrono_tz::ParseError;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse().map(Self)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Route {
     pub route_id: i32,
     pub origin: String,
     pub destination: String,
+    #[sqlx(try_from = "String")]
+    pub origin_timezone: AirportTimezone,
+    #[sqlx(try_from = "String")]
+    pub destination_timezone: AirportTimezone,
     pub distance: f32,
     pub estimated_duration: chrono::NaiveTime,
+    // Bumped by every write to the row; the route's ETag
+    pub updated_at: DateTime<Utc>,
+}
+
+// The code in brackets at the end of an airport's name; names without one are taken as codes
+pub fn airport_code(airport: &str) -> &str {
+    airport
+        .trim_end()
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once('('))
+        .map_or(airport, |(_, code)| code)
 }
 
 impl Route {
     pub fn new(
         origin: String,
         destination: String,
+        origin_timezone: AirportTimezone,
+        destination_timezone: AirportTimezone,
         distance: f32,
         estimated_duration: chrono::NaiveTime,
     ) -> Self {
@@ -21,23 +73,31 @@ impl Route {
             route_id: 0,
             origin,
             destination,
+            origin_timezone,
+            destination_timezone,
             distance,
             estimated_duration,
+            updated_at: Utc::now(),
         }
     }
 
-    pub async fn find_by_id(pool: &Pool<MySql>, id: i32) -> Result<Option<Self>, sqlx::Error> {
+    // IATA code of the origin airport, e.g. KBP for "Kyiv (KBP)"
+    pub fn origin_code(&self) -> &str {
+        airport_code(&self.origin)
+    }
+
+    pub fn destination_code(&self) -> &str {
+        airport_code(&self.destination)
+    }
+
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
         sqlx::query_as::<_, Self>("SELECT * FROM routes WHERE route_id = ?")
             .bind(id)
             .fetch_optional(pool)
             .await
     }
 
-    pub async fn find_all(
-        pool: &MySqlPool,
-        page: i32,
-        limit: i32,
-    ) -> Result<Vec<Self>, sqlx::Error> {
+    pub async fn find_all(pool: &DbPool, page: i32, limit: i32) -> Result<Vec<Self>, sqlx::Error> {
         let offset = (page - 1) * limit;
         sqlx::query_as::<_, Self>("SELECT * FROM routes LIMIT ? OFFSET ?")
             .bind(limit)
@@ -46,10 +106,57 @@ impl Route {
             .await
     }
 
-    pub async fn count(pool: &MySqlPool) -> Result<i64, sqlx::Error> {
+    // Keyset page in route id order, starting after `after_id`
+    pub async fn find_after(
+        pool: &DbPool,
+        after_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM routes WHERE route_id > ? ORDER BY route_id LIMIT ?",
+        )
+        .bind(after_id.unwrap_or(0))
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn count(pool: &DbPool) -> Result<i64, sqlx::Error> {
         let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM routes")
             .fetch_one(pool)
             .await?;
         Ok(count)
     }
+
+    // Insert new record
+    pub async fn insert(&self, pool: &DbPool) -> Result<i32, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO routes (origin, destination, origin_timezone, destination_timezone, distance, estimated_duration)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&self.origin)
+        .bind(&self.destination)
+        .bind(self.origin_timezone.name())
+        .bind(self.destination_timezone.name())
+        .bind(self.distance)
+        .bind(self.estimated_duration)
+        .execute(pool)
+        .await?;
+
+        Ok(db::last_insert_id(&result) as i32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn airport_codes_come_from_the_brackets() {
+        assert_eq!(airport_code("Kyiv (KBP)"), "KBP");
+        assert_eq!(airport_code("Frankfurt am Main (FRA)"), "FRA");
+        assert_eq!(airport_code("LWO"), "LWO");
+    }
 }
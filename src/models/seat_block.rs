@@ -0,0 +1,138 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::db::{self, DbPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SeatBlockReason {
+    CrewRest,
+    Equipment,
+    WeightAndBalance,
+}
+
+// A seat held back by operations; never offered to passengers
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SeatBlock {
+    pub block_id: i32,
+    pub flight_id: i32,
+    pub seat_number: String,
+    pub reason: SeatBlockReason,
+    pub note: Option<String>,
+    pub blocked_by: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+// Seat usage of a flight
+#[derive(Debug, Clone, Serialize)]
+pub struct Occupancy {
+    pub flight_id: i32,
+    pub capacity: i64,
+    pub booked: i64,
+    pub blocked: i64,
+    pub available: i64,
+    pub load_factor: f64,
+}
+
+impl SeatBlock {
+    pub async fn find_by_flight(pool: &DbPool, flight_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM seat_blocks WHERE flight_id = ? ORDER BY seat_number",
+        )
+        .bind(flight_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Whether the seat is already ticketed or blocked
+    pub async fn seat_taken(
+        pool: &DbPool,
+        flight_id: i32,
+        seat_number: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let (taken,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT (SELECT COUNT(*) FROM tickets WHERE flight_id = ? AND seat_number = ?)
+                 + (SELECT COUNT(*) FROM seat_blocks WHERE flight_id = ? AND seat_number = ?)
+            "#,
+        )
+        .bind(flight_id)
+        .bind(seat_number)
+        .bind(flight_id)
+        .bind(seat_number)
+        .fetch_one(pool)
+        .await?;
+        Ok(taken > 0)
+    }
+
+    pub async fn create(
+        pool: &DbPool,
+        flight_id: i32,
+        seat_number: &str,
+        reason: SeatBlockReason,
+        note: Option<&str>,
+        blocked_by: i32,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO seat_blocks (flight_id, seat_number, reason, note, blocked_by, created_at)
+            VALUES (?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(flight_id)
+        .bind(seat_number)
+        .bind(reason)
+        .bind(note)
+        .bind(blocked_by)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM seat_blocks WHERE block_id = ?")
+            .bind(db::last_insert_id(&result) as i32)
+            .fetch_one(pool)
+            .await
+    }
+
+    pub async fn delete(pool: &DbPool, flight_id: i32, block_id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM seat_blocks WHERE block_id = ? AND flight_id = ?")
+            .bind(block_id)
+            .bind(flight_id)
+            .execute(pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Capacity, booked and blocked seats of a flight; blocked seats are not available for sale
+    pub async fn occupancy(pool: &DbPool, flight_id: i32) -> Result<Occupancy, sqlx::Error> {
+        let (capacity, booked, blocked): (i32, i64, i64) = sqlx::query_as(
+            r#"
+            SELECT a.capacity,
+                   (SELECT COUNT(*) FROM tickets t WHERE t.flight_id = f.flight_id),
+                   (SELECT COUNT(*) FROM seat_blocks b WHERE b.flight_id = f.flight_id)
+            FROM flights f
+            JOIN aircraft a ON a.aircraft_id = f.aircraft_id
+            WHERE f.flight_id = ?
+            "#,
+        )
+        .bind(flight_id)
+        .fetch_one(pool)
+        .await?;
+
+        let capacity = capacity as i64;
+        let sellable = capacity - blocked;
+        Ok(Occupancy {
+            flight_id,
+            capacity,
+            booked,
+            blocked,
+            available: (sellable - booked).max(0),
+            load_factor: if sellable > 0 {
+                booked as f64 / sellable as f64
+            } else {
+                0.0
+            },
+        })
+    }
+}
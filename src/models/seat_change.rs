@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::db::{self, DbPool};
+
+// A move of a ticket to another seat after booking
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SeatChange {
+    pub change_id: i32,
+    pub ticket_id: i32,
+    pub from_seat: String,
+    pub to_seat: String,
+    // Charged for moving to a premium seat, 0 otherwise
+    pub fee: f64,
+    pub currency: String,
+    pub changed_by: Option<i32>,
+    pub changed_at: DateTime<Utc>,
+}
+
+impl SeatChange {
+    pub async fn find_by_ticket(pool: &DbPool, ticket_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM seat_changes WHERE ticket_id = ? ORDER BY change_id",
+        )
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Move the ticket from `from_seat` to `to_seat` and record the change in one transaction.
+    // None if the ticket moved or the seat was blocked meanwhile; a seat booked meanwhile fails
+    // on the unique seat key
+    pub async fn apply(
+        pool: &DbPool,
+        ticket_id: i32,
+        from_seat: &str,
+        to_seat: &str,
+        fee: (f64, &str),
+        changed_by: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE tickets t SET t.seat_number = ?
+            WHERE t.ticket_id = ? AND t.seat_number = ?
+              AND NOT EXISTS (
+                  SELECT 1 FROM seat_blocks b WHERE b.flight_id = t.flight_id AND b.seat_number = ?
+              )
+            "#,
+        )
+        .bind(to_seat)
+        .bind(ticket_id)
+        .bind(from_seat)
+        .bind(to_seat)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO seat_changes
+                (ticket_id, from_seat, to_seat, fee, currency, changed_by, changed_at)
+            VALUES (?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(ticket_id)
+        .bind(from_seat)
+        .bind(to_seat)
+        .bind(fee.0)
+        .bind(fee.1)
+        .bind(changed_by)
+        .execute(&mut *tx)
+        .await?;
+
+        let change = sqlx::query_as::<_, Self>("SELECT * FROM seat_changes WHERE change_id = ?")
+            .bind(db::last_insert_id(&result) as i32)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(change))
+    }
+}
@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::RevokedToken;
+use crate::db::{DbConnection, DbPool};
+
+// A login on one device. Its refresh tokens and access tokens carry the session id,
+// so revoking the session signs that device out
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Session {
+    pub session_id: String,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    // Last login or token refresh
+    pub last_used_at: DateTime<Utc>,
+}
+
+impl Session {
+    pub async fn create(
+        pool: &DbPool,
+        user_id: i32,
+        user_agent: Option<&str>,
+        ip_address: Option<&str>,
+    ) -> Result<String, sqlx::Error> {
+        let session_id = Uuid::new_v4().to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO sessions (session_id, user_id, user_agent, ip_address, created_at, last_used_at)
+            VALUES (?, ?, ?, ?, UTC_TIMESTAMP(), UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(&session_id)
+        .bind(user_id)
+        .bind(user_agent)
+        .bind(ip_address)
+        .execute(pool)
+        .await?;
+
+        Ok(session_id)
+    }
+
+    pub async fn find_active_by_user(
+        pool: &DbPool,
+        user_id: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT session_id, user_agent, ip_address, created_at, last_used_at
+            FROM sessions
+            WHERE user_id = ? AND revoked_at IS NULL
+            ORDER BY last_used_at DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Every session the user ever had, revoked ones included
+    pub async fn find_by_user(pool: &DbPool, user_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT session_id, user_agent, ip_address, created_at, last_used_at
+            FROM sessions
+            WHERE user_id = ?
+            ORDER BY created_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn touch(conn: &mut DbConnection, session_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE sessions SET last_used_at = UTC_TIMESTAMP() WHERE session_id = ?")
+            .bind(session_id)
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+
+    // Revoke one of the user's sessions; false if it does not exist or is already revoked
+    pub async fn revoke(
+        pool: &DbPool,
+        user_id: i32,
+        session_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE sessions SET revoked_at = UTC_TIMESTAMP() WHERE session_id = ? AND user_id = ? AND revoked_at IS NULL",
+        )
+        .bind(session_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        Self::revoke_in(&mut tx, session_id).await?;
+        tx.commit().await?;
+        RevokedToken::forget_session(session_id);
+        Ok(true)
+    }
+
+    // Mark the session and all of its refresh tokens revoked. The caller drops cached token checks
+    // with `RevokedToken::forget_session` once committed, or a check made in between could
+    // remember the session as live
+    pub async fn revoke_in(conn: &mut DbConnection, session_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE sessions SET revoked_at = UTC_TIMESTAMP() WHERE session_id = ? AND revoked_at IS NULL",
+        )
+        .bind(session_id)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = UTC_TIMESTAMP() WHERE session_id = ? AND revoked_at IS NULL",
+        )
+        .bind(session_id)
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    // Sign the user out everywhere, e.g. after a password change; the caller calls
+    // `RevokedToken::forget_user` once committed
+    pub async fn revoke_all_for_user(
+        conn: &mut DbConnection,
+        user_id: i32,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE sessions SET revoked_at = UTC_TIMESTAMP() WHERE user_id = ? AND revoked_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = UTC_TIMESTAMP() WHERE user_id = ? AND revoked_at IS NULL",
+        )
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+
+// Direction of an `order=` query parameter
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+
+    // Comparison that selects the rows after a keyset cursor in this direction
+    pub fn after_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::FlightStatus;
+use crate::db::DbPool;
+
+// Shareable, unauthenticated link to the status of a ticket's flight
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct StatusToken {
+    pub token: String,
+    pub ticket_id: i32,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+// What a status link reveals: no passenger data, only the flight's progress
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct PublicFlightStatus {
+    pub flight_number: String,
+    pub origin: String,
+    pub destination: String,
+    pub status: FlightStatus,
+    pub gate: Option<String>,
+    pub terminal: Option<String>,
+    pub departure_time: DateTime<Utc>,
+    pub arrival_time: DateTime<Utc>,
+}
+
+impl StatusToken {
+    // Issue a token that stays valid until a day after the flight arrives
+    pub async fn create(pool: &DbPool, ticket_id: i32) -> Result<Self, sqlx::Error> {
+        let token = Uuid::new_v4().simple().to_string();
+
+        sqlx::query(
+            r#"
+            INSERT INTO flight_status_tokens (token, ticket_id, created_at, expires_at)
+            SELECT ?, t.ticket_id, UTC_TIMESTAMP(), f.arrival_time + INTERVAL 1 DAY
+            FROM tickets t
+            JOIN flights f ON f.flight_id = t.flight_id
+            WHERE t.ticket_id = ?
+            "#,
+        )
+        .bind(&token)
+        .bind(ticket_id)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM flight_status_tokens WHERE token = ?")
+            .bind(&token)
+            .fetch_one(pool)
+            .await
+    }
+
+    pub async fn flight_status(
+        pool: &DbPool,
+        token: &str,
+    ) -> Result<Option<PublicFlightStatus>, sqlx::Error> {
+        sqlx::query_as::<_, PublicFlightStatus>(
+            r#"
+            SELECT f.flight_number, r.origin, r.destination, f.status, f.gate,
+                   f.terminal, f.departure_time, f.arrival_time
+            FROM flight_status_tokens st
+            JOIN tickets t ON t.ticket_id = st.ticket_id
+            JOIN flights f ON f.flight_id = t.flight_id
+            JOIN routes r ON r.route_id = f.route_id
+            WHERE st.token = ? AND st.expires_at > UTC_TIMESTAMP()
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(pool)
+        .await
+    }
+}
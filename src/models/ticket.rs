@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::{FareClass, Meal, PassengerType};
+use crate::db::DbPool;
+use crate::export::{self, RowSender};
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Ticket {
+    pub ticket_id: i32,
+    pub ticket_number: String,
+    pub user_id: i32,
+    pub flight_id: i32,
+    pub seat_number: String,
+    pub fare_class: FareClass,
+    #[serde(default)]
+    pub passenger_type: PassengerType,
+    // Flight number the ticket was sold under, the operating one or a code-share's
+    #[serde(default)]
+    pub sold_as: Option<String>,
+    pub checked_in: bool,
+    // Not checked in by departure
+    #[serde(default)]
+    pub no_show: bool,
+    pub special_requests: Option<String>,
+    #[serde(default)]
+    pub meal: Option<Meal>,
+    // Booking group the ticket was issued in, if any
+    #[serde(default)]
+    pub group_id: Option<i32>,
+    // What was paid for the ticket; not recorded for tickets issued before fares were
+    #[serde(default)]
+    pub fare: Option<f64>,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+// Number printed on a newly issued or reissued ticket
+pub(crate) fn new_ticket_number() -> String {
+    format!(
+        "TK{}",
+        Uuid::new_v4().simple().to_string()[..10].to_uppercase()
+    )
+}
+
+impl Ticket {
+    // Mark the tickets of departed flights that were never checked in; returns how many
+    pub async fn flag_no_shows(pool: &DbPool) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE tickets t
+            JOIN flights f ON f.flight_id = t.flight_id
+            SET t.no_show = TRUE
+            WHERE f.status IN ('departed', 'arrived') AND t.checked_in = FALSE AND t.no_show = FALSE
+            "#,
+        )
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    // Check the ticket in unless it has assistance requests staff have not acknowledged yet.
+    // Checking in twice is harmless
+    pub async fn check_in(pool: &DbPool, id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE tickets SET checked_in = TRUE
+            WHERE ticket_id = ?
+              AND NOT EXISTS (
+                  SELECT 1 FROM assistance_requests
+                  WHERE ticket_id = ? AND acknowledged_at IS NULL
+              )
+            "#,
+        )
+        .bind(id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_meal(pool: &DbPool, id: i32, meal: Option<Meal>) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE tickets SET meal = ? WHERE ticket_id = ?")
+            .bind(meal)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn find_by_id(pool: &DbPool, id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM tickets WHERE ticket_id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_by_group(pool: &DbPool, group_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM tickets WHERE group_id = ? ORDER BY ticket_id")
+            .bind(group_id)
+            .fetch_all(pool)
+            .await
+    }
+
+    pub async fn find_by_user(pool: &DbPool, user_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM tickets WHERE user_id = ? ORDER BY ticket_id DESC")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await
+    }
+
+    // The user's tickets for a download
+    pub async fn export_by_user(pool: &DbPool, user_id: i32, rows: RowSender<Self>) {
+        let query = sqlx::query_as::<_, Self>(
+            "SELECT * FROM tickets WHERE user_id = ? ORDER BY ticket_id DESC",
+        )
+        .bind(user_id);
+        export::forward(query.fetch(pool), &rows).await;
+    }
+}
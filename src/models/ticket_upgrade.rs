@@ -0,0 +1,193 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+
+use super::ticket::new_ticket_number;
+use super::{FareClass, Ticket};
+use crate::db::{self, DbPool};
+
+// A paid move of a ticket to a higher class; the ticket is reissued under a new number
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TicketUpgrade {
+    pub upgrade_id: i32,
+    pub ticket_id: i32,
+    pub from_class: FareClass,
+    pub to_class: FareClass,
+    pub from_seat: String,
+    pub to_seat: String,
+    pub previous_ticket_number: String,
+    pub ticket_number: String,
+    // The fare difference between the classes, added to the ticket's fare in its currency
+    pub price: f64,
+    pub currency: String,
+    pub upgraded_by: Option<i32>,
+    pub upgraded_at: DateTime<Utc>,
+}
+
+// Outcome of buying an upgrade
+pub enum UpgradeOutcome {
+    Upgraded(TicketUpgrade),
+    // No sellable seat is left in the class
+    ClassFull,
+    // The requested seat was booked or blocked meanwhile
+    SeatTaken(String),
+    // The ticket changed class or seat meanwhile, or its fare is in another currency
+    TicketChanged,
+}
+
+impl TicketUpgrade {
+    pub async fn find_by_ticket(pool: &DbPool, ticket_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM ticket_upgrades WHERE ticket_id = ? ORDER BY upgrade_id",
+        )
+        .bind(ticket_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Move the ticket to `to_class`, in `seat_number` or the first free seat of the class, add the
+    // price to its fare, reissue it and record the upgrade, all in one transaction. The price must
+    // be in the ticket's currency; a ticket without a fare takes the price's. The flight row
+    // is locked so concurrent bookings and upgrades see each other's tickets when counting seats
+    pub async fn purchase(
+        pool: &DbPool,
+        ticket: &Ticket,
+        to_class: FareClass,
+        seat_number: Option<&str>,
+        price: (f64, &str),
+        upgraded_by: i32,
+    ) -> Result<UpgradeOutcome, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("SELECT flight_id FROM flights WHERE flight_id = ? FOR UPDATE")
+            .bind(ticket.flight_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let (available,): (i64,) = sqlx::query_as(
+            r#"
+            SELECT CAST(COALESCE(MAX(fc.seat_count), 0) AS SIGNED) - COUNT(t.ticket_id)
+            FROM flight_fare_classes fc
+            LEFT JOIN tickets t ON t.flight_id = fc.flight_id AND t.fare_class = fc.fare_class
+            WHERE fc.flight_id = ? AND fc.fare_class = ?
+            "#,
+        )
+        .bind(ticket.flight_id)
+        .bind(to_class)
+        .fetch_one(&mut *tx)
+        .await?;
+        if available < 1 {
+            tx.rollback().await?;
+            return Ok(UpgradeOutcome::ClassFull);
+        }
+
+        let to_seat = match seat_number {
+            Some(seat_number) => {
+                let (taken,): (i64,) = sqlx::query_as(
+                    r#"
+                    SELECT (SELECT COUNT(*) FROM tickets WHERE flight_id = ? AND seat_number = ?)
+                         + (SELECT COUNT(*) FROM seat_blocks
+                            WHERE flight_id = ? AND seat_number = ?)
+                    "#,
+                )
+                .bind(ticket.flight_id)
+                .bind(seat_number)
+                .bind(ticket.flight_id)
+                .bind(seat_number)
+                .fetch_one(&mut *tx)
+                .await?;
+                if taken > 0 {
+                    tx.rollback().await?;
+                    return Ok(UpgradeOutcome::SeatTaken(seat_number.to_string()));
+                }
+                seat_number.to_string()
+            }
+            None => {
+                let free: Option<String> = sqlx::query_scalar(
+                    r#"
+                    SELECT s.seat_number
+                    FROM flight_seats s
+                    WHERE s.flight_id = ? AND s.fare_class = ?
+                      AND NOT EXISTS (
+                          SELECT 1 FROM tickets t
+                          WHERE t.flight_id = s.flight_id AND t.seat_number = s.seat_number
+                      )
+                      AND NOT EXISTS (
+                          SELECT 1 FROM seat_blocks b
+                          WHERE b.flight_id = s.flight_id AND b.seat_number = s.seat_number
+                      )
+                    ORDER BY s.seat_row, s.seat_number
+                    LIMIT 1
+                    "#,
+                )
+                .bind(ticket.flight_id)
+                .bind(to_class)
+                .fetch_optional(&mut *tx)
+                .await?;
+                match free {
+                    Some(seat_number) => seat_number,
+                    None => {
+                        tx.rollback().await?;
+                        return Ok(UpgradeOutcome::ClassFull);
+                    }
+                }
+            }
+        };
+
+        let ticket_number = new_ticket_number();
+        let result = sqlx::query(
+            r#"
+            UPDATE tickets
+            SET fare_class = ?, seat_number = ?, ticket_number = ?, fare = COALESCE(fare, 0) + ?,
+                currency = COALESCE(currency, ?)
+            WHERE ticket_id = ? AND fare_class = ? AND seat_number = ?
+              AND (currency IS NULL OR currency = ?)
+            "#,
+        )
+        .bind(to_class)
+        .bind(&to_seat)
+        .bind(&ticket_number)
+        .bind(price.0)
+        .bind(price.1)
+        .bind(ticket.ticket_id)
+        .bind(ticket.fare_class)
+        .bind(&ticket.seat_number)
+        .bind(price.1)
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(UpgradeOutcome::TicketChanged);
+        }
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO ticket_upgrades
+                (ticket_id, from_class, to_class, from_seat, to_seat, previous_ticket_number,
+                 ticket_number, price, currency, upgraded_by, upgraded_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(ticket.ticket_id)
+        .bind(ticket.fare_class)
+        .bind(to_class)
+        .bind(&ticket.seat_number)
+        .bind(&to_seat)
+        .bind(&ticket.ticket_number)
+        .bind(&ticket_number)
+        .bind(price.0)
+        .bind(price.1)
+        .bind(upgraded_by)
+        .execute(&mut *tx)
+        .await?;
+
+        let upgrade =
+            sqlx::query_as::<_, Self>("SELECT * FROM ticket_upgrades WHERE upgrade_id = ?")
+                .bind(db::last_insert_id(&result) as i32)
+                .fetch_one(&mut *tx)
+                .await?;
+
+        tx.commit().await?;
+        Ok(UpgradeOutcome::Upgraded(upgrade))
+    }
+}
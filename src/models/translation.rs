@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::db::DbPool;
+
+// A display text (airport name, fare rule description, policy text, ...) in one locale.
+// Keys are dotted paths such as `airport.KBP.name` or `policy.cancellation`.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Translation {
+    pub content_key: String,
+    pub locale: String,
+    pub value: String,
+}
+
+// Text resolved for the caller's language
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalizedText {
+    pub content_key: String,
+    pub locale: String,
+    pub value: String,
+}
+
+// Pick the first locale the caller accepts, or any available one as a last resort
+fn pick(mut candidates: Vec<Translation>, locales: &[String]) -> Option<LocalizedText> {
+    if candidates.is_empty() {
+        return None;
+    }
+    let index = locales
+        .iter()
+        .find_map(|locale| candidates.iter().position(|t| &t.locale == locale))
+        .unwrap_or(0);
+    let chosen = candidates.swap_remove(index);
+    Some(LocalizedText {
+        content_key: chosen.content_key,
+        locale: chosen.locale,
+        value: chosen.value,
+    })
+}
+
+impl Translation {
+    pub async fn find_by_key(pool: &DbPool, key: &str) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM content_translations WHERE content_key = ? ORDER BY locale",
+        )
+        .bind(key)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn resolve(
+        pool: &DbPool,
+        key: &str,
+        locales: &[String],
+    ) -> Result<Option<LocalizedText>, sqlx::Error> {
+        Ok(pick(Self::find_by_key(pool, key).await?, locales))
+    }
+
+    // Resolve every key under a prefix, e.g. `airport.` for all airport names
+    pub async fn resolve_prefix(
+        pool: &DbPool,
+        prefix: &str,
+        locales: &[String],
+    ) -> Result<Vec<LocalizedText>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, Self>(
+            "SELECT * FROM content_translations WHERE content_key LIKE CONCAT(?, '%') ORDER BY content_key, locale",
+        )
+        .bind(prefix.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"))
+        .fetch_all(pool)
+        .await?;
+
+        let mut resolved = Vec::new();
+        let mut group: Vec<Translation> = Vec::new();
+        for row in rows {
+            if group
+                .first()
+                .is_some_and(|t| t.content_key != row.content_key)
+            {
+                resolved.extend(pick(std::mem::take(&mut group), locales));
+            }
+            group.push(row);
+        }
+        resolved.extend(pick(group, locales));
+        Ok(resolved)
+    }
+
+    pub async fn upsert(
+        pool: &DbPool,
+        key: &str,
+        locale: &str,
+        value: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO content_translations (content_key, locale, value)
+            VALUES (?, ?, ?)
+            ON DUPLICATE KEY UPDATE value = VALUES(value)
+            "#,
+        )
+        .bind(key)
+        .bind(locale)
+        .bind(value)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete(pool: &DbPool, key: &str, locale: &str) -> Result<bool, sqlx::Error> {
+        let result =
+            sqlx::query("DELETE FROM content_translations WHERE content_key = ? AND locale = ?")
+                .bind(key)
+                .bind(locale)
+                .execute(pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::db::DbPool;
+
+// A user's consent for another account holder to book tickets in their name
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TravelCompanion {
+    pub user_id: i32,
+    pub companion_id: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TravelCompanion {
+    // Who may book for the user, most recent first
+    pub async fn find_by_user(pool: &DbPool, user_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM travel_companions WHERE user_id = ? ORDER BY created_at DESC, companion_id",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Let `companion_id` book for `user_id`; granting it again keeps the first consent
+    pub async fn grant(
+        pool: &DbPool,
+        user_id: i32,
+        companion_id: i32,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT IGNORE INTO travel_companions (user_id, companion_id, created_at)
+            VALUES (?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(user_id)
+        .bind(companion_id)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM travel_companions WHERE user_id = ? AND companion_id = ?",
+        )
+        .bind(user_id)
+        .bind(companion_id)
+        .fetch_one(pool)
+        .await
+    }
+
+    // Whether there was a consent to withdraw
+    pub async fn revoke(
+        pool: &DbPool,
+        user_id: i32,
+        companion_id: i32,
+    ) -> Result<bool, sqlx::Error> {
+        let result =
+            sqlx::query("DELETE FROM travel_companions WHERE user_id = ? AND companion_id = ?")
+                .bind(user_id)
+                .bind(companion_id)
+                .execute(pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Those of `user_ids` who let `companion_id` book for them; ids of users that do not exist
+    // are never among them
+    pub async fn allowing(
+        pool: &DbPool,
+        companion_id: i32,
+        user_ids: &[i32],
+    ) -> Result<Vec<i32>, sqlx::Error> {
+        let mut allowing = Vec::new();
+        for user_id in user_ids {
+            let granted: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM travel_companions WHERE user_id = ? AND companion_id = ?)",
+            )
+            .bind(user_id)
+            .bind(companion_id)
+            .fetch_one(pool)
+            .await?;
+            if granted {
+                allowing.push(*user_id);
+            }
+        }
+        Ok(allowing)
+    }
+}
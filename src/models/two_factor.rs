@@ -0,0 +1,183 @@
+use chrono::{DateTime, Utc};
+use sqlx::FromRow;
+
+use crate::db::{DbConnection, DbPool};
+
+// Backup codes handed out on activation and on every regeneration
+pub const BACKUP_CODE_COUNT: usize = 10;
+// Wrong login codes in a row before login codes are refused for `LOCKOUT_MINUTES`
+pub const MAX_FAILED_ATTEMPTS: i32 = 5;
+pub const LOCKOUT_MINUTES: i64 = 15;
+
+// A user's TOTP secret; 2FA is enforced at login only once the enrollment is activated
+#[derive(Debug, Clone, FromRow)]
+pub struct UserTotp {
+    pub user_id: i32,
+    pub secret: String,
+    pub enabled_at: Option<DateTime<Utc>>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl UserTotp {
+    pub async fn find(pool: &DbPool, user_id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT user_id, secret, enabled_at, locked_until FROM user_totp WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+
+    // Whether login codes are refused after too many wrong ones
+    pub fn is_locked(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|until| until > now)
+    }
+
+    // Count a wrong login code; the `MAX_FAILED_ATTEMPTS`th in a row locks login codes for
+    // `LOCKOUT_MINUTES` and starts the count over
+    pub async fn record_failure(pool: &DbPool, user_id: i32) -> Result<(), sqlx::Error> {
+        // MySQL applies assignments left to right, so `failed_attempts` below is already
+        // incremented
+        sqlx::query(
+            r#"
+            UPDATE user_totp
+            SET failed_attempts = failed_attempts + 1,
+                locked_until = IF(failed_attempts >= ?, UTC_TIMESTAMP() + INTERVAL ? MINUTE,
+                                  locked_until),
+                failed_attempts = IF(failed_attempts >= ?, 0, failed_attempts)
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(MAX_FAILED_ATTEMPTS)
+        .bind(LOCKOUT_MINUTES)
+        .bind(MAX_FAILED_ATTEMPTS)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // Start the count of wrong login codes over after an accepted one
+    pub async fn reset_failures(pool: &DbPool, user_id: i32) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE user_totp SET failed_attempts = 0 WHERE user_id = ? AND failed_attempts > 0",
+        )
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // Store a new pending secret, replacing an earlier enrollment that was never activated
+    pub async fn start_enrollment(
+        pool: &DbPool,
+        user_id: i32,
+        secret: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO user_totp
+                (user_id, secret, enabled_at, last_used_step, failed_attempts, locked_until,
+                 created_at)
+            VALUES (?, ?, NULL, NULL, 0, NULL, UTC_TIMESTAMP())
+            ON DUPLICATE KEY UPDATE secret = VALUES(secret), enabled_at = NULL,
+                                    last_used_step = NULL, failed_attempts = 0,
+                                    locked_until = NULL, created_at = VALUES(created_at)
+            "#,
+        )
+        .bind(user_id)
+        .bind(secret)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // Accept a code's time step once; a step at or before the last accepted one is a replay
+    pub async fn consume_step(pool: &DbPool, user_id: i32, step: u64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE user_totp SET last_used_step = ?
+            WHERE user_id = ? AND (last_used_step IS NULL OR last_used_step < ?)
+            "#,
+        )
+        .bind(step as i64)
+        .bind(user_id)
+        .bind(step as i64)
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    // Turn on 2FA for the user together with their first set of backup codes
+    pub async fn activate(
+        pool: &DbPool,
+        user_id: i32,
+        backup_codes: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("UPDATE user_totp SET enabled_at = UTC_TIMESTAMP() WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        Self::insert_backup_codes(&mut tx, user_id, backup_codes).await?;
+
+        tx.commit().await
+    }
+
+    // Replace all backup codes of the user, used or not
+    pub async fn replace_backup_codes(
+        pool: &DbPool,
+        user_id: i32,
+        backup_codes: &[String],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        Self::insert_backup_codes(&mut tx, user_id, backup_codes).await?;
+        tx.commit().await
+    }
+
+    // Spend a backup code; each one works once
+    pub async fn use_backup_code(
+        pool: &DbPool,
+        user_id: i32,
+        code: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE totp_backup_codes SET used_at = UTC_TIMESTAMP()
+            WHERE user_id = ? AND code_hash = SHA2(?, 256) AND used_at IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(code.to_ascii_uppercase())
+        .execute(pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn insert_backup_codes(
+        conn: &mut DbConnection,
+        user_id: i32,
+        backup_codes: &[String],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM totp_backup_codes WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *conn)
+            .await?;
+
+        for code in backup_codes {
+            sqlx::query(
+                "INSERT INTO totp_backup_codes (user_id, code_hash, used_at) VALUES (?, SHA2(?, 256), NULL)",
+            )
+            .bind(user_id)
+            .bind(code)
+            .execute(&mut *conn)
+            .await?;
+        }
+        Ok(())
+    }
+}
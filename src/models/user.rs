@@ -0,0 +1,744 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use super::{RevokedToken, Session, SortOrder, UserPermissions};
+use crate::db::{self, DbConnection, DbPool};
+use crate::export::{self, ExportFormat, RowSender};
+use crate::i18n::Locale;
+use crate::routes::CURRENT_PREFIX;
+use crate::signed_url;
+use crate::validation::{self, in_the_past, not_blank, phone_number};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UserRole {
+    Admin,
+    Worker,
+    User,
+}
+
+impl UserRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserRole::Admin => "admin",
+            UserRole::Worker => "worker",
+            UserRole::User => "user",
+        }
+    }
+
+    pub fn is_staff(&self) -> bool {
+        matches!(self, UserRole::Admin | UserRole::Worker)
+    }
+}
+
+// Operational job of a Worker, used to narrow endpoints down from all staff
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum StaffPosition {
+    GateAgent,
+    CheckInAgent,
+    Pilot,
+    Dispatcher,
+    Engineer,
+}
+
+impl StaffPosition {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StaffPosition::GateAgent => "gate_agent",
+            StaffPosition::CheckInAgent => "check_in_agent",
+            StaffPosition::Pilot => "pilot",
+            StaffPosition::Dispatcher => "dispatcher",
+            StaffPosition::Engineer => "engineer",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct User {
+    pub user_id: i32,
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    #[serde(skip_serializing)]
+    pub password: String,
+    pub passport_number: Option<String>,
+    pub nationality: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    #[serde(default)]
+    pub gender: Option<Gender>,
+    pub role: UserRole,
+    // Only set for Workers
+    pub staff_position: Option<StaffPosition>,
+    // File name of the profile photo in storage, sent as a signed `avatar_url`
+    #[serde(
+        rename = "avatar_url",
+        serialize_with = "avatar_url",
+        skip_deserializing
+    )]
+    pub avatar_key: Option<String>,
+    // Language of the user's notifications
+    pub language: Locale,
+    // Bumped by every write to the row; the profile's ETag
+    pub updated_at: DateTime<Utc>,
+}
+
+fn avatar_url<S: serde::Serializer>(
+    key: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    key.as_deref()
+        .map(|file| signed_url::sign(&format!("{}/avatars/{}", CURRENT_PREFIX, file)))
+        .serialize(serializer)
+}
+
+// Account to create; the password is already hashed
+#[derive(Debug)]
+pub struct NewUser {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub password_hash: String,
+    pub role: UserRole,
+    pub staff_position: Option<StaffPosition>,
+}
+
+// Passenger account created by a bulk import, with the profile filled in up front
+#[derive(Debug)]
+pub struct NewPassenger {
+    pub first_name: String,
+    pub last_name: String,
+    pub email: String,
+    pub phone: Option<String>,
+    pub passport_number: Option<String>,
+    pub nationality: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+}
+
+// As given on the profile; not saying is None
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Gender {
+    Female,
+    Male,
+    Other,
+}
+
+// Self-service profile changes; absent fields keep their current value
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateProfile {
+    #[validate(custom(function = "not_blank"), length(max = 100))]
+    pub first_name: Option<String>,
+    #[validate(custom(function = "not_blank"), length(max = 100))]
+    pub last_name: Option<String>,
+    #[validate(custom(function = "phone_number"))]
+    pub phone: Option<String>,
+    #[validate(custom(function = "validation::passport_number"))]
+    pub passport_number: Option<String>,
+    #[validate(length(max = 64))]
+    pub nationality: Option<String>,
+    #[validate(custom(function = "in_the_past"))]
+    pub date_of_birth: Option<NaiveDate>,
+    pub gender: Option<Gender>,
+    pub language: Option<Locale>,
+}
+
+impl UpdateProfile {
+    // Names of the fields the update sets, for the audit log
+    pub fn changed_fields(&self) -> Vec<&'static str> {
+        [
+            ("first_name", self.first_name.is_some()),
+            ("last_name", self.last_name.is_some()),
+            ("phone", self.phone.is_some()),
+            ("passport_number", self.passport_number.is_some()),
+            ("nationality", self.nationality.is_some()),
+            ("date_of_birth", self.date_of_birth.is_some()),
+            ("gender", self.gender.is_some()),
+            ("language", self.language.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, set)| set.then_some(name))
+        .collect()
+    }
+}
+
+// Columns the user listing can be sorted by (`sort=`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSort {
+    #[default]
+    LastName,
+    FirstName,
+    Email,
+    UserId,
+}
+
+impl UserSort {
+    fn column(&self) -> &'static str {
+        match self {
+            UserSort::LastName => "last_name",
+            UserSort::FirstName => "first_name",
+            UserSort::Email => "email",
+            UserSort::UserId => "user_id",
+        }
+    }
+}
+
+// Query parameters of the user listing; every filter is optional
+#[derive(Debug, Default, Deserialize)]
+pub struct UserFilter {
+    pub role: Option<UserRole>,
+    pub staff_position: Option<StaffPosition>,
+    pub nationality: Option<String>,
+    pub sort: Option<UserSort>,
+    pub order: Option<SortOrder>,
+    pub page: Option<i32>,
+    pub limit: Option<i32>,
+    // Keyset pagination, only with the default sort
+    pub cursor: Option<String>,
+    // csv or xlsx to download every matching user
+    pub format: Option<ExportFormat>,
+}
+
+impl UserFilter {
+    // Sort column and direction, ties broken by user id so pages never overlap
+    fn order_by(&self) -> String {
+        let order = self.order.unwrap_or_default().as_sql();
+        match self.sort.unwrap_or_default() {
+            UserSort::UserId => format!("user_id {}", order),
+            sort => format!("{} {}, user_id {}", sort.column(), order, order),
+        }
+    }
+}
+
+const FILTER: &str = r#"
+    FROM users
+    WHERE (? IS NULL OR role = ?)
+      AND (? IS NULL OR staff_position = ?)
+      AND (? IS NULL OR nationality = ?)
+"#;
+
+macro_rules! bind_filter {
+    ($query:expr, $filter:expr) => {
+        $query
+            .bind($filter.role)
+            .bind($filter.role)
+            .bind($filter.staff_position)
+            .bind($filter.staff_position)
+            .bind(&$filter.nationality)
+            .bind(&$filter.nationality)
+    };
+}
+
+impl User {
+    pub async fn insert(pool: &DbPool, user: &NewUser) -> Result<i32, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO users (first_name, last_name, email, phone, password, role, staff_position)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&user.first_name)
+        .bind(&user.last_name)
+        .bind(&user.email)
+        .bind(&user.phone)
+        .bind(&user.password_hash)
+        .bind(user.role)
+        .bind(user.staff_position)
+        .execute(pool)
+        .await?;
+
+        Ok(db::last_insert_id(&result) as i32)
+    }
+
+    // Insert a passenger (role `user`) on the import's connection; `password_hash` is shared by
+    // the whole import and known to nobody, passengers set their own with a password reset
+    pub async fn insert_passenger(
+        conn: &mut DbConnection,
+        passenger: &NewPassenger,
+        password_hash: &str,
+    ) -> Result<i32, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO users (first_name, last_name, email, phone, password, passport_number,
+                               nationality, date_of_birth, role)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'user')
+            "#,
+        )
+        .bind(&passenger.first_name)
+        .bind(&passenger.last_name)
+        .bind(&passenger.email)
+        .bind(&passenger.phone)
+        .bind(password_hash)
+        .bind(&passenger.passport_number)
+        .bind(&passenger.nationality)
+        .bind(passenger.date_of_birth)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(db::last_insert_id(&result) as i32)
+    }
+
+    // An existing user with the passenger's email, phone or passport number
+    pub async fn find_conflicting(
+        conn: &mut DbConnection,
+        passenger: &NewPassenger,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM users
+            WHERE email = ? OR phone = ? OR passport_number = ?
+            LIMIT 1
+            "#,
+        )
+        .bind(&passenger.email)
+        .bind(&passenger.phone)
+        .bind(&passenger.passport_number)
+        .fetch_optional(&mut *conn)
+        .await
+    }
+
+    pub async fn find_by_id(pool: &DbPool, user_id: i32) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM users WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_all(
+        pool: &DbPool,
+        filter: &UserFilter,
+        page: i32,
+        limit: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let offset = (page - 1) * limit;
+        let sql = format!(
+            "SELECT * {} ORDER BY {} LIMIT ? OFFSET ?",
+            FILTER,
+            filter.order_by()
+        );
+
+        bind_filter!(sqlx::query_as::<_, Self>(&sql), filter)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+    }
+
+    // Keyset page in name order, starting after the (last_name, user_id) of the previous
+    // page's last user
+    pub async fn find_after(
+        pool: &DbPool,
+        filter: &UserFilter,
+        after: Option<(String, i32)>,
+        limit: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let sql = format!(
+            r#"
+            SELECT * {}
+              AND (? IS NULL OR last_name {op} ? OR (last_name = ? AND user_id {op} ?))
+            ORDER BY {}
+            LIMIT ?
+            "#,
+            FILTER,
+            filter.order_by(),
+            op = filter.order.unwrap_or_default().after_sql()
+        );
+        let (last_name, user_id) = after.unzip();
+
+        bind_filter!(sqlx::query_as::<_, Self>(&sql), filter)
+            .bind(&last_name)
+            .bind(&last_name)
+            .bind(&last_name)
+            .bind(user_id)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+    }
+
+    pub async fn count(pool: &DbPool, filter: &UserFilter) -> Result<i64, sqlx::Error> {
+        let sql = format!("SELECT COUNT(*) {}", FILTER);
+
+        bind_filter!(sqlx::query_scalar(&sql), filter)
+            .fetch_one(pool)
+            .await
+    }
+
+    // Every user matching the filter, in listing order, for a download
+    pub async fn export(pool: &DbPool, filter: &UserFilter, rows: RowSender<Self>) {
+        let sql = format!("SELECT * {} ORDER BY {}", FILTER, filter.order_by());
+        export::forward(
+            bind_filter!(sqlx::query_as::<_, Self>(&sql), filter).fetch(pool),
+            &rows,
+        )
+        .await;
+    }
+
+    pub async fn find_by_email(pool: &DbPool, email: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM users WHERE email = ?")
+            .bind(email)
+            .fetch_optional(pool)
+            .await
+    }
+
+    pub async fn find_by_phone(pool: &DbPool, phone: &str) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM users WHERE phone = ?")
+            .bind(phone)
+            .fetch_optional(pool)
+            .await
+    }
+
+    // Store a new bcrypt hash; tokens issued before now stop working and all sessions are revoked
+    pub async fn update_password(
+        pool: &DbPool,
+        user_id: i32,
+        password_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE users SET password = ?, password_changed_at = UTC_TIMESTAMP() WHERE user_id = ?",
+        )
+        .bind(password_hash)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        Session::revoke_all_for_user(&mut tx, user_id).await?;
+
+        tx.commit().await?;
+        RevokedToken::forget_user(user_id);
+        Ok(())
+    }
+
+    // Point the profile photo at another stored file, or remove it; returns the previous one
+    pub async fn set_avatar(
+        pool: &DbPool,
+        user_id: i32,
+        avatar_key: Option<&str>,
+    ) -> Result<Option<String>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let previous: Option<(Option<String>,)> =
+            sqlx::query_as("SELECT avatar_key FROM users WHERE user_id = ? FOR UPDATE")
+                .bind(user_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        sqlx::query("UPDATE users SET avatar_key = ? WHERE user_id = ?")
+            .bind(avatar_key)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(previous.and_then(|(key,)| key))
+    }
+
+    // Right to erasure. The row stays, anonymized, because tickets, miles and seat blocks still
+    // reference it: every personal field is cleared or replaced, the password becomes
+    // `password_hash` (one nobody knows), free text on the user's tickets is dropped, sessions are
+    // revoked and stripped of their client details, and credentials, stored responses, exports,
+    // companion consents and document records are deleted (the caller removes the document and
+    // photo files)
+    pub async fn erase(
+        pool: &DbPool,
+        user_id: i32,
+        password_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            "DELETE FROM otp_codes WHERE phone = (SELECT phone FROM users WHERE user_id = ?)",
+        )
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET first_name = 'Erased',
+                last_name = 'User',
+                email = CONCAT('erased-', user_id, '@erased.invalid'),
+                phone = NULL,
+                password = ?,
+                passport_number = NULL,
+                nationality = NULL,
+                date_of_birth = NULL,
+                gender = NULL,
+                role = 'user',
+                staff_position = NULL,
+                avatar_key = NULL,
+                password_changed_at = UTC_TIMESTAMP(),
+                erased_at = UTC_TIMESTAMP()
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(password_hash)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE tickets SET special_requests = NULL WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        Session::revoke_all_for_user(&mut tx, user_id).await?;
+        sqlx::query("UPDATE sessions SET user_agent = NULL, ip_address = NULL WHERE user_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for table in [
+            "refresh_tokens",
+            "password_reset_tokens",
+            "user_totp",
+            "totp_backup_codes",
+            "idempotency_keys",
+            "data_exports",
+            "user_documents",
+            "travel_companions",
+        ] {
+            sqlx::query(&format!("DELETE FROM {} WHERE user_id = ?", table))
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        sqlx::query("DELETE FROM travel_companions WHERE companion_id = ?")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        RevokedToken::forget_user(user_id);
+        UserPermissions::forget(user_id);
+        Ok(())
+    }
+
+    pub async fn update_profile(
+        pool: &DbPool,
+        user_id: i32,
+        profile: &UpdateProfile,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE users
+            SET first_name = COALESCE(?, first_name),
+                last_name = COALESCE(?, last_name),
+                phone = COALESCE(?, phone),
+                passport_number = COALESCE(?, passport_number),
+                nationality = COALESCE(?, nationality),
+                date_of_birth = COALESCE(?, date_of_birth),
+                gender = COALESCE(?, gender),
+                language = COALESCE(?, language)
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(&profile.first_name)
+        .bind(&profile.last_name)
+        .bind(&profile.phone)
+        .bind(&profile.passport_number)
+        .bind(&profile.nationality)
+        .bind(profile.date_of_birth)
+        .bind(profile.gender)
+        .bind(profile.language)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+        Self::find_by_id(pool, user_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_db;
+
+    fn new_user(email: &str) -> NewUser {
+        NewUser {
+            first_name: "Ada".to_string(),
+            last_name: "Lovelace".to_string(),
+            email: email.to_string(),
+            phone: Some("+380501234567".to_string()),
+            password_hash: "$2b$12$hash".to_string(),
+            role: UserRole::Worker,
+            staff_position: Some(StaffPosition::GateAgent),
+        }
+    }
+
+    fn empty_profile() -> UpdateProfile {
+        UpdateProfile {
+            first_name: None,
+            last_name: None,
+            phone: None,
+            passport_number: None,
+            nationality: None,
+            date_of_birth: None,
+            gender: None,
+            language: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn permissions_are_read_from_the_stored_user() {
+        let pool = test_db::pool().await;
+        let id = User::insert(&pool, &new_user("ada@example.com"))
+            .await
+            .unwrap();
+
+        let permissions = UserPermissions::find(&pool, id).await.unwrap().unwrap();
+        assert_eq!(permissions.role, UserRole::Worker);
+        assert_eq!(permissions.staff_position, Some(StaffPosition::GateAgent));
+        assert!(UserPermissions::find(&pool, id + 1)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn insert_returns_id_of_stored_user() {
+        let pool = test_db::pool().await;
+
+        let first = User::insert(&pool, &new_user("ada@example.com"))
+            .await
+            .unwrap();
+        let mut other = new_user("grace@example.com");
+        other.phone = None;
+        other.role = UserRole::User;
+        other.staff_position = None;
+        let second = User::insert(&pool, &other).await.unwrap();
+        assert_ne!(first, second);
+
+        let user = User::find_by_id(&pool, first).await.unwrap().unwrap();
+        assert_eq!(user.email, "ada@example.com");
+        assert_eq!(user.password, "$2b$12$hash");
+        assert_eq!(user.role, UserRole::Worker);
+        assert_eq!(user.staff_position, Some(StaffPosition::GateAgent));
+        assert_eq!(user.passport_number, None);
+
+        let user = User::find_by_email(&pool, "grace@example.com")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.user_id, second);
+        assert_eq!(user.staff_position, None);
+
+        let user = User::find_by_phone(&pool, "+380501234567")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.user_id, first);
+        assert!(User::find_by_email(&pool, "nobody@example.com")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn insert_rejects_duplicate_email() {
+        let pool = test_db::pool().await;
+        User::insert(&pool, &new_user("ada@example.com"))
+            .await
+            .unwrap();
+
+        let mut duplicate = new_user("ada@example.com");
+        duplicate.phone = None;
+        let error = User::insert(&pool, &duplicate).await.unwrap_err();
+        assert!(matches!(error, sqlx::Error::Database(e) if e.is_unique_violation()));
+    }
+
+    #[tokio::test]
+    async fn update_profile_only_changes_given_fields() {
+        let pool = test_db::pool().await;
+        let user_id = User::insert(&pool, &new_user("ada@example.com"))
+            .await
+            .unwrap();
+
+        let profile = UpdateProfile {
+            last_name: Some("King".to_string()),
+            passport_number: Some("FA123456".to_string()),
+            date_of_birth: NaiveDate::from_ymd_opt(1815, 12, 10),
+            gender: Some(Gender::Female),
+            language: Some(Locale::Uk),
+            ..empty_profile()
+        };
+        let user = User::update_profile(&pool, user_id, &profile)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(user.first_name, "Ada");
+        assert_eq!(user.last_name, "King");
+        assert_eq!(user.phone.as_deref(), Some("+380501234567"));
+        assert_eq!(user.passport_number.as_deref(), Some("FA123456"));
+        assert_eq!(user.nationality, None);
+        assert_eq!(user.date_of_birth, NaiveDate::from_ymd_opt(1815, 12, 10));
+        assert_eq!(user.gender, Some(Gender::Female));
+        assert_eq!(user.language, Locale::Uk);
+
+        // An empty update leaves the row as it was
+        let unchanged = User::update_profile(&pool, user_id, &empty_profile())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(unchanged.last_name, "King");
+        assert_eq!(unchanged.passport_number.as_deref(), Some("FA123456"));
+    }
+
+    #[tokio::test]
+    async fn update_profile_of_missing_user_is_none() {
+        let pool = test_db::pool().await;
+        let profile = UpdateProfile {
+            first_name: Some("Nobody".to_string()),
+            ..empty_profile()
+        };
+        assert!(User::update_profile(&pool, 42, &profile)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn find_all_filters_by_role_and_sorts() {
+        let pool = test_db::pool().await;
+        for (email, role) in [
+            ("b@example.com", UserRole::Worker),
+            ("a@example.com", UserRole::Worker),
+            ("c@example.com", UserRole::User),
+        ] {
+            let mut user = new_user(email);
+            user.phone = None;
+            user.role = role;
+            User::insert(&pool, &user).await.unwrap();
+        }
+
+        let filter = UserFilter {
+            role: Some(UserRole::Worker),
+            sort: Some(UserSort::Email),
+            order: Some(SortOrder::Desc),
+            ..Default::default()
+        };
+        let found = User::find_all(&pool, &filter, 1, 10).await.unwrap();
+        let emails: Vec<_> = found.iter().map(|user| user.email.as_str()).collect();
+        assert_eq!(emails, ["b@example.com", "a@example.com"]);
+        assert_eq!(User::count(&pool, &filter).await.unwrap(), 2);
+    }
+
+    #[test]
+    fn changed_fields_lists_set_fields_in_column_order() {
+        assert!(empty_profile().changed_fields().is_empty());
+
+        let profile = UpdateProfile {
+            nationality: Some("UA".to_string()),
+            first_name: Some("Ada".to_string()),
+            ..empty_profile()
+        };
+        assert_eq!(profile.changed_fields(), ["first_name", "nationality"]);
+    }
+}
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::db::{self, DbPool};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentType {
+    Passport,
+    NationalId,
+    Visa,
+    Other,
+}
+
+impl DocumentType {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "passport" => Some(DocumentType::Passport),
+            "national_id" => Some(DocumentType::NationalId),
+            "visa" => Some(DocumentType::Visa),
+            "other" => Some(DocumentType::Other),
+            _ => None,
+        }
+    }
+}
+
+// Metadata of an uploaded identity document; the content is read from object storage
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserDocument {
+    pub document_id: i32,
+    pub user_id: i32,
+    pub document_type: DocumentType,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i32,
+    pub sha256: String,
+    #[serde(skip_serializing)]
+    pub storage_key: String,
+    pub uploaded_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+// A stored file to record
+#[derive(Debug)]
+pub struct NewUserDocument {
+    pub user_id: i32,
+    pub document_type: DocumentType,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i32,
+    pub sha256: String,
+    pub storage_key: String,
+    pub uploaded_by: i32,
+}
+
+impl UserDocument {
+    pub async fn insert(pool: &DbPool, document: &NewUserDocument) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO user_documents
+                (user_id, document_type, file_name, content_type, size_bytes, sha256, storage_key,
+                 uploaded_by, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(document.user_id)
+        .bind(document.document_type)
+        .bind(&document.file_name)
+        .bind(&document.content_type)
+        .bind(document.size_bytes)
+        .bind(&document.sha256)
+        .bind(&document.storage_key)
+        .bind(document.uploaded_by)
+        .execute(pool)
+        .await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM user_documents WHERE document_id = ?")
+            .bind(db::last_insert_id(&result) as i32)
+            .fetch_one(pool)
+            .await
+    }
+
+    // Newest first
+    pub async fn find_by_user(pool: &DbPool, user_id: i32) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM user_documents WHERE user_id = ? ORDER BY created_at DESC, document_id DESC",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn find(
+        pool: &DbPool,
+        user_id: i32,
+        document_id: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM user_documents WHERE document_id = ? AND user_id = ?",
+        )
+        .bind(document_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await
+    }
+}
@@ -0,0 +1,56 @@
+use std::{sync::OnceLock, time::Duration};
+
+use sqlx::FromRow;
+
+use super::{StaffPosition, UserRole};
+use crate::db::DbPool;
+use crate::ttl_cache::TtlCache;
+
+// Users whose permissions are remembered at most
+const CACHE_CAPACITY: usize = 10_000;
+
+// Keyed by user id; unset (every lookup reads the database) until `cache` is called
+static PERMISSIONS: OnceLock<TtlCache<i32, UserPermissions>> = OnceLock::new();
+
+// What a user may do as stored now, which the role in a token issued earlier may no longer match
+#[derive(Debug, Clone, Copy, FromRow)]
+pub struct UserPermissions {
+    pub role: UserRole,
+    pub staff_position: Option<StaffPosition>,
+}
+
+impl UserPermissions {
+    // Remember lookups for `ttl` so the auth extractor does not query the database on every
+    // request. Changes made by this instance apply at once; ones made by another instance take
+    // up to `ttl` to be seen here
+    pub fn cache(ttl: Duration) {
+        if !ttl.is_zero() {
+            let _ = PERMISSIONS.set(TtlCache::new(ttl, CACHE_CAPACITY));
+        }
+    }
+
+    // Forget the user's remembered permissions after changing their role or position
+    pub fn forget(user_id: i32) {
+        if let Some(permissions) = PERMISSIONS.get() {
+            permissions.invalidate(|id, _| *id == user_id);
+        }
+    }
+
+    // None once the user no longer exists
+    pub async fn find(pool: &DbPool, user_id: i32) -> Result<Option<Self>, sqlx::Error> {
+        let cache = PERMISSIONS.get();
+        if let Some(permissions) = cache.and_then(|cache| cache.get(&user_id)) {
+            return Ok(Some(permissions));
+        }
+
+        let permissions =
+            sqlx::query_as::<_, Self>("SELECT role, staff_position FROM users WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(pool)
+                .await?;
+        if let (Some(cache), Some(permissions)) = (cache, permissions) {
+            cache.insert(user_id, permissions);
+        }
+        Ok(permissions)
+    }
+}
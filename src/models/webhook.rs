@@ -0,0 +1,290 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::{types::Json, FromRow};
+use uuid::Uuid;
+
+use crate::db::{self, DbPool};
+
+// Deliveries are given up after this many attempts
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 6;
+
+// Wait before the first retry, doubled after every further failure
+const RETRY_BASE_SECS: i64 = 30;
+
+// Events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    #[serde(rename = "ticket.created")]
+    TicketCreated,
+    #[serde(rename = "flight.cancelled")]
+    FlightCancelled,
+    #[serde(rename = "payment.succeeded")]
+    PaymentSucceeded,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::TicketCreated => "ticket.created",
+            WebhookEvent::FlightCancelled => "flight.cancelled",
+            WebhookEvent::PaymentSucceeded => "payment.succeeded",
+        }
+    }
+}
+
+// Endpoint registered by an admin; payloads are signed with its secret
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct Webhook {
+    pub webhook_id: i32,
+    pub url: String,
+    pub events: Json<Vec<WebhookEvent>>,
+    pub created_by: i32,
+    pub created_at: DateTime<Utc>,
+    pub disabled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "enum", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Succeeded,
+    Failed,
+}
+
+// One event sent (or to be sent) to one webhook
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct WebhookDelivery {
+    pub delivery_id: i64,
+    pub webhook_id: i32,
+    pub event_id: String,
+    pub event: String,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub next_attempt_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+// A delivery that is due, with what is needed to send it
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingDelivery {
+    pub delivery_id: i64,
+    pub event_id: String,
+    pub event: String,
+    pub payload: String,
+    pub attempts: i32,
+    pub url: String,
+    pub secret: String,
+}
+
+impl Webhook {
+    pub async fn find_all(pool: &DbPool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT webhook_id, url, events, created_by, created_at, disabled_at
+            FROM webhooks
+            ORDER BY created_at DESC
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn exists(pool: &DbPool, webhook_id: i32) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM webhooks WHERE webhook_id = ?)")
+            .bind(webhook_id)
+            .fetch_one(pool)
+            .await
+    }
+
+    // Register an endpoint and return it together with its signing secret
+    pub async fn create(
+        pool: &DbPool,
+        url: &str,
+        events: &[WebhookEvent],
+        created_by: i32,
+    ) -> Result<(Self, String), sqlx::Error> {
+        let secret = format!("whsec_{}", Uuid::new_v4().simple());
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO webhooks (url, secret, events, created_by, created_at)
+            VALUES (?, ?, ?, ?, UTC_TIMESTAMP())
+            "#,
+        )
+        .bind(url)
+        .bind(&secret)
+        .bind(Json(events))
+        .bind(created_by)
+        .execute(pool)
+        .await?;
+
+        let webhook = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT webhook_id, url, events, created_by, created_at, disabled_at
+            FROM webhooks
+            WHERE webhook_id = ?
+            "#,
+        )
+        .bind(db::last_insert_id(&result) as i32)
+        .fetch_one(pool)
+        .await?;
+
+        Ok((webhook, secret))
+    }
+
+    // Stop sending to the endpoint; its delivery log is kept
+    pub async fn disable(pool: &DbPool, webhook_id: i32) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let result = sqlx::query(
+            "UPDATE webhooks SET disabled_at = UTC_TIMESTAMP() WHERE webhook_id = ? AND disabled_at IS NULL",
+        )
+        .bind(webhook_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "UPDATE webhook_deliveries SET status = 'failed', last_error = 'webhook disabled' WHERE webhook_id = ? AND status = 'pending'",
+        )
+        .bind(webhook_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl WebhookDelivery {
+    // Queue the event for every active webhook subscribed to it; returns the number queued
+    pub async fn enqueue(
+        pool: &DbPool,
+        event: WebhookEvent,
+        data: Value,
+    ) -> Result<u64, sqlx::Error> {
+        let event_id = Uuid::new_v4().to_string();
+        let payload = json!({
+            "event_id": event_id,
+            "event": event.as_str(),
+            "occurred_at": Utc::now(),
+            "data": data,
+        });
+
+        let result = sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries
+                (webhook_id, event_id, event, payload, status, attempts, next_attempt_at, created_at)
+            SELECT webhook_id, ?, ?, ?, 'pending', 0, UTC_TIMESTAMP(), UTC_TIMESTAMP()
+            FROM webhooks
+            WHERE disabled_at IS NULL AND JSON_CONTAINS(events, JSON_QUOTE(?))
+            "#,
+        )
+        .bind(&event_id)
+        .bind(event.as_str())
+        .bind(payload.to_string())
+        .bind(event.as_str())
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn find_by_webhook(
+        pool: &DbPool,
+        webhook_id: i32,
+        limit: i32,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT delivery_id, webhook_id, event_id, event, status, attempts, response_status,
+                   last_error, next_attempt_at, created_at, delivered_at
+            FROM webhook_deliveries
+            WHERE webhook_id = ?
+            ORDER BY delivery_id DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(webhook_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
+    // Pending deliveries whose next attempt is due, oldest first
+    pub async fn due(pool: &DbPool, limit: i32) -> Result<Vec<PendingDelivery>, sqlx::Error> {
+        sqlx::query_as::<_, PendingDelivery>(
+            r#"
+            SELECT d.delivery_id, d.event_id, d.event, d.payload, d.attempts, w.url, w.secret
+            FROM webhook_deliveries d
+            JOIN webhooks w ON w.webhook_id = d.webhook_id
+            WHERE d.status = 'pending' AND d.next_attempt_at <= UTC_TIMESTAMP()
+                  AND w.disabled_at IS NULL
+            ORDER BY d.next_attempt_at
+            LIMIT ?
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn mark_succeeded(
+        pool: &DbPool,
+        delivery_id: i64,
+        response_status: u16,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = 'succeeded', attempts = attempts + 1, response_status = ?,
+                last_error = NULL, delivered_at = UTC_TIMESTAMP()
+            WHERE delivery_id = ?
+            "#,
+        )
+        .bind(response_status)
+        .bind(delivery_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    // Record a failed attempt and schedule the retry with exponential backoff,
+    // or give up after the last attempt
+    pub async fn mark_attempt_failed(
+        pool: &DbPool,
+        delivery: &PendingDelivery,
+        response_status: Option<u16>,
+        error: &str,
+    ) -> Result<(), sqlx::Error> {
+        let attempts = delivery.attempts + 1;
+        let status = if attempts >= MAX_DELIVERY_ATTEMPTS {
+            DeliveryStatus::Failed
+        } else {
+            DeliveryStatus::Pending
+        };
+        let retry_in = RETRY_BASE_SECS << (attempts - 1).min(10);
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = ?, attempts = ?, response_status = ?, last_error = ?,
+                next_attempt_at = UTC_TIMESTAMP() + INTERVAL ? SECOND
+            WHERE delivery_id = ?
+            "#,
+        )
+        .bind(status)
+        .bind(attempts)
+        .bind(response_status)
+        .bind(error)
+        .bind(retry_in)
+        .bind(delivery.delivery_id)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+}
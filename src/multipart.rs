@@ -0,0 +1,174 @@
+// multipart/form-data bodies (RFC 7578), for file uploads. The body is buffered first, so the
+// route's body limit bounds the memory a request can use
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+};
+
+use crate::error::{AppError, ErrorCode};
+
+#[derive(Debug)]
+pub struct Part {
+    pub name: String,
+    pub filename: Option<String>,
+    pub content_type: Option<String>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum MultipartError {
+    #[error("Content-Type is not multipart/form-data with a boundary")]
+    NotMultipart,
+    #[error("Malformed multipart body")]
+    Malformed,
+}
+
+// `name="value"` parameters of a header value such as Content-Disposition
+fn parameter<'a>(value: &'a str, name: &str) -> Option<&'a str> {
+    value.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.trim().split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+pub fn is_multipart(headers: &HeaderMap) -> bool {
+    boundary(headers).is_some()
+}
+
+fn boundary(headers: &HeaderMap) -> Option<&str> {
+    let content_type = headers.get(header::CONTENT_TYPE)?.to_str().ok()?;
+    let mime = content_type.split(';').next()?.trim();
+    if !mime.eq_ignore_ascii_case("multipart/form-data") {
+        return None;
+    }
+    parameter(content_type, "boundary").filter(|boundary| !boundary.is_empty())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn parse_part(raw: &[u8]) -> Result<Part, MultipartError> {
+    // A part without headers starts with the blank line right away
+    let (head, data) = if let Some(data) = raw.strip_prefix(b"\r\n") {
+        (&b""[..], data)
+    } else {
+        let end = find(raw, b"\r\n\r\n").ok_or(MultipartError::Malformed)?;
+        (&raw[..end], &raw[end + 4..])
+    };
+    let head = std::str::from_utf8(head).map_err(|_| MultipartError::Malformed)?;
+
+    let mut disposition = None;
+    let mut content_type = None;
+    for line in head.split("\r\n") {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        if key.trim().eq_ignore_ascii_case("content-disposition") {
+            disposition = Some(value.trim());
+        } else if key.trim().eq_ignore_ascii_case("content-type") {
+            content_type = Some(value.trim().to_string());
+        }
+    }
+
+    let disposition = disposition.ok_or(MultipartError::Malformed)?;
+    Ok(Part {
+        name: parameter(disposition, "name")
+            .ok_or(MultipartError::Malformed)?
+            .to_string(),
+        filename: parameter(disposition, "filename").map(str::to_string),
+        content_type,
+        data: data.to_vec(),
+    })
+}
+
+// Every part of the body, in order
+pub fn parse(headers: &HeaderMap, body: &[u8]) -> Result<Vec<Part>, MultipartError> {
+    let boundary = boundary(headers).ok_or(MultipartError::NotMultipart)?;
+    let delimiter = format!("\r\n--{}", boundary);
+    let delimiter = delimiter.as_bytes();
+
+    // With a leading CRLF the first delimiter looks like all the others
+    let body = [&b"\r\n"[..], body].concat();
+    let start = find(&body, delimiter).ok_or(MultipartError::Malformed)?;
+    let mut rest = &body[start + delimiter.len()..];
+
+    let mut parts = Vec::new();
+    loop {
+        if rest.starts_with(b"--") {
+            return Ok(parts);
+        }
+        rest = rest
+            .strip_prefix(b"\r\n")
+            .ok_or(MultipartError::Malformed)?;
+        let end = find(rest, delimiter).ok_or(MultipartError::Malformed)?;
+        parts.push(parse_part(&rest[..end])?);
+        rest = &rest[end + delimiter.len()..];
+    }
+}
+
+// Every part of a multipart/form-data request body, as an extractor for upload handlers
+pub struct Form(pub Vec<Part>);
+
+impl<S: Send + Sync> FromRequest<S> for Form {
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let headers = req.headers().clone();
+        let body = Bytes::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+        parse(&headers, &body).map(Form).map_err(|e| {
+            AppError::ValidationError(ErrorCode::ValidationFailed, e.to_string()).into_response()
+        })
+    }
+}
+
+// The part named `name`, e.g. the `file` field of an upload form
+pub fn take(parts: Vec<Part>, name: &str) -> Option<Part> {
+    parts.into_iter().find(|part| part.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn splits_parts_with_their_disposition() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("multipart/form-data; boundary=\"XyZ\""),
+        );
+        let body =
+            "preamble\r\n--XyZ\r\nContent-Disposition: form-data; name=\"note\"\r\n\r\nhello\r\n\
+                    --XyZ\r\nContent-Disposition: form-data; name=\"file\"; filename=\"p.csv\"\r\n\
+                    Content-Type: text/csv\r\n\r\na,b\r\n1,2\r\n\r\n--XyZ--\r\n";
+
+        let parts = parse(&headers, body.as_bytes()).unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].name, "note");
+        assert_eq!(parts[0].data, b"hello");
+        let file = take(parts, "file").unwrap();
+        assert_eq!(file.filename.as_deref(), Some("p.csv"));
+        assert_eq!(file.content_type.as_deref(), Some("text/csv"));
+        assert_eq!(file.data, b"a,b\r\n1,2\r\n");
+    }
+
+    #[test]
+    fn requires_a_multipart_content_type() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/csv"));
+        assert_eq!(
+            parse(&headers, b"").unwrap_err(),
+            MultipartError::NotMultipart
+        );
+    }
+}
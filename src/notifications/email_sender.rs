@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+
+use super::{Notification, NotificationError, NotificationSender};
+use crate::db::DbPool;
+use crate::jobs::queue;
+use crate::jobs::send_email::SendEmail;
+use crate::mailer::Email;
+
+// Delivers notifications that have an email address as `SendEmail` jobs, so they survive
+// restarts and are retried while the mail server is unreachable
+pub struct EmailSender {
+    pool: DbPool,
+}
+
+impl EmailSender {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl NotificationSender for EmailSender {
+    fn channel(&self) -> &'static str {
+        "email"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError> {
+        let to = notification
+            .email
+            .clone()
+            .ok_or(NotificationError::MissingAddress(self.channel()))?;
+        let email = Email {
+            to,
+            subject: notification.subject.clone(),
+            text: notification.body.clone(),
+            html: notification.html.clone(),
+        };
+        queue::enqueue(&self.pool, &SendEmail { email })
+            .await
+            .map(|_| ())
+            .map_err(|e| NotificationError::Delivery(e.to_string()))
+    }
+}
@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use super::{Notification, NotificationError, NotificationSender};
+
+// Default sender used until a real email/SMS backend is configured: writes notifications to the log
+pub struct LogSender;
+
+#[async_trait]
+impl NotificationSender for LogSender {
+    fn channel(&self) -> &'static str {
+        "log"
+    }
+
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError> {
+        let address = notification
+            .email
+            .as_deref()
+            .or(notification.phone.as_deref())
+            .ok_or(NotificationError::MissingAddress(self.channel()))?;
+
+        info!(
+            "Notification for user {} <{}>: {} - {}",
+            notification.user_id, address, notification.subject, notification.body
+        );
+        Ok(())
+    }
+}
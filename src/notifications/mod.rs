@@ -0,0 +1,224 @@
+pub mod email_sender;
+pub mod log_sender;
+pub mod sms;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use thiserror::Error;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tracing::{error, info};
+
+use crate::i18n::Locale;
+use crate::mailer::EmailTemplate;
+use crate::models::{AirportTimezone, Flight, FlightStatus, Route, Ticket, TicketHolder, User};
+
+pub use email_sender::EmailSender;
+pub use log_sender::LogSender;
+pub use sms::{LogSmsProvider, SmsProvider};
+
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    #[error("recipient has no address for channel {0}")]
+    MissingAddress(&'static str),
+    #[error("delivery failed: {0}")]
+    Delivery(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub user_id: i32,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub subject: String,
+    // Plain text, for every channel
+    pub body: String,
+    // Rich version of `body` for email
+    pub html: Option<String>,
+}
+
+// A delivery backend (email, SMS, ...). New backends only need to implement this trait
+// and be registered in `Notifier::start`.
+#[async_trait]
+pub trait NotificationSender: Send + Sync {
+    fn channel(&self) -> &'static str;
+
+    async fn send(&self, notification: &Notification) -> Result<(), NotificationError>;
+}
+
+// Handle to the notification queue. Cloning is cheap, every clone feeds the same worker.
+#[derive(Clone)]
+pub struct Notifier {
+    queue: mpsc::UnboundedSender<Notification>,
+}
+
+impl Notifier {
+    // Spawn the worker that drains the queue and hands every notification to all senders.
+    // The worker exits once every handle is dropped and the queue is empty
+    pub fn start(senders: Vec<Arc<dyn NotificationSender>>) -> (Self, JoinHandle<()>) {
+        let (queue, mut rx) = mpsc::unbounded_channel::<Notification>();
+
+        let worker = tokio::spawn(async move {
+            while let Some(notification) = rx.recv().await {
+                for sender in &senders {
+                    if let Err(e) = sender.send(&notification).await {
+                        error!(
+                            "Failed to send {} notification to user {}: {}",
+                            sender.channel(),
+                            notification.user_id,
+                            e
+                        );
+                    }
+                }
+            }
+        });
+
+        (Self { queue }, worker)
+    }
+
+    pub fn enqueue(&self, notification: Notification) {
+        if self.queue.send(notification).is_err() {
+            error!("Notification queue is closed, dropping notification");
+        }
+    }
+
+    // Render an email template and queue it; the templates are built in, so a failure here is a bug
+    // in a template or its context and is only logged
+    fn enqueue_template(
+        &self,
+        user_id: i32,
+        email: Option<String>,
+        phone: Option<String>,
+        locale: Locale,
+        template: EmailTemplate,
+        context: serde_json::Value,
+    ) {
+        match template.render(locale, &context) {
+            Ok(rendered) => self.enqueue(Notification {
+                user_id,
+                email,
+                phone,
+                subject: rendered.subject,
+                body: rendered.text,
+                html: Some(rendered.html),
+            }),
+            Err(e) => error!(
+                "Failed to render {:?} email for user {}: {}",
+                template, user_id, e
+            ),
+        }
+    }
+
+    // Notify every ticket holder that their flight was delayed or cancelled
+    pub fn flight_disrupted(
+        &self,
+        flight: &Flight,
+        route: &Route,
+        status: FlightStatus,
+        holders: Vec<TicketHolder>,
+    ) {
+        info!(
+            "Enqueuing {} notifications for flight {}",
+            holders.len(),
+            flight.flight_id
+        );
+
+        for holder in holders {
+            let mut flight_context = flight_context(flight, route, holder.language);
+            flight_context["cancelled"] = json!(status == FlightStatus::Cancelled);
+            self.enqueue_template(
+                holder.user_id,
+                holder.email,
+                holder.phone,
+                holder.language,
+                EmailTemplate::FlightDisruption,
+                json!({
+                    "passenger": passenger(&holder.first_name, &holder.last_name),
+                    "ticket": { "number": holder.ticket_number },
+                    "flight": flight_context,
+                }),
+            );
+        }
+    }
+
+    // Email a password reset token; never sent by SMS since the token grants account access
+    pub fn password_reset(&self, user: &User, token: &str, expires_in_secs: u64) {
+        self.enqueue_template(
+            user.user_id,
+            Some(user.email.clone()),
+            None,
+            user.language,
+            EmailTemplate::PasswordReset,
+            json!({
+                "passenger": passenger(&user.first_name, &user.last_name),
+                "token": token,
+                "expires_in_minutes": expires_in_secs / 60,
+            }),
+        );
+    }
+
+    // Email the code that confirms a user owns their address
+    pub fn email_verification(&self, user: &User, code: &str, expires_in_secs: u64) {
+        self.enqueue_template(
+            user.user_id,
+            Some(user.email.clone()),
+            None,
+            user.language,
+            EmailTemplate::Verification,
+            json!({
+                "passenger": passenger(&user.first_name, &user.last_name),
+                "token": code,
+                "expires_in_minutes": expires_in_secs / 60,
+            }),
+        );
+    }
+
+    // Confirm a purchased ticket with its flight details
+    pub fn booking_confirmed(&self, user: &User, ticket: &Ticket, flight: &Flight, route: &Route) {
+        self.enqueue_template(
+            user.user_id,
+            Some(user.email.clone()),
+            user.phone.clone(),
+            user.language,
+            EmailTemplate::BookingConfirmation,
+            json!({
+                "passenger": passenger(&user.first_name, &user.last_name),
+                "ticket": {
+                    "number": ticket.ticket_number,
+                    "seat": ticket.seat_number,
+                    "fare_class": ticket.fare_class.as_str(),
+                },
+                "flight": flight_context(flight, route, user.language),
+            }),
+        );
+    }
+}
+
+fn passenger(first_name: &str, last_name: &str) -> serde_json::Value {
+    json!({ "first_name": first_name, "last_name": last_name })
+}
+
+// Times as passengers read them, on the clock of the airport they happen at, e.g.
+// `Mon 2 Mar 2026, 10:15 EET`; the numeric `02.03.2026, 10:15 EET` where English month names
+// would be out of place
+fn display_time(time: DateTime<Utc>, timezone: AirportTimezone, locale: Locale) -> String {
+    let format = match locale {
+        Locale::En => "%a %-d %b %Y, %H:%M %Z",
+        Locale::Uk => "%d.%m.%Y, %H:%M %Z",
+    };
+    time.with_timezone(&timezone.0).format(format).to_string()
+}
+
+fn flight_context(flight: &Flight, route: &Route, locale: Locale) -> serde_json::Value {
+    json!({
+        "number": flight.flight_number,
+        "origin": route.origin,
+        "destination": route.destination,
+        "departure_time": display_time(flight.departure_time, flight.origin_timezone, locale),
+        "arrival_time": display_time(flight.arrival_time, flight.destination_timezone, locale),
+        "gate": flight.gate,
+        "terminal": flight.terminal,
+    })
+}
@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+use tracing::info;
+
+use super::NotificationError;
+
+// Direct SMS delivery for messages the caller waits on, such as login codes.
+// Unlike `NotificationSender` it targets one channel and is not queued
+#[async_trait]
+pub trait SmsProvider: Send + Sync {
+    async fn send_sms(&self, phone: &str, message: &str) -> Result<(), NotificationError>;
+}
+
+// Default provider used until a real SMS gateway is configured: writes messages to the log
+pub struct LogSmsProvider;
+
+#[async_trait]
+impl SmsProvider for LogSmsProvider {
+    async fn send_sms(&self, phone: &str, message: &str) -> Result<(), NotificationError> {
+        info!("SMS to {}: {}", phone, message);
+        Ok(())
+    }
+}
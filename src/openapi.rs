@@ -0,0 +1,295 @@
+use serde_json::{json, Map, Value};
+
+// How an operation authenticates its caller
+#[derive(Clone, Copy)]
+enum Security {
+    Public,
+    Bearer,
+    // Staff bearer token or a partner `X-API-Key` with the matching scope
+    BearerOrApiKey,
+}
+
+struct Operation {
+    method: &'static str,
+    path: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    security: Security,
+    // Success status, e.g. 201 for creates
+    status: u16,
+}
+
+const fn op(
+    method: &'static str,
+    path: &'static str,
+    tag: &'static str,
+    summary: &'static str,
+    security: Security,
+) -> Operation {
+    Operation {
+        method,
+        path,
+        tag,
+        summary,
+        security,
+        status: 200,
+    }
+}
+
+const fn created(mut operation: Operation) -> Operation {
+    operation.status = 201;
+    operation
+}
+
+const fn status(mut operation: Operation, status: u16) -> Operation {
+    operation.status = status;
+    operation
+}
+
+use Security::{Bearer, BearerOrApiKey, Public};
+
+// Every route served by the API under its current version; keep in sync with routes.rs
+#[rustfmt::skip]
+const OPERATIONS: &[Operation] = &[
+    op("get", "/health", "meta", "Service health", Public),
+    op("get", "/health/live", "meta", "Liveness probe", Public),
+    op("get", "/health/ready", "meta", "Readiness probe: database, migrations, pool", Public),
+    op("get", "/api/v1/meta/error-codes", "meta", "List error codes", Public),
+    op("get", "/api/v1/currencies", "meta", "List currencies and their exchange rates", Public),
+    op("get", "/api/v1/baggage-allowances", "baggage", "List baggage allowance rules per fare class and route", Public),
+    op("get", "/api/v1/routes", "routes", "List routes", Public),
+    op("get", "/api/v1/routes/{id}", "routes", "Get a route (honours If-None-Match)", Public),
+    op("post", "/api/v1/auth/login", "auth", "Log in with email and password", Public),
+    op("post", "/api/v1/auth/refresh", "auth", "Rotate a refresh token", Public),
+    status(op("post", "/api/v1/auth/logout", "auth", "Log out of the current session", Bearer), 204),
+    op("get", "/api/v1/auth/sessions", "auth", "List active sessions", Bearer),
+    status(op("delete", "/api/v1/auth/sessions/{id}", "auth", "Revoke a session", Bearer), 204),
+    status(op("post", "/api/v1/auth/otp/request", "auth", "Send a login code by SMS", Public), 202),
+    op("post", "/api/v1/auth/otp/verify", "auth", "Log in with an SMS code", Public),
+    op("post", "/api/v1/auth/2fa/enroll", "auth", "Start TOTP enrollment", Bearer),
+    op("post", "/api/v1/auth/2fa/activate", "auth", "Activate TOTP with a first code", Bearer),
+    op("post", "/api/v1/auth/2fa/backup-codes", "auth", "Regenerate backup codes", Bearer),
+    status(op("post", "/api/v1/auth/forgot-password", "auth", "Email a password reset link", Public), 202),
+    status(op("post", "/api/v1/auth/reset-password", "auth", "Set a new password with a reset token", Public), 204),
+    status(op("put", "/api/v1/auth/password", "auth", "Change the password", Bearer), 204),
+    op("get", "/api/v1/flights", "flights", "List or export flights (format=csv|xlsx), or the cheapest fare of each day around a date (flex_days)", Public),
+    created(op("post", "/api/v1/flights", "flights", "Schedule a flight on an aircraft that is not grounded (dispatcher)", Bearer)),
+    op("get", "/api/v1/flights/{id}", "flights", "Get a flight (honours If-None-Match)", Public),
+    op("patch", "/api/v1/flights/{id}/status", "flights", "Change flight status (staff, honours If-Match)", Bearer),
+    op("put", "/api/v1/flights/{id}/codeshares", "flights", "Set the marketing flight numbers of a code-share (dispatcher)", Bearer),
+    status(op("get", "/api/v1/flights/{id}/ws", "flights", "Live flight updates over a WebSocket", Public), 101),
+    op("get", "/api/v1/flights/{id}/status-history", "flights", "Flight status history", Public),
+    op("get", "/api/v1/flights/{id}/timeline", "flights", "Flight event timeline", BearerOrApiKey),
+    op("get", "/api/v1/flights/{id}/manifest", "flights", "Passenger manifest with assistance requests", BearerOrApiKey),
+    op("get", "/api/v1/flights/{id}/seat-blocks", "seats", "List seat blocks", BearerOrApiKey),
+    created(op("post", "/api/v1/flights/{id}/seat-blocks", "seats", "Block a seat (staff)", Bearer)),
+    op("delete", "/api/v1/flights/{id}/seat-blocks/{block_id}", "seats", "Release a seat block (staff)", Bearer),
+    op("get", "/api/v1/flights/{id}/occupancy", "seats", "Seat occupancy", Public),
+    op("get", "/api/v1/flights/{id}/meals", "meals", "Meals catered on a flight", Public),
+    op("put", "/api/v1/flights/{id}/meals", "meals", "Set the meals catered on a flight (dispatcher)", Bearer),
+    op("get", "/api/v1/flights/{id}/ancillaries", "ancillaries", "Ancillary products sold on a flight and how many were bought", Public),
+    op("put", "/api/v1/flights/{id}/ancillaries", "ancillaries", "Set the ancillary products sold on a flight and their capacity (dispatcher)", Bearer),
+    op("get", "/api/v1/flights/{id}/catering", "meals", "Meal counts to load on a flight (staff)", Bearer),
+    op("get", "/api/v1/flights/{id}/pets", "pets", "Pet places left in the cabin and hold, and pet fees", Public),
+    op("get", "/api/v1/flights/{id}/cargo", "cargo", "Cargo booked on a flight against its aircraft's limits (staff)", Bearer),
+    op("get", "/api/v1/flights/{id}/seats", "seats", "Seat map with the state of every seat", Public),
+    op("get", "/api/v1/flights/{id}/fare-classes", "fares", "Fare class availability and adult, child and lap infant prices (currency=EUR converts them)", Public),
+    op("put", "/api/v1/flights/{id}/fare-classes", "fares", "Set fare class inventory (staff)", Bearer),
+    op("get", "/api/v1/flights/{id}/crew", "crew", "Assigned crew", BearerOrApiKey),
+    op("put", "/api/v1/flights/{id}/crew", "crew", "Assign crew (dispatcher)", Bearer),
+    op("put", "/api/v1/flights/{id}/crew/override", "crew", "Assign crew past the duty-time limits (dispatcher)", Bearer),
+    op("get", "/api/v1/aircraft-types/{model}/crew-requirements", "crew", "Minimum crew of an aircraft type", BearerOrApiKey),
+    op("put", "/api/v1/aircraft-types/{model}/crew-requirements", "crew", "Set minimum crew (admin)", Bearer),
+    op("get", "/api/v1/aircraft/{id}/maintenance", "maintenance", "Maintenance records of an aircraft (staff)", Bearer),
+    created(op("post", "/api/v1/aircraft/{id}/maintenance", "maintenance", "Ground an aircraft for maintenance (engineer)", Bearer)),
+    op("delete", "/api/v1/aircraft/{id}/maintenance/{maintenance_id}", "maintenance", "Delete a maintenance record (engineer)", Bearer),
+    op("get", "/api/v1/airports/{code}/gates", "gates", "Gates of an airport (staff)", Bearer),
+    op("put", "/api/v1/flights/{id}/gate", "gates", "Assign a gate of the origin airport (dispatcher or gate agent)", Bearer),
+    op("get", "/api/v1/users", "users", "List or export users (staff, format=csv|xlsx)", Bearer),
+    status(op("post", "/api/v1/users/me/erase", "users", "Erase the caller's account and personal data", Bearer), 204),
+    op("get", "/api/v1/users/me/export", "users", "Request a copy of the caller's data", Bearer),
+    op("get", "/api/v1/users/me/export/{token}", "users", "Download a finished data export", Bearer),
+    op("get", "/api/v1/users/{id}", "users", "Get a profile (owner or staff, honours If-None-Match)", Bearer),
+    op("put", "/api/v1/users/{id}", "users", "Update a profile, including the notification `language` (owner or staff, honours If-Match)", Bearer),
+    op("get", "/api/v1/users/{id}/tickets", "users", "List or export a user's tickets (owner or staff, format=csv|xlsx)", Bearer),
+    op("get", "/api/v1/users/{id}/companions", "users", "Account holders the user lets book in their name (owner or staff)", Bearer),
+    created(op("post", "/api/v1/users/{id}/companions", "users", "Let another account holder book tickets in the user's name (owner or staff)", Bearer)),
+    status(op("delete", "/api/v1/users/{id}/companions/{companion_id}", "users", "Withdraw a companion's consent (owner or staff)", Bearer), 204),
+    created(op("post", "/api/v1/users/{id}/documents", "users", "Upload an identity document (owner or staff, multipart)", Bearer)),
+    op("get", "/api/v1/users/{id}/documents", "users", "List a user's identity documents (staff)", Bearer),
+    op("get", "/api/v1/users/{id}/documents/{document_id}", "users", "Download an identity document (staff)", Bearer),
+    op("put", "/api/v1/users/{id}/avatar", "users", "Set a profile photo (owner or staff, multipart)", Bearer),
+    status(op("delete", "/api/v1/users/{id}/avatar", "users", "Remove a profile photo (owner or staff)", Bearer), 204),
+    op("get", "/api/v1/avatars/{file}", "users", "Fetch a profile photo by its signed link", Public),
+    op("get", "/api/v1/tickets/stream", "tickets", "Server-sent events for the caller's tickets", Bearer),
+    created(op("post", "/api/v1/tickets/group", "tickets", "Book several passengers on one flight, all or none, under a group reference (honours Idempotency-Key)", Bearer)),
+    op("get", "/api/v1/tickets/group/{reference}", "tickets", "A booking group and its tickets (booker, passengers or staff)", Bearer),
+    op("get", "/api/v1/tickets/{id}", "tickets", "Get a ticket (holder or staff)", Bearer),
+    op("get", "/api/v1/tickets/{id}/pet", "pets", "The pet travelling on a ticket (holder or staff)", Bearer),
+    created(op("post", "/api/v1/tickets/{id}/pet", "pets", "Book a pet in the cabin or hold (holder or staff)", Bearer)),
+    op("get", "/api/v1/tickets/{id}/infant", "tickets", "The infant travelling on a ticket holder's lap (holder or staff)", Bearer),
+    created(op("post", "/api/v1/tickets/{id}/infant", "tickets", "Book an infant under 2 on an adult holder's lap (holder or staff)", Bearer)),
+    op("put", "/api/v1/tickets/{id}/meal", "meals", "Choose the meal of a ticket before the cutoff (holder or staff)", Bearer),
+    op("get", "/api/v1/tickets/{id}/ancillaries", "ancillaries", "Ancillaries bought for a ticket and its total with them (holder or staff)", Bearer),
+    created(op("post", "/api/v1/tickets/{id}/ancillaries", "ancillaries", "Buy an ancillary product the flight sells (holder or staff, honours Idempotency-Key)", Bearer)),
+    op("post", "/api/v1/tickets/{id}/change-seat", "tickets", "Move a ticket to a free seat of its class, paying for exit rows (holder or staff)", Bearer),
+    op("get", "/api/v1/tickets/{id}/upgrade", "tickets", "Higher classes a ticket can be upgraded to now, with their price (holder or staff)", Bearer),
+    created(op("post", "/api/v1/tickets/{id}/upgrade", "tickets", "Buy an upgrade to a higher class and reissue the ticket (holder or staff, honours Idempotency-Key)", Bearer)),
+    op("post", "/api/v1/tickets/{id}/check-in", "tickets", "Check a ticket in once its assistance requests are acknowledged and ages match (holder or staff)", Bearer),
+    op("get", "/api/v1/tickets/{id}/assistance", "tickets", "Assistance requested on a ticket (holder or staff)", Bearer),
+    created(op("post", "/api/v1/tickets/{id}/assistance", "tickets", "Request wheelchair, unaccompanied minor or medical assistance (holder or staff)", Bearer)),
+    op("post", "/api/v1/tickets/{id}/assistance/{assistance_id}/acknowledge", "tickets", "Acknowledge an assistance request (check-in or gate agent)", Bearer),
+    op("get", "/api/v1/tickets/{id}/baggage", "baggage", "Track the checked bags of a ticket (holder or staff)", Bearer),
+    created(op("post", "/api/v1/tickets/{id}/baggage", "baggage", "Check a bag in, charge any excess and issue its tag (check-in agent)", Bearer)),
+    op("get", "/api/v1/tickets/{id}/baggage-allowance", "baggage", "Baggage allowance of a ticket and what it has used (holder or staff)", Bearer),
+    created(op("post", "/api/v1/tickets/{id}/baggage-allowance", "baggage", "Buy extra checked pieces before departure (holder or staff)", Bearer)),
+    created(op("post", "/api/v1/cargo/shipments", "cargo", "Accept a shipment and issue its air waybill (staff)", Bearer)),
+    op("get", "/api/v1/cargo/shipments/{awb_number}", "cargo", "Track a shipment by its air waybill number", Public),
+    op("put", "/api/v1/cargo/shipments/{awb_number}/flight", "cargo", "Book a shipment on a flight within the aircraft's cargo limits (dispatcher)", Bearer),
+    op("post", "/api/v1/cargo/shipments/{awb_number}/events", "cargo", "Record a shipment as loaded, in transit, arrived or delivered (ground staff)", Bearer),
+    op("post", "/api/v1/baggage/{tag_number}/scans", "baggage", "Scan a bag as loaded, unloaded or delivered (ground staff)", Bearer),
+    created(op("post", "/api/v1/tickets/{id}/status-token", "tickets", "Create a shareable status link", Bearer)),
+    op("get", "/api/v1/public/flights/{token}/status", "tickets", "Public flight status behind a share link", Public),
+    op("get", "/api/v1/content", "content", "List display content", Public),
+    op("get", "/api/v1/content/{key}", "content", "Get display content", Public),
+    op("get", "/api/v1/admin/content/{key}", "admin", "List translations of a content key", Bearer),
+    op("put", "/api/v1/admin/content/{key}/{locale}", "admin", "Create or update a translation", Bearer),
+    op("delete", "/api/v1/admin/content/{key}/{locale}", "admin", "Delete a translation", Bearer),
+    op("get", "/api/v1/admin/api-keys", "admin", "List partner API keys", Bearer),
+    created(op("post", "/api/v1/admin/api-keys", "admin", "Issue a partner API key", Bearer)),
+    op("delete", "/api/v1/admin/api-keys/{id}", "admin", "Revoke a partner API key", Bearer),
+    op("get", "/api/v1/admin/promo-codes", "admin", "List promo codes", Bearer),
+    created(op("post", "/api/v1/admin/promo-codes", "admin", "Create a promo code", Bearer)),
+    op("delete", "/api/v1/admin/promo-codes/{id}", "admin", "Delete a promo code", Bearer),
+    op("get", "/api/v1/admin/ancillaries", "admin", "List the ancillary catalog", Bearer),
+    created(op("post", "/api/v1/admin/ancillaries", "admin", "Add an ancillary product such as priority boarding or lounge access", Bearer)),
+    op("put", "/api/v1/admin/ancillaries/{id}", "admin", "Replace or deactivate an ancillary product", Bearer),
+    op("get", "/api/v1/admin/cabin-layouts", "admin", "List cabin layouts", Bearer),
+    created(op("post", "/api/v1/admin/cabin-layouts", "admin", "Create a cabin layout", Bearer)),
+    op("put", "/api/v1/admin/aircraft/{id}/cabin-layout", "admin", "Set the cabin layout new flights of an aircraft are seated by", Bearer),
+    op("put", "/api/v1/admin/aircraft/{id}/pet-limits", "admin", "Set the pets an aircraft carries in the cabin and hold", Bearer),
+    op("put", "/api/v1/admin/aircraft/{id}/cargo-limits", "admin", "Set the cargo payload and volume of an aircraft", Bearer),
+    created(op("post", "/api/v1/admin/airports/{code}/gates", "admin", "Add a gate to an airport", Bearer)),
+    op("put", "/api/v1/admin/baggage-allowances", "admin", "Set the baggage allowance of a fare class on a route or every route", Bearer),
+    op("get", "/api/v1/reports/load-factor", "reports", "Seats sold against capacity per flight in a date range, by route (admin, format=csv|xlsx)", Bearer),
+    op("get", "/api/v1/reports/revenue", "reports", "Ticket and ancillary revenue in a date range by route or month (admin, group_by=route|month, currency, format=csv|xlsx)", Bearer),
+    op("get", "/api/v1/reports/on-time", "reports", "On-time departures and arrivals and average delay by route, aircraft or month (admin, group_by=route|aircraft|month, format=csv|xlsx)", Bearer),
+    op("get", "/api/v1/reports/demand", "reports", "Most booked routes, busiest days of the week and booking lead times in a date range (admin, limit)", Bearer),
+    op("get", "/api/v1/reports/demographics", "reports", "Ticket holders by nationality, age bracket and gender per route or month (admin, group_by=route|month, route_id)", Bearer),
+    op("get", "/api/v1/admin/audit-logs", "admin", "Search the audit log", Bearer),
+    op("post", "/api/v1/admin/users/import", "admin", "Import passengers from a CSV file", Bearer),
+    op("get", "/api/v1/admin/webhooks", "admin", "List webhooks", Bearer),
+    created(op("post", "/api/v1/admin/webhooks", "admin", "Register a webhook", Bearer)),
+    op("delete", "/api/v1/admin/webhooks/{id}", "admin", "Disable a webhook", Bearer),
+    op("get", "/api/v1/admin/webhooks/{id}/deliveries", "admin", "Delivery log of a webhook", Bearer),
+    op("post", "/api/v1/promo-codes/validate", "promo-codes", "Quote a promo code", Public),
+    op("get", "/api/v1/loyalty/miles", "loyalty", "Miles balance and recent entries", Bearer),
+    op("post", "/api/v1/loyalty/redeem", "loyalty", "Redeem miles against a ticket (honours Idempotency-Key)", Bearer),
+];
+
+fn path_parameters(path: &str) -> Vec<Value> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| {
+            let schema = if name == "id" || name.ends_with("_id") {
+                json!({ "type": "integer" })
+            } else {
+                json!({ "type": "string" })
+            };
+            json!({ "name": name, "in": "path", "required": true, "schema": schema })
+        })
+        .collect()
+}
+
+fn operation(operation: &Operation) -> Value {
+    let mut value = json!({
+        "tags": [operation.tag],
+        "summary": operation.summary,
+        "parameters": path_parameters(operation.path),
+        "responses": {
+            operation.status.to_string(): { "description": "Success" },
+            "default": {
+                "description": "Error",
+                "content": {
+                    "application/json": { "schema": { "$ref": "#/components/schemas/Error" } }
+                }
+            }
+        }
+    });
+
+    // Every JSON response can be trimmed with `?fields=` (middleware::fields)
+    if operation.method == "get" {
+        value["parameters"]
+            .as_array_mut()
+            .expect("parameters is an array")
+            .push(json!({
+                "name": "fields",
+                "in": "query",
+                "required": false,
+                "description": "Comma-separated fields of `data` to return",
+                "schema": { "type": "string" }
+            }));
+    }
+
+    if matches!(operation.method, "post" | "put" | "patch") {
+        value["requestBody"] = json!({
+            "content": { "application/json": { "schema": { "type": "object" } } }
+        });
+    }
+
+    value["security"] = match operation.security {
+        Public => json!([]),
+        Bearer => json!([{ "bearer": [] }]),
+        BearerOrApiKey => json!([{ "bearer": [] }, { "apiKey": [] }]),
+    };
+
+    value
+}
+
+// OpenAPI 3.0 document describing every route
+pub fn document() -> Value {
+    let mut paths = Map::new();
+    for entry in OPERATIONS {
+        let item = paths
+            .entry(entry.path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[entry.method] = operation(entry);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Airlines API",
+            "version": env!("CARGO_PKG_VERSION")
+        },
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearer": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                "apiKey": { "type": "apiKey", "in": "header", "name": "X-API-Key" }
+            },
+            "schemas": {
+                "Error": {
+                    "type": "object",
+                    "properties": {
+                        "success": { "type": "boolean" },
+                        "error": { "type": "string" },
+                        "error_code": { "type": "string" },
+                        "fields": {
+                            "type": "array",
+                            "description": "Broken field rules of an invalid request body",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "field": { "type": "string" },
+                                    "code": { "type": "string" },
+                                    "message": { "type": "string" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,156 @@
+use crate::config::Config;
+use crate::error::{AppError, ErrorCode};
+
+// Passwords from public breach top lists, compared case-insensitively
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "qwerty",
+    "qwerty123",
+    "qwertyuiop",
+    "password",
+    "password1",
+    "password123",
+    "passw0rd",
+    "111111",
+    "000000",
+    "abc123",
+    "iloveyou",
+    "admin",
+    "admin123",
+    "welcome",
+    "welcome1",
+    "letmein",
+    "monkey",
+    "dragon",
+    "football",
+    "baseball",
+    "sunshine",
+    "princess",
+    "trustno1",
+    "1q2w3e4r",
+    "zaq12wsx",
+    "airline",
+    "airlines",
+];
+
+// Password requirements, configured through PASSWORD_MIN_LENGTH and PASSWORD_MIN_CHARACTER_CLASSES
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    // How many of lowercase, uppercase, digits and symbols must appear
+    pub min_character_classes: usize,
+}
+
+impl PasswordPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            min_length: config.password_min_length,
+            min_character_classes: config.password_min_character_classes,
+        }
+    }
+
+    // Every rule the password breaks
+    pub fn violations(&self, password: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if password.chars().count() < self.min_length {
+            violations.push(format!(
+                "must be at least {} characters long",
+                self.min_length
+            ));
+        }
+
+        let classes = [
+            password.chars().any(|c| c.is_lowercase()),
+            password.chars().any(|c| c.is_uppercase()),
+            password.chars().any(|c| c.is_ascii_digit()),
+            password.chars().any(|c| !c.is_alphanumeric()),
+        ]
+        .into_iter()
+        .filter(|present| *present)
+        .count();
+        if classes < self.min_character_classes {
+            violations.push(format!(
+                "must mix at least {} of: lowercase letters, uppercase letters, digits, symbols",
+                self.min_character_classes
+            ));
+        }
+
+        let lowered = password.to_lowercase();
+        if COMMON_PASSWORDS.contains(&lowered.as_str()) {
+            violations.push("is too common".to_string());
+        }
+
+        violations
+    }
+
+    // Shared check for every place a password is set
+    pub fn validate(&self, password: &str) -> Result<(), AppError> {
+        let violations = self.violations(password);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::ValidationError(
+                ErrorCode::WeakPassword,
+                format!("Password {}", violations.join(", ")),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> PasswordPolicy {
+        PasswordPolicy {
+            min_length: 10,
+            min_character_classes: 3,
+        }
+    }
+
+    fn rejects(password: &str, rule: &str) -> bool {
+        match policy().validate(password) {
+            Err(AppError::ValidationError(ErrorCode::WeakPassword, message)) => {
+                message.contains(rule)
+            }
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn length_is_counted_in_characters() {
+        assert!(policy().validate("Tr4vel-ok!").is_ok());
+        assert!(rejects("Tr4vel-ok", "at least 10 characters"));
+        // Ten characters, more bytes
+        assert!(policy().validate("Київ-2026a").is_ok());
+    }
+
+    #[test]
+    fn enough_character_classes_must_be_mixed() {
+        assert!(policy().validate("lowercase-and-1").is_ok());
+        assert!(policy().validate("Lowercase and Upper").is_ok());
+        assert!(rejects("onlylowercaseletters", "at least 3 of"));
+        assert!(rejects("lowercase-symbols", "at least 3 of"));
+    }
+
+    #[test]
+    fn common_passwords_are_banned_in_any_case() {
+        assert!(policy().validate("Password12345").is_ok());
+        let lenient = PasswordPolicy {
+            min_length: 1,
+            min_character_classes: 1,
+        };
+        assert!(lenient.validate("PassWord123").is_err());
+        assert!(rejects("Password123", "is too common"));
+    }
+
+    #[test]
+    fn every_broken_rule_is_reported() {
+        assert_eq!(policy().violations("qwerty").len(), 3);
+        assert!(policy().violations("Tr4vel-ok!").is_empty());
+    }
+}
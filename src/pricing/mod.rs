@@ -0,0 +1,70 @@
+pub mod strategies;
+
+use std::{str::FromStr, sync::Arc};
+
+pub use strategies::{DemandPricing, FixedPricing};
+
+// Inputs a strategy may use to price a seat
+#[derive(Debug, Clone, Copy)]
+pub struct PricingContext {
+    pub base_fare: f64,
+    pub days_until_departure: i64,
+    // Share of the class already sold, 0.0..=1.0
+    pub load_factor: f64,
+}
+
+// Strategy chosen with PRICING_STRATEGY
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrategyKind {
+    Fixed,
+    Demand,
+}
+
+impl FromStr for StrategyKind {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "fixed" => Ok(StrategyKind::Fixed),
+            "demand" => Ok(StrategyKind::Demand),
+            _ => Err(()),
+        }
+    }
+}
+
+pub trait PricingStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn price(&self, context: &PricingContext) -> f64;
+}
+
+// Shared handle to the configured strategy
+#[derive(Clone)]
+pub struct PricingEngine {
+    strategy: Arc<dyn PricingStrategy>,
+}
+
+impl PricingEngine {
+    pub fn new(strategy: Arc<dyn PricingStrategy>) -> Self {
+        Self { strategy }
+    }
+
+    // Build the engine for the strategy chosen in configuration
+    pub fn from_kind(kind: StrategyKind) -> Self {
+        let strategy: Arc<dyn PricingStrategy> = match kind {
+            StrategyKind::Fixed => Arc::new(FixedPricing),
+            StrategyKind::Demand => Arc::new(DemandPricing),
+        };
+        Self::new(strategy)
+    }
+
+    pub fn strategy_name(&self) -> &'static str {
+        self.strategy.name()
+    }
+
+    // Price rounded to cents, never below zero
+    pub fn quote(&self, context: &PricingContext) -> f64 {
+        let price = self.strategy.price(context).max(0.0);
+        (price * 100.0).round() / 100.0
+    }
+}
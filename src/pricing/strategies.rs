@@ -0,0 +1,99 @@
+use super::{PricingContext, PricingStrategy};
+
+// Always charges the base fare
+pub struct FixedPricing;
+
+impl PricingStrategy for FixedPricing {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    fn price(&self, context: &PricingContext) -> f64 {
+        context.base_fare
+    }
+}
+
+// Cheaper far ahead of departure, more expensive close to it and as the cabin fills up
+pub struct DemandPricing;
+
+impl DemandPricing {
+    fn time_multiplier(days_until_departure: i64) -> f64 {
+        match days_until_departure {
+            d if d >= 60 => 0.85,
+            d if d >= 30 => 1.0,
+            d if d >= 14 => 1.15,
+            d if d >= 7 => 1.3,
+            _ => 1.5,
+        }
+    }
+
+    // No surcharge until half the class is sold, then up to +50% when full
+    fn load_multiplier(load_factor: f64) -> f64 {
+        1.0 + (load_factor.clamp(0.0, 1.0) - 0.5).max(0.0)
+    }
+}
+
+impl PricingStrategy for DemandPricing {
+    fn name(&self) -> &'static str {
+        "demand"
+    }
+
+    fn price(&self, context: &PricingContext) -> f64 {
+        context.base_fare
+            * Self::time_multiplier(context.days_until_departure)
+            * Self::load_multiplier(context.load_factor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(days_until_departure: i64, load_factor: f64) -> PricingContext {
+        PricingContext {
+            base_fare: 100.0,
+            days_until_departure,
+            load_factor,
+        }
+    }
+
+    #[test]
+    fn demand_pricing_surcharges_only_the_second_half_of_the_class() {
+        let price = |load_factor| DemandPricing.price(&context(45, load_factor));
+
+        assert_eq!(price(0.0), 100.0);
+        assert_eq!(price(0.5), 100.0);
+        assert!((price(0.75) - 125.0).abs() < 1e-9);
+        assert_eq!(price(1.0), 150.0);
+        // Out-of-range load factors are clamped
+        assert_eq!(price(-0.2), 100.0);
+        assert_eq!(price(1.3), 150.0);
+    }
+
+    #[test]
+    fn demand_pricing_rises_toward_departure() {
+        let price = |days| DemandPricing.price(&context(days, 0.0));
+
+        assert_eq!(price(90), 85.0);
+        assert_eq!(price(60), 85.0);
+        assert_eq!(price(59), 100.0);
+        assert_eq!(price(30), 100.0);
+        assert!((price(29) - 115.0).abs() < 1e-9);
+        assert!((price(14) - 115.0).abs() < 1e-9);
+        assert_eq!(price(13), 130.0);
+        assert_eq!(price(7), 130.0);
+        assert_eq!(price(6), 150.0);
+        assert_eq!(price(0), 150.0);
+    }
+
+    #[test]
+    fn demand_pricing_combines_time_and_load() {
+        assert_eq!(DemandPricing.price(&context(3, 1.0)), 225.0);
+        assert!((DemandPricing.price(&context(90, 1.0)) - 127.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn fixed_pricing_ignores_demand() {
+        assert_eq!(FixedPricing.price(&context(0, 1.0)), 100.0);
+    }
+}
@@ -0,0 +1,262 @@
+// Repositories that answer reads from the cache and fall back to the wrapped ones. A flight is
+// keyed by id and dropped when written through the repository; flight searches and counts are
+// keyed by a generation number that every write bumps, so one increment retires all of them and
+// the stale entries simply expire
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use super::{FlightRepository, RouteRepository};
+use crate::cache::{self, Cache};
+use crate::export::RowSender;
+use crate::models::{
+    Flight, FlightFilter, FlightStatus, FlightStatusChange, Gate, ManifestEntry, NewFlight, Route,
+    TicketHolder,
+};
+
+const FLIGHT_GENERATION: &str = "airlines:flights:generation";
+
+// Key of one listing or count: the query's parameters hashed under the current generation
+fn search_key(prefix: &str, generation: i64, query: &str) -> String {
+    let digest = hex::encode(&Sha256::digest(query.as_bytes())[..16]);
+    format!("{}:{}:{}", prefix, generation, digest)
+}
+
+// Retire every cached search; if the cache is down they expire on their own
+async fn bump_generation(cache: &dyn Cache, key: &str) {
+    if let Err(e) = cache.increment(key).await {
+        warn!("Cache invalidation of {} failed: {}", key, e);
+    }
+}
+
+async fn forget(cache: &dyn Cache, key: &str) {
+    if let Err(e) = cache.delete(key).await {
+        warn!("Cache invalidation of {} failed: {}", key, e);
+    }
+}
+
+pub struct CachedFlightRepository {
+    inner: Arc<dyn FlightRepository>,
+    cache: Arc<dyn Cache>,
+    // For single flights
+    ttl: Duration,
+    // For listings and counts
+    search_ttl: Duration,
+}
+
+impl CachedFlightRepository {
+    pub fn new(
+        inner: Arc<dyn FlightRepository>,
+        cache: Arc<dyn Cache>,
+        ttl: Duration,
+        search_ttl: Duration,
+    ) -> Self {
+        Self {
+            inner,
+            cache,
+            ttl,
+            search_ttl,
+        }
+    }
+
+    // FlightFilter's Debug output covers every field, so equal filters share a key
+    async fn search_key(&self, query: String) -> String {
+        let generation = cache::get_json(&*self.cache, FLIGHT_GENERATION)
+            .await
+            .unwrap_or(0);
+        search_key("airlines:flights:search", generation, &query)
+    }
+}
+
+#[async_trait]
+impl FlightRepository for CachedFlightRepository {
+    async fn find_by_id(&self, flight_id: i32) -> Result<Option<Flight>, sqlx::Error> {
+        let key = format!("airlines:flights:{}", flight_id);
+        if let Some(flight) = cache::get_json(&*self.cache, &key).await {
+            return Ok(Some(flight));
+        }
+        let flight = self.inner.find_by_id(flight_id).await?;
+        if let Some(flight) = &flight {
+            cache::set_json(&*self.cache, &key, flight, self.ttl).await;
+        }
+        Ok(flight)
+    }
+
+    async fn find_all(
+        &self,
+        filter: &FlightFilter,
+        page: i32,
+        limit: i32,
+    ) -> Result<Vec<Flight>, sqlx::Error> {
+        let key = self
+            .search_key(format!("find_all|{:?}|{}|{}", filter, page, limit))
+            .await;
+        if let Some(flights) = cache::get_json(&*self.cache, &key).await {
+            return Ok(flights);
+        }
+        let flights = self.inner.find_all(filter, page, limit).await?;
+        cache::set_json(&*self.cache, &key, &flights, self.search_ttl).await;
+        Ok(flights)
+    }
+
+    async fn find_after(
+        &self,
+        filter: &FlightFilter,
+        after: Option<(DateTime<Utc>, i32)>,
+        limit: i32,
+    ) -> Result<Vec<Flight>, sqlx::Error> {
+        let key = self
+            .search_key(format!("find_after|{:?}|{:?}|{}", filter, after, limit))
+            .await;
+        if let Some(flights) = cache::get_json(&*self.cache, &key).await {
+            return Ok(flights);
+        }
+        let flights = self.inner.find_after(filter, after, limit).await?;
+        cache::set_json(&*self.cache, &key, &flights, self.search_ttl).await;
+        Ok(flights)
+    }
+
+    async fn count(&self, filter: &FlightFilter) -> Result<i64, sqlx::Error> {
+        let key = self.search_key(format!("count|{:?}", filter)).await;
+        if let Some(count) = cache::get_json(&*self.cache, &key).await {
+            return Ok(count);
+        }
+        let count = self.inner.count(filter).await?;
+        cache::set_json(&*self.cache, &key, &count, self.search_ttl).await;
+        Ok(count)
+    }
+
+    // Downloads always read the database
+    async fn export(&self, filter: &FlightFilter, rows: RowSender<Flight>) {
+        self.inner.export(filter, rows).await
+    }
+
+    async fn insert(&self, flight: &NewFlight, actor_id: Option<i32>) -> Result<i32, sqlx::Error> {
+        let flight_id = self.inner.insert(flight, actor_id).await?;
+        bump_generation(&*self.cache, FLIGHT_GENERATION).await;
+        Ok(flight_id)
+    }
+
+    async fn update_status(
+        &self,
+        flight_id: i32,
+        from: FlightStatus,
+        to: FlightStatus,
+        actor_id: Option<i32>,
+    ) -> Result<bool, sqlx::Error> {
+        let moved = self
+            .inner
+            .update_status(flight_id, from, to, actor_id)
+            .await?;
+        if moved {
+            forget(&*self.cache, &format!("airlines:flights:{}", flight_id)).await;
+            bump_generation(&*self.cache, FLIGHT_GENERATION).await;
+        }
+        Ok(moved)
+    }
+
+    async fn set_gate(
+        &self,
+        flight_id: i32,
+        gate: Option<&Gate>,
+        actor_id: Option<i32>,
+    ) -> Result<(), sqlx::Error> {
+        self.inner.set_gate(flight_id, gate, actor_id).await?;
+        forget(&*self.cache, &format!("airlines:flights:{}", flight_id)).await;
+        bump_generation(&*self.cache, FLIGHT_GENERATION).await;
+        Ok(())
+    }
+
+    async fn set_marketing_numbers(
+        &self,
+        flight_id: i32,
+        flight_numbers: &[String],
+    ) -> Result<(), sqlx::Error> {
+        self.inner
+            .set_marketing_numbers(flight_id, flight_numbers)
+            .await?;
+        forget(&*self.cache, &format!("airlines:flights:{}", flight_id)).await;
+        bump_generation(&*self.cache, FLIGHT_GENERATION).await;
+        Ok(())
+    }
+
+    // History, passengers and manifests change with every booking and check-in
+    async fn status_history(&self, flight_id: i32) -> Result<Vec<FlightStatusChange>, sqlx::Error> {
+        self.inner.status_history(flight_id).await
+    }
+
+    async fn ticket_holders(&self, flight_id: i32) -> Result<Vec<TicketHolder>, sqlx::Error> {
+        self.inner.ticket_holders(flight_id).await
+    }
+
+    async fn manifest(&self, flight_id: i32) -> Result<Vec<ManifestEntry>, sqlx::Error> {
+        self.inner.manifest(flight_id).await
+    }
+}
+
+// Routes are only written by the seed, which runs outside the server, so cached routes simply
+// live for the reference TTL
+pub struct CachedRouteRepository {
+    inner: Arc<dyn RouteRepository>,
+    cache: Arc<dyn Cache>,
+    ttl: Duration,
+}
+
+impl CachedRouteRepository {
+    pub fn new(inner: Arc<dyn RouteRepository>, cache: Arc<dyn Cache>, ttl: Duration) -> Self {
+        Self { inner, cache, ttl }
+    }
+
+    async fn cached<T, F>(&self, key: String, load: F) -> Result<T, sqlx::Error>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Send,
+        F: std::future::Future<Output = Result<T, sqlx::Error>> + Send,
+    {
+        if let Some(value) = cache::get_json(&*self.cache, &key).await {
+            return Ok(value);
+        }
+        let value = load.await?;
+        cache::set_json(&*self.cache, &key, &value, self.ttl).await;
+        Ok(value)
+    }
+}
+
+#[async_trait]
+impl RouteRepository for CachedRouteRepository {
+    // Unknown ids are not cached, so a new route shows up at once
+    async fn find_by_id(&self, route_id: i32) -> Result<Option<Route>, sqlx::Error> {
+        let key = format!("airlines:routes:{}", route_id);
+        if let Some(route) = cache::get_json(&*self.cache, &key).await {
+            return Ok(Some(route));
+        }
+        let route = self.inner.find_by_id(route_id).await?;
+        if let Some(route) = &route {
+            cache::set_json(&*self.cache, &key, route, self.ttl).await;
+        }
+        Ok(route)
+    }
+
+    async fn find_all(&self, page: i32, limit: i32) -> Result<Vec<Route>, sqlx::Error> {
+        let key = format!("airlines:routes:page:{}:{}", page, limit);
+        self.cached(key, self.inner.find_all(page, limit)).await
+    }
+
+    async fn find_after(
+        &self,
+        after_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<Route>, sqlx::Error> {
+        let after = after_id.map_or("start".to_string(), |id| id.to_string());
+        let key = format!("airlines:routes:after:{}:{}", after, limit);
+        self.cached(key, self.inner.find_after(after_id, limit))
+            .await
+    }
+
+    async fn count(&self) -> Result<i64, sqlx::Error> {
+        self.cached("airlines:routes:count".to_string(), self.inner.count())
+            .await
+    }
+}
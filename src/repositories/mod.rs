@@ -0,0 +1,133 @@
+// Storage used by the handlers, extracted as e.g. `State<Arc<dyn UserRepository>>`. The
+// server wires in the MySQL implementations; tests can put fakes into `AppState` instead.
+// Errors stay `sqlx::Error` so handlers turn them into `AppError` the same way as before
+pub mod cached;
+pub mod mysql;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::export::RowSender;
+use crate::models::{
+    Flight, FlightFilter, FlightStatus, FlightStatusChange, Gate, ManifestEntry, NewFlight, Route,
+    TicketHolder, UpdateProfile, User, UserFilter,
+};
+
+pub use cached::{CachedFlightRepository, CachedRouteRepository};
+pub use mysql::{MySqlFlightRepository, MySqlRouteRepository, MySqlUserRepository};
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn find_by_id(&self, user_id: i32) -> Result<Option<User>, sqlx::Error>;
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, sqlx::Error>;
+
+    async fn find_all(
+        &self,
+        filter: &UserFilter,
+        page: i32,
+        limit: i32,
+    ) -> Result<Vec<User>, sqlx::Error>;
+
+    // Keyset page ordered by (last_name, user_id), after the given key
+    async fn find_after(
+        &self,
+        filter: &UserFilter,
+        after: Option<(String, i32)>,
+        limit: i32,
+    ) -> Result<Vec<User>, sqlx::Error>;
+
+    async fn count(&self, filter: &UserFilter) -> Result<i64, sqlx::Error>;
+
+    // Send every matching user, in listing order, for a download
+    async fn export(&self, filter: &UserFilter, rows: RowSender<User>);
+
+    async fn find_by_phone(&self, phone: &str) -> Result<Option<User>, sqlx::Error>;
+
+    // Absent fields keep their value; None if there is no such user
+    async fn update_profile(
+        &self,
+        user_id: i32,
+        profile: &UpdateProfile,
+    ) -> Result<Option<User>, sqlx::Error>;
+
+    // Store a new password hash and revoke all of the user's sessions
+    async fn update_password(&self, user_id: i32, password_hash: &str) -> Result<(), sqlx::Error>;
+
+    // Anonymize the account in place and delete its credentials; see `User::erase`
+    async fn erase(&self, user_id: i32, password_hash: &str) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait FlightRepository: Send + Sync {
+    async fn find_by_id(&self, flight_id: i32) -> Result<Option<Flight>, sqlx::Error>;
+
+    async fn find_all(
+        &self,
+        filter: &FlightFilter,
+        page: i32,
+        limit: i32,
+    ) -> Result<Vec<Flight>, sqlx::Error>;
+
+    // Keyset page ordered by (departure_time, flight_id), after the given key
+    async fn find_after(
+        &self,
+        filter: &FlightFilter,
+        after: Option<(DateTime<Utc>, i32)>,
+        limit: i32,
+    ) -> Result<Vec<Flight>, sqlx::Error>;
+
+    async fn count(&self, filter: &FlightFilter) -> Result<i64, sqlx::Error>;
+
+    // Send every matching flight, in listing order, for a download
+    async fn export(&self, filter: &FlightFilter, rows: RowSender<Flight>);
+
+    // Schedule a flight; returns its id
+    async fn insert(&self, flight: &NewFlight, actor_id: Option<i32>) -> Result<i32, sqlx::Error>;
+
+    // Compare-and-set from `from` to `to`; false if the flight was changed concurrently
+    async fn update_status(
+        &self,
+        flight_id: i32,
+        from: FlightStatus,
+        to: FlightStatus,
+        actor_id: Option<i32>,
+    ) -> Result<bool, sqlx::Error>;
+
+    // Give the flight a gate, or take it away with None
+    async fn set_gate(
+        &self,
+        flight_id: i32,
+        gate: Option<&Gate>,
+        actor_id: Option<i32>,
+    ) -> Result<(), sqlx::Error>;
+
+    // Replace the marketing flight numbers the flight is sold under
+    async fn set_marketing_numbers(
+        &self,
+        flight_id: i32,
+        flight_numbers: &[String],
+    ) -> Result<(), sqlx::Error>;
+
+    async fn status_history(&self, flight_id: i32) -> Result<Vec<FlightStatusChange>, sqlx::Error>;
+
+    async fn ticket_holders(&self, flight_id: i32) -> Result<Vec<TicketHolder>, sqlx::Error>;
+
+    async fn manifest(&self, flight_id: i32) -> Result<Vec<ManifestEntry>, sqlx::Error>;
+}
+
+#[async_trait]
+pub trait RouteRepository: Send + Sync {
+    async fn find_by_id(&self, route_id: i32) -> Result<Option<Route>, sqlx::Error>;
+
+    async fn find_all(&self, page: i32, limit: i32) -> Result<Vec<Route>, sqlx::Error>;
+
+    // Keyset page ordered by route_id, after the given id
+    async fn find_after(
+        &self,
+        after_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<Route>, sqlx::Error>;
+
+    async fn count(&self) -> Result<i64, sqlx::Error>;
+}
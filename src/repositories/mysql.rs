@@ -0,0 +1,200 @@
+// The repositories backed by the server's database; the queries themselves stay in the models
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use super::{FlightRepository, RouteRepository, UserRepository};
+use crate::db::DbPool;
+use crate::export::RowSender;
+use crate::models::{
+    Flight, FlightFilter, FlightStatus, FlightStatusChange, Gate, ManifestEntry, NewFlight, Route,
+    TicketHolder, UpdateProfile, User, UserFilter,
+};
+
+#[derive(Clone)]
+pub struct MySqlUserRepository {
+    pool: DbPool,
+}
+
+impl MySqlUserRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepository for MySqlUserRepository {
+    async fn find_by_id(&self, user_id: i32) -> Result<Option<User>, sqlx::Error> {
+        User::find_by_id(&self.pool, user_id).await
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>, sqlx::Error> {
+        User::find_by_email(&self.pool, email).await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &UserFilter,
+        page: i32,
+        limit: i32,
+    ) -> Result<Vec<User>, sqlx::Error> {
+        User::find_all(&self.pool, filter, page, limit).await
+    }
+
+    async fn find_after(
+        &self,
+        filter: &UserFilter,
+        after: Option<(String, i32)>,
+        limit: i32,
+    ) -> Result<Vec<User>, sqlx::Error> {
+        User::find_after(&self.pool, filter, after, limit).await
+    }
+
+    async fn count(&self, filter: &UserFilter) -> Result<i64, sqlx::Error> {
+        User::count(&self.pool, filter).await
+    }
+
+    async fn export(&self, filter: &UserFilter, rows: RowSender<User>) {
+        User::export(&self.pool, filter, rows).await
+    }
+
+    async fn find_by_phone(&self, phone: &str) -> Result<Option<User>, sqlx::Error> {
+        User::find_by_phone(&self.pool, phone).await
+    }
+
+    async fn update_profile(
+        &self,
+        user_id: i32,
+        profile: &UpdateProfile,
+    ) -> Result<Option<User>, sqlx::Error> {
+        User::update_profile(&self.pool, user_id, profile).await
+    }
+
+    async fn update_password(&self, user_id: i32, password_hash: &str) -> Result<(), sqlx::Error> {
+        User::update_password(&self.pool, user_id, password_hash).await
+    }
+
+    async fn erase(&self, user_id: i32, password_hash: &str) -> Result<(), sqlx::Error> {
+        User::erase(&self.pool, user_id, password_hash).await
+    }
+}
+
+#[derive(Clone)]
+pub struct MySqlFlightRepository {
+    pool: DbPool,
+}
+
+impl MySqlFlightRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl FlightRepository for MySqlFlightRepository {
+    async fn find_by_id(&self, flight_id: i32) -> Result<Option<Flight>, sqlx::Error> {
+        Flight::find_by_id(&self.pool, flight_id).await
+    }
+
+    async fn find_all(
+        &self,
+        filter: &FlightFilter,
+        page: i32,
+        limit: i32,
+    ) -> Result<Vec<Flight>, sqlx::Error> {
+        Flight::find_all(&self.pool, filter, page, limit).await
+    }
+
+    async fn find_after(
+        &self,
+        filter: &FlightFilter,
+        after: Option<(DateTime<Utc>, i32)>,
+        limit: i32,
+    ) -> Result<Vec<Flight>, sqlx::Error> {
+        Flight::find_after(&self.pool, filter, after, limit).await
+    }
+
+    async fn count(&self, filter: &FlightFilter) -> Result<i64, sqlx::Error> {
+        Flight::count(&self.pool, filter).await
+    }
+
+    async fn export(&self, filter: &FlightFilter, rows: RowSender<Flight>) {
+        Flight::export(&self.pool, filter, rows).await
+    }
+
+    async fn insert(&self, flight: &NewFlight, actor_id: Option<i32>) -> Result<i32, sqlx::Error> {
+        Flight::insert(&self.pool, flight, actor_id).await
+    }
+
+    async fn update_status(
+        &self,
+        flight_id: i32,
+        from: FlightStatus,
+        to: FlightStatus,
+        actor_id: Option<i32>,
+    ) -> Result<bool, sqlx::Error> {
+        Flight::update_status(&self.pool, flight_id, from, to, actor_id).await
+    }
+
+    async fn set_gate(
+        &self,
+        flight_id: i32,
+        gate: Option<&Gate>,
+        actor_id: Option<i32>,
+    ) -> Result<(), sqlx::Error> {
+        Flight::set_gate(&self.pool, flight_id, gate, actor_id).await
+    }
+
+    async fn set_marketing_numbers(
+        &self,
+        flight_id: i32,
+        flight_numbers: &[String],
+    ) -> Result<(), sqlx::Error> {
+        Flight::set_marketing_numbers(&self.pool, flight_id, flight_numbers).await
+    }
+
+    async fn status_history(&self, flight_id: i32) -> Result<Vec<FlightStatusChange>, sqlx::Error> {
+        Flight::status_history(&self.pool, flight_id).await
+    }
+
+    async fn ticket_holders(&self, flight_id: i32) -> Result<Vec<TicketHolder>, sqlx::Error> {
+        Flight::ticket_holders(&self.pool, flight_id).await
+    }
+
+    async fn manifest(&self, flight_id: i32) -> Result<Vec<ManifestEntry>, sqlx::Error> {
+        Flight::manifest(&self.pool, flight_id).await
+    }
+}
+
+#[derive(Clone)]
+pub struct MySqlRouteRepository {
+    pool: DbPool,
+}
+
+impl MySqlRouteRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RouteRepository for MySqlRouteRepository {
+    async fn find_by_id(&self, route_id: i32) -> Result<Option<Route>, sqlx::Error> {
+        Route::find_by_id(&self.pool, route_id).await
+    }
+
+    async fn find_all(&self, page: i32, limit: i32) -> Result<Vec<Route>, sqlx::Error> {
+        Route::find_all(&self.pool, page, limit).await
+    }
+
+    async fn find_after(
+        &self,
+        after_id: Option<i32>,
+        limit: i32,
+    ) -> Result<Vec<Route>, sqlx::Error> {
+        Route::find_after(&self.pool, after_id, limit).await
+    }
+
+    async fn count(&self) -> Result<i64, sqlx::Error> {
+        Route::count(&self.pool).await
+    }
+}
@@ -0,0 +1,505 @@
+use axum::{
+    extract::DefaultBodyLimit,
+    routing::{delete, get, patch, post, put},
+    Router,
+};
+
+use crate::handlers;
+use crate::middleware::{self, guard::ADMIN};
+use crate::state::AppState;
+
+// Prefix of the current API version; `/api` without a version is a deprecated alias of it
+pub const CURRENT_PREFIX: &str = "/api/v1";
+
+// Every versioned endpoint, with the admin routes behind the role guard
+fn v1(state: &AppState) -> Router<AppState> {
+    let admin = Router::new()
+        .route(
+            "/admin/content/{key}",
+            get(handlers::content_handler::get_translations),
+        )
+        .route(
+            "/admin/content/{key}/{locale}",
+            put(handlers::content_handler::upsert_translation)
+                .delete(handlers::content_handler::delete_translation),
+        )
+        .route(
+            "/admin/api-keys",
+            get(handlers::api_key_handler::get_api_keys)
+                .post(handlers::api_key_handler::create_api_key),
+        )
+        .route(
+            "/admin/api-keys/{id}",
+            delete(handlers::api_key_handler::revoke_api_key),
+        )
+        .route(
+            "/admin/promo-codes",
+            get(handlers::promo_code_handler::get_promo_codes)
+                .post(handlers::promo_code_handler::create_promo_code),
+        )
+        .route(
+            "/admin/promo-codes/{id}",
+            delete(handlers::promo_code_handler::delete_promo_code),
+        )
+        .route(
+            "/admin/ancillaries",
+            get(handlers::ancillary_handler::get_ancillaries)
+                .post(handlers::ancillary_handler::create_ancillary),
+        )
+        .route(
+            "/admin/ancillaries/{id}",
+            put(handlers::ancillary_handler::update_ancillary),
+        )
+        .route(
+            "/admin/cabin-layouts",
+            get(handlers::cabin_layout_handler::get_cabin_layouts)
+                .post(handlers::cabin_layout_handler::create_cabin_layout),
+        )
+        .route(
+            "/admin/airports/{code}/gates",
+            post(handlers::gate_handler::create_gate),
+        )
+        .route(
+            "/admin/baggage-allowances",
+            put(handlers::baggage_handler::set_baggage_allowance),
+        )
+        .route(
+            "/admin/aircraft/{id}/cabin-layout",
+            put(handlers::cabin_layout_handler::set_aircraft_cabin_layout),
+        )
+        .route(
+            "/admin/aircraft/{id}/pet-limits",
+            put(handlers::pet_handler::set_aircraft_pet_limits),
+        )
+        .route(
+            "/admin/aircraft/{id}/cargo-limits",
+            put(handlers::cargo_handler::set_aircraft_cargo_limits),
+        )
+        .route(
+            "/admin/users/import",
+            post(handlers::import_handler::import_passengers).layer(DefaultBodyLimit::max(
+                handlers::import_handler::MAX_IMPORT_BYTES,
+            )),
+        )
+        .route(
+            "/reports/load-factor",
+            get(handlers::report_handler::get_load_factor_report),
+        )
+        .route(
+            "/reports/revenue",
+            get(handlers::report_handler::get_revenue_report),
+        )
+        .route(
+            "/reports/on-time",
+            get(handlers::report_handler::get_on_time_report),
+        )
+        .route(
+            "/reports/demand",
+            get(handlers::report_handler::get_demand_report),
+        )
+        .route(
+            "/reports/demographics",
+            get(handlers::report_handler::get_demographics_report),
+        )
+        .route(
+            "/admin/audit-logs",
+            get(handlers::audit_log_handler::get_audit_logs),
+        )
+        .route(
+            "/admin/webhooks",
+            get(handlers::webhook_handler::get_webhooks)
+                .post(handlers::webhook_handler::create_webhook),
+        )
+        .route(
+            "/admin/webhooks/{id}",
+            delete(handlers::webhook_handler::disable_webhook),
+        )
+        .route(
+            "/admin/webhooks/{id}/deliveries",
+            get(handlers::webhook_handler::get_webhook_deliveries),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::guard::require_role::<ADMIN>,
+        ));
+
+    Router::new()
+        .route("/routes", get(handlers::route_handler::get_routes))
+        .route(
+            "/routes/{id}",
+            get(handlers::route_handler::get_route_by_id),
+        )
+        .route(
+            "/meta/error-codes",
+            get(handlers::meta_handler::get_error_codes),
+        )
+        .route(
+            "/currencies",
+            get(handlers::currency_handler::get_currencies),
+        )
+        .route(
+            "/baggage-allowances",
+            get(handlers::baggage_handler::list_baggage_allowances),
+        )
+        .route("/auth/login", post(handlers::auth_handler::login))
+        .route("/auth/refresh", post(handlers::auth_handler::refresh))
+        .route("/auth/logout", post(handlers::auth_handler::logout))
+        .route("/auth/sessions", get(handlers::auth_handler::get_sessions))
+        .route(
+            "/auth/sessions/{id}",
+            delete(handlers::auth_handler::revoke_session),
+        )
+        .route(
+            "/auth/otp/request",
+            post(handlers::auth_handler::request_otp),
+        )
+        .route("/auth/otp/verify", post(handlers::auth_handler::verify_otp))
+        .route(
+            "/auth/2fa/enroll",
+            post(handlers::two_factor_handler::enroll),
+        )
+        .route(
+            "/auth/2fa/activate",
+            post(handlers::two_factor_handler::activate),
+        )
+        .route(
+            "/auth/2fa/backup-codes",
+            post(handlers::two_factor_handler::regenerate_backup_codes),
+        )
+        .route(
+            "/auth/forgot-password",
+            post(handlers::auth_handler::forgot_password),
+        )
+        .route(
+            "/auth/reset-password",
+            post(handlers::auth_handler::reset_password),
+        )
+        .route(
+            "/auth/password",
+            put(handlers::auth_handler::change_password),
+        )
+        .route(
+            "/flights",
+            get(handlers::flight_handler::get_flights)
+                .post(handlers::flight_handler::schedule_flight),
+        )
+        .route(
+            "/flights/{id}",
+            get(handlers::flight_handler::get_flight_by_id),
+        )
+        .route(
+            "/flights/{id}/status",
+            patch(handlers::flight_handler::update_flight_status),
+        )
+        .route(
+            "/flights/{id}/ws",
+            get(handlers::live_handler::flight_updates_ws),
+        )
+        .route(
+            "/flights/{id}/status-history",
+            get(handlers::flight_handler::get_flight_status_history),
+        )
+        .route(
+            "/flights/{id}/timeline",
+            get(handlers::flight_handler::get_flight_timeline),
+        )
+        .route(
+            "/flights/{id}/manifest",
+            get(handlers::flight_handler::get_flight_manifest),
+        )
+        .route(
+            "/flights/{id}/seat-blocks",
+            get(handlers::seat_block_handler::get_seat_blocks)
+                .post(handlers::seat_block_handler::create_seat_block),
+        )
+        .route(
+            "/flights/{id}/seat-blocks/{block_id}",
+            delete(handlers::seat_block_handler::delete_seat_block),
+        )
+        .route(
+            "/flights/{id}/fare-classes",
+            get(handlers::fare_class_handler::get_fare_classes)
+                .put(handlers::fare_class_handler::update_fare_classes),
+        )
+        .route(
+            "/flights/{id}/crew",
+            get(handlers::crew_handler::get_flight_crew)
+                .put(handlers::crew_handler::assign_flight_crew),
+        )
+        .route(
+            "/flights/{id}/crew/override",
+            put(handlers::crew_handler::override_flight_crew),
+        )
+        .route(
+            "/aircraft-types/{model}/crew-requirements",
+            get(handlers::crew_handler::get_crew_requirements)
+                .put(handlers::crew_handler::update_crew_requirements),
+        )
+        .route(
+            "/aircraft/{id}/maintenance",
+            get(handlers::maintenance_handler::get_maintenance_records)
+                .post(handlers::maintenance_handler::create_maintenance_record),
+        )
+        .route(
+            "/aircraft/{id}/maintenance/{maintenance_id}",
+            delete(handlers::maintenance_handler::delete_maintenance_record),
+        )
+        .route(
+            "/airports/{code}/gates",
+            get(handlers::gate_handler::get_airport_gates),
+        )
+        .route(
+            "/flights/{id}/codeshares",
+            put(handlers::flight_handler::set_flight_codeshares),
+        )
+        .route(
+            "/flights/{id}/gate",
+            put(handlers::gate_handler::assign_flight_gate),
+        )
+        .route(
+            "/flights/{id}/meals",
+            get(handlers::meal_handler::get_flight_meals)
+                .put(handlers::meal_handler::set_flight_meals),
+        )
+        .route(
+            "/flights/{id}/ancillaries",
+            get(handlers::ancillary_handler::get_flight_ancillaries)
+                .put(handlers::ancillary_handler::set_flight_ancillaries),
+        )
+        .route(
+            "/flights/{id}/catering",
+            get(handlers::meal_handler::get_catering_summary),
+        )
+        .route(
+            "/flights/{id}/pets",
+            get(handlers::pet_handler::get_flight_pets),
+        )
+        .route(
+            "/flights/{id}/cargo",
+            get(handlers::cargo_handler::get_flight_cargo),
+        )
+        .route(
+            "/flights/{id}/seats",
+            get(handlers::seat_block_handler::get_flight_seats),
+        )
+        .route(
+            "/flights/{id}/occupancy",
+            get(handlers::seat_block_handler::get_flight_occupancy),
+        )
+        .route("/users", get(handlers::user_handler::get_users))
+        .route(
+            "/users/me/erase",
+            post(handlers::user_handler::erase_account),
+        )
+        .route(
+            "/users/me/export",
+            get(handlers::data_export_handler::request_data_export),
+        )
+        .route(
+            "/users/me/export/{token}",
+            get(handlers::data_export_handler::download_data_export),
+        )
+        .route(
+            "/users/{id}",
+            get(handlers::user_handler::get_user).put(handlers::user_handler::update_user),
+        )
+        .route(
+            "/users/{id}/tickets",
+            get(handlers::user_handler::get_user_tickets),
+        )
+        .route(
+            "/users/{id}/companions",
+            get(handlers::companion_handler::get_companions)
+                .post(handlers::companion_handler::grant_companion),
+        )
+        .route(
+            "/users/{id}/companions/{companion_id}",
+            delete(handlers::companion_handler::revoke_companion),
+        )
+        .route(
+            "/users/{id}/documents",
+            get(handlers::document_handler::get_documents)
+                .post(handlers::document_handler::upload_document)
+                .layer(DefaultBodyLimit::max(
+                    state.config.document_max_bytes
+                        + handlers::document_handler::FORM_OVERHEAD_BYTES,
+                )),
+        )
+        .route(
+            "/users/{id}/documents/{document_id}",
+            get(handlers::document_handler::download_document),
+        )
+        .route(
+            "/users/{id}/avatar",
+            put(handlers::avatar_handler::upload_avatar)
+                .delete(handlers::avatar_handler::delete_avatar)
+                .layer(DefaultBodyLimit::max(
+                    state.config.avatar_max_bytes + handlers::document_handler::FORM_OVERHEAD_BYTES,
+                )),
+        )
+        .route("/avatars/{file}", get(handlers::avatar_handler::get_avatar))
+        .route(
+            "/tickets/stream",
+            get(handlers::live_handler::ticket_events),
+        )
+        .route(
+            "/tickets/group",
+            post(handlers::booking_handler::book_group).layer(
+                axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::idempotency::idempotent,
+                ),
+            ),
+        )
+        .route(
+            "/tickets/group/{reference}",
+            get(handlers::booking_handler::get_group),
+        )
+        .route("/tickets/{id}", get(handlers::ticket_handler::get_ticket))
+        .route(
+            "/tickets/{id}/pet",
+            get(handlers::pet_handler::get_ticket_pet).post(handlers::pet_handler::add_ticket_pet),
+        )
+        .route(
+            "/tickets/{id}/infant",
+            get(handlers::passenger_handler::get_ticket_infant)
+                .post(handlers::passenger_handler::add_ticket_infant),
+        )
+        .route(
+            "/tickets/{id}/meal",
+            put(handlers::meal_handler::choose_ticket_meal),
+        )
+        .route(
+            "/tickets/{id}/ancillaries",
+            get(handlers::ancillary_handler::get_ticket_ancillaries).merge(
+                post(handlers::ancillary_handler::purchase_ticket_ancillary).layer(
+                    axum::middleware::from_fn_with_state(
+                        state.clone(),
+                        middleware::idempotency::idempotent,
+                    ),
+                ),
+            ),
+        )
+        .route(
+            "/tickets/{id}/change-seat",
+            post(handlers::ticket_handler::change_seat),
+        )
+        .route(
+            "/tickets/{id}/upgrade",
+            get(handlers::upgrade_handler::get_upgrade_offers).merge(
+                post(handlers::upgrade_handler::purchase_upgrade).layer(
+                    axum::middleware::from_fn_with_state(
+                        state.clone(),
+                        middleware::idempotency::idempotent,
+                    ),
+                ),
+            ),
+        )
+        .route(
+            "/tickets/{id}/check-in",
+            post(handlers::ticket_handler::check_in_ticket),
+        )
+        .route(
+            "/tickets/{id}/assistance",
+            get(handlers::assistance_handler::get_ticket_assistance)
+                .post(handlers::assistance_handler::request_assistance),
+        )
+        .route(
+            "/tickets/{id}/assistance/{assistance_id}/acknowledge",
+            post(handlers::assistance_handler::acknowledge_assistance),
+        )
+        .route(
+            "/tickets/{id}/baggage",
+            get(handlers::baggage_handler::get_ticket_baggage)
+                .post(handlers::baggage_handler::check_in_bag),
+        )
+        .route(
+            "/tickets/{id}/baggage-allowance",
+            get(handlers::baggage_handler::get_ticket_allowance)
+                .post(handlers::baggage_handler::buy_ticket_allowance),
+        )
+        .route(
+            "/baggage/{tag_number}/scans",
+            post(handlers::baggage_handler::scan_bag),
+        )
+        .route(
+            "/cargo/shipments",
+            post(handlers::cargo_handler::create_shipment),
+        )
+        .route(
+            "/cargo/shipments/{awb_number}",
+            get(handlers::cargo_handler::track_shipment),
+        )
+        .route(
+            "/cargo/shipments/{awb_number}/flight",
+            put(handlers::cargo_handler::assign_shipment),
+        )
+        .route(
+            "/cargo/shipments/{awb_number}/events",
+            post(handlers::cargo_handler::record_shipment_status),
+        )
+        .route(
+            "/tickets/{id}/status-token",
+            post(handlers::status_token_handler::create_status_token),
+        )
+        .route(
+            "/public/flights/{token}/status",
+            get(handlers::status_token_handler::get_public_flight_status),
+        )
+        .route("/content", get(handlers::content_handler::list_content))
+        .route(
+            "/content/{key}",
+            get(handlers::content_handler::get_content),
+        )
+        .route(
+            "/promo-codes/validate",
+            post(handlers::promo_code_handler::validate_promo_code),
+        )
+        .route("/loyalty/miles", get(handlers::loyalty_handler::get_miles))
+        .route(
+            "/loyalty/redeem",
+            post(handlers::loyalty_handler::redeem_miles).layer(
+                axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::idempotency::idempotent,
+                ),
+            ),
+        )
+        .merge(admin)
+}
+
+// Unversioned endpoints, each API version under its prefix, and the legacy aliases.
+// A v2 is added as another `nest("/api/v2", v2(state))`
+pub fn app_router(state: &AppState) -> Router<AppState> {
+    let v1 = v1(state);
+
+    Router::new()
+        .route("/health", get(handlers::health_check::health_check))
+        .route("/health/live", get(handlers::health_check::liveness))
+        .route("/health/ready", get(handlers::health_check::readiness))
+        .route(
+            "/api/openapi.json",
+            get(handlers::docs_handler::get_openapi),
+        )
+        .route("/api/docs", get(handlers::docs_handler::swagger_ui))
+        .nest(CURRENT_PREFIX, v1.clone())
+        .nest(
+            "/api",
+            v1.layer(axum::middleware::from_fn(
+                middleware::deprecation::deprecated,
+            )),
+        )
+        // Route listing predates the `/api` prefix
+        .route(
+            "/routes",
+            get(handlers::route_handler::get_routes).layer(axum::middleware::from_fn(
+                middleware::deprecation::deprecated,
+            )),
+        )
+        .route(
+            "/routes/{id}",
+            get(handlers::route_handler::get_route_by_id).layer(axum::middleware::from_fn(
+                middleware::deprecation::deprecated,
+            )),
+        )
+}
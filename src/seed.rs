@@ -0,0 +1,292 @@
+// `--seed`: fills an empty development or demo database with routes between a handful of
+// airports and their gates, aircraft with their crew requirements, 30 days of flights and one
+// demo user per role. Everything goes through the model layer, so the schema constraints apply
+use std::env;
+
+use chrono::{Duration, NaiveTime, Utc};
+use chrono_tz::{Europe, Tz};
+use tracing::info;
+
+use crate::currencies::BASE_CURRENCY;
+use crate::db::DbPool;
+use crate::models::{
+    Aircraft, AirportTimezone, CrewMember, CrewRequirement, CrewRole, FareClass,
+    FareClassInventory, Flight, FlightFareClass, Gate, Meal, NewFlight, NewGate, NewUser, Route,
+    StaffPosition, User, UserRole,
+};
+
+const DAYS: i64 = 30;
+
+// Terminal and gate codes every airport is given
+const GATES: &[(&str, &str)] = &[
+    ("A", "A1"),
+    ("A", "A2"),
+    ("A", "A3"),
+    ("B", "B1"),
+    ("B", "B2"),
+    ("B", "B3"),
+];
+
+// Meals catered on every flight
+const MEALS: &[Meal] = &[Meal::Standard, Meal::Vegetarian, Meal::Halal, Meal::Child];
+
+// Password of every demo user unless SEED_PASSWORD is set
+const DEFAULT_PASSWORD: &str = "Demo-Passw0rd";
+
+// IATA code, city and timezone
+const AIRPORTS: &[(&str, &str, Tz)] = &[
+    ("KBP", "Kyiv", Europe::Kyiv),
+    ("LWO", "Lviv", Europe::Kyiv),
+    ("WAW", "Warsaw", Europe::Warsaw),
+    ("FRA", "Frankfurt", Europe::Berlin),
+    ("LHR", "London", Europe::London),
+    ("CDG", "Paris", Europe::Paris),
+    ("LIS", "Lisbon", Europe::Lisbon),
+];
+
+// Origin, destination, distance in km, block time in minutes, departure hour (UTC)
+const ROUTES: &[(&str, &str, f32, i64, u32)] = &[
+    ("KBP", "LWO", 470.0, 70, 6),
+    ("KBP", "WAW", 690.0, 95, 8),
+    ("KBP", "FRA", 1_650.0, 170, 9),
+    ("LWO", "WAW", 330.0, 60, 11),
+    ("WAW", "LHR", 1_450.0, 150, 13),
+    ("FRA", "CDG", 450.0, 70, 15),
+    ("KBP", "LIS", 3_300.0, 275, 7),
+];
+
+// Model, seats, business seats, pilots, cabin crew
+const FLEET: &[(&str, i32, i32, i32, i32)] = &[
+    ("Airbus A320", 180, 12, 2, 4),
+    ("Boeing 737-800", 189, 16, 2, 4),
+    ("Embraer E190", 100, 8, 2, 3),
+];
+
+const CREW: &[(&str, &str, CrewRole)] = &[
+    ("Olena", "Kovalenko", CrewRole::Pilot),
+    ("Marek", "Nowak", CrewRole::Pilot),
+    ("Anna", "Schmidt", CrewRole::Pilot),
+    ("Taras", "Melnyk", CrewRole::Pilot),
+    ("Iryna", "Bondar", CrewRole::CabinCrew),
+    ("Zofia", "Wiśniewska", CrewRole::CabinCrew),
+    ("Lukas", "Weber", CrewRole::CabinCrew),
+    ("Claire", "Martin", CrewRole::CabinCrew),
+    ("Dmytro", "Shevchenko", CrewRole::CabinCrew),
+    ("Emily", "Clarke", CrewRole::CabinCrew),
+];
+
+// Email, first and last name, role, staff position
+const USERS: &[(&str, &str, &str, UserRole, Option<StaffPosition>)] = &[
+    ("admin@demo.airlines", "Ada", "Admin", UserRole::Admin, None),
+    (
+        "gate@demo.airlines",
+        "Greg",
+        "Gate",
+        UserRole::Worker,
+        Some(StaffPosition::GateAgent),
+    ),
+    (
+        "checkin@demo.airlines",
+        "Chloe",
+        "Counter",
+        UserRole::Worker,
+        Some(StaffPosition::CheckInAgent),
+    ),
+    (
+        "pilot@demo.airlines",
+        "Pavlo",
+        "Pilot",
+        UserRole::Worker,
+        Some(StaffPosition::Pilot),
+    ),
+    (
+        "dispatch@demo.airlines",
+        "Dana",
+        "Dispatch",
+        UserRole::Worker,
+        Some(StaffPosition::Dispatcher),
+    ),
+    (
+        "engineer@demo.airlines",
+        "Erin",
+        "Engineer",
+        UserRole::Worker,
+        Some(StaffPosition::Engineer),
+    ),
+    (
+        "passenger@demo.airlines",
+        "Pat",
+        "Passenger",
+        UserRole::User,
+        None,
+    ),
+];
+
+fn airport(code: &str) -> String {
+    let city = AIRPORTS
+        .iter()
+        .find(|(iata, _, _)| *iata == code)
+        .map_or(code, |(_, city, _)| city);
+    format!("{} ({})", city, code)
+}
+
+fn airport_timezone(code: &str) -> AirportTimezone {
+    AIRPORTS
+        .iter()
+        .find(|(iata, _, _)| *iata == code)
+        .map(|&(_, _, timezone)| AirportTimezone(timezone))
+        .unwrap_or_default()
+}
+
+pub async fn run(pool: &DbPool) -> Result<(), Box<dyn std::error::Error>> {
+    // The demo users are inserted last, so their presence means an earlier run completed
+    if User::find_by_email(pool, USERS[0].0).await?.is_some() {
+        info!("Database is already seeded, nothing to do");
+        return Ok(());
+    }
+
+    let mut fleet = Vec::new();
+    for &(model, capacity, business, pilots, cabin_crew) in FLEET {
+        let aircraft_id = Aircraft::insert(pool, model, capacity).await?;
+        CrewRequirement::replace_for_model(
+            pool,
+            model,
+            &[(CrewRole::Pilot, pilots), (CrewRole::CabinCrew, cabin_crew)],
+        )
+        .await?;
+        fleet.push((aircraft_id, capacity, business));
+    }
+    for &(first_name, last_name, role) in CREW {
+        CrewMember::insert(pool, first_name, last_name, role).await?;
+    }
+
+    let mut gates = Vec::new();
+    for &(code, _, _) in AIRPORTS {
+        for &(terminal, gate_code) in GATES {
+            let gate = NewGate {
+                gate_code: gate_code.to_string(),
+                terminal: Some(terminal.to_string()),
+            };
+            gates.push(Gate::create(pool, code, &gate).await?);
+        }
+    }
+
+    // Every route is flown in both directions, the return leg three hours after arrival
+    let mut routes = Vec::new();
+    for &(origin, destination, distance, minutes, hour) in ROUTES {
+        let duration = NaiveTime::from_hms_opt((minutes / 60) as u32, (minutes % 60) as u32, 0)
+            .ok_or("route block time must be under 24 hours")?;
+        for (from, to, departure_hour) in [
+            (origin, destination, hour),
+            (
+                destination,
+                origin,
+                hour + 3 + (minutes as u32).div_ceil(60),
+            ),
+        ] {
+            let route = Route::new(
+                airport(from),
+                airport(to),
+                airport_timezone(from),
+                airport_timezone(to),
+                distance,
+                duration,
+            );
+            let route_id = route.insert(pool).await?;
+            routes.push((route_id, from, distance, minutes, departure_hour));
+        }
+    }
+
+    let today = Utc::now().date_naive();
+    let mut flights = 0;
+    for day in 1..=DAYS {
+        let date = today + Duration::days(day);
+        for (index, &(route_id, from, distance, minutes, hour)) in routes.iter().enumerate() {
+            let (aircraft_id, capacity, business) = fleet[index % fleet.len()];
+            let departure_time = date
+                .and_hms_opt(hour % 24, 0, 0)
+                .ok_or("invalid departure hour")?
+                .and_utc();
+            let flight = NewFlight {
+                flight_number: format!("DA{}", 100 + index),
+                route_id,
+                aircraft_id,
+                departure_time,
+                arrival_time: departure_time + Duration::minutes(minutes),
+                gate: None,
+            };
+            let flight_id = Flight::insert(pool, &flight, None).await?;
+            let origin_gates: Vec<&Gate> =
+                gates.iter().filter(|g| g.airport_code == from).collect();
+            Flight::set_gate(
+                pool,
+                flight_id,
+                Some(origin_gates[index % GATES.len()]),
+                None,
+            )
+            .await?;
+
+            let economy_price = (40.0 + distance as f64 * 0.12).round();
+            FlightFareClass::replace_for_flight(
+                pool,
+                flight_id,
+                &[
+                    FareClassInventory {
+                        fare_class: FareClass::Economy,
+                        seat_count: capacity - business,
+                        price: economy_price,
+                        currency: BASE_CURRENCY.to_string(),
+                    },
+                    FareClassInventory {
+                        fare_class: FareClass::Business,
+                        seat_count: business,
+                        price: economy_price * 3.0,
+                        currency: BASE_CURRENCY.to_string(),
+                    },
+                ],
+            )
+            .await?;
+            Meal::replace_for_flight(pool, flight_id, MEALS).await?;
+            flights += 1;
+        }
+    }
+
+    // Development convenience only; not read from Config as the server never needs it
+    let password = env::var("SEED_PASSWORD").unwrap_or_else(|_| DEFAULT_PASSWORD.to_string());
+    let password_hash = bcrypt::hash(&password, bcrypt::DEFAULT_COST)?;
+    for &(email, first_name, last_name, role, staff_position) in USERS {
+        User::insert(
+            pool,
+            &NewUser {
+                first_name: first_name.to_string(),
+                last_name: last_name.to_string(),
+                email: email.to_string(),
+                phone: None,
+                password_hash: password_hash.clone(),
+                role,
+                staff_position,
+            },
+        )
+        .await?;
+    }
+
+    info!(
+        "Seeded {} aircraft, {} crew members, {} gates, {} routes, {} flights and {} users",
+        FLEET.len(),
+        CREW.len(),
+        gates.len(),
+        routes.len(),
+        flights,
+        USERS.len()
+    );
+    info!(
+        "Demo users: {} (password from SEED_PASSWORD, default {:?})",
+        USERS
+            .iter()
+            .map(|(email, ..)| *email)
+            .collect::<Vec<_>>()
+            .join(", "),
+        DEFAULT_PASSWORD
+    );
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use tokio::sync::watch;
+use tracing::info;
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+// Set once shutdown started; readiness fails so load balancers stop routing here
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::Relaxed)
+}
+
+// Resolves on Ctrl+C or, on Unix, SIGTERM (sent by orchestrators before killing the process)
+async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to listen for Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+// Background jobs watch this and stop after finishing the batch they are working on
+pub type ShutdownReceiver = watch::Receiver<bool>;
+
+// On the first signal stop accepting connections and give in-flight requests `grace`
+// to complete before the server is stopped
+pub fn on_signal(server: axum_server::Handle, grace: Duration) {
+    tokio::spawn(async move {
+        signal().await;
+        DRAINING.store(true, Ordering::Relaxed);
+        info!(
+            "Shutdown requested, draining in-flight requests for up to {:?}",
+            grace
+        );
+        server.graceful_shutdown(Some(grace));
+    });
+}
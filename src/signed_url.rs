@@ -0,0 +1,104 @@
+// Expiring links to files that are fetched without a bearer token, such as profile photos in
+// `<img>` tags. A link carries `expires` (Unix seconds) and an HMAC of its path and expiry; the
+// expiry is rounded so a file keeps one URL, and stays cacheable, for a whole TTL window
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+use crate::config::Config;
+
+struct Signer {
+    key: Vec<u8>,
+    ttl: Duration,
+}
+
+static SIGNER: OnceLock<Signer> = OnceLock::new();
+
+// One day with a key of this process only, unless `init` ran before the first link was signed
+fn signer() -> &'static Signer {
+    SIGNER.get_or_init(|| {
+        let mut key = vec![0; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        Signer {
+            key,
+            ttl: Duration::from_secs(86_400),
+        }
+    })
+}
+
+// Use URL_SIGNING_SECRET, so every instance accepts the others' links and they survive
+// restarts; returns false when it is unset and a random key is used instead
+pub fn init(config: &Config) -> bool {
+    let ttl = Duration::from_secs(config.signed_url_ttl.max(1));
+    match &config.url_signing_secret {
+        Some(secret) => {
+            let _ = SIGNER.set(Signer {
+                key: secret.as_bytes().to_vec(),
+                ttl,
+            });
+            true
+        }
+        None => {
+            let mut key = vec![0; 32];
+            rand::thread_rng().fill_bytes(&mut key);
+            let _ = SIGNER.set(Signer { key, ttl });
+            false
+        }
+    }
+}
+
+fn mac(path: &str, expires: i64) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(&signer().key).expect("HMAC takes keys of any size");
+    mac.update(format!("{}\n{}", path, expires).as_bytes());
+    mac
+}
+
+// `path` with `expires` and `signature` query parameters, valid for one to two TTLs
+pub fn sign(path: &str) -> String {
+    let ttl = signer().ttl.as_secs() as i64;
+    let expires = (Utc::now().timestamp() / ttl + 2) * ttl;
+    format!(
+        "{}?expires={}&signature={}",
+        path,
+        expires,
+        hex::encode(mac(path, expires).finalize().into_bytes())
+    )
+}
+
+// Whether the link was signed by `sign` and has not expired
+pub fn verify(path: &str, expires: i64, signature_hex: &str) -> bool {
+    if expires < Utc::now().timestamp() {
+        return false;
+    }
+    let Ok(given) = hex::decode(signature_hex) else {
+        return false;
+    };
+    mac(path, expires).verify_slice(&given).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_only_its_own_unexpired_links() {
+        let link = sign("/api/v1/avatars/a.png");
+        let query = link.split_once('?').unwrap().1;
+        let params: Vec<(&str, &str)> = query
+            .split('&')
+            .map(|pair| pair.split_once('=').unwrap())
+            .collect();
+        let expires: i64 = params[0].1.parse().unwrap();
+        let signature = params[1].1;
+
+        assert!(verify("/api/v1/avatars/a.png", expires, signature));
+        assert!(!verify("/api/v1/avatars/b.png", expires, signature));
+        assert!(!verify("/api/v1/avatars/a.png", expires + 1, signature));
+        assert!(!verify("/api/v1/avatars/a.png", 1, signature));
+    }
+}
@@ -0,0 +1,91 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::extract::FromRef;
+
+use crate::auth::JwtKeys;
+use crate::cache::Cache;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::repositories::{
+    CachedFlightRepository, CachedRouteRepository, FlightRepository, MySqlFlightRepository,
+    MySqlRouteRepository, MySqlUserRepository, RouteRepository, UserRepository,
+};
+
+// Router state. Handlers extract the part they need, e.g. `State<DbPool>` or `State<Config>`
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: DbPool,
+    pub config: Config,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub users: Arc<dyn UserRepository>,
+    pub flights: Arc<dyn FlightRepository>,
+    pub routes: Arc<dyn RouteRepository>,
+}
+
+impl AppState {
+    // State backed by the database; swap the repositories afterwards to run handlers on fakes
+    pub fn new(pool: DbPool, config: Config, jwt_keys: Arc<JwtKeys>) -> Self {
+        Self {
+            users: Arc::new(MySqlUserRepository::new(pool.clone())),
+            flights: Arc::new(MySqlFlightRepository::new(pool.clone())),
+            routes: Arc::new(MySqlRouteRepository::new(pool.clone())),
+            pool,
+            config,
+            jwt_keys,
+        }
+    }
+
+    // Serve flight and route reads from `cache`: single records for `ttl`, flight searches and
+    // counts for `search_ttl`
+    pub fn with_cache(
+        mut self,
+        cache: Arc<dyn Cache>,
+        ttl: Duration,
+        search_ttl: Duration,
+    ) -> Self {
+        self.flights = Arc::new(CachedFlightRepository::new(
+            self.flights,
+            cache.clone(),
+            ttl,
+            search_ttl,
+        ));
+        self.routes = Arc::new(CachedRouteRepository::new(self.routes, cache, ttl));
+        self
+    }
+}
+
+impl FromRef<AppState> for DbPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Config {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<JwtKeys> {
+    fn from_ref(state: &AppState) -> Self {
+        state.jwt_keys.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn UserRepository> {
+    fn from_ref(state: &AppState) -> Self {
+        state.users.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn FlightRepository> {
+    fn from_ref(state: &AppState) -> Self {
+        state.flights.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<dyn RouteRepository> {
+    fn from_ref(state: &AppState) -> Self {
+        state.routes.clone()
+    }
+}
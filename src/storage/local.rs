@@ -0,0 +1,81 @@
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+
+use super::{validate_key, ObjectStorage, StorageError};
+
+// Objects as files under a directory on the server, for development and single-node setups
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, StorageError> {
+        validate_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for LocalStorage {
+    async fn put(&self, key: &str, data: Vec<u8>, _content_type: &str) -> Result<(), StorageError> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // Written next to the target and renamed, so a reader never sees half a file
+        let partial = path.with_extension("partial");
+        tokio::fs::write(&partial, data).await?;
+        tokio::fs::rename(&partial, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        match tokio::fs::remove_file(self.path(key)?).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn stores_reads_and_deletes_objects() {
+        let root = std::env::temp_dir().join(format!("storage-test-{}", uuid::Uuid::new_v4()));
+        let storage = LocalStorage::new(&root);
+
+        storage
+            .put("documents/1/a.pdf", b"%PDF".to_vec(), "application/pdf")
+            .await
+            .unwrap();
+        assert_eq!(
+            storage.get("documents/1/a.pdf").await.unwrap().as_deref(),
+            Some(&b"%PDF"[..])
+        );
+        storage.delete("documents/1/a.pdf").await.unwrap();
+        assert!(storage.get("documents/1/a.pdf").await.unwrap().is_none());
+        storage.delete("documents/1/a.pdf").await.unwrap();
+
+        assert!(matches!(
+            storage.get("../etc/passwd").await,
+            Err(StorageError::InvalidKey(_))
+        ));
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,93 @@
+// Where uploaded and generated files live: a local directory or an S3-compatible bucket, chosen
+// by STORAGE_BACKEND. Objects are addressed by a slash-separated key chosen by the caller (e.g.
+// `documents/42/<uuid>`); the database keeps the key next to the file's metadata
+mod local;
+mod s3;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use url::Url;
+
+use crate::config::Config;
+use crate::http_client::HttpError;
+
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("invalid object key `{0}`")]
+    InvalidKey(String),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Http(#[from] HttpError),
+    #[error("{0}")]
+    Backend(String),
+    #[error("{0}")]
+    Config(String),
+}
+
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    // Store `data` under `key`, replacing any object already there
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<(), StorageError>;
+
+    // None if there is no object under `key`
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError>;
+
+    // Deleting a missing object is not an error
+    async fn delete(&self, key: &str) -> Result<(), StorageError>;
+}
+
+// The backend selected by the configuration
+pub fn from_config(config: &Config) -> Result<Arc<dyn ObjectStorage>, StorageError> {
+    match config.storage_backend.as_str() {
+        "local" => Ok(Arc::new(LocalStorage::new(&config.storage_path))),
+        "s3" => {
+            let setting = |value: &Option<String>, name: &str| {
+                value
+                    .clone()
+                    .ok_or_else(|| StorageError::Config(format!("{} must be set", name)))
+            };
+            let endpoint = match &config.s3_endpoint {
+                Some(endpoint) => endpoint.clone(),
+                None => format!("https://s3.{}.amazonaws.com", config.s3_region),
+            };
+            let endpoint = Url::parse(&endpoint)
+                .map_err(|e| StorageError::Config(format!("S3_ENDPOINT is invalid: {}", e)))?;
+            Ok(Arc::new(S3Storage::new(
+                endpoint,
+                setting(&config.s3_bucket, "S3_BUCKET")?,
+                config.s3_region.clone(),
+                setting(&config.s3_access_key_id, "S3_ACCESS_KEY_ID")?,
+                setting(&config.s3_secret_access_key, "S3_SECRET_ACCESS_KEY")?,
+                config.s3_path_style,
+            )))
+        }
+        other => Err(StorageError::Config(format!(
+            "STORAGE_BACKEND must be one of: local, s3 (got {})",
+            other
+        ))),
+    }
+}
+
+// Keys are relative paths of plain segments, so no backend can be made to reach outside its root
+fn validate_key(key: &str) -> Result<(), StorageError> {
+    let valid = !key.is_empty()
+        && key.split('/').all(|segment| {
+            !segment.is_empty()
+                && segment != "."
+                && segment != ".."
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidKey(key.to_string()))
+    }
+}
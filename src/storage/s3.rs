@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use url::Url;
+
+use super::{validate_key, ObjectStorage, StorageError};
+use crate::http_client;
+use crate::sigv4::{self, SigningRequest};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// Amazon S3 or an S3-compatible service such as MinIO, signed with AWS Signature Version 4
+pub struct S3Storage {
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    // `<endpoint>/<bucket>/<key>` instead of `<bucket>.<endpoint host>/<key>`, as MinIO expects
+    path_style: bool,
+}
+
+impl S3Storage {
+    pub fn new(
+        endpoint: Url,
+        bucket: String,
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+        path_style: bool,
+    ) -> Self {
+        Self {
+            endpoint,
+            bucket,
+            region,
+            access_key_id,
+            secret_access_key,
+            path_style,
+        }
+    }
+
+    fn object_url(&self, key: &str) -> Result<Url, StorageError> {
+        validate_key(key)?;
+        let mut url = self.endpoint.clone();
+        if self.path_style {
+            url.set_path(&format!("/{}/{}", self.bucket, key));
+        } else {
+            let host = format!("{}.{}", self.bucket, url.host_str().unwrap_or_default());
+            url.set_host(Some(&host))
+                .map_err(|e| StorageError::Backend(format!("invalid bucket host: {}", e)))?;
+            url.set_path(&format!("/{}", key));
+        }
+        Ok(url)
+    }
+
+    async fn request(
+        &self,
+        method: &'static str,
+        key: &str,
+        body: Vec<u8>,
+        content_type: Option<&str>,
+    ) -> Result<http_client::HttpResponse, StorageError> {
+        let url = self.object_url(key)?;
+        let mut headers = vec![("host".to_string(), http_client::host_header(&url))];
+        if let Some(content_type) = content_type {
+            headers.push(("content-type".to_string(), content_type.to_string()));
+        }
+        let authorization = sigv4::sign(
+            SigningRequest {
+                service: "s3",
+                method,
+                path: url.path(),
+                headers: &mut headers,
+                payload: &body,
+                region: &self.region,
+                access_key_id: &self.access_key_id,
+                secret_access_key: &self.secret_access_key,
+            },
+            Utc::now(),
+        );
+        headers.push(("authorization".to_string(), authorization));
+        // The Host line is written by the client itself
+        headers.retain(|(name, _)| name != "host");
+
+        Ok(http_client::send(method, url, headers, body, TIMEOUT).await?)
+    }
+}
+
+fn unexpected(method: &str, key: &str, response: &http_client::HttpResponse) -> StorageError {
+    StorageError::Backend(format!(
+        "{} {} returned {}: {}",
+        method,
+        key,
+        response.status,
+        String::from_utf8_lossy(&response.body)
+            .chars()
+            .take(200)
+            .collect::<String>()
+    ))
+}
+
+#[async_trait]
+impl ObjectStorage for S3Storage {
+    async fn put(&self, key: &str, data: Vec<u8>, content_type: &str) -> Result<(), StorageError> {
+        let response = self.request("PUT", key, data, Some(content_type)).await?;
+        match response.status {
+            200..=299 => Ok(()),
+            _ => Err(unexpected("PUT", key, &response)),
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, StorageError> {
+        let response = self.request("GET", key, Vec::new(), None).await?;
+        match response.status {
+            200 => Ok(Some(response.body)),
+            404 => Ok(None),
+            _ => Err(unexpected("GET", key, &response)),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        let response = self.request("DELETE", key, Vec::new(), None).await?;
+        match response.status {
+            200..=299 | 404 => Ok(()),
+            _ => Err(unexpected("DELETE", key, &response)),
+        }
+    }
+}
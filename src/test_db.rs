@@ -0,0 +1,50 @@
+// In-memory SQLite database for unit tests of the model layer. The schema here is a SQLite
+// rendering of the tables under test, not the migrations, which are MySQL; queries that use
+// MySQL-only functions (UTC_TIMESTAMP, SHA2, JSON_CONTAINS…) are still covered by MySQL only
+use sqlx::sqlite::SqlitePoolOptions;
+
+use crate::db::DbPool;
+
+const SCHEMA: &[&str] = &[r#"
+    CREATE TABLE users (
+        user_id INTEGER PRIMARY KEY AUTOINCREMENT,
+        first_name TEXT NOT NULL,
+        last_name TEXT NOT NULL,
+        email TEXT NOT NULL UNIQUE,
+        phone TEXT NULL UNIQUE,
+        password TEXT NOT NULL,
+        passport_number TEXT NULL,
+        nationality TEXT NULL,
+        date_of_birth TEXT NULL,
+        gender TEXT NULL CHECK (gender IN ('female', 'male', 'other')),
+        role TEXT NOT NULL DEFAULT 'user'
+            CHECK (role IN ('admin', 'worker', 'user')),
+        staff_position TEXT NULL
+            CHECK (staff_position IN ('gate_agent', 'check_in_agent', 'pilot', 'dispatcher', 'engineer')),
+        avatar_key TEXT NULL,
+        language TEXT NOT NULL DEFAULT 'en' CHECK (language IN ('en', 'uk')),
+        password_changed_at TEXT NULL,
+        created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+        updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+    )
+    "#];
+
+// A fresh, empty database per call. One connection that is never recycled, because every
+// connection to `sqlite::memory:` opens a database of its own
+pub async fn pool() -> DbPool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect("sqlite::memory:")
+        .await
+        .expect("open in-memory SQLite database");
+
+    for statement in SCHEMA {
+        sqlx::query(statement)
+            .execute(&pool)
+            .await
+            .expect("create test schema");
+    }
+    pool
+}
@@ -0,0 +1,144 @@
+// Lets the server terminate TLS itself when it is not behind a proxy that does
+mod stream;
+
+use std::{fs::File, future::Future, io, io::BufReader, pin::Pin, sync::Arc, time::Duration};
+
+use axum::{
+    http::{header, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+use axum_server::accept::Accept;
+use rustls::{Certificate, PrivateKey, ServerConfig, ServerConnection};
+use tokio::net::TcpStream;
+
+use crate::config::{Config, ConfigError};
+
+pub use stream::TlsStream;
+
+// Clients that do not finish the handshake in time are disconnected
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn key_file_error(path: &str, reason: impl ToString) -> ConfigError {
+    ConfigError::KeyFile {
+        path: path.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn open(path: &str) -> Result<BufReader<File>, ConfigError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| key_file_error(path, e))
+}
+
+fn load_certificates(path: &str) -> Result<Vec<Certificate>, ConfigError> {
+    let certificates = rustls_pemfile::certs(&mut open(path)?)
+        .map_err(|e| key_file_error(path, e))?
+        .into_iter()
+        .map(Certificate)
+        .collect::<Vec<_>>();
+    if certificates.is_empty() {
+        return Err(key_file_error(path, "no certificates found"));
+    }
+    Ok(certificates)
+}
+
+// First PKCS#8, RSA or SEC1 private key in the file
+fn load_private_key(path: &str) -> Result<PrivateKey, ConfigError> {
+    let mut reader = open(path)?;
+    loop {
+        match rustls_pemfile::read_one(&mut reader).map_err(|e| key_file_error(path, e))? {
+            Some(rustls_pemfile::Item::PKCS8Key(key))
+            | Some(rustls_pemfile::Item::RSAKey(key))
+            | Some(rustls_pemfile::Item::ECKey(key)) => return Ok(PrivateKey(key)),
+            Some(_) => continue,
+            None => return Err(key_file_error(path, "no private key found")),
+        }
+    }
+}
+
+// TLS settings from TLS_CERT_PATH and TLS_KEY_PATH; None serves plain HTTP
+pub fn server_config(config: &Config) -> Result<Option<Arc<ServerConfig>>, ConfigError> {
+    let (cert_path, key_path) = match (&config.tls_cert_path, &config.tls_key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (None, None) => return Ok(None),
+        (None, Some(_)) => return Err(ConfigError::Missing("TLS_CERT_PATH")),
+        (Some(_), None) => return Err(ConfigError::Missing("TLS_KEY_PATH")),
+    };
+
+    let mut server_config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(load_certificates(cert_path)?, load_private_key(key_path)?)
+        .map_err(|e| key_file_error(key_path, e))?;
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Some(Arc::new(server_config)))
+}
+
+// Wraps accepted connections in TLS before they reach hyper
+#[derive(Clone)]
+pub struct TlsAcceptor {
+    config: Arc<ServerConfig>,
+}
+
+impl TlsAcceptor {
+    pub fn new(config: Arc<ServerConfig>) -> Self {
+        Self { config }
+    }
+}
+
+impl<S: Send + 'static> Accept<TcpStream, S> for TlsAcceptor {
+    type Stream = TlsStream;
+    type Service = S;
+    type Future = Pin<Box<dyn Future<Output = io::Result<(TlsStream, S)>> + Send>>;
+
+    fn accept(&self, tcp: TcpStream, service: S) -> Self::Future {
+        let config = self.config.clone();
+        Box::pin(async move {
+            let connection = ServerConnection::new(config).map_err(io::Error::other)?;
+            let mut stream = TlsStream::new(tcp, connection);
+            tokio::time::timeout(
+                HANDSHAKE_TIMEOUT,
+                std::future::poll_fn(|cx| stream.poll_handshake(cx)),
+            )
+            .await
+            .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+            Ok((stream, service))
+        })
+    }
+}
+
+// "example.com:80" -> "example.com", "[::1]:80" -> "[::1]"
+fn without_port(host: &str) -> &str {
+    match host.find(']') {
+        Some(end) => &host[..=end],
+        None => host.split(':').next().unwrap_or(host),
+    }
+}
+
+// Plain HTTP app sending every request to the same path on the HTTPS listener
+pub fn redirect_router(https_port: u16) -> Router {
+    Router::new().fallback(move |headers: axum::http::HeaderMap, uri: Uri| async move {
+        let Some(host) = headers
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .map(without_port)
+        else {
+            return StatusCode::BAD_REQUEST.into_response();
+        };
+
+        let authority = match https_port {
+            443 => host.to_string(),
+            port => format!("{}:{}", host, port),
+        };
+        let path = uri.path_and_query().map_or("/", |path| path.as_str());
+        let location = format!("https://{}{}", authority, path);
+
+        Response::builder()
+            .status(StatusCode::PERMANENT_REDIRECT)
+            .header(header::LOCATION, location)
+            .body(axum::body::Body::empty())
+            .unwrap_or_else(|_| StatusCode::BAD_REQUEST.into_response())
+    })
+}
@@ -0,0 +1,179 @@
+// Async adapter over rustls' blocking-style connection API, in the spirit of tokio-rustls
+use std::{
+    io::{self, Read, Write},
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use rustls::ServerConnection;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    net::TcpStream,
+};
+
+// Presents the socket to rustls as std Read/Write, turning `Pending` into `WouldBlock`
+struct SyncIo<'a, 'b> {
+    io: &'a mut TcpStream,
+    cx: &'a mut Context<'b>,
+}
+
+impl Read for SyncIo<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut buf = ReadBuf::new(buf);
+        match Pin::new(&mut *self.io).poll_read(self.cx, &mut buf) {
+            Poll::Ready(Ok(())) => Ok(buf.filled().len()),
+            Poll::Ready(Err(e)) => Err(e),
+            Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl Write for SyncIo<'_, '_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match Pin::new(&mut *self.io).poll_write(self.cx, buf) {
+            Poll::Ready(result) => result,
+            Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match Pin::new(&mut *self.io).poll_flush(self.cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+fn would_block<T>(result: io::Result<T>) -> Poll<io::Result<T>> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::WouldBlock => Poll::Pending,
+        result => Poll::Ready(result),
+    }
+}
+
+pub struct TlsStream {
+    io: TcpStream,
+    conn: ServerConnection,
+    close_notify_sent: bool,
+}
+
+impl TlsStream {
+    pub fn new(io: TcpStream, conn: ServerConnection) -> Self {
+        Self {
+            io,
+            conn,
+            close_notify_sent: false,
+        }
+    }
+
+    // Feed received records to rustls; `Ok(0)` means the peer closed the socket
+    fn read_tls(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let mut io = SyncIo {
+            io: &mut self.io,
+            cx,
+        };
+        let read = ready!(would_block(self.conn.read_tls(&mut io)))?;
+        if let Err(e) = self.conn.process_new_packets() {
+            // Best effort to tell the peer why, via the alert rustls queued
+            let _ = self.write_tls(cx);
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidData, e)));
+        }
+        Poll::Ready(Ok(read))
+    }
+
+    fn write_tls(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut io = SyncIo {
+            io: &mut self.io,
+            cx,
+        };
+        match ready!(would_block(self.conn.write_tls(&mut io)))? {
+            0 => Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn flush_tls(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.conn.wants_write() {
+            ready!(self.write_tls(cx))?;
+        }
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    // Drive the handshake until the session is established
+    pub fn poll_handshake(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.conn.is_handshaking() {
+            if self.conn.wants_write() {
+                ready!(self.flush_tls(cx))?;
+            } else if ready!(self.read_tls(cx))? == 0 {
+                return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+            }
+        }
+        self.flush_tls(cx)
+    }
+}
+
+impl AsyncRead for TlsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            match this.conn.reader().read(buf.initialize_unfilled()) {
+                Ok(read) => {
+                    buf.advance(read);
+                    return Poll::Ready(Ok(()));
+                }
+                // Clients often drop the socket without close_notify; treat it as end of stream
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Poll::Ready(Ok(())),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                Err(e) => return Poll::Ready(Err(e)),
+            }
+
+            if this.conn.wants_write() {
+                ready!(this.flush_tls(cx))?;
+            }
+            ready!(this.read_tls(cx))?;
+        }
+    }
+}
+
+impl AsyncWrite for TlsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        // Only take more plaintext once the previous records are on the wire
+        while this.conn.wants_write() {
+            ready!(this.write_tls(cx))?;
+        }
+        let written = this.conn.writer().write(buf)?;
+        while this.conn.wants_write() {
+            match this.write_tls(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => break,
+            }
+        }
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.conn.writer().flush()?;
+        this.flush_tls(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if !this.close_notify_sent {
+            this.conn.send_close_notify();
+            this.close_notify_sent = true;
+        }
+        ready!(this.flush_tls(cx))?;
+        Pin::new(&mut this.io).poll_shutdown(cx)
+    }
+}
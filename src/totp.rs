@@ -0,0 +1,169 @@
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha1::Sha1;
+
+// RFC 6238 parameters understood by every authenticator app
+pub const DIGITS: u32 = 6;
+pub const PERIOD_SECS: u64 = 30;
+// Accept codes from one step before and after the current one to absorb clock drift
+const DRIFT_STEPS: u64 = 1;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// A new random 160-bit secret, base32 encoded
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 20];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base32_encode(&bytes)
+}
+
+// `otpauth://` URI to be rendered as a QR code by the client
+pub fn provisioning_uri(issuer: &str, account: &str, secret: &str) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={DIGITS}&period={PERIOD_SECS}",
+        issuer = urlencoding::encode(issuer),
+        account = urlencoding::encode(account),
+    )
+}
+
+// Time step of a Unix timestamp
+pub fn step_at(unix_secs: u64) -> u64 {
+    unix_secs / PERIOD_SECS
+}
+
+// The step within the drift window whose code matches, if any
+pub fn matching_step(secret: &str, code: &str, unix_secs: u64) -> Option<u64> {
+    let key = base32_decode(secret)?;
+    let current = step_at(unix_secs);
+    (current.saturating_sub(DRIFT_STEPS)..=current + DRIFT_STEPS)
+        .find(|&step| code_for_step(&key, step) == code)
+}
+
+fn code_for_step(key: &[u8], step: u64) -> String {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation, RFC 4226 section 5.3
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+// RFC 4648 base32 without padding
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut output = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            output.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        output.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    output
+}
+
+fn base32_decode(encoded: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in encoded.trim_end_matches('=').chars() {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    Some(output)
+}
+
+// Single-use recovery code for a lost authenticator, e.g. `K7Q2M9XD`
+pub fn generate_backup_code() -> String {
+    let mut bytes = [0u8; 5];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    base32_encode(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The 20-byte key of the RFC 4226 and RFC 6238 test vectors, "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn hotp_matches_the_rfc_4226_vectors() {
+        let key = base32_decode(RFC_SECRET).unwrap();
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314", "254676", "287922", "162583",
+            "399871", "520489",
+        ];
+
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(code_for_step(&key, counter as u64), *code);
+        }
+    }
+
+    #[test]
+    fn totp_matches_the_rfc_6238_vectors() {
+        // The RFC lists 8 digits; these are their last six
+        let vectors = [
+            (59, "287082"),
+            (1_111_111_109, "081804"),
+            (1_111_111_111, "050471"),
+            (1_234_567_890, "005924"),
+            (2_000_000_000, "279037"),
+            (20_000_000_000, "353130"),
+        ];
+
+        for (unix_secs, code) in vectors {
+            assert_eq!(
+                matching_step(RFC_SECRET, code, unix_secs),
+                Some(step_at(unix_secs))
+            );
+        }
+    }
+
+    #[test]
+    fn codes_one_step_off_are_accepted_and_two_are_not() {
+        // 287082 is the code of step 1, unix seconds 30..60
+        assert_eq!(matching_step(RFC_SECRET, "287082", 0), Some(1));
+        assert_eq!(matching_step(RFC_SECRET, "287082", 89), Some(1));
+        assert_eq!(matching_step(RFC_SECRET, "287082", 90), None);
+        assert_eq!(matching_step(RFC_SECRET, "359152", 29), None);
+        assert_eq!(matching_step(RFC_SECRET, "755224", 59), Some(0));
+        assert_eq!(matching_step(RFC_SECRET, "755224", 60), None);
+    }
+
+    #[test]
+    fn base32_round_trips_and_matches_rfc_4648() {
+        assert_eq!(base32_encode(b"12345678901234567890"), RFC_SECRET);
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("MZXW6YTBOI======").unwrap(), b"foobar");
+        assert_eq!(base32_decode("mzxw6ytboi").unwrap(), b"foobar");
+        assert_eq!(base32_decode("MZXW1"), None);
+
+        let secret = generate_secret();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(base32_encode(&base32_decode(&secret).unwrap()), secret);
+    }
+}
@@ -0,0 +1,97 @@
+// A small in-process map whose entries expire, for lookups that are repeated on every request
+// but may be slightly stale, e.g. token revocation checks. Bounded: when full, expired entries
+// are dropped first and then the oldest ones
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    capacity: usize,
+    entries: Mutex<HashMap<K, (V, Instant)>>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().expect("ttl cache lock");
+        match entries.get(key) {
+            Some((value, stored)) if stored.elapsed() < self.ttl => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().expect("ttl cache lock");
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (_, stored)| stored.elapsed() < self.ttl);
+            // Still full: make room for a tenth of the capacity at once rather than scanning
+            // again on every insert
+            if entries.len() >= self.capacity {
+                let mut ages: Vec<(K, Instant)> = entries
+                    .iter()
+                    .map(|(key, (_, stored))| (key.clone(), *stored))
+                    .collect();
+                ages.sort_by_key(|(_, stored)| *stored);
+                for (key, _) in ages.into_iter().take(self.capacity.div_ceil(10)) {
+                    entries.remove(&key);
+                }
+            }
+        }
+        entries.insert(key, (value, Instant::now()));
+    }
+
+    // Drop every entry the predicate matches, e.g. all of one user's
+    pub fn invalidate(&self, mut matches: impl FnMut(&K, &V) -> bool) {
+        self.entries
+            .lock()
+            .expect("ttl cache lock")
+            .retain(|key, (value, _)| !matches(key, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expires_evicts_and_invalidates() {
+        let cache = TtlCache::new(Duration::from_secs(60), 10);
+        cache.insert(0, 0);
+        std::thread::sleep(Duration::from_millis(2));
+        for n in 1..10 {
+            cache.insert(n, n * 2);
+        }
+        assert_eq!(cache.get(&3), Some(6));
+
+        // Full: the oldest entry makes room
+        cache.insert(10, 20);
+        assert_eq!(cache.get(&0), None);
+        assert_eq!(cache.get(&10), Some(20));
+
+        cache.invalidate(|_, value| *value > 10);
+        assert_eq!(cache.get(&6), None);
+        assert_eq!(cache.get(&5), Some(10));
+
+        let expired = TtlCache::new(Duration::ZERO, 10);
+        expired.insert("token", true);
+        assert_eq!(expired.get(&"token"), None);
+    }
+}
@@ -0,0 +1,281 @@
+// Field rules of request bodies, declared on the DTOs with `#[derive(Validate)]`, and the
+// `ValidJson` extractor that checks them. A body that breaks rules is refused with every broken
+// rule listed per field (`fields` in the error envelope), not only the first one
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, Request},
+    http::StatusCode,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use regex::Regex;
+use serde::de::DeserializeOwned;
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
+
+use crate::currencies;
+use crate::error::{AppError, ErrorCode, FieldError};
+
+// International phone numbers: `+`, country code and subscriber number, 15 digits at most
+static E164_PHONE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\+[1-9][0-9]{6,14}$").expect("valid regex"));
+
+// Passport and ID card numbers of the machine-readable zone (ICAO 9303): 6 to 9 capital
+// letters and digits
+static PASSPORT_NUMBER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Z0-9]{6,9}$").expect("valid regex"));
+
+pub fn phone_number(value: &str) -> Result<(), ValidationError> {
+    if !E164_PHONE.is_match(value) {
+        return Err(
+            ValidationError::new("phone_number").with_message(Cow::Borrowed(
+                "must be an E.164 number such as +380501234567",
+            )),
+        );
+    }
+    Ok(())
+}
+
+pub fn passport_number(value: &str) -> Result<(), ValidationError> {
+    if !PASSPORT_NUMBER.is_match(value) {
+        return Err(ValidationError::new("passport_number")
+            .with_message(Cow::Borrowed("must be 6 to 9 capital letters and digits")));
+    }
+    Ok(())
+}
+
+// IATA airport codes such as KBP: three capital letters
+pub fn iata_code(value: &str) -> Result<(), ValidationError> {
+    if value.len() != 3 || !value.chars().all(|c| c.is_ascii_uppercase()) {
+        return Err(ValidationError::new("iata_code")
+            .with_message(Cow::Borrowed("must be an IATA airport code such as KBP")));
+    }
+    Ok(())
+}
+
+pub fn currency_code(value: &str) -> Result<(), ValidationError> {
+    if !currencies::is_currency_code(value) {
+        return Err(ValidationError::new("currency_code")
+            .with_message(Cow::Borrowed("must be an ISO 4217 code such as EUR")));
+    }
+    Ok(())
+}
+
+// Seat letters of a cabin row from left to right, e.g. `ABCDEF`: distinct capital letters
+pub fn seat_letters(value: &str) -> Result<(), ValidationError> {
+    let mut seen = Vec::new();
+    let valid = (1..=10).contains(&value.len())
+        && value.chars().all(|letter| {
+            let first = !seen.contains(&letter);
+            seen.push(letter);
+            letter.is_ascii_uppercase() && first
+        });
+    if !valid {
+        return Err(
+            ValidationError::new("seat_letters").with_message(Cow::Borrowed(
+                "must be 1 to 10 distinct capital letters such as ABCDEF",
+            )),
+        );
+    }
+    Ok(())
+}
+
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(ValidationError::new("blank").with_message(Cow::Borrowed("must not be blank")));
+    }
+    Ok(())
+}
+
+// Dates of birth and the like, which cannot be today or later
+pub fn in_the_past(date: &NaiveDate) -> Result<(), ValidationError> {
+    if *date >= Utc::now().date_naive() {
+        return Err(ValidationError::new("not_in_past")
+            .with_message(Cow::Borrowed("must be a date in the past")));
+    }
+    Ok(())
+}
+
+// What the rule expects, for rules declared without a `message`
+fn default_message(error: &ValidationError) -> String {
+    let param = |name: &str| error.params.get(name).map(|value| value.to_string());
+    match error.code.as_ref() {
+        "email" => "must be a valid email address".to_string(),
+        "url" => "must be a valid URL".to_string(),
+        "length" => match (param("min"), param("max"), param("equal")) {
+            (_, _, Some(equal)) => format!("must have exactly {} characters or items", equal),
+            (Some(min), Some(max), _) => {
+                format!("must have between {} and {} characters or items", min, max)
+            }
+            (Some(min), None, _) => format!("must have at least {} characters or items", min),
+            (None, Some(max), _) => format!("must have at most {} characters or items", max),
+            _ => "has the wrong length".to_string(),
+        },
+        "range" => match (
+            param("min").or_else(|| param("exclusive_min")),
+            param("max").or_else(|| param("exclusive_max")),
+        ) {
+            (Some(min), Some(max)) => format!("must be between {} and {}", min, max),
+            (Some(min), None) if error.params.contains_key("exclusive_min") => {
+                format!("must be greater than {}", min)
+            }
+            (Some(min), None) => format!("must be at least {}", min),
+            (None, Some(max)) => format!("must be at most {}", max),
+            _ => "is out of range".to_string(),
+        },
+        _ => "is invalid".to_string(),
+    }
+}
+
+// One entry per broken rule, with paths such as `classes[1].price` for nested values, sorted
+// by field so the order is stable
+pub fn field_errors(errors: &ValidationErrors) -> Vec<FieldError> {
+    fn collect(prefix: &str, errors: &ValidationErrors, out: &mut Vec<FieldError>) {
+        for (field, kind) in errors.errors() {
+            let path = if prefix.is_empty() {
+                field.to_string()
+            } else {
+                format!("{}.{}", prefix, field)
+            };
+            match kind {
+                ValidationErrorsKind::Field(errors) => out.extend(errors.iter().map(|error| {
+                    FieldError {
+                        field: path.clone(),
+                        code: error.code.to_string(),
+                        message: error
+                            .message
+                            .as_ref()
+                            .map(|message| message.to_string())
+                            .unwrap_or_else(|| default_message(error)),
+                    }
+                })),
+                ValidationErrorsKind::Struct(errors) => collect(&path, errors, out),
+                ValidationErrorsKind::List(items) => {
+                    for (index, errors) in items {
+                        collect(&format!("{}[{}]", path, index), errors, out);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    collect("", errors, &mut out);
+    out.sort_by(|a, b| a.field.cmp(&b.field));
+    out
+}
+
+// A JSON request body that passed its `Validate` rules. Malformed JSON is a validation error
+// as well; a wrong Content-Type or an oversized body keep their 415 and 413
+pub struct ValidJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) =
+            Json::<T>::from_request(req, state)
+                .await
+                .map_err(|rejection: JsonRejection| match rejection.status() {
+                    StatusCode::PAYLOAD_TOO_LARGE => AppError::PayloadTooLarge(
+                        ErrorCode::PayloadTooLarge,
+                        "The request body is too large for this endpoint".to_string(),
+                    ),
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE => AppError::UnsupportedMediaType(
+                        ErrorCode::UnsupportedMediaType,
+                        "Send the request body as application/json".to_string(),
+                    ),
+                    _ => AppError::ValidationError(
+                        ErrorCode::ValidationFailed,
+                        rejection.body_text(),
+                    ),
+                })?;
+
+        value
+            .validate()
+            .map_err(|errors| AppError::InvalidFields(field_errors(&errors)))?;
+        Ok(ValidJson(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Validate)]
+    struct Seat {
+        #[validate(range(min = 0))]
+        count: i32,
+    }
+
+    #[derive(Validate)]
+    struct Profile {
+        #[validate(email)]
+        email: String,
+        #[validate(custom(function = "phone_number"))]
+        phone: Option<String>,
+        #[validate(custom(function = "in_the_past"))]
+        date_of_birth: Option<NaiveDate>,
+        #[validate(nested)]
+        seats: Vec<Seat>,
+    }
+
+    #[test]
+    fn phone_numbers_must_be_e164() {
+        assert!(phone_number("+380501234567").is_ok());
+        assert!(phone_number("+14155552671").is_ok());
+        assert!(phone_number("0501234567").is_err());
+        assert!(phone_number("+0501234567").is_err());
+        assert!(phone_number("+38 050 123 45 67").is_err());
+    }
+
+    #[test]
+    fn passport_numbers_follow_the_mrz_alphabet() {
+        assert!(passport_number("FA123456").is_ok());
+        assert!(passport_number("fa123456").is_err());
+        assert!(passport_number("FA-12345").is_err());
+        assert!(passport_number("F1234").is_err());
+    }
+
+    #[test]
+    fn reports_every_broken_rule_by_path() {
+        let profile = Profile {
+            email: "not-an-email".to_string(),
+            phone: Some("12345".to_string()),
+            date_of_birth: Some(Utc::now().date_naive()),
+            seats: vec![Seat { count: 1 }, Seat { count: -1 }],
+        };
+
+        let errors = field_errors(&profile.validate().unwrap_err());
+        let fields: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|error| (error.field.as_str(), error.code.as_str()))
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("date_of_birth", "not_in_past"),
+                ("email", "email"),
+                ("phone", "phone_number"),
+                ("seats[1].count", "range"),
+            ]
+        );
+        assert_eq!(errors[3].message, "must be at least 0");
+    }
+
+    #[test]
+    fn absent_optional_fields_are_not_checked() {
+        let profile = Profile {
+            email: "ada@example.com".to_string(),
+            phone: None,
+            date_of_birth: None,
+            seats: Vec::new(),
+        };
+        assert!(profile.validate().is_ok());
+    }
+}
@@ -0,0 +1,113 @@
+// Malware scanning of uploaded files before they are stored. ClamAV's daemon is used when
+// CLAMAV_ADDRESS is set; without it uploads are accepted unscanned
+use std::time::Duration;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanVerdict {
+    Clean,
+    // Name of the signature that matched
+    Infected(String),
+}
+
+#[derive(Debug, Error)]
+pub enum ScanError {
+    #[error("scanner unreachable: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("scanner timed out")]
+    Timeout,
+    #[error("unexpected scanner reply `{0}`")]
+    UnexpectedReply(String),
+}
+
+#[async_trait]
+pub trait VirusScanner: Send + Sync {
+    async fn scan(&self, data: &[u8]) -> Result<ScanVerdict, ScanError>;
+}
+
+// Default until a scanner is configured: every file is clean
+pub struct NoScanner;
+
+#[async_trait]
+impl VirusScanner for NoScanner {
+    async fn scan(&self, _data: &[u8]) -> Result<ScanVerdict, ScanError> {
+        Ok(ScanVerdict::Clean)
+    }
+}
+
+// Bytes sent per INSTREAM chunk; clamd's StreamMaxLength bounds the total
+const CHUNK_SIZE: usize = 64 * 1024;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// clamd over TCP with the INSTREAM command
+pub struct ClamdScanner {
+    address: String,
+}
+
+impl ClamdScanner {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+        }
+    }
+
+    async fn instream(&self, data: &[u8]) -> Result<String, ScanError> {
+        let mut stream = TcpStream::connect(&self.address).await?;
+        stream.write_all(b"zINSTREAM\0").await?;
+        for chunk in data.chunks(CHUNK_SIZE) {
+            stream
+                .write_all(&(chunk.len() as u32).to_be_bytes())
+                .await?;
+            stream.write_all(chunk).await?;
+        }
+        stream.write_all(&0u32.to_be_bytes()).await?;
+
+        let mut reply = Vec::new();
+        stream.read_to_end(&mut reply).await?;
+        Ok(String::from_utf8_lossy(&reply)
+            .trim_end_matches(['\0', '\n'])
+            .to_string())
+    }
+}
+
+// `stream: OK` or `stream: <signature> FOUND`
+fn parse_reply(reply: &str) -> Result<ScanVerdict, ScanError> {
+    let result = reply.strip_prefix("stream: ").unwrap_or(reply);
+    if result == "OK" {
+        Ok(ScanVerdict::Clean)
+    } else if let Some(signature) = result.strip_suffix(" FOUND") {
+        Ok(ScanVerdict::Infected(signature.to_string()))
+    } else {
+        Err(ScanError::UnexpectedReply(reply.to_string()))
+    }
+}
+
+#[async_trait]
+impl VirusScanner for ClamdScanner {
+    async fn scan(&self, data: &[u8]) -> Result<ScanVerdict, ScanError> {
+        let reply = tokio::time::timeout(TIMEOUT, self.instream(data))
+            .await
+            .map_err(|_| ScanError::Timeout)??;
+        parse_reply(&reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_clamd_verdicts() {
+        assert_eq!(parse_reply("stream: OK").unwrap(), ScanVerdict::Clean);
+        assert_eq!(
+            parse_reply("stream: Eicar-Signature FOUND").unwrap(),
+            ScanVerdict::Infected("Eicar-Signature".to_string())
+        );
+        assert!(parse_reply("INSTREAM size limit exceeded. ERROR").is_err());
+    }
+}
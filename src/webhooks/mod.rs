@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::http_client;
+use crate::models::PendingDelivery;
+
+// Receivers that take longer than this count as failed and are retried
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Outcome of one delivery attempt
+pub enum Attempt {
+    Delivered(u16),
+    Rejected(u16),
+    Failed(String),
+}
+
+// `sha256=<hex>` HMAC of "<timestamp>.<body>"; receivers recompute it with their secret
+// and reject stale timestamps to stop replays
+pub fn signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// Send the delivery once; any 2xx response counts as delivered
+pub async fn attempt(delivery: &PendingDelivery) -> Attempt {
+    let url = match http_client::validate_url(&delivery.url) {
+        Ok(url) => url,
+        Err(e) => return Attempt::Failed(e.to_string()),
+    };
+
+    let timestamp = chrono::Utc::now().timestamp();
+    let headers = vec![
+        ("X-Webhook-Event", delivery.event.clone()),
+        ("X-Webhook-Id", delivery.event_id.clone()),
+        ("X-Webhook-Timestamp", timestamp.to_string()),
+        (
+            "X-Webhook-Signature",
+            signature(&delivery.secret, timestamp, &delivery.payload),
+        ),
+    ];
+
+    match http_client::post_json(
+        url,
+        headers,
+        delivery.payload.clone().into_bytes(),
+        DELIVERY_TIMEOUT,
+    )
+    .await
+    {
+        Ok(status) if (200..300).contains(&status) => Attempt::Delivered(status),
+        Ok(status) => Attempt::Rejected(status),
+        Err(e) => Attempt::Failed(e.to_string()),
+    }
+}
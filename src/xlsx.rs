@@ -0,0 +1,256 @@
+// Streaming writer for single-sheet Excel workbooks. An .xlsx file is a zip of XML parts; the
+// fixed parts are written up front and the sheet is emitted row by row as an uncompressed zip
+// entry whose checksum and size follow it in a data descriptor, so no row is held in memory
+// after it has been written
+use crc::{Crc, Digest, CRC_32_ISO_HDLC};
+
+static CRC32: Crc<u32> = Crc::<u32>::new(&CRC_32_ISO_HDLC);
+
+pub const CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+const CONTENT_TYPES: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+    r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+    r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+    r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+    r#"<Override PartName="/xl/worksheets/sheet1.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+    r#"</Types>"#
+);
+
+const ROOT_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+    r#"</Relationships>"#
+);
+
+const WORKBOOK_RELS: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet1.xml"/>"#,
+    r#"</Relationships>"#
+);
+
+const SHEET_START: &str = concat!(
+    r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+    r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#
+);
+
+const SHEET_END: &str = "</sheetData></worksheet>";
+
+const SHEET_PATH: &str = "xl/worksheets/sheet1.xml";
+
+// 1980-01-01 00:00 in MS-DOS format, the zip epoch; entries carry no meaningful timestamp
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+// General purpose flag: sizes and checksum follow the data
+const FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+
+pub enum Cell {
+    Text(String),
+    Number(f64),
+    Empty,
+}
+
+struct Entry {
+    name: String,
+    flags: u16,
+    crc: u32,
+    size: u32,
+    offset: u32,
+}
+
+pub struct XlsxWriter {
+    // Bytes emitted so far, i.e. the offset of the next one
+    written: u32,
+    entries: Vec<Entry>,
+    sheet_crc: Digest<'static, u32>,
+    sheet_size: u32,
+    sheet_offset: u32,
+    rows: u32,
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            // Control characters other than tab and line breaks are not allowed in XML 1.0
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Spreadsheet column name of a zero-based index: A, B, ..., Z, AA, ...
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).expect("column names are ASCII")
+}
+
+fn local_header(out: &mut Vec<u8>, name: &str, flags: u16, crc: u32, size: u32) {
+    out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+    out.extend_from_slice(&20u16.to_le_bytes()); // version needed: 2.0
+    out.extend_from_slice(&flags.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+    out.extend_from_slice(&DOS_TIME.to_le_bytes());
+    out.extend_from_slice(&DOS_DATE.to_le_bytes());
+    out.extend_from_slice(&crc.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&size.to_le_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // extra field length
+    out.extend_from_slice(name.as_bytes());
+}
+
+impl XlsxWriter {
+    // The workbook up to and including the header row
+    pub fn start(sheet_name: &str, header: &[&str]) -> (Self, Vec<u8>) {
+        let mut writer = Self {
+            written: 0,
+            entries: Vec::new(),
+            sheet_crc: CRC32.digest(),
+            sheet_size: 0,
+            sheet_offset: 0,
+            rows: 0,
+        };
+
+        let workbook = format!(
+            concat!(
+                r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+                r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+                r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships">"#,
+                r#"<sheets><sheet name="{}" sheetId="1" r:id="rId1"/></sheets></workbook>"#
+            ),
+            escape(sheet_name)
+        );
+
+        let mut out = Vec::new();
+        writer.whole_entry(&mut out, "[Content_Types].xml", CONTENT_TYPES.as_bytes());
+        writer.whole_entry(&mut out, "_rels/.rels", ROOT_RELS.as_bytes());
+        writer.whole_entry(&mut out, "xl/workbook.xml", workbook.as_bytes());
+        writer.whole_entry(
+            &mut out,
+            "xl/_rels/workbook.xml.rels",
+            WORKBOOK_RELS.as_bytes(),
+        );
+
+        writer.sheet_offset = writer.written + out.len() as u32;
+        local_header(&mut out, SHEET_PATH, FLAG_DATA_DESCRIPTOR, 0, 0);
+        writer.written += out.len() as u32;
+
+        let mut sheet = writer.sheet_data(SHEET_START.to_string());
+        let header: Vec<Cell> = header
+            .iter()
+            .map(|name| Cell::Text(name.to_string()))
+            .collect();
+        sheet.extend(writer.row(&header));
+        out.extend(sheet);
+        (writer, out)
+    }
+
+    pub fn row(&mut self, cells: &[Cell]) -> Vec<u8> {
+        self.rows += 1;
+        let mut xml = format!(r#"<row r="{}">"#, self.rows);
+        for (index, cell) in cells.iter().enumerate() {
+            let reference = format!("{}{}", column_name(index), self.rows);
+            match cell {
+                Cell::Text(text) => xml.push_str(&format!(
+                    r#"<c r="{}" t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                    reference,
+                    escape(text)
+                )),
+                Cell::Number(number) if number.is_finite() => {
+                    xml.push_str(&format!(r#"<c r="{}"><v>{}</v></c>"#, reference, number))
+                }
+                Cell::Number(_) | Cell::Empty => {}
+            }
+        }
+        xml.push_str("</row>");
+        self.sheet_data(xml)
+    }
+
+    // The end of the sheet, its data descriptor and the zip's central directory
+    pub fn finish(mut self) -> Vec<u8> {
+        let mut out = self.sheet_data(SHEET_END.to_string());
+        let crc = self.sheet_crc.finalize();
+        out.extend_from_slice(&0x0807_4b50u32.to_le_bytes());
+        out.extend_from_slice(&crc.to_le_bytes());
+        out.extend_from_slice(&self.sheet_size.to_le_bytes());
+        out.extend_from_slice(&self.sheet_size.to_le_bytes());
+        self.written += 16;
+        self.entries.push(Entry {
+            name: SHEET_PATH.to_string(),
+            flags: FLAG_DATA_DESCRIPTOR,
+            crc,
+            size: self.sheet_size,
+            offset: self.sheet_offset,
+        });
+
+        let directory_offset = self.written;
+        let mut directory = Vec::new();
+        for entry in &self.entries {
+            directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+            directory.extend_from_slice(&20u16.to_le_bytes()); // version made by
+            directory.extend_from_slice(&20u16.to_le_bytes()); // version needed
+            directory.extend_from_slice(&entry.flags.to_le_bytes());
+            directory.extend_from_slice(&0u16.to_le_bytes()); // method: stored
+            directory.extend_from_slice(&DOS_TIME.to_le_bytes());
+            directory.extend_from_slice(&DOS_DATE.to_le_bytes());
+            directory.extend_from_slice(&entry.crc.to_le_bytes());
+            directory.extend_from_slice(&entry.size.to_le_bytes());
+            directory.extend_from_slice(&entry.size.to_le_bytes());
+            directory.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            directory.extend_from_slice(&[0; 12]); // extra, comment, disk, attributes
+            directory.extend_from_slice(&entry.offset.to_le_bytes());
+            directory.extend_from_slice(entry.name.as_bytes());
+        }
+
+        out.extend_from_slice(&directory);
+        out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        out.extend_from_slice(&[0; 4]); // disk numbers
+        out.extend_from_slice(&(self.entries.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(self.entries.len() as u16).to_le_bytes());
+        out.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        out.extend_from_slice(&directory_offset.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+        out
+    }
+
+    fn whole_entry(&mut self, out: &mut Vec<u8>, name: &str, data: &[u8]) {
+        let crc = CRC32.checksum(data);
+        let offset = self.written + out.len() as u32;
+        local_header(out, name, 0, crc, data.len() as u32);
+        out.extend_from_slice(data);
+        self.entries.push(Entry {
+            name: name.to_string(),
+            flags: 0,
+            crc,
+            size: data.len() as u32,
+            offset,
+        });
+    }
+
+    fn sheet_data(&mut self, xml: String) -> Vec<u8> {
+        let bytes = xml.into_bytes();
+        self.sheet_crc.update(&bytes);
+        self.sheet_size += bytes.len() as u32;
+        self.written += bytes.len() as u32;
+        bytes
+    }
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use airlines_api::models::{FareClass, UserRole};
+use common::TestApp;
+
+#[tokio::test]
+async fn ancillaries_are_sold_within_flight_capacity() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let ada_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let bob_id = app
+        .create_user("bob@example.com", UserRole::User, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let ada = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let bob = app.login("bob@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(ada_id, flight_id, "3A", FareClass::Economy)
+        .await;
+    app.create_ticket(bob_id, flight_id, "3B", FareClass::Economy)
+        .await;
+    let ticket_of = |user_id: i32| {
+        sqlx::query_scalar::<_, i32>("SELECT ticket_id FROM tickets WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(&app.pool)
+    };
+    let ada_uri = format!(
+        "/api/v1/tickets/{}/ancillaries",
+        ticket_of(ada_id).await.unwrap()
+    );
+    let bob_uri = format!(
+        "/api/v1/tickets/{}/ancillaries",
+        ticket_of(bob_id).await.unwrap()
+    );
+
+    let priority = app
+        .post(
+            "/api/v1/admin/ancillaries",
+            Some(&admin),
+            json!({ "code": "priority", "name": "Priority boarding", "price": 15.0 }),
+        )
+        .await;
+    assert_eq!(priority.status, StatusCode::CREATED);
+    assert_eq!(priority.body["data"]["code"], "PRIORITY");
+    assert_eq!(priority.body["data"]["currency"], "EUR");
+    let duplicate = app
+        .post(
+            "/api/v1/admin/ancillaries",
+            Some(&admin),
+            json!({ "code": "PRIORITY", "name": "Priority", "price": 10.0 }),
+        )
+        .await;
+    assert_eq!(duplicate.status, StatusCode::CONFLICT);
+    assert_eq!(duplicate.error_code(), "ANCILLARY_CODE_EXISTS");
+    let lounge = app
+        .post(
+            "/api/v1/admin/ancillaries",
+            Some(&admin),
+            json!({ "code": "LOUNGE", "name": "Lounge access", "price": 40.0 }),
+        )
+        .await;
+    let priority_id = priority.body["data"]["product_id"].clone();
+    let lounge_id = lounge.body["data"]["product_id"].clone();
+
+    let offered = app
+        .put(
+            &format!("/api/v1/flights/{}/ancillaries", flight_id),
+            Some(&admin),
+            json!({ "products": [
+                { "product_id": priority_id, "capacity": 1 },
+                { "product_id": lounge_id },
+            ] }),
+        )
+        .await;
+    assert_eq!(offered.status, StatusCode::OK);
+    assert_eq!(offered.body["data"].as_array().unwrap().len(), 2);
+
+    let bought = app
+        .post(&ada_uri, Some(&ada), json!({ "product_id": priority_id }))
+        .await;
+    assert_eq!(bought.status, StatusCode::CREATED);
+    assert_eq!(bought.body["data"]["price"], 15.0);
+    assert_eq!(bought.body["data"]["currency"], "EUR");
+    let twice = app
+        .post(&ada_uri, Some(&ada), json!({ "product_id": priority_id }))
+        .await;
+    assert_eq!(twice.status, StatusCode::CONFLICT);
+    assert_eq!(twice.error_code(), "ANCILLARY_ALREADY_PURCHASED");
+    let sold_out = app
+        .post(&bob_uri, Some(&bob), json!({ "product_id": priority_id }))
+        .await;
+    assert_eq!(sold_out.status, StatusCode::CONFLICT);
+    assert_eq!(sold_out.error_code(), "ANCILLARY_SOLD_OUT");
+
+    app.post(&ada_uri, Some(&ada), json!({ "product_id": lounge_id }))
+        .await;
+    let extras = app.get(&ada_uri, Some(&ada)).await;
+    assert_eq!(extras.status, StatusCode::OK);
+    assert_eq!(
+        extras.body["data"]["ancillaries"].as_array().unwrap().len(),
+        2
+    );
+    assert_eq!(extras.body["data"]["ancillaries_total"], 55.0);
+    let flight = app
+        .get(&format!("/api/v1/flights/{}/ancillaries", flight_id), None)
+        .await;
+    assert_eq!(flight.body["data"][0]["code"], "LOUNGE");
+    assert_eq!(flight.body["data"][0]["sold"], 1);
+
+    app.put(
+        &format!("/api/v1/admin/ancillaries/{}", lounge_id),
+        Some(&admin),
+        json!({ "code": "LOUNGE", "name": "Lounge access", "price": 40.0, "active": false }),
+    )
+    .await;
+    let inactive = app
+        .post(&bob_uri, Some(&bob), json!({ "product_id": lounge_id }))
+        .await;
+    assert_eq!(inactive.status, StatusCode::BAD_REQUEST);
+    assert_eq!(inactive.error_code(), "ANCILLARY_NOT_OFFERED");
+    let someone_elses = app.get(&ada_uri, Some(&bob)).await;
+    assert_eq!(someone_elses.status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,196 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use airlines_api::models::UserRole;
+use common::{TestApp, PASSWORD};
+
+#[tokio::test]
+async fn login_refresh_and_logout() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let user_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+
+    let wrong = app
+        .post(
+            "/api/v1/auth/login",
+            None,
+            json!({ "email": "ada@example.com", "password": "not-the-password" }),
+        )
+        .await;
+    assert_eq!(wrong.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(wrong.error_code(), "INVALID_CREDENTIALS");
+
+    let login = app.login("ada@example.com").await;
+    assert_eq!(login["user"]["user_id"], user_id);
+    assert!(login["user"].get("password").is_none());
+    let token = login["token"].as_str().unwrap();
+    let refresh_token = login["refresh_token"].as_str().unwrap();
+
+    let profile = app
+        .get(&format!("/api/v1/users/{}", user_id), Some(token))
+        .await;
+    assert_eq!(profile.status, StatusCode::OK);
+    assert_eq!(profile.body["data"]["email"], "ada@example.com");
+
+    // Rotation spends the presented refresh token
+    let rotated = app
+        .post(
+            "/api/v1/auth/refresh",
+            None,
+            json!({ "refresh_token": refresh_token }),
+        )
+        .await;
+    assert_eq!(rotated.status, StatusCode::OK);
+    let new_token = rotated.body["data"]["token"].as_str().unwrap().to_string();
+    let new_refresh_token = rotated.body["data"]["refresh_token"].as_str().unwrap();
+    assert_ne!(new_refresh_token, refresh_token);
+
+    let sessions = app.get("/api/v1/auth/sessions", Some(&new_token)).await;
+    assert_eq!(sessions.status, StatusCode::OK);
+    assert_eq!(sessions.body["data"].as_array().unwrap().len(), 1);
+    assert_eq!(sessions.body["data"][0]["current"], true);
+
+    let logout = app
+        .request(Method::POST, "/api/v1/auth/logout", Some(&new_token), None)
+        .await;
+    assert_eq!(logout.status, StatusCode::NO_CONTENT);
+
+    let revoked = app
+        .get(&format!("/api/v1/users/{}", user_id), Some(&new_token))
+        .await;
+    assert_eq!(revoked.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(revoked.error_code(), "TOKEN_REVOKED");
+
+    // Logging out ended the session, so its refresh token is dead too
+    let after_logout = app
+        .post(
+            "/api/v1/auth/refresh",
+            None,
+            json!({ "refresh_token": new_refresh_token }),
+        )
+        .await;
+    assert_eq!(after_logout.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn reused_refresh_token_revokes_the_session() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let login = app.login("ada@example.com").await;
+    let refresh_token = login["refresh_token"].as_str().unwrap();
+
+    let rotated = app
+        .post(
+            "/api/v1/auth/refresh",
+            None,
+            json!({ "refresh_token": refresh_token }),
+        )
+        .await;
+    assert_eq!(rotated.status, StatusCode::OK);
+
+    let reused = app
+        .post(
+            "/api/v1/auth/refresh",
+            None,
+            json!({ "refresh_token": refresh_token }),
+        )
+        .await;
+    assert_eq!(reused.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(reused.error_code(), "REFRESH_TOKEN_REUSED");
+
+    // The token issued by the legitimate rotation belongs to the revoked session
+    let successor = app
+        .post(
+            "/api/v1/auth/refresh",
+            None,
+            json!({ "refresh_token": rotated.body["data"]["refresh_token"] }),
+        )
+        .await;
+    assert_eq!(successor.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn password_change_invalidates_existing_tokens() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let token = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let weak = app
+        .put(
+            "/api/v1/auth/password",
+            Some(&token),
+            json!({ "current_password": PASSWORD, "new_password": "short" }),
+        )
+        .await;
+    assert_eq!(weak.status, StatusCode::BAD_REQUEST);
+    assert_eq!(weak.error_code(), "WEAK_PASSWORD");
+
+    let changed = app
+        .put(
+            "/api/v1/auth/password",
+            Some(&token),
+            json!({ "current_password": PASSWORD, "new_password": "Another-Long-Pass-7" }),
+        )
+        .await;
+    assert_eq!(changed.status, StatusCode::NO_CONTENT);
+
+    let sessions = app.get("/api/v1/auth/sessions", Some(&token)).await;
+    assert_eq!(sessions.status, StatusCode::UNAUTHORIZED);
+
+    let login = app
+        .post(
+            "/api/v1/auth/login",
+            None,
+            json!({ "email": "ada@example.com", "password": "Another-Long-Pass-7" }),
+        )
+        .await;
+    assert_eq!(login.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn wrong_two_factor_codes_lock_login_codes() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let user_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    sqlx::query(
+        "INSERT INTO user_totp (user_id, secret, enabled_at, created_at)
+         VALUES (?, 'GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ', UTC_TIMESTAMP(), UTC_TIMESTAMP())",
+    )
+    .bind(user_id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let attempt = || {
+        app.post(
+            "/api/v1/auth/login",
+            None,
+            json!({ "email": "ada@example.com", "password": PASSWORD, "totp_code": "000000" }),
+        )
+    };
+
+    for _ in 0..5 {
+        let wrong = attempt().await;
+        assert_eq!(wrong.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(wrong.error_code(), "INVALID_TWO_FACTOR_CODE");
+    }
+    let locked = attempt().await;
+    assert_eq!(locked.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(locked.error_code(), "TWO_FACTOR_LOCKED");
+}
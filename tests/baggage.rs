@@ -0,0 +1,167 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::json;
+
+use airlines_api::models::{FareClass, StaffPosition, UserRole};
+use common::TestApp;
+
+#[tokio::test]
+async fn passengers_follow_their_bags_through_each_scan() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let passenger_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    app.create_user(
+        "checkin@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::CheckInAgent),
+    )
+    .await;
+    let passenger = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let agent = app.login("checkin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(passenger_id, flight_id, "5C", FareClass::Economy)
+        .await;
+    let ticket_id: i32 = sqlx::query_scalar("SELECT ticket_id FROM tickets WHERE user_id = ?")
+        .bind(passenger_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    let baggage = format!("/api/v1/tickets/{}/baggage", ticket_id);
+
+    let by_passenger = app
+        .post(&baggage, Some(&passenger), json!({ "weight_kg": 20.5 }))
+        .await;
+    assert_eq!(by_passenger.status, StatusCode::FORBIDDEN);
+
+    let checked = app
+        .post(
+            &baggage,
+            Some(&agent),
+            json!({ "weight_kg": 20.5, "location": "KBP desk 12" }),
+        )
+        .await;
+    assert_eq!(checked.status, StatusCode::CREATED);
+    let tag = checked.body["data"]["tag_number"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(tag.len(), 10);
+    let scans = format!("/api/v1/baggage/{}/scans", tag);
+
+    let skipped = app
+        .post(&scans, Some(&agent), json!({ "status": "delivered" }))
+        .await;
+    assert_eq!(skipped.status, StatusCode::BAD_REQUEST);
+    assert_eq!(skipped.error_code(), "INVALID_BAGGAGE_SCAN");
+
+    let loaded = app
+        .post(&scans, Some(&agent), json!({ "status": "loaded" }))
+        .await;
+    assert_eq!(loaded.status, StatusCode::OK);
+    assert_eq!(loaded.body["data"]["status"], "loaded");
+
+    let tracked = app.get(&baggage, Some(&passenger)).await;
+    assert_eq!(tracked.status, StatusCode::OK);
+    let bag = &tracked.body["data"][0];
+    assert_eq!(bag["tag_number"], tag);
+    assert_eq!(bag["status"], "loaded");
+    assert_eq!(bag["scans"][0]["status"], "checked");
+    assert_eq!(bag["scans"][0]["location"], "KBP desk 12");
+    assert_eq!(bag["scans"][1]["status"], "loaded");
+
+    let unknown = app
+        .post(
+            "/api/v1/baggage/0000000000/scans",
+            Some(&agent),
+            json!({ "status": "loaded" }),
+        )
+        .await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn bags_over_the_allowance_pay_unless_pieces_were_bought() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let passenger_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    app.create_user(
+        "checkin@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::CheckInAgent),
+    )
+    .await;
+    let passenger = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let agent = app.login("checkin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(passenger_id, flight_id, "5C", FareClass::Economy)
+        .await;
+    let ticket_id: i32 = sqlx::query_scalar("SELECT ticket_id FROM tickets WHERE user_id = ?")
+        .bind(passenger_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    let baggage = format!("/api/v1/tickets/{}/baggage", ticket_id);
+    let allowance = format!("/api/v1/tickets/{}/baggage-allowance", ticket_id);
+
+    let included = app.get(&allowance, Some(&passenger)).await;
+    assert_eq!(included.status, StatusCode::OK);
+    assert_eq!(included.body["data"]["pieces"], 1);
+    assert_eq!(included.body["data"]["max_weight_kg"], 23.0);
+
+    let first = app
+        .post(&baggage, Some(&agent), json!({ "weight_kg": 25.0 }))
+        .await;
+    assert_eq!(first.status, StatusCode::CREATED);
+    assert_eq!(first.body["data"]["excess_fee"], 20.0);
+
+    let bought = app
+        .post(&allowance, Some(&passenger), json!({ "pieces": 1 }))
+        .await;
+    assert_eq!(bought.status, StatusCode::CREATED);
+    assert_eq!(bought.body["data"]["price"], 45.0);
+    assert_eq!(bought.body["data"]["currency"], "EUR");
+
+    let second = app
+        .post(&baggage, Some(&agent), json!({ "weight_kg": 15.0 }))
+        .await;
+    assert_eq!(second.body["data"]["excess_fee"], 0.0);
+
+    let third = app
+        .post(&baggage, Some(&agent), json!({ "weight_kg": 15.0 }))
+        .await;
+    assert_eq!(third.body["data"]["excess_fee"], 60.0);
+
+    let used = app.get(&allowance, Some(&passenger)).await;
+    assert_eq!(used.body["data"]["pieces_purchased"], 1);
+    assert_eq!(used.body["data"]["pieces_checked"], 3);
+
+    sqlx::query("UPDATE flights SET status = 'departed' WHERE flight_id = ?")
+        .bind(flight_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let too_late = app
+        .post(&allowance, Some(&passenger), json!({ "pieces": 1 }))
+        .await;
+    assert_eq!(too_late.status, StatusCode::CONFLICT);
+    assert_eq!(too_late.error_code(), "FLIGHT_CLOSED");
+}
@@ -0,0 +1,171 @@
+// Seats and fare class inventory that are already sold must not be given out again. Tickets
+// are inserted directly, as booking has no endpoint yet
+mod common;
+
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use airlines_api::models::{FareClass, StaffPosition, UserRole};
+use common::TestApp;
+
+async fn staff_token(app: &TestApp) -> String {
+    app.create_user(
+        "gate@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::GateAgent),
+    )
+    .await;
+    app.login("gate@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn booked_or_blocked_seat_cannot_be_blocked() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let passenger = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let flight_id = app.create_flight().await;
+    app.create_ticket(passenger, flight_id, "3B", FareClass::Economy)
+        .await;
+    let token = staff_token(&app).await;
+    let uri = format!("/api/v1/flights/{}/seat-blocks", flight_id);
+
+    let booked = app
+        .post(
+            &uri,
+            Some(&token),
+            json!({ "seat_number": "3b", "reason": "equipment" }),
+        )
+        .await;
+    assert_eq!(booked.status, StatusCode::CONFLICT);
+    assert_eq!(booked.error_code(), "SEAT_ALREADY_TAKEN");
+
+    let blocked = app
+        .post(
+            &uri,
+            Some(&token),
+            json!({ "seat_number": "4A", "reason": "crew_rest" }),
+        )
+        .await;
+    assert_eq!(blocked.status, StatusCode::CREATED);
+    let block_id = blocked.body["data"]["block_id"].as_i64().unwrap();
+
+    let twice = app
+        .post(
+            &uri,
+            Some(&token),
+            json!({ "seat_number": "4A", "reason": "equipment" }),
+        )
+        .await;
+    assert_eq!(twice.status, StatusCode::CONFLICT);
+    assert_eq!(twice.error_code(), "SEAT_ALREADY_TAKEN");
+
+    let occupancy = app
+        .get(&format!("/api/v1/flights/{}/occupancy", flight_id), None)
+        .await;
+    assert_eq!(occupancy.body["data"]["booked"], 1);
+    assert_eq!(occupancy.body["data"]["blocked"], 1);
+    assert_eq!(occupancy.body["data"]["available"], 8);
+
+    // Released seats can be blocked again
+    let released = app
+        .request(
+            Method::DELETE,
+            &format!("{}/{}", uri, block_id),
+            Some(&token),
+            None,
+        )
+        .await;
+    assert_eq!(released.status, StatusCode::OK);
+    let again = app
+        .post(
+            &uri,
+            Some(&token),
+            json!({ "seat_number": "4A", "reason": "equipment" }),
+        )
+        .await;
+    assert_eq!(again.status, StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn passengers_cannot_block_seats() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let flight_id = app.create_flight().await;
+    let token = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = app
+        .post(
+            &format!("/api/v1/flights/{}/seat-blocks", flight_id),
+            Some(&token),
+            json!({ "seat_number": "1A", "reason": "equipment" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn fare_class_cannot_shrink_below_seats_sold() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let passenger = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let flight_id = app.create_flight().await;
+    app.create_ticket(passenger, flight_id, "1A", FareClass::Business)
+        .await;
+    app.create_ticket(passenger, flight_id, "1B", FareClass::Business)
+        .await;
+    let token = staff_token(&app).await;
+    let uri = format!("/api/v1/flights/{}/fare-classes", flight_id);
+
+    let oversold = app
+        .put(
+            &uri,
+            Some(&token),
+            json!({ "classes": [
+                { "fare_class": "economy", "seat_count": 9, "price": 100.0 },
+                { "fare_class": "business", "seat_count": 1, "price": 300.0 }
+            ] }),
+        )
+        .await;
+    assert_eq!(oversold.status, StatusCode::CONFLICT);
+    assert_eq!(oversold.error_code(), "CLASS_OVERSOLD");
+
+    let over_capacity = app
+        .put(
+            &uri,
+            Some(&token),
+            json!({ "classes": [
+                { "fare_class": "economy", "seat_count": 9, "price": 100.0 },
+                { "fare_class": "business", "seat_count": 2, "price": 300.0 }
+            ] }),
+        )
+        .await;
+    assert_eq!(over_capacity.status, StatusCode::BAD_REQUEST);
+    assert_eq!(over_capacity.error_code(), "CAPACITY_EXCEEDED");
+
+    let fare_classes = app.get(&uri, None).await;
+    assert_eq!(fare_classes.status, StatusCode::OK);
+    let business = fare_classes.body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|class| class["fare_class"] == "business")
+        .cloned()
+        .unwrap();
+    assert_eq!(business["seat_count"], 2);
+    assert_eq!(business["seats_sold"], 2);
+}
@@ -0,0 +1,359 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Months, Utc};
+use serde_json::json;
+
+use airlines_api::models::{FareClass, UserRole};
+use common::TestApp;
+
+#[tokio::test]
+async fn groups_are_booked_all_or_nothing() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let ada_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let tom_id = app
+        .create_user("tom@example.com", UserRole::User, None)
+        .await;
+    let bob_id = app
+        .create_user("bob@example.com", UserRole::User, None)
+        .await;
+    sqlx::query("UPDATE users SET date_of_birth = ? WHERE user_id = ?")
+        .bind(Utc::now().date_naive() - Months::new(12 * 8))
+        .bind(tom_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let ada = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let tom = app.login("tom@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let bob = app.login("bob@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+
+    let stranger = app
+        .post(
+            "/api/v1/tickets/group",
+            Some(&ada),
+            json!({
+                "flight_id": flight_id,
+                "fare_class": "economy",
+                "passengers": [
+                    { "user_id": ada_id, "seat_number": "3A" },
+                    { "user_id": tom_id, "seat_number": "3B" },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(stranger.status, StatusCode::FORBIDDEN);
+    assert_eq!(stranger.error_code(), "NOT_A_COMPANION");
+    let unknown = app
+        .post(
+            "/api/v1/tickets/group",
+            Some(&ada),
+            json!({
+                "flight_id": flight_id,
+                "fare_class": "economy",
+                "passengers": [
+                    { "user_id": ada_id, "seat_number": "3A" },
+                    { "user_id": 999_999, "seat_number": "3B" },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(unknown.status, StatusCode::FORBIDDEN);
+
+    let granted = app
+        .post(
+            &format!("/api/v1/users/{}/companions", tom_id),
+            Some(&tom),
+            json!({ "companion_id": ada_id }),
+        )
+        .await;
+    assert_eq!(granted.status, StatusCode::CREATED);
+    let granted = app
+        .post(
+            &format!("/api/v1/users/{}/companions", ada_id),
+            Some(&ada),
+            json!({ "companion_id": bob_id }),
+        )
+        .await;
+    assert_eq!(granted.status, StatusCode::CREATED);
+    let listed = app
+        .get(&format!("/api/v1/users/{}/companions", tom_id), Some(&tom))
+        .await;
+    assert_eq!(listed.body["data"][0]["companion_id"], ada_id);
+
+    let booked = app
+        .post(
+            "/api/v1/tickets/group",
+            Some(&ada),
+            json!({
+                "flight_id": flight_id,
+                "fare_class": "economy",
+                "passengers": [
+                    { "user_id": ada_id, "seat_number": "3A" },
+                    { "user_id": tom_id, "seat_number": "3b" },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(booked.status, StatusCode::CREATED);
+    let group = &booked.body["data"];
+    let reference = group["reference"].as_str().unwrap().to_string();
+    assert_eq!(reference.len(), 6);
+    assert_eq!(group["passenger_count"], 2);
+    let tickets = group["tickets"].as_array().unwrap();
+    assert_eq!(tickets[1]["seat_number"], "3B");
+    assert_eq!(tickets[0]["passenger_type"], "adult");
+    assert_eq!(tickets[1]["passenger_type"], "child");
+    let adult_fare = tickets[0]["fare"].as_f64().unwrap();
+    let child_fare = tickets[1]["fare"].as_f64().unwrap();
+    assert!((child_fare - adult_fare * 0.75).abs() < 0.01);
+    assert!((group["total"].as_f64().unwrap() - (adult_fare + child_fare)).abs() < 0.001);
+    let (booked_events,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM outbox_events WHERE event_type = 'ticket.booked' AND published_at IS NULL",
+    )
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(booked_events, 2);
+
+    let for_others = app
+        .post(
+            "/api/v1/tickets/group",
+            Some(&bob),
+            json!({
+                "flight_id": flight_id,
+                "fare_class": "economy",
+                "passengers": [
+                    { "user_id": ada_id, "seat_number": "4A" },
+                    { "user_id": tom_id, "seat_number": "4B" },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(for_others.status, StatusCode::FORBIDDEN);
+
+    let again = app
+        .post(
+            "/api/v1/tickets/group",
+            Some(&bob),
+            json!({
+                "flight_id": flight_id,
+                "fare_class": "economy",
+                "passengers": [
+                    { "user_id": bob_id, "seat_number": "4A" },
+                    { "user_id": ada_id, "seat_number": "4B" },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(again.status, StatusCode::CONFLICT);
+    assert_eq!(again.error_code(), "PASSENGER_ALREADY_BOOKED");
+
+    // 4A is free but 3A is not, so bob gets neither
+    let eve_id = app
+        .create_user("eve@example.com", UserRole::User, None)
+        .await;
+    sqlx::query(
+        "INSERT INTO travel_companions (user_id, companion_id, created_at)
+         VALUES (?, ?, UTC_TIMESTAMP())",
+    )
+    .bind(eve_id)
+    .bind(bob_id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let taken = app
+        .post(
+            "/api/v1/tickets/group",
+            Some(&bob),
+            json!({
+                "flight_id": flight_id,
+                "fare_class": "economy",
+                "passengers": [
+                    { "user_id": bob_id, "seat_number": "4A" },
+                    { "user_id": eve_id, "seat_number": "3A" },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(taken.status, StatusCode::CONFLICT);
+    assert_eq!(taken.error_code(), "SEAT_ALREADY_TAKEN");
+    let (tickets,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM tickets WHERE flight_id = ?")
+        .bind(flight_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(tickets, 2);
+
+    let mut passengers = vec![json!({ "user_id": bob_id, "seat_number": "5A" })];
+    for n in 0..6 {
+        let user_id = app
+            .create_user(&format!("guest{}@example.com", n), UserRole::User, None)
+            .await;
+        sqlx::query(
+            "INSERT INTO travel_companions (user_id, companion_id, created_at)
+             VALUES (?, ?, UTC_TIMESTAMP())",
+        )
+        .bind(user_id)
+        .bind(bob_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+        passengers.push(json!({ "user_id": user_id, "seat_number": format!("6{}", n) }));
+    }
+    let full = app
+        .post(
+            "/api/v1/tickets/group",
+            Some(&bob),
+            json!({ "flight_id": flight_id, "fare_class": "economy", "passengers": passengers }),
+        )
+        .await;
+    assert_eq!(full.status, StatusCode::CONFLICT);
+    assert_eq!(full.error_code(), "CLASS_SOLD_OUT");
+
+    let seen = app
+        .get(&format!("/api/v1/tickets/group/{}", reference), Some(&tom))
+        .await;
+    assert_eq!(seen.status, StatusCode::OK);
+    assert_eq!(seen.body["data"]["tickets"].as_array().unwrap().len(), 2);
+    let hidden = app
+        .get(&format!("/api/v1/tickets/group/{}", reference), Some(&bob))
+        .await;
+    assert_eq!(hidden.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn upgrades_are_priced_as_the_fare_difference_and_reissue_the_ticket() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let ada_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let bob_id = app
+        .create_user("bob@example.com", UserRole::User, None)
+        .await;
+    let tom_id = app
+        .create_user("tom@example.com", UserRole::User, None)
+        .await;
+    let ada = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let tom = app.login("tom@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(ada_id, flight_id, "3A", FareClass::Economy)
+        .await;
+    app.create_ticket(tom_id, flight_id, "3B", FareClass::Economy)
+        .await;
+    let ticket_of = |user_id: i32| {
+        sqlx::query_as::<_, (i32, String)>(
+            "SELECT ticket_id, ticket_number FROM tickets WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_one(&app.pool)
+    };
+    let (ticket_id, ticket_number) = ticket_of(ada_id).await.unwrap();
+    let upgrade_uri = format!("/api/v1/tickets/{}/upgrade", ticket_id);
+
+    let offers = app.get(&upgrade_uri, Some(&ada)).await;
+    assert_eq!(offers.status, StatusCode::OK);
+    let offers = offers.body["data"].as_array().unwrap().clone();
+    assert_eq!(offers.len(), 1);
+    assert_eq!(offers[0]["fare_class"], "business");
+    assert_eq!(offers[0]["seats_available"], 2);
+    assert_eq!(offers[0]["currency"], "EUR");
+    let price = offers[0]["price"].as_f64().unwrap();
+    assert!(price > 0.0);
+
+    let first = app
+        .post(
+            &upgrade_uri,
+            Some(&ada),
+            json!({ "fare_class": "first", "seat_number": "1A" }),
+        )
+        .await;
+    assert_eq!(first.status, StatusCode::BAD_REQUEST);
+    assert_eq!(first.error_code(), "UPGRADE_NOT_OFFERED");
+    let seatless = app
+        .post(
+            &upgrade_uri,
+            Some(&ada),
+            json!({ "fare_class": "business" }),
+        )
+        .await;
+    assert_eq!(seatless.status, StatusCode::BAD_REQUEST);
+
+    let upgraded = app
+        .post(
+            &upgrade_uri,
+            Some(&ada),
+            json!({ "fare_class": "business", "seat_number": "1a" }),
+        )
+        .await;
+    assert_eq!(upgraded.status, StatusCode::CREATED);
+    let ticket = &upgraded.body["data"]["ticket"];
+    assert_eq!(ticket["fare_class"], "business");
+    assert_eq!(ticket["seat_number"], "1A");
+    assert_ne!(ticket["ticket_number"], ticket_number.as_str());
+    let upgrade = &upgraded.body["data"]["upgrade"];
+    assert_eq!(upgrade["from_seat"], "3A");
+    assert_eq!(upgrade["previous_ticket_number"], ticket_number.as_str());
+    assert!((upgrade["price"].as_f64().unwrap() - price).abs() < 0.01);
+    // A ticket issued without a recorded fare takes the upgrade price as its fare
+    assert!((ticket["fare"].as_f64().unwrap() - price).abs() < 0.01);
+    assert_eq!(ticket["currency"], "EUR");
+    let none_higher = app.get(&upgrade_uri, Some(&ada)).await;
+    assert_eq!(none_higher.body["data"], json!([]));
+
+    // Tom paid in hryvnias, so his upgrade is priced in them too
+    sqlx::query(
+        "INSERT INTO exchange_rates (currency, per_euro, updated_at) VALUES ('UAH', 45, UTC_TIMESTAMP())",
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    sqlx::query("UPDATE tickets SET fare = 4500, currency = 'UAH' WHERE user_id = ?")
+        .bind(tom_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let (tom_ticket, _) = ticket_of(tom_id).await.unwrap();
+    let tom_uri = format!("/api/v1/tickets/{}/upgrade", tom_ticket);
+    let in_hryvnias = app.get(&tom_uri, Some(&tom)).await;
+    let offer = &in_hryvnias.body["data"][0];
+    assert_eq!(offer["currency"], "UAH");
+    assert!((offer["price"].as_f64().unwrap() - price * 45.0).abs() < 0.01);
+
+    app.create_ticket(bob_id, flight_id, "1B", FareClass::Business)
+        .await;
+    let sold_out = app.get(&tom_uri, Some(&tom)).await;
+    assert_eq!(sold_out.body["data"], json!([]));
+    let full = app
+        .post(
+            &tom_uri,
+            Some(&tom),
+            json!({ "fare_class": "business", "seat_number": "1C" }),
+        )
+        .await;
+    assert_eq!(full.status, StatusCode::CONFLICT);
+    assert_eq!(full.error_code(), "CLASS_SOLD_OUT");
+    let someone_elses = app.get(&tom_uri, Some(&ada)).await;
+    assert_eq!(someone_elses.status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,148 @@
+mod common;
+
+use axum::http::StatusCode;
+use serde_json::{json, Value};
+
+use airlines_api::models::{StaffPosition, UserRole};
+use common::TestApp;
+
+fn shipment(origin: &str, destination: &str, weight_kg: f64) -> Value {
+    json!({
+        "shipper": "Lviv Printworks",
+        "consignee": "Kyiv Books",
+        "origin_code": origin,
+        "destination_code": destination,
+        "description": "printed books",
+        "weight_kg": weight_kg,
+        "volume_m3": 2.5,
+    })
+}
+
+#[tokio::test]
+async fn shipments_are_booked_within_payload_and_tracked() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user(
+        "dispatch@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::Dispatcher),
+    )
+    .await;
+    app.create_user(
+        "gate@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::GateAgent),
+    )
+    .await;
+    let dispatcher = app.login("dispatch@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let agent = app.login("gate@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+
+    let mut body = shipment("KBP", "LWO", 1500.0);
+    body["location"] = json!("KBP cargo terminal");
+    let accepted = app
+        .post("/api/v1/cargo/shipments", Some(&dispatcher), body)
+        .await;
+    assert_eq!(accepted.status, StatusCode::CREATED);
+    assert_eq!(accepted.body["data"]["status"], "received");
+    let awb = accepted.body["data"]["awb_number"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert_eq!(awb.len(), 11);
+    let uri = format!("/api/v1/cargo/shipments/{}", awb);
+
+    let booked = app
+        .put(
+            &format!("{}/flight", uri),
+            Some(&dispatcher),
+            json!({ "flight_id": flight_id }),
+        )
+        .await;
+    assert_eq!(booked.status, StatusCode::OK);
+    assert_eq!(booked.body["data"]["status"], "booked");
+
+    let heavy = app
+        .post(
+            "/api/v1/cargo/shipments",
+            Some(&dispatcher),
+            shipment("KBP", "LWO", 600.0),
+        )
+        .await;
+    let heavy_awb = heavy.body["data"]["awb_number"].as_str().unwrap();
+    let over = app
+        .put(
+            &format!("/api/v1/cargo/shipments/{}/flight", heavy_awb),
+            Some(&dispatcher),
+            json!({ "flight_id": flight_id }),
+        )
+        .await;
+    assert_eq!(over.status, StatusCode::CONFLICT);
+    assert_eq!(over.error_code(), "CARGO_PAYLOAD_EXCEEDED");
+
+    let elsewhere = app
+        .post(
+            "/api/v1/cargo/shipments",
+            Some(&dispatcher),
+            shipment("KBP", "ODS", 10.0),
+        )
+        .await;
+    let elsewhere_awb = elsewhere.body["data"]["awb_number"].as_str().unwrap();
+    let mismatch = app
+        .put(
+            &format!("/api/v1/cargo/shipments/{}/flight", elsewhere_awb),
+            Some(&dispatcher),
+            json!({ "flight_id": flight_id }),
+        )
+        .await;
+    assert_eq!(mismatch.status, StatusCode::BAD_REQUEST);
+    assert_eq!(mismatch.error_code(), "CARGO_ROUTE_MISMATCH");
+
+    let skipped = app
+        .post(
+            &format!("{}/events", uri),
+            Some(&agent),
+            json!({ "status": "arrived" }),
+        )
+        .await;
+    assert_eq!(skipped.status, StatusCode::BAD_REQUEST);
+    assert_eq!(skipped.error_code(), "INVALID_CARGO_STATUS");
+
+    let loaded = app
+        .post(
+            &format!("{}/events", uri),
+            Some(&agent),
+            json!({ "status": "loaded", "location": "KBP stand 12" }),
+        )
+        .await;
+    assert_eq!(loaded.status, StatusCode::OK);
+
+    let tracked = app.get(&uri, None).await;
+    assert_eq!(tracked.status, StatusCode::OK);
+    assert_eq!(tracked.body["data"]["status"], "loaded");
+    let statuses: Vec<&str> = tracked.body["data"]["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["status"].as_str().unwrap())
+        .collect();
+    assert_eq!(statuses, ["received", "booked", "loaded"]);
+
+    let cargo = app
+        .get(
+            &format!("/api/v1/flights/{}/cargo", flight_id),
+            Some(&agent),
+        )
+        .await;
+    assert_eq!(cargo.status, StatusCode::OK);
+    assert_eq!(cargo.body["data"]["cargo_payload_kg"], 2000.0);
+    assert_eq!(cargo.body["data"]["booked"]["weight_kg"], 1500.0);
+    assert_eq!(cargo.body["data"]["shipments"].as_array().unwrap().len(), 1);
+}
@@ -0,0 +1,339 @@
+// Harness for the end-to-end tests: every test gets its own throwaway database on the MySQL
+// server behind TEST_DATABASE_URL, migrated from scratch, and drives the real router in-process.
+// Without TEST_DATABASE_URL the tests pass without doing anything
+#![allow(dead_code)] // each test binary uses a different part of the harness
+
+use std::future::poll_fn;
+use std::net::SocketAddr;
+use std::sync::{Arc, Once};
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{header, HeaderMap, HeaderName, Method, Request, StatusCode};
+use axum::Router;
+use chrono::{Duration, NaiveTime, Utc};
+use chrono_tz::Europe;
+use serde_json::Value;
+use tower_service::Service;
+use url::Url;
+use uuid::Uuid;
+
+use airlines_api::auth::JwtKeys;
+use airlines_api::config::Config;
+use airlines_api::currencies::BASE_CURRENCY;
+use airlines_api::db::{self, DbPool};
+use airlines_api::live::FlightUpdates;
+use airlines_api::models::{
+    Aircraft, AirportTimezone, FareClass, FareClassInventory, Flight, FlightFareClass, NewFlight,
+    NewUser, Route, StaffPosition, User, UserRole,
+};
+use airlines_api::notifications::{LogSender, LogSmsProvider, Notifier};
+use airlines_api::pricing::{PricingEngine, StrategyKind};
+use airlines_api::state::AppState;
+use airlines_api::storage::LocalStorage;
+use airlines_api::virus_scan::NoScanner;
+use airlines_api::Services;
+
+// Password of every user created through `TestApp::create_user`
+pub const PASSWORD: &str = "Correct-Horse-9";
+
+static ENV: Once = Once::new();
+
+pub struct TestApp {
+    pub router: Router,
+    pub pool: DbPool,
+    server_url: String,
+    database: String,
+}
+
+// A response with its body parsed as JSON (Null when empty)
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+impl TestResponse {
+    pub fn error_code(&self) -> &str {
+        self.body["error_code"].as_str().unwrap_or_default()
+    }
+}
+
+impl TestApp {
+    // None when TEST_DATABASE_URL is not set; callers return early
+    pub async fn spawn() -> Option<Self> {
+        let Ok(server_url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("TEST_DATABASE_URL is not set, skipping");
+            return None;
+        };
+
+        let database = format!(
+            "airlines_test_{}",
+            &Uuid::new_v4().simple().to_string()[..12]
+        );
+        let server = db::create_pool(&server_url)
+            .await
+            .expect("connect to TEST_DATABASE_URL");
+        sqlx::query(&format!("CREATE DATABASE `{}`", database))
+            .execute(&server)
+            .await
+            .expect("create test database");
+        server.close().await;
+
+        let mut database_url = Url::parse(&server_url).expect("TEST_DATABASE_URL is a url");
+        database_url.set_path(&database);
+        let pool = db::create_pool(database_url.as_str())
+            .await
+            .expect("connect to test database");
+        db::MIGRATOR.run(&pool).await.expect("apply migrations");
+
+        // Config::from_env requires these; everything else keeps its default
+        ENV.call_once(|| {
+            std::env::set_var("DATABASE_URL", &server_url);
+            std::env::set_var("JWT_SECRET", "integration-test-secret");
+        });
+        let mut config = Config::from_env().expect("test configuration");
+        config.database_url = database_url.to_string();
+        config.legacy_responses = false;
+        config.require_admin_2fa = false;
+
+        let jwt_keys = Arc::new(JwtKeys::from_config(&config).expect("test JWT keys"));
+        let (notifier, _worker) = Notifier::start(vec![Arc::new(LogSender)]);
+        let services = Services {
+            notifier,
+            flight_updates: FlightUpdates::default(),
+            sms: Arc::new(LogSmsProvider),
+            pricing: PricingEngine::from_kind(StrategyKind::Fixed),
+            storage: Arc::new(LocalStorage::new(std::env::temp_dir().join(&database))),
+            virus_scanner: Arc::new(NoScanner),
+        };
+        let router = airlines_api::app(AppState::new(pool.clone(), config, jwt_keys), services);
+
+        Some(Self {
+            router,
+            pool,
+            server_url,
+            database,
+        })
+    }
+
+    pub async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> TestResponse {
+        self.request_with_headers(method, uri, token, body, &[])
+            .await
+    }
+
+    pub async fn request_with_headers(
+        &self,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+        headers: &[(HeaderName, &str)],
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let mut request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .expect("valid request");
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))));
+
+        let mut router = self.router.clone();
+        poll_fn(|cx| Service::<Request<Body>>::poll_ready(&mut router, cx))
+            .await
+            .expect("router is ready");
+        let response = router.call(request).await.expect("router is infallible");
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("read response body");
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).expect("JSON response body")
+        };
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    pub async fn get(&self, uri: &str, token: Option<&str>) -> TestResponse {
+        self.request(Method::GET, uri, token, None).await
+    }
+
+    pub async fn post(&self, uri: &str, token: Option<&str>, body: Value) -> TestResponse {
+        self.request(Method::POST, uri, token, Some(body)).await
+    }
+
+    pub async fn put(&self, uri: &str, token: Option<&str>, body: Value) -> TestResponse {
+        self.request(Method::PUT, uri, token, Some(body)).await
+    }
+
+    pub async fn create_user(
+        &self,
+        email: &str,
+        role: UserRole,
+        staff_position: Option<StaffPosition>,
+    ) -> i32 {
+        // Minimum cost; the tests check the flow, not the hash strength
+        let password_hash = bcrypt::hash(PASSWORD, 4).expect("hash password");
+        User::insert(
+            &self.pool,
+            &NewUser {
+                first_name: "Test".to_string(),
+                last_name: "User".to_string(),
+                email: email.to_string(),
+                phone: None,
+                password_hash,
+                role,
+                staff_position,
+            },
+        )
+        .await
+        .expect("insert user")
+    }
+
+    // Log in and return the response data (token, refresh_token, user)
+    pub async fn login(&self, email: &str) -> Value {
+        let response = self
+            .post(
+                "/api/v1/auth/login",
+                None,
+                serde_json::json!({ "email": email, "password": PASSWORD }),
+            )
+            .await;
+        assert_eq!(
+            response.status,
+            StatusCode::OK,
+            "login failed: {}",
+            response.body
+        );
+        response.body["data"].clone()
+    }
+
+    // A scheduled flight tomorrow on a 10-seat aircraft: 8 economy and 2 business seats
+    pub async fn create_flight(&self) -> i32 {
+        let aircraft_id = Aircraft::insert(&self.pool, "Test Jet", 10)
+            .await
+            .expect("insert aircraft");
+        let route = Route::new(
+            "Kyiv (KBP)".to_string(),
+            "Lviv (LWO)".to_string(),
+            AirportTimezone(Europe::Kyiv),
+            AirportTimezone(Europe::Kyiv),
+            470.0,
+            NaiveTime::from_hms_opt(1, 10, 0).unwrap(),
+        );
+        let route_id = route.insert(&self.pool).await.expect("insert route");
+
+        let departure_time = Utc::now() + Duration::days(1);
+        let flight_id = Flight::insert(
+            &self.pool,
+            &NewFlight {
+                flight_number: "TS100".to_string(),
+                route_id,
+                aircraft_id,
+                departure_time,
+                arrival_time: departure_time + Duration::minutes(70),
+                gate: None,
+            },
+            None,
+        )
+        .await
+        .expect("insert flight");
+
+        FlightFareClass::replace_for_flight(
+            &self.pool,
+            flight_id,
+            &[
+                FareClassInventory {
+                    fare_class: FareClass::Economy,
+                    seat_count: 8,
+                    price: 100.0,
+                    currency: BASE_CURRENCY.to_string(),
+                },
+                FareClassInventory {
+                    fare_class: FareClass::Business,
+                    seat_count: 2,
+                    price: 300.0,
+                    currency: BASE_CURRENCY.to_string(),
+                },
+            ],
+        )
+        .await
+        .expect("set fare classes");
+        flight_id
+    }
+
+    // Issue a ticket without going through booking, for tests of what follows it
+    pub async fn create_ticket(
+        &self,
+        user_id: i32,
+        flight_id: i32,
+        seat_number: &str,
+        fare_class: FareClass,
+    ) {
+        // Sold under the operating flight number
+        sqlx::query(
+            r#"
+            INSERT INTO tickets (ticket_number, user_id, flight_id, seat_number, fare_class, sold_as)
+            SELECT ?, ?, flight_id, ?, ?, flight_number FROM flights WHERE flight_id = ?
+            "#,
+        )
+        .bind(format!("TK{}", &Uuid::new_v4().simple().to_string()[..10]))
+        .bind(user_id)
+        .bind(seat_number)
+        .bind(fare_class)
+        .bind(flight_id)
+        .execute(&self.pool)
+        .await
+        .expect("insert ticket");
+    }
+}
+
+// Drop the database even when the test panicked. Runs on a thread of its own, as the test's
+// runtime may already be shutting down
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        let server_url = self.server_url.clone();
+        let database = self.database.clone();
+        let cleanup = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("cleanup runtime");
+            runtime.block_on(async {
+                let server = db::create_pool(&server_url).await?;
+                sqlx::query(&format!("DROP DATABASE IF EXISTS `{}`", database))
+                    .execute(&server)
+                    .await?;
+                server.close().await;
+                Ok::<_, sqlx::Error>(())
+            })
+        });
+        let _ = std::fs::remove_dir_all(std::env::temp_dir().join(&self.database));
+        if let Ok(Err(e)) = cleanup.join() {
+            eprintln!("Failed to drop test database {}: {}", self.database, e);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+
+use common::TestApp;
+
+#[tokio::test]
+async fn fare_prices_are_converted_to_the_requested_currency() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let flight_id = app.create_flight().await;
+    sqlx::query(
+        "INSERT INTO exchange_rates (currency, per_euro, updated_at) VALUES ('USD', 1.1, NOW())",
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let uri = format!("/api/v1/flights/{}/fare-classes", flight_id);
+
+    let in_euro = app.get(&uri, None).await;
+    assert_eq!(in_euro.status, StatusCode::OK);
+    assert_eq!(in_euro.body["data"][0]["currency"], "EUR");
+    assert_eq!(in_euro.body["data"][0]["price"], 100.0);
+
+    let in_dollars = app.get(&format!("{}?currency=usd", uri), None).await;
+    assert_eq!(in_dollars.status, StatusCode::OK);
+    assert_eq!(in_dollars.body["data"][0]["currency"], "USD");
+    assert_eq!(in_dollars.body["data"][0]["price"], 110.0);
+    assert_eq!(in_dollars.body["data"][1]["price"], 330.0);
+
+    let unknown = app.get(&format!("{}?currency=GBP", uri), None).await;
+    assert_eq!(unknown.status, StatusCode::BAD_REQUEST);
+    assert_eq!(unknown.error_code(), "UNSUPPORTED_CURRENCY");
+
+    let currencies = app.get("/api/v1/currencies", None).await;
+    assert_eq!(currencies.body["data"]["base"], "EUR");
+    assert_eq!(currencies.body["data"]["rates"][0]["currency"], "USD");
+}
+
+#[tokio::test]
+async fn flexible_dates_show_the_cheapest_fare_of_each_day() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let flight_id = app.create_flight().await;
+    let today = Utc::now().date_naive();
+    let tomorrow = (Utc::now() + Duration::days(1)).date_naive();
+    let classes = app
+        .get(&format!("/api/v1/flights/{}/fare-classes", flight_id), None)
+        .await;
+    let uri = format!(
+        "/api/v1/flights?origin=KBP&destination=LWO&date={}&flex_days=1",
+        tomorrow
+    );
+
+    let calendar = app.get(&uri, None).await;
+    assert_eq!(calendar.status, StatusCode::OK);
+    let days = calendar.body["data"].as_array().unwrap();
+    assert_eq!(days.len(), 3);
+    assert_eq!(days[0]["date"], (tomorrow - Duration::days(1)).to_string());
+    assert!(days[0]["cheapest"].is_null());
+    assert_eq!(days[1]["date"], tomorrow.to_string());
+    assert_eq!(days[1]["cheapest"]["flight_id"], flight_id);
+    assert_eq!(days[1]["cheapest"]["fare_class"], "economy");
+    assert_eq!(
+        days[1]["cheapest"]["price"],
+        classes.body["data"][0]["current_price"]
+    );
+    assert!(days[2]["cheapest"].is_null());
+
+    // A sold-out class gives way to the next cheapest
+    sqlx::query(
+        "UPDATE flight_fare_classes SET seat_count = 0 WHERE flight_id = ? AND fare_class = 'economy'",
+    )
+    .bind(flight_id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let calendar = app.get(&uri, None).await;
+    assert_eq!(
+        calendar.body["data"][1]["cheapest"]["fare_class"],
+        "business"
+    );
+
+    let unbounded = app
+        .get(
+            &format!("/api/v1/flights?origin=KBP&date={}&flex_days=3", tomorrow),
+            None,
+        )
+        .await;
+    assert_eq!(unbounded.status, StatusCode::BAD_REQUEST);
+    assert_eq!(unbounded.error_code(), "VALIDATION_FAILED");
+    let too_wide = app
+        .get(&uri.replace("flex_days=1", "flex_days=30"), None)
+        .await;
+    assert_eq!(too_wide.status, StatusCode::BAD_REQUEST);
+
+    let on_the_day = app
+        .get(&format!("/api/v1/flights?date={}", tomorrow), None)
+        .await;
+    assert_eq!(on_the_day.body["data"][0]["flight_id"], flight_id);
+    let other_day = app
+        .get(&format!("/api/v1/flights?date={}", today), None)
+        .await;
+    assert_eq!(other_day.body["data"].as_array().unwrap().len(), 0);
+}
@@ -0,0 +1,483 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{DateTime, FixedOffset, Utc};
+use chrono_tz::Europe;
+use serde_json::json;
+
+use airlines_api::models::{CrewMember, CrewRole, StaffPosition, UserRole};
+use common::TestApp;
+
+#[tokio::test]
+async fn flight_times_come_in_utc_and_airport_local_time() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let flight_id = app.create_flight().await;
+
+    let flight = app
+        .get(&format!("/api/v1/flights/{}", flight_id), None)
+        .await;
+    assert_eq!(flight.status, StatusCode::OK);
+    let data = &flight.body["data"];
+    assert_eq!(data["origin_timezone"], "Europe/Kyiv");
+    assert_eq!(data["destination_timezone"], "Europe/Kyiv");
+
+    let utc: DateTime<Utc> = data["departure_time"].as_str().unwrap().parse().unwrap();
+    let local: DateTime<FixedOffset> =
+        DateTime::parse_from_rfc3339(data["departure_time_local"].as_str().unwrap()).unwrap();
+    assert_eq!(local, utc);
+    assert_eq!(
+        local.offset(),
+        utc.with_timezone(&Europe::Kyiv).fixed_offset().offset()
+    );
+
+    let listed = app.get("/api/v1/flights", None).await;
+    assert_eq!(
+        listed.body["data"][0]["arrival_time_local"],
+        data["arrival_time_local"]
+    );
+}
+
+#[tokio::test]
+async fn flights_cannot_be_scheduled_on_grounded_aircraft() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user(
+        "engineer@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::Engineer),
+    )
+    .await;
+    app.create_user(
+        "dispatch@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::Dispatcher),
+    )
+    .await;
+    let engineer = app.login("engineer@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let dispatcher = app.login("dispatch@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    let flight = app
+        .get(&format!("/api/v1/flights/{}", flight_id), None)
+        .await;
+    let aircraft_id = flight.body["data"]["aircraft_id"].as_i64().unwrap();
+    let route_id = flight.body["data"]["route_id"].as_i64().unwrap();
+    let maintenance = format!("/api/v1/aircraft/{}/maintenance", aircraft_id);
+
+    let by_dispatcher = app
+        .post(
+            &maintenance,
+            Some(&dispatcher),
+            json!({
+                "maintenance_type": "repair",
+                "grounded_from": "2030-05-01T00:00:00Z",
+                "grounded_to": "2030-05-03T00:00:00Z",
+            }),
+        )
+        .await;
+    assert_eq!(by_dispatcher.status, StatusCode::FORBIDDEN);
+
+    let grounded = app
+        .post(
+            &maintenance,
+            Some(&engineer),
+            json!({
+                "maintenance_type": "repair",
+                "grounded_from": "2030-05-01T00:00:00Z",
+                "grounded_to": "2030-05-03T00:00:00Z",
+                "notes": "Bird strike, engine 2",
+            }),
+        )
+        .await;
+    assert_eq!(grounded.status, StatusCode::CREATED);
+    let records = app.get(&maintenance, Some(&dispatcher)).await;
+    assert_eq!(records.body["data"][0]["notes"], "Bird strike, engine 2");
+
+    let schedule = |departure: &str, arrival: &str| {
+        json!({
+            "flight_number": "ts200",
+            "route_id": route_id,
+            "aircraft_id": aircraft_id,
+            "departure_time": departure,
+            "arrival_time": arrival,
+        })
+    };
+    let during = app
+        .post(
+            "/api/v1/flights",
+            Some(&dispatcher),
+            schedule("2030-05-02T23:30:00Z", "2030-05-03T00:40:00Z"),
+        )
+        .await;
+    assert_eq!(during.status, StatusCode::CONFLICT);
+    assert_eq!(during.error_code(), "AIRCRAFT_GROUNDED");
+
+    let after = app
+        .post(
+            "/api/v1/flights",
+            Some(&dispatcher),
+            schedule("2030-05-03T06:00:00Z", "2030-05-03T07:10:00Z"),
+        )
+        .await;
+    assert_eq!(after.status, StatusCode::CREATED);
+    assert_eq!(after.body["data"]["flight_number"], "TS200");
+    assert_eq!(after.body["data"]["status"], "scheduled");
+}
+
+#[tokio::test]
+async fn scheduled_flights_are_seated_by_the_aircraft_layout() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    let flight = app
+        .get(&format!("/api/v1/flights/{}", flight_id), None)
+        .await;
+    let aircraft_id = flight.body["data"]["aircraft_id"].as_i64().unwrap();
+    let route_id = flight.body["data"]["route_id"].as_i64().unwrap();
+
+    let overlapping = app
+        .post(
+            "/api/v1/admin/cabin-layouts",
+            Some(&admin),
+            json!({
+                "name": "Test Jet",
+                "sections": [
+                    { "fare_class": "business", "first_row": 1, "last_row": 1, "seat_letters": "AC" },
+                    { "fare_class": "economy", "first_row": 1, "last_row": 2, "seat_letters": "ABCD" },
+                ],
+            }),
+        )
+        .await;
+    assert_eq!(overlapping.status, StatusCode::BAD_REQUEST);
+
+    let layout = app
+        .post(
+            "/api/v1/admin/cabin-layouts",
+            Some(&admin),
+            json!({
+                "name": "Test Jet",
+                "sections": [
+                    { "fare_class": "business", "first_row": 1, "last_row": 1, "seat_letters": "AC" },
+                    { "fare_class": "economy", "first_row": 2, "last_row": 3, "seat_letters": "ABCD" },
+                ],
+                "exit_rows": [3],
+            }),
+        )
+        .await;
+    assert_eq!(layout.status, StatusCode::CREATED);
+    let attached = app
+        .put(
+            &format!("/api/v1/admin/aircraft/{}/cabin-layout", aircraft_id),
+            Some(&admin),
+            json!({ "layout_id": layout.body["data"]["layout_id"] }),
+        )
+        .await;
+    assert_eq!(attached.status, StatusCode::OK);
+
+    let scheduled = app
+        .post(
+            "/api/v1/flights",
+            Some(&admin),
+            json!({
+                "flight_number": "TS300",
+                "route_id": route_id,
+                "aircraft_id": aircraft_id,
+                "departure_time": "2030-06-01T08:00:00Z",
+                "arrival_time": "2030-06-01T09:10:00Z",
+            }),
+        )
+        .await;
+    assert_eq!(scheduled.status, StatusCode::CREATED);
+    let seats_uri = format!(
+        "/api/v1/flights/{}/seats",
+        scheduled.body["data"]["flight_id"]
+    );
+
+    let seats = app.get(&seats_uri, None).await;
+    let seats = seats.body["data"].as_array().unwrap();
+    assert_eq!(seats.len(), 10);
+    assert_eq!(seats[0]["seat_number"], "1A");
+    assert_eq!(seats[0]["fare_class"], "business");
+    assert_eq!(seats[9]["seat_number"], "3D");
+    assert_eq!(seats[9]["exit_row"], true);
+    assert_eq!(seats[9]["status"], "available");
+
+    let unknown = app
+        .post(
+            &seats_uri.replace("/seats", "/seat-blocks"),
+            Some(&admin),
+            json!({ "seat_number": "1B", "reason": "equipment" }),
+        )
+        .await;
+    assert_eq!(unknown.status, StatusCode::BAD_REQUEST);
+    assert_eq!(unknown.error_code(), "UNKNOWN_SEAT");
+
+    let without_layout = app
+        .get(&format!("/api/v1/flights/{}/seats", flight_id), None)
+        .await;
+    assert_eq!(without_layout.body["data"], json!([]));
+}
+
+#[tokio::test]
+async fn crew_cannot_be_on_two_overlapping_flights() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user(
+        "dispatch@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::Dispatcher),
+    )
+    .await;
+    let dispatcher = app.login("dispatch@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let pilot = CrewMember::insert(&app.pool, "Olena", "Koval", CrewRole::Pilot)
+        .await
+        .unwrap();
+    let first = app.create_flight().await;
+    let second = app.create_flight().await;
+
+    let assigned = app
+        .put(
+            &format!("/api/v1/flights/{}/crew", first),
+            Some(&dispatcher),
+            json!({ "crew_member_ids": [pilot] }),
+        )
+        .await;
+    assert_eq!(assigned.status, StatusCode::OK);
+
+    let clash = app
+        .put(
+            &format!("/api/v1/flights/{}/crew", second),
+            Some(&dispatcher),
+            json!({ "crew_member_ids": [pilot] }),
+        )
+        .await;
+    assert_eq!(clash.status, StatusCode::CONFLICT);
+    assert_eq!(clash.error_code(), "CREW_SCHEDULE_CONFLICT");
+    assert!(clash.body["error"]
+        .as_str()
+        .unwrap()
+        .contains("Olena Koval on TS100"));
+
+    let reassigned = app
+        .put(
+            &format!("/api/v1/flights/{}/crew", first),
+            Some(&dispatcher),
+            json!({ "crew_member_ids": [pilot] }),
+        )
+        .await;
+    assert_eq!(reassigned.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn duty_time_limits_can_be_overridden_by_dispatchers() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user(
+        "dispatch@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::Dispatcher),
+    )
+    .await;
+    let dispatcher = app.login("dispatch@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let pilot = CrewMember::insert(&app.pool, "Olena", "Koval", CrewRole::Pilot)
+        .await
+        .unwrap();
+    let flight = app
+        .get(
+            &format!("/api/v1/flights/{}", app.create_flight().await),
+            None,
+        )
+        .await;
+
+    // Seven hours out and seven back in one day, 10 hours apart
+    let mut flight_ids = Vec::new();
+    for (number, departure, arrival) in [
+        ("TS401", "2030-06-01T00:00:00Z", "2030-06-01T07:00:00Z"),
+        ("TS402", "2030-06-01T17:00:00Z", "2030-06-02T00:00:00Z"),
+    ] {
+        let scheduled = app
+            .post(
+                "/api/v1/flights",
+                Some(&dispatcher),
+                json!({
+                    "flight_number": number,
+                    "route_id": flight.body["data"]["route_id"],
+                    "aircraft_id": flight.body["data"]["aircraft_id"],
+                    "departure_time": departure,
+                    "arrival_time": arrival,
+                }),
+            )
+            .await;
+        assert_eq!(scheduled.status, StatusCode::CREATED);
+        flight_ids.push(scheduled.body["data"]["flight_id"].as_i64().unwrap());
+    }
+    let crew = json!({ "crew_member_ids": [pilot] });
+    let first = app
+        .put(
+            &format!("/api/v1/flights/{}/crew", flight_ids[0]),
+            Some(&dispatcher),
+            crew.clone(),
+        )
+        .await;
+    assert_eq!(first.status, StatusCode::OK);
+
+    let second_uri = format!("/api/v1/flights/{}/crew", flight_ids[1]);
+    let refused = app.put(&second_uri, Some(&dispatcher), crew).await;
+    assert_eq!(refused.status, StatusCode::CONFLICT);
+    assert_eq!(refused.error_code(), "CREW_DUTY_LIMIT_EXCEEDED");
+    assert!(refused.body["error"]
+        .as_str()
+        .unwrap()
+        .contains("Olena Koval: 14h on duty within 24 hours"));
+
+    let overridden = app
+        .put(
+            &format!("{}/override", second_uri),
+            Some(&dispatcher),
+            json!({ "crew_member_ids": [pilot], "reason": "Standby pilot sick" }),
+        )
+        .await;
+    assert_eq!(overridden.status, StatusCode::OK);
+    assert_eq!(overridden.body["data"]["crew"][0]["crew_member_id"], pilot);
+
+    let audited = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM audit_logs WHERE action = 'flight.crew_duty_overridden' AND entity_id = ?",
+    )
+    .bind(flight_ids[1].to_string())
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(audited, 1);
+}
+
+#[tokio::test]
+async fn gates_are_held_by_one_flight_at_a_time() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let first = app.create_flight().await;
+    let second = app.create_flight().await;
+
+    let mut gate_ids = Vec::new();
+    for (airport, gate_code) in [("kbp", "B4"), ("LWO", "A1")] {
+        let created = app
+            .post(
+                &format!("/api/v1/admin/airports/{}/gates", airport),
+                Some(&admin),
+                json!({ "gate_code": gate_code, "terminal": "D" }),
+            )
+            .await;
+        assert_eq!(created.status, StatusCode::CREATED);
+        gate_ids.push(created.body["data"]["gate_id"].clone());
+    }
+    let listed = app.get("/api/v1/airports/KBP/gates", Some(&admin)).await;
+    assert_eq!(listed.body["data"][0]["gate_code"], "B4");
+
+    let assigned = app
+        .put(
+            &format!("/api/v1/flights/{}/gate", first),
+            Some(&admin),
+            json!({ "gate_id": gate_ids[0] }),
+        )
+        .await;
+    assert_eq!(assigned.status, StatusCode::OK);
+    assert_eq!(assigned.body["data"]["gate"], "B4");
+    assert_eq!(assigned.body["data"]["terminal"], "D");
+
+    let taken = app
+        .put(
+            &format!("/api/v1/flights/{}/gate", second),
+            Some(&admin),
+            json!({ "gate_id": gate_ids[0] }),
+        )
+        .await;
+    assert_eq!(taken.status, StatusCode::CONFLICT);
+    assert_eq!(taken.error_code(), "GATE_OCCUPIED");
+
+    let elsewhere = app
+        .put(
+            &format!("/api/v1/flights/{}/gate", second),
+            Some(&admin),
+            json!({ "gate_id": gate_ids[1] }),
+        )
+        .await;
+    assert_eq!(elsewhere.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn code_share_flights_are_found_by_marketing_number() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user(
+        "dispatch@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::Dispatcher),
+    )
+    .await;
+    let dispatcher = app.login("dispatch@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    let uri = format!("/api/v1/flights/{}/codeshares", flight_id);
+
+    let repeated = app
+        .put(
+            &uri,
+            Some(&dispatcher),
+            json!({ "marketing_flight_numbers": ["LH5678", "TS100"] }),
+        )
+        .await;
+    assert_eq!(repeated.status, StatusCode::BAD_REQUEST);
+
+    let updated = app
+        .put(
+            &uri,
+            Some(&dispatcher),
+            json!({ "marketing_flight_numbers": ["lh5678", "LO3921"] }),
+        )
+        .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(
+        updated.body["data"]["marketing_flight_numbers"],
+        json!(["LH5678", "LO3921"])
+    );
+
+    let found = app.get("/api/v1/flights?flight_number=LH5678", None).await;
+    assert_eq!(found.body["data"][0]["flight_id"], flight_id);
+    assert_eq!(found.body["data"][0]["flight_number"], "TS100");
+    let operating = app.get("/api/v1/flights?flight_number=TS100", None).await;
+    assert_eq!(operating.body["data"][0]["flight_id"], flight_id);
+    let unknown = app.get("/api/v1/flights?flight_number=LH1", None).await;
+    assert_eq!(unknown.body["data"], json!([]));
+}
@@ -0,0 +1,520 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use chrono::{Days, Duration, Utc};
+use serde_json::json;
+
+use airlines_api::models::{FareClass, UserRole};
+use common::TestApp;
+
+#[tokio::test]
+async fn load_factor_is_reported_by_route_for_admins() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let ada_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let bob_id = app
+        .create_user("bob@example.com", UserRole::User, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let ada = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(ada_id, flight_id, "3A", FareClass::Economy)
+        .await;
+    app.create_ticket(bob_id, flight_id, "1A", FareClass::Business)
+        .await;
+    let today = Utc::now().date_naive();
+    let uri = format!(
+        "/api/v1/reports/load-factor?from={}&to={}",
+        today,
+        today + Days::new(2)
+    );
+
+    let report = app.get(&uri, Some(&admin)).await;
+    assert_eq!(report.status, StatusCode::OK);
+    let routes = report.body["data"].as_array().unwrap();
+    assert_eq!(routes.len(), 1);
+    assert_eq!(routes[0]["capacity"], 10);
+    assert_eq!(routes[0]["seats_sold"], 2);
+    assert_eq!(routes[0]["load_factor"], 0.2);
+    assert_eq!(routes[0]["flights"][0]["flight_id"], flight_id);
+
+    let earlier = app
+        .get(
+            &format!(
+                "/api/v1/reports/load-factor?from={}&to={}",
+                today - Days::new(30),
+                today
+            ),
+            Some(&admin),
+        )
+        .await;
+    assert_eq!(earlier.body["data"].as_array().unwrap().len(), 0);
+
+    let backwards = app
+        .get(
+            &format!(
+                "/api/v1/reports/load-factor?from={}&to={}",
+                today,
+                today - Days::new(1)
+            ),
+            Some(&admin),
+        )
+        .await;
+    assert_eq!(backwards.status, StatusCode::BAD_REQUEST);
+
+    let passenger = app.get(&uri, Some(&ada)).await;
+    assert_eq!(passenger.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn revenue_is_reported_by_route_and_month() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let ada_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let bob_id = app
+        .create_user("bob@example.com", UserRole::User, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(ada_id, flight_id, "3A", FareClass::Economy)
+        .await;
+    app.create_ticket(bob_id, flight_id, "1A", FareClass::Business)
+        .await;
+    sqlx::query(
+        "UPDATE tickets SET fare = IF(fare_class = 'business', 300, 100), currency = 'EUR'",
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let lounge = app
+        .post(
+            "/api/v1/admin/ancillaries",
+            Some(&admin),
+            json!({ "code": "LOUNGE", "name": "Lounge access", "price": 40.0 }),
+        )
+        .await;
+    sqlx::query(
+        r#"
+        INSERT INTO ticket_ancillaries (ticket_id, product_id, price, currency, purchased_at)
+        SELECT ticket_id, ?, 40, 'EUR', UTC_TIMESTAMP() FROM tickets WHERE user_id = ?
+        "#,
+    )
+    .bind(lounge.body["data"]["product_id"].as_i64().unwrap())
+    .bind(bob_id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let today = Utc::now().date_naive();
+    let uri = format!(
+        "/api/v1/reports/revenue?from={}&to={}",
+        today - Days::new(1),
+        today + Days::new(1)
+    );
+
+    let by_route = app.get(&uri, Some(&admin)).await;
+    assert_eq!(by_route.status, StatusCode::OK);
+    let rows = by_route.body["data"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["group"], "KBP-LWO");
+    assert_eq!(rows[0]["tickets_sold"], 2);
+    assert_eq!(rows[0]["ticket_revenue"], 400.0);
+    assert_eq!(rows[0]["ancillary_revenue"], 40.0);
+    assert_eq!(rows[0]["total"], 440.0);
+    assert_eq!(rows[0]["currency"], "EUR");
+
+    let by_month = app
+        .get(&format!("{}&group_by=month", uri), Some(&admin))
+        .await;
+    assert_eq!(by_month.status, StatusCode::OK);
+    let rows = by_month.body["data"].as_array().unwrap();
+    assert_eq!(rows[0]["group"], today.format("%Y-%m").to_string());
+    assert_eq!(rows[0]["route_id"], serde_json::Value::Null);
+    assert_eq!(rows[0]["total"], 440.0);
+
+    let unknown = app
+        .get(&format!("{}&currency=XYZ", uri), Some(&admin))
+        .await;
+    assert_eq!(unknown.status, StatusCode::BAD_REQUEST);
+    assert_eq!(unknown.error_code(), "UNSUPPORTED_CURRENCY");
+
+    let ada = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let passenger = app.get(&uri, Some(&ada)).await;
+    assert_eq!(passenger.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn revenue_counts_every_charge_in_the_month_it_was_paid() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let ada_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let bob_id = app
+        .create_user("bob@example.com", UserRole::User, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(ada_id, flight_id, "3A", FareClass::Economy)
+        .await;
+    app.create_ticket(bob_id, flight_id, "1A", FareClass::Business)
+        .await;
+    let ticket_id = |user_id: i32| {
+        let pool = app.pool.clone();
+        async move {
+            sqlx::query_scalar::<_, i32>("SELECT ticket_id FROM tickets WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        }
+    };
+    let ada_ticket = ticket_id(ada_id).await;
+    let bob_ticket = ticket_id(bob_id).await;
+
+    // Ada booked economy for 100 last month and upgraded to business for 200 now, which the
+    // ticket's fare includes
+    sqlx::query(
+        r#"
+        UPDATE tickets SET fare = 300, currency = 'EUR',
+               created_at = UTC_TIMESTAMP() - INTERVAL 40 DAY
+        WHERE ticket_id = ?
+        "#,
+    )
+    .bind(ada_ticket)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    sqlx::query(
+        r#"
+        INSERT INTO ticket_upgrades (ticket_id, from_class, to_class, from_seat, to_seat,
+                                     previous_ticket_number, ticket_number, price, currency,
+                                     upgraded_at)
+        VALUES (?, 'economy', 'business', '3A', '2A', 'TK0000000001', 'TK0000000002', 200, 'EUR',
+                UTC_TIMESTAMP())
+        "#,
+    )
+    .bind(ada_ticket)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    // Bob booked business for 300 now with a lap infant, a seat change, a prepaid bag, an
+    // overweight bag and a pet
+    sqlx::query("UPDATE tickets SET fare = 300, currency = 'EUR' WHERE ticket_id = ?")
+        .bind(bob_ticket)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    for statement in [
+        r#"INSERT INTO ticket_infants (ticket_id, first_name, last_name, date_of_birth, fare,
+                                      currency, created_at)
+           VALUES (?, 'Eve', 'Smith', CURDATE() - INTERVAL 1 YEAR, 10, 'EUR', UTC_TIMESTAMP())"#,
+        r#"INSERT INTO seat_changes (ticket_id, from_seat, to_seat, fee, currency, changed_at)
+           VALUES (?, '1A', '1B', 15, 'EUR', UTC_TIMESTAMP())"#,
+        r#"INSERT INTO baggage_purchases (ticket_id, pieces, price, currency, purchased_at)
+           VALUES (?, 1, 45, 'EUR', UTC_TIMESTAMP())"#,
+        r#"INSERT INTO baggage (ticket_id, tag_number, weight_kg, excess_fee, excess_fee_currency,
+                               created_at, updated_at)
+           VALUES (?, '0000000001', 25, 20, 'EUR', UTC_TIMESTAMP(), UTC_TIMESTAMP())"#,
+        r#"INSERT INTO ticket_pets (ticket_id, placement, species, weight_kg, fee, currency,
+                                   created_at)
+           VALUES (?, 'cabin', 'cat', 5, 50, 'EUR', UTC_TIMESTAMP())"#,
+    ] {
+        sqlx::query(statement)
+            .bind(bob_ticket)
+            .execute(&app.pool)
+            .await
+            .unwrap();
+    }
+
+    let now = Utc::now();
+    let last_month = now - Duration::days(40);
+    let by_month = app
+        .get(
+            &format!(
+                "/api/v1/reports/revenue?from={}&to={}&group_by=month",
+                last_month.date_naive() - Days::new(1),
+                now.date_naive() + Days::new(1)
+            ),
+            Some(&admin),
+        )
+        .await;
+    assert_eq!(by_month.status, StatusCode::OK);
+    let rows = by_month.body["data"].as_array().unwrap();
+    assert_eq!(rows.len(), 2);
+
+    assert_eq!(rows[0]["group"], last_month.format("%Y-%m").to_string());
+    assert_eq!(rows[0]["tickets_sold"], 1);
+    assert_eq!(rows[0]["ticket_revenue"], 100.0);
+    assert_eq!(rows[0]["ancillary_revenue"], 0.0);
+    assert_eq!(rows[0]["total"], 100.0);
+
+    assert_eq!(rows[1]["group"], now.format("%Y-%m").to_string());
+    assert_eq!(rows[1]["tickets_sold"], 1);
+    assert_eq!(rows[1]["ticket_revenue"], 510.0);
+    assert_eq!(rows[1]["ancillary_revenue"], 130.0);
+    assert_eq!(rows[1]["total"], 640.0);
+}
+
+#[tokio::test]
+async fn on_time_performance_compares_actual_times_with_the_schedule() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    sqlx::query("UPDATE flights SET status = 'boarding' WHERE flight_id = ?")
+        .bind(flight_id)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let status_uri = format!("/api/v1/flights/{}/status", flight_id);
+
+    let departed = app
+        .request(
+            Method::PATCH,
+            &status_uri,
+            Some(&admin),
+            Some(json!({ "status": "departed" })),
+        )
+        .await;
+    assert_eq!(departed.status, StatusCode::OK);
+    assert!(departed.body["data"]["actual_departure_time"].is_string());
+    assert!(departed.body["data"]["actual_arrival_time"].is_null());
+    let arrived = app
+        .request(
+            Method::PATCH,
+            &status_uri,
+            Some(&admin),
+            Some(json!({ "status": "arrived" })),
+        )
+        .await;
+    assert!(arrived.body["data"]["actual_arrival_time"].is_string());
+
+    // Half an hour late off the stand, ten minutes late on arrival
+    sqlx::query(
+        r#"
+        UPDATE flights
+        SET actual_departure_time = departure_time + INTERVAL 30 MINUTE,
+            actual_arrival_time = arrival_time + INTERVAL 10 MINUTE
+        WHERE flight_id = ?
+        "#,
+    )
+    .bind(flight_id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let today = Utc::now().date_naive();
+    let uri = format!(
+        "/api/v1/reports/on-time?from={}&to={}",
+        today,
+        today + Days::new(2)
+    );
+
+    let by_route = app.get(&uri, Some(&admin)).await;
+    assert_eq!(by_route.status, StatusCode::OK);
+    let rows = by_route.body["data"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["group"], "KBP-LWO");
+    assert_eq!(rows[0]["departures"], 1);
+    assert_eq!(rows[0]["on_time_departures_percent"], 0.0);
+    assert_eq!(rows[0]["average_departure_delay_minutes"], 30.0);
+    assert_eq!(rows[0]["on_time_arrivals_percent"], 100.0);
+    assert_eq!(rows[0]["average_arrival_delay_minutes"], 10.0);
+
+    let by_aircraft = app
+        .get(&format!("{}&group_by=aircraft", uri), Some(&admin))
+        .await;
+    let aircraft_id: i32 =
+        sqlx::query_scalar("SELECT aircraft_id FROM flights WHERE flight_id = ?")
+            .bind(flight_id)
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    assert_eq!(by_aircraft.body["data"][0]["aircraft_id"], aircraft_id);
+    assert_eq!(by_aircraft.body["data"][0]["departures"], 1);
+}
+
+#[tokio::test]
+async fn demand_lists_top_routes_weekdays_and_lead_times() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let ada_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(ada_id, flight_id, "3A", FareClass::Economy)
+        .await;
+    app.create_ticket(ada_id, flight_id, "3B", FareClass::Economy)
+        .await;
+    let departure: chrono::DateTime<Utc> =
+        sqlx::query_scalar("SELECT departure_time FROM flights WHERE flight_id = ?")
+            .bind(flight_id)
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    let today = Utc::now().date_naive();
+
+    let demand = app
+        .get(
+            &format!(
+                "/api/v1/reports/demand?from={}&to={}&limit=5",
+                today - Days::new(7),
+                today
+            ),
+            Some(&admin),
+        )
+        .await;
+    assert_eq!(demand.status, StatusCode::OK);
+    let data = &demand.body["data"];
+    assert_eq!(data["tickets"], 2);
+    assert_eq!(data["top_routes"][0]["origin"], "KBP");
+    assert_eq!(data["top_routes"][0]["tickets"], 2);
+    let weekday = departure.format("%A").to_string().to_lowercase();
+    assert_eq!(data["busiest_days"][0]["day"], weekday.as_str());
+    assert_eq!(data["busiest_days"].as_array().unwrap().len(), 7);
+    assert_eq!(data["lead_times"][0]["tickets"], 2);
+    assert_eq!(data["lead_times"][0]["percent"], 100.0);
+    assert_eq!(data["average_lead_days"], 1.0);
+}
+
+#[tokio::test]
+async fn demographics_count_unknown_profile_fields() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let ada_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let bob_id = app
+        .create_user("bob@example.com", UserRole::User, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let ada = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let profile = app
+        .put(
+            &format!("/api/v1/users/{}", ada_id),
+            Some(&ada),
+            json!({ "nationality": " ua ", "gender": "female", "date_of_birth": "1990-01-01" }),
+        )
+        .await;
+    assert_eq!(profile.body["data"]["gender"], "female");
+    let flight_id = app.create_flight().await;
+    app.create_ticket(ada_id, flight_id, "3A", FareClass::Economy)
+        .await;
+    app.create_ticket(bob_id, flight_id, "3B", FareClass::Economy)
+        .await;
+    let today = Utc::now().date_naive();
+    let uri = format!(
+        "/api/v1/reports/demographics?from={}&to={}",
+        today,
+        today + Days::new(2)
+    );
+
+    let by_route = app.get(&uri, Some(&admin)).await;
+    assert_eq!(by_route.status, StatusCode::OK);
+    let route = &by_route.body["data"][0];
+    assert_eq!(route["group"], "KBP-LWO");
+    assert_eq!(route["passengers"], 2);
+    assert_eq!(
+        route["nationalities"],
+        json!([
+            { "value": "UA", "passengers": 1 },
+            { "value": "unknown", "passengers": 1 },
+        ])
+    );
+    assert_eq!(route["age_brackets"][0]["value"], "35-44");
+    assert_eq!(route["age_brackets"][1]["value"], "unknown");
+    assert_eq!(
+        route["genders"],
+        json!([
+            { "value": "female", "passengers": 1 },
+            { "value": "unspecified", "passengers": 1 },
+        ])
+    );
+
+    let by_month = app
+        .get(&format!("{}&group_by=month", uri), Some(&admin))
+        .await;
+    assert_eq!(by_month.status, StatusCode::OK);
+    assert!(by_month.body["data"][0]["route_id"].is_null());
+    assert_eq!(by_month.body["data"][0]["passengers"], 2);
+
+    // A lap infant counts by their own date of birth, with no nationality or gender on record
+    sqlx::query(
+        r#"
+        INSERT INTO ticket_infants (ticket_id, first_name, last_name, date_of_birth, fare,
+                                    currency, created_at)
+        SELECT ticket_id, 'Eve', 'Lovelace', CURDATE() - INTERVAL 1 YEAR, 10, 'EUR',
+               UTC_TIMESTAMP()
+        FROM tickets WHERE user_id = ?
+        "#,
+    )
+    .bind(ada_id)
+    .execute(&app.pool)
+    .await
+    .unwrap();
+    let with_infant = app.get(&uri, Some(&admin)).await;
+    let route = &with_infant.body["data"][0];
+    assert_eq!(route["passengers"], 3);
+    assert_eq!(
+        route["nationalities"][0],
+        json!({ "value": "unknown", "passengers": 2 })
+    );
+    assert_eq!(
+        route["age_brackets"][0],
+        json!({ "value": "0-11", "passengers": 1 })
+    );
+    assert_eq!(
+        route["genders"][0],
+        json!({ "value": "unspecified", "passengers": 2 })
+    );
+}
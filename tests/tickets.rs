@@ -0,0 +1,553 @@
+// Check-in, meal choice, pets and infants of tickets. Tickets are inserted directly rather than
+// booked, to keep each test on one feature
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Months, Utc};
+use serde_json::json;
+
+use airlines_api::models::{FareClass, StaffPosition, UserRole};
+use common::TestApp;
+
+#[tokio::test]
+async fn assistance_must_be_acknowledged_before_check_in() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let passenger_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    app.create_user(
+        "gate@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::GateAgent),
+    )
+    .await;
+    let passenger = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let agent = app.login("gate@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(passenger_id, flight_id, "5C", FareClass::Economy)
+        .await;
+    let ticket_id: i32 = sqlx::query_scalar("SELECT ticket_id FROM tickets WHERE user_id = ?")
+        .bind(passenger_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    let assistance = format!("/api/v1/tickets/{}/assistance", ticket_id);
+    let check_in = format!("/api/v1/tickets/{}/check-in", ticket_id);
+
+    let requested = app
+        .post(
+            &assistance,
+            Some(&passenger),
+            json!({ "assistance_type": "wheelchair", "details": "cannot climb stairs" }),
+        )
+        .await;
+    assert_eq!(requested.status, StatusCode::CREATED);
+    let assistance_id = requested.body["data"]["assistance_id"].as_i64().unwrap();
+
+    let twice = app
+        .post(
+            &assistance,
+            Some(&passenger),
+            json!({ "assistance_type": "wheelchair" }),
+        )
+        .await;
+    assert_eq!(twice.status, StatusCode::CONFLICT);
+    assert_eq!(twice.error_code(), "ASSISTANCE_ALREADY_REQUESTED");
+
+    let manifest = app
+        .get(
+            &format!("/api/v1/flights/{}/manifest", flight_id),
+            Some(&agent),
+        )
+        .await;
+    assert_eq!(manifest.status, StatusCode::OK);
+    let needs = &manifest.body["data"][0]["assistance"];
+    assert_eq!(needs[0]["assistance_type"], "wheelchair");
+    assert_eq!(needs[0]["acknowledged_at"], serde_json::Value::Null);
+
+    let refused = app.post(&check_in, Some(&passenger), json!({})).await;
+    assert_eq!(refused.status, StatusCode::CONFLICT);
+    assert_eq!(refused.error_code(), "ASSISTANCE_NOT_ACKNOWLEDGED");
+
+    let acknowledge = format!("{}/{}/acknowledge", assistance, assistance_id);
+    let by_passenger = app.post(&acknowledge, Some(&passenger), json!({})).await;
+    assert_eq!(by_passenger.status, StatusCode::FORBIDDEN);
+    let acknowledged = app.post(&acknowledge, Some(&agent), json!({})).await;
+    assert_eq!(acknowledged.status, StatusCode::OK);
+    assert!(acknowledged.body["data"]["acknowledged_at"].is_string());
+
+    let checked = app.post(&check_in, Some(&passenger), json!({})).await;
+    assert_eq!(checked.status, StatusCode::OK);
+    assert_eq!(checked.body["data"]["checked_in"], true);
+}
+
+#[tokio::test]
+async fn meals_are_chosen_from_the_flight_until_the_cutoff() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let passenger_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    app.create_user(
+        "dispatch@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::Dispatcher),
+    )
+    .await;
+    let passenger = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let dispatcher = app.login("dispatch@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(passenger_id, flight_id, "5C", FareClass::Economy)
+        .await;
+    let ticket_id: i32 = sqlx::query_scalar("SELECT ticket_id FROM tickets WHERE user_id = ?")
+        .bind(passenger_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    let depart_in_hours = |hours: i32| {
+        sqlx::query(
+            "UPDATE flights SET departure_time = DATE_ADD(UTC_TIMESTAMP(), INTERVAL ? HOUR) WHERE flight_id = ?",
+        )
+        .bind(hours)
+        .bind(flight_id)
+        .execute(&app.pool)
+    };
+    depart_in_hours(72).await.unwrap();
+    let meals = format!("/api/v1/flights/{}/meals", flight_id);
+    let meal = format!("/api/v1/tickets/{}/meal", ticket_id);
+
+    let set = app
+        .put(
+            &meals,
+            Some(&dispatcher),
+            json!({ "meals": ["standard", "vegetarian"] }),
+        )
+        .await;
+    assert_eq!(set.status, StatusCode::OK);
+    assert_eq!(app.get(&meals, None).await.body["data"][1], "vegetarian");
+
+    let not_offered = app
+        .put(&meal, Some(&passenger), json!({ "meal": "kosher" }))
+        .await;
+    assert_eq!(not_offered.status, StatusCode::BAD_REQUEST);
+    assert_eq!(not_offered.error_code(), "MEAL_NOT_OFFERED");
+
+    let chosen = app
+        .put(&meal, Some(&passenger), json!({ "meal": "vegetarian" }))
+        .await;
+    assert_eq!(chosen.status, StatusCode::OK);
+    assert_eq!(chosen.body["data"]["meal"], "vegetarian");
+
+    let catering = app
+        .get(
+            &format!("/api/v1/flights/{}/catering", flight_id),
+            Some(&dispatcher),
+        )
+        .await;
+    assert_eq!(catering.status, StatusCode::OK);
+    assert_eq!(catering.body["data"]["passengers"], 1);
+    assert_eq!(catering.body["data"]["meals"][0]["meal"], "vegetarian");
+    assert_eq!(catering.body["data"]["meals"][0]["count"], 1);
+
+    depart_in_hours(2).await.unwrap();
+    let too_late = app
+        .put(&meal, Some(&passenger), json!({ "meal": "standard" }))
+        .await;
+    assert_eq!(too_late.status, StatusCode::CONFLICT);
+    assert_eq!(too_late.error_code(), "MEAL_SELECTION_CLOSED");
+}
+
+#[tokio::test]
+async fn pets_are_booked_within_the_aircraft_limits() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let passenger_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let other_id = app
+        .create_user("alan@example.com", UserRole::User, None)
+        .await;
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    app.create_user(
+        "gate@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::GateAgent),
+    )
+    .await;
+    let passenger = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let other = app.login("alan@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let agent = app.login("gate@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight_id = app.create_flight().await;
+    app.create_ticket(passenger_id, flight_id, "5C", FareClass::Economy)
+        .await;
+    app.create_ticket(other_id, flight_id, "6C", FareClass::Economy)
+        .await;
+    let ticket_of = |user_id: i32| {
+        sqlx::query_scalar::<_, i32>("SELECT ticket_id FROM tickets WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(&app.pool)
+    };
+    let pet = format!(
+        "/api/v1/tickets/{}/pet",
+        ticket_of(passenger_id).await.unwrap()
+    );
+    let other_pet = format!("/api/v1/tickets/{}/pet", ticket_of(other_id).await.unwrap());
+
+    let too_heavy = app
+        .post(
+            &pet,
+            Some(&passenger),
+            json!({ "placement": "cabin", "species": "dog", "weight_kg": 12.0 }),
+        )
+        .await;
+    assert_eq!(too_heavy.status, StatusCode::BAD_REQUEST);
+    assert_eq!(too_heavy.error_code(), "PET_NOT_ACCEPTED");
+
+    let booked = app
+        .post(
+            &pet,
+            Some(&passenger),
+            json!({ "placement": "cabin", "species": "cat", "weight_kg": 6.0 }),
+        )
+        .await;
+    assert_eq!(booked.status, StatusCode::CREATED);
+    assert_eq!(booked.body["data"]["fee"], 50.0);
+
+    let second = app
+        .post(
+            &pet,
+            Some(&passenger),
+            json!({ "placement": "hold", "species": "dog", "weight_kg": 30.0 }),
+        )
+        .await;
+    assert_eq!(second.status, StatusCode::CONFLICT);
+    assert_eq!(second.error_code(), "PET_ALREADY_BOOKED");
+
+    let aircraft_id: i32 =
+        sqlx::query_scalar("SELECT aircraft_id FROM flights WHERE flight_id = ?")
+            .bind(flight_id)
+            .fetch_one(&app.pool)
+            .await
+            .unwrap();
+    let limits = app
+        .put(
+            &format!("/api/v1/admin/aircraft/{}/pet-limits", aircraft_id),
+            Some(&admin),
+            json!({ "pets_in_cabin": 1, "pets_in_hold": 0, "pet_cabin_max_weight_kg": 8.0 }),
+        )
+        .await;
+    assert_eq!(limits.status, StatusCode::OK);
+
+    let no_hold = app
+        .post(
+            &other_pet,
+            Some(&other),
+            json!({ "placement": "hold", "species": "dog", "weight_kg": 30.0 }),
+        )
+        .await;
+    assert_eq!(no_hold.error_code(), "PET_NOT_ACCEPTED");
+    let cabin_full = app
+        .post(
+            &other_pet,
+            Some(&other),
+            json!({ "placement": "cabin", "species": "cat", "weight_kg": 4.0 }),
+        )
+        .await;
+    assert_eq!(cabin_full.status, StatusCode::CONFLICT);
+    assert_eq!(cabin_full.error_code(), "PET_CAPACITY_REACHED");
+
+    let manifest = app
+        .get(
+            &format!("/api/v1/flights/{}/manifest", flight_id),
+            Some(&agent),
+        )
+        .await;
+    assert_eq!(manifest.body["data"][0]["pet"]["species"], "cat");
+    assert_eq!(manifest.body["data"][1]["pet"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn passenger_ages_are_checked_at_booking_and_again_at_check_in() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let parent_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let child_id = app
+        .create_user("tom@example.com", UserRole::User, None)
+        .await;
+    app.create_user(
+        "gate@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::GateAgent),
+    )
+    .await;
+    let parent = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let child = app.login("tom@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let agent = app.login("gate@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let today = Utc::now().date_naive();
+    for (user_id, born) in [
+        (parent_id, today - Months::new(12 * 35)),
+        (child_id, today - Months::new(12 * 8)),
+    ] {
+        sqlx::query("UPDATE users SET date_of_birth = ? WHERE user_id = ?")
+            .bind(born)
+            .bind(user_id)
+            .execute(&app.pool)
+            .await
+            .unwrap();
+    }
+    let flight_id = app.create_flight().await;
+    app.create_ticket(parent_id, flight_id, "5C", FareClass::Economy)
+        .await;
+    app.create_ticket(child_id, flight_id, "5D", FareClass::Economy)
+        .await;
+    let ticket_of = |user_id: i32| {
+        sqlx::query_scalar::<_, i32>("SELECT ticket_id FROM tickets WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_one(&app.pool)
+    };
+    let parent_ticket = ticket_of(parent_id).await.unwrap();
+    let child_ticket = ticket_of(child_id).await.unwrap();
+    let infant = format!("/api/v1/tickets/{}/infant", parent_ticket);
+
+    // Under 2 today, but 2 on the departure date tomorrow
+    let departure_date = (Utc::now() + Duration::days(1)).date_naive();
+    let too_old = app
+        .post(
+            &infant,
+            Some(&parent),
+            json!({
+                "first_name": "Ann",
+                "last_name": "Lovelace",
+                "date_of_birth": departure_date - Months::new(24),
+            }),
+        )
+        .await;
+    assert_eq!(too_old.status, StatusCode::BAD_REQUEST);
+    assert_eq!(too_old.error_code(), "NOT_AN_INFANT");
+
+    let booked = app
+        .post(
+            &infant,
+            Some(&parent),
+            json!({
+                "first_name": "Ann",
+                "last_name": "Lovelace",
+                "date_of_birth": today - Months::new(10),
+            }),
+        )
+        .await;
+    assert_eq!(booked.status, StatusCode::CREATED);
+    assert_eq!(booked.body["data"]["fare"], 10.0);
+    assert_eq!(booked.body["data"]["currency"], "EUR");
+
+    let manifest = app
+        .get(
+            &format!("/api/v1/flights/{}/manifest", flight_id),
+            Some(&agent),
+        )
+        .await;
+    assert_eq!(manifest.body["data"][0]["infant"]["first_name"], "Ann");
+
+    // Booked as an adult, but 8 on the departure date
+    let check_in = format!("/api/v1/tickets/{}/check-in", child_ticket);
+    let mismatch = app.post(&check_in, Some(&child), json!({})).await;
+    assert_eq!(mismatch.status, StatusCode::CONFLICT);
+    assert_eq!(mismatch.error_code(), "PASSENGER_AGE_MISMATCH");
+
+    sqlx::query("UPDATE tickets SET passenger_type = 'child' WHERE ticket_id = ?")
+        .bind(child_ticket)
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    let lap = app
+        .post(
+            &format!("/api/v1/tickets/{}/infant", child_ticket),
+            Some(&child),
+            json!({
+                "first_name": "Ben",
+                "last_name": "Lovelace",
+                "date_of_birth": today - Months::new(3),
+            }),
+        )
+        .await;
+    assert_eq!(lap.status, StatusCode::BAD_REQUEST);
+    assert_eq!(lap.error_code(), "LAP_INFANT_NOT_ALLOWED");
+
+    let checked_in = app.post(&check_in, Some(&child), json!({})).await;
+    assert_eq!(checked_in.status, StatusCode::OK);
+    assert_eq!(checked_in.body["data"]["passenger_type"], "child");
+}
+
+#[tokio::test]
+async fn seats_are_changed_within_the_class_paying_for_exit_rows() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("admin@example.com", UserRole::Admin, None)
+        .await;
+    let passenger_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let other_id = app
+        .create_user("bob@example.com", UserRole::User, None)
+        .await;
+    let admin = app.login("admin@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let passenger = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let flight = app
+        .get(
+            &format!("/api/v1/flights/{}", app.create_flight().await),
+            None,
+        )
+        .await;
+    let aircraft_id = flight.body["data"]["aircraft_id"].as_i64().unwrap();
+    let layout = app
+        .post(
+            "/api/v1/admin/cabin-layouts",
+            Some(&admin),
+            json!({
+                "name": "Test Jet",
+                "sections": [
+                    { "fare_class": "business", "first_row": 1, "last_row": 1, "seat_letters": "AC" },
+                    { "fare_class": "economy", "first_row": 2, "last_row": 3, "seat_letters": "ABCD" },
+                ],
+                "exit_rows": [3],
+            }),
+        )
+        .await;
+    app.put(
+        &format!("/api/v1/admin/aircraft/{}/cabin-layout", aircraft_id),
+        Some(&admin),
+        json!({ "layout_id": layout.body["data"]["layout_id"] }),
+    )
+    .await;
+    let scheduled = app
+        .post(
+            "/api/v1/flights",
+            Some(&admin),
+            json!({
+                "flight_number": "TS300",
+                "route_id": flight.body["data"]["route_id"],
+                "aircraft_id": aircraft_id,
+                "departure_time": "2030-06-01T08:00:00Z",
+                "arrival_time": "2030-06-01T09:10:00Z",
+            }),
+        )
+        .await;
+    let flight_id = scheduled.body["data"]["flight_id"].as_i64().unwrap() as i32;
+    app.create_ticket(passenger_id, flight_id, "2A", FareClass::Economy)
+        .await;
+    app.create_ticket(other_id, flight_id, "2C", FareClass::Economy)
+        .await;
+    let ticket_id: i32 = sqlx::query_scalar("SELECT ticket_id FROM tickets WHERE user_id = ?")
+        .bind(passenger_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    let change_seat = format!("/api/v1/tickets/{}/change-seat", ticket_id);
+
+    let business = app
+        .post(
+            &change_seat,
+            Some(&passenger),
+            json!({ "seat_number": "1A" }),
+        )
+        .await;
+    assert_eq!(business.status, StatusCode::BAD_REQUEST);
+    assert_eq!(business.error_code(), "SEAT_NOT_IN_CLASS");
+
+    let taken = app
+        .post(
+            &change_seat,
+            Some(&passenger),
+            json!({ "seat_number": "2C" }),
+        )
+        .await;
+    assert_eq!(taken.status, StatusCode::CONFLICT);
+    assert_eq!(taken.error_code(), "SEAT_ALREADY_TAKEN");
+
+    let moved = app
+        .post(
+            &change_seat,
+            Some(&passenger),
+            json!({ "seat_number": "2b" }),
+        )
+        .await;
+    assert_eq!(moved.status, StatusCode::OK);
+    assert_eq!(moved.body["data"]["ticket"]["seat_number"], "2B");
+    assert_eq!(moved.body["data"]["change"]["from_seat"], "2A");
+    assert_eq!(moved.body["data"]["change"]["fee"], 0.0);
+
+    let exit_row = app
+        .post(
+            &change_seat,
+            Some(&passenger),
+            json!({ "seat_number": "3A" }),
+        )
+        .await;
+    assert_eq!(exit_row.status, StatusCode::OK);
+    assert_eq!(exit_row.body["data"]["change"]["fee"], 25.0);
+
+    let seats = app
+        .get(&format!("/api/v1/flights/{}/seats", flight_id), None)
+        .await;
+    let status = |seat: &str| {
+        seats.body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["seat_number"] == seat)
+            .unwrap()["status"]
+            .clone()
+    };
+    assert_eq!(status("2A"), "available");
+    assert_eq!(status("2B"), "available");
+    assert_eq!(status("3A"), "booked");
+}
@@ -0,0 +1,354 @@
+mod common;
+
+use axum::http::{header, Method, StatusCode};
+use serde_json::json;
+
+use airlines_api::models::{FareClass, StaffPosition, UserRole};
+use common::TestApp;
+
+#[tokio::test]
+async fn owner_reads_and_updates_own_profile() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let user_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let token = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let uri = format!("/api/v1/users/{}", user_id);
+
+    let updated = app
+        .put(
+            &uri,
+            Some(&token),
+            json!({ "last_name": "Lovelace", "passport_number": "FA123456", "date_of_birth": "1815-12-10" }),
+        )
+        .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(updated.body["data"]["first_name"], "Test");
+    assert_eq!(updated.body["data"]["last_name"], "Lovelace");
+    assert_eq!(updated.body["data"]["date_of_birth"], "1815-12-10");
+
+    let fetched = app.get(&uri, Some(&token)).await;
+    assert_eq!(fetched.body["data"]["passport_number"], "FA123456");
+
+    let blank = app
+        .put(
+            &uri,
+            Some(&token),
+            json!({ "first_name": "  ", "phone": "0501234567" }),
+        )
+        .await;
+    assert_eq!(blank.status, StatusCode::BAD_REQUEST);
+    assert_eq!(blank.error_code(), "VALIDATION_FAILED");
+    assert_eq!(blank.body["fields"][0]["field"], "first_name");
+    assert_eq!(blank.body["fields"][0]["code"], "blank");
+    assert_eq!(blank.body["fields"][1]["field"], "phone");
+    assert_eq!(blank.body["fields"][1]["code"], "phone_number");
+
+    let audit = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM audit_logs WHERE action = 'user.updated' AND entity_id = ?",
+    )
+    .bind(user_id.to_string())
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(audit, 1);
+}
+
+#[tokio::test]
+async fn profiles_are_private_to_owner_and_staff() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let ada = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let grace = app
+        .create_user("grace@example.com", UserRole::User, None)
+        .await;
+    app.create_user(
+        "gate@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::GateAgent),
+    )
+    .await;
+    let ada_token = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let staff_token = app.login("gate@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let anonymous = app.get(&format!("/api/v1/users/{}", grace), None).await;
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+
+    let other = app
+        .get(&format!("/api/v1/users/{}", grace), Some(&ada_token))
+        .await;
+    assert_eq!(other.status, StatusCode::FORBIDDEN);
+    assert_eq!(other.error_code(), "NOT_OWNER");
+
+    let update_other = app
+        .put(
+            &format!("/api/v1/users/{}", grace),
+            Some(&ada_token),
+            json!({ "first_name": "Mallory" }),
+        )
+        .await;
+    assert_eq!(update_other.status, StatusCode::FORBIDDEN);
+
+    let by_staff = app
+        .get(&format!("/api/v1/users/{}", ada), Some(&staff_token))
+        .await;
+    assert_eq!(by_staff.status, StatusCode::OK);
+    assert_eq!(by_staff.body["data"]["email"], "ada@example.com");
+
+    let missing = app.get("/api/v1/users/999999", Some(&staff_token)).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    assert_eq!(missing.error_code(), "USER_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn profile_etag_drives_conditional_requests() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let user_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let token = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let uri = format!("/api/v1/users/{}", user_id);
+
+    let fetched = app.get(&uri, Some(&token)).await;
+    let etag = fetched.headers[header::ETAG].to_str().unwrap().to_string();
+
+    let unchanged = app
+        .request_with_headers(
+            Method::GET,
+            &uri,
+            Some(&token),
+            None,
+            &[(header::IF_NONE_MATCH, &etag)],
+        )
+        .await;
+    assert_eq!(unchanged.status, StatusCode::NOT_MODIFIED);
+    assert_eq!(unchanged.body, serde_json::Value::Null);
+
+    let updated = app
+        .request_with_headers(
+            Method::PUT,
+            &uri,
+            Some(&token),
+            Some(json!({ "last_name": "Lovelace" })),
+            &[(header::IF_MATCH, &etag)],
+        )
+        .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_ne!(updated.headers[header::ETAG], etag.as_str());
+
+    let stale = app
+        .request_with_headers(
+            Method::PUT,
+            &uri,
+            Some(&token),
+            Some(json!({ "last_name": "Byron" })),
+            &[(header::IF_MATCH, &etag)],
+        )
+        .await;
+    assert_eq!(stale.status, StatusCode::PRECONDITION_FAILED);
+    assert_eq!(stale.error_code(), "PRECONDITION_FAILED");
+
+    let changed = app
+        .request_with_headers(
+            Method::GET,
+            &uri,
+            Some(&token),
+            None,
+            &[(header::IF_NONE_MATCH, &etag)],
+        )
+        .await;
+    assert_eq!(changed.status, StatusCode::OK);
+    assert_eq!(changed.body["data"]["last_name"], "Lovelace");
+}
+
+#[tokio::test]
+async fn language_is_saved_on_profile_and_errors_follow_accept_language() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let user_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let token = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let uri = format!("/api/v1/users/{}", user_id);
+
+    let updated = app
+        .put(&uri, Some(&token), json!({ "language": "uk" }))
+        .await;
+    assert_eq!(updated.status, StatusCode::OK);
+    assert_eq!(updated.body["data"]["language"], "uk");
+
+    let unsupported = app
+        .put(&uri, Some(&token), json!({ "language": "xx" }))
+        .await;
+    assert_eq!(unsupported.status, StatusCode::BAD_REQUEST);
+
+    let localized = app
+        .request_with_headers(
+            Method::PUT,
+            &uri,
+            Some(&token),
+            Some(json!({ "first_name": " " })),
+            &[(header::ACCEPT_LANGUAGE, "uk-UA, en;q=0.5")],
+        )
+        .await;
+    assert_eq!(localized.status, StatusCode::BAD_REQUEST);
+    assert_eq!(localized.error_code(), "VALIDATION_FAILED");
+    assert_eq!(localized.headers[header::CONTENT_LANGUAGE], "uk");
+    assert_eq!(
+        localized.body["error"],
+        "Тіло або параметри запиту недійсні"
+    );
+    assert_eq!(
+        localized.body["fields"][0]["message"],
+        "не може бути порожнім"
+    );
+
+    let english = app
+        .put(&uri, Some(&token), json!({ "first_name": " " }))
+        .await;
+    assert_eq!(english.headers[header::CONTENT_LANGUAGE], "en");
+    assert_eq!(english.body["fields"][0]["message"], "must not be blank");
+}
+
+#[tokio::test]
+async fn user_tickets_are_listed_for_owner() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let user_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let flight_id = app.create_flight().await;
+    app.create_ticket(user_id, flight_id, "1A", FareClass::Business)
+        .await;
+    app.create_ticket(user_id, flight_id, "5C", FareClass::Economy)
+        .await;
+    let token = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let tickets = app
+        .get(&format!("/api/v1/users/{}/tickets", user_id), Some(&token))
+        .await;
+    assert_eq!(tickets.status, StatusCode::OK);
+    let seats: Vec<&str> = tickets.body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|ticket| ticket["seat_number"].as_str().unwrap())
+        .collect();
+    assert_eq!(seats.len(), 2);
+    assert!(seats.contains(&"1A") && seats.contains(&"5C"));
+}
+
+#[tokio::test]
+async fn staff_list_users_with_sparse_fields() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    app.create_user("ada@example.com", UserRole::User, None)
+        .await;
+    app.create_user(
+        "agent@example.com",
+        UserRole::Worker,
+        Some(StaffPosition::GateAgent),
+    )
+    .await;
+    let token = app.login("agent@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let listed = app
+        .get("/api/v1/users?role=user&fields=user_id,email", Some(&token))
+        .await;
+    assert_eq!(listed.status, StatusCode::OK);
+    assert_eq!(listed.body["pagination"]["total_items"], 1);
+    assert_eq!(
+        listed.body["data"],
+        json!([{ "user_id": listed.body["data"][0]["user_id"], "email": "ada@example.com" }])
+    );
+}
+
+#[tokio::test]
+async fn erased_account_keeps_tickets_without_personal_data() {
+    let Some(app) = TestApp::spawn().await else {
+        return;
+    };
+    let user_id = app
+        .create_user("ada@example.com", UserRole::User, None)
+        .await;
+    let flight_id = app.create_flight().await;
+    app.create_ticket(user_id, flight_id, "1A", FareClass::Economy)
+        .await;
+    let token = app.login("ada@example.com").await["token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let wrong = app
+        .post(
+            "/api/v1/users/me/erase",
+            Some(&token),
+            json!({ "password": "not-the-password" }),
+        )
+        .await;
+    assert_eq!(wrong.status, StatusCode::UNAUTHORIZED);
+
+    let erased = app
+        .post(
+            "/api/v1/users/me/erase",
+            Some(&token),
+            json!({ "password": common::PASSWORD }),
+        )
+        .await;
+    assert_eq!(erased.status, StatusCode::NO_CONTENT);
+
+    let (email, phone, tickets): (String, Option<String>, i64) = sqlx::query_as(
+        "SELECT email, phone, (SELECT COUNT(*) FROM tickets WHERE user_id = users.user_id) FROM users WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_one(&app.pool)
+    .await
+    .unwrap();
+    assert_eq!(email, format!("erased-{}@erased.invalid", user_id));
+    assert_eq!(phone, None);
+    assert_eq!(tickets, 1);
+
+    let after = app
+        .get(&format!("/api/v1/users/{}", user_id), Some(&token))
+        .await;
+    assert_eq!(after.status, StatusCode::UNAUTHORIZED);
+    let login = app
+        .post(
+            "/api/v1/auth/login",
+            None,
+            json!({ "email": "ada@example.com", "password": common::PASSWORD }),
+        )
+        .await;
+    assert_eq!(login.status, StatusCode::UNAUTHORIZED);
+}